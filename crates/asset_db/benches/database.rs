@@ -0,0 +1,141 @@
+//! Benchmarks for asset database insert and query paths.
+
+use awgen_asset_db::prelude::*;
+use bevy::asset::RenderAssetUsages;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+
+/// The in-memory benchmark database identifier.
+struct BenchDatabase;
+impl AssetDatabaseName for BenchDatabase {
+    fn database_name() -> &'static str {
+        "bench"
+    }
+}
+
+/// Creates a headless app with an in-memory asset database and a single
+/// module, returning the app and the module ID.
+fn bench_app() -> (App, AssetModuleID) {
+    let mut app = App::new();
+    app.register_asset_db::<BenchDatabase, _>(":memory:")
+        .add_plugins((MinimalPlugins, AssetPlugin::default(), AwgenAssetPlugin))
+        .init_asset::<Image>();
+
+    let module = app
+        .world_mut()
+        .run_system_once(|assets: AwgenAssets<BenchDatabase>| {
+            assets
+                .create_module("Bench")
+                .expect("Failed to create module")
+        })
+        .expect("Failed to run system");
+
+    (app, module)
+}
+
+/// Creates a small image asset to insert into the database.
+fn image(size: u32) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255, 128, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Inserts `count` image assets into the database.
+fn insert_images(app: &mut App, module: AssetModuleID, count: usize, size: u32) {
+    let image = image(size);
+    app.world_mut()
+        .run_system_once(move |mut assets: AwgenAssets<BenchDatabase>| {
+            for i in 0 .. count {
+                assets
+                    .create_asset(format!("bench/{i}.png"), module, &image)
+                    .expect("Failed to create asset");
+            }
+        })
+        .expect("Failed to run system");
+
+    // Flush preview generation tasks so they do not accumulate.
+    app.update();
+}
+
+/// Benchmarks inserting image assets of varied sizes.
+fn bench_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("insert_asset");
+
+    for size in [16, 128, 512] {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter_batched_ref(
+                bench_app,
+                |(app, module)| insert_images(app, *module, 16, size),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmarks listing all assets in databases of varied sizes.
+fn bench_list_assets(c: &mut Criterion) {
+    let mut group = c.benchmark_group("list_assets");
+
+    for count in [100, 1000] {
+        let (mut app, module) = bench_app();
+        insert_images(&mut app, module, count, 16);
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| {
+                app.world_mut()
+                    .run_system_once(|assets: AwgenAssets<BenchDatabase>| {
+                        assets.list_assets().expect("Failed to list assets").len()
+                    })
+                    .expect("Failed to run system")
+            })
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmarks listing a single page of filtered assets in databases of varied
+/// sizes.
+fn bench_list_page(c: &mut Criterion) {
+    let mut group = c.benchmark_group("list_assets_filtered");
+
+    for count in [100, 1000] {
+        let (mut app, module) = bench_app();
+        insert_images(&mut app, module, count, 16);
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, &count| {
+            b.iter(|| {
+                app.world_mut()
+                    .run_system_once(move |assets: AwgenAssets<BenchDatabase>| {
+                        let filter = AssetFilter {
+                            module: Some(module),
+                            path_prefix: Some("bench/"),
+                            ..default()
+                        };
+                        assets
+                            .list_assets_filtered(&filter, 50, count / 2)
+                            .expect("Failed to list assets")
+                            .len()
+                    })
+                    .expect("Failed to run system")
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_insert, bench_list_assets, bench_list_page);
+criterion_main!(benches);
@@ -0,0 +1,133 @@
+//! This module implements snapshots of SQLite databases using the SQLite
+//! online backup API.
+//!
+//! The online backup API copies a database page by page while it remains
+//! open, so a snapshot can be taken without closing the connection. Restoring
+//! copies a snapshot back over the open database in the same way, so every
+//! existing handle to the connection sees the restored data.
+
+use std::ffi::CStr;
+use std::os::raw::c_int;
+use std::path::Path;
+use std::thread;
+use std::time::Instant;
+
+use sqlite::{Connection, ConnectionThreadSafe, OpenFlags};
+use sqlite3_sys as ffi;
+
+use crate::busy::{self, BusySettings};
+
+/// The name of the main schema of a connection.
+const MAIN: &CStr = c"main";
+
+/// The number of pages copied in each step of a backup.
+const PAGES_PER_STEP: c_int = 256;
+
+/// Writes a snapshot of the database behind the given connection to the
+/// database file at the given path, replacing its contents if it exists.
+pub fn backup_to(
+    connection: &ConnectionThreadSafe,
+    path: &Path,
+    busy: &BusySettings,
+) -> Result<(), sqlite::Error> {
+    let destination = Connection::open_thread_safe(path)?;
+    copy_database(connection, &destination, busy)
+}
+
+/// Replaces the contents of the database behind the given connection with the
+/// snapshot stored in the database file at the given path.
+pub fn restore_from(
+    connection: &ConnectionThreadSafe,
+    path: &Path,
+    busy: &BusySettings,
+) -> Result<(), sqlite::Error> {
+    let flags = OpenFlags::new().with_read_only().with_full_mutex();
+    let source = Connection::open_thread_safe_with_flags(path, flags)?;
+    copy_database(&source, connection, busy)
+}
+
+/// Copies the main schema of the source database over the main schema of the
+/// destination database.
+///
+/// Steps that fail because either database is locked are retried with the
+/// backoff of the given busy settings, but once the backup has been stalled
+/// for longer than their total wait, it is abandoned and a busy error is
+/// returned.
+fn copy_database(
+    source: &ConnectionThreadSafe,
+    destination: &ConnectionThreadSafe,
+    busy: &BusySettings,
+) -> Result<(), sqlite::Error> {
+    // SAFETY: Both database handles are valid for the duration of the backup,
+    // and the schema names are nul-terminated strings.
+    let backup = unsafe {
+        ffi::sqlite3_backup_init(
+            destination.as_raw(),
+            MAIN.as_ptr(),
+            source.as_raw(),
+            MAIN.as_ptr(),
+        )
+    };
+
+    if backup.is_null() {
+        return Err(last_error(destination));
+    }
+
+    let mut busy_since = None;
+    let mut retry = 0;
+    let timed_out = loop {
+        // SAFETY: The backup handle is valid until it is finished below.
+        let code = unsafe { ffi::sqlite3_backup_step(backup, PAGES_PER_STEP) };
+        if code == ffi::SQLITE_OK {
+            busy_since = None;
+            retry = 0;
+        } else if busy::is_busy_code(code as isize) {
+            let since = *busy_since.get_or_insert_with(Instant::now);
+            let delay = busy.backoff(retry);
+            if since.elapsed() + delay > busy.max_wait {
+                break true;
+            }
+            thread::sleep(delay);
+            retry += 1;
+        } else {
+            break false;
+        }
+    };
+
+    // SAFETY: The backup handle is valid, and is not used again. Finishing
+    // returns the error of the last step, if any.
+    let code = unsafe { ffi::sqlite3_backup_finish(backup) };
+    if timed_out {
+        return Err(sqlite::Error {
+            code: Some(ffi::SQLITE_BUSY as isize),
+            message: Some(format!(
+                "database was locked for more than {:?} during backup",
+                busy.max_wait
+            )),
+        });
+    }
+
+    if code != ffi::SQLITE_OK {
+        return Err(last_error(destination));
+    }
+
+    Ok(())
+}
+
+/// Gets the most recent error from the given connection.
+fn last_error(connection: &ConnectionThreadSafe) -> sqlite::Error {
+    // SAFETY: The database handle is valid, and SQLite always returns a valid
+    // nul-terminated string.
+    let (code, message) = unsafe {
+        let raw = connection.as_raw();
+        (
+            ffi::sqlite3_errcode(raw),
+            CStr::from_ptr(ffi::sqlite3_errmsg(raw)),
+        )
+    };
+
+    sqlite::Error {
+        code: Some(code as isize),
+        message: Some(message.to_string_lossy().into_owned()),
+    }
+}
@@ -0,0 +1,360 @@
+//! This module implements streaming access to asset blobs using SQLite
+//! incremental BLOB I/O.
+//!
+//! Asset data and previews may be large, so rather than loading an entire blob
+//! into memory at once, it is read or written in small chunks directly from the
+//! database file. This keeps peak memory usage bounded while loading and
+//! importing large assets.
+//!
+//! Opening, reading and writing a blob are retried while the database is
+//! busy, as configured by the [`BusySettings`] of the connection.
+
+use std::ffi::CStr;
+use std::io::{self, Read, SeekFrom};
+use std::os::raw::{c_int, c_void};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bevy::asset::io::{Reader, ReaderNotSeekableError, SeekableReader};
+use bevy::tasks::futures_lite::{AsyncRead, AsyncSeek};
+use sqlite::ConnectionThreadSafe;
+use sqlite3_sys as ffi;
+
+use crate::busy::{self, BusySettings};
+use crate::connection::AwgenDbError;
+
+/// The size of each chunk when copying data into a blob.
+pub(crate) const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The name of the database schema containing the assets table.
+const SCHEMA: &CStr = c"main";
+
+/// The name of the table containing asset blobs.
+const TABLE: &CStr = c"assets";
+
+/// A column of the assets table that contains a blob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BlobColumn {
+    /// The asset data column.
+    Data,
+
+    /// The asset preview column.
+    Preview,
+}
+
+impl BlobColumn {
+    /// Gets the name of this column.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            BlobColumn::Data => "data",
+            BlobColumn::Preview => "preview",
+        }
+    }
+
+    /// Gets the name of this column as a C string.
+    fn c_name(self) -> &'static CStr {
+        match self {
+            BlobColumn::Data => c"data",
+            BlobColumn::Preview => c"preview",
+        }
+    }
+}
+
+/// An open handle to a single blob within the asset database.
+struct BlobHandle {
+    /// The raw SQLite blob handle.
+    raw: *mut ffi::sqlite3_blob,
+
+    /// The connection the blob belongs to. This is kept alive for as long as
+    /// the blob handle is open.
+    connection: Arc<ConnectionThreadSafe>,
+
+    /// The total size of the blob, in bytes.
+    size: usize,
+
+    /// How blob I/O waits for locks held by other connections.
+    busy: BusySettings,
+}
+
+// SAFETY: The connection is opened in serialized (full mutex) mode, so SQLite
+// guards every call on the blob handle with the connection mutex.
+unsafe impl Send for BlobHandle {}
+
+// SAFETY: See above. The handle is never accessed without a mutable reference.
+unsafe impl Sync for BlobHandle {}
+
+impl BlobHandle {
+    /// Opens the blob in the given column and row of the assets table.
+    fn open(
+        connection: Arc<ConnectionThreadSafe>,
+        busy: BusySettings,
+        column: BlobColumn,
+        rowid: i64,
+        writable: bool,
+    ) -> Result<Self, AwgenDbError> {
+        let db = connection.as_raw();
+        let raw = busy::retry_busy(&busy, || {
+            let mut raw = std::ptr::null_mut();
+
+            // SAFETY: All strings are nul-terminated and `raw` is a valid out
+            // pointer. The database handle is kept alive by `connection`.
+            let code = unsafe {
+                ffi::sqlite3_blob_open(
+                    db,
+                    SCHEMA.as_ptr(),
+                    TABLE.as_ptr(),
+                    column.c_name().as_ptr(),
+                    rowid,
+                    c_int::from(writable),
+                    &mut raw,
+                )
+            };
+
+            if code != ffi::SQLITE_OK {
+                let error = last_error(&connection, code);
+
+                // SAFETY: Closing a null handle is a no-op, and a non-null
+                // handle must be closed even when opening fails.
+                unsafe { ffi::sqlite3_blob_close(raw) };
+                return Err(error);
+            }
+
+            Ok(raw)
+        })?;
+
+        // SAFETY: `raw` was successfully opened above.
+        let size = unsafe { ffi::sqlite3_blob_bytes(raw) } as usize;
+
+        Ok(Self {
+            raw,
+            connection,
+            size,
+            busy,
+        })
+    }
+
+    /// Reads bytes from the blob at the given offset into the buffer.
+    fn read_at(&mut self, buf: &mut [u8], offset: usize) -> io::Result<usize> {
+        let len = buf.len().min(self.size.saturating_sub(offset));
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let c_len = to_c_int(len).map_err(io_error)?;
+        let c_offset = to_c_int(offset).map_err(io_error)?;
+
+        busy::retry_busy(&self.busy, || {
+            // SAFETY: The buffer is valid for `len` bytes, and the range lies
+            // within the blob.
+            let code = unsafe {
+                ffi::sqlite3_blob_read(self.raw, buf.as_mut_ptr() as *mut c_void, c_len, c_offset)
+            };
+
+            if code != ffi::SQLITE_OK {
+                return Err(last_error(&self.connection, code));
+            }
+
+            Ok(())
+        })
+        .map_err(io_error)?;
+
+        Ok(len)
+    }
+
+    /// Writes the bytes from the buffer into the blob at the given offset.
+    ///
+    /// The blob cannot be resized, so the range must lie within the blob.
+    fn write_at(&mut self, buf: &[u8], offset: usize) -> Result<(), AwgenDbError> {
+        let c_len = to_c_int(buf.len())?;
+        let c_offset = to_c_int(offset)?;
+
+        busy::retry_busy(&self.busy, || {
+            // SAFETY: The buffer is valid for `buf.len()` bytes. SQLite checks
+            // that the range lies within the blob and returns an error
+            // otherwise.
+            let code = unsafe {
+                ffi::sqlite3_blob_write(self.raw, buf.as_ptr() as *const c_void, c_len, c_offset)
+            };
+
+            if code != ffi::SQLITE_OK {
+                return Err(last_error(&self.connection, code));
+            }
+
+            Ok(())
+        })
+    }
+}
+
+impl Drop for BlobHandle {
+    fn drop(&mut self) {
+        // SAFETY: The handle is open, and is never used again after this.
+        unsafe { ffi::sqlite3_blob_close(self.raw) };
+    }
+}
+
+/// A streaming reader over an asset blob stored in the asset database.
+///
+/// Data is read directly from the database in chunks as requested, rather than
+/// being loaded into memory all at once. If the underlying row is modified or
+/// removed while the reader is open, further reads will fail.
+pub struct AssetBlobReader {
+    /// The open blob handle, or `None` if the blob is empty or null.
+    handle: Option<BlobHandle>,
+
+    /// The current read position within the blob.
+    position: usize,
+}
+
+impl AssetBlobReader {
+    /// Opens a streaming reader over the blob in the given column and row of
+    /// the assets table.
+    pub(crate) fn open(
+        connection: Arc<ConnectionThreadSafe>,
+        busy: BusySettings,
+        column: BlobColumn,
+        rowid: i64,
+    ) -> Result<Self, AwgenDbError> {
+        let handle = BlobHandle::open(connection, busy, column, rowid, false)?;
+        Ok(Self {
+            handle: Some(handle),
+            position: 0,
+        })
+    }
+
+    /// Creates a reader with no contents.
+    pub(crate) fn empty() -> Self {
+        Self {
+            handle: None,
+            position: 0,
+        }
+    }
+
+    /// Gets the total size of the blob, in bytes.
+    pub fn len(&self) -> usize {
+        self.handle.as_ref().map_or(0, |handle| handle.size)
+    }
+
+    /// Returns true if the blob contains no data.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Read for AssetBlobReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(handle) = &mut self.handle else {
+            return Ok(0);
+        };
+
+        let count = handle.read_at(buf, self.position)?;
+        self.position += count;
+        Ok(count)
+    }
+}
+
+impl AsyncRead for AssetBlobReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(Read::read(self.get_mut(), buf))
+    }
+}
+
+impl AsyncSeek for AssetBlobReader {
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset as i64),
+            SeekFrom::End(offset) => (this.len() as i64).checked_add(offset),
+            SeekFrom::Current(offset) => (this.position as i64).checked_add(offset),
+        };
+
+        let Some(position) = position.filter(|position| *position >= 0) else {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Seek to a negative or overflowing position",
+            )));
+        };
+
+        this.position = position as usize;
+        Poll::Ready(Ok(position as u64))
+    }
+}
+
+impl Reader for AssetBlobReader {
+    fn seekable(&mut self) -> Result<&mut dyn SeekableReader, ReaderNotSeekableError> {
+        Ok(self)
+    }
+}
+
+/// Copies exactly `size` bytes from the given reader into the blob in the given
+/// column and row of the assets table, one chunk at a time.
+///
+/// The blob must already have been allocated with the given size, for example
+/// using `zeroblob(size)`.
+pub(crate) fn write_blob<R: Read>(
+    connection: Arc<ConnectionThreadSafe>,
+    busy: BusySettings,
+    column: BlobColumn,
+    rowid: i64,
+    size: usize,
+    mut reader: R,
+) -> Result<(), AwgenDbError> {
+    if size == 0 {
+        return Ok(());
+    }
+
+    let mut handle = BlobHandle::open(connection, busy, column, rowid, true)?;
+    let mut buf = vec![0u8; CHUNK_SIZE.min(size)];
+    let mut offset = 0;
+
+    while offset < size {
+        let len = buf.len().min(size - offset);
+        reader
+            .read_exact(&mut buf[.. len])
+            .map_err(|e| db_error(format!("Failed to read asset data: {}", e)))?;
+
+        handle.write_at(&buf[.. len], offset)?;
+        offset += len;
+    }
+
+    Ok(())
+}
+
+/// Converts a byte count or offset into the integer type taken by the SQLite
+/// blob functions, which cannot address blobs of 2 GiB or more.
+fn to_c_int(value: usize) -> Result<c_int, AwgenDbError> {
+    c_int::try_from(value).map_err(|_| db_error(format!("Blob offset {} is out of range", value)))
+}
+
+/// Gets the most recent error from the given connection.
+fn last_error(connection: &ConnectionThreadSafe, code: c_int) -> sqlite::Error {
+    // SAFETY: The database handle is valid, and SQLite always returns a valid
+    // nul-terminated string.
+    let message = unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(connection.as_raw())) };
+
+    sqlite::Error {
+        code: Some(code as isize),
+        message: Some(message.to_string_lossy().into_owned()),
+    }
+}
+
+/// Creates a database error with the given message.
+pub(crate) fn db_error(message: String) -> AwgenDbError {
+    AwgenDbError::Sqlite(sqlite::Error {
+        code: Some(ffi::SQLITE_ERROR as isize),
+        message: Some(message),
+    })
+}
+
+/// Converts a database error into an I/O error.
+fn io_error(error: AwgenDbError) -> io::Error {
+    io::Error::other(error.to_string())
+}
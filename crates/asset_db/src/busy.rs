@@ -0,0 +1,127 @@
+//! This module implements the handling of busy errors, which SQLite returns
+//! when another connection holds a lock on the database.
+//!
+//! Each connection first waits for the lock inside SQLite, up to the busy
+//! timeout. If the database is still busy after that, the statement is retried
+//! a bounded number of times with a growing, randomized delay, so that
+//! connections contending for the same lock do not retry in lockstep. Once
+//! every retry has failed, or the statement has been waiting for longer than
+//! the total wait allows, [`AwgenDbError::Busy`] is returned.
+//!
+//! This covers every statement run by an asset database, the incremental I/O
+//! of asset blobs, and the steps of backups and restores.
+
+use std::hash::{BuildHasher, RandomState};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+
+use crate::connection::AwgenDbError;
+
+/// The primary SQLite result code for a database locked by another
+/// connection.
+const SQLITE_BUSY: isize = 5;
+
+/// The primary SQLite result code for a table locked by another connection
+/// sharing the same cache.
+const SQLITE_LOCKED: isize = 6;
+
+/// Settings for how asset database connections wait for locks held by other
+/// connections.
+///
+/// Insert this resource before registering an asset database to configure
+/// its connection. Databases registered without it use the default settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct BusySettings {
+    /// How long SQLite itself waits for a lock before reporting the database
+    /// as busy.
+    pub timeout: Duration,
+
+    /// How many times a statement is retried after SQLite reports the
+    /// database as busy.
+    pub max_retries: u32,
+
+    /// The delay before the first retry. Each further retry waits twice as
+    /// long as the one before it.
+    pub retry_delay: Duration,
+
+    /// The longest total time an operation waits for the database, including
+    /// the time SQLite itself waits, before giving up.
+    pub max_wait: Duration,
+}
+
+impl Default for BusySettings {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(1),
+            max_retries: 3,
+            retry_delay: Duration::from_millis(25),
+            max_wait: Duration::from_secs(5),
+        }
+    }
+}
+
+impl BusySettings {
+    /// Gets the delay before the given retry, counting from zero.
+    ///
+    /// A random jitter of up to half the delay is added, so that connections
+    /// that failed together retry at different times.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let delay = self.retry_delay.saturating_mul(1u32 << retry.min(16));
+        let jitter = delay.mul_f64(random_fraction() * 0.5);
+        delay + jitter
+    }
+}
+
+/// Returns true if the given error reports that the database is locked by
+/// another connection.
+fn is_busy(error: &sqlite::Error) -> bool {
+    error.code.is_some_and(is_busy_code)
+}
+
+/// Returns true if the given SQLite result code reports that the database is
+/// locked by another connection.
+pub(crate) fn is_busy_code(code: isize) -> bool {
+    // Extended result codes keep the primary result code in the lowest byte.
+    matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED)
+}
+
+/// Runs the given operation, retrying it while it fails because the database
+/// is busy.
+///
+/// The operation must be safe to repeat after a busy error. Other errors are
+/// returned right away. No retry is started that would end after the total
+/// wait has passed.
+pub(crate) fn retry_busy<T>(
+    settings: &BusySettings,
+    mut operation: impl FnMut() -> Result<T, sqlite::Error>,
+) -> Result<T, AwgenDbError> {
+    let start = Instant::now();
+    let mut retry = 0;
+    loop {
+        match operation() {
+            Err(err) if is_busy(&err) => {
+                let delay = settings.backoff(retry);
+                if retry >= settings.max_retries || start.elapsed() + delay > settings.max_wait {
+                    return Err(AwgenDbError::Busy {
+                        attempts: retry + 1,
+                    });
+                }
+
+                thread::sleep(delay);
+                retry += 1;
+            }
+            result => return Ok(result?),
+        }
+    }
+}
+
+/// Gets a random number in the range `[0, 1)`.
+///
+/// Each [`RandomState`] is randomly seeded, which is plenty for spreading out
+/// retries without depending on a random number generator.
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().hash_one(()) >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
@@ -1,19 +1,27 @@
 //! This module handles the SQLite database connection for asset management.
 
+use std::collections::HashSet;
+use std::io::Read;
 use std::marker::PhantomData;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 // use std::sync::mpsc::Sender;
-use std::sync::{Arc, RwLock};
-use std::time::SystemTime;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Instant, SystemTime};
 
 use bevy::asset::io::{AssetReaderError, AssetSourceEvent, AssetWriterError};
 use bevy::prelude::*;
 use crossbeam_channel::Sender;
-use sqlite::{Connection, ConnectionThreadSafe, Value};
+use sqlite::{Connection, ConnectionThreadSafe, OpenFlags, Statement, Value};
 
+use crate::backup;
+use crate::blob::{self, AssetBlobReader, BlobColumn};
+use crate::busy::{self, BusySettings};
+use crate::id::IdPrefix;
 use crate::loaders::AwgenAsset;
 use crate::module::{AssetModule, AssetModuleID};
 use crate::record::{AssetRecord, AssetRecordID, ErasedAssetRecord};
+use crate::source::thumbnail_event;
+use crate::watcher::{AssetWatcherSettings, ExternalChanges, PendingEvents};
 
 /// Trait for obtaining the name of the asset database source.
 pub trait AssetDatabaseName {
@@ -38,6 +46,23 @@ pub struct AssetDatabase<Src: AssetDatabaseName> {
 
     /// List of active watchers monitoring the database for changes.
     watchers: Arc<RwLock<Vec<Sender<AssetSourceEvent>>>>,
+
+    /// Change events waiting to be sent to the watchers.
+    pending: Arc<Mutex<PendingEvents>>,
+
+    /// The state used to detect writes made by other processes.
+    external: Arc<Mutex<ExternalChanges>>,
+
+    /// Whether the database was opened in read-only mode.
+    read_only: bool,
+
+    /// How the connection waits for locks held by other connections.
+    busy: BusySettings,
+
+    /// Whether the database has a full-text search index over the asset
+    /// records. It is missing if SQLite was built without FTS5, or if a
+    /// read-only database was created before the index was added.
+    search_index: bool,
 }
 
 impl<Src: AssetDatabaseName> Clone for AssetDatabase<Src> {
@@ -46,6 +71,11 @@ impl<Src: AssetDatabaseName> Clone for AssetDatabase<Src> {
             connection: self.connection.clone(),
             _marker: PhantomData,
             watchers: self.watchers.clone(),
+            pending: self.pending.clone(),
+            external: self.external.clone(),
+            read_only: self.read_only,
+            busy: self.busy,
+            search_index: self.search_index,
         }
     }
 }
@@ -54,69 +84,271 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
     /// Creates a new [`AssetDatabase`] connection with the specified database
     /// file path. If the file does not exist, it will be created if possible.
     pub(crate) fn new<T: Into<PathBuf>>(path: T) -> Result<Self, AwgenDbError> {
+        Self::new_with_busy_settings(path, BusySettings::default())
+    }
+
+    /// Creates a new [`AssetDatabase`] connection with the specified database
+    /// file path, waiting for locks held by other connections as configured.
+    pub(crate) fn new_with_busy_settings<T: Into<PathBuf>>(
+        path: T,
+        busy: BusySettings,
+    ) -> Result<Self, AwgenDbError> {
         let connection = Connection::open_thread_safe(path.into())?;
+        set_busy_timeout(&connection, &busy)?;
+        busy::retry_busy(&busy, || enable_wal(&connection))?;
+        busy::retry_busy(&busy, || create_schema(&connection))?;
 
-        connection.execute(
-            r#"
-            CREATE TABLE IF NOT EXISTS modules (
-                uuid TEXT PRIMARY KEY,
-                name TEXT NOT NULL DEFAULT 'Unnamed'
-            );
-            CREATE TABLE IF NOT EXISTS assets (
-                uuid TEXT PRIMARY KEY,
-                type TEXT NOT NULL,
-                path TEXT NOT NULL,
-                module TEXT NOT NULL,
-                data BLOB,
-                preview BLOB,
-                created INTEGER NOT NULL,
-                last_modified INTEGER NOT NULL,
-                FOREIGN KEY (module) REFERENCES modules (uuid)
-            );
-            "#,
-        )?;
+        if let Err(err) = busy::retry_busy(&busy, || create_search_index(&connection)) {
+            warn!("Full-text asset search is unavailable: {}", err);
+        }
+        let search_index = busy::retry_busy(&busy, || has_search_index(&connection))?;
 
         Ok(Self {
             connection: Arc::new(connection),
             _marker: PhantomData,
             watchers: Arc::new(RwLock::new(Vec::new())),
+            pending: Arc::new(Mutex::new(PendingEvents::default())),
+            external: Arc::new(Mutex::new(ExternalChanges::default())),
+            read_only: false,
+            busy,
+            search_index,
         })
     }
 
+    /// Opens an existing [`AssetDatabase`] in read-only mode. This is used for
+    /// projects stored on read-only filesystems, such as network shares or
+    /// packaged builds.
+    ///
+    /// All methods that would modify the database will return
+    /// [`AwgenDbError::ReadOnly`].
+    pub(crate) fn new_read_only<T: Into<PathBuf>>(path: T) -> Result<Self, AwgenDbError> {
+        Self::new_read_only_with_busy_settings(path, BusySettings::default())
+    }
+
+    /// Opens an existing [`AssetDatabase`] in read-only mode, waiting for locks
+    /// held by other connections as configured.
+    pub(crate) fn new_read_only_with_busy_settings<T: Into<PathBuf>>(
+        path: T,
+        busy: BusySettings,
+    ) -> Result<Self, AwgenDbError> {
+        let flags = OpenFlags::new().with_read_only().with_full_mutex();
+        let connection = Connection::open_thread_safe_with_flags(path.into(), flags)?;
+        set_busy_timeout(&connection, &busy)?;
+        let search_index = busy::retry_busy(&busy, || has_search_index(&connection))?;
+
+        Ok(Self {
+            connection: Arc::new(connection),
+            _marker: PhantomData,
+            watchers: Arc::new(RwLock::new(Vec::new())),
+            pending: Arc::new(Mutex::new(PendingEvents::default())),
+            external: Arc::new(Mutex::new(ExternalChanges::default())),
+            read_only: true,
+            busy,
+            search_index,
+        })
+    }
+
+    /// Returns true if the database was opened in read-only mode.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Runs a statement that does not return rows to completion, retrying it
+    /// while the database is busy.
+    fn execute(&self, statement: &mut Statement) -> Result<(), AwgenDbError> {
+        busy::retry_busy(&self.busy, || {
+            statement.reset()?;
+            while let sqlite::State::Row = statement.next()? {}
+            Ok(())
+        })
+    }
+
+    /// Runs a statement that returns rows, reading each row with the given
+    /// function and skipping the rows it returns `None` for.
+    ///
+    /// While the database is busy, the statement is run again from its first
+    /// row, so rows read before the database became busy are not repeated.
+    fn query<T>(
+        &self,
+        statement: &mut Statement,
+        mut read: impl FnMut(&Statement) -> Result<Option<T>, sqlite::Error>,
+    ) -> Result<Vec<T>, AwgenDbError> {
+        busy::retry_busy(&self.busy, || {
+            statement.reset()?;
+            let mut rows = Vec::new();
+            while let sqlite::State::Row = statement.next()? {
+                rows.extend(read(statement)?);
+            }
+            Ok(rows)
+        })
+    }
+
+    /// Runs a statement that returns at most one row, reading the row with the
+    /// given function, and retrying it while the database is busy.
+    fn query_row<T>(
+        &self,
+        statement: &mut Statement,
+        mut read: impl FnMut(&Statement) -> Result<T, sqlite::Error>,
+    ) -> Result<Option<T>, AwgenDbError> {
+        busy::retry_busy(&self.busy, || {
+            statement.reset()?;
+            match statement.next()? {
+                sqlite::State::Row => Ok(Some(read(statement)?)),
+                sqlite::State::Done => Ok(None),
+            }
+        })
+    }
+
+    /// Returns an error if the database was opened in read-only mode.
+    fn check_writable(&self) -> Result<(), AwgenDbError> {
+        if self.read_only {
+            return Err(AwgenDbError::ReadOnly);
+        }
+
+        Ok(())
+    }
+
     /// Adds a new watcher to monitor the database for changes.
     pub(crate) fn add_watcher(&self, watcher: Sender<AssetSourceEvent>) {
         let mut watchers = self.watchers.write().unwrap();
         watchers.push(watcher);
     }
 
-    /// Sends an event to all registered watchers.
+    /// Queues an event to be sent to all registered watchers.
+    ///
+    /// Events are merged per asset and sent by
+    /// [`AssetDatabase::flush_events`] once the asset stops changing.
     fn send_event(&self, event: AssetSourceEvent) {
+        self.pending.lock().unwrap().push(event);
+    }
+
+    /// Sends all queued events that are ready according to the given settings
+    /// to the registered watchers, and returns the sent events.
+    pub(crate) fn flush_events(&self, settings: &AssetWatcherSettings) -> Vec<AssetSourceEvent> {
+        let events = self
+            .pending
+            .lock()
+            .unwrap()
+            .drain_ready(Instant::now(), settings);
+
+        if events.is_empty() {
+            return events;
+        }
+
         let watchers = self.watchers.read().unwrap();
-        for sender in watchers.iter() {
-            let _ = sender.send(event.clone());
+        for event in events.iter() {
+            let thumbnail = thumbnail_event(event);
+            for sender in watchers.iter() {
+                let _ = sender.send(event.clone());
+                if let Some(thumbnail) = &thumbnail {
+                    let _ = sender.send(thumbnail.clone());
+                }
+            }
         }
+
+        events
     }
 
-    /// Retrieves all asset modules from the database.
-    pub(crate) fn get_modules(&self) -> Result<Vec<AssetModule>, AwgenDbError> {
-        let query = "SELECT uuid, name FROM modules";
-        let mut modules = Vec::new();
+    /// Checks whether another process has written to the database, and queues
+    /// change events for the assets it added, modified or removed.
+    ///
+    /// SQLite only changes the `data_version` of a connection when another
+    /// connection commits a write, so this is a single cheap query unless an
+    /// external write occurred.
+    pub(crate) fn poll_external_changes(
+        &self,
+        settings: &AssetWatcherSettings,
+    ) -> Result<(), AwgenDbError> {
+        let mut external = self.external.lock().unwrap();
+        if !external.should_poll(Instant::now(), settings) {
+            return Ok(());
+        }
 
-        let mut statement = self.connection.prepare(query)?;
-        while let Ok(sqlite::State::Row) = statement.next() {
-            let uuid = statement.read::<String, _>("uuid")?;
-            let name = statement.read::<String, _>("name")?;
+        let mut statement = self.connection.prepare("PRAGMA data_version")?;
+        let version = self.query_row(&mut statement, |row| row.read::<i64, _>(0))?;
+        if !external.update_version(version.unwrap_or_default()) {
+            return Ok(());
+        }
 
-            let Some(id) = AssetModuleID::from_string(&uuid) else {
-                error!("Invalid UUID in asset database: {}", uuid);
-                continue;
-            };
+        let events = external.diff(&self.get_assets()?);
+        if !events.is_empty() {
+            debug!("Detected {} external asset changes", events.len());
+        }
+
+        for event in events {
+            self.send_event(event);
+        }
+
+        Ok(())
+    }
+
+    /// Writes a snapshot of the database to the database file at the given
+    /// path, replacing its contents if it exists.
+    pub(crate) fn backup_to(&self, path: &Path) -> Result<(), AwgenDbError> {
+        backup::backup_to(&self.connection, path, &self.busy)?;
+        Ok(())
+    }
+
+    /// Replaces the contents of the database with the snapshot stored in the
+    /// database file at the given path.
+    ///
+    /// Watchers are notified that every asset in the snapshot was modified,
+    /// and that every asset missing from it was removed.
+    pub(crate) fn restore_from(&self, path: &Path) -> Result<(), AwgenDbError> {
+        self.check_writable()?;
+
+        let old_assets = self.get_assets()?;
+        backup::restore_from(&self.connection, path, &self.busy)?;
+
+        // Snapshots of older databases may be missing newer tables.
+        busy::retry_busy(&self.busy, || create_schema(&self.connection))?;
+        if self.search_index {
+            busy::retry_busy(&self.busy, || create_search_index(&self.connection))?;
+        }
+        let new_assets = self.get_assets()?;
+
+        let restored = new_assets
+            .iter()
+            .map(|asset| asset.id)
+            .collect::<HashSet<_>>();
+        for asset in old_assets
+            .iter()
+            .filter(|asset| !restored.contains(&asset.id))
+        {
+            self.send_event(AssetSourceEvent::RemovedAsset(path_buf(
+                asset.id,
+                true,
+                Image::type_name(),
+            )));
+            self.send_event(AssetSourceEvent::RemovedAsset(path_buf(
+                asset.id,
+                false,
+                &asset.asset_type,
+            )));
+        }
 
-            let module = AssetModule { id, name };
-            modules.push(module);
+        for asset in new_assets {
+            self.send_event(AssetSourceEvent::ModifiedAsset(path_buf(
+                asset.id,
+                true,
+                Image::type_name(),
+            )));
+            self.send_event(AssetSourceEvent::ModifiedAsset(path_buf(
+                asset.id,
+                false,
+                &asset.asset_type,
+            )));
         }
 
-        Ok(modules)
+        Ok(())
+    }
+
+    /// Retrieves all asset modules from the database.
+    pub(crate) fn get_modules(&self) -> Result<Vec<AssetModule>, AwgenDbError> {
+        let query = "SELECT uuid, name FROM modules";
+
+        let mut statement = self.connection.prepare(query)?;
+        self.query(&mut statement, read_module_row)
     }
 
     /// Retrieves a specific asset module by its ID.
@@ -128,31 +360,19 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
 
         let mut statement = self.connection.prepare(query)?;
         statement.bind((":uuid", module_id))?;
-
-        if let Ok(sqlite::State::Row) = statement.next() {
-            let uuid = statement.read::<String, _>("uuid")?;
-            let name = statement.read::<String, _>("name")?;
-
-            let Some(id) = AssetModuleID::from_string(&uuid) else {
-                error!("Invalid AssetModuleID in asset database: {}", uuid);
-                return Ok(None);
-            };
-
-            let module = AssetModule { id, name };
-            Ok(Some(module))
-        } else {
-            Ok(None)
-        }
+        Ok(self.query_row(&mut statement, read_module_row)?.flatten())
     }
 
     /// Inserts (or updates) a new asset module into the database.
     pub(crate) fn insert_module(&self, module: &AssetModule) -> Result<(), AwgenDbError> {
+        self.check_writable()?;
+
         let query = "INSERT INTO modules (uuid, name) VALUES (:uuid, :name)";
 
         let mut statement = self.connection.prepare(query)?;
         statement.bind((":uuid", module.id))?;
         statement.bind((":name", module.name.as_str()))?;
-        while let sqlite::State::Row = statement.next()? {}
+        self.execute(&mut statement)?;
 
         Ok(())
     }
@@ -162,7 +382,14 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
     /// WARNING: This action will also delete *all* assets associated with this
     /// module.
     pub(crate) fn remove_module(&self, module: AssetModuleID) -> Result<(), AwgenDbError> {
-        let assets = self.get_assets()?;
+        self.check_writable()?;
+
+        let filter = AssetFilter {
+            module: Some(module),
+            ..default()
+        };
+
+        let assets = self.get_assets_filtered(&filter, usize::MAX, 0)?;
         for asset in assets {
             self.send_event(AssetSourceEvent::RemovedAsset(path_buf(
                 asset.id,
@@ -179,12 +406,16 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
         let module_query = "DELETE FROM modules WHERE uuid = :uuid";
         let mut statement = self.connection.prepare(module_query)?;
         statement.bind((":uuid", module))?;
-        while let sqlite::State::Row = statement.next()? {}
+        self.execute(&mut statement)?;
 
         let asset_query = "DELETE FROM assets WHERE module = :module";
         let mut statement = self.connection.prepare(asset_query)?;
         statement.bind((":module", module))?;
-        while let sqlite::State::Row = statement.next()? {}
+        self.execute(&mut statement)?;
+
+        let usage_query = "DELETE FROM asset_usage WHERE uuid NOT IN (SELECT uuid FROM assets)";
+        let mut statement = self.connection.prepare(usage_query)?;
+        self.execute(&mut statement)?;
 
         Ok(())
     }
@@ -204,78 +435,422 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
 
         let mut statement = self.connection.prepare(query)?;
         statement.bind((":uuid", id))?;
-        statement.next()?;
+        Ok(self.query_row(&mut statement, read_asset_row)?.flatten())
+    }
 
-        let uuid = statement.read::<String, _>("uuid")?;
-        let asset_type = statement.read::<String, _>("type")?;
-        let path = statement.read::<String, _>("path")?;
-        let module_uuid = statement.read::<String, _>("module")?;
-        let created = statement.read::<i64, _>("created")?;
-        let last_modified = statement.read::<i64, _>("last_modified")?;
+    /// Finds the IDs of all assets whose ID starts with the given prefix.
+    ///
+    /// IDs are compared in their simple form, as lowercase hexadecimal digits
+    /// without hyphens, like [`IdPrefix`] does.
+    pub(crate) fn find_asset_ids(
+        &self,
+        prefix: &IdPrefix,
+    ) -> Result<Vec<AssetRecordID>, AwgenDbError> {
+        let query = r#"
+            SELECT uuid FROM assets
+            WHERE substr(lower(replace(uuid, '-', '')), 1, length(:prefix)) = :prefix;
+        "#;
 
-        let Some(id) = AssetRecordID::from_string(&uuid) else {
-            error!("Invalid AssetRecordID in asset database: {}", uuid);
-            return Ok(None);
-        };
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":prefix", prefix.to_string().as_str()))?;
 
-        let Some(module) = AssetModuleID::from_string(&module_uuid) else {
-            error!("Invalid AssetModuleID in asset database: {}", module_uuid);
-            return Ok(None);
-        };
+        self.query(&mut statement, |row| {
+            let uuid = row.read::<String, _>("uuid")?;
+            let Some(id) = AssetRecordID::from_string(&uuid) else {
+                error!("Invalid AssetRecordID in asset database: {}", uuid);
+                return Ok(None);
+            };
 
-        let asset = ErasedAssetRecord {
-            id,
-            asset_type,
-            pathname: PathBuf::from(path),
-            module,
-            created,
-            last_modified,
-        };
-        Ok(Some(asset))
+            Ok(id.matches(prefix).then_some(id))
+        })
     }
 
-    /// Retrieves all asset records of the given type from the database as
-    /// partial records.
+    /// Retrieves all asset records from the database as partial records.
     ///
     /// Does not include preview or data fields.
     pub(crate) fn get_assets(&self) -> Result<Vec<ErasedAssetRecord>, AwgenDbError> {
         let query = "SELECT uuid, type, path, module, created, last_modified FROM assets";
-        let mut assets = Vec::new();
+        let mut statement = self.connection.prepare(query)?;
+        self.query(&mut statement, read_asset_row)
+    }
+
+    /// Retrieves a page of the asset records that pass the given filter from
+    /// the database as partial records, sorted by pathname.
+    ///
+    /// Up to `limit` records are returned, after skipping the first `offset`
+    /// records that pass the filter. Records with the same pathname are kept in
+    /// a stable order, so that consecutive pages do not overlap.
+    ///
+    /// Does not include preview or data fields.
+    pub(crate) fn get_assets_filtered(
+        &self,
+        filter: &AssetFilter,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<ErasedAssetRecord>, AwgenDbError> {
+        let query = format!(
+            r#"
+            SELECT uuid, type, path, module, created, last_modified
+            FROM assets
+            {}
+            ORDER BY path, rowid
+            LIMIT :limit OFFSET :offset;
+            "#,
+            filter.where_clause()
+        );
+
+        // SQLite reads a negative limit as no limit at all.
+        let limit = i64::try_from(limit).unwrap_or(-1);
+        let offset = i64::try_from(offset).unwrap_or(i64::MAX);
 
         let mut statement = self.connection.prepare(query)?;
-        while let Ok(sqlite::State::Row) = statement.next() {
-            let uuid = statement.read::<String, _>("uuid")?;
-            let asset_type = statement.read::<String, _>("type")?;
-            let path = statement.read::<String, _>("path")?;
-            let module_uuid = statement.read::<String, _>("module")?;
-            let created = statement.read::<i64, _>("created")?;
-            let last_modified = statement.read::<i64, _>("last_modified")?;
+        filter.bind(&mut statement)?;
+        statement.bind((":limit", limit))?;
+        statement.bind((":offset", offset))?;
+        self.query(&mut statement, read_asset_row)
+    }
 
-            let Some(id) = AssetRecordID::from_string(&uuid) else {
-                error!("Invalid AssetRecordID in asset database: {}", uuid);
-                continue;
-            };
+    /// Counts the asset records that pass the given filter.
+    pub(crate) fn count_assets_filtered(
+        &self,
+        filter: &AssetFilter,
+    ) -> Result<usize, AwgenDbError> {
+        let query = format!(
+            "SELECT COUNT(*) AS count FROM assets {}",
+            filter.where_clause()
+        );
 
-            let Some(module) = AssetModuleID::from_string(&module_uuid) else {
-                error!("Invalid AssetModuleID in asset database: {}", module_uuid);
-                continue;
-            };
+        let mut statement = self.connection.prepare(query)?;
+        filter.bind(&mut statement)?;
 
-            let asset = ErasedAssetRecord {
-                id,
-                asset_type,
-                pathname: PathBuf::from(path),
-                module,
-                created,
-                last_modified,
-            };
+        let count = self.query_row(&mut statement, |row| row.read::<i64, _>("count"))?;
+        Ok(count.unwrap_or_default() as usize)
+    }
+
+    /// Searches the asset records by pathname and module name, returning up to
+    /// `limit` matches as partial records, best matches first.
+    ///
+    /// The query is split into words, and only assets matching every word are
+    /// returned. Each word matches any word of the pathname or module name that
+    /// starts with it, ignoring case, so that partially typed queries already
+    /// find results. A query without any words matches nothing.
+    ///
+    /// Does not include preview or data fields.
+    pub(crate) fn search_assets(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<ErasedAssetRecord>, AwgenDbError> {
+        let words = query
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>();
+
+        if words.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // SQLite reads a negative limit as no limit at all.
+        let limit = i64::try_from(limit).unwrap_or(-1);
+
+        if !self.search_index {
+            return self.search_assets_unindexed(&words, limit);
+        }
+
+        // Matches in the pathname are ranked above matches in the module name.
+        let query = r#"
+            SELECT a.uuid, a.type, a.path, a.module, a.created, a.last_modified
+            FROM assets_fts
+            JOIN assets a ON a.uuid = assets_fts.uuid
+            WHERE assets_fts MATCH :query
+            ORDER BY bm25(assets_fts, 0.0, 2.0, 1.0), a.path, a.rowid
+            LIMIT :limit;
+        "#;
 
-            assets.push(asset);
+        let terms = words
+            .iter()
+            .map(|word| format!("\"{word}\"*"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":query", terms.as_str()))?;
+        statement.bind((":limit", limit))?;
+        self.query(&mut statement, read_asset_row)
+    }
+
+    /// Searches the asset records without the full-text search index, sorting
+    /// the matches by pathname.
+    ///
+    /// Words are matched anywhere in the pathname or module name, rather than
+    /// only at the start of a word.
+    fn search_assets_unindexed(
+        &self,
+        words: &[&str],
+        limit: i64,
+    ) -> Result<Vec<ErasedAssetRecord>, AwgenDbError> {
+        let conditions = (0 .. words.len())
+            .map(|i| format!("(a.path LIKE :word{i} OR m.name LIKE :word{i})"))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+
+        let query = format!(
+            r#"
+            SELECT a.uuid, a.type, a.path, a.module, a.created, a.last_modified
+            FROM assets a
+            LEFT JOIN modules m ON m.uuid = a.module
+            WHERE {conditions}
+            ORDER BY a.path, a.rowid
+            LIMIT :limit;
+            "#
+        );
+
+        let mut statement = self.connection.prepare(query)?;
+        for (i, word) in words.iter().enumerate() {
+            let pattern = format!("%{word}%");
+            statement.bind((format!(":word{i}").as_str(), pattern.as_str()))?;
+        }
+        statement.bind((":limit", limit))?;
+        self.query(&mut statement, read_asset_row)
+    }
+
+    /// Retrieves the most recently created asset records from the database as
+    /// partial records, newest first, up to the given limit.
+    ///
+    /// Assets with time-ordered IDs are read in order from the primary key
+    /// index. Assets with older, random IDs were created before any of those,
+    /// and are only read when fewer than `limit` assets have time-ordered IDs.
+    ///
+    /// Does not include preview or data fields.
+    pub(crate) fn get_recent_assets(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<ErasedAssetRecord>, AwgenDbError> {
+        let query = r#"
+            SELECT uuid, type, path, module, created, last_modified
+            FROM assets
+            WHERE substr(uuid, 15, 1) = '7'
+            ORDER BY uuid DESC
+            LIMIT :limit;
+        "#;
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":limit", limit as i64))?;
+        let mut assets = self.query(&mut statement, read_asset_row)?;
+
+        if assets.len() < limit {
+            let legacy_query = r#"
+                SELECT uuid, type, path, module, created, last_modified
+                FROM assets
+                WHERE substr(uuid, 15, 1) != '7'
+                ORDER BY created DESC
+                LIMIT :limit;
+            "#;
+
+            let mut statement = self.connection.prepare(legacy_query)?;
+            statement.bind((":limit", (limit - assets.len()) as i64))?;
+            assets.extend(self.query(&mut statement, read_asset_row)?);
         }
 
         Ok(assets)
     }
 
+    /// Records that an asset was opened or used, incrementing its use count and
+    /// setting its last use time to the current system time.
+    pub(crate) fn record_asset_use(&self, asset_id: AssetRecordID) -> Result<(), AwgenDbError> {
+        self.check_writable()?;
+
+        let query = r#"
+            INSERT INTO asset_usage (uuid, use_count, last_used)
+            VALUES (:uuid, 1, :now)
+            ON CONFLICT(uuid) DO UPDATE SET
+                use_count = use_count + 1,
+                last_used = excluded.last_used;
+        "#;
+
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("System time set before UNIX EPOCH!")
+            .as_millis() as i64;
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":uuid", asset_id))?;
+        statement.bind((":now", now))?;
+        self.execute(&mut statement)?;
+
+        Ok(())
+    }
+
+    /// Retrieves the most recently used asset records from the database as
+    /// partial records, most recent first, up to the given limit.
+    ///
+    /// Does not include preview or data fields.
+    pub(crate) fn get_recently_used_assets(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<ErasedAssetRecord>, AwgenDbError> {
+        let query = r#"
+            SELECT assets.uuid, type, path, module, created, last_modified
+            FROM asset_usage
+            JOIN assets ON assets.uuid = asset_usage.uuid
+            WHERE use_count > 0
+            ORDER BY last_used DESC
+            LIMIT :limit;
+        "#;
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":limit", limit as i64))?;
+        self.query(&mut statement, read_asset_row)
+    }
+
+    /// Gets the number of times an asset has been used.
+    pub(crate) fn get_asset_use_count(&self, asset_id: AssetRecordID) -> Result<u64, AwgenDbError> {
+        let query = "SELECT use_count FROM asset_usage WHERE uuid = :uuid";
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":uuid", asset_id))?;
+
+        let count = self.query_row(&mut statement, |row| row.read::<i64, _>("use_count"))?;
+        Ok(count.unwrap_or_default() as u64)
+    }
+
+    /// Pins or unpins an asset as a favorite.
+    pub(crate) fn set_asset_favorite(
+        &self,
+        asset_id: AssetRecordID,
+        favorite: bool,
+    ) -> Result<(), AwgenDbError> {
+        self.check_writable()?;
+
+        let query = r#"
+            INSERT INTO asset_usage (uuid, favorite)
+            VALUES (:uuid, :favorite)
+            ON CONFLICT(uuid) DO UPDATE SET
+                favorite = excluded.favorite;
+        "#;
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":uuid", asset_id))?;
+        statement.bind((":favorite", favorite as i64))?;
+        self.execute(&mut statement)?;
+
+        Ok(())
+    }
+
+    /// Returns true if the asset is pinned as a favorite.
+    pub(crate) fn is_asset_favorite(&self, asset_id: AssetRecordID) -> Result<bool, AwgenDbError> {
+        let query = "SELECT favorite FROM asset_usage WHERE uuid = :uuid";
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":uuid", asset_id))?;
+
+        let favorite = self.query_row(&mut statement, |row| row.read::<i64, _>("favorite"))?;
+        Ok(favorite.is_some_and(|favorite| favorite != 0))
+    }
+
+    /// Retrieves all favorite asset records from the database as partial
+    /// records, sorted by pathname.
+    ///
+    /// Does not include preview or data fields.
+    pub(crate) fn get_favorite_assets(&self) -> Result<Vec<ErasedAssetRecord>, AwgenDbError> {
+        let query = r#"
+            SELECT assets.uuid, type, path, module, created, last_modified
+            FROM asset_usage
+            JOIN assets ON assets.uuid = asset_usage.uuid
+            WHERE favorite != 0
+            ORDER BY path;
+        "#;
+
+        let mut statement = self.connection.prepare(query)?;
+        self.query(&mut statement, read_asset_row)
+    }
+
+    /// Gets the value of a setting, or `None` if it has not been stored.
+    pub(crate) fn get_setting(&self, key: &str) -> Result<Option<String>, AwgenDbError> {
+        let query = "SELECT value FROM settings WHERE key = :key";
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":key", key))?;
+
+        self.query_row(&mut statement, |row| row.read::<String, _>("value"))
+    }
+
+    /// Stores the value of a setting, replacing any previous value.
+    pub(crate) fn set_setting(&self, key: &str, value: &str) -> Result<(), AwgenDbError> {
+        self.check_writable()?;
+
+        let query = "INSERT OR REPLACE INTO settings (key, value) VALUES (:key, :value)";
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":key", key))?;
+        statement.bind((":value", value))?;
+        self.execute(&mut statement)?;
+
+        Ok(())
+    }
+
+    /// Retrieves all asset records of the given type from the database as
+    /// partial records.
+    ///
+    /// Does not include preview or data fields.
+    pub(crate) fn get_assets_of_type(
+        &self,
+        asset_type: &str,
+    ) -> Result<Vec<ErasedAssetRecord>, AwgenDbError> {
+        let query = r#"
+            SELECT uuid, type, path, module, created, last_modified
+            FROM assets
+            WHERE type = :type;
+        "#;
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":type", asset_type))?;
+        self.query(&mut statement, read_asset_row)
+    }
+
+    /// Retrieves all asset records of a specific type that have no preview
+    /// image.
+    pub(crate) fn get_assets_missing_preview(
+        &self,
+        asset_type: &str,
+    ) -> Result<Vec<ErasedAssetRecord>, AwgenDbError> {
+        let query = r#"
+            SELECT uuid, type, path, module, created, last_modified
+            FROM assets
+            WHERE type = :type AND (preview IS NULL OR length(preview) = 0);
+        "#;
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":type", asset_type))?;
+        self.query(&mut statement, read_asset_row)
+    }
+
+    /// Counts the number of assets of each type in the database, sorted by
+    /// type name.
+    pub(crate) fn count_assets_by_type(&self) -> Result<Vec<(String, usize)>, AwgenDbError> {
+        let query = "SELECT type, COUNT(*) AS count FROM assets GROUP BY type ORDER BY type";
+
+        let mut statement = self.connection.prepare(query)?;
+        self.query(&mut statement, read_type_count_row)
+    }
+
+    /// Counts the number of assets of each type that have no preview image,
+    /// sorted by type name.
+    pub(crate) fn count_assets_missing_preview_by_type(
+        &self,
+    ) -> Result<Vec<(String, usize)>, AwgenDbError> {
+        let query = r#"
+            SELECT type, COUNT(*) AS count
+            FROM assets
+            WHERE preview IS NULL OR length(preview) = 0
+            GROUP BY type
+            ORDER BY type;
+        "#;
+
+        let mut statement = self.connection.prepare(query)?;
+        self.query(&mut statement, read_type_count_row)
+    }
+
     /// Inserts (or updates) a new asset record into the database.
     ///
     /// If the [`AssetRecord::created`] or [`AssetRecord::last_modified`] fields
@@ -286,14 +861,76 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
         asset: &AssetRecord<A>,
         data: &[u8],
     ) -> Result<(), AwgenDbError> {
+        self.check_writable()?;
+
+        self.upsert_asset(asset, AssetDataSource::Bytes(data))?;
+        self.send_event(AssetSourceEvent::AddedAsset(path_buf(
+            asset.id,
+            false,
+            A::type_name(),
+        )));
+
+        Ok(())
+    }
+
+    /// Inserts (or updates) a new asset record into the database, streaming
+    /// exactly `size` bytes of asset data from the given reader.
+    ///
+    /// Unlike [`AssetDatabase::insert_asset`], the asset data is never held in
+    /// memory all at once. Instead, space for the blob is allocated up front
+    /// and the data is copied into it in small chunks, keeping peak memory
+    /// usage bounded for large assets.
+    ///
+    /// If the reader fails partway through, the asset record will remain in
+    /// the database with incomplete data.
+    pub(crate) fn insert_asset_from_reader<A: AwgenAsset, R: Read>(
+        &self,
+        asset: &AssetRecord<A>,
+        size: usize,
+        reader: R,
+    ) -> Result<(), AwgenDbError> {
+        self.check_writable()?;
+
+        let rowid = self.upsert_asset(asset, AssetDataSource::Zeroed(size))?;
+        blob::write_blob(
+            self.connection.clone(),
+            self.busy,
+            BlobColumn::Data,
+            rowid,
+            size,
+            reader,
+        )?;
+
+        self.send_event(AssetSourceEvent::AddedAsset(path_buf(
+            asset.id,
+            false,
+            A::type_name(),
+        )));
+
+        Ok(())
+    }
+
+    /// Writes the asset record and its data into the database, returning the
+    /// row ID of the asset.
+    fn upsert_asset<A: AwgenAsset>(
+        &self,
+        asset: &AssetRecord<A>,
+        data: AssetDataSource,
+    ) -> Result<i64, AwgenDbError> {
         let module_query = r#"
             INSERT OR IGNORE INTO modules (uuid, name)
             VALUES (:module, 'Unnamed');
         "#;
 
-        let asset_query = r#"
+        let data_expr = match data {
+            AssetDataSource::Bytes(_) => ":data",
+            AssetDataSource::Zeroed(_) => "zeroblob(:data)",
+        };
+
+        let asset_query = format!(
+            r#"
             INSERT INTO assets (uuid, type, path, module, created, last_modified, data)
-            VALUES (:uuid, :type, :path, :module, :created, :last_modified, :data)
+            VALUES (:uuid, :type, :path, :module, :created, :last_modified, {data_expr})
             ON CONFLICT(uuid) DO UPDATE SET
                 type = excluded.type,
                 path = excluded.path,
@@ -301,7 +938,8 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
                 created = excluded.created,
                 last_modified = excluded.last_modified,
                 data = excluded.data;
-        "#;
+        "#
+        );
 
         let mut created = asset.created;
         if created < 0 {
@@ -323,7 +961,7 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
 
         let mut statement = self.connection.prepare(module_query)?;
         statement.bind((":module", asset.module))?;
-        while let sqlite::State::Row = statement.next()? {}
+        self.execute(&mut statement)?;
 
         let mut statement = self.connection.prepare(asset_query)?;
         statement.bind((":uuid", asset.id))?;
@@ -332,16 +970,19 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
         statement.bind((":module", asset.module))?;
         statement.bind((":created", created))?;
         statement.bind((":last_modified", last_modified))?;
-        statement.bind((":data", data))?;
 
-        while let sqlite::State::Row = statement.next()? {}
-        self.send_event(AssetSourceEvent::AddedAsset(path_buf(
-            asset.id,
-            false,
-            A::type_name(),
-        )));
+        match data {
+            AssetDataSource::Bytes(data) => statement.bind((":data", data))?,
+            AssetDataSource::Zeroed(size) => statement.bind((":data", size as i64))?,
+        }
 
-        Ok(())
+        self.execute(&mut statement)?;
+
+        let query = "SELECT rowid FROM assets WHERE uuid = :uuid";
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":uuid", asset.id))?;
+        self.query_row(&mut statement, |row| row.read::<i64, _>("rowid"))?
+            .ok_or_else(|| blob::db_error(format!("Asset {} was not written", asset.id)))
     }
 
     /// Sets the data blob for a specific asset by its ID.
@@ -357,8 +998,10 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
         asset_id: AssetRecordID,
         data: &[u8],
     ) -> Result<(), AwgenDbError> {
+        self.check_writable()?;
+
         let record = self.get_asset(asset_id)?.ok_or_else(|| {
-            AwgenDbError(sqlite::Error {
+            AwgenDbError::Sqlite(sqlite::Error {
                 code: Some(1),
                 message: Some(format!("Asset with ID {} does not exist.", asset_id)),
             })
@@ -381,7 +1024,7 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
         statement.bind((":last_modified", last_modified))?;
         statement.bind((":data", data))?;
 
-        while let sqlite::State::Row = statement.next()? {}
+        self.execute(&mut statement)?;
         self.send_event(AssetSourceEvent::ModifiedAsset(path_buf(
             asset_id,
             false,
@@ -402,6 +1045,8 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
         asset_id: AssetRecordID,
         preview: Option<&[u8]>,
     ) -> Result<(), AwgenDbError> {
+        self.check_writable()?;
+
         let query = r#"
             UPDATE assets
             SET preview = :preview,
@@ -424,7 +1069,7 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
             statement.bind((":preview", Value::Null))?;
         }
 
-        while let sqlite::State::Row = statement.next()? {}
+        self.execute(&mut statement)?;
 
         self.send_event(AssetSourceEvent::ModifiedAsset(path_buf(
             asset_id,
@@ -436,6 +1081,9 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
     }
 
     /// Retrieves the data blob for a specific asset by its ID.
+    ///
+    /// This loads the entire blob into memory. Prefer
+    /// [`AssetDatabase::open_asset_blob`] for large assets.
     pub(crate) fn get_asset_data(
         &self,
         asset_id: AssetRecordID,
@@ -445,38 +1093,235 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
         let mut statement = self.connection.prepare(query)?;
         statement.bind((":uuid", asset_id))?;
 
-        if let Ok(sqlite::State::Row) = statement.next() {
-            let data = statement.read::<Vec<u8>, _>("data")?;
-            Ok(Some(data))
-        } else {
-            Ok(None)
+        self.query_row(&mut statement, |row| row.read::<Vec<u8>, _>("data"))
+    }
+
+    /// Opens a streaming reader over the data or preview blob for a specific
+    /// asset by its ID.
+    ///
+    /// The blob is read directly from the database in chunks as it is
+    /// consumed, rather than being loaded into memory all at once. If the
+    /// asset exists but the blob is empty, an empty reader is returned.
+    pub(crate) fn open_asset_blob(
+        &self,
+        asset_id: AssetRecordID,
+        column: BlobColumn,
+    ) -> Result<Option<AssetBlobReader>, AwgenDbError> {
+        let query = format!(
+            "SELECT rowid, ifnull(length({0}), 0) AS size FROM assets WHERE uuid = :uuid",
+            column.name()
+        );
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":uuid", asset_id))?;
+
+        let row = self.query_row(&mut statement, |row| {
+            Ok((row.read::<i64, _>("rowid")?, row.read::<i64, _>("size")?))
+        })?;
+        let Some((rowid, size)) = row else {
+            return Ok(None);
+        };
+
+        if size == 0 {
+            return Ok(Some(AssetBlobReader::empty()));
         }
+
+        let reader = AssetBlobReader::open(self.connection.clone(), self.busy, column, rowid)?;
+        Ok(Some(reader))
     }
 
-    /// Retrieves the preview data for a specific asset by its ID.
-    pub(crate) fn get_asset_preview(
+    /// Duplicates an asset, copying its data, preview and metadata into a new
+    /// asset record with a new ID.
+    ///
+    /// The copy is placed in the same module, with a pathname that does not
+    /// collide with any other asset in that module, such as `rock (2).png` for
+    /// a copy of `rock.png`.
+    ///
+    /// Returns the ID of the new asset, or `None` if the original asset does
+    /// not exist.
+    pub(crate) fn duplicate_asset(
         &self,
         asset_id: AssetRecordID,
-    ) -> Result<Option<Vec<u8>>, AwgenDbError> {
-        let query = "SELECT preview FROM assets WHERE uuid = :uuid";
+    ) -> Result<Option<AssetRecordID>, AwgenDbError> {
+        self.check_writable()?;
+
+        let Some(record) = self.get_asset(asset_id)? else {
+            return Ok(None);
+        };
+
+        let existing = self
+            .get_assets()?
+            .into_iter()
+            .filter(|asset| asset.module == record.module)
+            .map(|asset| asset.pathname)
+            .collect::<HashSet<_>>();
+
+        let new_id = AssetRecordID::new();
+        let pathname = copy_pathname(&record.pathname, &existing);
+
+        let query = r#"
+            INSERT INTO assets (uuid, type, path, module, created, last_modified, data, preview)
+            SELECT :new_uuid, type, :path, module, :now, :now, data, preview
+            FROM assets
+            WHERE uuid = :uuid;
+        "#;
+
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("System time set before UNIX EPOCH!")
+            .as_millis() as i64;
 
         let mut statement = self.connection.prepare(query)?;
+        statement.bind((":new_uuid", new_id))?;
         statement.bind((":uuid", asset_id))?;
+        statement.bind((":path", pathname.display().to_string().as_str()))?;
+        statement.bind((":now", now))?;
+        self.execute(&mut statement)?;
 
-        if let Ok(sqlite::State::Row) = statement.next() {
-            let preview = statement.read::<Vec<u8>, _>("preview")?;
-            if preview.is_empty() {
-                Ok(None)
-            } else {
-                Ok(Some(preview))
-            }
-        } else {
-            Ok(None)
+        self.send_event(AssetSourceEvent::AddedAsset(path_buf(
+            new_id,
+            false,
+            &record.asset_type,
+        )));
+
+        // The preview is only reported if one was copied, as there is no
+        // preview image to load otherwise.
+        if self.has_preview(new_id)? {
+            self.send_event(AssetSourceEvent::AddedAsset(path_buf(
+                new_id,
+                true,
+                Image::type_name(),
+            )));
+        }
+
+        Ok(Some(new_id))
+    }
+
+    /// Checks whether the asset with the given ID has a preview image.
+    fn has_preview(&self, asset_id: AssetRecordID) -> Result<bool, AwgenDbError> {
+        let query = r#"
+            SELECT 1 FROM assets
+            WHERE uuid = :uuid AND preview IS NOT NULL AND length(preview) > 0;
+        "#;
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":uuid", asset_id))?;
+        Ok(self.query_row(&mut statement, |_| Ok(()))?.is_some())
+    }
+
+    /// Changes the pathname of an asset, updating its `last_modified`
+    /// timestamp.
+    ///
+    /// Returns [`AwgenDbError::PathnameTaken`] if another asset in the same
+    /// module already has the new pathname.
+    ///
+    /// Returns `false` if the asset does not exist.
+    pub(crate) fn rename_asset(
+        &self,
+        asset_id: AssetRecordID,
+        pathname: &Path,
+    ) -> Result<bool, AwgenDbError> {
+        self.relocate_asset(asset_id, |record| (pathname.to_path_buf(), record.module))
+    }
+
+    /// Moves an asset into another module, updating its `last_modified`
+    /// timestamp.
+    ///
+    /// Note that this method does not check that the module exists; it is the
+    /// caller's responsibility to ensure the module has been created.
+    ///
+    /// Returns [`AwgenDbError::PathnameTaken`] if an asset in the target
+    /// module already has the pathname of the moved asset.
+    ///
+    /// Returns `false` if the asset does not exist.
+    pub(crate) fn move_asset(
+        &self,
+        asset_id: AssetRecordID,
+        module: AssetModuleID,
+    ) -> Result<bool, AwgenDbError> {
+        self.relocate_asset(asset_id, |record| (record.pathname.clone(), module))
+    }
+
+    /// Changes where an asset is listed, to the pathname and module returned
+    /// by `target` for its current record.
+    ///
+    /// Asset source paths are derived from the asset ID, not from its pathname
+    /// or module, so the source path of the asset stays the same. Watchers are
+    /// sent a modified event for it instead, so that loaded handles reload the
+    /// updated record.
+    fn relocate_asset(
+        &self,
+        asset_id: AssetRecordID,
+        target: impl FnOnce(&ErasedAssetRecord) -> (PathBuf, AssetModuleID),
+    ) -> Result<bool, AwgenDbError> {
+        self.check_writable()?;
+
+        let Some(record) = self.get_asset(asset_id)? else {
+            return Ok(false);
+        };
+
+        let (pathname, module) = target(&record);
+        if self.pathname_taken(asset_id, module, &pathname)? {
+            return Err(AwgenDbError::PathnameTaken { pathname, module });
         }
+
+        let last_modified = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("System time set before UNIX EPOCH!")
+            .as_millis() as i64;
+
+        let query = r#"
+            UPDATE assets
+            SET path = :path,
+                module = :module,
+                last_modified = :last_modified
+            WHERE uuid = :uuid;
+        "#;
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":uuid", asset_id))?;
+        statement.bind((":path", pathname.display().to_string().as_str()))?;
+        statement.bind((":module", module))?;
+        statement.bind((":last_modified", last_modified))?;
+
+        self.execute(&mut statement)?;
+        self.send_event(AssetSourceEvent::ModifiedAsset(path_buf(
+            asset_id,
+            false,
+            &record.asset_type,
+        )));
+        self.send_event(AssetSourceEvent::RenamedAsset {
+            old: rename_path(asset_id, &record.asset_type, &record.pathname),
+            new: rename_path(asset_id, &record.asset_type, &pathname),
+        });
+
+        Ok(true)
+    }
+
+    /// Checks whether an asset other than the given one already has the given
+    /// pathname within the given module.
+    fn pathname_taken(
+        &self,
+        asset_id: AssetRecordID,
+        module: AssetModuleID,
+        pathname: &Path,
+    ) -> Result<bool, AwgenDbError> {
+        let query = r#"
+            SELECT 1 FROM assets
+            WHERE module = :module AND path = :path AND uuid != :uuid;
+        "#;
+
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":module", module))?;
+        statement.bind((":path", pathname.display().to_string().as_str()))?;
+        statement.bind((":uuid", asset_id))?;
+        Ok(self.query_row(&mut statement, |_| Ok(()))?.is_some())
     }
 
     /// Removes an asset record from the database by its ID.
     pub(crate) fn remove_asset(&self, asset_id: AssetRecordID) -> Result<(), AwgenDbError> {
+        self.check_writable()?;
+
         let Some(record) = self.get_asset(asset_id)? else {
             return Ok(());
         };
@@ -485,7 +1330,12 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
 
         let mut statement = self.connection.prepare(query)?;
         statement.bind((":uuid", asset_id))?;
-        while let sqlite::State::Row = statement.next()? {}
+        self.execute(&mut statement)?;
+
+        let usage_query = "DELETE FROM asset_usage WHERE uuid = :uuid";
+        let mut statement = self.connection.prepare(usage_query)?;
+        statement.bind((":uuid", asset_id))?;
+        self.execute(&mut statement)?;
 
         self.send_event(AssetSourceEvent::RemovedAsset(path_buf(
             asset_id,
@@ -502,40 +1352,382 @@ impl<Src: AssetDatabaseName> AssetDatabase<Src> {
     }
 }
 
+/// A filter that selects a subset of the asset records in an asset database.
+///
+/// Each field that is `None` lets every asset through, so the default filter
+/// selects every asset.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AssetFilter<'a> {
+    /// Only selects the assets of this module.
+    pub module: Option<AssetModuleID>,
+
+    /// Only selects the assets of this type.
+    pub asset_type: Option<&'a str>,
+
+    /// Only selects the assets whose pathname starts with this prefix, which
+    /// is matched case-sensitively.
+    pub path_prefix: Option<&'a str>,
+}
+
+impl AssetFilter<'_> {
+    /// Gets the `WHERE` clause that selects the assets passing this filter, or
+    /// an empty string if every asset passes it.
+    fn where_clause(&self) -> String {
+        let mut conditions = Vec::new();
+        if self.module.is_some() {
+            conditions.push("module = :module");
+        }
+        if self.asset_type.is_some() {
+            conditions.push("type = :type");
+        }
+        if self.path_prefix.is_some() {
+            conditions.push("path >= :path_start AND path < :path_end");
+        }
+
+        if conditions.is_empty() {
+            return String::new();
+        }
+
+        format!("WHERE {}", conditions.join(" AND "))
+    }
+
+    /// Binds the parameters of the [`AssetFilter::where_clause`] of this
+    /// filter to the given statement.
+    fn bind(&self, statement: &mut Statement) -> Result<(), AwgenDbError> {
+        if let Some(module) = self.module {
+            statement.bind((":module", module))?;
+        }
+
+        if let Some(asset_type) = self.asset_type {
+            statement.bind((":type", asset_type))?;
+        }
+
+        // Every pathname that starts with the prefix sorts between the prefix
+        // itself and the prefix followed by the highest code point. Unlike
+        // LIKE, this range can be looked up in the path indices.
+        if let Some(prefix) = self.path_prefix {
+            let end = format!("{prefix}{}", char::MAX);
+            statement.bind((":path_start", prefix))?;
+            statement.bind((":path_end", end.as_str()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The source of the data blob written when inserting an asset.
+enum AssetDataSource<'a> {
+    /// The data is written directly from a byte slice.
+    Bytes(&'a [u8]),
+
+    /// A zero-filled blob of the given size is allocated, to be filled in
+    /// afterwards using incremental blob I/O.
+    Zeroed(usize),
+}
+
 /// An error that can occur while interacting with the database.
 #[derive(Debug, thiserror::Error)]
-#[error("Failed to connect with database: {0}")]
-pub struct AwgenDbError(#[from] pub sqlite::Error);
+pub enum AwgenDbError {
+    /// An error returned by SQLite.
+    #[error("Failed to connect with database: {0}")]
+    Sqlite(#[from] sqlite::Error),
+
+    /// A write was attempted on a database opened in read-only mode.
+    #[error("The database is open in read-only mode")]
+    ReadOnly,
+
+    /// The database stayed locked by another connection through every retry.
+    #[error("The database is busy, gave up after {attempts} attempts")]
+    Busy {
+        /// The number of times the statement was attempted.
+        attempts: u32,
+    },
+
+    /// An asset was renamed or moved onto the pathname of another asset in
+    /// the same module.
+    #[error("An asset named {} already exists in module {module}", .pathname.display())]
+    PathnameTaken {
+        /// The pathname that is already taken.
+        pathname: PathBuf,
+
+        /// The module that the pathname is taken in.
+        module: AssetModuleID,
+    },
+}
+
+impl AwgenDbError {
+    /// Converts this error into an I/O error.
+    fn into_io_error(self) -> std::io::Error {
+        match self {
+            AwgenDbError::Sqlite(err) => std::io::Error::new(
+                std::io::ErrorKind::ConnectionAborted,
+                format!(
+                    "Error {}: {}",
+                    err.code.unwrap_or(-1),
+                    err.message.unwrap_or("Unknown error".into())
+                ),
+            ),
+            AwgenDbError::ReadOnly => {
+                std::io::Error::new(std::io::ErrorKind::PermissionDenied, self.to_string())
+            }
+            AwgenDbError::Busy { .. } => {
+                std::io::Error::new(std::io::ErrorKind::ResourceBusy, self.to_string())
+            }
+            AwgenDbError::PathnameTaken { .. } => {
+                std::io::Error::new(std::io::ErrorKind::AlreadyExists, self.to_string())
+            }
+        }
+    }
+}
 
 impl From<AwgenDbError> for AssetReaderError {
     fn from(value: AwgenDbError) -> Self {
-        AssetReaderError::Io(Arc::new(std::io::Error::new(
-            std::io::ErrorKind::ConnectionAborted,
-            format!(
-                "Error {}: {}",
-                value.0.code.unwrap_or(-1),
-                value.0.message.unwrap_or("Unknown error".into())
-            ),
-        )))
+        AssetReaderError::Io(Arc::new(value.into_io_error()))
     }
 }
 
 impl From<AwgenDbError> for AssetWriterError {
     fn from(value: AwgenDbError) -> Self {
-        AssetWriterError::Io(std::io::Error::new(
-            std::io::ErrorKind::ConnectionAborted,
-            format!(
-                "Error {}: {}",
-                value.0.code.unwrap_or(-1),
-                value.0.message.unwrap_or("Unknown error".into())
-            ),
-        ))
+        AssetWriterError::Io(value.into_io_error())
     }
 }
 
+/// Sets how long SQLite waits for locks held by other connections before
+/// reporting the database as busy.
+///
+/// SQLite never waits longer than the total wait of the busy settings.
+fn set_busy_timeout(connection: &Connection, busy: &BusySettings) -> Result<(), AwgenDbError> {
+    connection.execute(format!(
+        "PRAGMA busy_timeout = {}",
+        busy.timeout.min(busy.max_wait).as_millis()
+    ))?;
+    Ok(())
+}
+
+/// Switches the database to write-ahead logging, so that reading connections
+/// are not blocked while another connection writes, and writers only wait for
+/// each other.
+///
+/// The journal mode is stored in the database file, so connections opened
+/// later, including read-only ones, use it as well. In-memory databases keep
+/// their own journal mode.
+fn enable_wal(connection: &Connection) -> Result<(), sqlite::Error> {
+    connection.execute("PRAGMA journal_mode = WAL")
+}
+
+/// Creates the tables and indices of the asset database, if they do not exist
+/// yet.
+fn create_schema(connection: &Connection) -> Result<(), sqlite::Error> {
+    connection.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS modules (
+            uuid TEXT PRIMARY KEY,
+            name TEXT NOT NULL DEFAULT 'Unnamed'
+        );
+        CREATE TABLE IF NOT EXISTS assets (
+            uuid TEXT PRIMARY KEY,
+            type TEXT NOT NULL,
+            path TEXT NOT NULL,
+            module TEXT NOT NULL,
+            data BLOB,
+            preview BLOB,
+            created INTEGER NOT NULL,
+            last_modified INTEGER NOT NULL,
+            FOREIGN KEY (module) REFERENCES modules (uuid)
+        );
+
+        -- Time-ordered IDs are already sorted by creation time in the
+        -- primary key index. Assets with older, random IDs are sorted by
+        -- this partial index instead.
+        CREATE INDEX IF NOT EXISTS assets_legacy_created
+            ON assets (created)
+            WHERE substr(uuid, 15, 1) != '7';
+
+        -- Filtered asset lists are sorted by path, so each filter has an
+        -- index that keeps its matches in path order.
+        CREATE INDEX IF NOT EXISTS assets_path
+            ON assets (path);
+        CREATE INDEX IF NOT EXISTS assets_type_path
+            ON assets (type, path);
+        CREATE INDEX IF NOT EXISTS assets_module_path
+            ON assets (module, path);
+
+        CREATE TABLE IF NOT EXISTS asset_usage (
+            uuid TEXT PRIMARY KEY,
+            use_count INTEGER NOT NULL DEFAULT 0,
+            last_used INTEGER NOT NULL DEFAULT 0,
+            favorite INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS asset_usage_last_used
+            ON asset_usage (last_used);
+
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// Creates the full-text search index over the pathnames and module names of
+/// the asset records, if it does not exist yet.
+///
+/// The index is kept up to date by triggers, and is filled from the existing
+/// asset records when it is first created. This fails if SQLite was built
+/// without FTS5.
+fn create_search_index(connection: &Connection) -> Result<(), sqlite::Error> {
+    connection.execute(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS assets_fts USING fts5 (
+            uuid UNINDEXED,
+            path,
+            module_name
+        );
+
+        CREATE TRIGGER IF NOT EXISTS assets_fts_insert AFTER INSERT ON assets
+        BEGIN
+            INSERT INTO assets_fts (uuid, path, module_name)
+            VALUES (
+                new.uuid,
+                new.path,
+                (SELECT name FROM modules WHERE uuid = new.module)
+            );
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS assets_fts_update AFTER UPDATE OF path, module ON assets
+        BEGIN
+            UPDATE assets_fts
+            SET path = new.path,
+                module_name = (SELECT name FROM modules WHERE uuid = new.module)
+            WHERE uuid = old.uuid;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS assets_fts_delete AFTER DELETE ON assets
+        BEGIN
+            DELETE FROM assets_fts WHERE uuid = old.uuid;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS assets_fts_module AFTER UPDATE OF name ON modules
+        BEGIN
+            UPDATE assets_fts
+            SET module_name = new.name
+            WHERE uuid IN (SELECT uuid FROM assets WHERE module = new.uuid);
+        END;
+
+        INSERT INTO assets_fts (uuid, path, module_name)
+        SELECT a.uuid, a.path, m.name
+        FROM assets a
+        LEFT JOIN modules m ON m.uuid = a.module
+        WHERE NOT EXISTS (SELECT 1 FROM assets_fts);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// Returns true if the database has a full-text search index over the asset
+/// records.
+fn has_search_index(connection: &Connection) -> Result<bool, sqlite::Error> {
+    let query = "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'assets_fts'";
+    let mut statement = connection.prepare(query)?;
+    Ok(statement.next()? == sqlite::State::Row)
+}
+
+/// Reads the asset module in the current row of the given statement, or
+/// `None` if its ID is invalid.
+///
+/// The statement must select the `uuid` and `name` columns.
+fn read_module_row(statement: &Statement) -> Result<Option<AssetModule>, sqlite::Error> {
+    let uuid = statement.read::<String, _>("uuid")?;
+    let name = statement.read::<String, _>("name")?;
+
+    let Some(id) = AssetModuleID::from_string(&uuid) else {
+        error!("Invalid AssetModuleID in asset database: {}", uuid);
+        return Ok(None);
+    };
+
+    Ok(Some(AssetModule { id, name }))
+}
+
+/// Reads the asset type and asset count in the current row of the given
+/// statement.
+///
+/// The statement must select the `type` and `count` columns.
+fn read_type_count_row(statement: &Statement) -> Result<Option<(String, usize)>, sqlite::Error> {
+    let asset_type = statement.read::<String, _>("type")?;
+    let count = statement.read::<i64, _>("count")?;
+    Ok(Some((asset_type, count as usize)))
+}
+
+/// Reads the asset record in the current row of the given statement, or
+/// `None` if its IDs are invalid.
+///
+/// The statement must select the `uuid`, `type`, `path`, `module`, `created`
+/// and `last_modified` columns.
+fn read_asset_row(statement: &Statement) -> Result<Option<ErasedAssetRecord>, sqlite::Error> {
+    let uuid = statement.read::<String, _>("uuid")?;
+    let asset_type = statement.read::<String, _>("type")?;
+    let path = statement.read::<String, _>("path")?;
+    let module_uuid = statement.read::<String, _>("module")?;
+    let created = statement.read::<i64, _>("created")?;
+    let last_modified = statement.read::<i64, _>("last_modified")?;
+
+    let Some(id) = AssetRecordID::from_string(&uuid) else {
+        error!("Invalid AssetRecordID in asset database: {}", uuid);
+        return Ok(None);
+    };
+
+    let Some(module) = AssetModuleID::from_string(&module_uuid) else {
+        error!("Invalid AssetModuleID in asset database: {}", module_uuid);
+        return Ok(None);
+    };
+
+    Ok(Some(ErasedAssetRecord {
+        id,
+        asset_type,
+        pathname: PathBuf::from(path),
+        module,
+        created,
+        last_modified,
+    }))
+}
+
+/// Generates a pathname for a copy of an asset that does not collide with any
+/// of the given existing pathnames.
+///
+/// A number is appended to the file stem, such that `rock.png` becomes
+/// `rock (2).png`. If the original already ends in a number, such as
+/// `rock (2).png`, counting continues from that number instead.
+fn copy_pathname(pathname: &Path, existing: &HashSet<PathBuf>) -> PathBuf {
+    let stem = pathname
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = pathname
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    let (base, start) = match stem
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once(" ("))
+        .and_then(|(base, num)| Some((base, num.parse::<u32>().ok()?)))
+    {
+        Some((base, num)) => (base.to_string(), num + 1),
+        None => (stem, 2),
+    };
+
+    (start ..)
+        .map(|num| pathname.with_file_name(format!("{} ({}){}", base, num, extension)))
+        .find(|candidate| !existing.contains(candidate))
+        .expect("Ran out of copy numbers")
+}
+
 /// Generates a path buffer for the asset data or preview based on the asset ID
 /// and whether it's a preview or not.
-fn path_buf(id: AssetRecordID, is_preview: bool, asset_type: &str) -> PathBuf {
+pub(crate) fn path_buf(id: AssetRecordID, is_preview: bool, asset_type: &str) -> PathBuf {
     let format = match is_preview {
         true => "preview",
         false => "data",
@@ -543,6 +1735,15 @@ fn path_buf(id: AssetRecordID, is_preview: bool, asset_type: &str) -> PathBuf {
     PathBuf::from(format!("{}.{}.{}", id, format, asset_type))
 }
 
+/// Generates the path that a rename event uses for the asset with the given ID
+/// and type, which is its pathname nested under the path of its data.
+///
+/// The asset paths seen by Bevy do not change when an asset is renamed, so
+/// this is how rename events carry the old and new pathnames of the asset.
+pub(crate) fn rename_path(id: AssetRecordID, asset_type: &str, pathname: &Path) -> PathBuf {
+    path_buf(id, false, asset_type).join(pathname)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -572,6 +1773,16 @@ mod tests {
         }
     }
 
+    fn read_preview(db: &AssetDatabase<TestDatabase>, asset_id: AssetRecordID) -> Vec<u8> {
+        let mut preview = Vec::new();
+        db.open_asset_blob(asset_id, BlobColumn::Preview)
+            .unwrap()
+            .unwrap()
+            .read_to_end(&mut preview)
+            .unwrap();
+        preview
+    }
+
     #[test]
     fn test_database_connection() {
         let db = AssetDatabase::<TestDatabase>::new(":memory:");
@@ -687,8 +1898,545 @@ mod tests {
         let fetched_data = db.get_asset_data(asset_id).unwrap().unwrap();
         assert_eq!(fetched_data, data);
 
-        let fetched_preview = db.get_asset_preview(asset_id).unwrap().unwrap();
-        assert_eq!(fetched_preview, preview);
+        assert_eq!(read_preview(&db, asset_id), preview);
+    }
+
+    #[test]
+    fn streamed_data() {
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+
+        let asset = asset();
+        let data = (0 .. 200_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        db.insert_asset_from_reader(&asset, data.len(), data.as_slice())
+            .unwrap();
+
+        let mut reader = db
+            .open_asset_blob(asset.id, BlobColumn::Data)
+            .unwrap()
+            .unwrap();
+        assert_eq!(reader.len(), data.len());
+
+        let mut fetched_data = Vec::new();
+        reader.read_to_end(&mut fetched_data).unwrap();
+        assert_eq!(fetched_data, data);
+
+        let preview = db
+            .open_asset_blob(asset.id, BlobColumn::Preview)
+            .unwrap()
+            .unwrap();
+        assert!(preview.is_empty());
+    }
+
+    #[test]
+    fn read_only_rejects_writes() {
+        let name = format!("awgen_read_only_{}.db", AssetRecordID::new());
+        let path = std::env::temp_dir().join(name);
+        AssetDatabase::<TestDatabase>::new(path.clone()).unwrap();
+
+        let db = AssetDatabase::<TestDatabase>::new_read_only(path.clone()).unwrap();
+        assert!(db.is_read_only());
+        assert!(db.get_assets().unwrap().is_empty());
+        assert!(matches!(
+            db.insert_module(&module()),
+            Err(AwgenDbError::ReadOnly)
+        ));
+
+        drop(db);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn busy_writes_give_up_after_retries() {
+        let name = format!("awgen_busy_{}.db", AssetRecordID::new());
+        let path = std::env::temp_dir().join(name);
+        let busy = BusySettings {
+            timeout: std::time::Duration::ZERO,
+            max_retries: 2,
+            retry_delay: std::time::Duration::from_millis(1),
+            max_wait: std::time::Duration::from_secs(5),
+        };
+
+        let db = AssetDatabase::<TestDatabase>::new_with_busy_settings(&path, busy).unwrap();
+        let lock = sqlite::open(&path).unwrap();
+        lock.execute("BEGIN EXCLUSIVE").unwrap();
+        assert!(matches!(
+            db.insert_module(&module()),
+            Err(AwgenDbError::Busy { attempts: 3 })
+        ));
+
+        lock.execute("COMMIT").unwrap();
+        db.insert_module(&module()).unwrap();
+
+        drop(lock);
+        drop(db);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn busy_writes_give_up_after_the_total_wait() {
+        let name = format!("awgen_busy_wait_{}.db", AssetRecordID::new());
+        let path = std::env::temp_dir().join(name);
+        let busy = BusySettings {
+            timeout: std::time::Duration::ZERO,
+            max_retries: u32::MAX,
+            retry_delay: std::time::Duration::from_millis(1),
+            max_wait: std::time::Duration::from_millis(50),
+        };
+
+        let db = AssetDatabase::<TestDatabase>::new_with_busy_settings(&path, busy).unwrap();
+        let lock = sqlite::open(&path).unwrap();
+        lock.execute("BEGIN EXCLUSIVE").unwrap();
+
+        let start = std::time::Instant::now();
+        assert!(matches!(
+            db.insert_module(&module()),
+            Err(AwgenDbError::Busy { .. })
+        ));
+        assert!(start.elapsed() < busy.max_wait * 2);
+
+        lock.execute("COMMIT").unwrap();
+        drop(lock);
+        drop(db);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn file_databases_use_write_ahead_logging() {
+        let name = format!("awgen_wal_{}.db", AssetRecordID::new());
+        let path = std::env::temp_dir().join(name);
+
+        let db = AssetDatabase::<TestDatabase>::new(&path).unwrap();
+        let other = sqlite::open(&path).unwrap();
+        let mut statement = other.prepare("PRAGMA journal_mode").unwrap();
+        statement.next().unwrap();
+        assert_eq!(statement.read::<String, _>(0).unwrap(), "wal");
+        drop(statement);
+
+        // Readers are not blocked while another connection writes.
+        other.execute("BEGIN IMMEDIATE").unwrap();
+        other
+            .execute("INSERT INTO settings (key, value) VALUES ('a', 'b')")
+            .unwrap();
+        assert_eq!(db.get_setting("a").unwrap(), None);
+        other.execute("COMMIT").unwrap();
+        assert_eq!(db.get_setting("a").unwrap(), Some("b".to_string()));
+
+        drop(other);
+        drop(db);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn find_by_short_id() {
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+
+        let asset = asset();
+        db.insert_asset(&asset, &[1, 2, 3]).unwrap();
+
+        let short = asset.id.short().to_string();
+        assert_eq!(short.len(), crate::id::SHORT_ID_LEN);
+        assert!(short.parse::<AssetRecordID>().is_err());
+
+        let prefix = short.parse::<IdPrefix>().unwrap();
+        assert_eq!(db.find_asset_ids(&prefix).unwrap(), vec![asset.id]);
+
+        let upper = short.to_uppercase().parse::<IdPrefix>().unwrap();
+        assert_eq!(db.find_asset_ids(&upper).unwrap(), vec![asset.id]);
+
+        // Only the start of an ID is matched, like `IdPrefix::matches`.
+        let simple = asset.id.to_string().replace('-', "");
+        let suffix = simple[simple.len() - 8 ..].parse::<IdPrefix>().unwrap();
+        assert!(!simple.starts_with(&suffix.to_string()));
+        assert!(!asset.id.matches(&suffix));
+        assert!(db.find_asset_ids(&suffix).unwrap().is_empty());
+
+        let full = asset.id.to_string().parse::<AssetRecordID>().unwrap();
+        assert_eq!(full, asset.id);
+    }
+
+    #[test]
+    fn recent_assets_newest_first() {
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+
+        let legacy = AssetRecord {
+            id: AssetRecordID::from_string(uuid::Uuid::new_v4().to_string()).unwrap(),
+            ..asset()
+        };
+        db.insert_asset(&legacy, &[1, 2, 3]).unwrap();
+
+        let assets = (0 .. 5).map(|_| asset()).collect::<Vec<_>>();
+        for asset in assets.iter() {
+            assert!(asset.id.is_time_ordered());
+            db.insert_asset(asset, &[1, 2, 3]).unwrap();
+        }
+
+        let recent = db.get_recent_assets(3).unwrap();
+        let ids = recent.iter().map(|asset| asset.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![assets[4].id, assets[3].id, assets[2].id]);
+
+        let all = db.get_recent_assets(10).unwrap();
+        assert_eq!(all.len(), 6);
+        assert_eq!(all.last().unwrap().id, legacy.id);
+    }
+
+    #[test]
+    fn duplicate_asset_names() {
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+
+        let asset = AssetRecord {
+            pathname: PathBuf::from("textures/rock.png"),
+            ..asset()
+        };
+        db.insert_asset(&asset, &[1, 2, 3]).unwrap();
+        db.set_asset_preview(asset.id, Some(&[4, 5, 6])).unwrap();
+
+        let copy1 = db.duplicate_asset(asset.id).unwrap().unwrap();
+        let copy2 = db.duplicate_asset(asset.id).unwrap().unwrap();
+        let copy3 = db.duplicate_asset(copy1).unwrap().unwrap();
+
+        let path = |id| db.get_asset(id).unwrap().unwrap().pathname;
+        assert_eq!(path(copy1), PathBuf::from("textures/rock (2).png"));
+        assert_eq!(path(copy2), PathBuf::from("textures/rock (3).png"));
+        assert_eq!(path(copy3), PathBuf::from("textures/rock (4).png"));
+
+        assert_eq!(db.get_asset_data(copy1).unwrap().unwrap(), vec![1, 2, 3]);
+        assert_eq!(read_preview(&db, copy1), vec![4, 5, 6]);
+        assert_eq!(db.duplicate_asset(AssetRecordID::new()).unwrap(), None);
+    }
+
+    #[test]
+    fn duplicate_reports_copied_previews_only() {
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+        let settings = AssetWatcherSettings {
+            debounce: std::time::Duration::ZERO,
+            max_delay: std::time::Duration::ZERO,
+            external_poll: None,
+        };
+
+        let plain = asset();
+        db.insert_asset(&plain, &[1, 2, 3]).unwrap();
+        let with_preview = asset();
+        db.insert_asset(&with_preview, &[1, 2, 3]).unwrap();
+        db.set_asset_preview(with_preview.id, Some(&[4, 5, 6]))
+            .unwrap();
+        db.flush_events(&settings);
+
+        let plain_copy = db.duplicate_asset(plain.id).unwrap().unwrap();
+        let preview_copy = db.duplicate_asset(with_preview.id).unwrap().unwrap();
+
+        let events = db.flush_events(&settings);
+        let preview = |id| AssetSourceEvent::AddedAsset(path_buf(id, true, Image::type_name()));
+        assert!(!events.contains(&preview(plain_copy)));
+        assert!(events.contains(&preview(preview_copy)));
+    }
+
+    #[test]
+    fn rename_and_move_asset() {
+        use crate::watcher::{AssetRecordChange, AssetRecordChanged};
+
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+        let settings = AssetWatcherSettings {
+            debounce: std::time::Duration::ZERO,
+            max_delay: std::time::Duration::ZERO,
+            external_poll: None,
+        };
+
+        let asset = asset();
+        db.insert_asset(&asset, &[1, 2, 3]).unwrap();
+        db.flush_events(&settings);
+
+        let target = module();
+        db.insert_module(&target).unwrap();
+
+        assert!(db.rename_asset(asset.id, Path::new("stone.png")).unwrap());
+        assert!(db.move_asset(asset.id, target.id).unwrap());
+
+        let record = db.get_asset(asset.id).unwrap().unwrap();
+        assert_eq!(record.pathname, PathBuf::from("stone.png"));
+        assert_eq!(record.module, target.id);
+        assert!(record.last_modified > asset.last_modified);
+        assert_eq!(db.get_asset_data(asset.id).unwrap().unwrap(), vec![1, 2, 3]);
+
+        let changes = db
+            .flush_events(&settings)
+            .iter()
+            .filter_map(AssetRecordChanged::<TestDatabase>::from_event)
+            .collect::<Vec<_>>();
+        assert_eq!(changes.len(), 3);
+        assert!(changes.iter().all(|change| change.id == asset.id));
+        assert_eq!(changes[0].change, AssetRecordChange::Modified);
+        assert_eq!(
+            changes[1].change,
+            AssetRecordChange::Renamed {
+                old: PathBuf::from("test/asset.png"),
+                new: PathBuf::from("stone.png"),
+            }
+        );
+        assert_eq!(
+            changes[2].change,
+            AssetRecordChange::Renamed {
+                old: PathBuf::from("stone.png"),
+                new: PathBuf::from("stone.png"),
+            }
+        );
+
+        let missing = AssetRecordID::new();
+        assert!(!db.rename_asset(missing, Path::new("stone.png")).unwrap());
+        assert!(!db.move_asset(missing, target.id).unwrap());
+    }
+
+    #[test]
+    fn relocating_onto_a_taken_pathname_fails() {
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+
+        let stone = AssetRecord {
+            pathname: PathBuf::from("stone.png"),
+            ..asset()
+        };
+        let dirt = AssetRecord {
+            pathname: PathBuf::from("dirt.png"),
+            module: stone.module,
+            ..asset()
+        };
+        db.insert_asset(&stone, &[1]).unwrap();
+        db.insert_asset(&dirt, &[2]).unwrap();
+
+        let err = db
+            .rename_asset(dirt.id, Path::new("stone.png"))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AwgenDbError::PathnameTaken { pathname, module }
+                if pathname == Path::new("stone.png") && module == stone.module
+        ));
+        assert_eq!(
+            db.get_asset(dirt.id).unwrap().unwrap().pathname,
+            PathBuf::from("dirt.png")
+        );
+
+        // Renaming an asset to its own pathname is not a collision.
+        assert!(db.rename_asset(dirt.id, Path::new("dirt.png")).unwrap());
+
+        let target = module();
+        db.insert_module(&target).unwrap();
+        let other = AssetRecord {
+            pathname: PathBuf::from("stone.png"),
+            module: target.id,
+            ..asset()
+        };
+        db.insert_asset(&other, &[3]).unwrap();
+
+        let err = db.move_asset(stone.id, target.id).unwrap_err();
+        assert!(matches!(
+            err,
+            AwgenDbError::PathnameTaken { module, .. } if module == target.id
+        ));
+        assert_eq!(
+            db.get_asset(stone.id).unwrap().unwrap().module,
+            stone.module
+        );
+
+        assert!(db.move_asset(dirt.id, target.id).unwrap());
+    }
+
+    #[test]
+    fn assets_by_type() {
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+
+        let images = (0 .. 3).map(|_| asset()).collect::<Vec<_>>();
+        for image in &images {
+            db.insert_asset(image, &[1, 2, 3]).unwrap();
+        }
+
+        let query = "INSERT INTO assets (uuid, type, path, module, created, last_modified) \
+                     VALUES (:uuid, 'other', 'other.bin', :module, 0, 0)";
+        let mut statement = db.connection.prepare(query).unwrap();
+        statement.bind((":uuid", AssetRecordID::new())).unwrap();
+        statement.bind((":module", AssetModuleID::new())).unwrap();
+        while let sqlite::State::Row = statement.next().unwrap() {}
+
+        let images = db.get_assets_of_type(Image::type_name()).unwrap();
+        assert_eq!(images.len(), 3);
+        assert!(images.iter().all(|a| a.asset_type == Image::type_name()));
+
+        let counts = db.count_assets_by_type().unwrap();
+        assert_eq!(
+            counts,
+            vec![
+                (Image::type_name().to_string(), 3),
+                ("other".to_string(), 1)
+            ]
+        );
+
+        db.set_asset_preview(images[0].id, Some(&[4, 5, 6]))
+            .unwrap();
+        let missing = db.count_assets_missing_preview_by_type().unwrap();
+        assert_eq!(
+            missing,
+            vec![
+                (Image::type_name().to_string(), 2),
+                ("other".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn filtered_assets() {
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+        let module1 = AssetModuleID::new();
+        let module2 = AssetModuleID::new();
+
+        for (module, path) in [
+            (module1, "textures/b.png"),
+            (module1, "textures/a.png"),
+            (module1, "models/a.png"),
+            (module2, "textures/c.png"),
+            (module2, "texture.png"),
+        ] {
+            let asset = AssetRecord {
+                module,
+                pathname: PathBuf::from(path),
+                ..asset()
+            };
+            db.insert_asset(&asset, &[1, 2, 3]).unwrap();
+        }
+
+        let paths = |filter: &AssetFilter, limit: usize, offset: usize| {
+            db.get_assets_filtered(filter, limit, offset)
+                .unwrap()
+                .into_iter()
+                .map(|asset| asset.pathname.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+
+        let all = AssetFilter::default();
+        assert_eq!(db.count_assets_filtered(&all).unwrap(), 5);
+        assert_eq!(
+            paths(&all, usize::MAX, 0),
+            vec![
+                "models/a.png",
+                "texture.png",
+                "textures/a.png",
+                "textures/b.png",
+                "textures/c.png"
+            ]
+        );
+        assert_eq!(paths(&all, 2, 1), vec!["texture.png", "textures/a.png"]);
+        assert_eq!(paths(&all, 2, 4), vec!["textures/c.png"]);
+        assert!(paths(&all, 2, 5).is_empty());
+
+        let textures = AssetFilter {
+            path_prefix: Some("textures/"),
+            ..default()
+        };
+        assert_eq!(db.count_assets_filtered(&textures).unwrap(), 3);
+        assert_eq!(
+            paths(&textures, 10, 0),
+            vec!["textures/a.png", "textures/b.png", "textures/c.png"]
+        );
+
+        let module_textures = AssetFilter {
+            module: Some(module1),
+            asset_type: Some(Image::type_name()),
+            path_prefix: Some("textures/"),
+        };
+        assert_eq!(
+            paths(&module_textures, 10, 0),
+            vec!["textures/a.png", "textures/b.png"]
+        );
+
+        let other_type = AssetFilter {
+            asset_type: Some("other"),
+            ..default()
+        };
+        assert_eq!(db.count_assets_filtered(&other_type).unwrap(), 0);
+        assert!(paths(&other_type, 10, 0).is_empty());
+    }
+
+    #[test]
+    fn search_assets() {
+        let mut db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+        let nature = AssetModule {
+            id: AssetModuleID::new(),
+            name: "Nature Pack".into(),
+        };
+        let town = AssetModule {
+            id: AssetModuleID::new(),
+            name: "Town Pack".into(),
+        };
+        db.insert_module(&nature).unwrap();
+        db.insert_module(&town).unwrap();
+
+        let mut ids = Vec::new();
+        for (module, path) in [
+            (nature.id, "textures/rock_01.png"),
+            (nature.id, "textures/grass.png"),
+            (town.id, "textures/rocky_road.png"),
+            (town.id, "models/nature_statue.png"),
+        ] {
+            let asset = AssetRecord {
+                module,
+                pathname: PathBuf::from(path),
+                ..asset()
+            };
+            db.insert_asset(&asset, &[1, 2, 3]).unwrap();
+            ids.push(asset.id);
+        }
+
+        let search = |db: &AssetDatabase<TestDatabase>, query: &str| {
+            let mut paths = db
+                .search_assets(query, 10)
+                .unwrap()
+                .into_iter()
+                .map(|asset| asset.pathname.to_string_lossy().into_owned())
+                .collect::<Vec<_>>();
+            paths.sort();
+            paths
+        };
+
+        for indexed in [true, false] {
+            db.search_index = indexed;
+
+            assert_eq!(
+                search(&db, "rock"),
+                vec!["textures/rock_01.png", "textures/rocky_road.png"]
+            );
+            assert_eq!(search(&db, "ROCK town"), vec!["textures/rocky_road.png"]);
+            assert_eq!(
+                search(&db, "nature"),
+                vec![
+                    "models/nature_statue.png",
+                    "textures/grass.png",
+                    "textures/rock_01.png"
+                ]
+            );
+            assert!(search(&db, "water").is_empty());
+            assert!(search(&db, " / ").is_empty());
+        }
+
+        db.search_index = true;
+
+        // Matches in the pathname rank above matches in the module name.
+        let ranked = db.search_assets("nature", 1).unwrap();
+        assert_eq!(
+            ranked[0].pathname,
+            PathBuf::from("models/nature_statue.png")
+        );
+
+        db.remove_asset(ids[0]).unwrap();
+        assert_eq!(search(&db, "rock"), vec!["textures/rocky_road.png"]);
+
+        let copy = db.duplicate_asset(ids[1]).unwrap().unwrap();
+        assert_eq!(
+            search(&db, "grass"),
+            vec!["textures/grass (2).png", "textures/grass.png"]
+        );
+        assert!(
+            db.search_assets("grass", 10)
+                .unwrap()
+                .iter()
+                .any(|asset| asset.id == copy)
+        );
     }
 
     #[test]
@@ -743,4 +2491,172 @@ mod tests {
         let assets = db.get_assets().unwrap();
         assert_eq!(assets.len(), 3);
     }
+
+    #[test]
+    fn recent_and_favorite_assets() {
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+
+        let first = asset();
+        let second = asset();
+        db.insert_asset(&first, &[1, 2, 3]).unwrap();
+        db.insert_asset(&second, &[4, 5, 6]).unwrap();
+
+        db.record_asset_use(second.id).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        db.record_asset_use(first.id).unwrap();
+        db.record_asset_use(first.id).unwrap();
+
+        let recent = db.get_recently_used_assets(10).unwrap();
+        let ids = recent.iter().map(|asset| asset.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![first.id, second.id]);
+        assert_eq!(db.get_asset_use_count(first.id).unwrap(), 2);
+
+        db.set_asset_favorite(second.id, true).unwrap();
+        assert!(db.is_asset_favorite(second.id).unwrap());
+        assert_eq!(db.get_asset_use_count(second.id).unwrap(), 1);
+
+        let favorites = db.get_favorite_assets().unwrap();
+        assert_eq!(favorites.len(), 1);
+        assert_eq!(favorites[0].id, second.id);
+
+        db.set_asset_favorite(second.id, false).unwrap();
+        assert!(db.get_favorite_assets().unwrap().is_empty());
+
+        db.remove_asset(first.id).unwrap();
+        assert_eq!(db.get_recently_used_assets(10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+        assert_eq!(db.get_setting("zoom").unwrap(), None);
+
+        db.set_setting("zoom", "small").unwrap();
+        db.set_setting("zoom", "large").unwrap();
+        assert_eq!(db.get_setting("zoom").unwrap().as_deref(), Some("large"));
+        assert_eq!(db.get_setting("other").unwrap(), None);
+    }
+
+    #[test]
+    fn backup_and_restore() {
+        let name = format!("awgen_backup_{}.db", AssetRecordID::new());
+        let path = std::env::temp_dir().join(name);
+
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+        let kept = asset();
+        db.insert_asset(&kept, &[1, 2, 3]).unwrap();
+        db.backup_to(&path).unwrap();
+
+        let added = asset();
+        db.insert_asset(&added, &[4, 5, 6]).unwrap();
+        db.remove_asset(kept.id).unwrap();
+
+        db.restore_from(&path).unwrap();
+        assert!(db.get_asset(kept.id).unwrap().is_some());
+        assert!(db.get_asset(added.id).unwrap().is_none());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn pending_events_keep_queue_order() {
+        let settings = AssetWatcherSettings {
+            debounce: std::time::Duration::ZERO,
+            max_delay: std::time::Duration::ZERO,
+            external_poll: None,
+        };
+
+        let paths = (0 .. 16)
+            .map(|i| PathBuf::from(format!("asset_{i}")))
+            .collect::<Vec<_>>();
+
+        let mut pending = PendingEvents::default();
+        for path in &paths {
+            pending.push(AssetSourceEvent::ModifiedAsset(path.clone()));
+        }
+        pending.push(AssetSourceEvent::ModifiedAsset(paths[0].clone()));
+
+        let events = pending.drain_ready(Instant::now(), &settings);
+        let expected = paths
+            .into_iter()
+            .map(AssetSourceEvent::ModifiedAsset)
+            .collect::<Vec<_>>();
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn external_changes() {
+        let name = format!("awgen_external_{}.db", AssetRecordID::new());
+        let path = std::env::temp_dir().join(name);
+        let settings = AssetWatcherSettings {
+            debounce: std::time::Duration::ZERO,
+            max_delay: std::time::Duration::ZERO,
+            external_poll: Some(std::time::Duration::ZERO),
+        };
+
+        let local = AssetDatabase::<TestDatabase>::new(&path).unwrap();
+        let remote = AssetDatabase::<TestDatabase>::new(&path).unwrap();
+        local.poll_external_changes(&settings).unwrap();
+
+        let asset = asset();
+        remote.insert_asset(&asset, &[1, 2, 3]).unwrap();
+        local.poll_external_changes(&settings).unwrap();
+
+        let events = local
+            .pending
+            .lock()
+            .unwrap()
+            .drain_ready(Instant::now(), &settings);
+        let data = path_buf(asset.id, false, Image::type_name());
+        assert!(events.contains(&AssetSourceEvent::AddedAsset(data.clone())));
+
+        remote.remove_asset(asset.id).unwrap();
+        local.poll_external_changes(&settings).unwrap();
+
+        let events = local
+            .pending
+            .lock()
+            .unwrap()
+            .drain_ready(Instant::now(), &settings);
+        assert!(events.contains(&AssetSourceEvent::RemovedAsset(data)));
+
+        drop(local);
+        drop(remote);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn record_changes() {
+        use crate::watcher::{AssetRecordChange, AssetRecordChanged};
+
+        let db = AssetDatabase::<TestDatabase>::new(":memory:").unwrap();
+        let settings = AssetWatcherSettings {
+            debounce: std::time::Duration::ZERO,
+            max_delay: std::time::Duration::ZERO,
+            external_poll: None,
+        };
+
+        let asset = asset();
+        db.insert_asset(&asset, &[1, 2, 3]).unwrap();
+
+        let changes = db
+            .flush_events(&settings)
+            .iter()
+            .filter_map(AssetRecordChanged::<TestDatabase>::from_event)
+            .collect::<Vec<_>>();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].id, asset.id);
+        assert_eq!(changes[0].asset_type, Image::type_name());
+        assert_eq!(changes[0].change, AssetRecordChange::Added);
+
+        db.remove_asset(asset.id).unwrap();
+
+        let changes = db
+            .flush_events(&settings)
+            .iter()
+            .filter_map(AssetRecordChanged::<TestDatabase>::from_event)
+            .collect::<Vec<_>>();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].change, AssetRecordChange::Removed);
+    }
 }
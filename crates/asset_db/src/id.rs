@@ -0,0 +1,115 @@
+//! This module implements helpers for displaying and parsing asset and module
+//! IDs in a compact, human-friendly form.
+//!
+//! Full UUIDs are unwieldy in user interfaces and logs, so IDs may be shown in
+//! a short form consisting of the first few hexadecimal digits, similar to an
+//! abbreviated commit hash.
+
+use std::fmt;
+use std::str::FromStr;
+
+use uuid::Uuid;
+
+/// The number of hexadecimal digits shown in the short form of an ID.
+pub const SHORT_ID_LEN: usize = 8;
+
+/// Displays an ID in its short form.
+///
+/// This is created using `short()` on [`AssetRecordID`] or [`AssetModuleID`].
+///
+/// [`AssetRecordID`]: crate::record::AssetRecordID
+/// [`AssetModuleID`]: crate::module::AssetModuleID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShortId(pub(crate) Uuid);
+
+impl fmt::Display for ShortId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = Uuid::encode_buffer();
+        let simple = self.0.simple().encode_lower(&mut buf);
+        f.write_str(&simple[.. SHORT_ID_LEN])
+    }
+}
+
+/// Returns true if the given ID is a time-ordered (version 7) UUID.
+pub(crate) fn is_time_ordered(id: &Uuid) -> bool {
+    id.get_version_num() == 7
+}
+
+/// A prefix of an ID, parsed from either the short or the full form.
+///
+/// A prefix does not identify an ID on its own, but can be resolved against
+/// the IDs stored in an asset database.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdPrefix(String);
+
+impl IdPrefix {
+    /// Returns true if the given ID starts with this prefix.
+    pub(crate) fn matches(&self, id: &Uuid) -> bool {
+        let mut buf = Uuid::encode_buffer();
+        id.simple().encode_lower(&mut buf).starts_with(&self.0)
+    }
+
+    /// Returns true if this prefix is a complete ID.
+    pub fn is_complete(&self) -> bool {
+        self.0.len() == 32
+    }
+}
+
+impl fmt::Display for IdPrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for IdPrefix {
+    type Err = IdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s
+            .trim()
+            .chars()
+            .filter(|c| *c != '-')
+            .map(|c| c.to_ascii_lowercase())
+            .collect::<String>();
+
+        if digits.len() < 4 || digits.len() > 32 {
+            return Err(IdParseError::Length(digits.len()));
+        }
+
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(IdParseError::Invalid(s.to_string()));
+        }
+
+        Ok(IdPrefix(digits))
+    }
+}
+
+/// Parses a full ID from a string in any standard UUID format.
+pub(crate) fn parse_full(s: &str) -> Result<Uuid, IdParseError> {
+    let s = s.trim();
+    Uuid::parse_str(s).map_err(|_| {
+        if s.len() < 32 {
+            IdParseError::Incomplete(s.to_string())
+        } else {
+            IdParseError::Invalid(s.to_string())
+        }
+    })
+}
+
+/// An error that can occur while parsing an ID.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum IdParseError {
+    /// The string is not a valid ID.
+    #[error("Invalid ID: {0}")]
+    Invalid(String),
+
+    /// The string is a short form ID, which must be resolved against the
+    /// database to find the full ID.
+    #[error("Incomplete ID: {0}. Short IDs must be resolved with the asset database")]
+    Incomplete(String),
+
+    /// The string has too few or too many hexadecimal digits to be an ID
+    /// prefix.
+    #[error("An ID prefix must have between 4 and 32 hexadecimal digits, found {0}")]
+    Length(usize),
+}
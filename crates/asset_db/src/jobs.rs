@@ -0,0 +1,478 @@
+//! This module implements the [`BackgroundJobs`] resource, which runs long
+//! tasks on the async compute task pool and tracks their progress.
+//!
+//! Each job is spawned with a [`JobContext`] that it can use to report its
+//! progress and to check whether it has been cancelled. When a job finishes,
+//! or is cancelled, a [`JobFinished`] message is written with its result.
+//! The output type of a job must be registered with
+//! [`BackgroundJobsAppExt::register_job_output`] so that its jobs are polled.
+//!
+//! Jobs spawned with [`BackgroundJobs::spawn_blocking`] block the user
+//! interface while they run, and a busy overlay is shown until they finish or
+//! are cancelled. Jobs spawned with [`BackgroundJobs::spawn_hidden`] are short
+//! and frequent, such as chunk meshing, and are not listed to the user. Their
+//! results are usually collected with [`BackgroundJobs::wait_all`] instead of
+//! being reported as messages.
+
+use std::any::Any;
+use std::fmt;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task, block_on, poll_once};
+
+/// Plugin that sets up the background job manager.
+pub struct BackgroundJobsPlugin;
+impl Plugin for BackgroundJobsPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<BackgroundJobs>();
+    }
+}
+
+/// The system sets for background jobs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
+pub enum BackgroundJobSystems {
+    /// Polls running jobs and writes [`JobFinished`] messages for those that
+    /// have finished.
+    Poll,
+}
+
+/// Extension trait for registering the output types of background jobs.
+pub trait BackgroundJobsAppExt {
+    /// Registers a job output type, adding the [`JobFinished`] message and the
+    /// system that polls jobs with this output type.
+    fn register_job_output<T>(&mut self) -> &mut Self
+    where
+        T: Send + Sync + 'static;
+}
+
+impl BackgroundJobsAppExt for App {
+    fn register_job_output<T>(&mut self) -> &mut Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.add_message::<JobFinished<T>>()
+            .add_systems(Update, poll_jobs::<T>.in_set(BackgroundJobSystems::Poll))
+    }
+}
+
+/// A unique identifier for a background job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JobId(u64);
+
+impl fmt::Display for JobId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A handle to a background job with an output of type `T`.
+pub struct JobHandle<T> {
+    /// The ID of the job.
+    id: JobId,
+
+    /// Marker for the output type of the job.
+    _output: PhantomData<fn() -> T>,
+}
+
+impl<T> JobHandle<T> {
+    /// Gets the ID of the job.
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+}
+
+impl<T> Clone for JobHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for JobHandle<T> {}
+
+impl<T> PartialEq for JobHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for JobHandle<T> {}
+
+impl<T> fmt::Debug for JobHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("JobHandle").field(&self.id).finish()
+    }
+}
+
+/// The state of a job that is shared with the task running it.
+#[derive(Debug, Default)]
+struct JobState {
+    /// The progress of the job, as the bits of an `f32` between 0 and 1.
+    progress: AtomicU32,
+
+    /// Whether the job has been cancelled.
+    cancelled: AtomicBool,
+}
+
+/// The context given to a running job, used to report progress and to check
+/// for cancellation.
+#[derive(Debug, Clone)]
+pub struct JobContext {
+    /// The shared state of the job.
+    state: Arc<JobState>,
+}
+
+impl JobContext {
+    /// Sets the progress of the job, between 0 and 1.
+    pub fn set_progress(&self, progress: f32) {
+        let progress = progress.clamp(0.0, 1.0);
+        self.state
+            .progress
+            .store(progress.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns true if the job has been cancelled, in which case it should
+    /// stop as soon as possible. Its output will be discarded.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Creates the context of a job that is spawned later with
+    /// [`BackgroundJobs::spawn_with_context`], so that the future of the job
+    /// can be created before the job is started.
+    pub(crate) fn pending() -> Self {
+        Self {
+            state: Arc::new(JobState::default()),
+        }
+    }
+}
+
+/// A background job that is still running.
+struct Job {
+    /// The ID of the job.
+    id: JobId,
+
+    /// The name of the job, shown to the user.
+    label: String,
+
+    /// Whether the job blocks the user interface while it runs.
+    blocking: bool,
+
+    /// Whether the job is hidden from the user.
+    hidden: bool,
+
+    /// The state shared with the task.
+    state: Arc<JobState>,
+
+    /// The task running the job, as a `Task<T>` for the job output type `T`.
+    task: Box<dyn Any + Send + Sync>,
+}
+
+/// A summary of a running job.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobInfo<'a> {
+    /// The ID of the job.
+    pub id: JobId,
+
+    /// The name of the job.
+    pub label: &'a str,
+
+    /// The progress of the job, between 0 and 1.
+    pub progress: f32,
+
+    /// Whether the job has been cancelled, but has not stopped yet.
+    pub cancelled: bool,
+
+    /// Whether the job blocks the user interface while it runs.
+    pub blocking: bool,
+}
+
+/// A resource that runs and tracks background jobs.
+#[derive(Default, Resource)]
+pub struct BackgroundJobs {
+    /// The ID given to the next job.
+    next_id: u64,
+
+    /// The jobs that are still running, in the order they were spawned.
+    jobs: Vec<Job>,
+}
+
+impl BackgroundJobs {
+    /// Spawns a new job on the async compute task pool.
+    ///
+    /// The job is given a [`JobContext`] to report its progress. Its output
+    /// type must be registered with
+    /// [`BackgroundJobsAppExt::register_job_output`], or it will never be
+    /// reported as finished.
+    pub fn spawn<T, F, Fut>(&mut self, label: impl Into<String>, job: F) -> JobHandle<T>
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(JobContext) -> Fut,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        self.spawn_job(label.into(), false, false, job)
+    }
+
+    /// Spawns a new job on the async compute task pool that blocks the user
+    /// interface while it runs, such as restoring a project, loading a map,
+    /// or importing a huge asset.
+    ///
+    /// A busy overlay with the label and progress of the job is shown until it
+    /// finishes, and the user may cancel the job from it. Otherwise, this is
+    /// the same as [`BackgroundJobs::spawn`].
+    pub fn spawn_blocking<T, F, Fut>(&mut self, label: impl Into<String>, job: F) -> JobHandle<T>
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(JobContext) -> Fut,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        self.spawn_job(label.into(), true, false, job)
+    }
+
+    /// Spawns a new job on the async compute task pool that is not listed by
+    /// [`BackgroundJobs::iter`], for short jobs that are spawned very often,
+    /// such as remeshing a chunk.
+    ///
+    /// Otherwise, this is the same as [`BackgroundJobs::spawn`].
+    pub fn spawn_hidden<T, F, Fut>(&mut self, label: impl Into<String>, job: F) -> JobHandle<T>
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(JobContext) -> Fut,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        self.spawn_job(label.into(), false, true, job)
+    }
+
+    /// Spawns a new job on the async compute task pool.
+    fn spawn_job<T, F, Fut>(
+        &mut self,
+        label: String,
+        blocking: bool,
+        hidden: bool,
+        job: F,
+    ) -> JobHandle<T>
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(JobContext) -> Fut,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let context = JobContext::pending();
+        let future = job(context.clone());
+        self.spawn_task(label, blocking, hidden, context, future)
+    }
+
+    /// Spawns a new job on the async compute task pool, running a future that
+    /// was created with the given context before the job was started.
+    ///
+    /// Otherwise, this is the same as [`BackgroundJobs::spawn`].
+    pub(crate) fn spawn_with_context<T, Fut>(
+        &mut self,
+        label: impl Into<String>,
+        context: JobContext,
+        future: Fut,
+    ) -> JobHandle<T>
+    where
+        T: Send + Sync + 'static,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        self.spawn_task(label.into(), false, false, context, future)
+    }
+
+    /// Spawns the future of a job on the async compute task pool.
+    fn spawn_task<T, Fut>(
+        &mut self,
+        label: String,
+        blocking: bool,
+        hidden: bool,
+        context: JobContext,
+        future: Fut,
+    ) -> JobHandle<T>
+    where
+        T: Send + Sync + 'static,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let id = JobId(self.next_id);
+        self.next_id += 1;
+
+        let state = context.state;
+        let task: Task<T> = AsyncComputeTaskPool::get().spawn(future);
+
+        if !hidden {
+            debug!("Started background job {}: {}", id, label);
+        }
+
+        self.jobs.push(Job {
+            id,
+            label,
+            blocking,
+            hidden,
+            state,
+            task: Box::new(task),
+        });
+
+        JobHandle {
+            id,
+            _output: PhantomData,
+        }
+    }
+
+    /// Cancels the job with the given ID.
+    ///
+    /// The job is dropped the next time jobs are polled, and a
+    /// [`JobResult::Cancelled`] message is written for it. Returns false if
+    /// the job is not running.
+    pub fn cancel(&self, id: JobId) -> bool {
+        let Some(job) = self.get(id) else {
+            return false;
+        };
+
+        job.state.cancelled.store(true, Ordering::Relaxed);
+        true
+    }
+
+    /// Gets the progress of the job with the given ID, between 0 and 1, or
+    /// `None` if the job is not running.
+    pub fn progress(&self, id: JobId) -> Option<f32> {
+        self.get(id)
+            .map(|job| f32::from_bits(job.state.progress.load(Ordering::Relaxed)))
+    }
+
+    /// Returns true if the job with the given ID is still running.
+    pub fn is_running(&self, id: JobId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Blocks until every running job with an output of type `T` has
+    /// finished, and returns their results in the order they were spawned.
+    ///
+    /// This is meant for short jobs whose results are needed right away, such
+    /// as chunk meshes that are waited on to avoid flickering. No
+    /// [`JobFinished`] messages are written for the returned jobs.
+    pub fn wait_all<T>(&mut self) -> Vec<(JobHandle<T>, JobResult<T>)>
+    where
+        T: Send + Sync + 'static,
+    {
+        let mut waiting = Vec::new();
+        let mut remaining = Vec::with_capacity(self.jobs.len());
+        for job in self.jobs.drain(..) {
+            if job.task.is::<Task<T>>() {
+                waiting.push(job);
+            } else {
+                remaining.push(job);
+            }
+        }
+        self.jobs = remaining;
+
+        waiting
+            .into_iter()
+            .map(|job| {
+                let handle = JobHandle {
+                    id: job.id,
+                    _output: PhantomData,
+                };
+
+                let Ok(task) = job.task.downcast::<Task<T>>() else {
+                    unreachable!("job output type was checked above");
+                };
+
+                let result = if job.state.cancelled.load(Ordering::Relaxed) {
+                    JobResult::Cancelled
+                } else {
+                    JobResult::Completed(block_on(*task))
+                };
+
+                (handle, result)
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the jobs that are still running, except for
+    /// hidden jobs.
+    pub fn iter(&self) -> impl Iterator<Item = JobInfo<'_>> {
+        self.jobs
+            .iter()
+            .filter(|job| !job.hidden)
+            .map(|job| JobInfo {
+                id: job.id,
+                label: &job.label,
+                progress: f32::from_bits(job.state.progress.load(Ordering::Relaxed)),
+                cancelled: job.state.cancelled.load(Ordering::Relaxed),
+                blocking: job.blocking,
+            })
+    }
+
+    /// Returns the number of jobs that are still running, including hidden
+    /// jobs.
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    /// Returns true if no jobs are running.
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Gets the running job with the given ID.
+    fn get(&self, id: JobId) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+}
+
+/// The result of a background job.
+#[derive(Debug)]
+pub enum JobResult<T> {
+    /// The job ran to completion with the given output.
+    Completed(T),
+
+    /// The job was cancelled before it completed.
+    Cancelled,
+}
+
+/// A message written when a background job with an output of type `T` has
+/// finished or was cancelled.
+#[derive(Debug, Message)]
+pub struct JobFinished<T: Send + Sync + 'static> {
+    /// The handle of the job.
+    pub handle: JobHandle<T>,
+
+    /// The result of the job.
+    pub result: JobResult<T>,
+}
+
+/// System to poll the jobs with an output of type `T`, writing a
+/// [`JobFinished`] message for each job that has finished.
+fn poll_jobs<T>(mut jobs: ResMut<BackgroundJobs>, mut finished: MessageWriter<JobFinished<T>>)
+where
+    T: Send + Sync + 'static,
+{
+    jobs.jobs.retain_mut(|job| {
+        let Some(task) = job.task.downcast_mut::<Task<T>>() else {
+            return true;
+        };
+
+        let result = if job.state.cancelled.load(Ordering::Relaxed) {
+            debug!("Cancelled background job {}: {}", job.id, job.label);
+            JobResult::Cancelled
+        } else {
+            match block_on(poll_once(task)) {
+                Some(output) => {
+                    debug!("Finished background job {}: {}", job.id, job.label);
+                    JobResult::Completed(output)
+                }
+                None => return true,
+            }
+        };
+
+        finished.write(JobFinished {
+            handle: JobHandle {
+                id: job.id,
+                _output: PhantomData,
+            },
+            result,
+        });
+        false
+    });
+}
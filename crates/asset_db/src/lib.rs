@@ -9,26 +9,39 @@ use std::path::PathBuf;
 use bevy::asset::io::{AssetSource, AssetSourceId};
 use bevy::prelude::*;
 
+use crate::busy::BusySettings;
 use crate::connection::{AssetDatabase, AssetDatabaseName};
-use crate::loaders::AwgenImageAssetLoader;
-use crate::param::AssetDatabaseTasks;
+use crate::jobs::{BackgroundJobSystems, BackgroundJobs, BackgroundJobsAppExt};
+use crate::loaders::{AwgenImageAssetLoader, AwgenThumbnailAssetLoader};
+use crate::param::{AssetDatabaseTasks, PreviewResult};
 use crate::source::{AwgenDbSource, AwgenDbWatcher};
+use crate::watcher::{AssetRecordChanged, AssetWatcherSettings};
 
+pub mod backup;
+pub mod blob;
+pub mod busy;
 pub mod connection;
+pub mod id;
+pub mod jobs;
 pub mod loaders;
 pub mod module;
 pub mod param;
 pub mod record;
 pub mod source;
 mod systems;
+pub mod tileset;
+pub mod watcher;
 
 /// Prelude module for easy importing of commonly used items.
 pub mod prelude {
+    pub use super::busy::BusySettings;
     pub use super::connection::*;
+    pub use super::id::*;
     pub use super::loaders::*;
     pub use super::module::*;
     pub use super::param::*;
     pub use super::record::*;
+    pub use super::watcher::{AssetRecordChange, AssetRecordChanged, AssetWatcherSettings};
     pub use super::{AwgenAssetPlugin, AwgenAssetPluginExt};
 }
 
@@ -37,7 +50,12 @@ pub struct AwgenAssetPlugin;
 impl Plugin for AwgenAssetPlugin {
     fn build(&self, app_: &mut App) {
         app_.register_asset_loader(AwgenImageAssetLoader)
-            .init_resource::<AssetDatabaseTasks>();
+            .register_asset_loader(AwgenThumbnailAssetLoader)
+            .init_resource::<AssetDatabaseTasks>()
+            .init_resource::<BackgroundJobs>()
+            .register_job_output::<PreviewResult>()
+            .init_resource::<AssetWatcherSettings>()
+            .init_resource::<BusySettings>();
     }
 }
 
@@ -55,6 +73,16 @@ pub trait AwgenAssetPluginExt {
     where
         N: AssetDatabaseName + Unpin + Send + Sync + 'static,
         P: Into<PathBuf>;
+
+    /// Registers an existing Awgen asset database source with the given name
+    /// and path, opened in read-only mode.
+    ///
+    /// All methods that would modify the database will return
+    /// [`AwgenDbError::ReadOnly`](connection::AwgenDbError::ReadOnly).
+    fn register_read_only_asset_db<N, P>(&mut self, path: P) -> &mut Self
+    where
+        N: AssetDatabaseName + Unpin + Send + Sync + 'static,
+        P: Into<PathBuf>;
 }
 
 impl AwgenAssetPluginExt for App {
@@ -63,25 +91,64 @@ impl AwgenAssetPluginExt for App {
         N: AssetDatabaseName + Unpin + Send + Sync + 'static,
         P: Into<PathBuf>,
     {
-        let database = AssetDatabase::<N>::new(path).expect("Failed to connect to asset database");
-        let reader = Box::new(AwgenDbSource {
-            database: database.clone(),
-        });
-        let watcher = database.clone();
+        let busy = busy_settings(self);
+        let database = AssetDatabase::<N>::new_with_busy_settings(path, busy)
+            .expect("Failed to connect to asset database");
+        register_database(self, database)
+    }
 
-        self.insert_resource(database)
-            .register_asset_source(
-                AssetSourceId::Name(N::database_name().into()),
-                AssetSource::build()
-                    .with_reader(move || reader.clone())
-                    .with_watcher(move |sender| {
-                        watcher.add_watcher(sender);
-                        Some(Box::new(AwgenDbWatcher))
-                    }),
-            )
-            .add_systems(
-                Update,
-                systems::update_previews::<N>.in_set(AwgenAssetSystems::TaskPolling),
-            )
+    fn register_read_only_asset_db<N, P>(&mut self, path: P) -> &mut Self
+    where
+        N: AssetDatabaseName + Unpin + Send + Sync + 'static,
+        P: Into<PathBuf>,
+    {
+        let busy = busy_settings(self);
+        let database = AssetDatabase::<N>::new_read_only_with_busy_settings(path, busy)
+            .expect("Failed to open asset database in read-only mode");
+        register_database(self, database)
     }
 }
+
+/// Gets the busy settings used to open asset databases, which are the defaults
+/// unless the [`BusySettings`] resource was inserted.
+fn busy_settings(app_: &App) -> BusySettings {
+    app_.world()
+        .get_resource::<BusySettings>()
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Registers the given asset database as an asset source, along with the
+/// systems that maintain it.
+fn register_database<N>(app_: &mut App, database: AssetDatabase<N>) -> &mut App
+where
+    N: AssetDatabaseName + Unpin + Send + Sync + 'static,
+{
+    let reader = Box::new(AwgenDbSource {
+        database: database.clone(),
+    });
+    let watcher = database.clone();
+
+    app_.insert_resource(database)
+        .add_message::<AssetRecordChanged<N>>()
+        .register_asset_source(
+            AssetSourceId::Name(N::database_name().into()),
+            AssetSource::build()
+                .with_reader(move || reader.clone())
+                .with_watcher(move |sender| {
+                    watcher.add_watcher(sender);
+                    Some(Box::new(AwgenDbWatcher))
+                }),
+        )
+        .add_systems(
+            Update,
+            (
+                systems::update_previews::<N>,
+                systems::poll_external_changes::<N>,
+                systems::flush_watcher_events::<N>,
+            )
+                .chain()
+                .in_set(AwgenAssetSystems::TaskPolling)
+                .after(BackgroundJobSystems::Poll),
+        )
+}
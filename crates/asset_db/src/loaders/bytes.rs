@@ -0,0 +1,303 @@
+//! This module implements the [`ByteWriter`] and [`ByteReader`] helpers, which
+//! write and parse the binary formats of Awgen assets.
+//!
+//! All numbers are stored as little-endian 32-bit integers. Asset blobs start
+//! with a header made of the magic number of the asset type, followed by a
+//! version marker and a format version byte, so that loaders can reject blobs
+//! of the wrong type and parse older versions of their format.
+
+use std::io::Write;
+
+use crate::loaders::AssetDataError;
+
+/// The marker written after the magic number of a header to indicate that a
+/// version byte follows.
+///
+/// Blobs written before headers were versioned store a positive number
+/// directly after the magic number, so a negative marker tells them apart.
+const VERSION_MARKER: i32 = -1;
+
+/// A simple in-memory writer for byte vectors.
+#[derive(Debug, Default, Clone)]
+pub struct ByteWriter {
+    /// The byte data being written.
+    pub data: Vec<u8>,
+}
+
+impl ByteWriter {
+    /// Creates a new empty [`ByteWriter`].
+    pub fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    /// Writes a versioned header, made of the given magic number, the version
+    /// marker and the format version.
+    pub fn write_header(&mut self, magic: &[u8], version: u8) -> Result<(), AssetDataError> {
+        self.write_all(magic)?;
+        self.write_num(VERSION_MARKER)?;
+        self.write_byte(version)
+    }
+
+    /// Writes a 32-bit little-endian integer to the byte stream.
+    pub fn write_num(&mut self, value: i32) -> Result<(), AssetDataError> {
+        self.write_all(&value.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Writes a single byte to the byte stream.
+    pub fn write_byte(&mut self, value: u8) -> Result<(), AssetDataError> {
+        self.write_all(&[value])?;
+        Ok(())
+    }
+
+    /// Writes a UTF-8 string to the byte stream, prefixed by its length in
+    /// bytes.
+    pub fn write_string(&mut self, value: &str) -> Result<(), AssetDataError> {
+        let len = i32::try_from(value.len())
+            .map_err(|_| AssetDataError(format!("String is too long: {} bytes", value.len())))?;
+
+        self.write_num(len)?;
+        self.write_all(value.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl Write for ByteWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A reader that parses data written by a [`ByteWriter`] from a byte slice.
+///
+/// Every read checks that enough data remains, and fails with an
+/// [`AssetDataError`] naming the offset of the truncated value otherwise.
+#[derive(Debug, Clone)]
+pub struct ByteReader<'a> {
+    /// The byte data being read.
+    data: &'a [u8],
+
+    /// The offset of the next byte to read.
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    /// Creates a new [`ByteReader`] that reads from the start of the given
+    /// data.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// Gets the offset of the next byte to read.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Gets the data that has not been read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.data[self.position ..]
+    }
+
+    /// Returns true if all data has been read.
+    pub fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    /// Reads a versioned header written by [`ByteWriter::write_header`], and
+    /// returns its format version.
+    ///
+    /// Blobs written before headers were versioned have no version marker, and
+    /// are reported as version 1. Versions newer than `max_version` are
+    /// rejected.
+    pub fn read_header(&mut self, magic: &[u8], max_version: u8) -> Result<u8, AssetDataError> {
+        self.check_magic(magic)?;
+
+        let version = if self.peek_num()? == VERSION_MARKER {
+            self.position += 4;
+            self.read_byte()?
+        } else {
+            1
+        };
+
+        if version == 0 || version > max_version {
+            return Err(AssetDataError(format!(
+                "Unsupported format version: {}",
+                version
+            )));
+        }
+
+        Ok(version)
+    }
+
+    /// Reads the given magic number, failing if the data does not start with
+    /// it.
+    pub fn check_magic(&mut self, magic: &[u8]) -> Result<(), AssetDataError> {
+        let offset = self.position;
+        let found = self
+            .read_bytes(magic.len())
+            .map_err(|_| AssetDataError(String::from("Missing magic number")))?;
+
+        if found != magic {
+            return Err(AssetDataError(format!(
+                "Invalid magic number at offset {}: expected {:?}",
+                offset,
+                String::from_utf8_lossy(magic)
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Reads a 32-bit little-endian integer.
+    pub fn read_num(&mut self) -> Result<i32, AssetDataError> {
+        Ok(i32::from_le_bytes(self.read_array()?))
+    }
+
+    /// Reads a single byte.
+    pub fn read_byte(&mut self) -> Result<u8, AssetDataError> {
+        let [value] = self.read_array()?;
+        Ok(value)
+    }
+
+    /// Reads a UTF-8 string written by [`ByteWriter::write_string`].
+    pub fn read_string(&mut self) -> Result<String, AssetDataError> {
+        let offset = self.position;
+        let len = self.read_num()?;
+        let len = usize::try_from(len).map_err(|_| {
+            AssetDataError(format!(
+                "Invalid string length at offset {}: {}",
+                offset, len
+            ))
+        })?;
+
+        let bytes = self.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| AssetDataError(format!("Invalid UTF-8 string at offset {}", offset)))
+    }
+
+    /// Reads the given number of bytes.
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], AssetDataError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| {
+                AssetDataError(format!(
+                    "Unexpected end of data: expected {} bytes at offset {}, found {}",
+                    len,
+                    self.position,
+                    self.data.len().saturating_sub(self.position)
+                ))
+            })?;
+
+        let bytes = &self.data[self.position .. end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Reads a fixed number of bytes into an array.
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], AssetDataError> {
+        let bytes = self.read_bytes(N)?;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    /// Reads a 32-bit little-endian integer without advancing the reader.
+    fn peek_num(&self) -> Result<i32, AssetDataError> {
+        self.clone().read_num()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The magic number used by the tests.
+    const MAGIC: &[u8] = b"TEST";
+
+    #[test]
+    fn values_round_trip() {
+        let mut writer = ByteWriter::new();
+        writer.write_header(MAGIC, 2).unwrap();
+        writer.write_num(-42).unwrap();
+        writer.write_byte(7).unwrap();
+        writer.write_string("héllo").unwrap();
+        writer.write_all(&[1, 2, 3]).unwrap();
+
+        let mut reader = ByteReader::new(&writer.data);
+        assert_eq!(reader.read_header(MAGIC, 2).unwrap(), 2);
+        assert_eq!(reader.read_num().unwrap(), -42);
+        assert_eq!(reader.read_byte().unwrap(), 7);
+        assert_eq!(reader.read_string().unwrap(), "héllo");
+        assert_eq!(reader.remaining(), &[1, 2, 3]);
+        assert_eq!(reader.read_array::<3>().unwrap(), [1, 2, 3]);
+        assert!(reader.is_empty());
+        assert_eq!(reader.position(), writer.data.len());
+    }
+
+    #[test]
+    fn unversioned_headers_are_version_one() {
+        let mut writer = ByteWriter::new();
+        writer.write_all(MAGIC).unwrap();
+        writer.write_num(128).unwrap();
+
+        let mut reader = ByteReader::new(&writer.data);
+        assert_eq!(reader.read_header(MAGIC, 3).unwrap(), 1);
+        assert_eq!(reader.read_num().unwrap(), 128);
+    }
+
+    #[test]
+    fn unsupported_versions_are_rejected() {
+        for version in [0, 4] {
+            let mut writer = ByteWriter::new();
+            writer.write_header(MAGIC, version).unwrap();
+
+            let mut reader = ByteReader::new(&writer.data);
+            assert!(reader.read_header(MAGIC, 3).is_err());
+        }
+    }
+
+    #[test]
+    fn wrong_magic_is_rejected() {
+        let mut writer = ByteWriter::new();
+        writer.write_header(b"NOPE", 1).unwrap();
+
+        assert!(ByteReader::new(&writer.data).read_header(MAGIC, 1).is_err());
+        assert!(ByteReader::new(b"TE").check_magic(MAGIC).is_err());
+    }
+
+    #[test]
+    fn truncated_reads_fail_without_advancing() {
+        let mut reader = ByteReader::new(&[1, 2, 3]);
+        assert!(reader.read_num().is_err());
+        assert_eq!(reader.position(), 0);
+
+        assert_eq!(reader.read_bytes(2).unwrap(), &[1, 2]);
+        assert!(reader.read_bytes(2).is_err());
+        assert!(reader.read_bytes(usize::MAX).is_err());
+        assert_eq!(reader.read_byte().unwrap(), 3);
+        assert!(reader.read_byte().is_err());
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn invalid_strings_are_rejected() {
+        let mut writer = ByteWriter::new();
+        writer.write_num(-1).unwrap();
+        assert!(ByteReader::new(&writer.data).read_string().is_err());
+
+        let mut writer = ByteWriter::new();
+        writer.write_num(10).unwrap();
+        writer.write_all(b"short").unwrap();
+        assert!(ByteReader::new(&writer.data).read_string().is_err());
+
+        let mut writer = ByteWriter::new();
+        writer.write_num(2).unwrap();
+        writer.write_all(&[0xff, 0xfe]).unwrap();
+        assert!(ByteReader::new(&writer.data).read_string().is_err());
+    }
+}
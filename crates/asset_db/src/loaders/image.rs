@@ -4,7 +4,13 @@ use std::io::{Read, Write};
 
 use bevy::asset::io::Reader;
 use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext, RenderAssetUsages};
-use bevy::image::{ImageSampler, IntoDynamicImageError};
+use bevy::image::{
+    ImageAddressMode,
+    ImageFilterMode,
+    ImageSampler,
+    ImageSamplerDescriptor,
+    IntoDynamicImageError,
+};
 use bevy::prelude::*;
 use bevy::render::render_resource::{
     Extent3d,
@@ -13,15 +19,23 @@ use bevy::render::render_resource::{
     TextureDimension,
     TextureFormat,
     TextureUsages,
+    TextureViewDescriptor,
+    TextureViewDimension,
 };
-use bevy::tasks::{AsyncComputeTaskPool, Task};
 use flate2::Compression;
 use flate2::read::ZlibDecoder;
 use flate2::write::ZlibEncoder;
-use image::EncodableLayout;
-use image::imageops::FilterType;
+use image::{DynamicImage, Rgba, Rgba32FImage, RgbaImage};
 
-use crate::loaders::{AssetDataError, AwgenAsset, ByteWriter, ImagePreviewData};
+use crate::jobs::JobContext;
+use crate::loaders::{
+    AssetDataError,
+    AwgenAsset,
+    ByteReader,
+    ByteWriter,
+    ImagePreviewData,
+    PreviewFuture,
+};
 
 /// The Awgen image asset type name.
 pub const AWGEN_IMAGE_TYPE: &str = "awgen_image";
@@ -29,6 +43,12 @@ pub const AWGEN_IMAGE_TYPE: &str = "awgen_image";
 /// The magic number used to identify Awgen image assets.
 const MAGIC_NUMBER: &[u8] = AWGEN_IMAGE_TYPE.as_bytes();
 
+/// The current version of the Awgen image format.
+const FORMAT_VERSION: u8 = 2;
+
+/// The number of bytes used to store the sampler settings.
+const SAMPLER_BYTES: usize = 11;
+
 impl AwgenAsset for Image {
     fn type_name() -> &'static str {
         AWGEN_IMAGE_TYPE
@@ -36,10 +56,12 @@ impl AwgenAsset for Image {
 
     fn save(&self) -> Result<Vec<u8>, AssetDataError> {
         let mut writer = ByteWriter::new();
-        writer.write_all(MAGIC_NUMBER)?;
+        writer.write_header(MAGIC_NUMBER, FORMAT_VERSION)?;
 
-        if self.texture_descriptor.size.depth_or_array_layers != 1 {
-            return Err(AssetDataError(String::from("Only 2D images are supported")));
+        if self.texture_descriptor.dimension != TextureDimension::D2 {
+            return Err(AssetDataError(String::from(
+                "Only 2D images and 2D texture arrays are supported",
+            )));
         }
 
         if self.texture_descriptor.format != TextureFormat::Rgba8UnormSrgb {
@@ -48,78 +70,80 @@ impl AwgenAsset for Image {
             )));
         }
 
-        if self.data_order != TextureDataOrder::LayerMajor {
-            return Err(AssetDataError(String::from(
-                "Only LayerMajor data order is supported",
+        let Some(data) = &self.data else {
+            return Err(AssetDataError(String::from("Image has no data")));
+        };
+
+        let width = self.texture_descriptor.size.width;
+        let height = self.texture_descriptor.size.height;
+        let layers = self.texture_descriptor.size.depth_or_array_layers;
+        let mipmaps = self.texture_descriptor.mip_level_count;
+
+        let mip_sizes = mip_sizes(width, height, mipmaps)?;
+        let expected = data_size(&mip_sizes, layers)?;
+        if data.len() != expected {
+            return Err(AssetDataError(format!(
+                "Image data is {} bytes, expected {} bytes for {} layers and {} mipmaps",
+                data.len(),
+                expected,
+                layers,
+                mipmaps
             )));
         }
 
-        let width = self.texture_descriptor.size.width as i32;
-        let height = self.texture_descriptor.size.height as i32;
-        let mipmaps = self.texture_descriptor.mip_level_count as i32;
-        writer.write_num(width)?;
-        writer.write_num(height)?;
-        writer.write_num(mipmaps)?;
+        writer.write_num(width as i32)?;
+        writer.write_num(height as i32)?;
+        writer.write_num(layers as i32)?;
+        writer.write_num(mipmaps as i32)?;
+        for size in mip_sizes {
+            writer.write_num(size as i32)?;
+        }
 
-        let Some(data) = &self.data else {
-            return Err(AssetDataError(String::from("Image has no data")));
-        };
+        write_sampler(&mut writer, &self.sampler)?;
 
+        // Images are always stored layer by layer, with the mip levels of each
+        // layer following each other.
         let mut encoder = ZlibEncoder::new(writer, Compression::new(4));
-        encoder.write_all(data)?;
+        match self.data_order {
+            TextureDataOrder::LayerMajor => encoder.write_all(data)?,
+            TextureDataOrder::MipMajor => {
+                encoder.write_all(&mip_major_to_layer_major(data, &mip_sizes, layers))?
+            }
+        }
 
         let writer = encoder.finish()?;
         Ok(writer.data)
     }
 
-    fn generate_preview(&self) -> Task<Result<ImagePreviewData, AssetDataError>> {
+    fn generate_preview(&self, job: JobContext) -> PreviewFuture {
         let image = self.clone();
-        let pool = AsyncComputeTaskPool::get();
-        pool.spawn(async move {
-            let mut image = match image.try_into_dynamic() {
+        Box::pin(async move {
+            AssetDataError::check_cancelled(&job)?;
+            let image = base_level(&image);
+            let source = match image.clone().try_into_dynamic() {
                 Ok(img) => img,
                 Err(IntoDynamicImageError::UninitializedImage) => {
                     return Err(AssetDataError(String::from(
                         "Uninitialized image for preview generation",
                     )));
                 }
-                Err(_) => {
-                    return Err(AssetDataError(String::from(
-                        "Unsupported image format for preview generation",
-                    )));
-                }
+                Err(_) => sample_linear(&image)?,
             };
 
-            image = image.resize_to_fill(
-                ImagePreviewData::WIDTH as u32,
-                ImagePreviewData::HEIGHT as u32,
-                FilterType::Triangle,
-            );
+            AssetDataError::check_cancelled(&job)?;
+            let rgba = match source {
+                DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+                    tonemap(source.into_rgba32f())
+                }
+                _ => source.into_rgba8(),
+            };
 
-            let mut preview = ImagePreviewData::new();
-            preview[..].copy_from_slice(image.into_rgba8().as_bytes());
-            Ok(preview)
+            Ok(ImagePreviewData::from_rgba8(&rgba))
         })
     }
-}
-
-/// Awgen image asset loader.
-pub struct AwgenImageAssetLoader;
-impl AssetLoader for AwgenImageAssetLoader {
-    type Asset = Image;
-    type Settings = ();
-    type Error = AssetDataError;
-
-    async fn load(
-        &self,
-        reader: &mut dyn Reader,
-        _: &Self::Settings,
-        _: &mut LoadContext<'_>,
-    ) -> Result<Self::Asset, Self::Error> {
-        let mut magic_number = [0u8; MAGIC_NUMBER.len()];
-        let byte_count = reader.read(&mut magic_number).await?;
 
-        if byte_count == 0 {
+    fn load(bytes: &[u8]) -> Result<Self, AssetDataError> {
+        if bytes.is_empty() {
             warn!("Loaded image asset with zero bytes, creating default 4x4 transparent image");
             return Ok(Image::new(
                 Extent3d {
@@ -134,37 +158,88 @@ impl AssetLoader for AwgenImageAssetLoader {
             ));
         }
 
-        if magic_number != MAGIC_NUMBER || byte_count != MAGIC_NUMBER.len() {
-            return Err(AssetDataError(String::from("Invalid image format")));
-        }
+        // Version 1 blobs have no version marker, and store the width directly
+        // after the magic number.
+        let mut data = ByteReader::new(bytes);
+        let version = data.read_header(MAGIC_NUMBER, FORMAT_VERSION)?;
 
-        let mut int_buf = [0u8; 4];
+        let (width, height, layers, mipmaps, sampler) = match version {
+            1 => {
+                let width = data.read_num()?;
+                let height = data.read_num()?;
+                let mipmaps = data.read_num()?;
 
-        reader.read_exact(&mut int_buf).await?;
-        let width = i32::from_le_bytes(int_buf);
+                if width <= 0 || height <= 0 || mipmaps <= 0 {
+                    return Err(AssetDataError(String::from("Invalid image dimensions")));
+                }
 
-        reader.read_exact(&mut int_buf).await?;
-        let height = i32::from_le_bytes(int_buf);
+                (width, height, 1, mipmaps, ImageSampler::nearest())
+            }
+            _ => {
+                let width = data.read_num()?;
+                let height = data.read_num()?;
+                let layers = data.read_num()?;
+                let mipmaps = data.read_num()?;
 
-        reader.read_exact(&mut int_buf).await?;
-        let mipmaps = i32::from_le_bytes(int_buf);
+                if width <= 0 || height <= 0 || layers <= 0 || mipmaps <= 0 {
+                    return Err(AssetDataError(String::from("Invalid image dimensions")));
+                }
+
+                for size in mip_sizes(width as u32, height as u32, mipmaps as u32)? {
+                    let stored = data.read_num()?;
+                    if stored as usize != size {
+                        return Err(AssetDataError(format!(
+                            "Invalid mipmap size: expected {} bytes, found {} bytes",
+                            size, stored
+                        )));
+                    }
+                }
 
-        let mut compressed_data = Vec::new();
-        reader.read_to_end(&mut compressed_data).await?;
+                let sampler = read_sampler(&mut data)?;
+
+                (width, height, layers, mipmaps, sampler)
+            }
+        };
 
-        let mut decoder = ZlibDecoder::new(compressed_data.as_slice());
+        let mip_sizes = mip_sizes(width as u32, height as u32, mipmaps as u32)?;
+        let expected = data_size(&mip_sizes, layers as u32)?;
 
+        // Version 2 blobs must decompress to exactly the expected size, so
+        // reading stops just past it rather than inflating corrupted data
+        // without bound.
+        let mut decoder = ZlibDecoder::new(data.remaining());
         let mut uncompressed_data = Vec::new();
-        decoder.read_to_end(&mut uncompressed_data)?;
+        if version >= 2 {
+            decoder
+                .take(expected as u64 + 1)
+                .read_to_end(&mut uncompressed_data)?;
+        } else {
+            decoder.read_to_end(&mut uncompressed_data)?;
+        }
+
+        if version >= 2 && uncompressed_data.len() != expected {
+            return Err(AssetDataError(format!(
+                "Corrupted image data: expected {} bytes, found {} bytes",
+                expected,
+                uncompressed_data.len()
+            )));
+        }
 
         debug!(
-            "Loaded image asset: {}x{} ({} mipmaps), {} bytes",
+            "Loaded image asset (v{}): {}x{}x{} ({} mipmaps), {} bytes",
+            version,
             width,
             height,
+            layers,
             mipmaps,
             uncompressed_data.len()
         );
 
+        let texture_view_descriptor = (layers > 1).then(|| TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::D2Array),
+            ..default()
+        });
+
         Ok(Image {
             data: Some(uncompressed_data),
             data_order: TextureDataOrder::LayerMajor,
@@ -173,7 +248,7 @@ impl AssetLoader for AwgenImageAssetLoader {
                 size: Extent3d {
                     width: width as u32,
                     height: height as u32,
-                    depth_or_array_layers: 1,
+                    depth_or_array_layers: layers as u32,
                 },
                 mip_level_count: mipmaps as u32,
                 sample_count: 1,
@@ -182,14 +257,434 @@ impl AssetLoader for AwgenImageAssetLoader {
                 usage: TextureUsages::TEXTURE_BINDING,
                 view_formats: &[],
             },
-            sampler: ImageSampler::nearest(),
-            texture_view_descriptor: None,
+            sampler,
+            texture_view_descriptor,
             asset_usage: RenderAssetUsages::RENDER_WORLD,
             copy_on_resize: false,
         })
     }
+}
+
+/// Samples every pixel of an image whose format cannot be converted directly,
+/// such as half-float HDR formats, into a linear floating point image.
+fn sample_linear(image: &Image) -> Result<DynamicImage, AssetDataError> {
+    let mut linear = Rgba32FImage::new(image.width(), image.height());
+    for (x, y, pixel) in linear.enumerate_pixels_mut() {
+        let color = image.get_color_at(x, y).map_err(|_| {
+            AssetDataError(String::from(
+                "Unsupported image format for preview generation",
+            ))
+        })?;
+
+        let color = color.to_linear();
+        *pixel = Rgba([color.red, color.green, color.blue, color.alpha]);
+    }
+
+    Ok(DynamicImage::ImageRgba32F(linear))
+}
+
+/// Converts a linear floating point image into an sRGB image.
+///
+/// If the image contains values above 1.0, it is tonemapped using the extended
+/// Reinhard operator with its brightest value as the white point, so that
+/// highlights are compressed rather than clipped.
+fn tonemap(image: Rgba32FImage) -> RgbaImage {
+    let white = image
+        .pixels()
+        .flat_map(|pixel| pixel.0[.. 3].iter().copied())
+        .filter(|value| value.is_finite())
+        .fold(1.0f32, f32::max);
+
+    let map = |value: f32| {
+        let value = value.max(0.0);
+        if white > 1.0 {
+            value * (1.0 + value / (white * white)) / (1.0 + value)
+        } else {
+            value
+        }
+    };
+
+    RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        let srgb = Srgba::from(LinearRgba::new(map(r), map(g), map(b), a.clamp(0.0, 1.0)));
+        Rgba(srgb.to_u8_array())
+    })
+}
+
+/// Awgen image asset loader.
+pub struct AwgenImageAssetLoader;
+impl AssetLoader for AwgenImageAssetLoader {
+    type Asset = Image;
+    type Settings = ();
+    type Error = AssetDataError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _: &Self::Settings,
+        _: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        Image::load(&bytes)
+    }
 
     fn extensions(&self) -> &[&str] {
         &[AWGEN_IMAGE_TYPE]
     }
 }
+
+/// The largest side, in pixels, of the thumbnail of an image.
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+/// Bevy asset loader for the thumbnails of Awgen image assets.
+///
+/// Thumbnails are loaded from `<id>.thumbnail.awgen_image` paths, which read
+/// the data of the image. Only the first layer of the image is kept, scaled
+/// down to at most [`THUMBNAIL_SIZE`] pixels on each side, so that views
+/// showing many images at once do not keep every full image in memory.
+pub struct AwgenThumbnailAssetLoader;
+impl AssetLoader for AwgenThumbnailAssetLoader {
+    type Asset = Image;
+    type Settings = ();
+    type Error = AssetDataError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _: &Self::Settings,
+        _: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        thumbnail(&Image::load(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[THUMBNAIL_EXTENSION]
+    }
+}
+
+/// The extension of the asset paths of image thumbnails.
+const THUMBNAIL_EXTENSION: &str = "thumbnail.awgen_image";
+
+/// Creates the thumbnail of the first layer of an image, which is at most
+/// [`THUMBNAIL_SIZE`] pixels on each side.
+///
+/// The largest mip level of the image that fits is used if there is one.
+/// Otherwise, the base level is scaled down.
+fn thumbnail(image: &Image) -> Result<Image, AssetDataError> {
+    let size = image.texture_descriptor.size;
+    if size.width.max(size.height) <= THUMBNAIL_SIZE {
+        return Ok(base_level(image));
+    }
+
+    let Some(data) = &image.data else {
+        return Err(AssetDataError(String::from("Image has no data")));
+    };
+
+    let mipmaps = image.texture_descriptor.mip_level_count;
+    let mut offset = 0;
+    for (level, level_size) in mip_sizes(size.width, size.height, mipmaps)?
+        .into_iter()
+        .enumerate()
+    {
+        let width = (size.width >> level).max(1);
+        let height = (size.height >> level).max(1);
+        if width.max(height) <= THUMBNAIL_SIZE {
+            let pixels = data.get(offset .. offset + level_size).ok_or_else(|| {
+                AssetDataError(String::from("Image data is shorter than its mipmaps"))
+            })?;
+            return Ok(thumbnail_image(image, width, height, pixels.to_vec()));
+        }
+        offset += level_size;
+    }
+
+    let base = base_level(image);
+    let Some(pixels) = base.data else {
+        return Err(AssetDataError(String::from("Image has no data")));
+    };
+
+    let rgba = RgbaImage::from_raw(size.width, size.height, pixels)
+        .ok_or_else(|| AssetDataError(String::from("Image data does not match its size")))?;
+
+    let scale = THUMBNAIL_SIZE as f32 / size.width.max(size.height) as f32;
+    let width = ((size.width as f32 * scale) as u32).clamp(1, THUMBNAIL_SIZE);
+    let height = ((size.height as f32 * scale) as u32).clamp(1, THUMBNAIL_SIZE);
+    let scaled = image::imageops::thumbnail(&rgba, width, height);
+
+    Ok(thumbnail_image(image, width, height, scaled.into_raw()))
+}
+
+/// Creates a single layer image without mipmaps from the given pixels, with
+/// the sampler of the given image.
+fn thumbnail_image(image: &Image, width: u32, height: u32, pixels: Vec<u8>) -> Image {
+    let mut thumbnail = Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    thumbnail.sampler = image.sampler.clone();
+    thumbnail
+}
+
+/// Gets the largest number of mip levels an image of the given size can have,
+/// which is one more than the base 2 logarithm of its largest side.
+fn max_mip_levels(width: u32, height: u32) -> u32 {
+    u32::BITS - width.max(height).max(1).leading_zeros()
+}
+
+/// Calculates the size, in bytes, of each mip level of a single RGBA8 layer.
+///
+/// Returns an error if the image cannot have that many mip levels, or if a
+/// level is too large to be addressed.
+fn mip_sizes(width: u32, height: u32, mipmaps: u32) -> Result<Vec<usize>, AssetDataError> {
+    let max = max_mip_levels(width, height);
+    if mipmaps == 0 || mipmaps > max {
+        return Err(AssetDataError(format!(
+            "Invalid mipmap count: a {}x{} image has 1 to {} mipmaps, found {}",
+            width, height, max, mipmaps
+        )));
+    }
+
+    (0 .. mipmaps)
+        .map(|level| {
+            let w = (width >> level).max(1) as usize;
+            let h = (height >> level).max(1) as usize;
+            w.checked_mul(h)
+                .and_then(|pixels| pixels.checked_mul(4))
+                .ok_or_else(|| AssetDataError(String::from("Image is too large")))
+        })
+        .collect()
+}
+
+/// Calculates the size, in bytes, of the data of an image with the given mip
+/// level sizes and number of layers.
+fn data_size(mip_sizes: &[usize], layers: u32) -> Result<usize, AssetDataError> {
+    mip_sizes
+        .iter()
+        .try_fold(0usize, |total, &size| total.checked_add(size))
+        .and_then(|layer| layer.checked_mul(layers as usize))
+        .ok_or_else(|| AssetDataError(String::from("Image is too large")))
+}
+
+/// Reorders image data stored mip level by mip level, with the layers of each
+/// level following each other, into data stored layer by layer.
+fn mip_major_to_layer_major(data: &[u8], mip_sizes: &[usize], layers: u32) -> Vec<u8> {
+    let layers = layers as usize;
+    let mut level_starts = Vec::with_capacity(mip_sizes.len());
+    let mut start = 0;
+    for &size in mip_sizes {
+        level_starts.push(start);
+        start += size * layers;
+    }
+
+    let mut reordered = Vec::with_capacity(data.len());
+    for layer in 0 .. layers {
+        for (&size, &level_start) in mip_sizes.iter().zip(&level_starts) {
+            let offset = level_start + layer * size;
+            reordered.extend_from_slice(&data[offset .. offset + size]);
+        }
+    }
+    reordered
+}
+
+/// Creates a copy of the given image containing only the base mip level of the
+/// first layer.
+fn base_level(image: &Image) -> Image {
+    let size = image.texture_descriptor.size;
+    if size.depth_or_array_layers == 1 && image.texture_descriptor.mip_level_count == 1 {
+        return image.clone();
+    }
+
+    let mut base = image.clone();
+    base.texture_descriptor.size.depth_or_array_layers = 1;
+    base.texture_descriptor.mip_level_count = 1;
+    base.texture_view_descriptor = None;
+
+    if let Some(data) = &mut base.data {
+        let pixel_size = image.texture_descriptor.format.pixel_size().unwrap_or(4);
+        data.truncate(size.width as usize * size.height as usize * pixel_size);
+    }
+
+    base
+}
+
+/// Writes the sampler settings of an image.
+///
+/// The sampler is written as a flag byte, followed by the address modes and
+/// filter modes if the sampler is not the default sampler.
+fn write_sampler(writer: &mut ByteWriter, sampler: &ImageSampler) -> Result<(), AssetDataError> {
+    let descriptor = match sampler {
+        ImageSampler::Default => {
+            writer.write_all(&[0; SAMPLER_BYTES])?;
+            return Ok(());
+        }
+        ImageSampler::Descriptor(descriptor) => descriptor,
+    };
+
+    let address_mode = |mode: ImageAddressMode| match mode {
+        ImageAddressMode::ClampToEdge => 0,
+        ImageAddressMode::Repeat => 1,
+        ImageAddressMode::MirrorRepeat => 2,
+        ImageAddressMode::ClampToBorder => 3,
+    };
+
+    let filter_mode = |mode: ImageFilterMode| match mode {
+        ImageFilterMode::Nearest => 0,
+        ImageFilterMode::Linear => 1,
+    };
+
+    writer.write_all(&[
+        1,
+        address_mode(descriptor.address_mode_u),
+        address_mode(descriptor.address_mode_v),
+        address_mode(descriptor.address_mode_w),
+        filter_mode(descriptor.mag_filter),
+        filter_mode(descriptor.min_filter),
+        filter_mode(descriptor.mipmap_filter),
+    ])?;
+    writer.write_num(descriptor.anisotropy_clamp as i32)?;
+
+    Ok(())
+}
+
+/// Reads the sampler settings written by [`write_sampler`].
+fn read_sampler(reader: &mut ByteReader) -> Result<ImageSampler, AssetDataError> {
+    let [flag, address_u, address_v, address_w, mag, min, mipmap] = reader.read_array()?;
+    let anisotropy_clamp = reader.read_num()?;
+
+    if flag == 0 {
+        return Ok(ImageSampler::Default);
+    }
+
+    let address_mode = |value: u8| match value {
+        0 => Ok(ImageAddressMode::ClampToEdge),
+        1 => Ok(ImageAddressMode::Repeat),
+        2 => Ok(ImageAddressMode::MirrorRepeat),
+        3 => Ok(ImageAddressMode::ClampToBorder),
+        _ => Err(AssetDataError(format!("Invalid address mode: {}", value))),
+    };
+
+    let filter_mode = |value: u8| match value {
+        0 => Ok(ImageFilterMode::Nearest),
+        1 => Ok(ImageFilterMode::Linear),
+        _ => Err(AssetDataError(format!("Invalid filter mode: {}", value))),
+    };
+
+    Ok(ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: address_mode(address_u)?,
+        address_mode_v: address_mode(address_v)?,
+        address_mode_w: address_mode(address_w)?,
+        mag_filter: filter_mode(mag)?,
+        min_filter: filter_mode(min)?,
+        mipmap_filter: filter_mode(mipmap)?,
+        anisotropy_clamp: anisotropy_clamp.clamp(1, u16::MAX as i32) as u16,
+        ..default()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mip_counts_are_limited_by_image_size() {
+        assert_eq!(max_mip_levels(1, 1), 1);
+        assert_eq!(max_mip_levels(16, 4), 5);
+        assert_eq!(max_mip_levels(17, 1), 5);
+        assert!(mip_sizes(16, 16, 5).is_ok());
+        assert!(mip_sizes(16, 16, 6).is_err());
+        assert!(mip_sizes(16, 16, 0).is_err());
+        assert!(mip_sizes(u32::MAX, u32::MAX, 40).is_err());
+    }
+
+    #[test]
+    fn load_rejects_too_many_mipmaps() {
+        let mut writer = ByteWriter::new();
+        writer.write_header(MAGIC_NUMBER, FORMAT_VERSION).unwrap();
+        for value in [4, 4, 1, 64] {
+            writer.write_num(value).unwrap();
+        }
+
+        assert!(Image::load(&writer.data).is_err());
+    }
+
+    #[test]
+    fn mip_major_images_are_saved_layer_major() {
+        let size = Extent3d {
+            width: 2,
+            height: 2,
+            depth_or_array_layers: 2,
+        };
+
+        // Level 0 of both layers, followed by level 1 of both layers.
+        let layer_0 = [[1u8; 16].as_slice(), &[2; 4]].concat();
+        let layer_1 = [[3u8; 16].as_slice(), &[4; 4]].concat();
+        let mip_major = [
+            &layer_0[.. 16],
+            &layer_1[.. 16],
+            &layer_0[16 ..],
+            &layer_1[16 ..],
+        ]
+        .concat();
+
+        let mut image = Image::new(
+            size,
+            TextureDimension::D2,
+            vec![0; 32],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        image.data = Some(mip_major);
+        image.texture_descriptor.mip_level_count = 2;
+        image.data_order = TextureDataOrder::MipMajor;
+
+        let loaded = Image::load(&image.save().unwrap()).unwrap();
+        assert_eq!(loaded.data_order, TextureDataOrder::LayerMajor);
+        assert_eq!(loaded.data.unwrap(), [layer_0, layer_1].concat());
+    }
+
+    #[test]
+    fn thumbnails_fit_within_the_thumbnail_size() {
+        let image = |width: u32, height: u32, mipmaps: u32, data: Vec<u8>| {
+            let mut image = Image::new(
+                Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                vec![0; (width * height * 4) as usize],
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::RENDER_WORLD,
+            );
+            image.data = Some(data);
+            image.texture_descriptor.mip_level_count = mipmaps;
+            image
+        };
+
+        let small = thumbnail(&image(64, 32, 1, vec![1; 64 * 32 * 4])).unwrap();
+        assert_eq!(small.size(), UVec2::new(64, 32));
+
+        // The second mip level fits, so it is used as is.
+        let base = vec![1; 512 * 256 * 4];
+        let level_1 = vec![2; 256 * 128 * 4];
+        let mipmapped = image(512, 256, 2, [base, level_1.clone()].concat());
+        let from_mip = thumbnail(&mipmapped).unwrap();
+        assert_eq!(from_mip.size(), UVec2::new(256, 128));
+        assert_eq!(from_mip.data.unwrap(), level_1);
+
+        let large = thumbnail(&image(1024, 512, 1, vec![3; 1024 * 512 * 4])).unwrap();
+        assert_eq!(large.size(), UVec2::new(256, 128));
+        assert_eq!(large.texture_descriptor.mip_level_count, 1);
+    }
+}
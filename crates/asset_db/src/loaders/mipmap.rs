@@ -0,0 +1,165 @@
+//! This module implements downsampling of RGBA8 images by a factor of two,
+//! which is used to generate mipmaps and to shrink images for previews.
+//!
+//! The gamma-correct filter averages pixels in linear color space and weights
+//! each color by its alpha, so that mipmaps do not darken or pick up the color
+//! of fully transparent pixels. Color conversions use lookup tables, and the
+//! channels of each pixel are processed as fixed-size arrays, which the
+//! compiler can vectorize.
+
+use lazy_static::lazy_static;
+
+/// The number of entries in the lookup table that converts linear values back
+/// to sRGB.
+const LINEAR_LUT_SIZE: usize = 4096;
+
+lazy_static! {
+    /// Converts an sRGB channel value into a linear value between 0 and 1.
+    static ref SRGB_TO_LINEAR: [f32; 256] = {
+        let mut table = [0.0; 256];
+        for (value, entry) in table.iter_mut().enumerate() {
+            let value = value as f32 / 255.0;
+            *entry = if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            };
+        }
+        table
+    };
+
+    /// Converts a linear value, scaled to the size of the table, back into an
+    /// sRGB channel value.
+    static ref LINEAR_TO_SRGB: [u8; LINEAR_LUT_SIZE] = {
+        let mut table = [0; LINEAR_LUT_SIZE];
+        for (value, entry) in table.iter_mut().enumerate() {
+            let value = value as f32 / (LINEAR_LUT_SIZE - 1) as f32;
+            let srgb = if value <= 0.0031308 {
+                value * 12.92
+            } else {
+                1.055 * value.powf(1.0 / 2.4) - 0.055
+            };
+            *entry = (srgb * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+        table
+    };
+}
+
+/// The filter used to downsample images.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MipmapQuality {
+    /// Averages the raw sRGB values of each 2x2 block of pixels. This is the
+    /// fastest filter, but darkens edges between light and dark areas, and
+    /// bleeds the color of transparent pixels.
+    Fast,
+
+    /// Averages each 2x2 block of pixels in linear color space, weighted by
+    /// alpha.
+    #[default]
+    GammaCorrect,
+}
+
+/// Downsamples an RGBA8 image to half its size in each dimension, rounded
+/// down, but never smaller than one pixel.
+///
+/// Returns the downsampled pixels along with their width and height. The
+/// pixel data must contain exactly `width * height` RGBA8 pixels.
+pub fn downsample_rgba8(
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    quality: MipmapQuality,
+) -> (Vec<u8>, u32, u32) {
+    debug_assert_eq!(pixels.len(), (width * height * 4) as usize);
+
+    let new_width = (width / 2).max(1);
+    let new_height = (height / 2).max(1);
+    let mut output = vec![0; (new_width * new_height * 4) as usize];
+
+    let pixel_at = |x: u32, y: u32| -> &[u8] {
+        let index = ((y * width + x) * 4) as usize;
+        &pixels[index .. index + 4]
+    };
+
+    for y in 0 .. new_height {
+        let y0 = (y * 2).min(height - 1);
+        let y1 = (y * 2 + 1).min(height - 1);
+
+        for x in 0 .. new_width {
+            let x0 = (x * 2).min(width - 1);
+            let x1 = (x * 2 + 1).min(width - 1);
+
+            let block = [
+                pixel_at(x0, y0),
+                pixel_at(x1, y0),
+                pixel_at(x0, y1),
+                pixel_at(x1, y1),
+            ];
+
+            let index = ((y * new_width + x) * 4) as usize;
+            let pixel = match quality {
+                MipmapQuality::Fast => average_srgb(block),
+                MipmapQuality::GammaCorrect => average_linear(block),
+            };
+            output[index .. index + 4].copy_from_slice(&pixel);
+        }
+    }
+
+    (output, new_width, new_height)
+}
+
+/// Averages a block of four pixels using their raw sRGB values.
+fn average_srgb(block: [&[u8]; 4]) -> [u8; 4] {
+    let mut sum = [2u32; 4];
+    for pixel in block {
+        for (total, &value) in sum.iter_mut().zip(pixel) {
+            *total += value as u32;
+        }
+    }
+
+    sum.map(|value| (value / 4) as u8)
+}
+
+/// Averages a block of four pixels in linear color space, weighting each
+/// color by its alpha.
+fn average_linear(block: [&[u8]; 4]) -> [u8; 4] {
+    let mut weighted = [0.0f32; 3];
+    let mut unweighted = [0.0f32; 3];
+    let mut alpha = 0.0f32;
+
+    for pixel in block {
+        let a = pixel[3] as f32 / 255.0;
+        let color = [
+            SRGB_TO_LINEAR[pixel[0] as usize],
+            SRGB_TO_LINEAR[pixel[1] as usize],
+            SRGB_TO_LINEAR[pixel[2] as usize],
+        ];
+
+        for ((weighted, unweighted), value) in weighted.iter_mut().zip(&mut unweighted).zip(color) {
+            *weighted += value * a;
+            *unweighted += value;
+        }
+        alpha += a;
+    }
+
+    // A fully transparent block has no weight, so its color is averaged
+    // evenly instead.
+    let color = if alpha > 0.0 {
+        weighted.map(|value| value / alpha)
+    } else {
+        unweighted.map(|value| value / 4.0)
+    };
+
+    [
+        linear_to_srgb(color[0]),
+        linear_to_srgb(color[1]),
+        linear_to_srgb(color[2]),
+        (alpha / 4.0 * 255.0).round() as u8,
+    ]
+}
+
+/// Converts a linear value between 0 and 1 into an sRGB channel value.
+fn linear_to_srgb(value: f32) -> u8 {
+    let index = (value.clamp(0.0, 1.0) * (LINEAR_LUT_SIZE - 1) as f32).round() as usize;
+    LINEAR_TO_SRGB[index]
+}
@@ -1,14 +1,20 @@
 //! This module implements the asset loaders for Awgen asset databases.
 
-use std::io::Write;
+use std::future::Future;
+use std::pin::Pin;
 
 use bevy::prelude::*;
-use bevy::tasks::Task;
 
+use crate::jobs::JobContext;
+
+mod bytes;
 mod image;
+mod mipmap;
 mod preview;
 
+pub use bytes::*;
 pub use image::*;
+pub use mipmap::*;
 pub use preview::*;
 
 /// An asset that is supported by the Awgen asset management system.
@@ -23,51 +29,52 @@ pub trait AwgenAsset: Asset + Sized {
     /// database.
     fn save(&self) -> Result<Vec<u8>, AssetDataError>;
 
-    /// Spawns a task that generates a preview image of this asset for asset
-    /// thumbnails.
+    /// Decodes an asset from a byte slice previously written by
+    /// [`AwgenAsset::save`].
+    ///
+    /// This is shared by the Bevy asset loader of this asset type and by
+    /// [`AwgenAssets::read_asset`](crate::param::AwgenAssets::read_asset).
+    fn load(bytes: &[u8]) -> Result<Self, AssetDataError>;
+
+    /// Creates the future that generates a preview image of this asset for
+    /// asset thumbnails. The future is created when the preview is queued, and
+    /// is run as a background job once the preview is started, so it must
+    /// take what it needs from the asset up front.
     ///
     /// A preview image should be a 128x128 RGBA image, with bilinear sampling.
-    fn generate_preview(&self) -> Task<Result<ImagePreviewData, AssetDataError>>;
+    ///
+    /// The future should check the given job context between expensive steps,
+    /// and stop early with [`AssetDataError::cancelled`] once the job is
+    /// cancelled, such as when a newer preview has superseded it.
+    fn generate_preview(&self, job: JobContext) -> PreviewFuture;
 }
 
+/// The future that generates the preview image of an asset.
+pub type PreviewFuture =
+    Pin<Box<dyn Future<Output = Result<ImagePreviewData, AssetDataError>> + Send>>;
+
 /// Error type for Awgen asset processing.
 #[derive(Debug, thiserror::Error)]
 #[error("Failed to process Awgen asset: {0}")]
 pub struct AssetDataError(pub String);
 
-impl From<std::io::Error> for AssetDataError {
-    fn from(e: std::io::Error) -> Self {
-        AssetDataError(format!("I/O error: {}", e))
+impl AssetDataError {
+    /// Creates the error returned by a task that was cancelled.
+    pub fn cancelled() -> Self {
+        AssetDataError(String::from("Task was cancelled"))
     }
-}
 
-/// A simple in-memory writer for byte vectors.
-#[derive(Debug, Default, Clone)]
-pub struct ByteWriter {
-    /// The byte data being written.
-    pub data: Vec<u8>,
-}
-
-impl ByteWriter {
-    /// Creates a new empty [`ByteWriter`].
-    pub fn new() -> Self {
-        Self { data: Vec::new() }
-    }
-
-    /// Writes a 32-bit little-endian integer to the byte stream.
-    pub fn write_num(&mut self, value: i32) -> Result<(), AssetDataError> {
-        self.write_all(&value.to_le_bytes())?;
+    /// Returns an error if the given job has been cancelled.
+    pub fn check_cancelled(job: &JobContext) -> Result<(), Self> {
+        if job.is_cancelled() {
+            return Err(Self::cancelled());
+        }
         Ok(())
     }
 }
 
-impl Write for ByteWriter {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.data.extend_from_slice(buf);
-        Ok(buf.len())
-    }
-
-    fn flush(&mut self) -> std::io::Result<()> {
-        Ok(())
+impl From<std::io::Error> for AssetDataError {
+    fn from(e: std::io::Error) -> Self {
+        AssetDataError(format!("I/O error: {}", e))
     }
 }
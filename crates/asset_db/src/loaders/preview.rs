@@ -7,6 +7,23 @@ use std::slice::SliceIndex;
 use bevy::asset::RenderAssetUsages;
 use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use image::imageops::FilterType;
+use image::{DynamicImage, RgbaImage};
+
+use crate::loaders::{MipmapQuality, downsample_rgba8};
+
+/// The aspect ratio beyond which images are letterboxed to fit the preview,
+/// rather than cropped to fill it.
+const LETTERBOX_ASPECT_RATIO: f32 = 2.0;
+
+/// The size, in pixels, of each square of the transparency checkerboard.
+const CHECKER_SIZE: u32 = 8;
+
+/// The color of the light squares of the transparency checkerboard.
+const CHECKER_LIGHT: [u8; 3] = [204, 204, 204];
+
+/// The color of the dark squares of the transparency checkerboard.
+const CHECKER_DARK: [u8; 3] = [153, 153, 153];
 
 /// A newtype wrapper for image preview data.
 ///
@@ -28,6 +45,73 @@ impl ImagePreviewData {
     pub fn new() -> Self {
         Self(vec![255; Self::WIDTH * Self::HEIGHT * Self::BITS_PER_PIXEL])
     }
+
+    /// Creates a preview from the given image.
+    ///
+    /// Images with a typical aspect ratio are scaled and cropped to fill the
+    /// preview, while images with an extreme aspect ratio are letterboxed so
+    /// they remain recognizable. Transparent areas of the image are drawn over
+    /// a checkerboard backdrop, and the letterbox bars are left transparent.
+    pub fn from_rgba8(image: &RgbaImage) -> Self {
+        let width = Self::WIDTH as u32;
+        let height = Self::HEIGHT as u32;
+
+        let aspect = image.width() as f32 / image.height().max(1) as f32;
+        let letterbox =
+            !(1.0 / LETTERBOX_ASPECT_RATIO ..= LETTERBOX_ASPECT_RATIO).contains(&aspect);
+
+        // Halve the image with the gamma-correct filter while it is much
+        // larger than the preview, so the final resize only blends nearby
+        // pixels.
+        let mut pixels = image.as_raw().clone();
+        let (mut image_width, mut image_height) = image.dimensions();
+        while image_width >= width * 4 && image_height >= height * 4 {
+            (pixels, image_width, image_height) = downsample_rgba8(
+                &pixels,
+                image_width,
+                image_height,
+                MipmapQuality::GammaCorrect,
+            );
+        }
+
+        let source = RgbaImage::from_raw(image_width, image_height, pixels)
+            .map(DynamicImage::ImageRgba8)
+            .expect("Downsampled image has the wrong size");
+        let scaled = if letterbox {
+            source.resize(width, height, FilterType::Triangle)
+        } else {
+            source.resize_to_fill(width, height, FilterType::Triangle)
+        }
+        .into_rgba8();
+
+        let offset_x = (width - scaled.width().min(width)) / 2;
+        let offset_y = (height - scaled.height().min(height)) / 2;
+
+        let mut preview = Self(vec![0; Self::WIDTH * Self::HEIGHT * Self::BITS_PER_PIXEL]);
+        for (x, y, pixel) in scaled.enumerate_pixels() {
+            let (x, y) = (x + offset_x, y + offset_y);
+            if x >= width || y >= height {
+                continue;
+            }
+
+            let checker = if (x / CHECKER_SIZE + y / CHECKER_SIZE) % 2 == 0 {
+                CHECKER_LIGHT
+            } else {
+                CHECKER_DARK
+            };
+
+            let alpha = pixel[3] as u32;
+            let index = (y as usize * Self::WIDTH + x as usize) * Self::BITS_PER_PIXEL;
+            for channel in 0 .. 3 {
+                let blended =
+                    pixel[channel] as u32 * alpha + checker[channel] as u32 * (255 - alpha);
+                preview.0[index + channel] = (blended / 255) as u8;
+            }
+            preview.0[index + 3] = 255;
+        }
+
+        preview
+    }
 }
 
 impl<I> Index<I> for ImagePreviewData
@@ -1,10 +1,13 @@
 //! This module implements the [`AssetModule`] struct and related functionality.
 
 use std::fmt;
+use std::str::FromStr;
 
 use sqlite::{BindableWithIndex, ParameterIndex, Statement};
 use uuid::Uuid;
 
+use crate::id::{self, IdParseError, ShortId};
+
 /// Unique identifier for an asset module.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct AssetModuleID(Uuid);
@@ -20,6 +23,31 @@ impl AssetModuleID {
     pub(crate) fn from_string(s: &str) -> Option<Self> {
         Uuid::parse_str(s).ok().map(AssetModuleID)
     }
+
+    /// Gets the short form of this ID, for display in user interfaces and
+    /// logs.
+    pub fn short(&self) -> ShortId {
+        ShortId(self.0)
+    }
+}
+
+impl FromStr for AssetModuleID {
+    type Err = IdParseError;
+
+    /// Parses a full ID. Short form IDs are rejected with
+    /// [`IdParseError::Incomplete`], since they must be resolved against the
+    /// database.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        id::parse_full(s).map(AssetModuleID)
+    }
+}
+
+impl TryFrom<&str> for AssetModuleID {
+    type Error = IdParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
 }
 
 impl fmt::Display for AssetModuleID {
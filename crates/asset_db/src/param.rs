@@ -1,25 +1,212 @@
 //! This module implements the [`AssetDatabase`] system parameter for
 //! accessing Awgen asset databases within Bevy systems.
 
-use std::path::PathBuf;
+use std::collections::VecDeque;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
 
 use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
-use bevy::tasks::Task;
 
-use crate::loaders::{AssetDataError, AwgenAsset, ImagePreviewData};
+use crate::id::{IdParseError, IdPrefix};
+use crate::jobs::{BackgroundJobs, JobContext, JobHandle};
+use crate::loaders::{AssetDataError, AwgenAsset, ImagePreviewData, PreviewFuture};
 use crate::module::{AssetModule, AssetModuleID};
-use crate::prelude::{AssetDatabase, AssetDatabaseName, AwgenDbError};
+use crate::prelude::{AssetDatabase, AssetDatabaseName, AssetFilter, AwgenDbError};
 use crate::record::{AssetRecord, AssetRecordID, ErasedAssetRecord};
 
 /// A resource to track assets that need their previews updated.
-#[derive(Debug, Default, Resource)]
+///
+/// Previews are generated as [`BackgroundJobs`]. Preview tasks are queued
+/// rather than spawned right away, and only a limited number of them run at
+/// once, so that importing many assets does not starve the compute task pool
+/// of threads needed for interactive work such as chunk meshing. Queued
+/// [`PreviewPriority::Interactive`] previews always start before queued
+/// [`PreviewPriority::Bulk`] previews.
+#[derive(Debug, Resource)]
 pub struct AssetDatabaseTasks {
-    /// Tasks for generating asset previews.
-    preview_generation: Vec<(
-        AssetRecordID,
-        Task<Result<ImagePreviewData, AssetDataError>>,
-    )>,
+    /// Tasks for generating asset previews that are currently running.
+    preview_generation: Vec<PreviewTask>,
+
+    /// Preview tasks that are waiting to be started, in the order they were
+    /// queued.
+    queued_previews: VecDeque<QueuedPreview>,
+
+    /// The maximum number of preview tasks that may run at once.
+    max_running_previews: usize,
+}
+
+impl Default for AssetDatabaseTasks {
+    fn default() -> Self {
+        // Leave half of the compute threads free for interactive work.
+        let threads = std::thread::available_parallelism().map_or(2, |n| n.get());
+
+        Self {
+            preview_generation: Vec::new(),
+            queued_previews: VecDeque::new(),
+            max_running_previews: (threads / 2).max(1),
+        }
+    }
+}
+
+impl AssetDatabaseTasks {
+    /// Gets the maximum number of preview tasks that may run at once.
+    pub fn max_running_previews(&self) -> usize {
+        self.max_running_previews
+    }
+
+    /// Sets the maximum number of preview tasks that may run at once. The
+    /// limit is at least one.
+    pub fn set_max_running_previews(&mut self, max: usize) {
+        self.max_running_previews = max.max(1);
+    }
+
+    /// Gets the number of preview tasks that are currently running.
+    pub fn running_previews(&self) -> usize {
+        self.preview_generation.len()
+    }
+
+    /// Gets the number of preview tasks that are waiting to be started.
+    pub fn queued_previews(&self) -> usize {
+        self.queued_previews.len()
+    }
+
+    /// Moves the queued preview task of the asset with the specified asset
+    /// record ID into the interactive lane, so that it starts before any bulk
+    /// previews.
+    ///
+    /// Does nothing if the preview is not queued, such as when it is already
+    /// running.
+    pub fn prioritize_preview(&mut self, id: AssetRecordID) {
+        for queued in self.queued_previews.iter_mut() {
+            if queued.id == id {
+                queued.priority = PreviewPriority::Interactive;
+            }
+        }
+    }
+
+    /// Starts queued preview tasks as background jobs until the running limit
+    /// is reached, starting interactive previews first.
+    pub(crate) fn start_queued_previews(&mut self, jobs: &mut BackgroundJobs) {
+        while self.preview_generation.len() < self.max_running_previews {
+            let index = self
+                .queued_previews
+                .iter()
+                .position(|queued| queued.priority == PreviewPriority::Interactive)
+                .unwrap_or(0);
+
+            let Some(queued) = self.queued_previews.remove(index) else {
+                return;
+            };
+
+            debug!(
+                "Starting {:?} preview generation task for asset {}",
+                queued.priority, queued.id
+            );
+            let label = format!("Generating preview for {}", queued.id.short());
+            let future = queued
+                .future
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner);
+            let job = jobs.spawn_with_context(label, queued.context, future);
+            self.preview_generation
+                .push(PreviewTask { id: queued.id, job });
+        }
+    }
+
+    /// Cancels and drops any preview generation tasks for the asset with the
+    /// specified asset record ID, as their results are now out of date.
+    ///
+    /// The priority of a cancelled queued task is returned, so that a task
+    /// replacing it can keep its lane.
+    fn cancel_previews(
+        &mut self,
+        id: AssetRecordID,
+        jobs: &BackgroundJobs,
+    ) -> Option<PreviewPriority> {
+        self.preview_generation.retain(|task| {
+            if task.id != id {
+                return true;
+            }
+
+            debug!(
+                "Cancelling superseded preview generation task for asset {}",
+                id
+            );
+            jobs.cancel(task.job.id());
+            false
+        });
+
+        let mut priority = None;
+        self.queued_previews.retain(|queued| {
+            if queued.id != id {
+                return true;
+            }
+
+            priority = Some(queued.priority);
+            false
+        });
+        priority
+    }
+
+    /// Cancels all running and queued preview generation tasks.
+    fn cancel_all_previews(&mut self, jobs: &BackgroundJobs) {
+        for task in self.preview_generation.drain(..) {
+            jobs.cancel(task.job.id());
+        }
+        self.queued_previews.clear();
+    }
+}
+
+/// The lane a queued preview task waits in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PreviewPriority {
+    /// A preview that the user is waiting on, such as the thumbnail of a
+    /// visible grid cell.
+    Interactive,
+
+    /// A preview generated in the background, such as after a bulk import.
+    #[default]
+    Bulk,
+}
+
+/// The result of a preview generation job.
+pub type PreviewResult = Result<ImagePreviewData, AssetDataError>;
+
+/// A preview task that is waiting to be started.
+struct QueuedPreview {
+    /// The asset that the preview is generated for.
+    id: AssetRecordID,
+
+    /// The lane the task waits in.
+    priority: PreviewPriority,
+
+    /// The context of the job that the preview is generated in.
+    context: JobContext,
+
+    /// The future generating the preview. It is only polled once the task is
+    /// started, and the mutex only makes it shareable while it waits.
+    future: Mutex<PreviewFuture>,
+}
+
+impl std::fmt::Debug for QueuedPreview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueuedPreview")
+            .field("id", &self.id)
+            .field("priority", &self.priority)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A background job that generates the preview of an asset.
+#[derive(Debug)]
+pub(crate) struct PreviewTask {
+    /// The asset that the preview is generated for.
+    pub(crate) id: AssetRecordID,
+
+    /// The job generating the preview.
+    pub(crate) job: JobHandle<PreviewResult>,
 }
 
 /// System parameter for accessing the Awgen asset database.
@@ -36,6 +223,9 @@ where
 
     /// Tasks for managing asset database operations.
     tasks: ResMut<'w, AssetDatabaseTasks>,
+
+    /// The background jobs that previews are generated in.
+    jobs: ResMut<'w, BackgroundJobs>,
 }
 
 impl<'w, Src> AwgenAssets<'w, Src>
@@ -50,8 +240,7 @@ where
     /// watcher system.
     pub fn load_asset<A: AwgenAsset>(&self, id: AssetRecordID) -> Handle<A> {
         debug!("Loading asset {} of type {}", id, A::type_name());
-        let path = format!("{}://{}.data.{}", Src::database_name(), id, A::type_name());
-        self.asset_server.load(path)
+        self.asset_server.load(self.asset_path::<A>(id))
     }
 
     /// Loads the preview image for an asset from the specified source and
@@ -65,13 +254,45 @@ where
     /// watcher system.
     pub fn load_asset_preview(&self, id: AssetRecordID) -> Handle<Image> {
         debug!("Loading preview for asset {}", id);
-        let path = format!(
+        self.asset_server.load(self.preview_path(id))
+    }
+
+    /// Gets the Bevy asset path of the asset of type `A` with the specified
+    /// asset record ID, for code that loads the asset lazily.
+    pub fn asset_path<A: AwgenAsset>(&self, id: AssetRecordID) -> String {
+        format!("{}://{}.data.{}", Src::database_name(), id, A::type_name())
+    }
+
+    /// Gets the Bevy asset path of the thumbnail of the image asset with the
+    /// specified asset record ID, for code that loads the thumbnail lazily.
+    ///
+    /// Thumbnails are larger than previews, at most
+    /// [`THUMBNAIL_SIZE`](crate::loaders::THUMBNAIL_SIZE) pixels on each side,
+    /// but are much smaller than most full images.
+    pub fn thumbnail_path(&self, id: AssetRecordID) -> String {
+        format!(
+            "{}://{}.thumbnail.{}",
+            Src::database_name(),
+            id,
+            Image::type_name()
+        )
+    }
+
+    /// Gets the Bevy asset path of the preview image for the asset with the
+    /// specified asset record ID, for code that loads the preview lazily.
+    pub fn preview_path(&self, id: AssetRecordID) -> String {
+        format!(
             "{}://{}.preview.{}",
             Src::database_name(),
             id,
             Image::type_name()
-        );
-        self.asset_server.load(path)
+        )
+    }
+
+    /// Returns true if the asset database was opened in read-only mode. All
+    /// methods that modify the database will fail in this mode.
+    pub fn is_read_only(&self) -> bool {
+        self.db.is_read_only()
     }
 
     /// Lists all asset records available in the asset database.
@@ -84,6 +305,242 @@ where
         Ok(self.db.get_assets()?)
     }
 
+    /// Lists a page of the asset records in the asset database that pass the
+    /// given filter, sorted by pathname.
+    ///
+    /// Up to `limit` records are returned, after skipping the first `offset`
+    /// records that pass the filter, so that large projects can be browsed one
+    /// page at a time. Use [`AwgenAssets::count_assets_filtered`] to get the
+    /// number of pages.
+    ///
+    /// This method requires a Database query, but only reads the returned
+    /// records.
+    pub fn list_assets_filtered(
+        &self,
+        filter: &AssetFilter,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<ErasedAssetRecord>, AwgenAssetsError> {
+        debug!(
+            "Fetch {} asset records from offset {} matching {:?} from the database",
+            limit, offset, filter
+        );
+        Ok(self.db.get_assets_filtered(filter, limit, offset)?)
+    }
+
+    /// Counts the asset records in the asset database that pass the given
+    /// filter.
+    ///
+    /// This method requires a Database query.
+    pub fn count_assets_filtered(&self, filter: &AssetFilter) -> Result<usize, AwgenAssetsError> {
+        Ok(self.db.count_assets_filtered(filter)?)
+    }
+
+    /// Searches the asset records in the asset database by pathname and module
+    /// name, returning up to `limit` matches, best matches first.
+    ///
+    /// Every word of the query must match the start of a word in the pathname
+    /// or module name, ignoring case, so the results can be updated live while
+    /// a search box is typed in. A query without any words matches nothing.
+    ///
+    /// This method requires a Database query, but only reads the returned
+    /// records.
+    pub fn search_assets(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<ErasedAssetRecord>, AwgenAssetsError> {
+        debug!(
+            "Search up to {} asset records matching {:?} in the database",
+            limit, query
+        );
+        Ok(self.db.search_assets(query, limit)?)
+    }
+
+    /// Retrieves the asset record with the specified ID, if it exists.
+    ///
+    /// This does not include the binary data or asset preview, and requires a
+    /// Database query.
+    pub fn get_asset(
+        &self,
+        id: AssetRecordID,
+    ) -> Result<Option<ErasedAssetRecord>, AwgenAssetsError> {
+        Ok(self.db.get_asset(id)?)
+    }
+
+    /// Lists the most recently created asset records in the asset database,
+    /// newest first, up to the given limit.
+    ///
+    /// This method requires a Database query, but only reads the returned
+    /// records.
+    pub fn list_recent_assets(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<ErasedAssetRecord>, AwgenAssetsError> {
+        debug!(
+            "Fetch the {} most recent asset records from the database",
+            limit
+        );
+        Ok(self.db.get_recent_assets(limit)?)
+    }
+
+    /// Records that the asset with the given ID was opened or used, so that it
+    /// is listed by [`AwgenAssets::list_recent`].
+    ///
+    /// This method requires a Database query.
+    pub fn mark_asset_used(&self, id: AssetRecordID) -> Result<(), AwgenAssetsError> {
+        debug!("Record use of asset {} in the database", id);
+        Ok(self.db.record_asset_use(id)?)
+    }
+
+    /// Lists the most recently used asset records in the asset database, most
+    /// recent first, up to the given limit.
+    ///
+    /// This method requires a Database query, but only reads the returned
+    /// records.
+    pub fn list_recent(&self, limit: usize) -> Result<Vec<ErasedAssetRecord>, AwgenAssetsError> {
+        debug!(
+            "Fetch the {} most recently used asset records from the database",
+            limit
+        );
+        Ok(self.db.get_recently_used_assets(limit)?)
+    }
+
+    /// Gets the number of times the asset with the given ID has been used.
+    ///
+    /// This method requires a Database query.
+    pub fn asset_use_count(&self, id: AssetRecordID) -> Result<u64, AwgenAssetsError> {
+        Ok(self.db.get_asset_use_count(id)?)
+    }
+
+    /// Pins the asset with the given ID as a favorite.
+    ///
+    /// This method requires a Database query.
+    pub fn pin_favorite(&self, id: AssetRecordID) -> Result<(), AwgenAssetsError> {
+        debug!("Pin asset {} as a favorite", id);
+        Ok(self.db.set_asset_favorite(id, true)?)
+    }
+
+    /// Unpins the asset with the given ID as a favorite.
+    ///
+    /// This method requires a Database query.
+    pub fn unpin_favorite(&self, id: AssetRecordID) -> Result<(), AwgenAssetsError> {
+        debug!("Unpin asset {} as a favorite", id);
+        Ok(self.db.set_asset_favorite(id, false)?)
+    }
+
+    /// Returns true if the asset with the given ID is pinned as a favorite.
+    ///
+    /// This method requires a Database query.
+    pub fn is_favorite(&self, id: AssetRecordID) -> Result<bool, AwgenAssetsError> {
+        Ok(self.db.is_asset_favorite(id)?)
+    }
+
+    /// Lists all favorite asset records in the asset database, sorted by
+    /// pathname.
+    ///
+    /// This method requires a Database query.
+    pub fn list_favorites(&self) -> Result<Vec<ErasedAssetRecord>, AwgenAssetsError> {
+        debug!("Fetch all favorite asset records from the database");
+        Ok(self.db.get_favorite_assets()?)
+    }
+
+    /// Gets the value of a setting stored in the asset database, such as the
+    /// layout of a tool that browses it, or `None` if it has not been stored.
+    ///
+    /// This method requires a Database query.
+    pub fn setting(&self, key: &str) -> Result<Option<String>, AwgenAssetsError> {
+        Ok(self.db.get_setting(key)?)
+    }
+
+    /// Stores the value of a setting in the asset database, replacing any
+    /// previous value.
+    ///
+    /// This method requires a Database query.
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<(), AwgenAssetsError> {
+        debug!("Store setting {} in the database", key);
+        Ok(self.db.set_setting(key, value)?)
+    }
+
+    /// Lists all asset records of type `A` available in the asset database.
+    ///
+    /// This method is very slow and should be used sparingly. Values should be
+    /// cached where possible.
+    pub fn list_assets_of<A: AwgenAsset>(&self) -> Result<Vec<AssetRecord<A>>, AwgenAssetsError> {
+        debug!(
+            "Fetch all asset records of type {} from the database",
+            A::type_name()
+        );
+
+        Ok(self
+            .db
+            .get_assets_of_type(A::type_name())?
+            .into_iter()
+            .filter_map(ErasedAssetRecord::typed)
+            .collect())
+    }
+
+    /// Lists all asset records of type `A` that have no preview image, such
+    /// as assets imported without being decoded.
+    ///
+    /// This method requires a Database query and is very slow.
+    pub fn list_assets_missing_preview<A: AwgenAsset>(
+        &self,
+    ) -> Result<Vec<AssetRecord<A>>, AwgenAssetsError> {
+        debug!(
+            "Fetch asset records of type {} without previews from the database",
+            A::type_name()
+        );
+
+        Ok(self
+            .db
+            .get_assets_missing_preview(A::type_name())?
+            .into_iter()
+            .filter_map(ErasedAssetRecord::typed)
+            .collect())
+    }
+
+    /// Counts the number of assets of each type in the asset database, sorted
+    /// by type name.
+    ///
+    /// This method requires a Database query and is very slow.
+    pub fn count_assets_by_type(&self) -> Result<Vec<(String, usize)>, AwgenAssetsError> {
+        debug!("Count asset records by type in the database");
+        Ok(self.db.count_assets_by_type()?)
+    }
+
+    /// Counts the number of assets of each type that have no preview image,
+    /// sorted by type name. Types whose assets all have a preview are omitted.
+    ///
+    /// This method requires a Database query and is very slow.
+    pub fn count_assets_missing_preview_by_type(
+        &self,
+    ) -> Result<Vec<(String, usize)>, AwgenAssetsError> {
+        debug!("Count asset records without previews by type in the database");
+        Ok(self.db.count_assets_missing_preview_by_type()?)
+    }
+
+    /// Resolves an asset ID from either its full or its short form.
+    ///
+    /// Short form IDs are looked up in the database, and must match exactly
+    /// one asset.
+    ///
+    /// This method requires a Database query and is very slow.
+    pub fn resolve_asset_id(&self, id: &str) -> Result<AssetRecordID, AwgenAssetsError> {
+        let prefix = match id.parse::<AssetRecordID>() {
+            Ok(id) => return Ok(id),
+            Err(IdParseError::Incomplete(_)) => id.parse::<IdPrefix>()?,
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut matches = self.db.find_asset_ids(&prefix)?;
+        match matches.len() {
+            0 => Err(AwgenAssetsError::UnknownId(prefix)),
+            1 => Ok(matches.remove(0)),
+            count => Err(AwgenAssetsError::AmbiguousId(prefix, count)),
+        }
+    }
+
     /// Lists all asset modules available in the asset database.
     ///
     /// This method is very slow and should be used sparingly. Values should be
@@ -116,7 +573,7 @@ where
         };
 
         self.db.insert_module(&module)?;
-        info!("Created new asset module {}: {}", id, name);
+        info!("Created new asset module {}: {}", id.short(), name);
 
         Ok(id)
     }
@@ -128,7 +585,7 @@ where
         // TODO: Move this impl into the task pool?
 
         self.db.remove_module(id)?;
-        info!("Removed asset module {}", id);
+        info!("Removed asset module {}", id.short());
 
         Ok(())
     }
@@ -161,16 +618,56 @@ where
 
         info!(
             "Created new asset {} \"{}\" of type {} in module {}",
-            id,
+            id.short(),
             record.pathname.display(),
             A::type_name(),
-            module
+            module.short()
         );
 
         self.update_preview(id, asset);
         Ok(id)
     }
 
+    /// Creates a new asset of type `A` in the specified asset module, streaming
+    /// exactly `size` bytes of already-encoded asset data from the given
+    /// reader.
+    ///
+    /// This is intended for importing large assets, such as audio or meshes,
+    /// without holding the entire asset in memory. Since the asset is never
+    /// decoded, no preview is generated.
+    ///
+    /// This method requires a Database query and is very slow.
+    pub fn create_asset_from_reader<A: AwgenAsset, P: Into<PathBuf>, R: Read>(
+        &self,
+        pathname: P,
+        module: AssetModuleID,
+        size: usize,
+        reader: R,
+    ) -> Result<AssetRecordID, AwgenAssetsError> {
+        let id = AssetRecordID::new();
+        let record = AssetRecord::<A> {
+            id,
+            pathname: pathname.into(),
+            module,
+            created: -1,
+            last_modified: -1,
+            _marker: std::marker::PhantomData,
+        };
+
+        self.db.insert_asset_from_reader(&record, size, reader)?;
+
+        info!(
+            "Imported new asset {} \"{}\" of type {} ({} bytes) in module {}",
+            id.short(),
+            record.pathname.display(),
+            A::type_name(),
+            size,
+            module.short()
+        );
+
+        Ok(id)
+    }
+
     /// Saves the given asset of type `A` into the asset database with the
     /// specified asset record ID, updating the existing asset data.
     ///
@@ -198,13 +695,46 @@ where
         let data = asset.save()?;
         self.db.set_asset_data(id, &data)?;
 
-        info!("Updated asset {} of type {}", id, A::type_name());
+        info!("Updated asset {} of type {}", id.short(), A::type_name());
 
         self.update_preview(id, asset);
 
         Ok(())
     }
 
+    /// Reads the asset of type `A` with the specified asset record ID directly
+    /// from the asset database, decoding it without going through the Bevy
+    /// asset server.
+    ///
+    /// This is useful for tools and tests that need the asset right away.
+    /// Prefer [`AwgenAssets::load_asset`] within the game, as the returned
+    /// asset is not cached or hot-reloaded.
+    ///
+    /// This method requires a Database query and is very slow.
+    pub fn read_asset<A: AwgenAsset>(&self, id: AssetRecordID) -> Result<A, AwgenAssetsError> {
+        let Some(record) = self.db.get_asset(id)? else {
+            return Err(AwgenAssetsError::MissingAsset(id));
+        };
+
+        if record.asset_type != A::type_name() {
+            return Err(AwgenAssetsError::WrongType(
+                A::type_name().to_string(),
+                record.asset_type,
+            ));
+        }
+
+        let Some(data) = self.db.get_asset_data(id)? else {
+            return Err(AwgenAssetsError::MissingAsset(id));
+        };
+
+        debug!(
+            "Read asset {} of type {} from the database",
+            id,
+            A::type_name()
+        );
+        Ok(A::load(&data)?)
+    }
+
     /// Saves the preview image for an asset into the asset database with the
     /// specified asset record ID.
     ///
@@ -222,44 +752,190 @@ where
             let image: Image = preview.into();
             let data = image.save()?;
             self.db.set_asset_preview(id, Some(&data))?;
-            info!("Updated preview for asset {}", id);
+            info!("Updated preview for asset {}", id.short());
         } else {
             self.db.set_asset_preview(id, None)?;
-            info!("Reset preview for asset {}", id);
+            info!("Reset preview for asset {}", id.short());
         }
 
         Ok(())
     }
 
-    /// This method spawns a background task to generate a new preview image for
-    /// the asset with the specified asset record ID, using the provided asset
-    /// data.
+    /// This method queues a background task to generate a new preview image
+    /// for the asset with the specified asset record ID, using the provided
+    /// asset data.
+    ///
+    /// Any preview tasks still running or queued for the same asset are
+    /// cancelled. The new task is queued in the bulk lane, unless it replaces
+    /// a task that was prioritized.
     fn update_preview<A: AwgenAsset>(&mut self, id: AssetRecordID, asset: &A) {
-        debug!("Spawning preview generation task for asset {}", id);
-        let task = A::generate_preview(asset);
-        self.tasks.preview_generation.push((id, task));
+        let priority = self
+            .tasks
+            .cancel_previews(id, &self.jobs)
+            .unwrap_or_default();
+
+        debug!("Queuing preview generation task for asset {}", id);
+        let context = JobContext::pending();
+        let future = asset.generate_preview(context.clone());
+        self.tasks.queued_previews.push_back(QueuedPreview {
+            id,
+            priority,
+            context,
+            future: Mutex::new(future),
+        });
+    }
+
+    /// Reads the asset of type `A` with the specified asset record ID from the
+    /// asset database, and queues a background task to generate a new preview
+    /// image for it.
+    ///
+    /// This method requires a Database query and is very slow.
+    pub fn regenerate_preview<A: AwgenAsset>(
+        &mut self,
+        id: AssetRecordID,
+    ) -> Result<(), AwgenAssetsError> {
+        let asset = self.read_asset::<A>(id)?;
+        self.update_preview(id, &asset);
+        Ok(())
+    }
+
+    /// Gets the number of preview generation tasks that are running or waiting
+    /// to be started.
+    pub fn pending_previews(&self) -> usize {
+        self.tasks.running_previews() + self.tasks.queued_previews()
+    }
+
+    /// Moves the queued preview task of the asset with the specified asset
+    /// record ID ahead of any bulk preview tasks, such as when its thumbnail
+    /// becomes visible.
+    pub fn prioritize_preview(&mut self, id: AssetRecordID) {
+        self.tasks.prioritize_preview(id);
+    }
+
+    /// Duplicates the asset with the specified asset record ID, copying its
+    /// data, preview and metadata into a new asset in the same module.
+    ///
+    /// The copy is given a unique pathname within the module, such as
+    /// `rock (2).png` for a copy of `rock.png`.
+    ///
+    /// This method requires a Database query and is very slow.
+    pub fn duplicate_asset(&self, id: AssetRecordID) -> Result<AssetRecordID, AwgenAssetsError> {
+        let Some(new_id) = self.db.duplicate_asset(id)? else {
+            return Err(AwgenAssetsError::MissingAsset(id));
+        };
+
+        info!("Duplicated asset {} as {}", id.short(), new_id.short());
+        Ok(new_id)
+    }
+
+    /// Changes the pathname of the asset with the specified asset record ID.
+    /// Fails with [`AwgenDbError::PathnameTaken`] if another asset in its
+    /// module already has the new pathname.
+    ///
+    /// Loaded handles of the asset are reloaded, as the asset is reported to
+    /// watchers as modified. The rename is also reported to watchers, along
+    /// with the old and new pathnames of the asset.
+    ///
+    /// This method requires a Database query and is very slow.
+    pub fn rename_asset<P: Into<PathBuf>>(
+        &self,
+        id: AssetRecordID,
+        new_path: P,
+    ) -> Result<(), AwgenAssetsError> {
+        let new_path = new_path.into();
+        if !self.db.rename_asset(id, &new_path)? {
+            return Err(AwgenAssetsError::MissingAsset(id));
+        }
+
+        info!("Renamed asset {} to {}", id.short(), new_path.display());
+        Ok(())
+    }
+
+    /// Moves the asset with the specified asset record ID into another asset
+    /// module, keeping its pathname. Fails with
+    /// [`AwgenDbError::PathnameTaken`] if an asset in the target module
+    /// already has that pathname.
+    ///
+    /// Loaded handles of the asset are reloaded, as the asset is reported to
+    /// watchers as modified. The rename is also reported to watchers, along
+    /// with the old and new pathnames of the asset.
+    ///
+    /// This method requires a Database query and is very slow.
+    pub fn move_asset(
+        &self,
+        id: AssetRecordID,
+        new_module: AssetModuleID,
+    ) -> Result<(), AwgenAssetsError> {
+        if self.db.get_module(new_module)?.is_none() {
+            return Err(AwgenAssetsError::MissingModule(new_module));
+        }
+
+        if !self.db.move_asset(id, new_module)? {
+            return Err(AwgenAssetsError::MissingAsset(id));
+        }
+
+        info!(
+            "Moved asset {} to module {}",
+            id.short(),
+            new_module.short()
+        );
+        Ok(())
     }
 
     /// Deletes the asset with the specified asset record ID from the asset
     /// database.
     ///
     /// This method requires a Database query and is very slow.
-    pub fn delete_asset(&self, id: AssetRecordID) -> Result<(), AwgenAssetsError> {
+    pub fn delete_asset(&mut self, id: AssetRecordID) -> Result<(), AwgenAssetsError> {
         // TODO: Move this impl into the task pool?
 
-        info!("Deleting asset {}", id);
+        info!("Deleting asset {}", id.short());
         self.db.remove_asset(id)?;
+        self.tasks.cancel_previews(id);
+        Ok(())
+    }
+
+    /// Writes a snapshot of the asset database to the database file at the
+    /// given path, replacing its contents if it exists.
+    ///
+    /// The snapshot is taken while the database remains open, so it can be
+    /// used before risky operations.
+    pub fn backup_to(&self, path: &Path) -> Result<(), AwgenAssetsError> {
+        info!("Backing up asset database to {}", path.display());
+        self.db.backup_to(path)?;
         Ok(())
     }
 
-    /// Provides mutable access to the preview generation tasks.
-    pub(crate) fn preview_tasks_mut(
+    /// Replaces the contents of the asset database with the snapshot stored in
+    /// the database file at the given path.
+    ///
+    /// Pending preview generation tasks are cancelled, and all loaded assets
+    /// are reloaded from the restored database.
+    pub fn restore_from(&mut self, path: &Path) -> Result<(), AwgenAssetsError> {
+        info!("Restoring asset database from {}", path.display());
+        self.db.restore_from(path)?;
+
+        self.tasks.cancel_all_previews(&self.jobs);
+        Ok(())
+    }
+
+    /// Removes the running preview generation task with the given job handle,
+    /// returning the asset record ID it was generating a preview for.
+    ///
+    /// Returns `None` if the job does not belong to this asset database, or
+    /// if it was superseded.
+    pub(crate) fn finish_preview_task(
         &mut self,
-    ) -> &mut Vec<(
-        AssetRecordID,
-        Task<Result<ImagePreviewData, AssetDataError>>,
-    )> {
-        &mut self.tasks.preview_generation
+        job: JobHandle<PreviewResult>,
+    ) -> Option<AssetRecordID> {
+        let tasks = &mut self.tasks.preview_generation;
+        let index = tasks.iter().position(|task| task.job == job)?;
+        Some(tasks.remove(index).id)
+    }
+
+    /// Starts queued preview generation tasks, up to the running limit.
+    pub(crate) fn start_queued_previews(&mut self) {
+        self.tasks.start_queued_previews(&mut self.jobs);
     }
 }
 
@@ -281,4 +957,73 @@ pub enum AwgenAssetsError {
     /// The specified asset record was not found.
     #[error("Asset record not found: {0}")]
     MissingAsset(AssetRecordID),
+
+    /// The specified asset module was not found.
+    #[error("Asset module not found: {0}")]
+    MissingModule(AssetModuleID),
+
+    /// An asset ID could not be parsed.
+    #[error("{0}")]
+    InvalidId(#[from] IdParseError),
+
+    /// No asset ID starts with the given prefix.
+    #[error("No asset found with ID {0}")]
+    UnknownId(IdPrefix),
+
+    /// More than one asset ID starts with the given prefix.
+    #[error("Asset ID {0} is ambiguous, matching {1} assets")]
+    AmbiguousId(IdPrefix, usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::RenderAssetUsages;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    use super::*;
+    use crate::{AwgenAssetPlugin, AwgenAssetPluginExt};
+
+    struct TestDatabase;
+    impl AssetDatabaseName for TestDatabase {
+        fn database_name() -> &'static str {
+            "test_database"
+        }
+    }
+
+    #[test]
+    fn stored_image_round_trip() {
+        let mut app_ = App::new();
+        app_.register_asset_db::<TestDatabase, _>(":memory:")
+            .add_plugins((MinimalPlugins, AssetPlugin::default(), AwgenAssetPlugin));
+
+        let size = Extent3d {
+            width: 2,
+            height: 2,
+            depth_or_array_layers: 1,
+        };
+        let pixels = (0 .. 16).collect::<Vec<u8>>();
+        let image = Image::new(
+            size,
+            TextureDimension::D2,
+            pixels.clone(),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+
+        let (loaded, missing) = app_
+            .world_mut()
+            .run_system_once(move |mut assets: AwgenAssets<TestDatabase>| {
+                let module = assets.create_module("Test Module").unwrap();
+                let id = assets.create_asset("test/asset", module, &image).unwrap();
+                let loaded = assets.read_asset::<Image>(id).unwrap();
+                let missing = assets.read_asset::<Image>(AssetRecordID::new());
+                (loaded, missing)
+            })
+            .unwrap();
+
+        assert_eq!(loaded.texture_descriptor.size, size);
+        assert_eq!(loaded.data, Some(pixels));
+        assert!(matches!(missing, Err(AwgenAssetsError::MissingAsset(_))));
+    }
 }
@@ -2,28 +2,71 @@
 
 use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use sqlite::{BindableWithIndex, ParameterIndex, Statement};
 use uuid::Uuid;
 
+use crate::id::{self, IdParseError, IdPrefix, ShortId};
 use crate::loaders::AwgenAsset;
 use crate::module::AssetModuleID;
 
 /// Unique identifier for an asset record.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct AssetRecordID(Uuid);
 
 impl AssetRecordID {
-    /// Creates a new `AssetRecordID` with a generated UUID.
+    /// Creates a new `AssetRecordID` with a generated time-ordered UUID.
+    ///
+    /// IDs are ordered by the time they were generated, even when generated
+    /// within the same millisecond, so sorting assets by ID sorts them by
+    /// creation time. Assets created before IDs were time-ordered have random
+    /// IDs instead.
     #[allow(clippy::new_without_default)]
     pub(crate) fn new() -> Self {
-        AssetRecordID(Uuid::new_v4())
+        AssetRecordID(Uuid::now_v7())
+    }
+
+    /// Returns true if this ID is time-ordered, rather than a random ID of an
+    /// asset created before IDs were time-ordered.
+    pub fn is_time_ordered(&self) -> bool {
+        id::is_time_ordered(&self.0)
     }
 
     /// Creates an `AssetRecordID` from a string representation of a UUID.
     pub(crate) fn from_string<S: AsRef<str>>(s: S) -> Option<Self> {
         Uuid::parse_str(s.as_ref()).ok().map(AssetRecordID)
     }
+
+    /// Gets the short form of this ID, for display in user interfaces and
+    /// logs.
+    pub fn short(&self) -> ShortId {
+        ShortId(self.0)
+    }
+
+    /// Returns true if this ID starts with the given prefix.
+    pub fn matches(&self, prefix: &IdPrefix) -> bool {
+        prefix.matches(&self.0)
+    }
+}
+
+impl FromStr for AssetRecordID {
+    type Err = IdParseError;
+
+    /// Parses a full ID. Short form IDs are rejected with
+    /// [`IdParseError::Incomplete`], since they must be resolved against the
+    /// database.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        id::parse_full(s).map(AssetRecordID)
+    }
+}
+
+impl TryFrom<&str> for AssetRecordID {
+    type Error = IdParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
 }
 
 impl fmt::Display for AssetRecordID {
@@ -74,6 +117,25 @@ pub struct ErasedAssetRecord {
     pub last_modified: i64,
 }
 
+impl ErasedAssetRecord {
+    /// Converts this record into a typed [`AssetRecord`], if the asset type
+    /// matches `A`.
+    pub fn typed<A: AwgenAsset>(self) -> Option<AssetRecord<A>> {
+        if self.asset_type != A::type_name() {
+            return None;
+        }
+
+        Some(AssetRecord {
+            id: self.id,
+            pathname: self.pathname,
+            module: self.module,
+            created: self.created,
+            last_modified: self.last_modified,
+            _marker: std::marker::PhantomData,
+        })
+    }
+}
+
 /// Represents an asset record in the asset database.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct AssetRecord<A: AwgenAsset> {
@@ -1,12 +1,21 @@
 //! The asset source implementation for Awgen asset database.
 
-use std::path::Path;
-
-use bevy::asset::io::{AssetReader, AssetReaderError, AssetWatcher, PathStream, Reader, VecReader};
+use std::path::{Path, PathBuf};
+
+use bevy::asset::io::{
+    AssetReader,
+    AssetReaderError,
+    AssetSourceEvent,
+    AssetWatcher,
+    PathStream,
+    Reader,
+    VecReader,
+};
 use bevy::prelude::*;
 use lazy_static::lazy_static;
 use regex::Regex;
 
+use crate::blob::{AssetBlobReader, BlobColumn};
 use crate::connection::AssetDatabase;
 use crate::loaders::AwgenAsset;
 use crate::prelude::AssetDatabaseName;
@@ -14,7 +23,21 @@ use crate::record::AssetRecordID;
 
 lazy_static! {
     static ref REGEX: Regex =
-        Regex::new(r"^([a-f0-9\-]{36})\.(data|preview).([a-zA-Z0-9_-]+)$").unwrap();
+        Regex::new(r"^([a-f0-9\-]{36})\.(data|preview|thumbnail).([a-zA-Z0-9_-]+)$").unwrap();
+}
+
+/// The part of an asset that an asset path refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PathKind {
+    /// The data of the asset.
+    Data,
+
+    /// The preview image of the asset.
+    Preview,
+
+    /// A thumbnail made from the data of an image asset, which is larger than
+    /// its preview but smaller than the full image.
+    Thumbnail,
 }
 
 /// Asset source that reads and writes assets to the [`AssetDatabase`].
@@ -42,30 +65,17 @@ where
     Src: AssetDatabaseName + Send + Sync + 'static,
 {
     async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
-        let path_str = path.to_string_lossy().to_string();
-        let Some(captures) = REGEX.captures(&path_str) else {
+        let Some((asset_id, kind, asset_type)) = parse_path(path) else {
             return Err(AssetReaderError::NotFound(path.to_path_buf()));
         };
 
-        let uuid = &captures[1];
-        let asset_type = &captures[3];
-        let is_preview = match &captures[2] {
-            "preview" => true,
-            "data" => false,
-            _ => unreachable!(),
-        };
-
-        let asset_id = AssetRecordID::from_string(uuid)
-            .ok_or(AssetReaderError::NotFound(path.to_path_buf()))?;
+        if kind != PathKind::Data && asset_type != Image::type_name() {
+            return Err(AssetReaderError::NotFound(path.to_path_buf()));
+        }
 
-        let data = match is_preview {
-            true => {
-                if asset_type != Image::type_name() {
-                    return Err(AssetReaderError::NotFound(path.to_path_buf()));
-                }
-                self.database.get_asset_preview(asset_id)
-            }
-            false => {
+        let column = match kind {
+            PathKind::Preview => BlobColumn::Preview,
+            PathKind::Data | PathKind::Thumbnail => {
                 let Some(record) = self.database.get_asset(asset_id)? else {
                     return Err(AssetReaderError::NotFound(path.to_path_buf()));
                 };
@@ -74,14 +84,14 @@ where
                     return Err(AssetReaderError::NotFound(path.to_path_buf()));
                 }
 
-                self.database.get_asset_data(asset_id)
+                BlobColumn::Data
             }
         };
 
-        match data {
-            Ok(Some(data)) => Ok(VecReader::new(data)),
-            Ok(None) => Ok(VecReader::new(Vec::new())),
-            Err(e) => Err(e.into()),
+        match self.database.open_asset_blob(asset_id, column)? {
+            Some(reader) => Ok(reader),
+            None if kind == PathKind::Preview => Ok(AssetBlobReader::empty()),
+            None => Err(AssetReaderError::NotFound(path.to_path_buf())),
         }
     }
 
@@ -101,6 +111,60 @@ where
     }
 }
 
+/// Parses an asset path of the form `<id>.<data|preview|thumbnail>.<type>`,
+/// returning the asset ID, the part of the asset the path refers to, and the
+/// asset type.
+pub(crate) fn parse_path(path: &Path) -> Option<(AssetRecordID, PathKind, String)> {
+    let path_str = path.to_string_lossy();
+    let captures = REGEX.captures(&path_str)?;
+
+    let asset_id = AssetRecordID::from_string(&captures[1])?;
+    let kind = match &captures[2] {
+        "preview" => PathKind::Preview,
+        "thumbnail" => PathKind::Thumbnail,
+        _ => PathKind::Data,
+    };
+    Some((asset_id, kind, captures[3].to_string()))
+}
+
+/// Parses a path made by [`rename_path`](crate::connection::rename_path),
+/// returning the asset ID, the asset type, and the pathname of the asset.
+pub(crate) fn parse_rename_path(path: &Path) -> Option<(AssetRecordID, String, PathBuf)> {
+    let mut components = path.components();
+    let data = components.next()?;
+
+    let (id, kind, asset_type) = parse_path(Path::new(data.as_os_str()))?;
+    if kind != PathKind::Data {
+        return None;
+    }
+
+    Some((id, asset_type, components.as_path().to_path_buf()))
+}
+
+/// Gets the event for the thumbnail of the image that the given event refers
+/// to, if it refers to the data of an image asset.
+///
+/// Thumbnails are made from the data of their image, so they are reloaded
+/// whenever their image changes.
+pub(crate) fn thumbnail_event(event: &AssetSourceEvent) -> Option<AssetSourceEvent> {
+    let (make, path): (fn(PathBuf) -> AssetSourceEvent, _) = match event {
+        AssetSourceEvent::AddedAsset(path) => (AssetSourceEvent::AddedAsset, path),
+        AssetSourceEvent::ModifiedAsset(path) => (AssetSourceEvent::ModifiedAsset, path),
+        AssetSourceEvent::RemovedAsset(path) => (AssetSourceEvent::RemovedAsset, path),
+        _ => return None,
+    };
+
+    let (id, kind, asset_type) = parse_path(path)?;
+    if kind != PathKind::Data || asset_type != Image::type_name() {
+        return None;
+    }
+
+    Some(make(PathBuf::from(format!(
+        "{}.thumbnail.{}",
+        id, asset_type
+    ))))
+}
+
 /// Watcher that monitors the asset database for changes.
 pub struct AwgenDbWatcher;
 impl AssetWatcher for AwgenDbWatcher {}
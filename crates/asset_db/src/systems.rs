@@ -1,36 +1,40 @@
 //! this module implements the systems for the Awgen asset database plugin.
 
 use bevy::prelude::*;
-use bevy::tasks::Task;
-use bevy::tasks::futures_lite::future;
 
-use crate::connection::AssetDatabaseName;
-use crate::loaders::{AssetDataError, ImagePreviewData};
-use crate::param::AwgenAssets;
-use crate::record::AssetRecordID;
+use crate::connection::{AssetDatabase, AssetDatabaseName};
+use crate::jobs::{JobFinished, JobResult};
+use crate::param::{AwgenAssets, PreviewResult};
+use crate::watcher::{AssetRecordChanged, AssetWatcherSettings};
 
-/// System to update asset previews for assets whose preview generation tasks
-/// have completed.
+/// System to update asset previews for assets whose preview generation jobs
+/// have finished, and to start queued preview tasks in their place.
+///
+/// Jobs that belong to other asset databases are ignored, as are jobs that
+/// were cancelled, since their previews have been superseded.
 pub(super) fn update_previews<Src>(
-    mut results: Local<Vec<(AssetRecordID, Result<ImagePreviewData, AssetDataError>)>>,
+    mut finished: MessageReader<JobFinished<PreviewResult>>,
     mut assets: AwgenAssets<Src>,
 ) where
     Src: AssetDatabaseName + Send + Sync + 'static,
 {
-    assets
-        .preview_tasks_mut()
-        .retain_mut(|(id, task)| match poll(task) {
-            Some(result) => {
-                results.push((*id, result));
-                false
+    let results = finished
+        .read()
+        .filter_map(|message| {
+            let id = assets.finish_preview_task(message.handle)?;
+            match &message.result {
+                JobResult::Completed(result) => Some((id, result)),
+                JobResult::Cancelled => None,
             }
-            None => true,
-        });
+        })
+        .collect::<Vec<_>>();
+
+    assets.start_queued_previews();
 
-    for (id, result) in results.drain(..) {
+    for (id, result) in results {
         match result {
             Ok(preview) => {
-                if let Err(e) = assets.save_asset_preview(id, Some(preview)) {
+                if let Err(e) = assets.save_asset_preview(id, Some(preview.clone())) {
                     error!("Failed to save preview for asset {}: {}", id, e);
                 }
             }
@@ -44,10 +48,33 @@ pub(super) fn update_previews<Src>(
     }
 }
 
-/// A small helper function to poll a Bevy task.
-///
-/// If the task is complete, it returns `Some` with the result; otherwise,
-/// it returns `None`.
-fn poll<T>(task: &mut Task<T>) -> Option<T> {
-    future::block_on(future::poll_once(task))
+/// System to detect writes made to the asset database by other processes, and
+/// queue change events for them.
+pub(super) fn poll_external_changes<Src>(
+    database: Res<AssetDatabase<Src>>,
+    settings: Option<Res<AssetWatcherSettings>>,
+) where
+    Src: AssetDatabaseName + Send + Sync + 'static,
+{
+    let settings = settings.as_deref().copied().unwrap_or_default();
+    if let Err(e) = database.poll_external_changes(&settings) {
+        error!(
+            "Failed to check the asset database for external changes: {}",
+            e
+        );
+    }
+}
+
+/// System to send debounced asset change events to Bevy's asset watcher, and
+/// to report the changed asset records as messages.
+pub(super) fn flush_watcher_events<Src>(
+    database: Res<AssetDatabase<Src>>,
+    settings: Option<Res<AssetWatcherSettings>>,
+    mut changes: MessageWriter<AssetRecordChanged<Src>>,
+) where
+    Src: AssetDatabaseName + Send + Sync + 'static,
+{
+    let settings = settings.as_deref().copied().unwrap_or_default();
+    let events = database.flush_events(&settings);
+    changes.write_batch(events.iter().filter_map(AssetRecordChanged::from_event));
 }
@@ -1,4 +1,8 @@
-//! This module implements the data structure for a tileset in Awgen.
+//! This module implements the data structure for a tileset in Awgen, and the
+//! `.tiles` file format that tilesets are stored in.
+//!
+//! It is shared by the engine, which builds and loads tilesets, and by tools
+//! such as the asset explorer, which compose them.
 
 use bevy::asset::RenderAssetUsages;
 use bevy::image::{ImageAddressMode, ImageSampler};
@@ -6,6 +10,8 @@ use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 use image::DynamicImage;
 
+use crate::loaders::{MipmapQuality, downsample_rgba8};
+
 /// The magic number that identifies a valid Tileset file.
 pub const MAGIC_NUMBER: &[u8; 13] = b"AWGEN TILESET";
 
@@ -24,6 +30,9 @@ pub struct Tileset {
 
     /// The number of mipmaps generated for each tile in the tileset.
     mipmaps: u32,
+
+    /// The filter used to generate the mipmaps of new tiles.
+    mipmap_quality: MipmapQuality,
 }
 
 impl Tileset {
@@ -34,9 +43,21 @@ impl Tileset {
             size: 0,
             tile_count: 0,
             mipmaps: 0,
+            mipmap_quality: MipmapQuality::default(),
         }
     }
 
+    /// Gets the number of tiles in the tileset.
+    pub fn tile_count(&self) -> u32 {
+        self.tile_count
+    }
+
+    /// Sets the filter used to generate the mipmaps of tiles that are
+    /// appended after this call. Defaults to [`MipmapQuality::GammaCorrect`].
+    pub fn set_mipmap_quality(&mut self, quality: MipmapQuality) {
+        self.mipmap_quality = quality;
+    }
+
     /// Creates a new [`Tileset`] from a binary representation.
     pub fn from_binary(binary: Vec<u8>) -> Result<Self, TilesetError> {
         let mut offset = 0;
@@ -51,6 +72,7 @@ impl Tileset {
             size,
             tile_count,
             mipmaps,
+            mipmap_quality: MipmapQuality::default(),
         };
 
         let expected_binary_len =
@@ -120,40 +142,8 @@ impl Tileset {
 
         let mut size = self.size;
         for _ in 0 .. self.mipmaps {
-            size /= 2;
-            let mut new_pixels = Vec::new();
-
-            for y in 0 .. size {
-                for x in 0 .. size {
-                    let mut r = 0;
-                    let mut g = 0;
-                    let mut b = 0;
-                    let mut a = 0;
-
-                    for j in 0 .. 2 {
-                        for i in 0 .. 2 {
-                            let index = ((y * 2 + j) * size * 2 + x * 2 + i) as usize * 4;
-                            r += pixels[index] as u32;
-                            g += pixels[index + 1] as u32;
-                            b += pixels[index + 2] as u32;
-                            a += pixels[index + 3] as u32;
-                        }
-                    }
-
-                    r /= 4;
-                    g /= 4;
-                    b /= 4;
-                    a /= 4;
-
-                    new_pixels.push(r as u8);
-                    new_pixels.push(g as u8);
-                    new_pixels.push(b as u8);
-                    new_pixels.push(a as u8);
-                }
-            }
-
-            self.binary.extend_from_slice(&new_pixels);
-            pixels = new_pixels;
+            (pixels, size, _) = downsample_rgba8(&pixels, size, size, self.mipmap_quality);
+            self.binary.extend_from_slice(&pixels);
         }
     }
 
@@ -271,6 +261,37 @@ impl TileImage for DynamicImage {
     }
 }
 
+/// Only the base mip level of the first layer of the image is used as the
+/// tile. The image must use an RGBA8 texture format, as checked by
+/// [`is_rgba8`].
+impl TileImage for Image {
+    fn binary(&self) -> Vec<u8> {
+        let bytes = (self.width() * self.height() * 4) as usize;
+        self.data
+            .as_ref()
+            .and_then(|data| data.get(.. bytes))
+            .map(<[u8]>::to_vec)
+            .unwrap_or_default()
+    }
+
+    fn width(&self) -> u32 {
+        self.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.height()
+    }
+}
+
+/// Checks if the given image uses an RGBA8 texture format, which is the only
+/// format that can be added to a tileset as an [`Image`].
+pub fn is_rgba8(image: &Image) -> bool {
+    matches!(
+        image.texture_descriptor.format,
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb
+    )
+}
+
 /// Checks if the given number is a power of two.
 pub fn is_power_of_two(n: u32) -> bool {
     n > 0 && (n & (n - 1)) == 0
@@ -287,6 +308,43 @@ pub fn mipmap_count(size: u32) -> u32 {
     count
 }
 
+/// Extracts a single tile from a tileset image as a standalone 2D image, such
+/// as for displaying the tile in the UI. Mipmaps are not included.
+///
+/// Returns `None` if the tile index is out of range, or if the pixel data of
+/// the tileset is not available in the main world.
+pub fn extract_tile(tileset: &Image, index: u32) -> Option<Image> {
+    let size = tileset.texture_descriptor.size;
+    if index >= size.depth_or_array_layers {
+        return None;
+    }
+
+    let mut tile_bytes = 0;
+    let mut s = size.width;
+    for _ in 0 .. tileset.texture_descriptor.mip_level_count {
+        tile_bytes += s * s * 4;
+        s /= 2;
+    }
+
+    let start = (tile_bytes * index) as usize;
+    let end = start + (size.width * size.width * 4) as usize;
+    let data = tileset.data.as_ref()?.get(start .. end)?.to_vec();
+
+    let mut tile = Image::new(
+        Extent3d {
+            width: size.width,
+            height: size.width,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        tileset.texture_descriptor.format,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    tile.sampler = ImageSampler::nearest();
+    Some(tile)
+}
+
 /// Read the magic number from the given byte slice at the given offset and
 /// increments the offset by the length of the magic number.
 fn read_magic(bytes: &[u8], offset: &mut usize) -> Result<(), TilesetError> {
@@ -313,3 +371,50 @@ fn read_uint(bytes: &[u8], offset: &mut usize) -> Result<u32, TilesetError> {
     *offset += 4;
     Ok(int)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile(size: u32, format: TextureFormat) -> Image {
+        let mut image = Image::new(
+            Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            vec![128; (size * size * 4) as usize],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        image.texture_descriptor.format = format;
+        image
+    }
+
+    #[test]
+    fn image_tiles_round_trip_through_the_file_format() {
+        let mut tileset = Tileset::new();
+        tileset
+            .append_tile(tile(16, TextureFormat::Rgba8UnormSrgb))
+            .unwrap();
+        tileset
+            .append_tile(tile(16, TextureFormat::Rgba8UnormSrgb))
+            .unwrap();
+        assert!(matches!(
+            tileset.append_tile(tile(8, TextureFormat::Rgba8UnormSrgb)),
+            Err(TilesetError::TileSizeMismatch(16, 8))
+        ));
+
+        let loaded = Tileset::from_binary(tileset.as_binary()).unwrap();
+        assert_eq!(loaded.tile_count(), 2);
+        assert_eq!(loaded.as_binary(), tileset.as_binary());
+    }
+
+    #[test]
+    fn only_rgba8_images_are_tiles() {
+        assert!(is_rgba8(&tile(4, TextureFormat::Rgba8UnormSrgb)));
+        assert!(is_rgba8(&tile(4, TextureFormat::Rgba8Unorm)));
+        assert!(!is_rgba8(&tile(4, TextureFormat::Rgba16Float)));
+    }
+}
@@ -0,0 +1,372 @@
+//! This module implements debouncing for asset database change notifications.
+//!
+//! Rapid successive writes to the same asset, such as painting a texture, would
+//! otherwise notify Bevy once per write and cause the asset to be reloaded
+//! repeatedly. Instead, events are queued and merged per asset, and are only
+//! sent once the asset has stopped changing for a short time.
+//!
+//! Writes made by other processes, such as the asset explorer editing a
+//! project database while the game runs, do not produce events on their own.
+//! These are detected by periodically polling the database for external
+//! writes, and are then turned into the same events.
+//!
+//! Once sent, changes to asset records are also reported to systems as
+//! [`AssetRecordChanged`] messages, so that views of the database can be
+//! updated without listing every asset again.
+
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use bevy::asset::io::AssetSourceEvent;
+use bevy::prelude::*;
+
+use crate::connection::{AssetDatabaseName, path_buf};
+use crate::loaders::AwgenAsset;
+use crate::record::{AssetRecordID, ErasedAssetRecord};
+use crate::source::{PathKind, parse_path, parse_rename_path};
+
+/// Settings for how asset database change notifications are debounced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct AssetWatcherSettings {
+    /// How long an asset must go without changes before its queued event is
+    /// sent. Each new change to the asset restarts this window.
+    ///
+    /// Setting this to zero sends queued events on the next frame.
+    pub debounce: Duration,
+
+    /// The longest an event may be held back while an asset keeps changing.
+    /// This ensures that continuous edits still become visible periodically.
+    pub max_delay: Duration,
+
+    /// How often the database is checked for writes made by other processes,
+    /// or `None` to disable the check.
+    ///
+    /// Each check is a single cheap query, unless a write is detected, in which
+    /// case all asset records are read to find what changed.
+    pub external_poll: Option<Duration>,
+}
+
+impl Default for AssetWatcherSettings {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            external_poll: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+/// A message sent when an asset record in the database `Src` is added,
+/// modified or removed.
+///
+/// These are sent after debouncing, alongside the events sent to Bevy's asset
+/// watcher, and include writes made by other processes. Changes to asset
+/// previews are not reported.
+#[derive(Debug, Message)]
+pub struct AssetRecordChanged<Src: AssetDatabaseName> {
+    /// The ID of the changed asset.
+    pub id: AssetRecordID,
+
+    /// The type of the changed asset. For removed assets, this is the type the
+    /// asset had before it was removed.
+    pub asset_type: String,
+
+    /// How the asset record changed.
+    pub change: AssetRecordChange,
+
+    /// Marker for the asset source type.
+    _marker: PhantomData<Src>,
+}
+
+impl<Src: AssetDatabaseName> AssetRecordChanged<Src> {
+    /// Creates a message for the asset record that the given event refers to,
+    /// or `None` if the event does not refer to the data of a single asset.
+    pub(crate) fn from_event(event: &AssetSourceEvent) -> Option<Self> {
+        let (change, path) = match event {
+            AssetSourceEvent::AddedAsset(path) => (AssetRecordChange::Added, path),
+            AssetSourceEvent::ModifiedAsset(path) => (AssetRecordChange::Modified, path),
+            AssetSourceEvent::RemovedAsset(path) => (AssetRecordChange::Removed, path),
+            AssetSourceEvent::RenamedAsset { old, new } => {
+                let (id, asset_type, old) = parse_rename_path(old)?;
+                let (_, _, new) = parse_rename_path(new)?;
+                return Some(Self {
+                    id,
+                    asset_type,
+                    change: AssetRecordChange::Renamed { old, new },
+                    _marker: PhantomData,
+                });
+            }
+            _ => return None,
+        };
+
+        let (id, kind, asset_type) = parse_path(path)?;
+        if kind != PathKind::Data {
+            return None;
+        }
+
+        Some(Self {
+            id,
+            asset_type,
+            change,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// How an asset record changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetRecordChange {
+    /// The asset was added, or its type was changed.
+    Added,
+
+    /// The data or metadata of the asset was modified.
+    Modified,
+
+    /// The asset was removed, or its type was changed.
+    Removed,
+
+    /// The asset was renamed or moved to another module. This is reported
+    /// alongside the modification of the asset.
+    Renamed {
+        /// The pathname of the asset before it was renamed.
+        old: PathBuf,
+
+        /// The pathname of the asset after it was renamed.
+        new: PathBuf,
+    },
+}
+
+/// A single queued event for an asset path.
+#[derive(Debug)]
+struct PendingEvent {
+    /// The merged event to send.
+    event: AssetSourceEvent,
+
+    /// The order in which the first event in this batch was queued, relative
+    /// to the other queued events.
+    sequence: u64,
+
+    /// When the first event in this batch was queued.
+    first: Instant,
+
+    /// When the most recent event in this batch was queued.
+    last: Instant,
+}
+
+/// A queue of change events, merged per asset path.
+#[derive(Debug, Default)]
+pub(crate) struct PendingEvents {
+    /// The queued events, keyed by asset path.
+    events: HashMap<PathBuf, PendingEvent>,
+
+    /// Events that are not associated with a single asset path, along with
+    /// the order in which they were queued.
+    passthrough: Vec<(u64, AssetSourceEvent)>,
+
+    /// The order assigned to the next queued batch.
+    next_sequence: u64,
+}
+
+impl PendingEvents {
+    /// Queues an event, merging it with any pending event for the same asset.
+    pub(crate) fn push(&mut self, event: AssetSourceEvent) {
+        let now = Instant::now();
+
+        let Some(path) = event_path(&event).cloned() else {
+            let sequence = self.next_sequence();
+            self.passthrough.push((sequence, event));
+            return;
+        };
+
+        match self.events.get_mut(&path) {
+            Some(pending) => {
+                pending.event = merge(&pending.event, event);
+                pending.last = now;
+            }
+            None => {
+                let sequence = self.next_sequence();
+                self.events.insert(
+                    path,
+                    PendingEvent {
+                        event,
+                        sequence,
+                        first: now,
+                        last: now,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Removes and returns all events that are ready to be sent at the given
+    /// time, according to the given settings.
+    ///
+    /// Events are returned in the order their batches were first queued.
+    pub(crate) fn drain_ready(
+        &mut self,
+        now: Instant,
+        settings: &AssetWatcherSettings,
+    ) -> Vec<AssetSourceEvent> {
+        let mut ready = std::mem::take(&mut self.passthrough);
+
+        self.events.retain(|_, pending| {
+            let settled = now.duration_since(pending.last) >= settings.debounce;
+            let overdue = now.duration_since(pending.first) >= settings.max_delay;
+
+            if settled || overdue {
+                ready.push((pending.sequence, pending.event.clone()));
+                false
+            } else {
+                true
+            }
+        });
+
+        ready.sort_by_key(|(sequence, _)| *sequence);
+        ready.into_iter().map(|(_, event)| event).collect()
+    }
+
+    /// Takes the order for a newly queued batch.
+    fn next_sequence(&mut self) -> u64 {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        sequence
+    }
+}
+
+/// Tracks the state of the database as seen by this process, in order to
+/// detect writes made by other processes.
+#[derive(Debug, Default)]
+pub(crate) struct ExternalChanges {
+    /// The `data_version` of the database as of the last check.
+    data_version: Option<i64>,
+
+    /// The type and last modified time of each asset as of the last snapshot,
+    /// or `None` before the first snapshot.
+    snapshot: Option<BTreeMap<AssetRecordID, (String, i64)>>,
+
+    /// When the database was last checked.
+    last_poll: Option<Instant>,
+}
+
+impl ExternalChanges {
+    /// Returns true if the database should be checked at the given time,
+    /// according to the given settings, and records the check if so.
+    pub(crate) fn should_poll(&mut self, now: Instant, settings: &AssetWatcherSettings) -> bool {
+        let Some(interval) = settings.external_poll else {
+            return false;
+        };
+
+        if let Some(last) = self.last_poll {
+            if now.duration_since(last) < interval {
+                return false;
+            }
+        }
+
+        self.last_poll = Some(now);
+        true
+    }
+
+    /// Records the `data_version` read from the database, returning true if
+    /// the database was written to by another process since the last check.
+    ///
+    /// The first version read is always reported as changed, so that the
+    /// first snapshot is taken.
+    pub(crate) fn update_version(&mut self, version: i64) -> bool {
+        let changed = self.data_version != Some(version);
+        self.data_version = Some(version);
+        changed
+    }
+
+    /// Replaces the snapshot with the given asset records, returning events
+    /// for each asset that was added, modified or removed since the previous
+    /// snapshot.
+    ///
+    /// The first snapshot produces no events. Assets changed by this process
+    /// since the previous snapshot are reported as well, which only causes
+    /// them to be reloaded once more. Removals are reported first, followed by
+    /// additions and modifications, each in asset ID order.
+    pub(crate) fn diff(&mut self, assets: &[ErasedAssetRecord]) -> Vec<AssetSourceEvent> {
+        let new = assets
+            .iter()
+            .map(|asset| (asset.id, (asset.asset_type.clone(), asset.last_modified)))
+            .collect::<BTreeMap<_, _>>();
+
+        let Some(old) = self.snapshot.take() else {
+            self.snapshot = Some(new);
+            return Vec::new();
+        };
+
+        let mut events = Vec::new();
+        for (id, (asset_type, _)) in old.iter() {
+            if new
+                .get(id)
+                .is_some_and(|(new_type, _)| new_type == asset_type)
+            {
+                continue;
+            }
+
+            events.push(AssetSourceEvent::RemovedAsset(path_buf(
+                *id, false, asset_type,
+            )));
+            if !new.contains_key(id) {
+                events.push(AssetSourceEvent::RemovedAsset(path_buf(
+                    *id,
+                    true,
+                    Image::type_name(),
+                )));
+            }
+        }
+
+        for (id, (asset_type, last_modified)) in new.iter() {
+            let preview = path_buf(*id, true, Image::type_name());
+            let data = path_buf(*id, false, asset_type);
+
+            match old.get(id) {
+                None => {
+                    events.push(AssetSourceEvent::AddedAsset(preview));
+                    events.push(AssetSourceEvent::AddedAsset(data));
+                }
+                Some((old_type, _)) if old_type != asset_type => {
+                    events.push(AssetSourceEvent::ModifiedAsset(preview));
+                    events.push(AssetSourceEvent::AddedAsset(data));
+                }
+                Some((_, old_modified)) if old_modified != last_modified => {
+                    events.push(AssetSourceEvent::ModifiedAsset(preview));
+                    events.push(AssetSourceEvent::ModifiedAsset(data));
+                }
+                Some(_) => {}
+            }
+        }
+
+        self.snapshot = Some(new);
+        events
+    }
+}
+
+/// Gets the asset path that an event refers to, if it refers to a single asset.
+///
+/// Rename events are not merged, as they carry the pathnames of the asset
+/// rather than its path, and must not replace its pending modification.
+fn event_path(event: &AssetSourceEvent) -> Option<&PathBuf> {
+    match event {
+        AssetSourceEvent::AddedAsset(path)
+        | AssetSourceEvent::ModifiedAsset(path)
+        | AssetSourceEvent::RemovedAsset(path) => Some(path),
+        _ => None,
+    }
+}
+
+/// Merges a new event into a pending event for the same asset.
+///
+/// The most recent event wins, except that a modification does not hide the
+/// fact that the asset was newly added within the same batch.
+fn merge(pending: &AssetSourceEvent, event: AssetSourceEvent) -> AssetSourceEvent {
+    match (pending, event) {
+        (AssetSourceEvent::AddedAsset(_), AssetSourceEvent::ModifiedAsset(path)) => {
+            AssetSourceEvent::AddedAsset(path)
+        }
+        (_, event) => event,
+    }
+}
@@ -0,0 +1,25 @@
+//! A deterministic random number generator shared by the benchmarks.
+
+/// A small, deterministic xorshift random number generator, so benchmark
+/// inputs are identical between runs.
+pub struct Rng(u64);
+
+impl Rng {
+    /// Creates a new random number generator with the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    /// Returns the next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a random number in the range `0.0 .. 1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
@@ -0,0 +1,70 @@
+//! Benchmarks for chunk mesh generation.
+
+use awgen::map::{SectionMask, SectionMeshes, WorldPos, build_mesh};
+use awgen::tiles::TilePalette;
+use bevy::prelude::*;
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+
+mod common;
+mod world;
+
+/// Benchmarks meshing a single chunk at varied fill densities.
+fn bench_chunk_density(c: &mut Criterion) {
+    let palette = TilePalette::default();
+    let mut group = c.benchmark_group("mesh_chunk");
+
+    for density in [0.0, 0.1, 0.25, 0.5, 0.75, 1.0] {
+        let chunk = world::random_chunk(density, 42);
+        group.bench_with_input(BenchmarkId::from_parameter(density), &chunk, |b, chunk| {
+            b.iter(|| build_mesh(chunk, &palette))
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmarks remeshing a dense chunk after a single block edit, where only the
+/// sections around the block are rebuilt before merging.
+fn bench_single_block_edit(c: &mut Criterion) {
+    let palette = TilePalette::default();
+    let chunk = world::random_chunk(1.0, 42);
+
+    let mut sections = SectionMeshes::default();
+    sections.rebuild(&chunk, &palette, SectionMask::ALL);
+
+    let mut dirty = SectionMask::NONE;
+    dirty.insert_block(WorldPos::new(8, 8, 8));
+
+    c.bench_function("mesh_chunk_edit", |b| {
+        b.iter(|| {
+            sections.rebuild(&chunk, &palette, dirty);
+            sections.merge()
+        })
+    });
+}
+
+/// Benchmarks meshing every chunk of a large synthetic terrain world.
+fn bench_terrain_world(c: &mut Criterion) {
+    let world = world::terrain_world(UVec3::new(8, 4, 8), 42);
+    let palette = TilePalette::default();
+
+    let mut group = c.benchmark_group("mesh_world");
+    group.sample_size(10);
+    group.bench_function("terrain_8x4x8", |b| {
+        b.iter(|| {
+            world
+                .iter()
+                .map(|chunk| build_mesh(chunk, &palette))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_chunk_density,
+    bench_single_block_edit,
+    bench_terrain_world
+);
+criterion_main!(benches);
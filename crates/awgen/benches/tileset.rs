@@ -0,0 +1,81 @@
+//! Benchmarks for building tilesets, including mipmap generation.
+
+use awgen::tiles::{TileImage, Tileset};
+use awgen_asset_db::loaders::MipmapQuality;
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+
+mod common;
+
+/// A procedurally generated tile image.
+struct NoiseTile {
+    /// The size of the tile in pixels.
+    size: u32,
+
+    /// The seed used to generate the tile.
+    seed: u64,
+}
+
+impl TileImage for NoiseTile {
+    fn binary(&self) -> Vec<u8> {
+        let mut rng = common::Rng::new(self.seed);
+        (0 .. self.size * self.size * 4)
+            .map(|_| (rng.next_f32() * 256.0) as u8)
+            .collect()
+    }
+
+    fn width(&self) -> u32 {
+        self.size
+    }
+
+    fn height(&self) -> u32 {
+        self.size
+    }
+}
+
+/// Benchmarks appending tiles of different sizes to a tileset, with each
+/// mipmap filter.
+fn bench_build_tileset(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_tileset");
+
+    for (name, quality) in [
+        ("fast", MipmapQuality::Fast),
+        ("gamma_correct", MipmapQuality::GammaCorrect),
+    ] {
+        for size in [16, 64, 256] {
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &size| {
+                b.iter(|| {
+                    let mut tileset = Tileset::new();
+                    tileset.set_mipmap_quality(quality);
+                    for seed in 1 ..= 32 {
+                        tileset
+                            .append_tile(NoiseTile { size, seed })
+                            .expect("Failed to append tile");
+                    }
+                    tileset
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Benchmarks serializing and deserializing a tileset.
+fn bench_tileset_binary(c: &mut Criterion) {
+    let mut tileset = Tileset::new();
+    for seed in 1 ..= 32 {
+        tileset
+            .append_tile(NoiseTile { size: 64, seed })
+            .expect("Failed to append tile");
+    }
+
+    c.bench_function("tileset_as_binary", |b| b.iter(|| tileset.as_binary()));
+
+    let binary = tileset.as_binary();
+    c.bench_function("tileset_from_binary", |b| {
+        b.iter(|| Tileset::from_binary(binary.clone()).expect("Failed to read tileset"))
+    });
+}
+
+criterion_group!(benches, bench_build_tileset, bench_tileset_binary);
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+//! A synthetic world generator for the meshing benchmarks.
+
+use awgen::map::{BlockModel, CHUNK_SIZE, ChunkModels, Cube, TileFace, WorldPos};
+use bevy::prelude::*;
+
+use crate::common::Rng;
+
+/// Creates a cube block model with a random tile on each face.
+pub fn random_cube(rng: &mut Rng, tile_count: u32) -> BlockModel {
+    let mut face = || TileFace {
+        tile_index: (rng.next_u64() % tile_count as u64) as u32,
+        rotation: Mat2::IDENTITY,
+        uv_window: None,
+    };
+
+    BlockModel::Cube(Cube {
+        pos_y: face(),
+        pos_z: face(),
+        neg_z: face(),
+        pos_x: face(),
+        neg_x: face(),
+    })
+}
+
+/// Generates a chunk where each block has a `density` chance of being a cube.
+pub fn random_chunk(density: f32, seed: u64) -> ChunkModels {
+    let mut rng = Rng::new(seed);
+    let mut chunk = ChunkModels::default();
+
+    for x in 0 .. CHUNK_SIZE as i32 {
+        for y in 0 .. CHUNK_SIZE as i32 {
+            for z in 0 .. CHUNK_SIZE as i32 {
+                if rng.next_f32() < density {
+                    *chunk.get_mut(WorldPos::new(x, y, z)) = random_cube(&mut rng, 16);
+                }
+            }
+        }
+    }
+
+    chunk
+}
+
+/// Generates a rolling terrain world of `size.x * size.z` chunk columns, each
+/// `size.y` chunks tall. The terrain height is a sum of sine waves, so the
+/// surface has a realistic mix of flat areas, slopes, and overhang-free
+/// cliffs.
+pub fn terrain_world(size: UVec3, seed: u64) -> Vec<ChunkModels> {
+    let mut rng = Rng::new(seed);
+    let phase = Vec2::new(rng.next_f32(), rng.next_f32()) * std::f32::consts::TAU;
+    let max_height = (size.y * CHUNK_SIZE as u32) as f32;
+
+    let mut chunks = Vec::new();
+    for cx in 0 .. size.x as i32 {
+        for cy in 0 .. size.y as i32 {
+            for cz in 0 .. size.z as i32 {
+                let mut chunk = ChunkModels::default();
+                let origin = IVec3::new(cx, cy, cz) * CHUNK_SIZE as i32;
+
+                for x in 0 .. CHUNK_SIZE as i32 {
+                    for z in 0 .. CHUNK_SIZE as i32 {
+                        let wx = (origin.x + x) as f32;
+                        let wz = (origin.z + z) as f32;
+                        let wave = (wx * 0.07 + phase.x).sin() * 0.5
+                            + (wz * 0.05 + phase.y).cos() * 0.35
+                            + ((wx + wz) * 0.13).sin() * 0.15;
+                        let height = ((wave * 0.5 + 0.5) * max_height) as i32;
+
+                        for y in 0 .. CHUNK_SIZE as i32 {
+                            if origin.y + y <= height {
+                                *chunk.get_mut(WorldPos::new(x, y, z)) = random_cube(&mut rng, 16);
+                            }
+                        }
+                    }
+                }
+
+                chunks.push(chunk);
+            }
+        }
+    }
+
+    chunks
+}
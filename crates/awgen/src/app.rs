@@ -1,17 +1,28 @@
 //! This module prepares and launches the Bevy framework.
 
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use awgen_asset_db::prelude::{AwgenAssetPlugin, AwgenAssetPluginExt};
+use awgen_ui::assets::AwgenUiAssetsExt;
 use bevy::asset::io::AssetSourceBuilder;
 use bevy::log::LogPlugin;
 use bevy::prelude::*;
 use bevy::window::{PresentMode, WindowMode};
-use bevy::winit::WinitSettings;
 
-use crate::map::MapPlugin;
+use crate::crash::CrashReportPlugin;
+use crate::database::backup::GameDatabaseRestored;
+use crate::database::{Database, GameDatabase, ProjectAssets};
+use crate::framepace::FramePacePlugin;
+use crate::jobs::BackgroundJobsPlugin;
+use crate::logging::{self, DefaultLogFilter};
+use crate::map::{MapPlugin, MapSettings};
 use crate::scripts::{ScriptEnginePlugin, ScriptSockets};
+use crate::shutdown::ShutdownPlugin;
+use crate::telemetry::TelemetryPlugin;
 use crate::tiles::TilesetPlugin;
 use crate::ux::UxPlugin;
+use crate::window_state::WindowStatePlugin;
 
 /// Settings for initializing the game.
 #[derive(Debug)]
@@ -36,15 +47,44 @@ pub struct GameInitSettings {
 
     /// Whether or not to launch the game in editor mode.
     pub editor: bool,
+
+    /// Whether or not the project was opened in read-only mode.
+    pub read_only: bool,
+
+    /// The tick rate, in ticks per second, to run game logic at in fixed-tick
+    /// mode. If `None`, script packets are processed once per frame.
+    pub tick_rate: Option<f64>,
 }
 
 #[derive(Debug, Resource)]
 pub struct ProjectSettings {
     /// The project folder.
     project_folder: PathBuf,
+
+    /// Whether the project was opened in read-only mode.
+    read_only: bool,
 }
 
 impl ProjectSettings {
+    /// Creates new project settings for the given project folder.
+    pub fn new(project_folder: impl Into<PathBuf>) -> Self {
+        Self {
+            project_folder: project_folder.into(),
+            read_only: false,
+        }
+    }
+
+    /// Sets whether the project was opened in read-only mode.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Returns true if the project was opened in read-only mode.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     /// Gets the project folder path.
     pub fn project_folder(&self) -> &Path {
         self.project_folder.as_path()
@@ -69,7 +109,7 @@ pub enum AwgenState {
 
 /// Launch a new game window with the Bevy framework, setting up the
 /// necessary plugins and resources.
-pub fn run(settings: GameInitSettings, sockets: ScriptSockets) -> AppExit {
+pub fn run(settings: GameInitSettings, sockets: ScriptSockets, database: Arc<Database>) -> AppExit {
     let window_title = format!(
         "{} - {}{}",
         settings.name,
@@ -97,17 +137,40 @@ pub fn run(settings: GameInitSettings, sockets: ScriptSockets) -> AppExit {
         WindowMode::Windowed
     };
 
-    let project_settings = ProjectSettings {
-        project_folder: PathBuf::from(settings.project_folder.clone()),
+    let map_settings = match MapSettings::load(&database) {
+        Ok(map_settings) => map_settings,
+        Err(err) => {
+            // The log plugin is not running yet.
+            eprintln!("Failed to load the map settings: {}", err);
+            return AppExit::from_code(1);
+        }
     };
 
+    let project_settings =
+        ProjectSettings::new(settings.project_folder.clone()).with_read_only(settings.read_only);
+
+    let mut script_plugin = ScriptEnginePlugin::new(sockets);
+    if let Some(tick_rate) = settings.tick_rate {
+        script_plugin = script_plugin.with_fixed_tick(tick_rate);
+    }
+
     let game_assets = format!("{}/assets", settings.project_folder);
     let editor_assets = format!("{}/editor/assets", settings.project_folder,);
+    let ui_assets = PathBuf::from(&settings.project_folder).join("editor/assets/ui");
+    let project_assets = PathBuf::from(&settings.project_folder).join("game.awgen");
+
+    let mut app_ = App::new();
+    if settings.read_only {
+        app_.register_read_only_asset_db::<ProjectAssets, _>(project_assets);
+    } else {
+        app_.register_asset_db::<ProjectAssets, _>(project_assets);
+    }
 
-    App::new()
-        .insert_resource(ClearColor(Color::BLACK))
-        .insert_resource(WinitSettings::game())
+    app_.insert_resource(ClearColor(Color::BLACK))
         .insert_resource(project_settings)
+        .insert_resource(map_settings)
+        .insert_resource(GameDatabase(database))
+        .insert_resource(DefaultLogFilter::from_level(debug_level))
         .register_asset_source(
             "game",
             AssetSourceBuilder::platform_default(&game_assets, None),
@@ -116,6 +179,7 @@ pub fn run(settings: GameInitSettings, sockets: ScriptSockets) -> AppExit {
             "editor",
             AssetSourceBuilder::platform_default(&editor_assets, None),
         )
+        .register_ui_assets(Some(ui_assets))
         .add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
@@ -126,25 +190,44 @@ pub fn run(settings: GameInitSettings, sockets: ScriptSockets) -> AppExit {
                         present_mode,
                         ..default()
                     }),
+                    close_when_requested: false,
                     ..default()
                 })
                 .set(LogPlugin {
-                    level: debug_level,
-                    filter: "wgpu=error,naga=warn,calloop=debug,polling=debug,cosmic_text=info"
-                        .to_string(),
+                    // The runtime log filter decides which logs are shown.
+                    level: bevy::log::Level::TRACE,
+                    filter: "trace".to_string(),
+                    custom_layer: logging::log_layer,
+                    ..default()
+                })
+                .set(AssetPlugin {
+                    // Hot reload assets, such as tilesets, while editing.
+                    watch_for_changes_override: Some(settings.editor),
                     ..default()
                 })
                 .set(ImagePlugin::default_nearest()),
         )
         .insert_state(AwgenState::Init(settings.editor))
+        .add_message::<GameDatabaseRestored>()
         .add_plugins((
-            ScriptEnginePlugin::new(sockets),
+            script_plugin,
+            AwgenAssetPlugin,
+            BackgroundJobsPlugin,
+            FramePacePlugin,
+            WindowStatePlugin,
+            ShutdownPlugin,
+            CrashReportPlugin,
+            TelemetryPlugin,
             TilesetPlugin,
             MapPlugin,
             UxPlugin,
         ))
-        .add_systems(Last, finish_init)
-        .run()
+        .add_systems(Last, finish_init);
+
+    #[cfg(feature = "networking")]
+    app_.add_plugins(crate::net::NetPlugin);
+
+    app_.run()
 }
 
 /// Finishes initialization and transitions to the next state.
@@ -0,0 +1,352 @@
+//! This module implements the crash reporter, which writes a report to the
+//! project folder whenever the engine panics.
+//!
+//! A report contains the panic message and backtrace, the most recent log
+//! lines, and a snapshot of the engine state. The next time the project is
+//! opened, a dialog offers to show the report, so it can be attached to a bug
+//! report.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, io};
+
+use awgen_ui::menus::layers::UiLayer;
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::button::{ButtonBuilder, ButtonContent, button};
+use bevy::log::BoxedLayer;
+use bevy::log::tracing::field::{Field, Visit};
+use bevy::log::tracing::{Event, Subscriber};
+use bevy::log::tracing_subscriber::Layer;
+use bevy::log::tracing_subscriber::layer::Context;
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+
+use crate::app::ProjectSettings;
+use crate::map::ChunkTable;
+use crate::scripts::ScriptEngine;
+
+/// The folder, relative to the project folder, that crash reports are written
+/// to.
+pub const REPORTS_FOLDER: &str = "crash_reports";
+
+/// The file within the reports folder that names the report that has not been
+/// shown to the user yet.
+const PENDING_FILE: &str = "pending";
+
+/// The number of recent log lines that are kept for crash reports.
+const LOG_HISTORY: usize = 200;
+
+/// The most recent log lines, oldest first.
+static RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// The engine state included in crash reports.
+static CRASH_CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext {
+    project_folder: None,
+    chunk_count: 0,
+    scripts_running: false,
+});
+
+/// A snapshot of the engine state, updated every frame.
+#[derive(Debug)]
+struct CrashContext {
+    /// The project folder, once known.
+    project_folder: Option<PathBuf>,
+
+    /// The number of loaded chunks.
+    chunk_count: usize,
+
+    /// Whether the script engine thread is still running.
+    scripts_running: bool,
+}
+
+/// Plugin that keeps the crash report state up to date, and shows the report
+/// of the previous session if it crashed.
+pub struct CrashReportPlugin;
+impl Plugin for CrashReportPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_systems(Startup, show_pending_report)
+            .add_systems(Last, update_crash_context)
+            .add_observer(on_report_activate);
+    }
+}
+
+/// Installs a panic hook that writes a crash report to the given project
+/// folder before running the default panic hook.
+pub fn install_panic_hook(project_folder: impl Into<PathBuf>) {
+    lock(&CRASH_CONTEXT).project_folder = Some(project_folder.into());
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match write_report(info) {
+            Ok(Some(path)) => eprintln!("A crash report was written to {}", path.display()),
+            Ok(None) => {}
+            Err(err) => eprintln!("Failed to write crash report: {}", err),
+        }
+        default_hook(info);
+    }));
+}
+
+/// Creates the log layer that records recent log lines for crash reports.
+///
+/// This is intended to be used as the `custom_layer` of the `LogPlugin`.
+pub fn log_layer(_app: &mut App) -> Option<BoxedLayer> {
+    Some(Box::new(RecentLogLayer))
+}
+
+/// Locks a mutex, ignoring poisoning, as the data is still useful for a crash
+/// report after another thread panicked.
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// A log layer that keeps the most recent log lines in memory.
+struct RecentLogLayer;
+
+impl<S: Subscriber> Layer<S> for RecentLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut line = format!("{:>5} {}:", metadata.level(), metadata.target());
+        event.record(&mut LineVisitor(&mut line));
+
+        let mut logs = lock(&RECENT_LOGS);
+        if logs.len() >= LOG_HISTORY {
+            logs.pop_front();
+        }
+        logs.push_back(line);
+    }
+}
+
+/// Writes the fields of a log event onto a single line.
+struct LineVisitor<'a>(&'a mut String);
+
+impl Visit for LineVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {:?}", value);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Writes a crash report for the given panic, returning the path of the
+/// report, or `None` if the project folder is not known yet.
+fn write_report(info: &PanicHookInfo) -> io::Result<Option<PathBuf>> {
+    // A panic while the context is locked would otherwise deadlock here.
+    let Ok(context) = CRASH_CONTEXT.try_lock() else {
+        return Ok(None);
+    };
+    let Some(project_folder) = context.project_folder.clone() else {
+        return Ok(None);
+    };
+
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<unknown>".to_string());
+
+    let location = info
+        .location()
+        .map(|location| location.to_string())
+        .unwrap_or_else(|| "<unknown>".to_string());
+
+    let thread = std::thread::current();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    let mut report = String::new();
+    let _ = writeln!(report, "Awgen crash report");
+    let _ = writeln!(report, "Version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "Time: {} (seconds since the Unix epoch)", timestamp);
+    let _ = writeln!(report, "Project: {}", project_folder.display());
+    let _ = writeln!(report, "Loaded chunks: {}", context.chunk_count);
+    let scripts = if context.scripts_running {
+        "running"
+    } else {
+        "stopped"
+    };
+    let _ = writeln!(report, "Script engine: {}", scripts);
+    drop(context);
+
+    let thread_name = thread.name().unwrap_or("<unnamed>");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "Panic in thread '{}' at {}:", thread_name, location);
+    let _ = writeln!(report, "{}", message);
+    let _ = writeln!(report);
+    let _ = writeln!(report, "Backtrace:");
+    let _ = writeln!(report, "{}", std::backtrace::Backtrace::force_capture());
+
+    let _ = writeln!(report, "Recent log lines:");
+    if let Ok(logs) = RECENT_LOGS.try_lock() {
+        for line in logs.iter() {
+            let _ = writeln!(report, "{}", line);
+        }
+    }
+
+    let folder = project_folder.join(REPORTS_FOLDER);
+    fs::create_dir_all(&folder)?;
+
+    let file_name = format!("crash_{}.txt", timestamp);
+    let path = folder.join(&file_name);
+    fs::write(&path, report)?;
+    fs::write(folder.join(PENDING_FILE), file_name)?;
+
+    Ok(Some(path))
+}
+
+/// Takes the path of the crash report that has not been shown yet, if any.
+fn take_pending_report(project_folder: &Path) -> Option<PathBuf> {
+    let folder = project_folder.join(REPORTS_FOLDER);
+    let pending = folder.join(PENDING_FILE);
+    let file_name = fs::read_to_string(&pending).ok()?;
+
+    if let Err(err) = fs::remove_file(&pending) {
+        warn!("Failed to clear the pending crash report: {}", err);
+    }
+
+    Some(folder.join(file_name.trim()))
+}
+
+/// Records the current engine state for crash reports.
+fn update_crash_context(chunk_table: Res<ChunkTable>, engine: Res<ScriptEngine>) {
+    let mut context = lock(&CRASH_CONTEXT);
+    context.chunk_count = chunk_table.len();
+    context.scripts_running = engine.is_running();
+}
+
+/// A marker component for the crash report dialog.
+#[derive(Debug, Component)]
+struct CrashReportDialog {
+    /// The path of the crash report.
+    path: PathBuf,
+}
+
+/// An action performed when a button in the crash report dialog is clicked.
+#[derive(Debug, Clone, Copy, Component)]
+enum ReportAction {
+    /// Shows the contents of the crash report.
+    View,
+
+    /// Closes the dialog.
+    Dismiss,
+}
+
+/// Shows a dialog if the previous session crashed.
+fn show_pending_report(
+    project: Res<ProjectSettings>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let Some(path) = take_pending_report(project.project_folder()) else {
+        return;
+    };
+
+    let theme = hearth_theme(&asset_server);
+    let dialog = commands
+        .spawn((
+            CrashReportDialog { path: path.clone() },
+            ScreenAnchor::Center,
+            UiLayer::Dialogs,
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: px(8.0),
+                max_width: percent(80.0),
+                max_height: percent(80.0),
+                ..default()
+            },
+            theme.outer_window.clone(),
+            children![
+                (
+                    Text::new("The game crashed during the last session."),
+                    theme.outer_window.text.clone(),
+                ),
+                (
+                    Text::new(format!("A crash report was saved to {}", path.display())),
+                    theme.outer_window.text.clone(),
+                ),
+            ],
+        ))
+        .id();
+
+    let buttons = commands
+        .spawn((
+            ChildOf(dialog),
+            Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: px(4.0),
+                ..default()
+            },
+        ))
+        .id();
+
+    for (action, label) in [
+        (ReportAction::View, "View Report"),
+        (ReportAction::Dismiss, "Dismiss"),
+    ] {
+        commands.spawn((
+            ChildOf(buttons),
+            action,
+            button(ButtonBuilder {
+                node: Node {
+                    flex_grow: 1.0,
+                    ..default()
+                },
+                content: ButtonContent::text(label),
+                theme: theme.clone(),
+            }),
+        ));
+    }
+}
+
+/// Performs the action of a crash report dialog button when it is clicked.
+fn on_report_activate(
+    trigger: On<Activate>,
+    actions: Query<(&ReportAction, &ChildOf)>,
+    parents: Query<&ChildOf>,
+    dialogs: Query<&CrashReportDialog>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let Ok((&action, &ChildOf(buttons))) = actions.get(trigger.event().entity) else {
+        return;
+    };
+
+    let Ok(&ChildOf(dialog_id)) = parents.get(buttons) else {
+        return;
+    };
+
+    let Ok(dialog) = dialogs.get(dialog_id) else {
+        return;
+    };
+
+    match action {
+        ReportAction::View => {
+            let contents = fs::read_to_string(&dialog.path)
+                .unwrap_or_else(|err| format!("Failed to read {}: {}", dialog.path.display(), err));
+
+            let theme = hearth_theme(&asset_server);
+            commands.entity(trigger.event().entity).despawn();
+            commands.spawn((
+                ChildOf(dialog_id),
+                Node {
+                    overflow: Overflow::scroll_y(),
+                    scrollbar_width: 4.0,
+                    ..default()
+                },
+                children![(Text::new(contents), theme.outer_window.text.clone())],
+            ));
+        }
+        ReportAction::Dismiss => {
+            commands.entity(dialog_id).despawn();
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! This module implements area tags, which are named regions of the world
+//! stored in the game database.
+//!
+//! Each area covers a box of blocks and carries a list of string tags, such as
+//! `"town"` or `"dungeon_1"`, that scripts may use to decide how the world
+//! behaves at a given position.
+
+use serde::{Deserialize, Serialize};
+use sqlite::{State, Statement, Value};
+use ts_rs::TS;
+
+use crate::database::{Database, DatabaseError, validate_name};
+use crate::map::{BlockRegion, WorldPos, check_area_size};
+
+/// A named, tagged region of the world.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Area {
+    /// The unique name of the area.
+    pub name: String,
+
+    /// The blocks covered by the area.
+    pub region: BlockRegion,
+
+    /// The tags of the area.
+    pub tags: Vec<String>,
+}
+
+impl Database {
+    /// Lists all areas in the database, sorted by name.
+    pub fn list_areas(&self) -> Result<Vec<Area>, DatabaseError> {
+        let query = "SELECT * FROM areas ORDER BY name";
+        let mut statement = self.connection.prepare(query)?;
+
+        let mut areas = Vec::new();
+        while let State::Row = statement.next()? {
+            areas.push(read_area(&statement)?);
+        }
+
+        Ok(areas)
+    }
+
+    /// Stores an area, replacing any existing area with the same name.
+    ///
+    /// Areas that overlap more chunks than can be indexed are rejected.
+    pub fn set_area(&self, area: &Area) -> Result<(), DatabaseError> {
+        self.check_writable()?;
+        validate_name(&area.name)?;
+        check_area_size(area)?;
+
+        let query = "
+            INSERT OR REPLACE INTO areas
+                (name, min_x, min_y, min_z, max_x, max_y, max_z, tags)
+            VALUES (:name, :min_x, :min_y, :min_z, :max_x, :max_y, :max_z, :tags)
+        ";
+        let mut statement = self.connection.prepare(query)?;
+        let BlockRegion { min, max } = area.region;
+        statement.bind::<&[(_, Value)]>(&[
+            (":name", area.name.as_str().into()),
+            (":min_x", (min.x as i64).into()),
+            (":min_y", (min.y as i64).into()),
+            (":min_z", (min.z as i64).into()),
+            (":max_x", (max.x as i64).into()),
+            (":max_y", (max.y as i64).into()),
+            (":max_z", (max.z as i64).into()),
+            (":tags", serde_json::to_string(&area.tags)?.into()),
+        ])?;
+        statement.next()?;
+        Ok(())
+    }
+
+    /// Deletes the area with the given name, if it exists.
+    pub fn delete_area(&self, name: &str) -> Result<(), DatabaseError> {
+        self.check_writable()?;
+
+        let query = "DELETE FROM areas WHERE name = :name";
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":name", name))?;
+        statement.next()?;
+        Ok(())
+    }
+}
+
+/// Reads the area in the current row of the given statement.
+fn read_area(statement: &Statement) -> Result<Area, DatabaseError> {
+    let min = WorldPos::new(
+        statement.read::<i64, _>("min_x")? as i32,
+        statement.read::<i64, _>("min_y")? as i32,
+        statement.read::<i64, _>("min_z")? as i32,
+    );
+    let max = WorldPos::new(
+        statement.read::<i64, _>("max_x")? as i32,
+        statement.read::<i64, _>("max_y")? as i32,
+        statement.read::<i64, _>("max_z")? as i32,
+    );
+    let tags = statement.read::<String, _>("tags")?;
+
+    Ok(Area {
+        name: statement.read::<String, _>("name")?,
+        region: BlockRegion::new(min, max),
+        tags: serde_json::from_str(&tags)?,
+    })
+}
@@ -0,0 +1,94 @@
+//! This module implements backups of the game database, so that users can
+//! snapshot a project before risky operations and roll back to it later.
+//!
+//! Backups are taken with the SQLite online backup API while the database
+//! remains open. Backups created from the editor are stored as their own
+//! SQLite files within the `backups` folder of the project.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use awgen_asset_db::backup;
+use awgen_asset_db::busy::BusySettings;
+use bevy::prelude::*;
+
+use crate::database::{Database, DatabaseError};
+
+/// The folder, relative to the project folder, that backups are stored in.
+pub const BACKUPS_FOLDER: &str = "backups";
+
+/// The file extension used for backup files.
+pub const BACKUP_EXTENSION: &str = "awgen";
+
+/// A message written after the game database has been restored from a backup,
+/// so that data loaded from it can be reloaded.
+#[derive(Debug, Clone, Copy, Message)]
+pub struct GameDatabaseRestored;
+
+impl Database {
+    /// Writes a snapshot of the game database to the given file, replacing its
+    /// contents if it exists.
+    pub fn backup_to(&self, path: &Path) -> Result<(), DatabaseError> {
+        if let Some(folder) = path.parent() {
+            std::fs::create_dir_all(folder)?;
+        }
+
+        backup::backup_to(&self.connection, path, &BusySettings::default())?;
+        Ok(())
+    }
+
+    /// Replaces the contents of the game database with the snapshot stored in
+    /// the given file.
+    pub fn restore_from(&self, path: &Path) -> Result<(), DatabaseError> {
+        self.check_writable()?;
+
+        if !path.is_file() {
+            return Err(DatabaseError::UnknownBackup(path.to_path_buf()));
+        }
+
+        backup::restore_from(&self.connection, path, &BusySettings::default())?;
+
+        // Backups of older projects may be missing newer tables.
+        self.init()
+    }
+}
+
+/// Writes a new backup of the game database to the backups folder of the
+/// given project folder, and returns the path of the backup file.
+pub fn create_backup(database: &Database, project_folder: &Path) -> Result<PathBuf, DatabaseError> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis())
+        .unwrap_or_default();
+
+    let path = project_folder
+        .join(BACKUPS_FOLDER)
+        .join(format!("game-{timestamp}.{BACKUP_EXTENSION}"));
+
+    database.backup_to(&path)?;
+    Ok(path)
+}
+
+/// Finds the most recently modified backup in the backups folder of the given
+/// project folder.
+pub fn latest_backup(project_folder: &Path) -> Result<Option<PathBuf>, DatabaseError> {
+    let folder = project_folder.join(BACKUPS_FOLDER);
+    if !folder.is_dir() {
+        return Ok(None);
+    }
+
+    let mut latest: Option<(SystemTime, PathBuf)> = None;
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != BACKUP_EXTENSION) {
+            continue;
+        }
+
+        let modified = std::fs::metadata(&path)?.modified()?;
+        if latest.as_ref().is_none_or(|(time, _)| modified > *time) {
+            latest = Some((modified, path));
+        }
+    }
+
+    Ok(latest.map(|(_, path)| path))
+}
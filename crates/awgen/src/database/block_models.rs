@@ -0,0 +1,152 @@
+//! This module implements named block models, which are block models authored
+//! in the editor and stored as assets in the project asset database.
+//!
+//! Stored block models can be selected as the block brush in the editor, and
+//! let scripts place blocks by name rather than building their faces by hand.
+//! The name of a block model is the pathname of its asset record.
+
+use std::path::Path;
+
+use awgen_asset_db::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::database::{DatabaseError, ProjectAssets, validate_name};
+use crate::jobs::JobContext;
+use crate::map::BlockModel;
+
+/// The name of the asset module that new block models are created in.
+const BLOCK_MODEL_MODULE: &str = "Block Models";
+
+/// A block model stored in the project asset database under a unique name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NamedBlockModel {
+    /// The unique name of the block model.
+    pub name: String,
+
+    /// The block model.
+    pub model: BlockModel,
+}
+
+/// The asset that a block model is stored as in the project asset database.
+#[derive(Debug, Clone, Asset, TypePath)]
+pub struct BlockModelAsset(pub BlockModel);
+
+impl AwgenAsset for BlockModelAsset {
+    fn type_name() -> &'static str {
+        "blockmodel"
+    }
+
+    fn save(&self) -> Result<Vec<u8>, AssetDataError> {
+        serde_json::to_vec(&self.0)
+            .map_err(|e| AssetDataError(format!("Failed to encode block model: {}", e)))
+    }
+
+    fn load(bytes: &[u8]) -> Result<Self, AssetDataError> {
+        serde_json::from_slice(bytes)
+            .map(BlockModelAsset)
+            .map_err(|e| AssetDataError(format!("Failed to decode block model: {}", e)))
+    }
+
+    fn generate_preview(&self, job: JobContext) -> PreviewFuture {
+        // Block models are previewed by the block model editor, so the asset
+        // explorer shows a placeholder.
+        Box::pin(async move {
+            AssetDataError::check_cancelled(&job)?;
+            Ok(ImagePreviewData::new())
+        })
+    }
+}
+
+/// Extension trait for storing named block models in the project asset
+/// database.
+pub trait BlockModelAssets {
+    /// Lists all block models in the database, sorted by name.
+    fn list_block_models(&self) -> Result<Vec<NamedBlockModel>, DatabaseError>;
+
+    /// Gets the block model with the given name, if it exists.
+    fn get_block_model(&self, name: &str) -> Result<Option<NamedBlockModel>, DatabaseError>;
+
+    /// Stores a block model, replacing any existing block model with the same
+    /// name.
+    fn set_block_model(&mut self, model: &NamedBlockModel) -> Result<(), DatabaseError>;
+
+    /// Deletes the block model with the given name, if it exists.
+    fn delete_block_model(&mut self, name: &str) -> Result<(), DatabaseError>;
+}
+
+impl BlockModelAssets for AwgenAssets<'_, ProjectAssets> {
+    fn list_block_models(&self) -> Result<Vec<NamedBlockModel>, DatabaseError> {
+        let mut models = Vec::new();
+        for record in self.list_assets_of::<BlockModelAsset>()? {
+            models.push(NamedBlockModel {
+                name: record.pathname.to_string_lossy().into_owned(),
+                model: self.read_asset::<BlockModelAsset>(record.id)?.0,
+            });
+        }
+
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(models)
+    }
+
+    fn get_block_model(&self, name: &str) -> Result<Option<NamedBlockModel>, DatabaseError> {
+        let Some(id) = find_block_model(self, name)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(NamedBlockModel {
+            name: name.to_string(),
+            model: self.read_asset::<BlockModelAsset>(id)?.0,
+        }))
+    }
+
+    fn set_block_model(&mut self, model: &NamedBlockModel) -> Result<(), DatabaseError> {
+        if self.is_read_only() {
+            return Err(DatabaseError::ReadOnly);
+        }
+        validate_name(&model.name)?;
+
+        let asset = BlockModelAsset(model.model.clone());
+        if let Some(id) = find_block_model(self, &model.name)? {
+            self.update_asset(id, &asset)?;
+            return Ok(());
+        }
+
+        let module = match self
+            .list_modules()?
+            .into_iter()
+            .find(|module| module.name == BLOCK_MODEL_MODULE)
+        {
+            Some(module) => module.id,
+            None => self.create_module(BLOCK_MODEL_MODULE)?,
+        };
+
+        self.create_asset(&model.name, module, &asset)?;
+        Ok(())
+    }
+
+    fn delete_block_model(&mut self, name: &str) -> Result<(), DatabaseError> {
+        if self.is_read_only() {
+            return Err(DatabaseError::ReadOnly);
+        }
+
+        if let Some(id) = find_block_model(self, name)? {
+            self.delete_asset(id)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the asset record of the block model with the given name.
+fn find_block_model(
+    assets: &AwgenAssets<ProjectAssets>,
+    name: &str,
+) -> Result<Option<AssetRecordID>, DatabaseError> {
+    Ok(assets
+        .list_assets_of::<BlockModelAsset>()?
+        .into_iter()
+        .find(|record| record.pathname == Path::new(name))
+        .map(|record| record.id))
+}
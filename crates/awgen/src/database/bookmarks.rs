@@ -0,0 +1,109 @@
+//! This module implements world bookmarks, which are named camera locations
+//! stored in the game database.
+//!
+//! Bookmarks let level designers quickly navigate large maps from the editor,
+//! and let scripts move the camera to named points in the world.
+
+use serde::{Deserialize, Serialize};
+use sqlite::{State, Statement, Value};
+use ts_rs::TS;
+
+use crate::database::{Database, DatabaseError, validate_name};
+use crate::map::WorldPos;
+
+/// A named camera location in the world.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Bookmark {
+    /// The unique name of the bookmark.
+    pub name: String,
+
+    /// The block the camera looks at.
+    pub pos: WorldPos,
+
+    /// The camera pitch, in degrees.
+    pub pitch: f32,
+
+    /// The camera yaw, in degrees.
+    pub yaw: f32,
+
+    /// The distance of the camera from the position it looks at.
+    pub distance: f32,
+}
+
+impl Database {
+    /// Lists all bookmarks in the database, sorted by name.
+    pub fn list_bookmarks(&self) -> Result<Vec<Bookmark>, DatabaseError> {
+        let query = "SELECT * FROM bookmarks ORDER BY name";
+        let mut statement = self.connection.prepare(query)?;
+
+        let mut bookmarks = Vec::new();
+        while let State::Row = statement.next()? {
+            bookmarks.push(read_bookmark(&statement)?);
+        }
+
+        Ok(bookmarks)
+    }
+
+    /// Gets the bookmark with the given name, if it exists.
+    pub fn get_bookmark(&self, name: &str) -> Result<Option<Bookmark>, DatabaseError> {
+        let query = "SELECT * FROM bookmarks WHERE name = :name";
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":name", name))?;
+
+        if let State::Row = statement.next()? {
+            Ok(Some(read_bookmark(&statement)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Stores a bookmark, replacing any existing bookmark with the same name.
+    pub fn set_bookmark(&self, bookmark: &Bookmark) -> Result<(), DatabaseError> {
+        self.check_writable()?;
+        validate_name(&bookmark.name)?;
+
+        let query = "
+            INSERT OR REPLACE INTO bookmarks (name, x, y, z, pitch, yaw, distance)
+            VALUES (:name, :x, :y, :z, :pitch, :yaw, :distance)
+        ";
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind::<&[(_, Value)]>(&[
+            (":name", bookmark.name.as_str().into()),
+            (":x", (bookmark.pos.x as i64).into()),
+            (":y", (bookmark.pos.y as i64).into()),
+            (":z", (bookmark.pos.z as i64).into()),
+            (":pitch", (bookmark.pitch as f64).into()),
+            (":yaw", (bookmark.yaw as f64).into()),
+            (":distance", (bookmark.distance as f64).into()),
+        ])?;
+        statement.next()?;
+        Ok(())
+    }
+
+    /// Deletes the bookmark with the given name, if it exists.
+    pub fn delete_bookmark(&self, name: &str) -> Result<(), DatabaseError> {
+        self.check_writable()?;
+
+        let query = "DELETE FROM bookmarks WHERE name = :name";
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":name", name))?;
+        statement.next()?;
+        Ok(())
+    }
+}
+
+/// Reads the bookmark in the current row of the given statement.
+fn read_bookmark(statement: &Statement) -> Result<Bookmark, DatabaseError> {
+    Ok(Bookmark {
+        name: statement.read::<String, _>("name")?,
+        pos: WorldPos::new(
+            statement.read::<i64, _>("x")? as i32,
+            statement.read::<i64, _>("y")? as i32,
+            statement.read::<i64, _>("z")? as i32,
+        ),
+        pitch: statement.read::<f64, _>("pitch")? as f32,
+        yaw: statement.read::<f64, _>("yaw")? as f32,
+        distance: statement.read::<f64, _>("distance")? as f32,
+    })
+}
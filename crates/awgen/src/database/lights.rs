@@ -0,0 +1,209 @@
+//! This module implements placed lights, which are point and spot lights that
+//! level designers place in the world from the editor.
+//!
+//! Lights are stored in the game database by name, so they persist between
+//! launches, and scripts may toggle them on and off by name.
+
+use bevy::reflect::Reflect;
+use serde::{Deserialize, Serialize};
+use sqlite::{State, Statement, Value};
+use ts_rs::TS;
+
+use crate::database::{Database, DatabaseError, validate_name};
+
+/// The kind of a placed light.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Reflect)]
+#[serde(rename_all = "camelCase")]
+pub enum LightKind {
+    /// A light that shines in all directions from a point.
+    #[default]
+    Point,
+
+    /// A light that shines in a cone from a point.
+    Spot,
+}
+
+impl LightKind {
+    /// Gets the name of this kind, as stored in the game database.
+    fn as_str(self) -> &'static str {
+        match self {
+            LightKind::Point => "point",
+            LightKind::Spot => "spot",
+        }
+    }
+
+    /// Parses a kind from its name in the game database.
+    fn from_str(kind: &str) -> Option<Self> {
+        match kind {
+            "point" => Some(LightKind::Point),
+            "spot" => Some(LightKind::Spot),
+            _ => None,
+        }
+    }
+}
+
+/// A named light placed in the world.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PlacedLight {
+    /// The unique name of the light.
+    pub name: String,
+
+    /// The kind of the light.
+    pub kind: LightKind,
+
+    /// The world position of the light.
+    pub position: [f32; 3],
+
+    /// The pitch of a spot light, in degrees. Zero points the light at the
+    /// horizon, and -90 points it straight down.
+    pub pitch: f32,
+
+    /// The yaw of a spot light, in degrees.
+    pub yaw: f32,
+
+    /// The red, green, and blue components of the light color, from 0 to 1.
+    pub color: [f32; 3],
+
+    /// The luminous power of the light, in lumens.
+    pub intensity: f32,
+
+    /// The distance, in blocks, beyond which the light has no effect.
+    pub range: f32,
+
+    /// The angle, in degrees, between the center and the edge of the cone of
+    /// a spot light.
+    pub angle: f32,
+
+    /// Whether the light casts shadows.
+    pub shadows: bool,
+
+    /// Whether the light is switched on.
+    pub enabled: bool,
+}
+
+impl PlacedLight {
+    /// Creates a new light of the given kind at the given position, with
+    /// default parameters.
+    pub fn new(name: impl Into<String>, kind: LightKind, position: [f32; 3]) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            position,
+            pitch: -90.0,
+            yaw: 0.0,
+            color: [1.0, 1.0, 1.0],
+            intensity: 100_000.0,
+            range: 20.0,
+            angle: 30.0,
+            shadows: false,
+            enabled: true,
+        }
+    }
+}
+
+impl Database {
+    /// Lists all lights in the database, sorted by name.
+    pub fn list_lights(&self) -> Result<Vec<PlacedLight>, DatabaseError> {
+        let query = "SELECT * FROM lights ORDER BY name";
+        let mut statement = self.connection.prepare(query)?;
+
+        let mut lights = Vec::new();
+        while let State::Row = statement.next()? {
+            lights.push(read_light(&statement)?);
+        }
+
+        Ok(lights)
+    }
+
+    /// Stores a light, replacing any existing light with the same name.
+    pub fn set_light(&self, light: &PlacedLight) -> Result<(), DatabaseError> {
+        self.check_writable()?;
+        validate_name(&light.name)?;
+
+        let query = "
+            INSERT OR REPLACE INTO lights
+                (name, kind, x, y, z, pitch, yaw, red, green, blue,
+                 intensity, range, angle, shadows, enabled)
+            VALUES (:name, :kind, :x, :y, :z, :pitch, :yaw, :red, :green, :blue,
+                    :intensity, :range, :angle, :shadows, :enabled)
+        ";
+        let mut statement = self.connection.prepare(query)?;
+        let [x, y, z] = light.position;
+        let [red, green, blue] = light.color;
+        statement.bind::<&[(_, Value)]>(&[
+            (":name", light.name.as_str().into()),
+            (":kind", light.kind.as_str().into()),
+            (":x", (x as f64).into()),
+            (":y", (y as f64).into()),
+            (":z", (z as f64).into()),
+            (":pitch", (light.pitch as f64).into()),
+            (":yaw", (light.yaw as f64).into()),
+            (":red", (red as f64).into()),
+            (":green", (green as f64).into()),
+            (":blue", (blue as f64).into()),
+            (":intensity", (light.intensity as f64).into()),
+            (":range", (light.range as f64).into()),
+            (":angle", (light.angle as f64).into()),
+            (":shadows", (light.shadows as i64).into()),
+            (":enabled", (light.enabled as i64).into()),
+        ])?;
+        statement.next()?;
+        Ok(())
+    }
+
+    /// Switches the light with the given name on or off.
+    pub fn set_light_enabled(&self, name: &str, enabled: bool) -> Result<(), DatabaseError> {
+        self.check_writable()?;
+
+        let query = "UPDATE lights SET enabled = :enabled WHERE name = :name";
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind::<&[(_, Value)]>(&[
+            (":name", name.into()),
+            (":enabled", (enabled as i64).into()),
+        ])?;
+        statement.next()?;
+
+        if self.connection.change_count() == 0 {
+            return Err(DatabaseError::UnknownLight(name.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Deletes the light with the given name, if it exists.
+    pub fn delete_light(&self, name: &str) -> Result<(), DatabaseError> {
+        self.check_writable()?;
+
+        let query = "DELETE FROM lights WHERE name = :name";
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":name", name))?;
+        statement.next()?;
+        Ok(())
+    }
+}
+
+/// Reads the light in the current row of the given statement.
+fn read_light(statement: &Statement) -> Result<PlacedLight, DatabaseError> {
+    let name = statement.read::<String, _>("name")?;
+    let kind = statement.read::<String, _>("kind")?;
+    let Some(kind) = LightKind::from_str(&kind) else {
+        return Err(DatabaseError::InvalidLight(name));
+    };
+
+    let read_f32 = |column: &str| statement.read::<f64, _>(column).map(|value| value as f32);
+
+    Ok(PlacedLight {
+        kind,
+        position: [read_f32("x")?, read_f32("y")?, read_f32("z")?],
+        pitch: read_f32("pitch")?,
+        yaw: read_f32("yaw")?,
+        color: [read_f32("red")?, read_f32("green")?, read_f32("blue")?],
+        intensity: read_f32("intensity")?,
+        range: read_f32("range")?,
+        angle: read_f32("angle")?,
+        shadows: statement.read::<i64, _>("shadows")? != 0,
+        enabled: statement.read::<i64, _>("enabled")? != 0,
+        name,
+    })
+}
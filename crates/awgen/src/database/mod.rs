@@ -1,35 +1,151 @@
 //! This module handles the implementation of the database connection for
 //! accessing game files.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use sqlite::{Connection, ConnectionThreadSafe, Error, State, Value};
+use awgen_asset_db::prelude::{AssetDatabaseName, AwgenAssetsError};
+use bevy::prelude::{Deref, Resource};
+use sqlite::{Connection, ConnectionThreadSafe, OpenFlags, State, Value};
+
+use crate::map::AreaTooLarge;
+
+pub mod areas;
+pub mod backup;
+pub mod block_models;
+pub mod bookmarks;
+pub mod lights;
+pub mod savegame;
+pub mod telemetry;
+
+/// The maximum length in bytes of the name of a stored item, such as a
+/// bookmark, area, light or block model.
+pub const MAX_NAME_LENGTH: usize = 64;
 
 /// Database struct that encapsulates the SQLite connection.
 pub struct Database {
     /// The SQLite connection to the game database.
     connection: ConnectionThreadSafe,
+
+    /// Whether the database was opened in read-only mode.
+    read_only: bool,
 }
 
 impl Database {
     /// Creates a new `Database` instance by opening a connection to the
     /// sqlite database file containing the game data.
-    pub fn new(project_folder: &Path) -> Result<Self, Error> {
+    pub fn new(project_folder: &Path) -> Result<Self, DatabaseError> {
         let path = project_folder.join("game.awgen");
         let connection = Connection::open_thread_safe(path)?;
-        let db = Database { connection };
+        let db = Database {
+            connection,
+            read_only: false,
+        };
         db.init()?;
         Ok(db)
     }
 
+    /// Creates a new `Database` instance by opening an existing game database
+    /// in read-only mode. This is used for projects stored on read-only
+    /// filesystems, such as network shares or packaged builds.
+    ///
+    /// All methods that would modify the database will return
+    /// [`DatabaseError::ReadOnly`].
+    pub fn new_read_only(project_folder: &Path) -> Result<Self, DatabaseError> {
+        let path = project_folder.join("game.awgen");
+        let flags = OpenFlags::new().with_read_only().with_full_mutex();
+        let connection = Connection::open_thread_safe_with_flags(path, flags)?;
+        Ok(Database {
+            connection,
+            read_only: true,
+        })
+    }
+
+    /// Creates a new `Database` instance backed by a temporary in-memory
+    /// database. All data is lost when the database is dropped.
+    pub fn in_memory() -> Result<Self, DatabaseError> {
+        let connection = Connection::open_thread_safe(":memory:")?;
+        let db = Database {
+            connection,
+            read_only: false,
+        };
+        db.init()?;
+        Ok(db)
+    }
+
+    /// Returns true if the database was opened in read-only mode.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Returns an error if the database was opened in read-only mode.
+    fn check_writable(&self) -> Result<(), DatabaseError> {
+        if self.read_only {
+            return Err(DatabaseError::ReadOnly);
+        }
+
+        Ok(())
+    }
+
     /// Initializes the database by creating necessary tables and indices.
-    fn init(&self) -> Result<(), Error> {
+    fn init(&self) -> Result<(), DatabaseError> {
         self.connection.execute(
             "
             CREATE TABLE IF NOT EXISTS settings (
                 key TEXT PRIMARY KEY,
                 value TEXT
             );
+
+            CREATE TABLE IF NOT EXISTS bookmarks (
+                name TEXT PRIMARY KEY,
+                x INTEGER NOT NULL,
+                y INTEGER NOT NULL,
+                z INTEGER NOT NULL,
+                pitch REAL NOT NULL,
+                yaw REAL NOT NULL,
+                distance REAL NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS areas (
+                name TEXT PRIMARY KEY,
+                min_x INTEGER NOT NULL,
+                min_y INTEGER NOT NULL,
+                min_z INTEGER NOT NULL,
+                max_x INTEGER NOT NULL,
+                max_y INTEGER NOT NULL,
+                max_z INTEGER NOT NULL,
+                tags TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS lights (
+                name TEXT PRIMARY KEY,
+                kind TEXT NOT NULL,
+                x REAL NOT NULL,
+                y REAL NOT NULL,
+                z REAL NOT NULL,
+                pitch REAL NOT NULL,
+                yaw REAL NOT NULL,
+                red REAL NOT NULL,
+                green REAL NOT NULL,
+                blue REAL NOT NULL,
+                intensity REAL NOT NULL,
+                range REAL NOT NULL,
+                angle REAL NOT NULL,
+                shadows INTEGER NOT NULL,
+                enabled INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS usage_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                started INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS usage_counters (
+                session INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                count INTEGER NOT NULL,
+                PRIMARY KEY (session, name)
+            );
             ",
         )?;
 
@@ -40,7 +156,7 @@ impl Database {
     ///
     /// Returns `Ok(Some(value))` if the key exists, `Ok(None)` if it does not,
     /// and `Err` if there was an error querying the database.
-    pub fn get_setting(&self, key: &str) -> Result<Option<String>, Error> {
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>, DatabaseError> {
         let query = "SELECT value FROM settings WHERE key = :key";
         let mut statement = self.connection.prepare(query)?;
         statement.bind((":key", key))?;
@@ -53,7 +169,9 @@ impl Database {
     }
 
     /// Sets a setting in the database.
-    pub fn set_setting(&self, key: &str, value: &str) -> Result<(), Error> {
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<(), DatabaseError> {
+        self.check_writable()?;
+
         let query = "INSERT OR REPLACE INTO settings (key, value) VALUES (:key, :value)";
         let mut statement = self.connection.prepare(query)?;
         statement.bind::<&[(_, Value)]>(&[(":key", key.into()), (":value", value.into())])?;
@@ -62,7 +180,9 @@ impl Database {
     }
 
     /// Clears a setting from the database by its key.
-    pub fn clear_setting(&self, key: &str) -> Result<(), Error> {
+    pub fn clear_setting(&self, key: &str) -> Result<(), DatabaseError> {
+        self.check_writable()?;
+
         let query = "DELETE FROM settings WHERE key = :key";
         let mut statement = self.connection.prepare(query)?;
         statement.bind((":key", key))?;
@@ -70,3 +190,110 @@ impl Database {
         Ok(())
     }
 }
+
+/// A resource that gives systems access to the game database.
+///
+/// This is the same database that is shared with the script engine.
+#[derive(Resource, Deref)]
+pub struct GameDatabase(pub Arc<Database>);
+
+/// The project asset database identifier.
+///
+/// The project asset database is stored in the same file as the game
+/// database, and holds assets authored in the editor, such as block models.
+pub struct ProjectAssets;
+impl AssetDatabaseName for ProjectAssets {
+    fn database_name() -> &'static str {
+        "project"
+    }
+}
+
+/// Checks that the name of a stored item, such as a bookmark, area, light or
+/// block model, is not blank and not too long.
+pub fn validate_name(name: &str) -> Result<(), NameError> {
+    if name.trim().is_empty() {
+        return Err(NameError::Blank);
+    }
+
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(NameError::TooLong(name.to_string()));
+    }
+
+    Ok(())
+}
+
+/// An error returned when the name of a stored item is invalid.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NameError {
+    /// The name is empty or only holds whitespace.
+    #[error("Names must not be blank")]
+    Blank,
+
+    /// The name is longer than [`MAX_NAME_LENGTH`] bytes.
+    #[error("Name {0:?} is longer than {MAX_NAME_LENGTH} bytes")]
+    TooLong(String),
+}
+
+/// An error that can occur while interacting with the game database.
+#[derive(Debug, thiserror::Error)]
+pub enum DatabaseError {
+    /// An error returned by SQLite.
+    #[error("{0}")]
+    Sqlite(#[from] sqlite::Error),
+
+    /// A write was attempted on a database opened in read-only mode.
+    #[error("The database is open in read-only mode")]
+    ReadOnly,
+
+    /// An error that occurred while accessing a savegame file.
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    /// An error that occurred while serializing or deserializing save data.
+    #[error("Failed to parse save data: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The save slot name contains unsupported characters.
+    #[error("Invalid save slot name: {0:?}")]
+    InvalidSlot(String),
+
+    /// No save exists in the given slot.
+    #[error("No save exists in slot {0:?}")]
+    UnknownSave(String),
+
+    /// The save file contains invalid map data.
+    #[error("The save file is corrupted")]
+    CorruptSave,
+
+    /// The name of a bookmark, area, light or block model is invalid.
+    #[error("{0}")]
+    InvalidName(#[from] NameError),
+
+    /// No bookmark exists with the given name.
+    #[error("No bookmark exists with the name {0:?}")]
+    UnknownBookmark(String),
+
+    /// No block model exists with the given name.
+    #[error("No block model exists with the name {0:?}")]
+    UnknownBlockModel(String),
+
+    /// The area overlaps too many chunks to be indexed.
+    #[error("{0}")]
+    AreaTooLarge(#[from] AreaTooLarge),
+
+    /// The stored light with the given name is invalid.
+    #[error("Invalid light: {0:?}")]
+    InvalidLight(String),
+
+    /// No light exists with the given name.
+    #[error("No light exists with the name {0:?}")]
+    UnknownLight(String),
+
+    /// No backup file exists at the given path.
+    #[error("No backup exists at {0:?}")]
+    UnknownBackup(PathBuf),
+
+    /// An error returned by the project asset database.
+    #[error("{0}")]
+    Assets(#[from] AwgenAssetsError),
+}
@@ -0,0 +1,236 @@
+//! This module implements savegame slots, which store player save data
+//! separately from the project data.
+//!
+//! Each slot is stored as its own SQLite file within the `saves` folder of the
+//! project. A save contains the state of the map, along with an arbitrary JSON
+//! value provided by the scripts.
+
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use sqlite::{Connection, ConnectionThreadSafe, OpenFlags, State, Value};
+use ts_rs::TS;
+
+use crate::database::DatabaseError;
+use crate::map::{BlockModel, ChunkModels, ChunkPos};
+
+/// The folder, relative to the project folder, that savegames are stored in.
+pub const SAVES_FOLDER: &str = "saves";
+
+/// The file extension used for savegame files.
+pub const SAVE_EXTENSION: &str = "awsave";
+
+/// The maximum length of a save slot name.
+const MAX_SLOT_LENGTH: usize = 64;
+
+/// Information about a save slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveInfo {
+    /// The name of the save slot.
+    pub slot: String,
+
+    /// The time the save was last written, in seconds since the Unix epoch.
+    pub modified: i64,
+}
+
+/// A single savegame slot.
+pub struct SaveGame {
+    /// The SQLite connection to the savegame file.
+    connection: ConnectionThreadSafe,
+}
+
+impl SaveGame {
+    /// Creates a new, empty save in the given slot.
+    ///
+    /// The save is written to a temporary file, and only replaces an existing
+    /// save in the same slot once [`SaveGame::commit`] is called.
+    pub fn create(project_folder: &Path, slot: &str) -> Result<Self, DatabaseError> {
+        let path = temp_path(&save_path(project_folder, slot)?);
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let connection = Connection::open_thread_safe(&path)?;
+        connection.execute(
+            "
+            CREATE TABLE chunks (
+                x INTEGER NOT NULL,
+                y INTEGER NOT NULL,
+                z INTEGER NOT NULL,
+                models TEXT NOT NULL,
+                PRIMARY KEY (x, y, z)
+            );
+
+            CREATE TABLE data (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                value TEXT NOT NULL
+            );
+            ",
+        )?;
+
+        Ok(Self { connection })
+    }
+
+    /// Opens the save in the given slot for reading.
+    pub fn open(project_folder: &Path, slot: &str) -> Result<Self, DatabaseError> {
+        let path = save_path(project_folder, slot)?;
+        if !path.is_file() {
+            return Err(DatabaseError::UnknownSave(slot.to_string()));
+        }
+
+        let flags = OpenFlags::new().with_read_only().with_full_mutex();
+        let connection = Connection::open_thread_safe_with_flags(path, flags)?;
+        Ok(Self { connection })
+    }
+
+    /// Finishes writing a save created with [`SaveGame::create`], replacing
+    /// any existing save in the same slot.
+    pub fn commit(self, project_folder: &Path, slot: &str) -> Result<(), DatabaseError> {
+        let path = save_path(project_folder, slot)?;
+        drop(self.connection);
+        std::fs::rename(temp_path(&path), path)?;
+        Ok(())
+    }
+
+    /// Writes the block models of a chunk to the save.
+    pub fn write_chunk(&self, pos: ChunkPos, models: &ChunkModels) -> Result<(), DatabaseError> {
+        let models = serde_json::to_string(models.as_slice())?;
+
+        let query = "
+            INSERT OR REPLACE INTO chunks (x, y, z, models)
+            VALUES (:x, :y, :z, :models)
+        ";
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind::<&[(_, Value)]>(&[
+            (":x", (pos.x as i64).into()),
+            (":y", (pos.y as i64).into()),
+            (":z", (pos.z as i64).into()),
+            (":models", models.into()),
+        ])?;
+        statement.next()?;
+        Ok(())
+    }
+
+    /// Reads the block models of all chunks stored in the save.
+    pub fn read_chunks(&self) -> Result<Vec<(ChunkPos, ChunkModels)>, DatabaseError> {
+        let query = "SELECT x, y, z, models FROM chunks";
+        let mut statement = self.connection.prepare(query)?;
+
+        let mut chunks = Vec::new();
+        while let State::Row = statement.next()? {
+            let pos = ChunkPos::new(
+                statement.read::<i64, _>("x")? as i32,
+                statement.read::<i64, _>("y")? as i32,
+                statement.read::<i64, _>("z")? as i32,
+            );
+
+            let models = statement.read::<String, _>("models")?;
+            let models = serde_json::from_str::<Vec<BlockModel>>(&models)?;
+            let models = ChunkModels::from_vec(models).ok_or(DatabaseError::CorruptSave)?;
+
+            chunks.push((pos, models));
+        }
+
+        Ok(chunks)
+    }
+
+    /// Writes the script-provided data to the save.
+    pub fn set_data(&self, data: &serde_json::Value) -> Result<(), DatabaseError> {
+        let query = "INSERT OR REPLACE INTO data (id, value) VALUES (0, :value)";
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":value", serde_json::to_string(data)?.as_str()))?;
+        statement.next()?;
+        Ok(())
+    }
+
+    /// Reads the script-provided data from the save.
+    ///
+    /// Returns `null` if no data was stored.
+    pub fn get_data(&self) -> Result<serde_json::Value, DatabaseError> {
+        let query = "SELECT value FROM data WHERE id = 0";
+        let mut statement = self.connection.prepare(query)?;
+
+        if let State::Row = statement.next()? {
+            let value = statement.read::<String, _>("value")?;
+            Ok(serde_json::from_str(&value)?)
+        } else {
+            Ok(serde_json::Value::Null)
+        }
+    }
+}
+
+/// Lists all save slots in the given project folder, sorted by slot name.
+pub fn list_saves(project_folder: &Path) -> Result<Vec<SaveInfo>, DatabaseError> {
+    let folder = project_folder.join(SAVES_FOLDER);
+    if !folder.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut saves = Vec::new();
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != SAVE_EXTENSION) {
+            continue;
+        }
+
+        let Some(slot) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        let modified = std::fs::metadata(&path)?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs() as i64)
+            .unwrap_or_default();
+
+        saves.push(SaveInfo {
+            slot: slot.to_string(),
+            modified,
+        });
+    }
+
+    saves.sort_by(|a, b| a.slot.cmp(&b.slot));
+    Ok(saves)
+}
+
+/// Deletes the save in the given slot.
+pub fn delete_save(project_folder: &Path, slot: &str) -> Result<(), DatabaseError> {
+    let path = save_path(project_folder, slot)?;
+    if !path.is_file() {
+        return Err(DatabaseError::UnknownSave(slot.to_string()));
+    }
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+/// Gets the file path of the save in the given slot.
+///
+/// Slot names may only contain ASCII letters, digits, underscores and hyphens,
+/// so that a slot can never refer to a file outside of the saves folder.
+fn save_path(project_folder: &Path, slot: &str) -> Result<PathBuf, DatabaseError> {
+    let is_valid = !slot.is_empty()
+        && slot.len() <= MAX_SLOT_LENGTH
+        && slot
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+    if !is_valid {
+        return Err(DatabaseError::InvalidSlot(slot.to_string()));
+    }
+
+    Ok(project_folder
+        .join(SAVES_FOLDER)
+        .join(format!("{slot}.{SAVE_EXTENSION}")))
+}
+
+/// Gets the temporary file path used while writing the given save file.
+fn temp_path(path: &Path) -> PathBuf {
+    path.with_extension(format!("{SAVE_EXTENSION}.tmp"))
+}
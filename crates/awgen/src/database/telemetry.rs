@@ -0,0 +1,150 @@
+//! This module stores the local usage counters of the editor, which count how
+//! often tools are used, packets are processed, and assets are created in each
+//! session.
+//!
+//! Usage counters are opt-in, and are only ever stored in the game database.
+
+use sqlite::{State, Value};
+
+use crate::database::{Database, DatabaseError};
+
+/// The settings key that stores whether usage counters are enabled.
+const USAGE_COUNTERS_KEY: &str = "usage_counters_enabled";
+
+/// The value of a named usage counter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageCount {
+    /// The name of the counter, such as `packets.processed`.
+    pub name: String,
+
+    /// The number of times the counted event occurred.
+    pub count: u64,
+}
+
+impl Database {
+    /// Returns true if the user has opted in to usage counters.
+    pub fn usage_counters_enabled(&self) -> Result<bool, DatabaseError> {
+        Ok(self.get_setting(USAGE_COUNTERS_KEY)?.as_deref() == Some("true"))
+    }
+
+    /// Opts in to or out of usage counters.
+    pub fn set_usage_counters_enabled(&self, enabled: bool) -> Result<(), DatabaseError> {
+        self.set_setting(USAGE_COUNTERS_KEY, if enabled { "true" } else { "false" })
+    }
+
+    /// Starts a new usage session at the given Unix timestamp, in seconds,
+    /// returning the ID of the session.
+    pub fn start_usage_session(&self, started: i64) -> Result<i64, DatabaseError> {
+        self.check_writable()?;
+
+        let query = "INSERT INTO usage_sessions (started) VALUES (:started)";
+        let mut statement = self.connection.prepare(query)?;
+        statement.bind((":started", started))?;
+        statement.next()?;
+
+        let mut statement = self
+            .connection
+            .prepare("SELECT last_insert_rowid() AS id")?;
+        statement.next()?;
+        Ok(statement.read::<i64, _>("id")?)
+    }
+
+    /// Adds the given amounts to the usage counters of a session.
+    ///
+    /// The counts are added in a single transaction, so either all of them are
+    /// stored or none are.
+    pub fn add_usage_counts(
+        &self,
+        session: i64,
+        counts: &[UsageCount],
+    ) -> Result<(), DatabaseError> {
+        self.check_writable()?;
+
+        self.connection.execute("BEGIN")?;
+        match self.insert_usage_counts(session, counts) {
+            Ok(()) => {
+                self.connection.execute("COMMIT")?;
+                Ok(())
+            }
+            Err(err) => {
+                // The insert error is more useful to the caller than a failed
+                // rollback, which SQLite also performs when the connection
+                // closes.
+                let _ = self.connection.execute("ROLLBACK");
+                Err(err)
+            }
+        }
+    }
+
+    /// Inserts the given amounts into the usage counters of a session, within
+    /// the current transaction.
+    fn insert_usage_counts(
+        &self,
+        session: i64,
+        counts: &[UsageCount],
+    ) -> Result<(), DatabaseError> {
+        let query = "
+            INSERT INTO usage_counters (session, name, count)
+            VALUES (:session, :name, :count)
+            ON CONFLICT (session, name) DO UPDATE SET count = count + excluded.count
+        ";
+        let mut statement = self.connection.prepare(query)?;
+        for count in counts {
+            statement.reset()?;
+            statement.bind::<&[(_, Value)]>(&[
+                (":session", session.into()),
+                (":name", count.name.as_str().into()),
+                (":count", (count.count as i64).into()),
+            ])?;
+            statement.next()?;
+        }
+
+        Ok(())
+    }
+
+    /// Lists the usage counters of the given session, or the totals across
+    /// all sessions if no session is given, sorted by name.
+    pub fn list_usage_counts(
+        &self,
+        session: Option<i64>,
+    ) -> Result<Vec<UsageCount>, DatabaseError> {
+        let query = match session {
+            Some(_) => {
+                "SELECT name, count FROM usage_counters WHERE session = :session ORDER BY name"
+            }
+            None => {
+                "SELECT name, SUM(count) AS count FROM usage_counters GROUP BY name ORDER BY name"
+            }
+        };
+        let mut statement = self.connection.prepare(query)?;
+        if let Some(session) = session {
+            statement.bind((":session", session))?;
+        }
+
+        let mut counts = Vec::new();
+        while let State::Row = statement.next()? {
+            counts.push(UsageCount {
+                name: statement.read::<String, _>("name")?,
+                count: statement.read::<i64, _>("count")? as u64,
+            });
+        }
+
+        Ok(counts)
+    }
+
+    /// Gets the number of recorded usage sessions.
+    pub fn usage_session_count(&self) -> Result<u64, DatabaseError> {
+        let query = "SELECT COUNT(*) AS count FROM usage_sessions";
+        let mut statement = self.connection.prepare(query)?;
+        statement.next()?;
+        Ok(statement.read::<i64, _>("count")? as u64)
+    }
+
+    /// Deletes all recorded usage sessions and counters.
+    pub fn clear_usage_counts(&self) -> Result<(), DatabaseError> {
+        self.check_writable()?;
+        self.connection
+            .execute("DELETE FROM usage_counters; DELETE FROM usage_sessions;")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+//! This module implements frame pacing for the game window, which limits the
+//! frame rate to a target, and throttles it further while the window is not
+//! focused.
+//!
+//! The frame rate limits are stored in the settings table of the game
+//! database, so they persist between launches of the project.
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::winit::{UpdateMode, WinitSettings};
+
+use crate::database::{Database, DatabaseError, GameDatabase};
+
+/// The settings key for the target frame rate.
+const TARGET_FPS_KEY: &str = "target_fps";
+
+/// The settings key for the frame rate while the window is unfocused.
+const UNFOCUSED_FPS_KEY: &str = "unfocused_fps";
+
+/// The default frame rate while the window is unfocused.
+const DEFAULT_UNFOCUSED_FPS: f64 = 10.0;
+
+/// Plugin that sets up frame pacing.
+pub struct FramePacePlugin;
+impl Plugin for FramePacePlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<FramePacing>()
+            .insert_resource(WinitSettings::game())
+            .add_systems(Startup, load_frame_pacing)
+            .add_systems(Update, apply_frame_pacing)
+            .add_systems(Last, limit_frame_rate);
+    }
+}
+
+/// A resource that controls the frame rate of the game window.
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+pub struct FramePacing {
+    /// The maximum frame rate while the window is focused, or `None` for no
+    /// limit.
+    pub target_fps: Option<f64>,
+
+    /// The maximum frame rate while the window is unfocused.
+    pub unfocused_fps: f64,
+}
+
+impl Default for FramePacing {
+    fn default() -> Self {
+        Self {
+            target_fps: None,
+            unfocused_fps: DEFAULT_UNFOCUSED_FPS,
+        }
+    }
+}
+
+impl FramePacing {
+    /// Loads the frame pacing settings from the game database, using the
+    /// defaults for any that are missing or invalid.
+    pub fn load(database: &Database) -> Result<Self, DatabaseError> {
+        let mut pacing = Self::default();
+
+        if let Some(fps) = read_fps(database, TARGET_FPS_KEY)? {
+            pacing.target_fps = Some(fps);
+        }
+
+        if let Some(fps) = read_fps(database, UNFOCUSED_FPS_KEY)? {
+            pacing.unfocused_fps = fps;
+        }
+
+        Ok(pacing)
+    }
+
+    /// Stores the frame pacing settings in the game database.
+    pub fn save(&self, database: &Database) -> Result<(), DatabaseError> {
+        match self.target_fps {
+            Some(fps) => database.set_setting(TARGET_FPS_KEY, &fps.to_string())?,
+            None => database.clear_setting(TARGET_FPS_KEY)?,
+        }
+
+        database.set_setting(UNFOCUSED_FPS_KEY, &self.unfocused_fps.to_string())
+    }
+
+    /// Returns true if the given frame rate is a usable limit.
+    pub fn is_valid_fps(fps: f64) -> bool {
+        fps.is_finite() && fps > 0.0
+    }
+}
+
+/// Reads a frame rate setting, ignoring values that are not valid limits.
+fn read_fps(database: &Database, key: &str) -> Result<Option<f64>, DatabaseError> {
+    let Some(value) = database.get_setting(key)? else {
+        return Ok(None);
+    };
+
+    match value.parse::<f64>() {
+        Ok(fps) if FramePacing::is_valid_fps(fps) => Ok(Some(fps)),
+        _ => {
+            warn!("Ignoring invalid frame rate setting {}: {}", key, value);
+            Ok(None)
+        }
+    }
+}
+
+/// Loads the frame pacing settings from the game database.
+fn load_frame_pacing(database: Res<GameDatabase>, mut pacing: ResMut<FramePacing>) {
+    match FramePacing::load(&database) {
+        Ok(loaded) => *pacing = loaded,
+        Err(err) => error!("Failed to load frame pacing settings: {}", err),
+    }
+}
+
+/// Throttles window updates while the window is unfocused, whenever the frame
+/// pacing settings change.
+fn apply_frame_pacing(pacing: Res<FramePacing>, mut winit_settings: ResMut<WinitSettings>) {
+    if !pacing.is_changed() {
+        return;
+    }
+
+    let wait = Duration::from_secs_f64(1.0 / pacing.unfocused_fps);
+    winit_settings.unfocused_mode = UpdateMode::reactive_low_power(wait);
+}
+
+/// Sleeps at the end of each frame for long enough to keep the frame rate at
+/// or below the target.
+fn limit_frame_rate(pacing: Res<FramePacing>, mut last_frame: Local<Option<Instant>>) {
+    if let (Some(fps), Some(last)) = (pacing.target_fps, *last_frame) {
+        let frame_time = Duration::from_secs_f64(1.0 / fps);
+        if let Some(remaining) = frame_time.checked_sub(last.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+
+    *last_frame = Some(Instant::now());
+}
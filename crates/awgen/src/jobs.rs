@@ -0,0 +1,8 @@
+//! This module re-exports the background job manager, which runs long tasks
+//! such as tileset builds, imports, and chunk meshing on the async compute
+//! task pool.
+//!
+//! The job manager lives in the asset database crate, so that asset preview
+//! generation can be run as background jobs as well.
+
+pub use awgen_asset_db::jobs::*;
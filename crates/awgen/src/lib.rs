@@ -0,0 +1,19 @@
+#![doc = include_str!("../../../README.md")]
+#![warn(missing_docs)]
+#![warn(clippy::missing_docs_in_private_items)]
+
+pub mod app;
+pub mod crash;
+pub mod database;
+pub mod framepace;
+pub mod jobs;
+pub mod logging;
+pub mod map;
+#[cfg(feature = "networking")]
+pub mod net;
+pub mod scripts;
+pub mod shutdown;
+pub mod telemetry;
+pub mod tiles;
+pub mod ux;
+pub mod window_state;
@@ -0,0 +1,246 @@
+//! This module implements structured logging to rolling log files in the
+//! project folder, and a log filter that can be changed while the game is
+//! running.
+//!
+//! Log lines from scripts are written to the same stream under the `script`
+//! target, so they can be told apart from engine logs and filtered with
+//! directives such as `script=debug`.
+//!
+//! The filter uses the same directive syntax as `RUST_LOG`, such as
+//! `info,awgen::map=debug`, and is stored in the settings table of the game
+//! database so it persists between launches.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use bevy::log::tracing_subscriber::filter::ParseError;
+use bevy::log::tracing_subscriber::{EnvFilter, Layer, Registry, fmt, reload};
+use bevy::log::{BoxedLayer, Level};
+use bevy::prelude::*;
+
+use crate::app::ProjectSettings;
+use crate::crash;
+use crate::database::{Database, DatabaseError, GameDatabase};
+
+/// The folder, relative to the project folder, that log files are written to.
+pub const LOGS_FOLDER: &str = "logs";
+
+/// The target that log lines from scripts are written under.
+pub const SCRIPT_TARGET: &str = "script";
+
+/// The settings key for the log filter.
+const LOG_FILTER_KEY: &str = "log_filter";
+
+/// The name of the log file that is currently being written to. Older files
+/// are renamed to `awgen.1.log`, `awgen.2.log`, and so on.
+const LOG_FILE_NAME: &str = "awgen";
+
+/// The size, in bytes, at which the current log file is rolled over.
+const MAX_LOG_SIZE: u64 = 10 * 1024 * 1024;
+
+/// The number of log files that are kept, including the current one.
+const MAX_LOG_FILES: usize = 5;
+
+/// The directives that are always appended to the log filter, to hide noisy
+/// logs from dependencies.
+const DEPENDENCY_FILTER: &str = "wgpu=error,naga=warn,calloop=debug,polling=debug,cosmic_text=info";
+
+/// A resource holding the log filter that is used when none is stored in the
+/// game database.
+///
+/// This must be inserted before the `LogPlugin` is added.
+#[derive(Debug, Clone, Resource)]
+pub struct DefaultLogFilter(pub String);
+
+impl DefaultLogFilter {
+    /// Creates the default log filter for the given log level.
+    pub fn from_level(level: Level) -> Self {
+        Self(level.to_string().to_lowercase())
+    }
+}
+
+/// A resource that changes the log filter while the game is running.
+#[derive(Resource)]
+pub struct LogFilter {
+    /// The handle used to replace the filter.
+    handle: reload::Handle<EnvFilter, Registry>,
+
+    /// The directives of the current filter, without the dependency filter.
+    directives: String,
+}
+
+impl LogFilter {
+    /// Gets the directives of the current filter.
+    pub fn directives(&self) -> &str {
+        &self.directives
+    }
+
+    /// Replaces the log filter with the given directives.
+    pub fn set(&mut self, directives: &str) -> Result<(), LogFilterError> {
+        let filter = build_filter(directives)?;
+        self.handle
+            .reload(filter)
+            .map_err(|_| LogFilterError::Unavailable)?;
+        self.directives = directives.to_string();
+        Ok(())
+    }
+
+    /// Replaces the log filter with the given directives, and stores them in
+    /// the game database.
+    pub fn set_and_save(
+        &mut self,
+        directives: &str,
+        database: &Database,
+    ) -> Result<(), LogFilterError> {
+        self.set(directives)?;
+        database.set_setting(LOG_FILTER_KEY, directives)?;
+        Ok(())
+    }
+}
+
+/// An error that can occur while changing the log filter.
+#[derive(Debug, thiserror::Error)]
+pub enum LogFilterError {
+    /// The filter directives could not be parsed.
+    #[error("Invalid log filter: {0}")]
+    Parse(#[from] ParseError),
+
+    /// The logger is no longer running.
+    #[error("The logger is not available")]
+    Unavailable,
+
+    /// The filter could not be stored in the game database.
+    #[error("Failed to store log filter: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Builds a filter from the given directives and the dependency filter.
+fn build_filter(directives: &str) -> Result<EnvFilter, ParseError> {
+    EnvFilter::builder().parse(format!("{},{}", directives, DEPENDENCY_FILTER))
+}
+
+/// Creates the log layers of the engine: the runtime log filter, the rolling
+/// log file, and the crash reporter log history.
+///
+/// This is intended to be used as the `custom_layer` of the `LogPlugin`, whose
+/// own filter should allow all levels so that this filter has the final say.
+/// It inserts the [`LogFilter`] resource.
+pub fn log_layer(app_: &mut App) -> Option<BoxedLayer> {
+    let world = app_.world();
+    let default_filter = world
+        .get_resource::<DefaultLogFilter>()
+        .map(|filter| filter.0.clone())
+        .unwrap_or_else(|| "info".to_string());
+
+    let stored_filter = world
+        .get_resource::<GameDatabase>()
+        .and_then(|database| database.get_setting(LOG_FILTER_KEY).ok().flatten());
+
+    let logs_folder = world
+        .get_resource::<ProjectSettings>()
+        .map(|project| project.project_folder().join(LOGS_FOLDER));
+
+    // The logger is not running yet, so problems are reported on stderr.
+    let directives = match stored_filter {
+        Some(stored) => match build_filter(&stored) {
+            Ok(_) => stored,
+            Err(err) => {
+                eprintln!("Ignoring invalid stored log filter \"{}\": {}", stored, err);
+                default_filter
+            }
+        },
+        None => default_filter,
+    };
+    let filter = build_filter(&directives).unwrap_or_default();
+
+    let (filter, handle) = reload::Layer::new(filter);
+    app_.insert_resource(LogFilter { handle, directives });
+
+    let file_layer = logs_folder.and_then(|folder| match RollingFile::open(&folder) {
+        Ok(file) => Some(
+            fmt::Layer::default()
+                .with_ansi(false)
+                .with_writer(Mutex::new(file))
+                .boxed(),
+        ),
+        Err(err) => {
+            eprintln!("Failed to open log file in {}: {}", folder.display(), err);
+            None
+        }
+    });
+
+    Some(Box::new(
+        filter.and_then(file_layer).and_then(crash::log_layer(app_)),
+    ))
+}
+
+/// A log file that is rolled over once it grows too large, keeping a limited
+/// number of older files.
+struct RollingFile {
+    /// The folder that the log files are written to.
+    folder: PathBuf,
+
+    /// The current log file.
+    file: File,
+
+    /// The number of bytes written to the current log file.
+    size: u64,
+}
+
+impl RollingFile {
+    /// Opens a new log file in the given folder, rolling over the log file of
+    /// the previous session.
+    fn open(folder: &Path) -> io::Result<Self> {
+        fs::create_dir_all(folder)?;
+        let file = roll_over(folder)?;
+
+        Ok(Self {
+            folder: folder.to_path_buf(),
+            file,
+            size: 0,
+        })
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size + buf.len() as u64 > MAX_LOG_SIZE {
+            self.file.flush()?;
+            self.file = roll_over(&self.folder)?;
+            self.size = 0;
+        }
+
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Gets the path of the log file with the given index, where 0 is the current
+/// log file.
+fn log_path(folder: &Path, index: usize) -> PathBuf {
+    match index {
+        0 => folder.join(format!("{}.log", LOG_FILE_NAME)),
+        index => folder.join(format!("{}.{}.log", LOG_FILE_NAME, index)),
+    }
+}
+
+/// Shifts each existing log file to the next index, deleting the oldest, and
+/// creates a new, empty current log file.
+fn roll_over(folder: &Path) -> io::Result<File> {
+    let _ = fs::remove_file(log_path(folder, MAX_LOG_FILES - 1));
+    for index in (0 .. MAX_LOG_FILES - 1).rev() {
+        let path = log_path(folder, index);
+        if path.exists() {
+            fs::rename(path, log_path(folder, index + 1))?;
+        }
+    }
+
+    File::create(log_path(folder, 0))
+}
@@ -1,24 +1,18 @@
-#![doc = include_str!("../../../README.md")]
+//! The launcher for the Awgen game engine.
+
 #![warn(missing_docs)]
 #![warn(clippy::missing_docs_in_private_items)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use awgen::database::{Database, DatabaseError};
+use awgen::scripts::{self, PacketIn, ScriptEngineSettings, ScriptLimits};
+use awgen::{app, crash};
 use bevy::prelude::*;
 use clap::Parser;
 
-use crate::database::Database;
-use crate::scripts::PacketIn;
-
-mod app;
-mod database;
-mod map;
-mod scripts;
-mod tiles;
-mod ux;
-
 /// The arguments for the command line interface.
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
@@ -30,16 +24,104 @@ struct Args {
     /// Whether to run the game in editor mode.
     #[arg(long, default_value_t = false)]
     editor: bool,
+
+    /// Whether to open the project in read-only mode. This is enabled
+    /// automatically if the project cannot be opened for writing.
+    #[arg(long, default_value_t = false)]
+    read_only: bool,
+
+    /// Runs game logic in fixed-tick mode at the given rate, in ticks per
+    /// second, instead of once per frame.
+    #[arg(long, value_name = "HZ")]
+    tick_rate: Option<f64>,
+
+    /// Writes a backup of the game database to the given file and exits,
+    /// without starting the game.
+    #[arg(long, value_name = "FILE", conflicts_with = "restore")]
+    backup: Option<PathBuf>,
+
+    /// Restores the game database from the given backup file before opening
+    /// the project.
+    #[arg(long, value_name = "FILE")]
+    restore: Option<PathBuf>,
+
+    /// The maximum size of the script heap, in megabytes. A size of 0 disables
+    /// the heap limit.
+    #[arg(long, value_name = "MB")]
+    script_heap: Option<usize>,
+
+    /// The longest a script may run without waiting for the engine, in
+    /// milliseconds. A budget of 0 disables the execution time limit.
+    #[arg(long, value_name = "MS")]
+    script_budget: Option<u64>,
+
+    /// Writes TypeScript definitions for the script engine packets to the
+    /// given folder and exits, without opening the project.
+    #[arg(long, value_name = "DIR")]
+    emit_types: Option<PathBuf>,
 }
 
 /// Run the Awgen game engine.
 fn main() -> AppExit {
     let args = Args::parse();
 
-    let db = Arc::new(Database::new(&args.project).unwrap_or_else(|err| {
-        eprintln!("Failed to open database: {}", err);
-        std::process::exit(1);
-    }));
+    if let Some(folder) = &args.emit_types {
+        return match scripts::emit_types(folder) {
+            Ok(()) => {
+                println!("Wrote script type definitions to {}", folder.display());
+                AppExit::Success
+            }
+            Err(err) => {
+                eprintln!("Failed to write script type definitions: {}", err);
+                AppExit::from_code(1)
+            }
+        };
+    }
+
+    if args
+        .tick_rate
+        .is_some_and(|rate| !rate.is_finite() || rate <= 0.0)
+    {
+        eprintln!("The tick rate must be a positive number.");
+        return AppExit::from_code(1);
+    }
+
+    crash::install_panic_hook(&args.project);
+
+    if let Some(path) = &args.backup {
+        let result = Database::new_read_only(&args.project).and_then(|db| db.backup_to(path));
+        return match result {
+            Ok(()) => {
+                println!("Wrote database backup to {}", path.display());
+                AppExit::Success
+            }
+            Err(err) => {
+                eprintln!("Failed to back up database: {}", err);
+                AppExit::from_code(1)
+            }
+        };
+    }
+
+    if let Some(path) = &args.restore {
+        if args.read_only {
+            eprintln!("Cannot restore a backup in read-only mode.");
+            return AppExit::from_code(1);
+        }
+
+        if let Err(err) = Database::new(&args.project).and_then(|db| db.restore_from(path)) {
+            eprintln!("Failed to restore database backup: {}", err);
+            return AppExit::from_code(1);
+        }
+        println!("Restored database backup from {}", path.display());
+    }
+
+    let db = Arc::new(
+        open_database(&args.project, args.read_only).unwrap_or_else(|err| {
+            eprintln!("Failed to open database: {}", err);
+            std::process::exit(1);
+        }),
+    );
+    let read_only = db.is_read_only();
 
     let script_path = if args.editor {
         args.project.join("editor/scripts")
@@ -47,7 +129,19 @@ fn main() -> AppExit {
         args.project.join("scripts")
     };
 
-    let mut sockets = match scripts::start_script_engine(script_path, db) {
+    let mut limits = ScriptLimits::default();
+    if let Some(megabytes) = args.script_heap {
+        limits = limits.with_max_heap_mb(megabytes);
+    }
+    if let Some(milliseconds) = args.script_budget {
+        limits = limits.with_callback_budget_ms(milliseconds);
+    }
+
+    let script_settings = ScriptEngineSettings::new(script_path)
+        .with_libraries(args.project.join("libs"))
+        .with_limits(limits);
+
+    let mut sockets = match scripts::start_script_engine(script_settings, db.clone()) {
         Ok(sockets) => sockets,
         Err(err) => {
             eprintln!("Failed to start script engine: {}", err);
@@ -69,7 +163,7 @@ fn main() -> AppExit {
         }
     };
 
-    let PacketIn::Init { name, version } = init_packet else {
+    let PacketIn::Init { name, version, .. } = init_packet else {
         eprintln!("Script Engine failed to properly initialize the game.");
         if let Err(err2) = sockets.shutdown_blocking() {
             eprintln!("The script engine has crashed: {}", err2);
@@ -85,7 +179,30 @@ fn main() -> AppExit {
         vsync: true,
         fullscreen: false,
         editor: args.editor,
+        read_only,
+        tick_rate: args.tick_rate,
     };
 
-    app::run(settings, sockets)
+    app::run(settings, sockets, db)
+}
+
+/// Opens the game database in the given project folder.
+///
+/// If the database cannot be opened for writing, such as when the project is
+/// stored on a read-only filesystem, it is opened in read-only mode instead.
+fn open_database(project: &Path, read_only: bool) -> Result<Database, DatabaseError> {
+    if read_only {
+        return Database::new_read_only(project);
+    }
+
+    match Database::new(project) {
+        Ok(db) => Ok(db),
+        Err(err) => {
+            eprintln!(
+                "Failed to open database for writing: {}. Retrying in read-only mode.",
+                err
+            );
+            Database::new_read_only(project)
+        }
+    }
 }
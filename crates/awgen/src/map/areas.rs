@@ -0,0 +1,188 @@
+//! This module implements the in-memory index of the areas stored in the game
+//! database, which allows quickly finding the areas that contain a block.
+//!
+//! Areas are bucketed by the chunks they overlap, so a lookup only needs to
+//! test the few areas that touch the chunk of the queried block. An area is
+//! listed in the bucket of every chunk it overlaps, so areas may overlap at
+//! most [`MAX_AREA_CHUNKS`] chunks.
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::database::GameDatabase;
+use crate::database::areas::Area;
+use crate::database::backup::GameDatabaseRestored;
+use crate::map::{BlockRegion, ChunkPos, WorldPos};
+
+/// The largest number of chunks that an area may overlap.
+pub const MAX_AREA_CHUNKS: u64 = 65_536;
+
+/// An error returned when an area overlaps more than [`MAX_AREA_CHUNKS`]
+/// chunks.
+#[derive(Debug, thiserror::Error)]
+#[error("Area {name:?} overlaps {chunks} chunks, but at most {MAX_AREA_CHUNKS} are allowed")]
+pub struct AreaTooLarge {
+    /// The name of the area.
+    pub name: String,
+
+    /// The number of chunks the area overlaps.
+    pub chunks: u64,
+}
+
+/// A resource that indexes the areas of the world by chunk.
+#[derive(Debug, Default, Resource)]
+pub struct AreaIndex {
+    /// The areas, keyed by name.
+    areas: HashMap<String, Area>,
+
+    /// The names of the areas that overlap each chunk.
+    chunks: HashMap<ChunkPos, Vec<String>>,
+}
+
+impl AreaIndex {
+    /// Creates a new index containing the given areas.
+    ///
+    /// Areas that are too large to be indexed are skipped, with a warning
+    /// logged.
+    pub fn new(areas: impl IntoIterator<Item = Area>) -> Self {
+        let mut index = Self::default();
+        for area in areas {
+            if let Err(err) = index.insert(area) {
+                warn!("Skipping area: {}", err);
+            }
+        }
+        index
+    }
+
+    /// Gets the area with the given name, if it exists.
+    pub fn get(&self, name: &str) -> Option<&Area> {
+        self.areas.get(name)
+    }
+
+    /// Iterates over all areas, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &Area> {
+        self.areas.values()
+    }
+
+    /// Returns the number of indexed areas.
+    pub fn len(&self) -> usize {
+        self.areas.len()
+    }
+
+    /// Returns true if no areas are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.areas.is_empty()
+    }
+
+    /// Adds an area to the index, replacing any existing area with the same
+    /// name.
+    ///
+    /// Returns an error, leaving the index unchanged, if the area overlaps more
+    /// than [`MAX_AREA_CHUNKS`] chunks.
+    pub fn insert(&mut self, area: Area) -> Result<(), AreaTooLarge> {
+        check_area_size(&area)?;
+        self.remove(&area.name);
+
+        for chunk in overlapped_chunks(&area) {
+            self.chunks
+                .entry(chunk)
+                .or_default()
+                .push(area.name.clone());
+        }
+
+        self.areas.insert(area.name.clone(), area);
+        Ok(())
+    }
+
+    /// Removes the area with the given name from the index, returning it if it
+    /// existed.
+    pub fn remove(&mut self, name: &str) -> Option<Area> {
+        let area = self.areas.remove(name)?;
+
+        for chunk in overlapped_chunks(&area) {
+            if let Some(names) = self.chunks.get_mut(&chunk) {
+                names.retain(|other| other != name);
+                if names.is_empty() {
+                    self.chunks.remove(&chunk);
+                }
+            }
+        }
+
+        Some(area)
+    }
+
+    /// Gets all areas that contain the given block, sorted by name.
+    pub fn areas_at(&self, pos: WorldPos) -> Vec<&Area> {
+        let Some(names) = self.chunks.get(&pos.as_chunk_pos()) else {
+            return Vec::new();
+        };
+
+        let mut areas = names
+            .iter()
+            .filter_map(|name| self.areas.get(name))
+            .filter(|area| area.region.contains(pos))
+            .collect::<Vec<_>>();
+        areas.sort_by(|a, b| a.name.cmp(&b.name));
+        areas
+    }
+}
+
+/// Gets the number of chunks that the given region overlaps.
+pub fn chunk_count(region: BlockRegion) -> u64 {
+    let min = region.min.as_chunk_pos();
+    let max = region.max.as_chunk_pos();
+    let span = |min: i32, max: i32| (max as i64 - min as i64 + 1).max(0) as u64;
+
+    span(min.x, max.x)
+        .saturating_mul(span(min.y, max.y))
+        .saturating_mul(span(min.z, max.z))
+}
+
+/// Checks that the given area overlaps at most [`MAX_AREA_CHUNKS`] chunks.
+pub fn check_area_size(area: &Area) -> Result<(), AreaTooLarge> {
+    let chunks = chunk_count(area.region);
+    if chunks > MAX_AREA_CHUNKS {
+        return Err(AreaTooLarge {
+            name: area.name.clone(),
+            chunks,
+        });
+    }
+
+    Ok(())
+}
+
+/// Iterates over the positions of all chunks that the given area overlaps.
+///
+/// The area must have been checked with [`check_area_size`], as this visits
+/// every overlapped chunk.
+fn overlapped_chunks(area: &Area) -> impl Iterator<Item = ChunkPos> {
+    let min = area.region.min.as_chunk_pos();
+    let max = area.region.max.as_chunk_pos();
+
+    (min.z ..= max.z).flat_map(move |z| {
+        (min.y ..= max.y).flat_map(move |y| (min.x ..= max.x).map(move |x| ChunkPos::new(x, y, z)))
+    })
+}
+
+/// Reloads the areas into the [`AreaIndex`] when the game database has been
+/// restored from a backup.
+pub(super) fn reload_areas_on_restore(
+    mut restored: MessageReader<GameDatabaseRestored>,
+    database: Res<GameDatabase>,
+    index: ResMut<AreaIndex>,
+) {
+    if restored.read().count() > 0 {
+        load_areas(database, index);
+    }
+}
+
+/// Loads the areas stored in the game database into the [`AreaIndex`].
+pub(super) fn load_areas(database: Res<GameDatabase>, mut index: ResMut<AreaIndex>) {
+    match database.list_areas() {
+        Ok(areas) => {
+            debug!("Loaded {} areas", areas.len());
+            *index = AreaIndex::new(areas);
+        }
+        Err(err) => error!("Failed to load areas: {}", err),
+    }
+}
@@ -0,0 +1,279 @@
+//! This module implements billboard sprites, which are flat textured quads
+//! placed in the world that turn to face the camera.
+//!
+//! Billboards are identified by a script-defined ID, and are spawned, updated,
+//! and removed through packets from the script engine. Billboards are rendered
+//! with an alpha mask rather than alpha blending, so they write to the depth
+//! buffer and are correctly sorted against terrain and other billboards.
+//!
+//! Scripts may opt billboards into click and hover events, which are sent back
+//! to the script engine when the player points at them.
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::scripts::PacketIn;
+
+/// The alpha value below which billboard texels are discarded.
+const ALPHA_CUTOFF: f32 = 0.5;
+
+/// How a billboard is oriented towards the camera.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum BillboardMode {
+    /// The billboard always faces the camera directly.
+    #[default]
+    Camera,
+
+    /// The billboard stays upright, rotating only around the vertical axis to
+    /// face the camera.
+    AxisLocked,
+}
+
+/// A sprite rendered in the world that turns to face the camera.
+///
+/// The sprite is anchored at the bottom center of its quad, so the position of
+/// a billboard is where it stands on the ground.
+#[derive(Debug, Component)]
+#[require(Transform, Visibility)]
+pub struct Billboard {
+    /// The script-defined ID of this billboard.
+    id: String,
+
+    /// The texture path of this billboard.
+    texture: String,
+
+    /// How this billboard is oriented towards the camera.
+    pub mode: BillboardMode,
+
+    /// Whether clicking this billboard is reported to the script engine.
+    pub clickable: bool,
+
+    /// Whether the cursor entering or leaving this billboard is reported to
+    /// the script engine.
+    pub hoverable: bool,
+}
+
+impl Billboard {
+    /// Gets the script-defined ID of this billboard.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Gets the texture path of this billboard.
+    pub fn texture(&self) -> &str {
+        &self.texture
+    }
+}
+
+/// A resource that maps script-defined billboard IDs to their entities, and
+/// holds the render assets shared between billboards.
+#[derive(Debug, Default, Resource)]
+pub struct BillboardTable {
+    /// The billboard entities, keyed by ID.
+    table: HashMap<String, Entity>,
+
+    /// The unit quad mesh shared by all billboards.
+    quad: Option<Handle<Mesh>>,
+
+    /// The billboard materials, keyed by texture path.
+    materials: HashMap<String, Handle<StandardMaterial>>,
+}
+
+impl BillboardTable {
+    /// Gets the billboard with the given ID, if it exists.
+    pub fn get(&self, id: &str) -> Option<Entity> {
+        self.table.get(id).copied()
+    }
+
+    /// Returns the number of billboards currently in the world.
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Returns true if there are no billboards in the world.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+}
+
+/// Handles a billboard packet from the script engine.
+pub(crate) fn handle(world: &mut World, packet: PacketIn) {
+    match packet {
+        PacketIn::SpawnSprite {
+            id,
+            texture,
+            position,
+            size,
+            mode,
+            clickable,
+            hoverable,
+        } => {
+            if let Some(old) = world.resource::<BillboardTable>().get(&id) {
+                world.despawn(old);
+            }
+
+            let mesh = quad_mesh(world);
+            let material = texture_material(world, &texture);
+            let entity = world
+                .spawn((
+                    Billboard {
+                        id: id.clone(),
+                        texture,
+                        mode: mode.unwrap_or_default(),
+                        clickable: clickable.unwrap_or_default(),
+                        hoverable: hoverable.unwrap_or_default(),
+                    },
+                    Transform::from_translation(Vec3::from(position))
+                        .with_scale(Vec3::new(size[0], size[1], 1.0)),
+                    Mesh3d(mesh),
+                    MeshMaterial3d(material),
+                ))
+                .id();
+
+            world
+                .resource_mut::<BillboardTable>()
+                .table
+                .insert(id, entity);
+        }
+        PacketIn::UpdateSprite {
+            id,
+            texture,
+            position,
+            size,
+            mode,
+            clickable,
+            hoverable,
+        } => {
+            let Some(entity) = world.resource::<BillboardTable>().get(&id) else {
+                error!("Cannot update sprite \"{}\": no such sprite", id);
+                return;
+            };
+
+            let material = texture
+                .as_deref()
+                .map(|texture| texture_material(world, texture));
+
+            let mut sprite = world.entity_mut(entity);
+            if let Some(material) = material {
+                sprite.insert(MeshMaterial3d(material));
+            }
+
+            if let Some(mut transform) = sprite.get_mut::<Transform>() {
+                if let Some(position) = position {
+                    transform.translation = Vec3::from(position);
+                }
+                if let Some(size) = size {
+                    transform.scale = Vec3::new(size[0], size[1], 1.0);
+                }
+            }
+
+            if let Some(mut billboard) = sprite.get_mut::<Billboard>() {
+                if let Some(texture) = texture {
+                    billboard.texture = texture;
+                }
+                if let Some(mode) = mode {
+                    billboard.mode = mode;
+                }
+                if let Some(clickable) = clickable {
+                    billboard.clickable = clickable;
+                }
+                if let Some(hoverable) = hoverable {
+                    billboard.hoverable = hoverable;
+                }
+            }
+        }
+        PacketIn::DespawnSprite { id } => match world.resource::<BillboardTable>().get(&id) {
+            Some(entity) => {
+                world.despawn(entity);
+            }
+            None => warn!("Cannot despawn sprite \"{}\": no such sprite", id),
+        },
+        _ => {}
+    }
+}
+
+/// Gets the unit quad mesh shared by all billboards, creating it if needed.
+fn quad_mesh(world: &mut World) -> Handle<Mesh> {
+    if let Some(quad) = &world.resource::<BillboardTable>().quad {
+        return quad.clone();
+    }
+
+    let mesh = Mesh::from(Rectangle::new(1.0, 1.0)).translated_by(Vec3::Y * 0.5);
+    let quad = world.resource_mut::<Assets<Mesh>>().add(mesh);
+    world.resource_mut::<BillboardTable>().quad = Some(quad.clone());
+    quad
+}
+
+/// Gets the billboard material for the given texture path, creating it if
+/// needed.
+fn texture_material(world: &mut World, texture: &str) -> Handle<StandardMaterial> {
+    if let Some(material) = world.resource::<BillboardTable>().materials.get(texture) {
+        return material.clone();
+    }
+
+    let image = world.resource::<AssetServer>().load(texture.to_string());
+    let material = world
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial {
+            base_color_texture: Some(image),
+            alpha_mode: AlphaMode::Mask(ALPHA_CUTOFF),
+            cull_mode: None,
+            unlit: true,
+            ..default()
+        });
+
+    world
+        .resource_mut::<BillboardTable>()
+        .materials
+        .insert(texture.to_string(), material.clone());
+    material
+}
+
+/// Removes a billboard from the [`BillboardTable`] when it is despawned.
+pub(super) fn on_billboard_despawn(
+    trigger: On<Remove, Billboard>,
+    billboards: Query<&Billboard>,
+    mut table: ResMut<BillboardTable>,
+) {
+    let Ok(billboard) = billboards.get(trigger.entity) else {
+        return;
+    };
+
+    // A billboard may have been replaced by a new one with the same ID.
+    if table.get(&billboard.id) == Some(trigger.entity) {
+        table.table.remove(&billboard.id);
+    }
+}
+
+/// Rotates all billboards to face the camera.
+///
+/// The transform of a billboard is only written when its rotation changes, so
+/// billboards are not marked as changed every frame while the camera is still.
+pub(super) fn face_camera(
+    camera: Query<&GlobalTransform, With<Camera3d>>,
+    mut billboards: Query<(&Billboard, &mut Transform)>,
+) {
+    let Ok(camera) = camera.single() else {
+        return;
+    };
+
+    let camera_rotation = camera.rotation();
+    let camera_pos = camera.translation();
+
+    for (billboard, mut transform) in billboards.iter_mut() {
+        let rotation = match billboard.mode {
+            BillboardMode::Camera => camera_rotation,
+            BillboardMode::AxisLocked => {
+                let dir = camera_pos - transform.translation;
+                Quat::from_rotation_y(dir.x.atan2(dir.z))
+            }
+        };
+
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
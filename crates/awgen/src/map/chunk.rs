@@ -3,7 +3,9 @@
 use bevy::prelude::*;
 
 use crate::map::ChunkPos;
-use crate::map::model::ChunkModels;
+use crate::map::mesher::SectionMeshes;
+use crate::map::model::{BlockModel, ChunkModels};
+use crate::map::pos::LocalPos;
 
 /// The size of a chunk in blocks along each axis.
 pub const CHUNK_SIZE: usize = 1 << CHUNK_SIZE_BITS as usize;
@@ -17,6 +19,102 @@ pub const CHUNK_SIZE_MASK: i32 = (1 << CHUNK_SIZE_BITS) - 1;
 /// The total number of blocks in a single chunk.
 pub const TOTAL_BLOCKS: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
 
+/// The bit-shift used to convert block coordinates within a chunk to section
+/// coordinates.
+pub const SECTION_SIZE_BITS: i32 = 2;
+
+/// The size of a chunk section in blocks along each axis.
+pub const SECTION_SIZE: usize = 1 << SECTION_SIZE_BITS as usize;
+
+/// The number of sections along each axis of a chunk.
+pub const SECTIONS_PER_AXIS: usize = CHUNK_SIZE / SECTION_SIZE;
+
+/// The total number of sections in a single chunk.
+pub const TOTAL_SECTIONS: usize = SECTIONS_PER_AXIS * SECTIONS_PER_AXIS * SECTIONS_PER_AXIS;
+
+const _: () = assert!(
+    TOTAL_SECTIONS <= 64,
+    "chunk sections must fit in a SectionMask"
+);
+
+/// A set of sections within a chunk, stored as a bitset.
+///
+/// Chunks are split into cubic sections of [`SECTION_SIZE`] blocks, so that a
+/// block change only needs to remesh the sections around it rather than the
+/// whole chunk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SectionMask(u64);
+
+impl SectionMask {
+    /// A mask containing no sections.
+    pub const NONE: Self = Self(0);
+
+    /// A mask containing every section of a chunk.
+    pub const ALL: Self = Self(u64::MAX >> (64 - TOTAL_SECTIONS));
+
+    /// Gets the index of the section containing the given local block
+    /// coordinates.
+    pub fn section_of(pos: IVec3) -> usize {
+        let section = pos >> SECTION_SIZE_BITS;
+        let axis = SECTIONS_PER_AXIS as i32;
+        (section.x + section.y * axis + section.z * axis * axis) as usize
+    }
+
+    /// Gets the local coordinates of the minimum corner block of the given
+    /// section.
+    pub fn section_origin(section: usize) -> IVec3 {
+        let x = section % SECTIONS_PER_AXIS;
+        let y = section / SECTIONS_PER_AXIS % SECTIONS_PER_AXIS;
+        let z = section / (SECTIONS_PER_AXIS * SECTIONS_PER_AXIS);
+        IVec3::new(x as i32, y as i32, z as i32) << SECTION_SIZE_BITS
+    }
+
+    /// Adds the given section to this mask.
+    pub fn insert(&mut self, section: usize) {
+        self.0 |= 1 << section;
+    }
+
+    /// Adds every section whose mesh depends on the block at the given
+    /// position to this mask.
+    ///
+    /// This includes the section containing the block, as well as the sections
+    /// of its direct neighbors within the chunk, whose faces may be occluded by
+    /// the block.
+    pub fn insert_block<P: Into<LocalPos>>(&mut self, pos: P) {
+        let pos = pos.into();
+        self.insert(Self::section_of(*pos));
+
+        for axis in [IVec3::X, IVec3::Y, IVec3::Z] {
+            for neighbor in [*pos - axis, *pos + axis] {
+                if neighbor.min_element() >= 0 && neighbor.max_element() < CHUNK_SIZE as i32 {
+                    self.insert(Self::section_of(neighbor));
+                }
+            }
+        }
+    }
+
+    /// Returns whether or not this mask contains the given section.
+    pub fn contains(self, section: usize) -> bool {
+        self.0 & (1 << section) != 0
+    }
+
+    /// Returns whether or not this mask contains no sections.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Gets the number of sections in this mask.
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Iterates over the indices of the sections in this mask, in ascending
+    /// order.
+    pub fn iter(self) -> impl Iterator<Item = usize> {
+        (0 .. TOTAL_SECTIONS).filter(move |&section| self.contains(section))
+    }
+}
+
 /// Represents a finite cubic grid of blocks within a voxel world.
 #[derive(Debug, Component)]
 #[require(Transform, Visibility)]
@@ -27,8 +125,19 @@ pub struct VoxelChunk {
     /// The models for each block in this chunk.
     models: ChunkModels,
 
-    /// Whether or not this chunk is marked as dirty and needs to be redrawn.
-    dirty: bool,
+    /// The sections of this chunk that are marked as dirty and need to be
+    /// redrawn.
+    dirty: SectionMask,
+
+    /// Whether the models of this chunk were changed since it was last marked
+    /// as clean, as opposed to only needing a redraw.
+    edited: bool,
+
+    /// The cached meshes of each section of this chunk, from the last redraw.
+    ///
+    /// This is taken while a redraw is in progress, in which case the next
+    /// redraw rebuilds every section.
+    pub(super) section_meshes: Option<SectionMeshes>,
 
     /// Entity for the opaque model entity of this chunk.
     pub opaque_entity: Option<Entity>,
@@ -40,7 +149,9 @@ impl VoxelChunk {
         Self {
             pos,
             models: ChunkModels::default(),
-            dirty: false,
+            dirty: SectionMask::NONE,
+            edited: false,
+            section_meshes: None,
             opaque_entity: None,
         }
     }
@@ -50,6 +161,13 @@ impl VoxelChunk {
         self.pos
     }
 
+    /// Gets the cached meshes of each section of this chunk from the last
+    /// redraw, or `None` if the chunk has not been drawn yet or is being
+    /// redrawn.
+    pub fn section_meshes(&self) -> Option<&SectionMeshes> {
+        self.section_meshes.as_ref()
+    }
+
     /// Gets a slice of all block models in this chunk.
     pub fn get_models(&self) -> &ChunkModels {
         &self.models
@@ -57,28 +175,64 @@ impl VoxelChunk {
 
     /// Gets a mutable slice of all block models in this chunk.
     ///
-    /// Calling this method will automatically mark the chunk as dirty.
+    /// Calling this method will automatically mark the whole chunk as dirty.
+    /// Prefer [`VoxelChunk::set_model`] when changing individual blocks, so
+    /// that only the affected sections are redrawn.
     pub fn get_models_mut(&mut self) -> &mut ChunkModels {
-        self.dirty = true;
+        self.dirty = SectionMask::ALL;
+        self.edited = true;
         &mut self.models
     }
 
+    /// Sets the block model at the specified local position within the chunk.
+    ///
+    /// Only the sections whose mesh depends on the block are marked as dirty.
+    pub fn set_model<P: Into<LocalPos>>(&mut self, pos: P, model: BlockModel) {
+        let pos = pos.into();
+        *self.models.get_mut(pos) = model;
+        self.dirty.insert_block(pos);
+        self.edited = true;
+    }
+
     /// Returns whether or not this chunk is marked as dirty and needs to be
     /// redrawn.
     ///
     /// A clean chunk does not guarantee that it has been redrawn, only that any
     /// redraw requests have already been processed.
     pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// Returns whether or not the models of this chunk were changed since it
+    /// was last marked as clean.
+    ///
+    /// Unlike [`VoxelChunk::is_dirty`], this is not set by
+    /// [`VoxelChunk::mark_dirty`], so chunks that are only redrawn, such as
+    /// when the active tileset changes, are not reported as edited.
+    pub fn is_edited(&self) -> bool {
+        self.edited
+    }
+
+    /// Gets the sections of this chunk that are marked as dirty.
+    pub fn dirty_sections(&self) -> SectionMask {
         self.dirty
     }
 
-    /// Marks this chunk as clean and not needing to be redrawn.
+    /// Marks this chunk as dirty, so that it is redrawn even though its models
+    /// have not changed, such as when the active tileset changes.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = SectionMask::ALL;
+    }
+
+    /// Marks this chunk as clean and not needing to be redrawn, returning the
+    /// sections that were dirty.
     ///
     /// This method is usually called after a redraw has been scheduled. Note
     /// that this does not guarantee that the chunk has been redrawn, only that
     /// it has been scheduled for redraw.
-    pub(super) fn mark_clean(&mut self) {
-        self.dirty = false;
+    pub(super) fn mark_clean(&mut self) -> SectionMask {
+        self.edited = false;
+        std::mem::take(&mut self.dirty)
     }
 }
 
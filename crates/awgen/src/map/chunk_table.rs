@@ -29,6 +29,12 @@ impl ChunkTable {
         self.table.remove(&pos);
     }
 
+    /// Removes every chunk from the table, such as when the chunks of the map
+    /// are replaced.
+    pub fn clear(&mut self) {
+        self.table.clear();
+    }
+
     /// Returns the number of chunks currently stored in the table.
     pub fn len(&self) -> usize {
         self.table.len()
@@ -16,6 +16,10 @@ pub const MESH_COUNT: DiagnosticPath = DiagnosticPath::const_new("map/mesh_count
 /// The name of the triangle count diagnostic.
 pub const TRIANGLE_COUNT: DiagnosticPath = DiagnosticPath::const_new("map/triangle_count");
 
+/// The name of the remesh time diagnostic, which measures the time in
+/// milliseconds that the main thread spends on chunk meshing each frame.
+pub const REMESH_TIME: DiagnosticPath = DiagnosticPath::const_new("map/remesh_time");
+
 /// The plugin that adds map diagnostics to the application.
 pub struct MapDiagnosticsPlugin;
 impl Plugin for MapDiagnosticsPlugin {
@@ -23,6 +27,7 @@ impl Plugin for MapDiagnosticsPlugin {
         app_.register_diagnostic(Diagnostic::new(CHUNK_COUNT).with_max_history_length(1))
             .register_diagnostic(Diagnostic::new(MESH_COUNT).with_max_history_length(1))
             .register_diagnostic(Diagnostic::new(TRIANGLE_COUNT).with_max_history_length(1))
+            .register_diagnostic(Diagnostic::new(REMESH_TIME).with_suffix("ms"))
             .add_systems(Update, (mesh_updates, chunks_updated));
     }
 }
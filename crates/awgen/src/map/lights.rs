@@ -0,0 +1,184 @@
+//! This module spawns the lights placed in the world as Bevy point and spot
+//! lights.
+//!
+//! Each placed light is an entity with a [`SceneLight`] component holding the
+//! stored light. Whenever that component changes, the light entity is updated
+//! to match, so editing a light only requires mutating its component.
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::database::GameDatabase;
+use crate::database::backup::GameDatabaseRestored;
+use crate::database::lights::{LightKind, PlacedLight};
+
+/// A light placed in the world.
+#[derive(Debug, Component)]
+#[require(Transform, Visibility)]
+pub struct SceneLight(pub PlacedLight);
+
+impl SceneLight {
+    /// Gets the transform of this light, which points spot lights along their
+    /// pitch and yaw.
+    pub fn transform(&self) -> Transform {
+        let light = &self.0;
+        Transform::from_translation(Vec3::from(light.position)).with_rotation(Quat::from_euler(
+            EulerRot::YXZ,
+            light.yaw.to_radians(),
+            light.pitch.to_radians(),
+            0.0,
+        ))
+    }
+}
+
+/// A resource that maps light names to their entities.
+#[derive(Debug, Default, Resource)]
+pub struct LightTable {
+    /// The light entities, keyed by name.
+    table: HashMap<String, Entity>,
+}
+
+impl LightTable {
+    /// Gets the light with the given name, if it exists.
+    pub fn get(&self, name: &str) -> Option<Entity> {
+        self.table.get(name).copied()
+    }
+
+    /// Iterates over all light entities, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.table.values().copied()
+    }
+
+    /// Returns the number of lights currently in the world.
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Returns true if there are no lights in the world.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+}
+
+/// Adds a light to the [`LightTable`] when it is spawned, despawning any
+/// existing light with the same name.
+pub(crate) fn on_light_add(
+    trigger: On<Add, SceneLight>,
+    lights: Query<&SceneLight>,
+    mut table: ResMut<LightTable>,
+    mut commands: Commands,
+) {
+    let Ok(light) = lights.get(trigger.entity) else {
+        return;
+    };
+
+    if let Some(old) = table.table.insert(light.0.name.clone(), trigger.entity) {
+        if old != trigger.entity {
+            commands.entity(old).despawn();
+        }
+    }
+}
+
+/// Removes a light from the [`LightTable`] when it is despawned.
+pub(crate) fn on_light_remove(
+    trigger: On<Remove, SceneLight>,
+    lights: Query<&SceneLight>,
+    mut table: ResMut<LightTable>,
+) {
+    let Ok(light) = lights.get(trigger.entity) else {
+        return;
+    };
+
+    // A light may have been replaced by a new one with the same name.
+    if table.get(&light.0.name) == Some(trigger.entity) {
+        table.table.remove(&light.0.name);
+    }
+}
+
+/// Updates the Bevy light of every light entity whose [`SceneLight`] has
+/// changed.
+pub(super) fn update_lights(
+    mut lights: Query<(Entity, &SceneLight, &mut Transform, &mut Visibility), Changed<SceneLight>>,
+    mut commands: Commands,
+) {
+    for (entity, light, mut transform, mut visibility) in lights.iter_mut() {
+        *transform = light.transform();
+        *visibility = if light.0.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        let PlacedLight {
+            kind,
+            color: [red, green, blue],
+            intensity,
+            range,
+            angle,
+            shadows,
+            ..
+        } = light.0;
+        let color = Color::srgb(red, green, blue);
+
+        match kind {
+            LightKind::Point => {
+                commands
+                    .entity(entity)
+                    .remove::<SpotLight>()
+                    .insert(PointLight {
+                        color,
+                        intensity,
+                        range,
+                        shadows_enabled: shadows,
+                        ..default()
+                    });
+            }
+            LightKind::Spot => {
+                let angle = angle.to_radians();
+                commands
+                    .entity(entity)
+                    .remove::<PointLight>()
+                    .insert(SpotLight {
+                        color,
+                        intensity,
+                        range,
+                        shadows_enabled: shadows,
+                        outer_angle: angle,
+                        inner_angle: angle * 0.8,
+                        ..default()
+                    });
+            }
+        }
+    }
+}
+
+/// Respawns the lights when the game database has been restored from a backup.
+pub(super) fn reload_lights_on_restore(
+    mut restored: MessageReader<GameDatabaseRestored>,
+    database: Res<GameDatabase>,
+    lights: Query<Entity, With<SceneLight>>,
+    mut commands: Commands,
+) {
+    if restored.read().count() == 0 {
+        return;
+    }
+
+    for entity in lights.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    load_lights(database, commands);
+}
+
+/// Spawns the lights stored in the game database.
+pub(super) fn load_lights(database: Res<GameDatabase>, mut commands: Commands) {
+    match database.list_lights() {
+        Ok(lights) => {
+            debug!("Loaded {} lights", lights.len());
+            for light in lights {
+                commands.spawn(SceneLight(light));
+            }
+        }
+        Err(err) => error!("Failed to load lights: {}", err),
+    }
+}
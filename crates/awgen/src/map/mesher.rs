@@ -1,34 +1,93 @@
 //! This module generates a renderable mesh from a voxel chunk.
+//!
+//! Chunks are meshed one section at a time, and the section meshes are cached
+//! in [`SectionMeshes`], so that a block change only rebuilds the sections
+//! around it before merging every section into the final chunk mesh.
 
 use bevy::prelude::*;
 
+use crate::map::chunk::{SECTION_SIZE, SectionMask, TOTAL_SECTIONS};
 use crate::map::model::ChunkModels;
-use crate::map::{CHUNK_SIZE, Occlusion, WorldPos};
-use crate::tiles::TerrainMesh;
+use crate::map::{Occlusion, WorldPos};
+use crate::tiles::{TerrainMesh, TilePalette};
 
-/// Generates a mesh from the given chunk.
-pub fn build_mesh(chunk: &ChunkModels) -> ChunkMesh {
+/// Generates a mesh from the given chunk, resolving the tiles of block faces
+/// with the given palette.
+pub fn build_mesh(chunk: &ChunkModels, palette: &TilePalette) -> ChunkMesh {
+    let mut sections = SectionMeshes::default();
+    sections.rebuild(chunk, palette, SectionMask::ALL);
+    sections.merge()
+}
+
+/// Generates the mesh of a single section of the given chunk, resolving the
+/// tiles of block faces with the given palette.
+pub fn build_section_mesh(
+    chunk: &ChunkModels,
+    palette: &TilePalette,
+    section: usize,
+) -> TerrainMesh {
     let mut mesh = TerrainMesh::new();
+    let origin = SectionMask::section_origin(section);
 
-    for x in 0 .. CHUNK_SIZE as i32 {
-        for y in 0 .. CHUNK_SIZE as i32 {
-            for z in 0 .. CHUNK_SIZE as i32 {
+    for x in origin.x .. origin.x + SECTION_SIZE as i32 {
+        for y in origin.y .. origin.y + SECTION_SIZE as i32 {
+            for z in origin.z .. origin.z + SECTION_SIZE as i32 {
                 let pos = WorldPos::new(x, y, z);
                 let model = &chunk.get(pos);
                 let transform = Transform::from_xyz(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5);
                 let occlusion = Occlusion::from_chunk_models(chunk, pos.into());
-                model.draw(&mut mesh, transform, occlusion);
+                model.draw(&mut mesh, transform, occlusion, palette);
             }
         }
     }
 
-    let mut chunk_mesh = ChunkMesh::default();
+    mesh
+}
+
+/// The cached meshes of each section of a chunk.
+#[derive(Debug, Clone)]
+pub struct SectionMeshes {
+    /// The mesh of each section, in section index order.
+    sections: Vec<TerrainMesh>,
+}
 
-    if !mesh.is_empty() {
-        chunk_mesh.opaque = Some(mesh.into());
+impl Default for SectionMeshes {
+    fn default() -> Self {
+        Self {
+            sections: vec![TerrainMesh::default(); TOTAL_SECTIONS],
+        }
     }
+}
 
-    chunk_mesh
+impl SectionMeshes {
+    /// Rebuilds the meshes of the given sections of the chunk.
+    pub fn rebuild(&mut self, chunk: &ChunkModels, palette: &TilePalette, sections: SectionMask) {
+        for section in sections.iter() {
+            self.sections[section] = build_section_mesh(chunk, palette, section);
+        }
+    }
+
+    /// Merges the meshes of every section into a single chunk mesh.
+    pub fn merge(&self) -> ChunkMesh {
+        let mesh = self.terrain_mesh();
+        let mut chunk_mesh = ChunkMesh::default();
+
+        if !mesh.is_empty() {
+            chunk_mesh.opaque = Some(mesh.into());
+        }
+
+        chunk_mesh
+    }
+
+    /// Merges the meshes of every section into a single terrain mesh, without
+    /// converting it into a renderable mesh.
+    pub fn terrain_mesh(&self) -> TerrainMesh {
+        let mut mesh = TerrainMesh::new();
+        for section in self.sections.iter() {
+            mesh.append(section, Transform::IDENTITY);
+        }
+        mesh
+    }
 }
 
 /// A multi-part mesh generated from a voxel chunk.
@@ -2,22 +2,44 @@
 
 use bevy::prelude::*;
 
+mod areas;
+pub(crate) mod billboard;
 mod chunk;
 mod chunk_table;
 mod diagnostics;
+mod lights;
 mod mesher;
 mod messages;
 mod model;
 mod occlusion;
+mod patch;
 mod pos;
+mod raycast;
+mod settings;
 mod systems;
 
-pub use chunk::{CHUNK_SIZE, TOTAL_BLOCKS, VoxelChunk};
+pub use areas::{AreaIndex, AreaTooLarge, MAX_AREA_CHUNKS, check_area_size, chunk_count};
+pub use billboard::{Billboard, BillboardMode, BillboardTable};
+pub use chunk::{CHUNK_SIZE, SECTION_SIZE, SectionMask, TOTAL_BLOCKS, TOTAL_SECTIONS, VoxelChunk};
 pub use chunk_table::ChunkTable;
-pub use diagnostics::{CHUNK_COUNT, MESH_COUNT, TRIANGLE_COUNT};
-pub use model::BlockModel;
-pub use occlusion::Occlusion;
+pub use diagnostics::{CHUNK_COUNT, MESH_COUNT, REMESH_TIME, TRIANGLE_COUNT};
+pub use lights::{LightTable, SceneLight};
+pub(crate) use lights::{on_light_add, on_light_remove};
+pub use mesher::{ChunkMesh, SectionMeshes, build_mesh, build_section_mesh};
+pub use model::{BlockModel, ChunkModels, Cube, TileFace, UvWindow};
+pub use occlusion::{Occluder, Occlusion};
+pub use patch::{
+    BlockChange,
+    BlockRegion,
+    MapPatch,
+    MapSnapshot,
+    MapSnapshots,
+    get_block,
+    set_block,
+};
 pub use pos::{ChunkPos, WorldPos};
+pub use raycast::{BlockFace, BlockHit, MAX_RAYCAST_DISTANCE, raycast_blocks};
+pub use settings::{MapSettings, MapSettingsError};
 
 /// This plugin is responsible for rendering the map in the Awgen application.
 pub struct MapPlugin;
@@ -25,13 +47,34 @@ impl Plugin for MapPlugin {
     fn build(&self, app_: &mut App) {
         app_.add_plugins(diagnostics::MapDiagnosticsPlugin)
             .init_resource::<chunk_table::ChunkTable>()
+            .init_resource::<billboard::BillboardTable>()
+            .init_resource::<patch::MapSnapshots>()
+            .init_resource::<areas::AreaIndex>()
+            .init_resource::<lights::LightTable>()
             .add_message::<messages::ChunkMeshUpdated>()
             .add_message::<messages::ChunkCreated>()
             .add_message::<messages::ChunkRemoved>()
+            .add_systems(Startup, (areas::load_areas, lights::load_lights))
             .add_systems(
                 Update,
-                systems::redraw_chunks.in_set(MapSystemSets::RedrawChunks),
+                (
+                    areas::reload_areas_on_restore,
+                    lights::reload_lights_on_restore,
+                    lights::update_lights,
+                    settings::save_map_settings.run_if(
+                        resource_exists::<settings::MapSettings>
+                            .and(resource_changed::<settings::MapSettings>),
+                    ),
+                    systems::redraw_chunks.in_set(MapSystemSets::RedrawChunks),
+                ),
             )
+            .add_systems(
+                PostUpdate,
+                billboard::face_camera.before(TransformSystems::Propagate),
+            )
+            .add_observer(billboard::on_billboard_despawn)
+            .add_observer(on_light_add)
+            .add_observer(on_light_remove)
             .add_observer(systems::on_chunk_spawn)
             .add_observer(systems::on_chunk_despawn);
     }
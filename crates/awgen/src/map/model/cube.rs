@@ -2,13 +2,15 @@
 
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
 use crate::map::Occlusion;
 use crate::map::model::TileFace;
-use crate::tiles::{TerrainMesh, TerrainPoly, TerrainQuad};
+use crate::map::raycast::BlockFace;
+use crate::tiles::{TerrainMesh, TerrainPoly, TerrainQuad, TilePalette};
 
 /// A cube block model.
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase", deny_unknown_fields, default)]
 pub struct Cube {
     /// The tile information for the top (Y+) face of the cube.
@@ -28,8 +30,41 @@ pub struct Cube {
 }
 
 impl Cube {
-    /// Draws the cube into the provided mesh at the specified transform.
-    pub fn draw(&self, mesh: &mut TerrainMesh, transform: Transform, occlusion: Occlusion) {
+    /// Gets the tile information for the given face of the cube. Returns `None`
+    /// for the bottom face, which is never drawn.
+    pub fn face(&self, face: BlockFace) -> Option<&TileFace> {
+        match face {
+            BlockFace::PosY => Some(&self.pos_y),
+            BlockFace::NegY => None,
+            BlockFace::PosZ => Some(&self.pos_z),
+            BlockFace::NegZ => Some(&self.neg_z),
+            BlockFace::PosX => Some(&self.pos_x),
+            BlockFace::NegX => Some(&self.neg_x),
+        }
+    }
+
+    /// Gets a mutable reference to the tile information for the given face of
+    /// the cube. Returns `None` for the bottom face, which is never drawn.
+    pub fn face_mut(&mut self, face: BlockFace) -> Option<&mut TileFace> {
+        match face {
+            BlockFace::PosY => Some(&mut self.pos_y),
+            BlockFace::NegY => None,
+            BlockFace::PosZ => Some(&mut self.pos_z),
+            BlockFace::NegZ => Some(&mut self.neg_z),
+            BlockFace::PosX => Some(&mut self.pos_x),
+            BlockFace::NegX => Some(&mut self.neg_x),
+        }
+    }
+
+    /// Draws the cube into the provided mesh at the specified transform,
+    /// resolving the tiles of its faces with the given palette.
+    pub fn draw(
+        &self,
+        mesh: &mut TerrainMesh,
+        transform: Transform,
+        occlusion: Occlusion,
+        palette: &TilePalette,
+    ) {
         // pos y
         if !occlusion.contains(Occlusion::PosY) {
             let mut quad = TerrainQuad::unit();
@@ -38,7 +73,10 @@ impl Cube {
             quad.rotate(transform.rotation);
             quad.shift(transform.translation);
             quad.rotate_uv(self.pos_y.rotation);
-            quad.set_layer(self.pos_y.tile_index);
+            if let Some(window) = self.pos_y.uv_window {
+                quad.set_uv_window(window.offset(), window.scale());
+            }
+            quad.set_layer(palette.layer(self.pos_y.tile_index));
             mesh.add_polygon(quad);
         }
 
@@ -51,7 +89,10 @@ impl Cube {
             quad.rotate(transform.rotation);
             quad.shift(transform.translation);
             quad.rotate_uv(self.pos_z.rotation);
-            quad.set_layer(self.pos_z.tile_index);
+            if let Some(window) = self.pos_z.uv_window {
+                quad.set_uv_window(window.offset(), window.scale());
+            }
+            quad.set_layer(palette.layer(self.pos_z.tile_index));
             mesh.add_polygon(quad);
         }
 
@@ -64,7 +105,10 @@ impl Cube {
             quad.rotate(transform.rotation);
             quad.shift(transform.translation);
             quad.rotate_uv(self.neg_z.rotation);
-            quad.set_layer(self.neg_z.tile_index);
+            if let Some(window) = self.neg_z.uv_window {
+                quad.set_uv_window(window.offset(), window.scale());
+            }
+            quad.set_layer(palette.layer(self.neg_z.tile_index));
             mesh.add_polygon(quad);
         }
 
@@ -77,7 +121,10 @@ impl Cube {
             quad.rotate(transform.rotation);
             quad.shift(transform.translation);
             quad.rotate_uv(self.pos_x.rotation);
-            quad.set_layer(self.pos_x.tile_index);
+            if let Some(window) = self.pos_x.uv_window {
+                quad.set_uv_window(window.offset(), window.scale());
+            }
+            quad.set_layer(palette.layer(self.pos_x.tile_index));
             mesh.add_polygon(quad);
         }
 
@@ -90,7 +137,10 @@ impl Cube {
             quad.rotate(transform.rotation);
             quad.shift(transform.translation);
             quad.rotate_uv(self.neg_x.rotation);
-            quad.set_layer(self.neg_x.tile_index);
+            if let Some(window) = self.neg_x.uv_window {
+                quad.set_uv_window(window.offset(), window.scale());
+            }
+            quad.set_layer(palette.layer(self.neg_x.tile_index));
             mesh.add_polygon(quad);
         }
     }
@@ -2,11 +2,12 @@
 
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
 use crate::map::occlusion::Occluder;
 use crate::map::pos::LocalPos;
 use crate::map::{Occlusion, TOTAL_BLOCKS};
-use crate::tiles::TerrainMesh;
+use crate::tiles::{TerrainMesh, TilePalette};
 
 mod cube;
 
@@ -15,7 +16,7 @@ pub use cube::Cube;
 /// Contains the definition for a block on the map, and how it should be
 /// rendered.
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, TS)]
 #[serde(
     tag = "type",
     rename_all = "camelCase",
@@ -32,11 +33,18 @@ pub enum BlockModel {
 }
 
 impl BlockModel {
-    /// Draws the block into the provided mesh at the specified transform.
-    pub fn draw(&self, mesh: &mut TerrainMesh, transform: Transform, occlusion: Occlusion) {
+    /// Draws the block into the provided mesh at the specified transform,
+    /// resolving the tiles of its faces with the given palette.
+    pub fn draw(
+        &self,
+        mesh: &mut TerrainMesh,
+        transform: Transform,
+        occlusion: Occlusion,
+        palette: &TilePalette,
+    ) {
         match self {
             BlockModel::Empty => {}
-            BlockModel::Cube(cube) => cube.draw(mesh, transform, occlusion),
+            BlockModel::Cube(cube) => cube.draw(mesh, transform, occlusion, palette),
         }
     }
 
@@ -50,13 +58,50 @@ impl BlockModel {
 }
 
 /// Represents a face of a block, which contains tile information for rendering.
-#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
 pub struct TileFace {
-    /// The tile index for the block face.
+    /// The tile palette ID for the block face. The [`TilePalette`] resolves it
+    /// to a layer of the active tileset, so it stays stable when tiles are
+    /// added to or removed from the tileset.
     pub tile_index: u32,
 
     /// The rotation matrix for the tile.
+    #[ts(type = "Mat2")]
     pub rotation: Mat2,
+
+    /// The region of the tileset layer that is drawn on the face. If not
+    /// provided, the whole layer is drawn.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub uv_window: Option<UvWindow>,
+}
+
+/// A rectangular region of a tileset layer, in texture coordinates between 0
+/// and 1.
+///
+/// This allows art packs whose tiles are smaller than the tileset resolution to
+/// store several tiles in a single layer, with each face drawing only one of
+/// them.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UvWindow {
+    /// The texture coordinates of the top left corner of the region.
+    pub offset: [f32; 2],
+
+    /// The size of the region, in texture coordinates.
+    pub scale: [f32; 2],
+}
+
+impl UvWindow {
+    /// Gets the texture coordinates of the top left corner of the region.
+    pub fn offset(&self) -> Vec2 {
+        Vec2::from(self.offset)
+    }
+
+    /// Gets the size of the region, in texture coordinates.
+    pub fn scale(&self) -> Vec2 {
+        Vec2::from(self.scale)
+    }
 }
 
 /// A data container for all block models within a chunk.
@@ -74,6 +119,23 @@ impl ChunkModels {
     pub fn get_mut<P: Into<LocalPos>>(&mut self, pos: P) -> &mut BlockModel {
         &mut self.0[pos.into().as_index()]
     }
+
+    /// Gets all block models within the chunk, in index order.
+    pub fn as_slice(&self) -> &[BlockModel] {
+        &self.0
+    }
+
+    /// Creates chunk models from a list of block models in index order.
+    ///
+    /// Returns `None` if the list does not contain exactly [`TOTAL_BLOCKS`]
+    /// models.
+    pub fn from_vec(models: Vec<BlockModel>) -> Option<Self> {
+        if models.len() != TOTAL_BLOCKS {
+            return None;
+        }
+
+        Some(Self(models))
+    }
 }
 
 impl Default for ChunkModels {
@@ -0,0 +1,317 @@
+//! This module implements map snapshots and patches.
+//!
+//! A [`MapSnapshot`] captures the block models of the map, or a region of it,
+//! at a point in time. Two snapshots may be compared to produce a [`MapPatch`],
+//! a compact list of the blocks that changed between them. Patches can be
+//! serialized, shared, and applied to another map to reproduce the changes.
+
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::map::{
+    BlockModel,
+    CHUNK_SIZE,
+    ChunkModels,
+    ChunkPos,
+    ChunkTable,
+    MapSettings,
+    VoxelChunk,
+    WorldPos,
+};
+
+/// A change to a single block in a [`MapPatch`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BlockChange {
+    /// The world position of the block.
+    pub pos: WorldPos,
+
+    /// The new block model.
+    pub model: BlockModel,
+}
+
+/// A list of block changes that transforms one map state into another.
+///
+/// A chunk that does not exist is different from a chunk that exists but is
+/// empty, so patches also list the chunks that are created and removed.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MapPatch {
+    /// The chunks that are created by this patch, even if they stay empty.
+    #[serde(default)]
+    pub added_chunks: Vec<ChunkPos>,
+
+    /// The chunks that are removed by this patch, along with all of their
+    /// blocks.
+    #[serde(default)]
+    pub removed_chunks: Vec<ChunkPos>,
+
+    /// The block changes, ordered by chunk.
+    pub changes: Vec<BlockChange>,
+}
+
+impl MapPatch {
+    /// Returns true if this patch contains no changes.
+    pub fn is_empty(&self) -> bool {
+        self.added_chunks.is_empty() && self.removed_chunks.is_empty() && self.changes.is_empty()
+    }
+
+    /// Applies this patch to the map in the given world.
+    ///
+    /// Chunks are removed and created before the block changes are applied.
+    /// Added chunks outside the height bounds of the [`MapSettings`] are not
+    /// created.
+    pub fn apply(&self, world: &mut World) {
+        for &chunk_pos in &self.removed_chunks {
+            if let Some(chunk_id) = world.resource::<ChunkTable>().get_chunk(chunk_pos) {
+                world.despawn(chunk_id);
+                world.resource_mut::<ChunkTable>().remove_chunk(chunk_pos);
+            }
+        }
+
+        let settings = world
+            .get_resource::<MapSettings>()
+            .copied()
+            .unwrap_or_default();
+
+        for &chunk_pos in &self.added_chunks {
+            if !settings.intersects_chunk(chunk_pos) {
+                warn!("Cannot add chunk at {chunk_pos}: outside of the world height bounds");
+                continue;
+            }
+
+            if world
+                .resource::<ChunkTable>()
+                .get_chunk(chunk_pos)
+                .is_none()
+            {
+                let chunk_id = world.spawn(VoxelChunk::new(chunk_pos)).id();
+                world
+                    .resource_mut::<ChunkTable>()
+                    .add_chunk(chunk_pos, chunk_id);
+            }
+        }
+
+        for change in &self.changes {
+            set_block(world, change.pos, change.model.clone());
+        }
+    }
+}
+
+/// An inclusive, axis-aligned box of block positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BlockRegion {
+    /// The minimum corner of the region.
+    pub min: WorldPos,
+
+    /// The maximum corner of the region.
+    pub max: WorldPos,
+}
+
+impl BlockRegion {
+    /// Creates a new region spanning the two given corners, in any order.
+    pub fn new(a: WorldPos, b: WorldPos) -> Self {
+        let min = a.min(*b);
+        let max = a.max(*b);
+        Self {
+            min: WorldPos::new(min.x, min.y, min.z),
+            max: WorldPos::new(max.x, max.y, max.z),
+        }
+    }
+
+    /// Returns true if the given position is within this region.
+    pub fn contains(&self, pos: WorldPos) -> bool {
+        pos.cmpge(*self.min).all() && pos.cmple(*self.max).all()
+    }
+
+    /// Returns true if any block of the given chunk is within this region.
+    pub fn intersects_chunk(&self, chunk: ChunkPos) -> bool {
+        let (chunk_min, chunk_max) = chunk_bounds(chunk);
+        chunk_min.cmple(*self.max).all() && chunk_max.cmpge(*self.min).all()
+    }
+
+    /// Returns true if every block of the given chunk is within this region.
+    pub fn contains_chunk(&self, chunk: ChunkPos) -> bool {
+        let (chunk_min, chunk_max) = chunk_bounds(chunk);
+        self.contains(chunk_min) && self.contains(chunk_max)
+    }
+}
+
+/// Gets the minimum and maximum block positions of the given chunk.
+fn chunk_bounds(chunk: ChunkPos) -> (WorldPos, WorldPos) {
+    let last = CHUNK_SIZE as i32 - 1;
+    let chunk_min = chunk.origin();
+    (chunk_min, chunk_min + WorldPos::new(last, last, last))
+}
+
+/// A copy of the block models of the map, or a region of it, at a point in
+/// time.
+#[derive(Debug, Default, Clone)]
+pub struct MapSnapshot {
+    /// The region that was captured, or `None` if the whole map was captured.
+    region: Option<BlockRegion>,
+
+    /// The captured chunks. Chunks that did not exist are omitted.
+    chunks: HashMap<ChunkPos, ChunkModels>,
+}
+
+impl MapSnapshot {
+    /// Captures the current state of the map in the given world, limited to
+    /// the given region if one is provided.
+    pub fn capture(world: &World, region: Option<BlockRegion>) -> Self {
+        let Some(mut query) = world.try_query::<&VoxelChunk>() else {
+            // No chunk has ever been spawned.
+            return Self {
+                region,
+                chunks: HashMap::new(),
+            };
+        };
+
+        let chunks = query
+            .iter(world)
+            .filter(|chunk| region.is_none_or(|region| region.intersects_chunk(chunk.pos())))
+            .map(|chunk| (chunk.pos(), chunk.get_models().clone()))
+            .collect();
+
+        Self { region, chunks }
+    }
+
+    /// Gets the region that was captured, or `None` if the whole map was
+    /// captured.
+    pub fn region(&self) -> Option<BlockRegion> {
+        self.region
+    }
+
+    /// Creates a patch that transforms this snapshot into the `other`
+    /// snapshot.
+    ///
+    /// Only blocks within the region of this snapshot are compared. Chunks
+    /// missing from only one of the snapshots are listed as added or removed.
+    /// A removed chunk that is only partly within the region is not removed,
+    /// as that would remove blocks outside of the region, so its blocks within
+    /// the region are cleared instead.
+    pub fn diff(&self, other: &MapSnapshot) -> MapPatch {
+        let empty = ChunkModels::default();
+
+        let mut positions = self
+            .chunks
+            .keys()
+            .chain(other.chunks.keys())
+            .copied()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        positions.sort_by_key(|pos| (pos.z, pos.y, pos.x));
+
+        let mut patch = MapPatch::default();
+        for chunk_pos in positions {
+            let before = self.chunks.get(&chunk_pos);
+            let after = other.chunks.get(&chunk_pos);
+            match (before, after) {
+                (None, Some(_)) => patch.added_chunks.push(chunk_pos),
+                (Some(_), None)
+                    if self
+                        .region
+                        .is_none_or(|region| region.contains_chunk(chunk_pos)) =>
+                {
+                    patch.removed_chunks.push(chunk_pos);
+                    continue;
+                }
+                _ => {}
+            }
+
+            let before = before.unwrap_or(&empty);
+            let after = after.unwrap_or(&empty);
+
+            let blocks = before.as_slice().iter().zip(after.as_slice());
+            for (index, (before, after)) in blocks.enumerate() {
+                if before == after {
+                    continue;
+                }
+
+                let pos = chunk_pos.block_at_index(index);
+                if self.region.is_some_and(|region| !region.contains(pos)) {
+                    continue;
+                }
+
+                patch.changes.push(BlockChange {
+                    pos,
+                    model: after.clone(),
+                });
+            }
+        }
+
+        patch
+    }
+}
+
+/// A resource that stores named map snapshots for the current session.
+#[derive(Debug, Default, Resource)]
+pub struct MapSnapshots {
+    /// The snapshots, keyed by name.
+    snapshots: HashMap<String, MapSnapshot>,
+}
+
+impl MapSnapshots {
+    /// Gets the snapshot with the given name, if it exists.
+    pub fn get(&self, name: &str) -> Option<&MapSnapshot> {
+        self.snapshots.get(name)
+    }
+
+    /// Stores a snapshot under the given name, replacing any existing snapshot
+    /// with the same name.
+    pub fn insert(&mut self, name: String, snapshot: MapSnapshot) {
+        self.snapshots.insert(name, snapshot);
+    }
+
+    /// Removes the snapshot with the given name, returning it if it existed.
+    pub fn remove(&mut self, name: &str) -> Option<MapSnapshot> {
+        self.snapshots.remove(name)
+    }
+}
+
+/// Sets the block model at the given world position, creating the chunk if it
+/// does not exist yet.
+///
+/// Blocks outside the height bounds of the [`MapSettings`] are not placed.
+pub fn set_block(world: &mut World, pos: WorldPos, model: BlockModel) {
+    if world
+        .get_resource::<MapSettings>()
+        .is_some_and(|settings| !settings.contains(pos))
+    {
+        warn!("Cannot set block at {pos}: outside of the world height bounds");
+        return;
+    }
+
+    let chunk_pos = pos.as_chunk_pos();
+    match world.resource::<ChunkTable>().get_chunk(chunk_pos) {
+        Some(chunk_id) => {
+            if let Some(mut chunk) = world.get_mut::<VoxelChunk>(chunk_id) {
+                chunk.set_model(pos, model);
+            } else {
+                error!("Failed to get chunk at position {chunk_pos} to set block at {pos}");
+            }
+        }
+        None => {
+            let mut chunk = VoxelChunk::new(chunk_pos);
+            chunk.set_model(pos, model);
+            let chunk_id = world.spawn(chunk).id();
+            world
+                .resource_mut::<ChunkTable>()
+                .add_chunk(chunk_pos, chunk_id);
+        }
+    };
+}
+
+/// Gets the block model at the given world position, or `None` if the chunk
+/// containing it does not exist.
+pub fn get_block(world: &World, pos: WorldPos) -> Option<&BlockModel> {
+    let chunk_id = world
+        .get_resource::<ChunkTable>()?
+        .get_chunk(pos.as_chunk_pos())?;
+    let chunk = world.get::<VoxelChunk>(chunk_id)?;
+    Some(chunk.get_models().get(pos))
+}
@@ -6,12 +6,13 @@ use std::ops::{Add, Mul};
 
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
 use crate::map::chunk::{CHUNK_SIZE, CHUNK_SIZE_BITS, CHUNK_SIZE_MASK};
 
 /// The position of a block in the world, represented in world-space.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deref, DerefMut, Serialize, Deserialize)]
-pub struct WorldPos(IVec3);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deref, DerefMut, Serialize, Deserialize, TS)]
+pub struct WorldPos(#[ts(type = "[x: number, y: number, z: number]")] IVec3);
 
 impl WorldPos {
     /// Creates a new [`WorldPos`] from the given x, y, and z coordinates.
@@ -61,8 +62,32 @@ impl Mul<i32> for WorldPos {
 }
 
 /// The position of a chunk in the world, represented in chunk-space.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deref, Serialize, Deserialize)]
-pub struct ChunkPos(IVec3);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deref, Serialize, Deserialize, TS)]
+pub struct ChunkPos(#[ts(type = "[x: number, y: number, z: number]")] IVec3);
+
+impl ChunkPos {
+    /// Creates a new [`ChunkPos`] from the given x, y, and z chunk coordinates.
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        ChunkPos(IVec3::new(x, y, z))
+    }
+
+    /// Gets the world position of the block at the minimum corner of this
+    /// chunk.
+    pub fn origin(self) -> WorldPos {
+        WorldPos(self.0 << CHUNK_SIZE_BITS)
+    }
+
+    /// Gets the world position of the block at the given array index within
+    /// this chunk. This is the inverse of [`LocalPos::as_index`].
+    pub fn block_at_index(self, index: usize) -> WorldPos {
+        let local = IVec3::new(
+            (index % CHUNK_SIZE) as i32,
+            (index / CHUNK_SIZE % CHUNK_SIZE) as i32,
+            (index / (CHUNK_SIZE * CHUNK_SIZE)) as i32,
+        );
+        self.origin() + WorldPos(local)
+    }
+}
 
 impl fmt::Display for ChunkPos {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
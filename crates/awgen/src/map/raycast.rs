@@ -0,0 +1,150 @@
+//! This module implements ray casting against the blocks of the map, which is
+//! used to find the block and face under the cursor.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::map::WorldPos;
+
+/// The maximum distance, in blocks, that a ray may travel.
+///
+/// Past about 2^24 blocks, stepping along a ray no longer changes its distance
+/// at `f32` precision, so longer rays would never end.
+pub const MAX_RAYCAST_DISTANCE: f32 = 65536.0;
+
+/// One of the six faces of a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum BlockFace {
+    /// The top (Y+) face.
+    PosY,
+
+    /// The bottom (Y-) face.
+    NegY,
+
+    /// The north (Z+) face.
+    PosZ,
+
+    /// The south (Z-) face.
+    NegZ,
+
+    /// The east (X+) face.
+    PosX,
+
+    /// The west (X-) face.
+    NegX,
+}
+
+impl BlockFace {
+    /// Gets the outward-facing unit normal of this face.
+    pub fn normal(self) -> IVec3 {
+        match self {
+            BlockFace::PosY => IVec3::Y,
+            BlockFace::NegY => IVec3::NEG_Y,
+            BlockFace::PosZ => IVec3::Z,
+            BlockFace::NegZ => IVec3::NEG_Z,
+            BlockFace::PosX => IVec3::X,
+            BlockFace::NegX => IVec3::NEG_X,
+        }
+    }
+}
+
+/// The result of a successful block ray cast.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHit {
+    /// The position of the block that was hit.
+    pub pos: WorldPos,
+
+    /// The face of the block that was hit.
+    pub face: BlockFace,
+
+    /// The distance along the ray to the hit point.
+    pub distance: f32,
+}
+
+/// Casts a ray through the block grid, returning the first solid block that
+/// the ray enters within `max_distance`.
+///
+/// The `is_solid` function is called for each block the ray passes through, in
+/// order. A block the ray starts inside of is never reported as a hit.
+///
+/// The distance is clamped to [`MAX_RAYCAST_DISTANCE`], and nothing is hit if
+/// it is negative or NaN.
+pub fn raycast_blocks(
+    ray: Ray3d,
+    max_distance: f32,
+    mut is_solid: impl FnMut(WorldPos) -> bool,
+) -> Option<BlockHit> {
+    if max_distance.is_nan() || max_distance < 0.0 {
+        return None;
+    }
+    let max_distance = max_distance.min(MAX_RAYCAST_DISTANCE);
+
+    let origin = ray.origin;
+    let dir = *ray.direction;
+
+    let mut block = origin.floor().as_ivec3();
+    let mut step = IVec3::ZERO;
+    let mut t_max = Vec3::INFINITY;
+    let mut t_delta = Vec3::INFINITY;
+
+    for axis in 0 .. 3 {
+        if dir[axis] > 0.0 {
+            step[axis] = 1;
+            t_max[axis] = (block[axis] as f32 + 1.0 - origin[axis]) / dir[axis];
+            t_delta[axis] = 1.0 / dir[axis];
+        } else if dir[axis] < 0.0 {
+            step[axis] = -1;
+            t_max[axis] = (origin[axis] - block[axis] as f32) / -dir[axis];
+            t_delta[axis] = 1.0 / -dir[axis];
+        }
+    }
+
+    // The face of the block that the ray enters by stepping along each axis.
+    let entry_faces = [
+        if step.x > 0 {
+            BlockFace::NegX
+        } else {
+            BlockFace::PosX
+        },
+        if step.y > 0 {
+            BlockFace::NegY
+        } else {
+            BlockFace::PosY
+        },
+        if step.z > 0 {
+            BlockFace::NegZ
+        } else {
+            BlockFace::PosZ
+        },
+    ];
+
+    loop {
+        let axis = if t_max.x < t_max.y && t_max.x < t_max.z {
+            0
+        } else if t_max.y < t_max.z {
+            1
+        } else {
+            2
+        };
+
+        let distance = t_max[axis];
+        if distance > max_distance {
+            return None;
+        }
+
+        block[axis] += step[axis];
+        t_max[axis] += t_delta[axis];
+
+        let pos = WorldPos::new(block.x, block.y, block.z);
+        if is_solid(pos) {
+            return Some(BlockHit {
+                pos,
+                face: entry_faces[axis],
+                distance,
+            });
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! This module implements the [`MapSettings`] resource, which describes the
+//! vertical bounds of the map of a project.
+//!
+//! The settings are stored in the settings table of the game database. They
+//! are validated when the game starts, and stored again whenever they change.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::database::{Database, DatabaseError, GameDatabase};
+use crate::map::{BlockModel, CHUNK_SIZE, ChunkModels, ChunkPos, WorldPos};
+
+/// The settings key for the map settings.
+const MAP_SETTINGS_KEY: &str = "map_settings";
+
+/// A resource that describes the dimensions of the map of a project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MapSettings {
+    /// The lowest Y coordinate that blocks may be placed at.
+    pub min_height: i32,
+
+    /// The highest Y coordinate that blocks may be placed at.
+    pub max_height: i32,
+}
+
+impl Default for MapSettings {
+    fn default() -> Self {
+        Self {
+            min_height: i32::MIN,
+            max_height: i32::MAX,
+        }
+    }
+}
+
+impl MapSettings {
+    /// Loads and validates the map settings from the game database, falling
+    /// back to the default settings if none have been stored.
+    pub fn load(database: &Database) -> Result<Self, MapSettingsError> {
+        let Some(value) = database.get_setting(MAP_SETTINGS_KEY)? else {
+            return Ok(Self::default());
+        };
+
+        let settings: Self = serde_json::from_str(&value).map_err(DatabaseError::from)?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Stores the map settings in the game database.
+    pub fn save(&self, database: &Database) -> Result<(), MapSettingsError> {
+        self.validate()?;
+        let value = serde_json::to_string(self).map_err(DatabaseError::from)?;
+        Ok(database.set_setting(MAP_SETTINGS_KEY, &value)?)
+    }
+
+    /// Checks that the height bounds are in order.
+    pub fn validate(&self) -> Result<(), MapSettingsError> {
+        if self.min_height > self.max_height {
+            return Err(MapSettingsError::InvalidHeights(
+                self.min_height,
+                self.max_height,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if blocks may be placed at the given world position.
+    pub fn contains(&self, pos: WorldPos) -> bool {
+        (self.min_height ..= self.max_height).contains(&pos.y)
+    }
+
+    /// Returns true if any block of the given chunk is within the height
+    /// bounds.
+    pub fn intersects_chunk(&self, chunk: ChunkPos) -> bool {
+        let min = chunk.origin().y;
+        let max = min + (CHUNK_SIZE as i32 - 1);
+        min <= self.max_height && max >= self.min_height
+    }
+
+    /// Removes the blocks of the given chunk that are outside the height
+    /// bounds, returning the number of blocks that were removed.
+    pub fn clip_chunk(&self, chunk: ChunkPos, models: &mut ChunkModels) -> usize {
+        let origin = chunk.origin();
+        let mut removed = 0;
+
+        for y in 0 .. CHUNK_SIZE as i32 {
+            if self.contains(origin + WorldPos::new(0, y, 0)) {
+                continue;
+            }
+
+            for x in 0 .. CHUNK_SIZE as i32 {
+                for z in 0 .. CHUNK_SIZE as i32 {
+                    let model = models.get_mut(WorldPos::new(x, y, z));
+                    if !matches!(model, BlockModel::Empty) {
+                        *model = BlockModel::Empty;
+                        removed += 1;
+                    }
+                }
+            }
+        }
+
+        removed
+    }
+}
+
+/// Stores the map settings in the game database whenever they change,
+/// including when they are first inserted, so that the project records the
+/// dimensions its map was made with.
+pub(super) fn save_map_settings(settings: Res<MapSettings>, database: Res<GameDatabase>) {
+    if database.is_read_only() {
+        return;
+    }
+
+    if let Err(err) = settings.save(&database) {
+        error!("Failed to store the map settings: {}", err);
+    }
+}
+
+/// An error that can occur while loading or storing the map settings.
+#[derive(Debug, thiserror::Error)]
+pub enum MapSettingsError {
+    /// The settings could not be read from or written to the database.
+    #[error("{0}")]
+    Database(#[from] DatabaseError),
+
+    /// The minimum height is above the maximum height.
+    #[error("Invalid height bounds: minimum {0} is above maximum {1}")]
+    InvalidHeights(i32, i32),
+}
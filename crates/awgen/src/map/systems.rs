@@ -1,22 +1,34 @@
 //! Systems for managing the map in the game.
 
+use std::sync::Arc;
+use std::time::Instant;
+
+use bevy::diagnostic::Diagnostics;
 use bevy::prelude::*;
-use bevy::tasks::{AsyncComputeTaskPool, Task, block_on};
 
-use crate::map::chunk::ChunkModelPart;
+use crate::jobs::{BackgroundJobs, JobResult};
+use crate::map::chunk::{ChunkModelPart, SectionMask};
 use crate::map::chunk_table::ChunkTable;
-use crate::map::mesher::{ChunkMesh, build_mesh};
+use crate::map::mesher::{ChunkMesh, SectionMeshes};
 use crate::map::messages::{ChunkCreated, ChunkMeshUpdated, ChunkRemoved};
-use crate::map::{ChunkPos, VoxelChunk};
-use crate::tiles::{ActiveTilesets, TilesetMaterial};
+use crate::map::{ChunkPos, REMESH_TIME, VoxelChunk};
+use crate::tiles::{ActiveTilesets, TilePalette, TilesetMaterial};
+
+/// The output of a chunk meshing job.
+type RemeshedChunk = (ChunkPos, SectionMeshes, ChunkMesh);
 
 /// This system updates every frame to redraw all chunks that have been marked
 /// for redraw.
+///
+/// Chunks are meshed as hidden [`BackgroundJobs`]. The time spent by this
+/// system, including waiting on the meshing jobs of the previous frame, is
+/// measured as the [`REMESH_TIME`] diagnostic.
 #[allow(clippy::too_many_arguments)]
 pub(super) fn redraw_chunks(
-    mut active_tasks: Local<Vec<Task<(ChunkPos, ChunkMesh)>>>,
+    mut jobs: ResMut<BackgroundJobs>,
     chunk_table: Res<ChunkTable>,
     active_tilesets: Res<ActiveTilesets>,
+    palette: Res<TilePalette>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut mesh_update_msg: MessageWriter<ChunkMeshUpdated>,
     mut chunks: Query<&mut VoxelChunk>,
@@ -25,12 +37,17 @@ pub(super) fn redraw_chunks(
         &mut MeshMaterial3d<TilesetMaterial>,
         &mut ChunkModelPart,
     )>,
+    mut diagnostics: Diagnostics,
     mut commands: Commands,
 ) {
-    // Wait on all pending redraw tasks to avoid flickering.
-    let finished_tasks = block_on(futures::future::join_all(active_tasks.drain(..)));
+    let start = Instant::now();
+
+    // Wait on all pending redraw jobs to avoid flickering.
+    for (_, result) in jobs.wait_all::<RemeshedChunk>() {
+        let JobResult::Completed((pos, section_meshes, chunk_mesh)) = result else {
+            continue;
+        };
 
-    for (pos, chunk_mesh) in finished_tasks {
         let Some(chunk_id) = chunk_table.get_chunk(pos) else {
             continue;
         };
@@ -39,6 +56,8 @@ pub(super) fn redraw_chunks(
             continue;
         };
 
+        chunk.section_meshes = Some(section_meshes);
+
         // opaque mesh
         match (chunk.opaque_entity, chunk_mesh.opaque) {
             (None, None) => {}
@@ -80,17 +99,34 @@ pub(super) fn redraw_chunks(
         mesh_update_msg.write(ChunkMeshUpdated);
     }
 
-    let pool = AsyncComputeTaskPool::get();
+    let mut shared_palette: Option<Arc<TilePalette>> = None;
     for mut chunk in chunks.iter_mut() {
         if !chunk.is_dirty() {
             continue;
         }
-        chunk.mark_clean();
+        let mut dirty = chunk.mark_clean();
+
+        // Chunks without cached section meshes have never been drawn, so every
+        // section needs to be built.
+        let mut section_meshes = chunk.section_meshes.take().unwrap_or_else(|| {
+            dirty = SectionMask::ALL;
+            SectionMeshes::default()
+        });
 
         let position = chunk.pos();
         let chunk_model = chunk.get_models().clone();
-        active_tasks.push(pool.spawn(async move { (position, build_mesh(&chunk_model)) }));
+        let palette = shared_palette
+            .get_or_insert_with(|| Arc::new(palette.clone()))
+            .clone();
+        let label = format!("Meshing chunk {position}");
+        jobs.spawn_hidden(label, move |_| async move {
+            section_meshes.rebuild(&chunk_model, &palette, dirty);
+            let chunk_mesh = section_meshes.merge();
+            (position, section_meshes, chunk_mesh)
+        });
     }
+
+    diagnostics.add_measurement(&REMESH_TIME, || start.elapsed().as_secs_f64() * 1000.0);
 }
 
 /// This observer is triggered whenever a new [`VoxelChunk`] is added to the
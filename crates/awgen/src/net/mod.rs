@@ -0,0 +1,195 @@
+//! This module implements basic networking, allowing one instance of the game
+//! to host a session that other instances may join over TCP.
+//!
+//! Networking follows a simple authority model where the host is the
+//! authority. Messages sent by the host's scripts are delivered to every
+//! client, while messages sent by a client's scripts are only delivered to the
+//! host. The host's scripts decide which client messages to accept and
+//! broadcast to the other clients.
+//!
+//! Only a whitelisted set of messages, described by [`NetMessage`], may be
+//! sent between peers.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::map::{BlockModel, WorldPos};
+use crate::scripts::{PacketIn, PacketOut, ScriptEngine};
+
+mod session;
+
+pub use session::{NetEvent, NetSession};
+
+/// The peer ID of the host. Clients are assigned IDs starting at 1.
+pub const HOST_PEER: u32 = 0;
+
+/// A message that may be sent between peers.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    deny_unknown_fields
+)]
+pub enum NetMessage {
+    /// A change to a block in the world.
+    SetBlock {
+        /// The world position.
+        pos: WorldPos,
+
+        /// The block model.
+        model: Box<BlockModel>,
+    },
+
+    /// An update to a script-defined entity.
+    EntityUpdate {
+        /// The script-defined ID of the entity.
+        entity: String,
+
+        /// The updated entity state.
+        data: serde_json::Value,
+    },
+
+    /// A chat message.
+    Chat {
+        /// The message text.
+        text: String,
+    },
+}
+
+/// The plugin that relays network messages between peers and the script
+/// engine.
+pub struct NetPlugin;
+impl Plugin for NetPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_systems(PreUpdate, poll_network);
+    }
+}
+
+/// Handles a networking packet from the script engine.
+pub(crate) fn handle(world: &mut World, packet: PacketIn) {
+    match packet {
+        PacketIn::HostGame { port } => {
+            leave_session(world);
+            info!("Hosting network session on port {}", port);
+            match NetSession::host(port) {
+                Ok(session) => {
+                    world.insert_resource(session);
+                }
+                Err(err) => net_error(world, format!("Failed to host on port {port}: {err}")),
+            }
+        }
+        PacketIn::JoinGame { address } => {
+            leave_session(world);
+            info!("Joining network session at {}", address);
+            match NetSession::join(&address) {
+                Ok(session) => {
+                    world.insert_resource(session);
+                }
+                Err(err) => net_error(world, format!("Failed to join {address}: {err}")),
+            }
+        }
+        PacketIn::LeaveGame => leave_session(world),
+        PacketIn::NetSend { message, to } => {
+            let Some(session) = world.get_resource::<NetSession>() else {
+                net_error(world, "Cannot send message: not connected".to_string());
+                return;
+            };
+
+            if let Err(err) = session.send(message, to) {
+                net_error(world, format!("Failed to send message: {err}"));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Closes the active network session, if any.
+///
+/// This is done before hosting or joining another session, so the peers of
+/// the old session are disconnected before the new one is started.
+fn leave_session(world: &mut World) {
+    if let Some(session) = world.remove_resource::<NetSession>() {
+        info!("Leaving network session.");
+        session.close();
+    }
+}
+
+/// Logs a networking error and reports it to the script engine.
+fn net_error(world: &World, error: String) {
+    error!("{}", error);
+    if let Some(engine) = world.get_resource::<ScriptEngine>() {
+        let _ = engine.send(PacketOut::NetError { error });
+    }
+}
+
+/// Forwards events from the active network session to the script engine.
+fn poll_network(
+    session: Option<Res<NetSession>>,
+    engine: Res<ScriptEngine>,
+    mut commands: Commands,
+) {
+    let Some(session) = session else {
+        return;
+    };
+
+    while let Some(event) = session.poll() {
+        let packet = match event {
+            NetEvent::Joined { peer } => PacketOut::NetJoined { peer },
+            NetEvent::Connected { peer } => PacketOut::PeerConnected { peer },
+            NetEvent::Disconnected { peer } => PacketOut::PeerDisconnected { peer },
+            NetEvent::Received { from, message } => PacketOut::NetReceived { from, message },
+            NetEvent::Closed { error } => {
+                commands.remove_resource::<NetSession>();
+                PacketOut::NetClosed { error }
+            }
+        };
+
+        if let Err(err) = engine.send(packet) {
+            error!(
+                "Failed to forward network event to the script engine: {}",
+                err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    /// Waits for the next event of the given session.
+    fn next_event(session: &NetSession) -> NetEvent {
+        let start = Instant::now();
+        loop {
+            if let Some(event) = session.poll() {
+                return event;
+            }
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "timed out waiting for event"
+            );
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn hosting_again_closes_the_previous_session() {
+        let mut world = World::new();
+        handle(&mut world, PacketIn::HostGame { port: 0 });
+        let old_port = world.resource::<NetSession>().port().unwrap();
+
+        let client = NetSession::join(&format!("127.0.0.1:{old_port}")).unwrap();
+        assert!(matches!(next_event(&client), NetEvent::Joined { .. }));
+
+        handle(&mut world, PacketIn::HostGame { port: 0 });
+        assert!(matches!(next_event(&client), NetEvent::Closed { .. }));
+        assert!(world.resource::<NetSession>().is_host());
+
+        handle(&mut world, PacketIn::LeaveGame);
+        assert!(!world.contains_resource::<NetSession>());
+    }
+}
@@ -0,0 +1,558 @@
+//! This module implements the network session, which manages the TCP
+//! connections between peers on background threads.
+//!
+//! Messages are sent as newline-delimited JSON frames. Each connection has a
+//! reader thread and a writer thread, so sending a message only queues the
+//! encoded frame and never blocks the caller on the network.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use smol::channel::{Receiver, Sender};
+
+use crate::net::{HOST_PEER, NetMessage};
+
+/// The largest frame that may be received from a peer, in bytes.
+const MAX_FRAME_SIZE: u64 = 1024 * 1024;
+
+/// How long to wait when connecting to a host.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the host waits between checks for new connections.
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A frame sent between peers.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    deny_unknown_fields
+)]
+enum Frame {
+    /// Sent by the host to a client when it connects, containing the peer ID
+    /// assigned to the client.
+    Welcome {
+        /// The peer ID of the client.
+        peer: u32,
+    },
+
+    /// A message from a peer.
+    Message {
+        /// The peer ID of the sender.
+        from: u32,
+
+        /// The message.
+        message: NetMessage,
+    },
+}
+
+/// An event that occurred in a network session.
+#[derive(Debug)]
+pub enum NetEvent {
+    /// This client has joined a session and was assigned the given peer ID.
+    Joined {
+        /// The peer ID of this client.
+        peer: u32,
+    },
+
+    /// A client has connected to this host.
+    Connected {
+        /// The peer ID of the client.
+        peer: u32,
+    },
+
+    /// A client has disconnected from this host.
+    Disconnected {
+        /// The peer ID of the client.
+        peer: u32,
+    },
+
+    /// A message was received from a peer.
+    Received {
+        /// The peer ID of the sender.
+        from: u32,
+
+        /// The message.
+        message: NetMessage,
+    },
+
+    /// The connection to the host was closed.
+    Closed {
+        /// The reason the connection was closed, if it was closed due to an
+        /// error.
+        error: Option<String>,
+    },
+}
+
+/// A connection to a peer, whose frames are written by a writer thread.
+#[derive(Debug)]
+struct Connection {
+    /// The stream of the connection, which is kept to shut it down.
+    stream: TcpStream,
+
+    /// The encoded frames waiting to be written to the peer.
+    frames: Sender<Arc<str>>,
+}
+
+impl Connection {
+    /// Opens a connection over the given stream, starting its writer thread
+    /// with the given name.
+    fn open(stream: TcpStream, name: String) -> io::Result<Self> {
+        let (frames, queue) = smol::channel::unbounded();
+        let writer = stream.try_clone()?;
+        std::thread::Builder::new()
+            .name(name)
+            .spawn(move || write_loop(writer, queue))?;
+
+        Ok(Self { stream, frames })
+    }
+
+    /// Queues an encoded frame to be written to the peer.
+    ///
+    /// Returns an error if the writer thread has stopped.
+    fn send(&self, frame: Arc<str>) -> io::Result<()> {
+        self.frames
+            .try_send(frame)
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Connection closed"))
+    }
+
+    /// Shuts the connection down, which stops its reader and writer threads.
+    fn shutdown(&self) {
+        self.frames.close();
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+/// The connections held by a session.
+#[derive(Debug)]
+enum Role {
+    /// This instance is hosting the session.
+    Host {
+        /// The port the host is listening on.
+        port: u16,
+
+        /// The connected clients, keyed by peer ID.
+        peers: Arc<Mutex<HashMap<u32, Connection>>>,
+    },
+
+    /// This instance has joined a session.
+    Client {
+        /// The connection to the host.
+        host: Connection,
+    },
+}
+
+/// An active network session, either hosted or joined.
+///
+/// The session is closed when it is dropped, so replacing the resource with a
+/// new session disconnects from the peers of the old one.
+#[derive(Debug, Resource)]
+pub struct NetSession {
+    /// The connections held by this session.
+    role: Role,
+
+    /// The events received from the background threads.
+    events: Receiver<NetEvent>,
+
+    /// Whether the session has been closed.
+    closed: Arc<AtomicBool>,
+}
+
+impl NetSession {
+    /// Hosts a new session on the given port.
+    pub fn host(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+
+        let (events_tx, events) = smol::channel::unbounded();
+        let peers = Arc::new(Mutex::new(HashMap::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let thread_peers = peers.clone();
+        let thread_closed = closed.clone();
+        std::thread::Builder::new()
+            .name("net_host".to_string())
+            .spawn(move || accept_loop(listener, thread_peers, events_tx, thread_closed))?;
+
+        Ok(Self {
+            role: Role::Host { port, peers },
+            events,
+            closed,
+        })
+    }
+
+    /// Joins the session hosted at the given address.
+    pub fn join(address: &str) -> io::Result<Self> {
+        let addr = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Address could not be resolved")
+        })?;
+
+        let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+        stream.set_nodelay(true)?;
+
+        let (events_tx, events) = smol::channel::unbounded();
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let reader = stream.try_clone()?;
+        let host = Connection::open(stream, "net_client_writer".to_string())?;
+        let thread_closed = closed.clone();
+        std::thread::Builder::new()
+            .name("net_client".to_string())
+            .spawn(move || client_loop(reader, events_tx, thread_closed))?;
+
+        Ok(Self {
+            role: Role::Client { host },
+            events,
+            closed,
+        })
+    }
+
+    /// Returns true if this instance is hosting the session.
+    pub fn is_host(&self) -> bool {
+        matches!(self.role, Role::Host { .. })
+    }
+
+    /// Gets the port this instance is listening on, if it is hosting the
+    /// session.
+    pub fn port(&self) -> Option<u16> {
+        match &self.role {
+            Role::Host { port, .. } => Some(*port),
+            Role::Client { .. } => None,
+        }
+    }
+
+    /// Sends a message to other peers.
+    ///
+    /// The host may send a message to a single client, or to all clients if
+    /// `to` is `None`. Clients may only send messages to the host.
+    ///
+    /// The message is queued to be written by the writer thread of each
+    /// connection, so this never waits on the network.
+    pub fn send(&self, message: NetMessage, to: Option<u32>) -> io::Result<()> {
+        let frame = encode_frame(&Frame::Message {
+            from: HOST_PEER,
+            message,
+        })?;
+
+        match &self.role {
+            Role::Host { peers, .. } => {
+                let peers = peers.lock().unwrap();
+                match to {
+                    Some(peer) => match peers.get(&peer) {
+                        Some(connection) => connection.send(frame),
+                        None => Err(io::Error::new(
+                            io::ErrorKind::NotFound,
+                            format!("Unknown peer {peer}"),
+                        )),
+                    },
+                    None => {
+                        for connection in peers.values() {
+                            // A failed send means the peer is disconnecting,
+                            // which its reader thread will report.
+                            let _ = connection.send(frame.clone());
+                        }
+                        Ok(())
+                    }
+                }
+            }
+            Role::Client { host } => {
+                if to.is_some_and(|peer| peer != HOST_PEER) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        "Clients may only send messages to the host",
+                    ));
+                }
+
+                host.send(frame)
+            }
+        }
+    }
+
+    /// Gets the next pending event, if any.
+    pub fn poll(&self) -> Option<NetEvent> {
+        self.events.try_recv().ok()
+    }
+
+    /// Closes the session, disconnecting from all peers.
+    pub fn close(self) {
+        drop(self);
+    }
+}
+
+impl Drop for NetSession {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Release);
+        match &self.role {
+            Role::Host { peers, .. } => {
+                for connection in peers.lock().unwrap().values() {
+                    connection.shutdown();
+                }
+            }
+            Role::Client { host } => host.shutdown(),
+        }
+    }
+}
+
+/// Accepts new clients until the session is closed.
+fn accept_loop(
+    listener: TcpListener,
+    peers: Arc<Mutex<HashMap<u32, Connection>>>,
+    events: Sender<NetEvent>,
+    closed: Arc<AtomicBool>,
+) {
+    let mut next_peer = HOST_PEER + 1;
+
+    while !closed.load(Ordering::Acquire) {
+        let stream = match listener.accept() {
+            Ok((stream, addr)) => {
+                debug!("Accepted connection from {}", addr);
+                stream
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                std::thread::sleep(ACCEPT_INTERVAL);
+                continue;
+            }
+            Err(err) => {
+                error!("Failed to accept connection: {}", err);
+                continue;
+            }
+        };
+
+        let peer = next_peer;
+        next_peer += 1;
+
+        if let Err(err) = add_peer(peer, stream, &peers, &events) {
+            error!("Failed to add peer {}: {}", peer, err);
+        }
+    }
+}
+
+/// Registers a newly connected client and starts its reader and writer
+/// threads.
+fn add_peer(
+    peer: u32,
+    stream: TcpStream,
+    peers: &Arc<Mutex<HashMap<u32, Connection>>>,
+    events: &Sender<NetEvent>,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_nodelay(true)?;
+
+    let reader = stream.try_clone()?;
+    let connection = Connection::open(stream, format!("net_peer_{peer}_writer"))?;
+    connection.send(encode_frame(&Frame::Welcome { peer })?)?;
+    peers.lock().unwrap().insert(peer, connection);
+    let _ = events.send_blocking(NetEvent::Connected { peer });
+
+    let peers = peers.clone();
+    let events = events.clone();
+    std::thread::Builder::new()
+        .name(format!("net_peer_{peer}"))
+        .spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let mut buf = String::new();
+
+            // Messages from clients are always attributed to the connection
+            // they arrived on, regardless of the sender they claim.
+            while let Ok(Some(frame)) = read_frame(&mut reader, &mut buf) {
+                if let Frame::Message { message, .. } = frame {
+                    let _ = events.send_blocking(NetEvent::Received {
+                        from: peer,
+                        message,
+                    });
+                }
+            }
+
+            if let Some(connection) = peers.lock().unwrap().remove(&peer) {
+                connection.shutdown();
+            }
+            let _ = events.send_blocking(NetEvent::Disconnected { peer });
+        })?;
+
+    Ok(())
+}
+
+/// Reads frames from the host until the connection is closed.
+fn client_loop(stream: TcpStream, events: Sender<NetEvent>, closed: Arc<AtomicBool>) {
+    let mut reader = BufReader::new(stream);
+    let mut buf = String::new();
+
+    let error = loop {
+        match read_frame(&mut reader, &mut buf) {
+            Ok(Some(Frame::Welcome { peer })) => {
+                let _ = events.send_blocking(NetEvent::Joined { peer });
+            }
+            Ok(Some(Frame::Message { from, message })) => {
+                let _ = events.send_blocking(NetEvent::Received { from, message });
+            }
+            Ok(None) => break None,
+            Err(_) if closed.load(Ordering::Acquire) => break None,
+            Err(err) => break Some(err.to_string()),
+        }
+    };
+
+    let _ = events.send_blocking(NetEvent::Closed { error });
+}
+
+/// Reads a single frame from a peer.
+///
+/// Returns `Ok(None)` if the connection was closed.
+fn read_frame(reader: &mut impl BufRead, buf: &mut String) -> io::Result<Option<Frame>> {
+    buf.clear();
+    if reader.take(MAX_FRAME_SIZE).read_line(buf)? == 0 {
+        return Ok(None);
+    }
+
+    if !buf.ends_with('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Frame too large",
+        ));
+    }
+
+    serde_json::from_str(buf)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Encodes a single frame as a line of JSON.
+fn encode_frame(frame: &Frame) -> io::Result<Arc<str>> {
+    let mut line = serde_json::to_string(frame)?;
+    line.push('\n');
+    Ok(line.into())
+}
+
+/// Writes queued frames to a peer until the connection is closed.
+///
+/// A failed write shuts the stream down, so the reader thread of the
+/// connection reports the disconnect.
+fn write_loop(mut stream: TcpStream, frames: Receiver<Arc<str>>) {
+    while let Ok(frame) = frames.recv_blocking() {
+        if let Err(err) = stream.write_all(frame.as_bytes()) {
+            debug!("Failed to write frame: {}", err);
+            let _ = stream.shutdown(Shutdown::Both);
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::time::Instant;
+
+    use super::*;
+
+    /// How long to wait for a network event before failing a test.
+    const EVENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Waits for the next event of the given session.
+    fn next_event(session: &NetSession) -> NetEvent {
+        let start = Instant::now();
+        loop {
+            if let Some(event) = session.poll() {
+                return event;
+            }
+            assert!(
+                start.elapsed() < EVENT_TIMEOUT,
+                "timed out waiting for event"
+            );
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// Hosts a session on a free port and joins it, returning the host and the
+    /// client once the client has been welcomed.
+    fn connected_pair() -> (NetSession, NetSession) {
+        let host = NetSession::host(0).unwrap();
+        let client = NetSession::join(&format!("127.0.0.1:{}", host.port().unwrap())).unwrap();
+
+        assert!(matches!(next_event(&host), NetEvent::Connected { peer: 1 }));
+        assert!(matches!(next_event(&client), NetEvent::Joined { peer: 1 }));
+        (host, client)
+    }
+
+    fn chat(text: &str) -> NetMessage {
+        NetMessage::Chat {
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn host_and_client_exchange_messages() {
+        let (host, client) = connected_pair();
+        assert!(host.is_host());
+        assert!(!client.is_host());
+
+        client.send(chat("hello"), None).unwrap();
+        let event = next_event(&host);
+        assert!(matches!(
+            event,
+            NetEvent::Received { from: 1, message: NetMessage::Chat { text } } if text == "hello"
+        ));
+
+        host.send(chat("welcome"), None).unwrap();
+        host.send(chat("direct"), Some(1)).unwrap();
+        for expected in ["welcome", "direct"] {
+            let event = next_event(&client);
+            assert!(matches!(
+                event,
+                NetEvent::Received { from: HOST_PEER, message: NetMessage::Chat { text } }
+                    if text == expected
+            ));
+        }
+    }
+
+    #[test]
+    fn sends_are_checked_against_the_authority_model() {
+        let (host, client) = connected_pair();
+
+        let err = client.send(chat("sneaky"), Some(2)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        let err = host.send(chat("nobody"), Some(7)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn dropping_the_host_closes_its_clients() {
+        let (host, client) = connected_pair();
+        drop(host);
+
+        assert!(matches!(next_event(&client), NetEvent::Closed { .. }));
+    }
+
+    #[test]
+    fn leaving_client_is_reported_as_disconnected() {
+        let (host, client) = connected_pair();
+        client.close();
+
+        assert!(matches!(
+            next_event(&host),
+            NetEvent::Disconnected { peer: 1 }
+        ));
+    }
+
+    #[test]
+    fn oversized_frames_are_rejected() {
+        let line = format!("{}\n", "x".repeat(MAX_FRAME_SIZE as usize + 1));
+        let mut reader = Cursor::new(line.into_bytes());
+        let err = read_frame(&mut reader, &mut String::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut reader = Cursor::new(Vec::new());
+        assert!(
+            read_frame(&mut reader, &mut String::new())
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -1,52 +1,170 @@
 //! This module contains API that can be used by the script engine to interact
 //! with the game client.
+//!
+//! Packets are exchanged through the ops of the [`awgen_packets`] extension,
+//! so that binary frames can be passed to and from the scripts as a
+//! `Uint8Array`. Packets sent as JSON may also be sent through the
+//! `sendPackets` function.
 
-use std::future::Future;
-use std::pin::Pin;
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::Arc;
 
+use bevy::log::{debug, error, info, trace, warn};
+use deno_error::JsErrorBox;
+use rustyscript::deno_core::{self, Extension, OpState, ToJsBuffer, op2};
 use rustyscript::{Error, Runtime};
+use serde::Serialize;
 use serde_json::Value;
 use smol::channel::{Receiver, Sender};
 
 use crate::database::Database;
-use crate::scripts::{PacketIn, PacketOut};
+use crate::logging::SCRIPT_TARGET;
+use crate::scripts::codec::{EncodedPacket, PacketCodec, decode_frame};
+use crate::scripts::limits::Watchdog;
+use crate::scripts::{PacketIn, StampedPacket};
+
+/// The channels between the game and the script engine, held in the state of
+/// the [`awgen_packets`] extension.
+#[derive(Clone)]
+pub(crate) struct PacketChannels {
+    /// The packets sent to the script engine.
+    socket: Arc<Receiver<StampedPacket>>,
+
+    /// The channel that packets from the script engine are sent through.
+    send_to_client: Sender<PacketIn>,
+
+    /// The codec of the negotiated packet encoding.
+    codec: PacketCodec,
+
+    /// The watchdog limiting the time spent in script callbacks, if any.
+    watchdog: Option<Watchdog>,
+}
+
+deno_core::extension!(
+    awgen_packets,
+    ops = [op_fetch_packet, op_send_frame],
+    options = { channels: PacketChannels },
+    state = |state, options| {
+        state.put(options.channels);
+    },
+);
+
+/// Creates the extension that exchanges packets with the script engine.
+pub fn extension(
+    socket: Arc<Receiver<StampedPacket>>,
+    send_to_client: Sender<PacketIn>,
+    codec: PacketCodec,
+) -> Extension {
+    awgen_packets::init(PacketChannels {
+        socket,
+        send_to_client,
+        codec,
+        watchdog: None,
+    })
+}
+
+/// Lets the given watchdog pause while the script engine waits for packets.
+///
+/// The watchdog can only be started once the runtime exists, after the packet
+/// extension has been created.
+pub fn attach_watchdog(runtime: &mut Runtime, watchdog: Option<Watchdog>) {
+    let op_state = runtime.deno_runtime().op_state();
+    op_state
+        .borrow_mut()
+        .borrow_mut::<PacketChannels>()
+        .watchdog = watchdog;
+}
+
+/// A packet as it is passed to the script engine, which is either a JSON
+/// value or a binary frame that reaches the scripts as a `Uint8Array`.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ScriptPacket {
+    /// A packet sent as JSON.
+    Json(Value),
+
+    /// A packet sent as a binary frame.
+    Frame(ToJsBuffer),
+}
+
+/// Waits for the next packet from the game and encodes it for the script
+/// engine.
+#[op2(async)]
+#[serde]
+async fn op_fetch_packet(state: Rc<RefCell<OpState>>) -> Result<ScriptPacket, JsErrorBox> {
+    let channels = state.borrow().borrow::<PacketChannels>().clone();
+
+    if let Some(watchdog) = &channels.watchdog {
+        watchdog.pause();
+    }
+
+    let packet = channels.socket.recv().await;
+
+    if let Some(watchdog) = &channels.watchdog {
+        watchdog.resume();
+    }
+
+    let packet = packet.map_err(|_| JsErrorBox::generic("Failed to receive packet"))?;
+    let encoded = channels
+        .codec
+        .encode(&packet)
+        .map_err(|e| JsErrorBox::generic(format!("Failed to encode packet: {e}")))?;
+
+    Ok(match encoded {
+        EncodedPacket::Json(value) => ScriptPacket::Json(value),
+        EncodedPacket::Frame(frame) => ScriptPacket::Frame(frame.into()),
+    })
+}
+
+/// Sends the packets held by a binary frame from the script engine to the
+/// game. The frame holds an array of packets.
+#[op2]
+fn op_send_frame(state: &mut OpState, #[buffer] frame: &[u8]) -> Result<(), JsErrorBox> {
+    let channels = state.borrow::<PacketChannels>();
+    let packets = decode_frame::<Vec<PacketIn>>(frame)
+        .map_err(|e| JsErrorBox::type_error(format!("Failed to parse packet: {e}")))?;
+
+    send_packets(&channels.send_to_client, &channels.codec, packets)
+        .map_err(|_| JsErrorBox::generic("Failed to send packet"))
+}
+
+/// Sends packets from the script engine to the game, as a single compound
+/// packet if there are several of them.
+///
+/// An init packet sets the encoding of the packets sent from then on.
+fn send_packets(
+    send_to_client: &Sender<PacketIn>,
+    codec: &PacketCodec,
+    mut packets: Vec<PacketIn>,
+) -> Result<(), smol::channel::SendError<PacketIn>> {
+    for packet in &packets {
+        if let PacketIn::Init { encoding, .. } = packet {
+            codec.set_encoding(encoding.unwrap_or_default());
+        }
+    }
+
+    let packet = match packets.len() {
+        0 => return Ok(()),
+        1 => packets.remove(0),
+        _ => PacketIn::Set { packets },
+    };
+
+    send_to_client.send_blocking(packet)
+}
 
 /// Registers the API functions with the script engine runtime.
 pub fn register(
     runtime: &mut Runtime,
-    socket: Arc<Receiver<PacketOut>>,
     send_to_client: Sender<PacketIn>,
+    codec: PacketCodec,
     database: Arc<Database>,
 ) -> Result<(), rustyscript::Error> {
     // Register sockets functions
 
-    runtime.register_async_function(
-        "fetchPacket",
-        move |args: Vec<Value>| -> Pin<Box<dyn Future<Output = Result<Value, Error>>>> {
-            let local = socket.clone();
-            Box::pin(async move {
-                if !args.is_empty() {
-                    return Err(Error::Runtime("Expected: fetchPacket()".to_string()));
-                }
-
-                let packet = local
-                    .recv()
-                    .await
-                    .map_err(|_| Error::Runtime("Failed to receive packet".to_string()))?;
-                serde_json::to_value(packet)
-                    .map_err(|e| Error::Runtime(format!("Failed to parse packet: {e}")))
-            })
-        },
-    )?;
-
     runtime.register_function(
         "sendPackets",
         move |args: &[Value]| -> Result<Value, Error> {
-            if args.is_empty() {
-                return Ok(Value::Null);
-            }
-
             let mut packets = vec![];
 
             for arg in args {
@@ -55,21 +173,35 @@ pub fn register(
                 packets.push(packet);
             }
 
-            if packets.len() == 1 {
-                send_to_client
-                    .send_blocking(packets.into_iter().next().unwrap())
-                    .map_err(|_| Error::Runtime("Failed to send packet".to_string()))?;
-            } else {
-                let compound = PacketIn::Set { packets };
-                send_to_client
-                    .send_blocking(compound)
-                    .map_err(|_| Error::Runtime("Failed to send packet".to_string()))?;
-            }
+            send_packets(&send_to_client, &codec, packets)
+                .map_err(|_| Error::Runtime("Failed to send packet".to_string()))?;
 
             Ok(Value::Null)
         },
     )?;
 
+    // Register logging functions
+
+    runtime.register_function("log", |args: &[Value]| -> Result<Value, Error> {
+        let (Some(level), Some(message)) = (
+            args.first().and_then(Value::as_str),
+            args.get(1).and_then(Value::as_str),
+        ) else {
+            return Err(Error::Runtime("Expected: log(level, message)".to_string()));
+        };
+
+        match level {
+            "error" => error!(target: SCRIPT_TARGET, "{}", message),
+            "warn" => warn!(target: SCRIPT_TARGET, "{}", message),
+            "info" => info!(target: SCRIPT_TARGET, "{}", message),
+            "debug" => debug!(target: SCRIPT_TARGET, "{}", message),
+            "trace" => trace!(target: SCRIPT_TARGET, "{}", message),
+            _ => return Err(Error::Runtime(format!("Unknown log level: {level}"))),
+        }
+
+        Ok(Value::Null)
+    })?;
+
     // Register database functions
 
     let db1 = database.clone();
@@ -0,0 +1,304 @@
+//! This module runs the packets of the script engine that may take a long
+//! time, loading saves and importing assets, as background jobs.
+//!
+//! Saves are always loaded as blocking jobs, as the map must not be edited
+//! while it is being replaced. Imports only block the user interface when the
+//! imported file is large, and are otherwise run in the background.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use crate::database::DatabaseError;
+use crate::database::savegame::SaveGame;
+use crate::jobs::{
+    BackgroundJobSystems,
+    BackgroundJobs,
+    BackgroundJobsAppExt,
+    JobContext,
+    JobFinished,
+    JobHandle,
+    JobResult,
+};
+use crate::map::{ChunkModels, ChunkPos, ChunkTable, MapSettings, VoxelChunk};
+use crate::scripts::PacketOut;
+use crate::scripts::plugin::{reply, save_failed};
+use crate::telemetry::{ASSETS_IMPORTED, UsageCounters};
+
+/// The size, in bytes, above which an imported file blocks the user interface
+/// while it is copied.
+const HUGE_IMPORT_SIZE: u64 = 16 * 1024 * 1024;
+
+/// The size, in bytes, of the chunks that imported files are copied in.
+const IMPORT_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Plugin that finishes the background jobs started by the script engine.
+pub(crate) struct ScriptJobsPlugin;
+impl Plugin for ScriptJobsPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<BackgroundJobs>()
+            .init_resource::<LoadingSaves>()
+            .register_job_output::<SaveRead>()
+            .register_job_output::<AssetImported>()
+            .add_systems(
+                Update,
+                (finish_save_loads, finish_imports).after(BackgroundJobSystems::Poll),
+            );
+    }
+}
+
+/// The output of a job that reads the chunks and script data of a save slot.
+pub(crate) struct SaveRead {
+    /// The name of the save slot.
+    slot: String,
+
+    /// The chunks and script data stored in the save, or the error that
+    /// occurred while reading them.
+    result: Result<(Vec<(ChunkPos, ChunkModels)>, serde_json::Value), DatabaseError>,
+}
+
+/// The output of a job that copies a file into the project directory.
+pub(crate) struct AssetImported {
+    /// The OS filepath of the imported file.
+    file: String,
+
+    /// The local asset path that the file was imported as.
+    asset_path: String,
+
+    /// The result of copying the file.
+    result: io::Result<()>,
+}
+
+/// A resource that holds the save slots that are being loaded.
+#[derive(Debug, Default, Resource)]
+struct LoadingSaves {
+    /// The jobs reading the saves, with the names of their save slots.
+    jobs: Vec<(JobHandle<SaveRead>, String)>,
+}
+
+/// Starts a blocking job that reads the given save slot, replacing the current
+/// map state with it once it has been read.
+///
+/// A save that is still being loaded is cancelled, as it would be replaced
+/// right away.
+pub(super) fn load_save(world: &mut World, project_folder: PathBuf, slot: String) {
+    let label = format!("Loading save \"{}\"", slot);
+    let read_slot = slot.clone();
+    let job = world
+        .resource_mut::<BackgroundJobs>()
+        .spawn_blocking(label, |ctx| async move {
+            let result = read_save(&project_folder, &read_slot, &ctx);
+            SaveRead {
+                slot: read_slot,
+                result,
+            }
+        });
+
+    world.resource_scope(|world, mut loading: Mut<LoadingSaves>| {
+        let jobs = world.resource::<BackgroundJobs>();
+        for (old_job, old_slot) in &loading.jobs {
+            if jobs.cancel(old_job.id()) {
+                info!("Cancelling superseded load of save \"{}\"", old_slot);
+            }
+        }
+        loading.jobs.push((job, slot));
+    });
+}
+
+/// Reads the chunks and script data stored in a save slot.
+fn read_save(
+    project_folder: &Path,
+    slot: &str,
+    ctx: &JobContext,
+) -> Result<(Vec<(ChunkPos, ChunkModels)>, serde_json::Value), DatabaseError> {
+    let save = SaveGame::open(project_folder, slot)?;
+    let chunks = save.read_chunks()?;
+    ctx.set_progress(0.9);
+
+    let data = save.get_data()?;
+    ctx.set_progress(1.0);
+    Ok((chunks, data))
+}
+
+/// Starts a job that copies the given file to the given destination path. The
+/// job blocks the user interface if the file is large.
+pub(super) fn import_asset(
+    world: &mut World,
+    file: String,
+    dest_path: PathBuf,
+    asset_path: String,
+) -> io::Result<()> {
+    let size = std::fs::metadata(&file)?.len();
+    let label = format!("Importing {}", asset_path);
+
+    let job = |ctx: JobContext| async move {
+        let result = copy_with_progress(Path::new(&file), &dest_path, size, &ctx);
+        AssetImported {
+            file,
+            asset_path,
+            result,
+        }
+    };
+
+    let mut jobs = world.resource_mut::<BackgroundJobs>();
+    if size >= HUGE_IMPORT_SIZE {
+        jobs.spawn_blocking(label, job);
+    } else {
+        jobs.spawn(label, job);
+    }
+    Ok(())
+}
+
+/// Copies a file in chunks, reporting the progress of the copy and stopping
+/// if the job is cancelled. A partially copied file is removed.
+fn copy_with_progress(from: &Path, to: &Path, size: u64, ctx: &JobContext) -> io::Result<()> {
+    let mut reader = File::open(from)?;
+    let mut writer = File::create(to)?;
+    let mut buffer = vec![0; IMPORT_CHUNK_SIZE];
+    let mut copied = 0;
+
+    loop {
+        if ctx.is_cancelled() {
+            drop(writer);
+            std::fs::remove_file(to)?;
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "import cancelled",
+            ));
+        }
+
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(());
+        }
+
+        writer.write_all(&buffer[.. read])?;
+        copied += read as u64;
+        ctx.set_progress(copied as f32 / size.max(1) as f32);
+    }
+}
+
+/// System to replace the map state with the saves that have been read, and to
+/// report saves that failed to load to the script engine.
+fn finish_save_loads(world: &mut World) {
+    let finished = world
+        .resource_mut::<Messages<JobFinished<SaveRead>>>()
+        .drain()
+        .collect::<Vec<_>>();
+
+    for job in finished {
+        let slot = world.resource_scope(|_, mut loading: Mut<LoadingSaves>| {
+            let index = loading
+                .jobs
+                .iter()
+                .position(|(handle, _)| *handle == job.handle)?;
+            Some(loading.jobs.remove(index).1)
+        });
+
+        let read = match job.result {
+            JobResult::Completed(read) => read,
+            JobResult::Cancelled => {
+                let Some(slot) = slot else {
+                    continue;
+                };
+
+                info!("Loading save \"{}\" was cancelled.", slot);
+                reply(
+                    world,
+                    PacketOut::SaveFailed {
+                        slot,
+                        error: "Loading the save was cancelled".to_string(),
+                    },
+                );
+                continue;
+            }
+        };
+
+        match read.result {
+            Ok((chunks, data)) => {
+                apply_save(world, &read.slot, chunks);
+                crate::shutdown::mark_saved(world, &read.slot);
+                reply(
+                    world,
+                    PacketOut::SaveLoaded {
+                        slot: read.slot,
+                        data,
+                    },
+                );
+            }
+            Err(err) => save_failed(world, read.slot, err),
+        }
+    }
+}
+
+/// Replaces the chunks of the current map with the chunks read from a save
+/// slot, skipping blocks outside of the world height bounds.
+fn apply_save(world: &mut World, slot: &str, chunks: Vec<(ChunkPos, ChunkModels)>) {
+    let old_chunks = world
+        .query_filtered::<Entity, With<VoxelChunk>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    for entity in old_chunks {
+        world.despawn(entity);
+    }
+    world.resource_mut::<ChunkTable>().clear();
+
+    let settings = world
+        .get_resource::<MapSettings>()
+        .copied()
+        .unwrap_or_default();
+
+    for (pos, mut models) in chunks {
+        if !settings.intersects_chunk(pos) {
+            warn!(
+                "Skipping chunk {} of save {}: outside of the world height bounds",
+                pos, slot
+            );
+            continue;
+        }
+
+        let removed = settings.clip_chunk(pos, &mut models);
+        if removed > 0 {
+            warn!(
+                "Removed {} blocks outside of the world height bounds from chunk {} of save {}",
+                removed, pos, slot
+            );
+        }
+
+        let mut chunk = VoxelChunk::new(pos);
+        *chunk.get_models_mut() = models;
+        let chunk_id = world.spawn(chunk).id();
+        world.resource_mut::<ChunkTable>().add_chunk(pos, chunk_id);
+    }
+}
+
+/// System to report finished imports.
+fn finish_imports(
+    mut finished: MessageReader<JobFinished<AssetImported>>,
+    mut counters: Option<ResMut<UsageCounters>>,
+) {
+    for job in finished.read() {
+        let JobResult::Completed(imported) = &job.result else {
+            info!("Asset import was cancelled.");
+            continue;
+        };
+
+        match &imported.result {
+            Ok(()) => {
+                debug!(
+                    "Imported asset from {} as {}",
+                    imported.file, imported.asset_path
+                );
+                if let Some(counters) = counters.as_mut() {
+                    counters.increment(ASSETS_IMPORTED);
+                }
+            }
+            Err(err) => error!(
+                "Failed to import asset file {} as {}: {}",
+                imported.file, imported.asset_path, err
+            ),
+        }
+    }
+}
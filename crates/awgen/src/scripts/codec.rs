@@ -0,0 +1,303 @@
+//! This module implements the encodings used for packets passed between the
+//! game and the script engine.
+//!
+//! Packets are exchanged as JSON by default. Scripts may opt into the
+//! MessagePack encoding in their [`PacketIn::Init`] packet, after which packets
+//! whose MessagePack form is at least [`FRAME_THRESHOLD`] bytes are exchanged
+//! as binary frames, which reach the scripts as a `Uint8Array`. Smaller packets
+//! are always exchanged as JSON, so that they stay easy to debug.
+//!
+//! A binary frame starts with a single header byte. A header of
+//! [`FRAME_MESSAGE_PACK`] is followed by the MessagePack packet itself, and a
+//! header of [`FRAME_LZ4`] is followed by the LZ4-compressed MessagePack
+//! packet, prefixed by its uncompressed size as a 32-bit little-endian integer.
+//! Frames of at least [`COMPRESSION_THRESHOLD`] bytes are compressed whenever
+//! that makes them smaller.
+//!
+//! [`PacketIn::Init`]: crate::scripts::PacketIn::Init
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ts_rs::TS;
+
+/// The size in bytes of the MessagePack form of a packet, above which the
+/// packet is sent as a binary frame.
+pub const FRAME_THRESHOLD: usize = 4096;
+
+/// The size in bytes of the MessagePack form of a packet, above which its
+/// frame is compressed.
+pub const COMPRESSION_THRESHOLD: usize = 16 * 1024;
+
+/// The header of a frame holding an uncompressed MessagePack packet.
+pub const FRAME_MESSAGE_PACK: u8 = 0;
+
+/// The header of a frame holding an LZ4-compressed MessagePack packet.
+pub const FRAME_LZ4: u8 = 1;
+
+/// The encoding used for large packets passed between the game and the script
+/// engine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum PacketEncoding {
+    /// Every packet is sent as JSON.
+    #[default]
+    Json,
+
+    /// Large packets are sent as binary frames holding MessagePack data.
+    MessagePack,
+}
+
+/// A packet encoded for the script engine.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodedPacket {
+    /// A packet sent as a JSON value.
+    Json(Value),
+
+    /// A packet sent as a binary frame.
+    Frame(Vec<u8>),
+}
+
+/// Encodes and decodes packets using the encoding negotiated with the script
+/// engine.
+///
+/// Clones of a codec share the same encoding, so that the encoding requested
+/// by an incoming [`PacketIn::Init`] packet applies to outgoing packets too.
+///
+/// [`PacketIn::Init`]: crate::scripts::PacketIn::Init
+#[derive(Debug, Clone, Default)]
+pub struct PacketCodec {
+    /// Whether the MessagePack encoding has been negotiated.
+    binary: Arc<AtomicBool>,
+}
+
+impl PacketCodec {
+    /// Gets the negotiated packet encoding.
+    pub fn encoding(&self) -> PacketEncoding {
+        if self.binary.load(Ordering::Relaxed) {
+            PacketEncoding::MessagePack
+        } else {
+            PacketEncoding::Json
+        }
+    }
+
+    /// Sets the negotiated packet encoding.
+    pub fn set_encoding(&self, encoding: PacketEncoding) {
+        let binary = encoding == PacketEncoding::MessagePack;
+        self.binary.store(binary, Ordering::Relaxed);
+    }
+
+    /// Encodes the given packet for the script engine, as a binary frame if
+    /// the MessagePack encoding was negotiated and the packet is large, or as
+    /// JSON otherwise.
+    pub fn encode<T: Serialize>(&self, packet: &T) -> Result<EncodedPacket, CodecError> {
+        if self.encoding() == PacketEncoding::Json {
+            return Ok(EncodedPacket::Json(serde_json::to_value(packet)?));
+        }
+
+        let data = rmp_serde::to_vec_named(packet)?;
+        if data.len() < FRAME_THRESHOLD {
+            return Ok(EncodedPacket::Json(serde_json::to_value(packet)?));
+        }
+
+        Ok(EncodedPacket::Frame(encode_frame(data)))
+    }
+}
+
+/// Wraps MessagePack data in a binary frame, compressing it if it is large
+/// and compression makes it smaller.
+fn encode_frame(data: Vec<u8>) -> Vec<u8> {
+    if data.len() >= COMPRESSION_THRESHOLD {
+        let compressed = lz4_flex::compress_prepend_size(&data);
+        if compressed.len() < data.len() {
+            let mut frame = Vec::with_capacity(compressed.len() + 1);
+            frame.push(FRAME_LZ4);
+            frame.extend_from_slice(&compressed);
+            return frame;
+        }
+    }
+
+    let mut frame = Vec::with_capacity(data.len() + 1);
+    frame.push(FRAME_MESSAGE_PACK);
+    frame.extend_from_slice(&data);
+    frame
+}
+
+/// Decodes a value from a binary frame sent by the script engine.
+pub fn decode_frame<T: DeserializeOwned>(frame: &[u8]) -> Result<T, CodecError> {
+    let Some((&header, data)) = frame.split_first() else {
+        return Err(CodecError::EmptyFrame);
+    };
+
+    match header {
+        FRAME_MESSAGE_PACK => Ok(rmp_serde::from_slice(data)?),
+        FRAME_LZ4 => {
+            let data = lz4_flex::decompress_size_prepended(data)?;
+            Ok(rmp_serde::from_slice(&data)?)
+        }
+        header => Err(CodecError::UnknownFrame(header)),
+    }
+}
+
+/// An error that can occur while encoding or decoding a packet.
+#[derive(Debug, thiserror::Error)]
+pub enum CodecError {
+    /// The packet could not be converted to or from JSON.
+    #[error("Invalid JSON packet: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The packet could not be encoded as MessagePack.
+    #[error("Failed to encode MessagePack packet: {0}")]
+    Encode(#[from] rmp_serde::encode::Error),
+
+    /// The frame data could not be decoded as MessagePack.
+    #[error("Invalid MessagePack packet: {0}")]
+    Decode(#[from] rmp_serde::decode::Error),
+
+    /// The frame data could not be decompressed.
+    #[error("Invalid compressed frame: {0}")]
+    Compression(#[from] lz4_flex::block::DecompressError),
+
+    /// The frame is empty, so it has no header.
+    #[error("Empty packet frame")]
+    EmptyFrame,
+
+    /// The frame header is not a known frame type.
+    #[error("Unknown packet frame header: {0}")]
+    UnknownFrame(u8),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scripts::{PacketIn, PacketOut, StampedPacket};
+
+    /// Creates a codec that has negotiated the MessagePack encoding.
+    fn binary_codec() -> PacketCodec {
+        let codec = PacketCodec::default();
+        codec.set_encoding(PacketEncoding::MessagePack);
+        codec
+    }
+
+    /// Creates a stamped packet of a dropped file with the given path.
+    fn file_drop(path: String) -> StampedPacket {
+        StampedPacket {
+            tick: Some(7),
+            packet: PacketOut::FileDrop { path },
+        }
+    }
+
+    /// Encodes a packet, expecting it to be sent as a binary frame.
+    fn frame<T: Serialize>(codec: &PacketCodec, packet: &T) -> Vec<u8> {
+        match codec.encode(packet).unwrap() {
+            EncodedPacket::Frame(frame) => frame,
+            EncodedPacket::Json(value) => panic!("Expected a frame, got {value}"),
+        }
+    }
+
+    #[test]
+    fn small_packets_stay_json() {
+        let packet = file_drop("image.png".to_string());
+        let json = serde_json::to_value(&packet).unwrap();
+
+        assert_eq!(
+            PacketCodec::default().encode(&packet).unwrap(),
+            EncodedPacket::Json(json.clone())
+        );
+        assert_eq!(
+            binary_codec().encode(&packet).unwrap(),
+            EncodedPacket::Json(json)
+        );
+    }
+
+    #[test]
+    fn large_packets_are_framed_only_when_negotiated() {
+        let packet = file_drop("image/".repeat(1000));
+
+        assert!(matches!(
+            PacketCodec::default().encode(&packet).unwrap(),
+            EncodedPacket::Json(_)
+        ));
+
+        let frame = frame(&binary_codec(), &packet);
+        assert_eq!(frame[0], FRAME_MESSAGE_PACK);
+        assert_eq!(
+            decode_frame::<Value>(&frame).unwrap(),
+            serde_json::to_value(&packet).unwrap()
+        );
+    }
+
+    #[test]
+    fn huge_frames_are_compressed() {
+        let path = "image/".repeat(10_000) + "\u{1F600} ünïcödé.png";
+        let packet = file_drop(path.clone());
+
+        let frame = frame(&binary_codec(), &packet);
+        assert_eq!(frame[0], FRAME_LZ4);
+        assert!(frame.len() < path.len() / 10);
+        assert_eq!(
+            decode_frame::<Value>(&frame).unwrap(),
+            serde_json::to_value(&packet).unwrap()
+        );
+    }
+
+    #[test]
+    fn incompressible_frames_are_sent_raw() {
+        let mut state = 0x2545_f491_u32;
+        let data = (0 .. COMPRESSION_THRESHOLD * 2)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect::<Vec<_>>();
+
+        let frame = encode_frame(data.clone());
+        assert_eq!(frame[0], FRAME_MESSAGE_PACK);
+        assert_eq!(&frame[1 ..], data.as_slice());
+    }
+
+    #[test]
+    fn frames_decode_incoming_packets() {
+        let packets = vec![
+            PacketIn::Init {
+                name: "Frames".to_string(),
+                version: "1.0.0".to_string(),
+                encoding: Some(PacketEncoding::MessagePack),
+            },
+            PacketIn::ConsolePrint {
+                text: "Awgen ".repeat(10_000),
+            },
+        ];
+        let expected = serde_json::to_value(&packets).unwrap();
+
+        let data = rmp_serde::to_vec_named(&packets).unwrap();
+        let mut raw = vec![FRAME_MESSAGE_PACK];
+        raw.extend_from_slice(&data);
+        let compressed = encode_frame(data);
+        assert_eq!(compressed[0], FRAME_LZ4);
+
+        for frame in [raw, compressed] {
+            let decoded = decode_frame::<Vec<PacketIn>>(&frame).unwrap();
+            assert_eq!(serde_json::to_value(&decoded).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn malformed_frames_are_rejected() {
+        assert!(matches!(
+            decode_frame::<Value>(&[]),
+            Err(CodecError::EmptyFrame)
+        ));
+        assert!(matches!(
+            decode_frame::<Value>(&[7, 0xc0]),
+            Err(CodecError::UnknownFrame(7))
+        ));
+        assert!(decode_frame::<Value>(&[FRAME_LZ4, 1, 2]).is_err());
+        assert!(decode_frame::<Value>(&[FRAME_MESSAGE_PACK, 0xc1]).is_err());
+    }
+}
@@ -0,0 +1,284 @@
+//! This module implements a deterministic test harness for the script protocol.
+//!
+//! The harness starts a real script engine inside a temporary project folder
+//! with an in-memory database, and a headless Bevy world that incoming packets
+//! can be applied to. Tests drive the engine by sending [`PacketOut`] packets
+//! and asserting on the resulting [`PacketIn`] stream and world state.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use awgen_asset_db::prelude::{AwgenAssetPlugin, AwgenAssetPluginExt};
+use bevy::prelude::*;
+
+use crate::app::ProjectSettings;
+use crate::database::{Database, DatabaseError, GameDatabase, ProjectAssets};
+use crate::framepace::FramePacing;
+use crate::jobs::BackgroundJobs;
+use crate::map::{
+    AreaIndex,
+    ChunkTable,
+    LightTable,
+    MapSettings,
+    MapSnapshots,
+    on_light_add,
+    on_light_remove,
+};
+use crate::scripts::background::ScriptJobsPlugin;
+use crate::scripts::{
+    PacketIn,
+    PacketOut,
+    ScriptEngineError,
+    ScriptEngineSettings,
+    ScriptLimits,
+    ScriptSockets,
+    plugin,
+};
+use crate::shutdown::UnsavedChanges;
+use crate::telemetry::UsageCounters;
+use crate::tiles::{ActiveTilesets, GeneratingTilesets, TilesetMaterial};
+use crate::ux::AtmosphereSettings;
+
+/// The default amount of time to wait for a packet before failing.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A counter used to give each harness a unique project folder.
+static HARNESS_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A test harness that runs the script engine against an in-memory project.
+pub struct ScriptHarness {
+    /// The sockets connected to the script engine.
+    sockets: ScriptSockets,
+
+    /// The headless Bevy app that incoming packets are applied to.
+    app: App,
+
+    /// The database shared with the script engine.
+    database: Arc<Database>,
+
+    /// The temporary project folder.
+    folder: PathBuf,
+
+    /// The amount of time to wait for a packet before failing.
+    timeout: Duration,
+}
+
+impl ScriptHarness {
+    /// Starts a new script engine with the given source code as the `Main.ts`
+    /// entry script.
+    ///
+    /// The sample project's `API` folder is copied next to the entry script, so
+    /// scripts may import it with `./API/...` paths.
+    pub fn new(main_script: &str) -> Result<Self, HarnessError> {
+        Self::new_with_limits(main_script, ScriptLimits::default())
+    }
+
+    /// Starts a new script engine like [`ScriptHarness::new`], using the given
+    /// resource limits.
+    pub fn new_with_limits(main_script: &str, limits: ScriptLimits) -> Result<Self, HarnessError> {
+        let folder = std::env::temp_dir().join(format!(
+            "awgen_harness_{}_{}",
+            std::process::id(),
+            HARNESS_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let scripts = folder.join("scripts");
+        std::fs::create_dir_all(&scripts)?;
+        std::fs::write(scripts.join("Main.ts"), main_script)?;
+
+        let api =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../sample_project/editor/scripts/API");
+        if api.is_dir() {
+            copy_dir(&api, &scripts.join("API"))?;
+        }
+
+        let database = Arc::new(Database::in_memory()?);
+        let settings = ScriptEngineSettings::new(scripts).with_limits(limits);
+        let sockets = super::start_script_engine(settings, database.clone())?;
+
+        let mut app = App::new();
+        app.register_asset_db::<ProjectAssets, _>(":memory:")
+            .add_plugins((MinimalPlugins, AssetPlugin::default(), AwgenAssetPlugin))
+            .init_asset::<Image>()
+            .init_asset::<TilesetMaterial>()
+            .insert_resource(ProjectSettings::new(folder.clone()))
+            .insert_resource(GameDatabase(database.clone()))
+            .init_resource::<ChunkTable>()
+            .init_resource::<MapSnapshots>()
+            .init_resource::<AreaIndex>()
+            .init_resource::<LightTable>()
+            .init_resource::<MapSettings>()
+            .init_resource::<FramePacing>()
+            .init_resource::<AtmosphereSettings>()
+            .init_resource::<BackgroundJobs>()
+            .init_resource::<UnsavedChanges>()
+            .init_resource::<ActiveTilesets>()
+            .init_resource::<GeneratingTilesets>()
+            .init_resource::<UsageCounters>()
+            .add_plugins(ScriptJobsPlugin)
+            .add_observer(on_light_add)
+            .add_observer(on_light_remove);
+
+        Ok(Self {
+            sockets,
+            app,
+            database,
+            folder,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Sets the amount of time to wait for a packet before failing.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Gets the database shared with the script engine.
+    pub fn database(&self) -> &Database {
+        &self.database
+    }
+
+    /// Gets the temporary project folder.
+    pub fn project_folder(&self) -> &Path {
+        &self.folder
+    }
+
+    /// Gets the headless world that packets are applied to.
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    /// Gets mutable access to the headless world that packets are applied to.
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    /// Sends a packet to the script engine.
+    pub fn send(&self, packet: PacketOut) -> Result<(), HarnessError> {
+        Ok(self.sockets.send(packet)?)
+    }
+
+    /// Waits for the next packet from the script engine.
+    ///
+    /// Compound [`PacketIn::Set`] packets are returned as-is.
+    pub fn recv(&self) -> Result<PacketIn, HarnessError> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            if let Some(packet) = self.sockets.recv()? {
+                return Ok(packet);
+            }
+
+            if Instant::now() >= deadline {
+                return Err(HarnessError::Timeout(self.timeout));
+            }
+
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Waits for the next packet from the script engine and checks it with the
+    /// given predicate, returning the packet if it matches.
+    pub fn expect(
+        &self,
+        predicate: impl FnOnce(&PacketIn) -> bool,
+    ) -> Result<PacketIn, HarnessError> {
+        let packet = self.recv()?;
+        if predicate(&packet) {
+            Ok(packet)
+        } else {
+            Err(HarnessError::Unexpected(Box::new(packet)))
+        }
+    }
+
+    /// Waits for the initialization packet, returning the game name and
+    /// version.
+    pub fn expect_init(&self) -> Result<(String, String), HarnessError> {
+        match self.recv()? {
+            PacketIn::Init { name, version, .. } => Ok((name, version)),
+            packet => Err(HarnessError::Unexpected(Box::new(packet))),
+        }
+    }
+
+    /// Applies the given packet to the headless world, exactly as the game
+    /// would when receiving it, then runs a single frame.
+    ///
+    /// If the packet started a blocking job, such as loading a save, frames
+    /// are run until the job has finished or the timeout has passed.
+    pub fn apply(&mut self, packet: PacketIn) {
+        let _ = plugin::handle(self.app.world_mut(), packet);
+        self.app.update();
+
+        let deadline = Instant::now() + self.timeout;
+        while self.is_blocked() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+            self.app.update();
+        }
+    }
+
+    /// Returns true if a blocking job is running in the headless world.
+    fn is_blocked(&self) -> bool {
+        self.app
+            .world()
+            .resource::<BackgroundJobs>()
+            .iter()
+            .any(|job| job.blocking)
+    }
+
+    /// Waits for the next packet from the script engine and applies it to the
+    /// headless world, returning a copy of the packet.
+    pub fn recv_and_apply(&mut self) -> Result<PacketIn, HarnessError> {
+        let packet = self.recv()?;
+        self.apply(packet.clone());
+        Ok(packet)
+    }
+}
+
+impl Drop for ScriptHarness {
+    fn drop(&mut self) {
+        self.sockets.shutdown();
+        let _ = std::fs::remove_dir_all(&self.folder);
+    }
+}
+
+/// Recursively copies a directory.
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        let dest = to.join(entry.file_name());
+        if path.is_dir() {
+            copy_dir(&path, &dest)?;
+        } else {
+            std::fs::copy(&path, &dest)?;
+        }
+    }
+    Ok(())
+}
+
+/// Errors that can occur while running the script harness.
+#[derive(Debug, thiserror::Error)]
+pub enum HarnessError {
+    /// Failed to prepare the temporary project folder.
+    #[error("Failed to prepare project folder: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Failed to open the in-memory database.
+    #[error("Failed to open database: {0}")]
+    Database(#[from] DatabaseError),
+
+    /// The script engine failed.
+    #[error("Script engine error: {0}")]
+    ScriptEngine(#[from] ScriptEngineError),
+
+    /// No packet was received within the timeout.
+    #[error("Timed out after {0:?} waiting for a packet")]
+    Timeout(Duration),
+
+    /// A packet was received that did not match what was expected.
+    #[error("Unexpected packet: {0:?}")]
+    Unexpected(Box<PacketIn>),
+}
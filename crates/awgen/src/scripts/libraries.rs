@@ -0,0 +1,236 @@
+//! This module implements module resolution for shared script libraries.
+//!
+//! Projects may vendor shared libraries in a library folder, with one
+//! subfolder per library. Scripts import a library using the `lib:` scheme:
+//!
+//! ```ts
+//! import { lerp } from "lib:math";          // libs/math/mod.ts
+//! import { Queue } from "lib:utils/queue.ts"; // libs/utils/queue.ts
+//! ```
+//!
+//! Library imports are resolved strictly within the library folder, so a
+//! script can never use them to load files from elsewhere on the system. Both
+//! the folder and the resolved file are canonicalized before they are
+//! compared, so symbolic links cannot point an import outside of the folder
+//! either.
+
+use std::path::{Component, Path, PathBuf};
+
+use rustyscript::deno_core::error::ModuleLoaderError;
+use rustyscript::deno_core::{ModuleSpecifier, ResolutionKind};
+use rustyscript::module_loader::ImportProvider;
+
+/// The import scheme used to refer to a script library.
+pub const LIBRARY_SCHEME: &str = "lib";
+
+/// The entry files that are searched for, in order, when a library is imported
+/// by name only.
+const ENTRY_FILES: [&str; 2] = ["mod.ts", "index.ts"];
+
+/// Resolves `lib:` imports to files within the project's library folder.
+#[derive(Debug, Clone)]
+pub struct LibraryResolver {
+    /// The folder containing the script libraries, if the project has one.
+    folder: Option<PathBuf>,
+}
+
+impl LibraryResolver {
+    /// Creates a new resolver for libraries stored in the given folder.
+    pub fn new(folder: Option<PathBuf>) -> Self {
+        Self { folder }
+    }
+
+    /// Resolves the path of a library import to a file path.
+    ///
+    /// The path is the part of the specifier after the `lib:` scheme, such as
+    /// `math` or `utils/queue.ts`.
+    pub fn resolve_path(&self, path: &str) -> Result<PathBuf, LibraryError> {
+        let Some(folder) = &self.folder else {
+            return Err(LibraryError::NoLibraryFolder(path.to_string()));
+        };
+
+        let relative = Path::new(path);
+        let is_valid = !path.is_empty()
+            && relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+        if !is_valid {
+            return Err(LibraryError::InvalidPath(path.to_string()));
+        }
+
+        // The folder may be given as a relative path, so it is made absolute
+        // before any file URLs are built from it.
+        let root = folder
+            .canonicalize()
+            .map_err(|_| LibraryError::NoLibraryFolder(path.to_string()))?;
+
+        let target = root.join(relative);
+        let candidates = if target.extension().is_some() {
+            vec![target]
+        } else {
+            ENTRY_FILES.iter().map(|file| target.join(file)).collect()
+        };
+
+        let Some(file) = candidates.iter().find(|candidate| candidate.is_file()) else {
+            return Err(LibraryError::NotFound {
+                path: path.to_string(),
+                searched: candidates,
+            });
+        };
+
+        match file.canonicalize() {
+            Ok(file) if file.starts_with(&root) => Ok(file),
+            _ => Err(LibraryError::InvalidPath(path.to_string())),
+        }
+    }
+}
+
+impl ImportProvider for LibraryResolver {
+    fn resolve(
+        &mut self,
+        specifier: &ModuleSpecifier,
+        _referrer: &str,
+        _kind: ResolutionKind,
+    ) -> Option<Result<ModuleSpecifier, ModuleLoaderError>> {
+        if specifier.scheme() != LIBRARY_SCHEME {
+            return None;
+        }
+
+        let resolved = self.resolve_path(specifier.path()).and_then(|file| {
+            ModuleSpecifier::from_file_path(&file)
+                .map_err(|_| LibraryError::InvalidPath(file.to_string_lossy().to_string()))
+        });
+
+        Some(resolved.map_err(|err| ModuleLoaderError::generic(err.to_string())))
+    }
+}
+
+/// An error that can occur while resolving a library import.
+#[derive(Debug, thiserror::Error)]
+pub enum LibraryError {
+    /// A library was imported, but the project has no library folder.
+    #[error("Cannot import library `{0}`: this project has no library folder")]
+    NoLibraryFolder(String),
+
+    /// The library path is empty or tries to leave the library folder.
+    #[error("Invalid library import `{0}`: paths must stay within the library folder")]
+    InvalidPath(String),
+
+    /// No file exists for the imported library.
+    #[error("Library `{path}` not found. Searched: {searched:?}")]
+    NotFound {
+        /// The library path that was imported.
+        path: String,
+
+        /// The files that were searched for.
+        searched: Vec<PathBuf>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// A counter for creating unique library folders across tests.
+    static FOLDER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Creates an empty folder for a test, containing a `libs` folder.
+    fn test_folder() -> PathBuf {
+        let folder = std::env::temp_dir().join(format!(
+            "awgen_libraries_{}_{}",
+            std::process::id(),
+            FOLDER_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(folder.join("libs")).unwrap();
+        folder
+    }
+
+    #[test]
+    fn resolves_entry_files_and_explicit_files() {
+        let folder = test_folder();
+        fs::create_dir_all(folder.join("libs/math")).unwrap();
+        fs::write(folder.join("libs/math/mod.ts"), "").unwrap();
+        fs::write(folder.join("libs/math/vec.ts"), "").unwrap();
+
+        let resolver = LibraryResolver::new(Some(folder.join("libs")));
+        let root = folder.join("libs").canonicalize().unwrap();
+        assert_eq!(
+            resolver.resolve_path("math").unwrap(),
+            root.join("math/mod.ts")
+        );
+        assert_eq!(
+            resolver.resolve_path("math/vec.ts").unwrap(),
+            root.join("math/vec.ts")
+        );
+        assert!(matches!(
+            resolver.resolve_path("physics"),
+            Err(LibraryError::NotFound { .. })
+        ));
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn relative_folders_resolve_to_absolute_paths() {
+        let folder = test_folder();
+        fs::write(folder.join("libs/util.ts"), "").unwrap();
+
+        let cwd = std::env::current_dir().unwrap();
+        let relative = pathdiff(&folder.join("libs"), &cwd);
+        let resolver = LibraryResolver::new(Some(relative));
+
+        let file = resolver.resolve_path("util.ts").unwrap();
+        assert!(file.is_absolute());
+        assert!(ModuleSpecifier::from_file_path(&file).is_ok());
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn rejects_paths_outside_the_folder() {
+        let folder = test_folder();
+        fs::write(folder.join("secret.ts"), "").unwrap();
+
+        let resolver = LibraryResolver::new(Some(folder.join("libs")));
+        for path in ["", "../secret.ts", "/secret.ts", "math/../../secret.ts"] {
+            assert!(matches!(
+                resolver.resolve_path(path),
+                Err(LibraryError::InvalidPath(_))
+            ));
+        }
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_outside_the_folder() {
+        let folder = test_folder();
+        fs::create_dir_all(folder.join("outside")).unwrap();
+        fs::write(folder.join("outside/mod.ts"), "").unwrap();
+        std::os::unix::fs::symlink(folder.join("outside"), folder.join("libs/escape")).unwrap();
+
+        let resolver = LibraryResolver::new(Some(folder.join("libs")));
+        assert!(matches!(
+            resolver.resolve_path("escape"),
+            Err(LibraryError::InvalidPath(_))
+        ));
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    /// Builds a path to `path` relative to `base`, by stepping up out of
+    /// `base` to the filesystem root first.
+    #[cfg(unix)]
+    fn pathdiff(path: &Path, base: &Path) -> PathBuf {
+        let mut relative = PathBuf::new();
+        for _ in base.components().skip(1) {
+            relative.push("..");
+        }
+        relative.join(path.strip_prefix("/").unwrap_or(path))
+    }
+}
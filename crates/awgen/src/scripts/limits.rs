@@ -0,0 +1,204 @@
+//! This module implements resource limits for the script engine.
+//!
+//! Scripts run on their own thread, but a runaway script could still allocate
+//! all available memory or spin forever, leaving the game unable to talk to
+//! it. The heap limit is enforced by V8 itself, while the execution time limit
+//! is enforced by a watchdog thread that terminates the script if it runs for
+//! too long without yielding back to the engine.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rustyscript::deno_core::v8::IsolateHandle;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// The longest time the watchdog sleeps between checks.
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The shortest time the watchdog sleeps between checks, so that very small
+/// budgets do not keep the watchdog thread spinning.
+const MIN_WATCHDOG_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Resource limits for the script engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptLimits {
+    /// The maximum size of the script heap, in bytes. If `None` or zero, the
+    /// default V8 heap limit is used.
+    pub max_heap_size: Option<usize>,
+
+    /// The longest a script may run without waiting for a packet from the
+    /// engine. If `None` or zero, scripts may run for any amount of time.
+    ///
+    /// Each time the script waits for a packet, the budget is reset.
+    pub callback_budget: Option<Duration>,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_heap_size: Some(512 * 1024 * 1024),
+            callback_budget: Some(Duration::from_secs(5)),
+        }
+    }
+}
+
+impl ScriptLimits {
+    /// Limits that never stop a script.
+    pub fn unlimited() -> Self {
+        Self {
+            max_heap_size: None,
+            callback_budget: None,
+        }
+    }
+
+    /// Sets the maximum size of the script heap, in megabytes. A size of 0
+    /// disables the heap limit.
+    pub fn with_max_heap_mb(mut self, megabytes: usize) -> Self {
+        self.max_heap_size = (megabytes > 0).then(|| megabytes.saturating_mul(1024 * 1024));
+        self
+    }
+
+    /// Sets the callback budget, in milliseconds. A budget of 0 disables the
+    /// execution time limit.
+    pub fn with_callback_budget_ms(mut self, milliseconds: u64) -> Self {
+        self.callback_budget = (milliseconds > 0).then(|| Duration::from_millis(milliseconds));
+        self
+    }
+
+    /// Gets the heap limit, treating a limit of zero bytes as disabled.
+    pub(crate) fn heap_limit(&self) -> Option<usize> {
+        self.max_heap_size.filter(|size| *size > 0)
+    }
+
+    /// Gets the callback budget, treating a zero budget as disabled.
+    pub(crate) fn budget(&self) -> Option<Duration> {
+        self.callback_budget.filter(|budget| !budget.is_zero())
+    }
+}
+
+/// A resource limit that may be exceeded by a script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum ScriptLimit {
+    /// The script heap limit.
+    Memory,
+
+    /// The execution time limit for a single callback.
+    Time,
+}
+
+/// The shared state between a [`Watchdog`] and its thread.
+#[derive(Debug, Default)]
+struct WatchdogState {
+    /// The time at which the script started running, or `None` if the script
+    /// is currently waiting for the engine.
+    busy_since: Mutex<Option<Instant>>,
+
+    /// Whether the watchdog has terminated the script.
+    tripped: AtomicBool,
+
+    /// Whether the watchdog thread should stop.
+    stopped: AtomicBool,
+}
+
+/// Terminates the script if it runs for longer than its time budget without
+/// waiting for the engine.
+///
+/// The watchdog thread stops when the last clone of the watchdog is dropped.
+#[derive(Debug, Clone)]
+pub(crate) struct Watchdog {
+    /// The shared watchdog state.
+    state: Arc<WatchdogState>,
+
+    /// Stops the watchdog thread when dropped.
+    _guard: Arc<WatchdogGuard>,
+}
+
+impl Watchdog {
+    /// Starts a new watchdog for the given isolate. The script is considered
+    /// to be running from this point on.
+    pub(crate) fn start(isolate: IsolateHandle, budget: Duration) -> std::io::Result<Self> {
+        let state = Arc::new(WatchdogState {
+            busy_since: Mutex::new(Some(Instant::now())),
+            ..Default::default()
+        });
+
+        let thread_state = state.clone();
+        let interval = (budget / 4).clamp(MIN_WATCHDOG_INTERVAL, WATCHDOG_INTERVAL);
+        std::thread::Builder::new()
+            .name("script_watchdog".to_string())
+            .spawn(move || {
+                while !thread_state.stopped.load(Ordering::Acquire) {
+                    std::thread::sleep(interval);
+
+                    let mut busy_since = thread_state.busy_since.lock().unwrap();
+                    if busy_since.is_some_and(|since| since.elapsed() >= budget) {
+                        *busy_since = None;
+                        thread_state.tripped.store(true, Ordering::Release);
+                        isolate.terminate_execution();
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            _guard: Arc::new(WatchdogGuard(state.clone())),
+            state,
+        })
+    }
+
+    /// Marks the script as running, starting its time budget.
+    pub(crate) fn resume(&self) {
+        *self.state.busy_since.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Marks the script as waiting for the engine, pausing its time budget.
+    pub(crate) fn pause(&self) {
+        *self.state.busy_since.lock().unwrap() = None;
+    }
+
+    /// Returns true if the watchdog has terminated the script.
+    pub(crate) fn tripped(&self) -> bool {
+        self.state.tripped.load(Ordering::Acquire)
+    }
+}
+
+/// Stops the watchdog thread when dropped.
+#[derive(Debug)]
+struct WatchdogGuard(Arc<WatchdogState>);
+
+impl Drop for WatchdogGuard {
+    fn drop(&mut self) {
+        self.0.stopped.store(true, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_limits_are_disabled() {
+        let limits = ScriptLimits::default()
+            .with_max_heap_mb(0)
+            .with_callback_budget_ms(0);
+        assert_eq!(limits, ScriptLimits::unlimited());
+
+        let limits = ScriptLimits {
+            max_heap_size: Some(0),
+            callback_budget: Some(Duration::ZERO),
+        };
+        assert_eq!(limits.heap_limit(), None);
+        assert_eq!(limits.budget(), None);
+    }
+
+    #[test]
+    fn limits_are_converted_from_settings() {
+        let limits = ScriptLimits::unlimited()
+            .with_max_heap_mb(64)
+            .with_callback_budget_ms(250);
+        assert_eq!(limits.heap_limit(), Some(64 * 1024 * 1024));
+        assert_eq!(limits.budget(), Some(Duration::from_millis(250)));
+    }
+}
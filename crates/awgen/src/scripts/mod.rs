@@ -2,25 +2,86 @@
 
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::thread::JoinHandle;
 
 use rustyscript::{Module, ModuleHandle, Runtime, RuntimeOptions, Undefined, json_args};
 use smol::channel::{Receiver, Sender, TryRecvError};
 
 mod api;
+mod background;
+mod codec;
+#[cfg(feature = "test_harness")]
+pub mod harness;
+mod libraries;
+mod limits;
 mod packet_in;
 mod packet_out;
 mod plugin;
+pub mod tick;
+mod typegen;
 
+pub use codec::{
+    COMPRESSION_THRESHOLD,
+    CodecError,
+    EncodedPacket,
+    FRAME_THRESHOLD,
+    PacketCodec,
+    PacketEncoding,
+    decode_frame,
+};
+pub use libraries::{LIBRARY_SCHEME, LibraryError, LibraryResolver};
+pub use limits::{ScriptLimit, ScriptLimits};
 pub use packet_in::PacketIn;
-pub use packet_out::PacketOut;
-pub use plugin::{ScriptEngine, ScriptEnginePlugin};
+pub use packet_out::{PacketOut, StampedPacket};
+pub(crate) use plugin::create_save;
+pub use plugin::{PACKET_QUEUE_DEPTH, ScriptEngine, ScriptEnginePlugin};
+pub use typegen::{TYPES_FILE, WRAPPERS_FILE, declarations, emit_types, wrappers};
 
 use crate::database::Database;
+use crate::scripts::limits::Watchdog;
+
+/// Settings for starting the script engine.
+#[derive(Debug, Clone)]
+pub struct ScriptEngineSettings {
+    /// The folder containing the `Main.ts` entry script.
+    pub folder: PathBuf,
+
+    /// The folder containing shared script libraries, which scripts may import
+    /// using the `lib:` scheme.
+    pub libraries: Option<PathBuf>,
+
+    /// The resource limits for the script engine.
+    pub limits: ScriptLimits,
+}
+
+impl ScriptEngineSettings {
+    /// Creates new settings for the scripts in the given folder, without any
+    /// shared libraries and with the default resource limits.
+    pub fn new(folder: PathBuf) -> Self {
+        Self {
+            folder,
+            libraries: None,
+            limits: ScriptLimits::default(),
+        }
+    }
+
+    /// Sets the folder containing shared script libraries.
+    pub fn with_libraries(mut self, libraries: PathBuf) -> Self {
+        self.libraries = Some(libraries);
+        self
+    }
+
+    /// Sets the resource limits for the script engine.
+    pub fn with_limits(mut self, limits: ScriptLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
 
 /// Spawns a new thread to run the script engine.
 pub fn start_script_engine(
-    folder: PathBuf,
+    settings: ScriptEngineSettings,
     database: Arc<Database>,
 ) -> Result<ScriptSockets, ScriptEngineError> {
     let (send_to_engine, get_from_client) = smol::channel::unbounded();
@@ -30,53 +91,65 @@ pub fn start_script_engine(
         .name("script_engine".to_string())
         .spawn(move || -> Result<(), ScriptEngineError> {
             let crash_handler = send_to_client.clone();
-            let (mut runtime, mod_handle) =
-                match prepare_script_engine(&folder, send_to_client, get_from_client, database) {
-                    Ok(a) => a,
-                    Err(err) => {
-                        crash_handler
-                            .send_blocking(PacketIn::Crashed {
-                                error: format!("{err}"),
-                            })
-                            .ok();
-                        return Err(err);
-                    }
-                };
-
-            match runtime.call_entrypoint::<Undefined>(&mod_handle, json_args!()) {
-                Ok(_) => {}
-                Err(err) => {
-                    crash_handler
-                        .send_blocking(PacketIn::Crashed {
-                            error: format!("{err}"),
-                        })
-                        .ok();
-                    return Err(err.into());
-                }
-            };
-
-            Ok(())
+            let mut watchdog = None;
+
+            let result = prepare_script_engine(
+                &settings,
+                send_to_client,
+                get_from_client,
+                database,
+                &mut watchdog,
+            )
+            .and_then(|(mut runtime, mod_handle)| {
+                runtime.call_entrypoint::<Undefined>(&mod_handle, json_args!())?;
+                Ok(())
+            });
+
+            if let Err(err) = &result {
+                let packet = crash_packet(err, watchdog.as_ref());
+                crash_handler.send_blocking(packet).ok();
+            }
+
+            result
         })?;
 
     Ok(ScriptSockets::new(thread, send_to_engine, get_from_engine))
 }
 
-/// Loads and prepares the script engine within the given script folder.
+/// Loads and prepares the script engine with the given settings.
 fn prepare_script_engine(
-    folder: &PathBuf,
+    settings: &ScriptEngineSettings,
     send_to_client: Sender<PacketIn>,
-    get_from_client: Receiver<PacketOut>,
+    get_from_client: Receiver<StampedPacket>,
     database: Arc<Database>,
+    watchdog: &mut Option<Watchdog>,
 ) -> Result<(Runtime, ModuleHandle), ScriptEngineError> {
+    let folder = &settings.folder;
     let index = Module::load(folder.join("Main.ts"))?;
 
+    let codec = PacketCodec::default();
+    let packets = api::extension(
+        Arc::new(get_from_client),
+        send_to_client.clone(),
+        codec.clone(),
+    );
+
+    let resolver = LibraryResolver::new(settings.libraries.clone());
     let mut runtime = Runtime::new(RuntimeOptions {
         default_entrypoint: Some("main".to_string()),
+        import_provider: Some(Box::new(resolver)),
+        max_heap_size: settings.limits.heap_limit(),
+        extensions: vec![packets],
         ..Default::default()
     })?;
 
-    let socket = Arc::new(get_from_client);
-    api::register(&mut runtime, socket, send_to_client, database)?;
+    if let Some(budget) = settings.limits.budget() {
+        let isolate = runtime.deno_runtime().v8_isolate().thread_safe_handle();
+        *watchdog = Some(Watchdog::start(isolate, budget)?);
+        api::attach_watchdog(&mut runtime, watchdog.clone());
+    }
+
+    api::register(&mut runtime, send_to_client, codec, database)?;
 
     let mod_handle = runtime.load_modules(&index, vec![])?;
     runtime.set_current_dir(folder)?;
@@ -84,6 +157,33 @@ fn prepare_script_engine(
     Ok((runtime, mod_handle))
 }
 
+/// Creates the packet that reports a script engine failure to the game.
+///
+/// Failures caused by the script exceeding one of its resource limits are
+/// reported with the limit that was hit.
+fn crash_packet(err: &ScriptEngineError, watchdog: Option<&Watchdog>) -> PacketIn {
+    let limit = if watchdog.is_some_and(Watchdog::tripped) {
+        Some(ScriptLimit::Time)
+    } else if matches!(
+        err,
+        ScriptEngineError::Runtime(rustyscript::Error::HeapExhausted)
+    ) {
+        Some(ScriptLimit::Memory)
+    } else {
+        None
+    };
+
+    match limit {
+        Some(limit) => PacketIn::LimitExceeded {
+            limit,
+            error: format!("{err}"),
+        },
+        None => PacketIn::Crashed {
+            error: format!("{err}"),
+        },
+    }
+}
+
 /// An error that can occur while loading, executing, or interacting with
 /// scripts.
 #[derive(Debug, thiserror::Error)]
@@ -112,26 +212,56 @@ pub struct ScriptSockets {
     thread: Option<JoinHandle<Result<(), ScriptEngineError>>>,
 
     /// The outgoing packets that can be sent to the script engine.
-    outgoing: Sender<PacketOut>,
+    outgoing: Sender<StampedPacket>,
 
     /// The incoming packets that can be received from the script engine.
     incoming: Receiver<PacketIn>,
+
+    /// The simulation tick that outgoing packets are stamped with, or 0
+    /// outside of fixed-tick mode.
+    tick: AtomicU64,
 }
 
 impl ScriptSockets {
     /// Creates a new `ScriptSockets` instance with the given thread handle.
     fn new(
         thread: JoinHandle<Result<(), ScriptEngineError>>,
-        outgoing: Sender<PacketOut>,
+        outgoing: Sender<StampedPacket>,
         incoming: Receiver<PacketIn>,
     ) -> Self {
         Self {
             thread: Some(thread),
             outgoing,
             incoming,
+            tick: AtomicU64::new(0),
         }
     }
 
+    /// Creates sockets that are not connected to a script engine, returning
+    /// them along with the engine side of the sockets.
+    ///
+    /// No runtime thread is spawned, so Bevy systems that consume the
+    /// [`ScriptEngine`] resource can be tested by injecting packets through the
+    /// returned [`FakeScriptEngine`].
+    #[cfg(feature = "test_harness")]
+    pub fn fake() -> (Self, FakeScriptEngine) {
+        let (send_to_engine, get_from_client) = smol::channel::unbounded();
+        let (send_to_client, get_from_engine) = smol::channel::unbounded();
+
+        let sockets = Self {
+            thread: None,
+            outgoing: send_to_engine,
+            incoming: get_from_engine,
+            tick: AtomicU64::new(0),
+        };
+        let engine = FakeScriptEngine {
+            outgoing: send_to_client,
+            incoming: get_from_client,
+        };
+
+        (sockets, engine)
+    }
+
     /// Joins the script engine thread, waiting for it to finish execution.
     /// Calling this method will drop the thread handle, so it should only be
     /// called once.
@@ -143,15 +273,42 @@ impl ScriptSockets {
         Ok(())
     }
 
-    /// Sends a packet to the script engine.
+    /// Returns true if the script engine thread has not finished execution.
+    pub fn is_running(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Sends a packet to the script engine, stamped with the current
+    /// simulation tick.
     ///
     /// Returns an error if the packet cannot be sent.
     pub fn send(&self, packet: PacketOut) -> Result<(), ScriptEngineError> {
+        let tick = match packet {
+            PacketOut::Tick { .. } => None,
+            _ => self.tick(),
+        };
+
         self.outgoing
-            .send_blocking(packet)
+            .send_blocking(StampedPacket { tick, packet })
             .map_err(|_| ScriptEngineError::SocketClosed)
     }
 
+    /// Gets the simulation tick that outgoing packets are stamped with, or
+    /// `None` outside of fixed-tick mode.
+    pub fn tick(&self) -> Option<u64> {
+        match self.tick.load(Ordering::Relaxed) {
+            0 => None,
+            tick => Some(tick),
+        }
+    }
+
+    /// Sets the simulation tick that outgoing packets are stamped with.
+    pub(crate) fn set_tick(&self, tick: u64) {
+        self.tick.store(tick, Ordering::Relaxed);
+    }
+
     /// Receives a packet from the script engine, if available.
     ///
     /// Returns `Ok(None)` if no packet is available, or an error if the socket
@@ -164,6 +321,12 @@ impl ScriptSockets {
         }
     }
 
+    /// Returns the number of packets from the script engine that are waiting
+    /// to be received.
+    pub fn queued(&self) -> usize {
+        self.incoming.len()
+    }
+
     /// Receives a packet from the script engine, blocking until a packet is
     /// available or the socket is closed.
     ///
@@ -185,3 +348,52 @@ impl ScriptSockets {
         self.join()
     }
 }
+
+/// The script engine side of the sockets created by [`ScriptSockets::fake`],
+/// which stands in for a running script engine.
+#[cfg(feature = "test_harness")]
+pub struct FakeScriptEngine {
+    /// The packets sent to the game, as if by the script engine.
+    outgoing: Sender<PacketIn>,
+
+    /// The packets sent by the game to the script engine.
+    incoming: Receiver<StampedPacket>,
+}
+
+#[cfg(feature = "test_harness")]
+impl FakeScriptEngine {
+    /// Sends a packet to the game, as if it had been sent by the script engine.
+    ///
+    /// Returns an error if the game side of the sockets has been dropped.
+    pub fn send(&self, packet: PacketIn) -> Result<(), ScriptEngineError> {
+        self.outgoing
+            .try_send(packet)
+            .map_err(|_| ScriptEngineError::SocketClosed)
+    }
+
+    /// Receives a packet sent by the game, if available.
+    ///
+    /// Returns `Ok(None)` if no packet is available, or an error if the game
+    /// side of the sockets has been dropped.
+    pub fn recv(&self) -> Result<Option<PacketOut>, ScriptEngineError> {
+        match self.incoming.try_recv() {
+            Ok(stamped) => Ok(Some(stamped.packet)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Closed) => Err(ScriptEngineError::SocketClosed),
+        }
+    }
+
+    /// Receives every packet that the game has sent so far.
+    pub fn drain(&self) -> Vec<PacketOut> {
+        self.drain_stamped()
+            .into_iter()
+            .map(|stamped| stamped.packet)
+            .collect()
+    }
+
+    /// Receives every packet that the game has sent so far, along with the
+    /// tick each was stamped with.
+    pub fn drain_stamped(&self) -> Vec<StampedPacket> {
+        std::iter::from_fn(|| self.incoming.try_recv().ok()).collect()
+    }
+}
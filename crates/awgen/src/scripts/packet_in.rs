@@ -6,12 +6,20 @@
 //! be used. These will cause serde to fail to serialize the enum.
 
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
-use crate::map::{BlockModel, WorldPos};
+use crate::database::areas::Area;
+use crate::database::bookmarks::Bookmark;
+use crate::map::{BillboardMode, BlockModel, BlockRegion, ChunkPos, MapPatch, WorldPos};
+#[cfg(feature = "networking")]
+use crate::net::NetMessage;
+use crate::scripts::{PacketEncoding, ScriptLimit};
+use crate::ux::file_picker::FileFilter;
+use crate::ux::{AtmosphereSettings, CameraProjection, PerformanceBudgets};
 
 /// The `PacketIn` enum, which is used to represent different types of
 /// incoming packets that may be received from the script engine.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(
     tag = "type",
     rename_all = "camelCase",
@@ -21,14 +29,21 @@ use crate::map::{BlockModel, WorldPos};
 pub enum PacketIn {
     /// A packet that contains the initial game state settings, which is sent
     /// when the script engine starts up. This packet must always be the first
-    /// packet sent by the script engine. Subsequent packets of this type are
-    /// ignored.
+    /// packet sent by the script engine. A subsequent packet of this type
+    /// means the scripts were reloaded, and removes the commands registered by
+    /// the previous scripts.
     Init {
         /// The name of the game.
         name: String,
 
         /// The game version.
         version: String,
+
+        /// The encoding to use for large packets from now on. Defaults to
+        /// JSON.
+        #[serde(default)]
+        #[ts(optional)]
+        encoding: Option<PacketEncoding>,
     },
 
     /// A packet that contains a collection of packets from the
@@ -51,6 +66,19 @@ pub enum PacketIn {
         error: String,
     },
 
+    /// A packet that indicates that the script engine was stopped because a
+    /// script exceeded one of its resource limits.
+    ///
+    /// Like [`PacketIn::Crashed`], this packet is generated automatically and
+    /// should never be sent by scripts.
+    LimitExceeded {
+        /// The resource limit that was exceeded.
+        limit: ScriptLimit,
+
+        /// The error message associated with the failure.
+        error: String,
+    },
+
     /// Import an asset file into the project directory.
     ImportAsset {
         /// The OS filepath of the asset file to import.
@@ -60,6 +88,50 @@ pub enum PacketIn {
         asset_path: String,
     },
 
+    /// Opens a native file dialog, so that the user can choose a file to
+    /// import. The engine responds with a [`PacketOut::FileDialogClosed`]
+    /// packet once the dialog is closed.
+    ///
+    /// File dialogs can only be opened in editor mode.
+    ///
+    /// [`PacketOut::FileDialogClosed`]: crate::scripts::PacketOut::FileDialogClosed
+    RequestFileDialog {
+        /// A script-defined ID that is sent back with the result.
+        id: String,
+
+        /// The title of the dialog window.
+        #[serde(default)]
+        #[ts(optional)]
+        title: Option<String>,
+
+        /// The filters limiting which files may be chosen. If empty, all files
+        /// are shown.
+        #[serde(default)]
+        #[ts(optional)]
+        filters: Vec<FileFilter>,
+    },
+
+    /// Subscribes to changes of the project assets at or within the given
+    /// asset path, such as `project://tiles/` for every asset in a folder.
+    /// The engine sends a [`PacketOut::AssetChanged`] packet whenever a
+    /// matching asset record is added, modified, or removed.
+    ///
+    /// Assets can only be watched in editor mode, and every subscription is
+    /// dropped when leaving the editor.
+    ///
+    /// [`PacketOut::AssetChanged`]: crate::scripts::PacketOut::AssetChanged
+    WatchAssets {
+        /// The asset path to watch, starting with `project://`.
+        path: String,
+    },
+
+    /// Unsubscribes from changes of the given asset path, which must match
+    /// the path given in the `WatchAssets` packet.
+    UnwatchAssets {
+        /// The asset path to stop watching.
+        path: String,
+    },
+
     /// Creates a new tileset from a list of tile asset paths.
     ///
     /// This packet will fail if the tiles cannot be loaded or if they are not
@@ -70,6 +142,12 @@ pub enum PacketIn {
 
         /// The output asset path for the tileset.
         output_path: String,
+
+        /// If true, mipmaps are generated with a faster filter that averages
+        /// raw sRGB values, rather than the default gamma-correct filter.
+        #[serde(default)]
+        #[ts(optional)]
+        fast_mipmaps: bool,
     },
 
     /// Sets the tilesets currently in use for the world.
@@ -86,4 +164,387 @@ pub enum PacketIn {
         /// The block model.
         model: Box<BlockModel>,
     },
+
+    /// Requests the block model at the specified world position. The engine
+    /// responds with a [`PacketOut::Block`] packet.
+    ///
+    /// [`PacketOut::Block`]: crate::scripts::PacketOut::Block
+    GetBlock {
+        /// The world position.
+        pos: WorldPos,
+    },
+
+    /// Casts a ray through the blocks of the map. The engine responds with a
+    /// [`PacketOut::RaycastResult`] packet holding the first non-empty block
+    /// that the ray enters, if any.
+    ///
+    /// [`PacketOut::RaycastResult`]: crate::scripts::PacketOut::RaycastResult
+    RaycastBlocks {
+        /// The world position that the ray starts from. A block the ray starts
+        /// inside of is never hit.
+        origin: [f32; 3],
+
+        /// The direction of the ray. It does not need to be normalized, but
+        /// must not be zero.
+        dir: [f32; 3],
+
+        /// The maximum distance, in blocks, that the ray travels. It must be
+        /// between 0 and 65536.
+        max_dist: f32,
+    },
+
+    /// Requests a summary of the chunk at the specified chunk position. The
+    /// engine responds with a [`PacketOut::ChunkSummary`] packet.
+    ///
+    /// [`PacketOut::ChunkSummary`]: crate::scripts::PacketOut::ChunkSummary
+    GetChunkSummary {
+        /// The chunk position.
+        pos: ChunkPos,
+    },
+
+    /// Finishes a tick in fixed-tick mode. All packets sent since the tick
+    /// started are applied together once this packet is received.
+    TickDone {
+        /// The tick that was finished.
+        #[ts(type = "number")]
+        tick: u64,
+    },
+
+    /// Requests a list of all save slots. The engine responds with a
+    /// [`PacketOut::SaveList`] packet.
+    ///
+    /// [`PacketOut::SaveList`]: crate::scripts::PacketOut::SaveList
+    ListSaves,
+
+    /// Saves the current map state, along with the given data, to a save slot.
+    /// Any existing save in the slot is replaced.
+    CreateSave {
+        /// The name of the save slot.
+        slot: String,
+
+        /// Arbitrary data to store with the save.
+        data: serde_json::Value,
+    },
+
+    /// Replaces the current map state with the state stored in a save slot.
+    /// The engine responds with the stored data in a
+    /// [`PacketOut::SaveLoaded`] packet.
+    ///
+    /// [`PacketOut::SaveLoaded`]: crate::scripts::PacketOut::SaveLoaded
+    LoadSave {
+        /// The name of the save slot.
+        slot: String,
+    },
+
+    /// Deletes a save slot.
+    DeleteSave {
+        /// The name of the save slot.
+        slot: String,
+    },
+
+    /// Prints a line of text to the player's chat console.
+    ConsolePrint {
+        /// The text to print.
+        text: String,
+    },
+
+    /// Spawns a billboard sprite in the world. If a sprite with the same ID
+    /// already exists, it is replaced.
+    SpawnSprite {
+        /// The script-defined ID of the sprite.
+        id: String,
+
+        /// The asset path of the sprite texture, such as
+        /// `"game://sprites/player.png"`.
+        texture: String,
+
+        /// The world position of the bottom center of the sprite.
+        position: [f32; 3],
+
+        /// The width and height of the sprite, in blocks.
+        size: [f32; 2],
+
+        /// How the sprite is oriented towards the camera. Defaults to facing
+        /// the camera directly.
+        #[serde(default)]
+        #[ts(optional)]
+        mode: Option<BillboardMode>,
+
+        /// Whether clicking the sprite sends a [`PacketOut::EntityClicked`]
+        /// packet. Defaults to false.
+        ///
+        /// [`PacketOut::EntityClicked`]: crate::scripts::PacketOut::EntityClicked
+        #[serde(default)]
+        #[ts(optional)]
+        clickable: Option<bool>,
+
+        /// Whether the cursor entering or leaving the sprite sends a
+        /// [`PacketOut::EntityHoverChanged`] packet. Defaults to false.
+        ///
+        /// [`PacketOut::EntityHoverChanged`]: crate::scripts::PacketOut::EntityHoverChanged
+        #[serde(default)]
+        #[ts(optional)]
+        hoverable: Option<bool>,
+    },
+
+    /// Updates an existing billboard sprite. Omitted fields are left
+    /// unchanged.
+    UpdateSprite {
+        /// The script-defined ID of the sprite.
+        id: String,
+
+        /// The asset path of the new sprite texture.
+        #[serde(default)]
+        #[ts(optional)]
+        texture: Option<String>,
+
+        /// The new world position of the bottom center of the sprite.
+        #[serde(default)]
+        #[ts(optional)]
+        position: Option<[f32; 3]>,
+
+        /// The new width and height of the sprite, in blocks.
+        #[serde(default)]
+        #[ts(optional)]
+        size: Option<[f32; 2]>,
+
+        /// The new orientation mode of the sprite.
+        #[serde(default)]
+        #[ts(optional)]
+        mode: Option<BillboardMode>,
+
+        /// Whether clicking the sprite is reported to the script engine.
+        #[serde(default)]
+        #[ts(optional)]
+        clickable: Option<bool>,
+
+        /// Whether hovering over the sprite is reported to the script engine.
+        #[serde(default)]
+        #[ts(optional)]
+        hoverable: Option<bool>,
+    },
+
+    /// Removes a billboard sprite from the world.
+    DespawnSprite {
+        /// The script-defined ID of the sprite.
+        id: String,
+    },
+
+    /// Captures a snapshot of the map, which may later be compared against
+    /// another snapshot. If a snapshot with the same name already exists, it is
+    /// replaced. Snapshots are only kept for the current session.
+    TakeSnapshot {
+        /// The name of the snapshot.
+        name: String,
+
+        /// The region of the map to capture. If omitted, the whole map is
+        /// captured.
+        #[serde(default)]
+        #[ts(optional)]
+        region: Option<BlockRegion>,
+    },
+
+    /// Removes a stored map snapshot.
+    DropSnapshot {
+        /// The name of the snapshot.
+        name: String,
+    },
+
+    /// Compares two map snapshots, replying with a patch that transforms the
+    /// first into the second.
+    DiffSnapshots {
+        /// The name of the snapshot to compare from.
+        from: String,
+
+        /// The name of the snapshot to compare to. If omitted, the current
+        /// state of the map is used.
+        #[serde(default)]
+        #[ts(optional)]
+        to: Option<String>,
+    },
+
+    /// Applies a map patch, setting each block it contains.
+    ApplyPatch {
+        /// The patch to apply.
+        patch: MapPatch,
+    },
+
+    /// Requests the list of bookmarks stored in the game database.
+    ListBookmarks,
+
+    /// Stores a bookmark in the game database, replacing any existing bookmark
+    /// with the same name.
+    SetBookmark {
+        /// The bookmark to store.
+        bookmark: Bookmark,
+    },
+
+    /// Deletes a bookmark from the game database.
+    DeleteBookmark {
+        /// The name of the bookmark.
+        name: String,
+    },
+
+    /// Moves the camera to a bookmark stored in the game database.
+    JumpToBookmark {
+        /// The name of the bookmark.
+        name: String,
+    },
+
+    /// Requests the list of block models stored in the project database.
+    ListBlockModels,
+
+    /// Sets the block at the given position to a block model stored in the
+    /// game database.
+    PlaceBlockModel {
+        /// The position of the block.
+        pos: WorldPos,
+
+        /// The name of the block model.
+        name: String,
+    },
+
+    /// Enables or disables camera collision, which pulls the camera in front
+    /// of terrain that blocks its view.
+    SetCameraCollision {
+        /// Whether camera collision is enabled.
+        enabled: bool,
+    },
+
+    /// Switches the camera between an orthographic and a perspective
+    /// projection, keeping the same focus point and framing.
+    SetCameraProjection {
+        /// The projection to use.
+        projection: CameraProjection,
+    },
+
+    /// Replaces the distance fog and the sky gradient of the terrain view until
+    /// the game is closed. The settings stored in the project are not changed.
+    SetAtmosphere {
+        /// The new atmosphere settings.
+        atmosphere: AtmosphereSettings,
+    },
+
+    /// Sets the frame rate limits of the game window. The limits are stored in
+    /// the game database, so they persist between launches.
+    SetFrameRate {
+        /// The maximum frame rate while the window is focused. If not
+        /// provided, the frame rate is not limited.
+        #[serde(default)]
+        #[ts(optional)]
+        target_fps: Option<f64>,
+
+        /// The maximum frame rate while the window is unfocused. If not
+        /// provided, the current limit is kept.
+        #[serde(default)]
+        #[ts(optional)]
+        unfocused_fps: Option<f64>,
+    },
+
+    /// Sets the performance budgets that the editor warns about when they are
+    /// exceeded. The budgets are stored in the game database, so they persist
+    /// between launches.
+    SetPerformanceBudgets {
+        /// The new performance budgets.
+        budgets: PerformanceBudgets,
+    },
+
+    /// Replaces the log filter, using the same directive syntax as `RUST_LOG`,
+    /// such as `info,script=debug`. The filter is stored in the game database,
+    /// so it persists between launches.
+    SetLogFilter {
+        /// The filter directives.
+        filter: String,
+    },
+
+    /// Requests the list of areas stored in the game database.
+    ListAreas,
+
+    /// Stores an area in the game database, replacing any existing area with
+    /// the same name.
+    SetArea {
+        /// The area to store.
+        area: Area,
+    },
+
+    /// Deletes an area from the game database.
+    DeleteArea {
+        /// The name of the area.
+        name: String,
+    },
+
+    /// Requests the areas that contain the given block.
+    GetAreasAt {
+        /// The position of the block.
+        pos: WorldPos,
+    },
+
+    /// Requests the list of lights placed in the world.
+    ListLights,
+
+    /// Switches a placed light on or off in the running scene. The change is
+    /// not stored in the game database.
+    SetLightEnabled {
+        /// The name of the light.
+        name: String,
+
+        /// Whether the light should be switched on.
+        enabled: bool,
+    },
+
+    /// Registers a command in the editor command palette, replacing any
+    /// command with the same ID registered by scripts. A command invoked packet
+    /// is sent when the user runs the command.
+    RegisterCommand {
+        /// The ID of the command.
+        id: String,
+
+        /// The name of the command shown in the command palette.
+        name: String,
+
+        /// A description of the keyboard shortcut of the command, shown in the
+        /// command palette.
+        #[serde(default)]
+        #[ts(optional)]
+        shortcut: Option<String>,
+    },
+
+    /// Removes a command registered by scripts from the editor command palette.
+    UnregisterCommand {
+        /// The ID of the command.
+        id: String,
+    },
+
+    /// Hosts a network session on the given port.
+    #[cfg(feature = "networking")]
+    HostGame {
+        /// The TCP port to listen on.
+        port: u16,
+    },
+
+    /// Joins the network session hosted at the given address.
+    #[cfg(feature = "networking")]
+    JoinGame {
+        /// The address of the host, such as `"127.0.0.1:7777"`.
+        address: String,
+    },
+
+    /// Leaves the current network session.
+    #[cfg(feature = "networking")]
+    LeaveGame,
+
+    /// Sends a message to other peers in the current network session.
+    ///
+    /// The host may send a message to a single client, or to all clients if
+    /// `to` is omitted. Clients may only send messages to the host.
+    #[cfg(feature = "networking")]
+    NetSend {
+        /// The message to send.
+        message: NetMessage,
+
+        /// The peer ID to send the message to.
+        #[serde(default)]
+        #[ts(optional)]
+        to: Option<u32>,
+    },
 }
@@ -5,10 +5,22 @@
 //! be used. These will cause serde to fail to serialize the enum.
 
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::database::areas::Area;
+use crate::database::block_models::NamedBlockModel;
+use crate::database::bookmarks::Bookmark;
+use crate::database::lights::PlacedLight;
+use crate::database::savegame::SaveInfo;
+use crate::map::{BlockHit, BlockModel, ChunkPos, MapPatch, WorldPos};
+#[cfg(feature = "networking")]
+use crate::net::NetMessage;
+use crate::ux::ClickButton;
+use crate::ux::asset_watch::AssetChangeKind;
 
 /// The `PacketOut` enum, which is used to represent different types of
 /// outgoing packets that may be sent to the script engine.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(
     tag = "type",
     rename_all = "camelCase",
@@ -16,7 +28,9 @@ use serde::{Deserialize, Serialize};
     deny_unknown_fields
 )]
 pub enum PacketOut {
-    /// A packet to request the script engine to shut down.
+    /// A packet to request the script engine to shut down. The script engine
+    /// acknowledges with its own `Shutdown` packet once it has finished
+    /// cleaning up.
     Shutdown,
 
     /// This packet is used to notify the script engine that a file has been
@@ -25,4 +39,319 @@ pub enum PacketOut {
         /// The file path of the dropped file.
         path: String,
     },
+
+    /// Sent when a file dialog requested by the script engine has been closed.
+    FileDialogClosed {
+        /// The ID given in the request.
+        id: String,
+
+        /// The OS file path of the chosen file, or `None` if the dialog was
+        /// cancelled or could not be opened.
+        path: Option<String>,
+    },
+
+    /// Sent when a project asset matching a path the script engine watches is
+    /// added, modified, or removed.
+    AssetChanged {
+        /// The asset path of the changed asset, such as
+        /// `project://tiles/grass.png`.
+        id: String,
+
+        /// How the asset changed.
+        kind: AssetChangeKind,
+    },
+
+    /// Starts a new tick in fixed-tick mode. The script engine must respond
+    /// with a `TickDone` packet once it has finished processing the tick.
+    Tick {
+        /// The tick number, starting at 1 and increasing by one each tick.
+        #[ts(type = "number")]
+        tick: u64,
+    },
+
+    /// The list of save slots, sent in response to a `ListSaves` packet.
+    SaveList {
+        /// The available save slots, sorted by name.
+        saves: Vec<SaveInfo>,
+    },
+
+    /// Sent when a save slot has been written successfully.
+    SaveCreated {
+        /// The name of the save slot.
+        slot: String,
+    },
+
+    /// Sent when a save slot has been loaded successfully.
+    SaveLoaded {
+        /// The name of the save slot.
+        slot: String,
+
+        /// The data that was stored with the save.
+        data: serde_json::Value,
+    },
+
+    /// Sent when a save slot has been deleted successfully.
+    SaveDeleted {
+        /// The name of the save slot.
+        slot: String,
+    },
+
+    /// Sent when a save operation has failed.
+    SaveFailed {
+        /// The name of the save slot.
+        slot: String,
+
+        /// The reason the operation failed.
+        error: String,
+    },
+
+    /// The result of comparing two map snapshots.
+    MapDiff {
+        /// The name of the snapshot compared from.
+        from: String,
+
+        /// The name of the snapshot compared to, or `None` if the snapshot was
+        /// compared to the current state of the map.
+        to: Option<String>,
+
+        /// The patch that transforms the first snapshot into the second.
+        patch: MapPatch,
+    },
+
+    /// Sent when a requested map snapshot does not exist.
+    SnapshotNotFound {
+        /// The name of the snapshot.
+        name: String,
+    },
+
+    /// The bookmarks stored in the game database, sorted by name. This is sent
+    /// in response to a list bookmarks request, and after a bookmark is stored
+    /// or deleted.
+    BookmarkList {
+        /// The stored bookmarks.
+        bookmarks: Vec<Bookmark>,
+    },
+
+    /// Sent when a bookmark operation has failed.
+    BookmarkFailed {
+        /// The name of the bookmark.
+        name: String,
+
+        /// A description of the error.
+        error: String,
+    },
+
+    /// The block models stored in the project database, sorted by name. This is
+    /// sent in response to a list block models request.
+    BlockModelList {
+        /// The stored block models.
+        models: Vec<NamedBlockModel>,
+    },
+
+    /// Sent when a block model operation has failed.
+    BlockModelFailed {
+        /// The name of the block model.
+        name: String,
+
+        /// A description of the error.
+        error: String,
+    },
+
+    /// The areas stored in the game database, sorted by name. This is sent in
+    /// response to a list areas request, and after an area is stored or
+    /// deleted.
+    AreaList {
+        /// The stored areas.
+        areas: Vec<Area>,
+    },
+
+    /// The areas that contain a block, sorted by name.
+    AreasAt {
+        /// The position of the block.
+        pos: WorldPos,
+
+        /// The areas containing the block.
+        areas: Vec<Area>,
+    },
+
+    /// Sent when an area operation has failed.
+    AreaFailed {
+        /// The name of the area.
+        name: String,
+
+        /// A description of the error.
+        error: String,
+    },
+
+    /// The lights placed in the world, sorted by name. This is sent in response
+    /// to a list lights request, and after a light is switched on or off.
+    LightList {
+        /// The placed lights.
+        lights: Vec<PlacedLight>,
+    },
+
+    /// Sent when a light operation has failed.
+    LightFailed {
+        /// The name of the light.
+        name: String,
+
+        /// A description of the error.
+        error: String,
+    },
+
+    /// The block model at a world position, sent in response to a get block
+    /// request. Blocks in chunks that do not exist are empty.
+    Block {
+        /// The world position.
+        pos: WorldPos,
+
+        /// The block model.
+        model: Box<BlockModel>,
+    },
+
+    /// The result of a ray cast, sent in response to a raycast blocks request.
+    RaycastResult {
+        /// The world position that the ray started from.
+        origin: [f32; 3],
+
+        /// The direction of the ray.
+        dir: [f32; 3],
+
+        /// The first non-empty block that the ray entered, or `None` if it
+        /// did not hit any block within its maximum distance.
+        hit: Option<BlockHit>,
+    },
+
+    /// A summary of a chunk, sent in response to a get chunk summary request.
+    ChunkSummary {
+        /// The chunk position.
+        pos: ChunkPos,
+
+        /// The world position of the block at the minimum corner of the chunk.
+        origin: WorldPos,
+
+        /// The number of blocks along each axis of the chunk.
+        size: u32,
+
+        /// Whether the chunk exists. Chunks are created when the first block
+        /// is placed in them, and are otherwise empty.
+        loaded: bool,
+
+        /// The number of non-empty blocks in the chunk.
+        solid_blocks: u32,
+    },
+
+    /// Sent when the player submits a line of text in the chat console.
+    ConsoleCommand {
+        /// The submitted text.
+        text: String,
+    },
+
+    /// Sent when the user runs a command registered by scripts from the editor
+    /// command palette.
+    CommandInvoked {
+        /// The ID of the command.
+        id: String,
+    },
+
+    /// Sent when the user deletes a sprite from the editor scene outliner.
+    SpriteDeleted {
+        /// The ID of the deleted sprite.
+        id: String,
+    },
+
+    /// Sent when the user duplicates a sprite from the editor scene outliner.
+    SpriteDuplicated {
+        /// The ID of the duplicated sprite.
+        source: String,
+
+        /// The ID of the new sprite.
+        id: String,
+    },
+
+    /// Sent when the player clicks a sprite that is clickable.
+    EntityClicked {
+        /// The ID of the clicked sprite.
+        id: String,
+
+        /// The mouse button that was pressed.
+        button: ClickButton,
+
+        /// The world position on the sprite that was clicked.
+        hit_pos: [f32; 3],
+    },
+
+    /// Sent when the cursor starts or stops hovering over a sprite that is
+    /// hoverable.
+    EntityHoverChanged {
+        /// The ID of the sprite.
+        id: String,
+
+        /// Whether the cursor is now hovering over the sprite.
+        hovered: bool,
+    },
+
+    /// Sent to a client when it has joined a network session.
+    #[cfg(feature = "networking")]
+    NetJoined {
+        /// The peer ID assigned to this client.
+        peer: u32,
+    },
+
+    /// Sent to the host when a client has connected.
+    #[cfg(feature = "networking")]
+    PeerConnected {
+        /// The peer ID of the client.
+        peer: u32,
+    },
+
+    /// Sent to the host when a client has disconnected.
+    #[cfg(feature = "networking")]
+    PeerDisconnected {
+        /// The peer ID of the client.
+        peer: u32,
+    },
+
+    /// A message received from another peer. Messages from the host have a
+    /// peer ID of 0.
+    #[cfg(feature = "networking")]
+    NetReceived {
+        /// The peer ID of the sender.
+        from: u32,
+
+        /// The message.
+        message: NetMessage,
+    },
+
+    /// Sent to a client when its connection to the host was closed.
+    #[cfg(feature = "networking")]
+    NetClosed {
+        /// The reason the connection was closed, if it was closed due to an
+        /// error.
+        error: Option<String>,
+    },
+
+    /// Sent when a networking operation has failed.
+    #[cfg(feature = "networking")]
+    NetError {
+        /// The reason the operation failed.
+        error: String,
+    },
+}
+
+/// A [`PacketOut`] as it is sent to the script engine, stamped with the
+/// simulation tick that was active when it was sent.
+///
+/// The tick is serialized as a `tick` field next to the fields of the packet,
+/// and is omitted outside of fixed-tick mode. [`PacketOut::Tick`] packets are
+/// never stamped, as they carry their own tick.
+#[derive(Debug, Clone, Serialize)]
+pub struct StampedPacket {
+    /// The tick that was active when the packet was sent, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tick: Option<u64>,
+
+    /// The packet.
+    #[serde(flatten)]
+    pub packet: PacketOut,
 }
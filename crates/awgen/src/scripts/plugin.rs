@@ -4,15 +4,54 @@
 use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
+use awgen_asset_db::loaders::MipmapQuality;
+use awgen_asset_db::param::AwgenAssets;
+use awgen_ui::widgets::command_palette::{CommandRegistry, PaletteCommand};
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy::ecs::system::SystemState;
 use bevy::prelude::*;
-use bevy::tasks::AsyncComputeTaskPool;
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::app::ProjectSettings;
-use crate::map::{ChunkTable, VoxelChunk};
-use crate::scripts::{PacketIn, ScriptSockets};
-use crate::tiles::{ActiveTilesets, GeneratingTilesets, TilesetMaterial};
+use crate::app::{AwgenState, ProjectSettings};
+use crate::database::areas::Area;
+use crate::database::block_models::BlockModelAssets;
+use crate::database::savegame::{self, SaveGame};
+use crate::database::{DatabaseError, GameDatabase, ProjectAssets};
+use crate::framepace::FramePacing;
+use crate::jobs::BackgroundJobs;
+use crate::logging::LogFilter;
+use crate::map::{
+    AreaIndex,
+    BlockModel,
+    BlockRegion,
+    CHUNK_SIZE,
+    ChunkTable,
+    LightTable,
+    MAX_RAYCAST_DISTANCE,
+    MapSnapshot,
+    MapSnapshots,
+    SceneLight,
+    VoxelChunk,
+    get_block,
+    raycast_blocks,
+    set_block,
+};
+use crate::scripts::background::{self, ScriptJobsPlugin};
+use crate::scripts::tick::{SimulationTick, advance_tick};
+use crate::scripts::{PacketIn, PacketOut, ScriptSockets};
+use crate::telemetry::{PACKETS_PROCESSED, UsageCounters};
+use crate::tiles::builder::create_tileset_with_progress;
+use crate::tiles::{ActiveTilesets, GeneratingTilesets, TilesetBuilt, TilesetMaterial};
+use crate::ux::asset_watch::AssetWatch;
+use crate::ux::file_picker::{self, PendingFileDialogs};
+use crate::ux::{CameraController, SCRIPT_COMMAND_PREFIX};
+
+/// The name of the packet queue depth diagnostic, which measures the number of
+/// packets from the script engine waiting to be processed at the start of each
+/// frame.
+pub const PACKET_QUEUE_DEPTH: DiagnosticPath =
+    DiagnosticPath::const_new("scripts/packet_queue_depth");
 
 lazy_static! {
     static ref ASSET_PATH_REGEX: Regex =
@@ -23,6 +62,10 @@ lazy_static! {
 pub struct ScriptEnginePlugin {
     /// The sockets used to communicate with the script engine.
     script_sockets: RwLock<Option<ScriptSockets>>,
+
+    /// The tick rate, in ticks per second, if packets should be processed in
+    /// fixed-tick mode.
+    tick_rate: Option<f64>,
 }
 
 impl ScriptEnginePlugin {
@@ -30,8 +73,18 @@ impl ScriptEnginePlugin {
     pub fn new(script_sockets: ScriptSockets) -> Self {
         Self {
             script_sockets: RwLock::new(Some(script_sockets)),
+            tick_rate: None,
         }
     }
+
+    /// Processes packets in fixed-tick mode at the given tick rate, in ticks
+    /// per second, instead of once per frame.
+    ///
+    /// See the [`tick`](crate::scripts::tick) module for details.
+    pub fn with_fixed_tick(mut self, tick_rate: f64) -> Self {
+        self.tick_rate = Some(tick_rate);
+        self
+    }
 }
 
 impl Plugin for ScriptEnginePlugin {
@@ -39,8 +92,21 @@ impl Plugin for ScriptEnginePlugin {
         let sockets = self.script_sockets.write().unwrap().take().unwrap();
 
         app_.insert_resource(ScriptEngine(sockets))
-            .add_systems(PreUpdate, recv)
+            .add_plugins(ScriptJobsPlugin)
+            .register_diagnostic(Diagnostic::new(PACKET_QUEUE_DEPTH))
+            .add_systems(PreUpdate, measure_queue_depth)
             .add_systems(Last, cleanup);
+
+        match self.tick_rate {
+            Some(tick_rate) => {
+                app_.insert_resource(Time::<Fixed>::from_hz(tick_rate))
+                    .init_resource::<SimulationTick>()
+                    .add_systems(FixedUpdate, advance_tick);
+            }
+            None => {
+                app_.add_systems(PreUpdate, recv.after(measure_queue_depth));
+            }
+        }
     }
 }
 
@@ -49,6 +115,12 @@ impl Plugin for ScriptEnginePlugin {
 #[derive(Resource, Deref, DerefMut)]
 pub struct ScriptEngine(ScriptSockets);
 
+/// Measures the number of packets waiting to be processed, before they are
+/// received.
+fn measure_queue_depth(engine: Res<ScriptEngine>, mut diagnostics: Diagnostics) {
+    diagnostics.add_measurement(&PACKET_QUEUE_DEPTH, || engine.queued() as f64);
+}
+
 /// A Bevy system that receives packets from the script engine, if any, and
 /// processes them.
 #[allow(clippy::type_complexity)]
@@ -73,11 +145,21 @@ fn cleanup(mut app_exit: ResMut<Messages<AppExit>>, mut sockets: ResMut<ScriptEn
 }
 
 /// Handles incoming packets from the script engine.
-fn handle(world: &mut World, packet: PacketIn) -> Result<(), ()> {
+pub(crate) fn handle(world: &mut World, packet: PacketIn) -> Result<(), ()> {
+    if !matches!(packet, PacketIn::Set { .. }) {
+        count_usage(world, PACKETS_PROCESSED);
+    }
+
     match packet {
         PacketIn::Init { .. } => {
-            warn!(
-                "Received init packet, but this should only be sent by the script engine on startup."
+            // The scripts were reloaded, so the commands registered by the
+            // previous scripts no longer have a script to report them to.
+            let removed = world
+                .get_resource_or_init::<CommandRegistry>()
+                .unregister_prefix(SCRIPT_COMMAND_PREFIX);
+            info!(
+                "The scripts were reloaded, removed {} script commands",
+                removed
             );
         }
         PacketIn::Set { packets } => {
@@ -94,27 +176,77 @@ fn handle(world: &mut World, packet: PacketIn) -> Result<(), ()> {
             error!("The script engine has crashed: {}", error);
             world.write_message(AppExit::from_code(1));
         }
+        PacketIn::LimitExceeded { limit, error } => {
+            error!(
+                "The script engine exceeded its {:?} limit: {}",
+                limit, error
+            );
+            world.write_message(AppExit::from_code(1));
+        }
+        PacketIn::TickDone { tick } => {
+            warn!(
+                "Received tick done packet for tick {}, but fixed-tick mode is not enabled.",
+                tick
+            );
+        }
         PacketIn::ImportAsset { file, asset_path } => {
             info!("Importing file \"{}\" as \"{}\"", file, asset_path);
 
             let project_folder = world.resource::<ProjectSettings>().project_folder();
             let dest_path = parse_asset_path(project_folder, &asset_path)?;
 
-            if let Err(err) = std::fs::copy(&file, &dest_path) {
-                error!(
-                    "Failed to copy asset file from {} to {}: {}",
-                    file,
-                    dest_path.display(),
-                    err
-                );
+            if let Err(err) = background::import_asset(world, file.clone(), dest_path, asset_path) {
+                error!("Failed to import asset file {}: {}", file, err);
                 return Err(());
             }
+        }
+        PacketIn::RequestFileDialog { id, title, filters } => {
+            let in_editor = world
+                .get_resource::<State<AwgenState>>()
+                .is_some_and(|state| *state.get() == AwgenState::Editor);
 
-            debug!("Imported asset from {} as {}", file, asset_path);
+            if !in_editor || !world.contains_resource::<PendingFileDialogs>() {
+                error!("Cannot open file dialog \"{}\": not in editor mode", id);
+                reply(world, PacketOut::FileDialogClosed { id, path: None });
+                return Err(());
+            }
+
+            info!("Opening file dialog \"{}\"", id);
+            file_picker::open_file_dialog(world, id, title, filters);
+        }
+        PacketIn::WatchAssets { path } => {
+            let in_editor = world
+                .get_resource::<State<AwgenState>>()
+                .is_some_and(|state| *state.get() == AwgenState::Editor);
+
+            if !in_editor || !world.contains_resource::<AssetWatch>() {
+                error!("Cannot watch assets at {}: not in editor mode", path);
+                return Err(());
+            }
+
+            if !AssetWatch::is_valid_path(&path) {
+                error!("Invalid asset path to watch: {}", path);
+                return Err(());
+            }
+
+            info!("Watching assets at {}", path);
+            world.resource_mut::<AssetWatch>().subscribe(path);
+        }
+        PacketIn::UnwatchAssets { path } => {
+            let Some(mut watch) = world.get_resource_mut::<AssetWatch>() else {
+                return Err(());
+            };
+
+            if !watch.unsubscribe(&path) {
+                warn!("Assets at {} are not being watched", path);
+                return Err(());
+            }
+            info!("Stopped watching assets at {}", path);
         }
         PacketIn::CreateTileset {
             tile_paths,
             output_path,
+            fast_mipmaps,
         } => {
             info!(
                 "Received create tileset packet: tile_paths = {:?}, asset_path = {}",
@@ -130,7 +262,7 @@ fn handle(world: &mut World, packet: PacketIn) -> Result<(), ()> {
             }
 
             let project_folder = world.resource::<ProjectSettings>().project_folder();
-            let tile_paths = tile_paths
+            let tile_files = tile_paths
                 .iter()
                 .map(|path| parse_asset_path(project_folder, path))
                 .collect::<Result<Vec<PathBuf>, ()>>()?;
@@ -138,17 +270,40 @@ fn handle(world: &mut World, packet: PacketIn) -> Result<(), ()> {
 
             let handle = world
                 .resource::<AssetServer>()
-                .get_handle(output_path)
+                .get_handle(&output_path)
                 .unwrap_or_else(|| world.resource_mut::<Assets<Image>>().reserve_handle());
 
-            let thread_pool = AsyncComputeTaskPool::get();
-            let task = thread_pool.spawn(async move {
-                (
-                    handle,
-                    crate::tiles::builder::create_tileset(tile_paths, asset_path),
-                )
+            let mipmap_quality = if fast_mipmaps {
+                MipmapQuality::Fast
+            } else {
+                MipmapQuality::GammaCorrect
+            };
+
+            let label = format!("Building tileset {}", output_path);
+            let built_path = output_path.clone();
+            let job = world
+                .resource_mut::<BackgroundJobs>()
+                .spawn(label, |ctx| async move {
+                    let result = create_tileset_with_progress(
+                        tile_files,
+                        asset_path,
+                        mipmap_quality,
+                        |progress| {
+                            ctx.set_progress(progress);
+                            !ctx.is_cancelled()
+                        },
+                    );
+                    TilesetBuilt {
+                        handle,
+                        output_path: built_path,
+                        tile_paths,
+                        result,
+                    }
+                });
+
+            world.resource_scope(|world, mut generating: Mut<GeneratingTilesets>| {
+                generating.add_job(output_path, job, world.resource::<BackgroundJobs>());
             });
-            world.resource_mut::<GeneratingTilesets>().add_task(task);
         }
         PacketIn::SetTilesets {
             opaque_tileset_path,
@@ -165,35 +320,535 @@ fn handle(world: &mut World, packet: PacketIn) -> Result<(), ()> {
             let opaque_mat_handle = materials.add(TilesetMaterial {
                 texture: opaque_img_handle,
                 alpha_mode: AlphaMode::Opaque,
+                ..default()
             });
 
             let mut active_tilesets = world.resource_mut::<ActiveTilesets>();
             active_tilesets.opaque = opaque_mat_handle;
         }
         PacketIn::SetBlock { pos, model } => {
-            let chunk_pos = pos.as_chunk_pos();
-            match world.resource::<ChunkTable>().get_chunk(chunk_pos) {
-                Some(chunk_id) => {
-                    if let Some(mut chunk) = world.get_mut::<VoxelChunk>(chunk_id) {
-                        *chunk.get_models_mut().get_mut(pos) = *model;
-                    } else {
-                        error!("Failed to get chunk at position {chunk_pos} to set block at {pos}");
-                    }
+            set_block(world, pos, *model);
+        }
+        PacketIn::GetBlock { pos } => {
+            let model = get_block(world, pos).cloned().unwrap_or_default();
+            reply(
+                world,
+                PacketOut::Block {
+                    pos,
+                    model: Box::new(model),
+                },
+            );
+        }
+        PacketIn::RaycastBlocks {
+            origin,
+            dir,
+            max_dist,
+        } => {
+            if !(0.0 ..= MAX_RAYCAST_DISTANCE).contains(&max_dist) {
+                error!(
+                    "Ray cast distance {} must be between 0 and {}",
+                    max_dist, MAX_RAYCAST_DISTANCE
+                );
+                reply(
+                    world,
+                    PacketOut::RaycastResult {
+                        origin,
+                        dir,
+                        hit: None,
+                    },
+                );
+                return Err(());
+            }
+
+            let hit = match Dir3::new(Vec3::from(dir)) {
+                Ok(direction) => {
+                    let ray = Ray3d::new(Vec3::from(origin), direction);
+                    raycast_blocks(ray, max_dist, |pos| {
+                        get_block(world, pos).is_some_and(|model| *model != BlockModel::Empty)
+                    })
+                }
+                Err(err) => {
+                    warn!("Cannot cast a ray in direction {:?}: {}", dir, err);
+                    None
+                }
+            };
+            reply(world, PacketOut::RaycastResult { origin, dir, hit });
+        }
+        PacketIn::GetChunkSummary { pos } => {
+            let chunk = world
+                .get_resource::<ChunkTable>()
+                .and_then(|table| table.get_chunk(pos))
+                .and_then(|chunk_id| world.get::<VoxelChunk>(chunk_id));
+
+            let solid_blocks = chunk.map_or(0, |chunk| {
+                chunk
+                    .get_models()
+                    .as_slice()
+                    .iter()
+                    .filter(|model| **model != BlockModel::Empty)
+                    .count()
+            });
+
+            reply(
+                world,
+                PacketOut::ChunkSummary {
+                    pos,
+                    origin: pos.origin(),
+                    size: CHUNK_SIZE as u32,
+                    loaded: chunk.is_some(),
+                    solid_blocks: solid_blocks as u32,
+                },
+            );
+        }
+        PacketIn::ListSaves => {
+            let project_folder = world.resource::<ProjectSettings>().project_folder();
+            match savegame::list_saves(project_folder) {
+                Ok(saves) => reply(world, PacketOut::SaveList { saves }),
+                Err(err) => {
+                    error!("Failed to list saves: {}", err);
+                    reply(world, PacketOut::SaveList { saves: vec![] });
+                }
+            }
+        }
+        PacketIn::CreateSave { slot, data } => {
+            info!("Saving game to slot \"{}\"", slot);
+            match create_save(world, &slot, &data) {
+                Ok(()) => {
+                    crate::shutdown::mark_saved(world, &slot);
+                    reply(world, PacketOut::SaveCreated { slot });
                 }
+                Err(err) => save_failed(world, slot, err),
+            }
+        }
+        PacketIn::LoadSave { slot } => {
+            info!("Loading game from slot \"{}\"", slot);
+            let project_folder = world
+                .resource::<ProjectSettings>()
+                .project_folder()
+                .to_path_buf();
+            background::load_save(world, project_folder, slot);
+        }
+        PacketIn::ConsolePrint { text } => {
+            debug!("Console: {}", text);
+            crate::ux::chat::print(world, text);
+        }
+        packet @ (PacketIn::SpawnSprite { .. }
+        | PacketIn::UpdateSprite { .. }
+        | PacketIn::DespawnSprite { .. }) => {
+            crate::map::billboard::handle(world, packet);
+        }
+        PacketIn::TakeSnapshot { name, region } => {
+            debug!("Taking map snapshot \"{}\"", name);
+            let region = region.map(|region| BlockRegion::new(region.min, region.max));
+            let snapshot = MapSnapshot::capture(world, region);
+            world.resource_mut::<MapSnapshots>().insert(name, snapshot);
+        }
+        PacketIn::DropSnapshot { name } => {
+            world.resource_mut::<MapSnapshots>().remove(&name);
+        }
+        PacketIn::DiffSnapshots { from, to } => {
+            let current;
+            let snapshots = world.resource::<MapSnapshots>();
+            let Some(before) = snapshots.get(&from) else {
+                reply(world, PacketOut::SnapshotNotFound { name: from });
+                return Err(());
+            };
+
+            let after = match &to {
+                Some(name) => match snapshots.get(name) {
+                    Some(after) => after,
+                    None => {
+                        reply(world, PacketOut::SnapshotNotFound { name: name.clone() });
+                        return Err(());
+                    }
+                },
                 None => {
-                    let mut chunk = VoxelChunk::new(chunk_pos);
-                    *chunk.get_models_mut().get_mut(pos) = *model;
-                    let chunk_id = world.spawn(chunk).id();
-                    world
-                        .resource_mut::<ChunkTable>()
-                        .add_chunk(chunk_pos, chunk_id);
+                    current = MapSnapshot::capture(world, before.region());
+                    &current
                 }
             };
+
+            let patch = before.diff(after);
+            debug!(
+                "Map diff from \"{}\" has {} changes",
+                from,
+                patch.changes.len()
+            );
+            reply(world, PacketOut::MapDiff { from, to, patch });
+        }
+        PacketIn::ApplyPatch { patch } => {
+            debug!("Applying map patch with {} changes", patch.changes.len());
+            patch.apply(world);
+        }
+        PacketIn::ListBookmarks => {
+            send_bookmarks(world);
+        }
+        PacketIn::SetBookmark { bookmark } => {
+            info!("Setting bookmark \"{}\" at {}", bookmark.name, bookmark.pos);
+            match world.resource::<GameDatabase>().set_bookmark(&bookmark) {
+                Ok(()) => send_bookmarks(world),
+                Err(err) => bookmark_failed(world, bookmark.name, err),
+            }
+        }
+        PacketIn::DeleteBookmark { name } => {
+            info!("Deleting bookmark \"{}\"", name);
+            match world.resource::<GameDatabase>().delete_bookmark(&name) {
+                Ok(()) => send_bookmarks(world),
+                Err(err) => bookmark_failed(world, name, err),
+            }
+        }
+        PacketIn::JumpToBookmark { name } => {
+            let bookmark = match world.resource::<GameDatabase>().get_bookmark(&name) {
+                Ok(Some(bookmark)) => bookmark,
+                Ok(None) => {
+                    bookmark_failed(world, name.clone(), DatabaseError::UnknownBookmark(name));
+                    return Err(());
+                }
+                Err(err) => {
+                    bookmark_failed(world, name, err);
+                    return Err(());
+                }
+            };
+
+            let mut cameras = world.query::<&mut CameraController>();
+            for mut camera in cameras.iter_mut(world) {
+                camera.jump_to(&bookmark);
+            }
+        }
+        PacketIn::ListBlockModels => {
+            match with_project_assets(world, |assets| assets.list_block_models()) {
+                Ok(models) => reply(world, PacketOut::BlockModelList { models }),
+                Err(err) => {
+                    error!("Failed to list block models: {}", err);
+                    reply(world, PacketOut::BlockModelList { models: vec![] });
+                }
+            }
+        }
+        PacketIn::PlaceBlockModel { pos, name } => {
+            let model = match with_project_assets(world, |assets| assets.get_block_model(&name)) {
+                Ok(Some(model)) => model.model,
+                Ok(None) => {
+                    let err = DatabaseError::UnknownBlockModel(name.clone());
+                    block_model_failed(world, name, err);
+                    return Err(());
+                }
+                Err(err) => {
+                    block_model_failed(world, name, err);
+                    return Err(());
+                }
+            };
+
+            set_block(world, pos, model);
+        }
+        PacketIn::SetCameraCollision { enabled } => {
+            let mut cameras = world.query::<&mut CameraController>();
+            for mut camera in cameras.iter_mut(world) {
+                camera.collision = enabled;
+            }
+        }
+        PacketIn::SetCameraProjection { projection } => {
+            let mut cameras = world.query::<&mut CameraController>();
+            for mut camera in cameras.iter_mut(world) {
+                camera.projection = projection;
+            }
+        }
+        PacketIn::SetAtmosphere { atmosphere } => {
+            if let Err(err) = atmosphere.validate() {
+                error!("Invalid atmosphere settings: {}", err);
+                return Err(());
+            }
+
+            info!("Setting atmosphere to {:?}", atmosphere);
+            world.insert_resource(atmosphere);
+        }
+        PacketIn::SetFrameRate {
+            target_fps,
+            unfocused_fps,
+        } => {
+            let invalid = target_fps
+                .into_iter()
+                .chain(unfocused_fps)
+                .find(|&fps| !FramePacing::is_valid_fps(fps));
+            if let Some(fps) = invalid {
+                error!("Invalid frame rate limit: {}", fps);
+                return Err(());
+            }
+
+            let mut pacing = *world.resource::<FramePacing>();
+            pacing.target_fps = target_fps;
+            if let Some(fps) = unfocused_fps {
+                pacing.unfocused_fps = fps;
+            }
+
+            info!(
+                "Setting frame rate limit to {:?}, or {} while unfocused",
+                pacing.target_fps, pacing.unfocused_fps
+            );
+            if let Err(err) = pacing.save(world.resource::<GameDatabase>()) {
+                warn!("Frame rate limits will not persist: {}", err);
+            }
+            world.insert_resource(pacing);
+        }
+        PacketIn::SetPerformanceBudgets { budgets } => {
+            if let Err(err) = budgets.validate() {
+                error!("Invalid performance budgets: {}", err);
+                return Err(());
+            }
+
+            info!("Setting performance budgets to {:?}", budgets);
+            if let Err(err) = budgets.save(world.resource::<GameDatabase>()) {
+                warn!("Performance budgets will not persist: {}", err);
+            }
+            world.insert_resource(budgets);
+        }
+        PacketIn::SetLogFilter { filter } => {
+            info!("Setting log filter to \"{}\"", filter);
+            let database = world.resource::<GameDatabase>().0.clone();
+            let Some(mut log_filter) = world.get_resource_mut::<LogFilter>() else {
+                error!("Cannot set the log filter: logging is not enabled");
+                return Err(());
+            };
+
+            if let Err(err) = log_filter.set_and_save(&filter, &database) {
+                error!("Failed to set log filter: {}", err);
+                return Err(());
+            }
+        }
+        PacketIn::ListAreas => {
+            send_areas(world);
+        }
+        PacketIn::SetArea { area } => {
+            info!("Setting area \"{}\" with tags {:?}", area.name, area.tags);
+            let area = Area {
+                region: BlockRegion::new(area.region.min, area.region.max),
+                ..area
+            };
+
+            match world.resource::<GameDatabase>().set_area(&area) {
+                Ok(()) => match world.resource_mut::<AreaIndex>().insert(area) {
+                    Ok(()) => send_areas(world),
+                    Err(err) => area_failed(world, err.name.clone(), err.into()),
+                },
+                Err(err) => area_failed(world, area.name, err),
+            }
+        }
+        PacketIn::DeleteArea { name } => {
+            info!("Deleting area \"{}\"", name);
+            match world.resource::<GameDatabase>().delete_area(&name) {
+                Ok(()) => {
+                    world.resource_mut::<AreaIndex>().remove(&name);
+                    send_areas(world);
+                }
+                Err(err) => area_failed(world, name, err),
+            }
+        }
+        PacketIn::ListLights => {
+            send_lights(world);
+        }
+        PacketIn::SetLightEnabled { name, enabled } => {
+            info!(
+                "Switching light \"{}\" {}",
+                name,
+                if enabled { "on" } else { "off" }
+            );
+            // Scripts only switch the light in the running scene. The stored
+            // state of the light is changed from the editor.
+            let light = world.resource::<LightTable>().get(&name);
+            let Some(mut light) = light.and_then(|entity| world.get_mut::<SceneLight>(entity))
+            else {
+                light_failed(world, name.clone(), DatabaseError::UnknownLight(name));
+                return Err(());
+            };
+
+            light.0.enabled = enabled;
+            send_lights(world);
+        }
+        PacketIn::RegisterCommand { id, name, shortcut } => {
+            debug!("Registering script command \"{}\"", id);
+            let mut command = PaletteCommand::new(format!("{SCRIPT_COMMAND_PREFIX}{id}"), name);
+            command.shortcut = shortcut;
+            world
+                .get_resource_or_init::<CommandRegistry>()
+                .register(command);
+        }
+        PacketIn::UnregisterCommand { id } => {
+            debug!("Unregistering script command \"{}\"", id);
+            world
+                .get_resource_or_init::<CommandRegistry>()
+                .unregister(&format!("{SCRIPT_COMMAND_PREFIX}{id}"));
+        }
+        PacketIn::GetAreasAt { pos } => {
+            let areas = world
+                .resource::<AreaIndex>()
+                .areas_at(pos)
+                .into_iter()
+                .cloned()
+                .collect();
+            reply(world, PacketOut::AreasAt { pos, areas });
+        }
+        #[cfg(feature = "networking")]
+        packet @ (PacketIn::HostGame { .. }
+        | PacketIn::JoinGame { .. }
+        | PacketIn::LeaveGame
+        | PacketIn::NetSend { .. }) => {
+            crate::net::handle(world, packet);
+        }
+        PacketIn::DeleteSave { slot } => {
+            info!("Deleting save slot \"{}\"", slot);
+            let project_folder = world.resource::<ProjectSettings>().project_folder();
+            match savegame::delete_save(project_folder, &slot) {
+                Ok(()) => reply(world, PacketOut::SaveDeleted { slot }),
+                Err(err) => save_failed(world, slot, err),
+            }
         }
     };
     Ok(())
 }
 
+/// Sends a response packet to the script engine, if it is running.
+pub(super) fn reply(world: &World, packet: PacketOut) {
+    let Some(engine) = world.get_resource::<ScriptEngine>() else {
+        return;
+    };
+
+    if let Err(err) = engine.send(packet) {
+        error!("Failed to send response to the script engine: {}", err);
+    }
+}
+
+/// Increments a usage counter, if usage counters are set up.
+fn count_usage(world: &mut World, name: &str) {
+    if let Some(mut counters) = world.get_resource_mut::<UsageCounters>() {
+        counters.increment(name);
+    }
+}
+
+/// Logs a failed save operation and reports it to the script engine.
+pub(super) fn save_failed(world: &World, slot: String, err: DatabaseError) {
+    error!("Save operation on slot \"{}\" failed: {}", slot, err);
+    reply(
+        world,
+        PacketOut::SaveFailed {
+            slot,
+            error: err.to_string(),
+        },
+    );
+}
+
+/// Sends the list of bookmarks stored in the game database to the script
+/// engine.
+fn send_bookmarks(world: &World) {
+    match world.resource::<GameDatabase>().list_bookmarks() {
+        Ok(bookmarks) => reply(world, PacketOut::BookmarkList { bookmarks }),
+        Err(err) => {
+            error!("Failed to list bookmarks: {}", err);
+            reply(world, PacketOut::BookmarkList { bookmarks: vec![] });
+        }
+    }
+}
+
+/// Logs a failed bookmark operation and reports it to the script engine.
+fn bookmark_failed(world: &World, name: String, err: DatabaseError) {
+    error!("Bookmark operation on \"{}\" failed: {}", name, err);
+    reply(
+        world,
+        PacketOut::BookmarkFailed {
+            name,
+            error: err.to_string(),
+        },
+    );
+}
+
+/// Runs the given closure with access to the project asset database, which
+/// block models are stored in.
+fn with_project_assets<R>(
+    world: &mut World,
+    f: impl FnOnce(&mut AwgenAssets<ProjectAssets>) -> R,
+) -> R {
+    let mut state = SystemState::<AwgenAssets<ProjectAssets>>::new(world);
+    let result = f(&mut state.get_mut(world));
+    state.apply(world);
+    result
+}
+
+/// Logs a failed block model operation and reports it to the script engine.
+fn block_model_failed(world: &World, name: String, err: DatabaseError) {
+    error!("Block model operation on \"{}\" failed: {}", name, err);
+    reply(
+        world,
+        PacketOut::BlockModelFailed {
+            name,
+            error: err.to_string(),
+        },
+    );
+}
+
+/// Sends the list of areas stored in the game database to the script engine.
+fn send_areas(world: &World) {
+    match world.resource::<GameDatabase>().list_areas() {
+        Ok(areas) => reply(world, PacketOut::AreaList { areas }),
+        Err(err) => {
+            error!("Failed to list areas: {}", err);
+            reply(world, PacketOut::AreaList { areas: vec![] });
+        }
+    }
+}
+
+/// Logs a failed area operation and reports it to the script engine.
+fn area_failed(world: &World, name: String, err: DatabaseError) {
+    error!("Area operation on \"{}\" failed: {}", name, err);
+    reply(
+        world,
+        PacketOut::AreaFailed {
+            name,
+            error: err.to_string(),
+        },
+    );
+}
+
+/// Sends the list of lights in the running scene to the script engine.
+fn send_lights(world: &World) {
+    let mut lights = world
+        .resource::<LightTable>()
+        .iter()
+        .filter_map(|entity| world.get::<SceneLight>(entity))
+        .map(|light| light.0.clone())
+        .collect::<Vec<_>>();
+    lights.sort_by(|a, b| a.name.cmp(&b.name));
+    reply(world, PacketOut::LightList { lights });
+}
+
+/// Logs a failed light operation and reports it to the script engine.
+fn light_failed(world: &World, name: String, err: DatabaseError) {
+    error!("Light operation on \"{}\" failed: {}", name, err);
+    reply(
+        world,
+        PacketOut::LightFailed {
+            name,
+            error: err.to_string(),
+        },
+    );
+}
+
+/// Writes the current map state and the given script data to a save slot.
+pub(crate) fn create_save(
+    world: &mut World,
+    slot: &str,
+    data: &serde_json::Value,
+) -> Result<(), DatabaseError> {
+    let project_folder = world
+        .resource::<ProjectSettings>()
+        .project_folder()
+        .to_path_buf();
+
+    let save = SaveGame::create(&project_folder, slot)?;
+    for chunk in world.query::<&VoxelChunk>().iter(world) {
+        save.write_chunk(chunk.pos(), chunk.get_models())?;
+    }
+    save.set_data(data)?;
+    save.commit(&project_folder, slot)
+}
+
 /// Attempts to parse the given string as an asset path. This function will also
 /// automatically create the necessary directories for the asset if they do not
 /// already exist.
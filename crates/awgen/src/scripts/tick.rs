@@ -0,0 +1,100 @@
+//! This module implements the fixed-tick mode for game logic.
+//!
+//! By default, packets from the script engine are applied as soon as they
+//! arrive, so the outcome of a script may depend on the frame rate. In
+//! fixed-tick mode, the engine instead advances a tick counter at a fixed rate,
+//! decoupled from render frames. Each tick, a [`PacketOut::Tick`] packet is
+//! sent to the script engine, and all packets sent by the scripts in response
+//! are buffered until the scripts finish the tick with a
+//! [`PacketIn::TickDone`] packet. The buffered packets are then applied
+//! together, in order, on that tick. Every other packet sent to the script
+//! engine is stamped with the tick that was active when it was sent, so the
+//! scripts can tell which tick a reply or event belongs to.
+//!
+//! The next tick is not started until the current tick has been finished, so
+//! the simulation runs in lockstep with the scripts. This makes the sequence of
+//! world mutations deterministic, which is the basis for replays and networked
+//! simulation.
+
+use bevy::prelude::*;
+
+use crate::scripts::plugin::{ScriptEngine, handle};
+use crate::scripts::{PacketIn, PacketOut};
+
+/// The state of the fixed-tick simulation.
+#[derive(Debug, Default, Resource)]
+pub struct SimulationTick {
+    /// The most recently started tick.
+    tick: u64,
+
+    /// Whether the scripts have not yet finished the current tick.
+    awaiting: bool,
+
+    /// Packets received during the current tick, which are applied once the
+    /// tick is finished.
+    pending: Vec<PacketIn>,
+}
+
+impl SimulationTick {
+    /// Gets the most recently started tick. Ticks start at 1, so this returns 0
+    /// if no tick has been started yet.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+}
+
+/// Processes packets from the script engine in fixed-tick mode, advancing the
+/// simulation by one tick once the scripts have finished the previous one.
+///
+/// This system runs in [`FixedUpdate`].
+pub(super) fn advance_tick(world: &mut World) {
+    let mut finished = None;
+
+    while let Ok(Some(packet)) = world.resource::<ScriptEngine>().recv() {
+        match packet {
+            PacketIn::TickDone { tick } => {
+                let mut sim = world.resource_mut::<SimulationTick>();
+                if !sim.awaiting || tick != sim.tick {
+                    warn!(
+                        "Received tick done for tick {}, but tick {} is active.",
+                        tick, sim.tick
+                    );
+                    continue;
+                }
+
+                sim.awaiting = false;
+                finished = Some(std::mem::take(&mut sim.pending));
+                break;
+            }
+
+            // These packets report on the state of the script engine itself,
+            // and must not wait for the tick to finish.
+            PacketIn::Shutdown | PacketIn::Crashed { .. } | PacketIn::LimitExceeded { .. } => {
+                let _ = handle(world, packet);
+            }
+
+            packet => world.resource_mut::<SimulationTick>().pending.push(packet),
+        }
+    }
+
+    if let Some(packets) = finished {
+        for packet in packets {
+            let _ = handle(world, packet);
+        }
+    }
+
+    let mut sim = world.resource_mut::<SimulationTick>();
+    if sim.awaiting {
+        return;
+    }
+
+    sim.tick += 1;
+    sim.awaiting = true;
+    let tick = sim.tick;
+
+    let engine = world.resource::<ScriptEngine>();
+    engine.set_tick(tick);
+    if let Err(err) = engine.send(PacketOut::Tick { tick }) {
+        error!("Failed to send tick {} to the script engine: {}", tick, err);
+    }
+}
@@ -0,0 +1,178 @@
+//! This module generates TypeScript type definitions for the packets that are
+//! sent between the game engine and the script engine.
+//!
+//! The definitions are derived from the serde representation of the Rust
+//! packet types, so they always match what the engine actually sends and
+//! accepts. They can be written to disk using the `--emit-types` command line
+//! flag.
+//!
+//! The generated files are committed to the sample project, and a test checks
+//! that they are kept up to date. Running the tests with `AWGEN_UPDATE_TYPES`
+//! set regenerates them.
+
+use std::path::Path;
+use std::{fs, io};
+
+use ts_rs::TS;
+
+use crate::database::areas::Area;
+use crate::database::block_models::NamedBlockModel;
+use crate::database::bookmarks::Bookmark;
+use crate::database::lights::{LightKind, PlacedLight};
+use crate::database::savegame::SaveInfo;
+use crate::map::{
+    BillboardMode,
+    BlockChange,
+    BlockFace,
+    BlockHit,
+    BlockModel,
+    BlockRegion,
+    ChunkPos,
+    Cube,
+    MapPatch,
+    TileFace,
+    UvWindow,
+    WorldPos,
+};
+use crate::scripts::{PacketEncoding, PacketIn, PacketOut, ScriptLimit};
+use crate::ux::asset_watch::AssetChangeKind;
+use crate::ux::file_picker::FileFilter;
+use crate::ux::{AtmosphereSettings, CameraProjection, ClickButton, PerformanceBudgets};
+
+/// The file name of the generated type definitions.
+pub const TYPES_FILE: &str = "Packets.d.ts";
+
+/// The file name of the generated packet wrapper functions.
+pub const WRAPPERS_FILE: &str = "PacketWrappers.ts";
+
+/// The header that is written at the top of every generated file.
+const HEADER: &str = "// This file is generated by `awgen --emit-types`. Do not edit it by hand.\n";
+
+/// Types that are referenced by the generated definitions, but are not
+/// derived from a Rust type.
+const PRELUDE: &str = "export type Mat2 = [number, number, number, number];\n";
+
+/// Thin wrapper functions for constructing and matching packets, built on top
+/// of the generated packet unions.
+const WRAPPERS: &str = r#"import type { PacketIn, PacketOut } from "./Packets.d.ts";
+
+/**
+ * The packet variant of the given type, that may be sent to the engine.
+ */
+export type PacketInOf<T extends PacketIn["type"]> = Extract<PacketIn, { type: T }>;
+
+/**
+ * The packet variant of the given type, that may be received from the engine.
+ */
+export type PacketOutOf<T extends PacketOut["type"]> = Extract<PacketOut, { type: T }>;
+
+/**
+ * Creates a packet to send to the engine.
+ */
+export function packetIn<T extends PacketIn["type"]>(
+  type: T,
+  fields: Omit<PacketInOf<T>, "type">,
+): PacketInOf<T> {
+  return { ...fields, type } as PacketInOf<T>;
+}
+
+/**
+ * Checks whether a packet received from the engine is of the given type.
+ */
+export function isPacketOut<T extends PacketOut["type"]>(
+  packet: PacketOut,
+  type: T,
+): packet is PacketOutOf<T> {
+  return packet.type === type;
+}
+"#;
+
+/// Writes the generated type definitions and wrapper functions to the given
+/// folder, creating it if it does not exist.
+pub fn emit_types(folder: &Path) -> io::Result<()> {
+    fs::create_dir_all(folder)?;
+    fs::write(folder.join(TYPES_FILE), declarations())?;
+    fs::write(folder.join(WRAPPERS_FILE), wrappers())?;
+    Ok(())
+}
+
+/// Generates the contents of the packet wrappers file.
+pub fn wrappers() -> String {
+    format!("{HEADER}\n{WRAPPERS}")
+}
+
+/// Generates the contents of the type definitions file.
+pub fn declarations() -> String {
+    let decls = [
+        serde_json::Value::decl(),
+        WorldPos::decl(),
+        ChunkPos::decl(),
+        UvWindow::decl(),
+        TileFace::decl(),
+        Cube::decl(),
+        BlockModel::decl(),
+        BillboardMode::decl(),
+        BlockRegion::decl(),
+        BlockChange::decl(),
+        MapPatch::decl(),
+        BlockFace::decl(),
+        BlockHit::decl(),
+        ScriptLimit::decl(),
+        PacketEncoding::decl(),
+        SaveInfo::decl(),
+        NamedBlockModel::decl(),
+        Bookmark::decl(),
+        Area::decl(),
+        LightKind::decl(),
+        PlacedLight::decl(),
+        CameraProjection::decl(),
+        AtmosphereSettings::decl(),
+        PerformanceBudgets::decl(),
+        ClickButton::decl(),
+        FileFilter::decl(),
+        AssetChangeKind::decl(),
+        #[cfg(feature = "networking")]
+        crate::net::NetMessage::decl(),
+        PacketIn::decl(),
+        PacketOut::decl(),
+    ];
+
+    let mut out = format!("{HEADER}\n{PRELUDE}");
+    for decl in decls {
+        out.push_str("\nexport ");
+        out.push_str(&decl);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Gets the folder of the sample project that the generated files are
+    /// committed to.
+    fn generated_folder() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../sample_project/editor/scripts/API/Generated")
+    }
+
+    /// The committed files are generated without optional features, so they
+    /// are only compared when those features are disabled.
+    #[test]
+    #[cfg(not(feature = "networking"))]
+    fn generated_files_are_up_to_date() {
+        let folder = generated_folder();
+        if std::env::var_os("AWGEN_UPDATE_TYPES").is_some() {
+            emit_types(&folder).unwrap();
+        }
+
+        let types = fs::read_to_string(folder.join(TYPES_FILE)).unwrap();
+        let wrapper_fns = fs::read_to_string(folder.join(WRAPPERS_FILE)).unwrap();
+        assert!(
+            types == declarations() && wrapper_fns == wrappers(),
+            "The generated script types are out of date. Rerun with AWGEN_UPDATE_TYPES=1."
+        );
+    }
+}
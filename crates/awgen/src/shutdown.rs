@@ -0,0 +1,377 @@
+//! This module implements the shutdown pipeline, which intercepts requests to
+//! close the game window so that work is not lost.
+//!
+//! When the window is closed while the map has unsaved changes, a dialog asks
+//! whether to save them, discard them, or cancel. Changes are saved to the
+//! slot that was last saved or loaded, or to the autosave slot if there is
+//! none. Once
+//! the user confirms, the engine waits for any background jobs that are still
+//! running, such as tileset builds, then sends a `Shutdown` packet to the
+//! script engine and waits for it to acknowledge with its own `Shutdown`
+//! packet before exiting. Both waits are bounded, so a stuck task or script
+//! cannot keep the window open.
+
+use std::time::{Duration, Instant};
+
+use awgen_ui::hotkeys::HotkeyDialog;
+use awgen_ui::menus::layers::UiLayer;
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::button::{ButtonBuilder, ButtonContent, button};
+use bevy::ecs::component::Tick;
+use bevy::ecs::system::SystemChangeTick;
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+use bevy::window::{PrimaryWindow, WindowCloseRequested};
+
+use crate::jobs::BackgroundJobs;
+use crate::map::{ChunkPos, MapSystemSets, VoxelChunk};
+use crate::scripts::{ScriptEngine, create_save};
+
+/// The save slot that unsaved changes are written to when saving on exit, if
+/// no save slot has been saved or loaded yet.
+pub const AUTOSAVE_SLOT: &str = "autosave";
+
+/// The maximum time to wait for background jobs to finish before exiting.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The maximum time to wait for the script engine to acknowledge the shutdown.
+const SCRIPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The color of the backdrop behind the shutdown dialog.
+const BACKDROP_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+
+/// Plugin that sets up the shutdown pipeline.
+///
+/// The window plugin must be configured with `close_when_requested` disabled,
+/// as this plugin decides when the app exits instead.
+pub struct ShutdownPlugin;
+impl Plugin for ShutdownPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<UnsavedChanges>()
+            .init_resource::<ShutdownState>()
+            .add_systems(
+                Update,
+                (
+                    track_unsaved_chunks.before(MapSystemSets::RedrawChunks),
+                    (request_shutdown, flush_pending_work, await_scripts).chain(),
+                ),
+            )
+            .add_observer(on_dialog_activate);
+    }
+}
+
+/// A resource that tracks the chunks that have been edited since the map was
+/// last saved or loaded.
+#[derive(Debug, Default, Resource)]
+pub struct UnsavedChanges {
+    /// The positions of the edited chunks.
+    chunks: HashSet<ChunkPos>,
+
+    /// The change tick at which the map was last saved or loaded.
+    saved_at: Tick,
+
+    /// The save slot that the map was last saved to or loaded from.
+    slot: Option<String>,
+}
+
+impl UnsavedChanges {
+    /// Returns the number of chunks with unsaved changes.
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Returns true if there are no unsaved changes.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Gets the save slot that the map was last saved to or loaded from, if
+    /// any.
+    pub fn slot(&self) -> Option<&str> {
+        self.slot.as_deref()
+    }
+}
+
+/// Marks the current map state as saved to or loaded from the given slot, so
+/// that only later edits count as unsaved changes.
+pub fn mark_saved(world: &mut World, slot: &str) {
+    let tick = world.change_tick();
+    let mut unsaved = world.resource_mut::<UnsavedChanges>();
+    unsaved.chunks.clear();
+    unsaved.saved_at = tick;
+    unsaved.slot = Some(slot.to_string());
+}
+
+/// A resource that holds the progress of the shutdown pipeline.
+#[derive(Debug, Default, Resource)]
+pub enum ShutdownState {
+    /// The game is running normally.
+    #[default]
+    Running,
+
+    /// The unsaved changes dialog is open.
+    Prompting {
+        /// The dialog backdrop entity.
+        dialog: Entity,
+    },
+
+    /// Waiting for background jobs to finish.
+    Flushing {
+        /// The time at which to stop waiting.
+        deadline: Instant,
+    },
+
+    /// Waiting for the script engine to acknowledge the shutdown.
+    AwaitingScripts {
+        /// The time at which to stop waiting.
+        deadline: Instant,
+    },
+}
+
+/// An action performed when a button in the shutdown dialog is clicked.
+#[derive(Debug, Clone, Copy, Component)]
+enum DialogAction {
+    /// Saves the unsaved changes to the active save slot, then exits.
+    Save,
+
+    /// Exits without saving.
+    Discard,
+
+    /// Closes the dialog and keeps the game running.
+    Cancel,
+}
+
+/// Records chunks whose blocks were edited since the map was last saved.
+///
+/// Chunks that are only redrawn, such as when the active tileset changes, are
+/// not edits. This must run before chunks are redrawn, as redrawing clears the
+/// flag that marks edited chunks.
+fn track_unsaved_chunks(
+    chunks: Query<Ref<VoxelChunk>>,
+    ticks: SystemChangeTick,
+    mut unsaved: ResMut<UnsavedChanges>,
+) {
+    let saved_at = unsaved.saved_at;
+    for chunk in chunks.iter() {
+        if chunk.is_edited()
+            && chunk
+                .last_changed()
+                .is_newer_than(saved_at, ticks.this_run())
+        {
+            unsaved.chunks.insert(chunk.pos());
+        }
+    }
+}
+
+/// Starts the shutdown pipeline when the main window is asked to close,
+/// prompting the user first if there are unsaved changes.
+///
+/// Requests to close other windows, such as detached editor panels, are
+/// handled by their owners instead.
+fn request_shutdown(
+    mut close_requests: MessageReader<WindowCloseRequested>,
+    primary: Query<(), With<PrimaryWindow>>,
+    asset_server: Res<AssetServer>,
+    unsaved: Res<UnsavedChanges>,
+    mut state: ResMut<ShutdownState>,
+    mut commands: Commands,
+) {
+    let main_closing = close_requests
+        .read()
+        .filter(|request| primary.contains(request.window))
+        .count()
+        > 0;
+
+    if !main_closing {
+        return;
+    }
+
+    match *state {
+        ShutdownState::Running if unsaved.is_empty() => {
+            *state = ShutdownState::Flushing {
+                deadline: Instant::now() + FLUSH_TIMEOUT,
+            };
+        }
+        ShutdownState::Running => {
+            let slot = unsaved.slot().unwrap_or(AUTOSAVE_SLOT);
+            let dialog = spawn_dialog(&asset_server, unsaved.len(), slot, &mut commands);
+            *state = ShutdownState::Prompting { dialog };
+        }
+        ShutdownState::Prompting { .. } => {}
+        ShutdownState::Flushing { .. } | ShutdownState::AwaitingScripts { .. } => {
+            warn!("Close requested again while shutting down; exiting immediately.");
+            commands.write_message(AppExit::Success);
+        }
+    }
+}
+
+/// Spawns the unsaved changes dialog, returning its backdrop entity.
+fn spawn_dialog(
+    asset_server: &AssetServer,
+    chunk_count: usize,
+    slot: &str,
+    commands: &mut Commands,
+) -> Entity {
+    let theme = hearth_theme(asset_server);
+    let backdrop = commands
+        .spawn((
+            HotkeyDialog,
+            ScreenAnchor::Fullscreen,
+            UiLayer::Dialogs,
+            Node {
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(BACKDROP_COLOR),
+        ))
+        .id();
+
+    let dialog = commands
+        .spawn((
+            ChildOf(backdrop),
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: px(8.0),
+                ..default()
+            },
+            theme.outer_window.clone(),
+            children![
+                (
+                    Text::new(format!("Save changes to \"{}\"?", slot)),
+                    theme.outer_window.text.clone(),
+                ),
+                (
+                    Text::new(format!(
+                        "{} edited chunk(s) will be lost if not saved.",
+                        chunk_count
+                    )),
+                    theme.outer_window.text.clone(),
+                ),
+            ],
+        ))
+        .id();
+
+    let buttons = commands
+        .spawn((
+            ChildOf(dialog),
+            Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: px(4.0),
+                ..default()
+            },
+        ))
+        .id();
+
+    for (action, label) in [
+        (DialogAction::Save, "Save"),
+        (DialogAction::Discard, "Discard"),
+        (DialogAction::Cancel, "Cancel"),
+    ] {
+        commands.spawn((
+            ChildOf(buttons),
+            action,
+            button(ButtonBuilder {
+                node: Node {
+                    flex_grow: 1.0,
+                    ..default()
+                },
+                content: ButtonContent::text(label),
+                theme: theme.clone(),
+            }),
+        ));
+    }
+
+    backdrop
+}
+
+/// Performs the action of a shutdown dialog button when it is clicked.
+fn on_dialog_activate(
+    trigger: On<Activate>,
+    actions: Query<&DialogAction>,
+    mut state: ResMut<ShutdownState>,
+    mut commands: Commands,
+) {
+    let Ok(&action) = actions.get(trigger.event().entity) else {
+        return;
+    };
+
+    let ShutdownState::Prompting { dialog } = *state else {
+        return;
+    };
+    commands.entity(dialog).despawn();
+
+    match action {
+        DialogAction::Save => {
+            commands.queue(|world: &mut World| {
+                let slot = world
+                    .resource::<UnsavedChanges>()
+                    .slot()
+                    .unwrap_or(AUTOSAVE_SLOT)
+                    .to_string();
+                info!("Saving unsaved changes to slot \"{}\"", slot);
+                match create_save(world, &slot, &serde_json::Value::Null) {
+                    Ok(()) => mark_saved(world, &slot),
+                    Err(err) => error!("Failed to save unsaved changes: {}", err),
+                }
+            });
+        }
+        DialogAction::Discard => {
+            info!("Discarding unsaved changes.");
+        }
+        DialogAction::Cancel => {
+            *state = ShutdownState::Running;
+            return;
+        }
+    }
+
+    *state = ShutdownState::Flushing {
+        deadline: Instant::now() + FLUSH_TIMEOUT,
+    };
+}
+
+/// Waits for background jobs to finish, then notifies the script engine
+/// that the game is shutting down.
+fn flush_pending_work(
+    jobs: Res<BackgroundJobs>,
+    engine: Res<ScriptEngine>,
+    mut state: ResMut<ShutdownState>,
+) {
+    let ShutdownState::Flushing { deadline } = *state else {
+        return;
+    };
+
+    if !jobs.is_empty() {
+        if Instant::now() < deadline {
+            return;
+        }
+        warn!(
+            "Timed out waiting for {} background job(s) to finish.",
+            jobs.len()
+        );
+    }
+
+    info!("Notifying the script engine of the shutdown.");
+    engine.shutdown();
+    *state = ShutdownState::AwaitingScripts {
+        deadline: Instant::now() + SCRIPT_TIMEOUT,
+    };
+}
+
+/// Exits the app if the script engine does not acknowledge the shutdown in
+/// time.
+///
+/// The acknowledgment itself is a `Shutdown` packet from the script engine,
+/// which exits the app when it is handled.
+fn await_scripts(state: Res<ShutdownState>, mut app_exit: MessageWriter<AppExit>) {
+    let ShutdownState::AwaitingScripts { deadline } = *state else {
+        return;
+    };
+
+    if Instant::now() >= deadline {
+        warn!("Timed out waiting for the script engine to shut down.");
+        app_exit.write(AppExit::Success);
+    }
+}
@@ -0,0 +1,219 @@
+//! This module implements local usage counters, which help users and
+//! developers understand where time is spent in the editor.
+//!
+//! Counting is opt-in: nothing is recorded until the user enables the counters
+//! with the "Toggle Usage Counters" command. Counts are kept in the
+//! [`UsageCounters`] resource and periodically flushed to the game database,
+//! grouped by session. They are never sent over the network.
+//!
+//! Editor tools and panels are counted through the commands that toggle them,
+//! which are run by both the command palette and their hotkeys.
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use awgen_ui::widgets::command_palette::{CommandInvoked, PaletteCommand, RegisterCommandExt};
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+use crate::database::telemetry::UsageCount;
+use crate::database::{Database, DatabaseError, GameDatabase};
+
+/// The counter of packets received from the script engine.
+pub const PACKETS_PROCESSED: &str = "packets.processed";
+
+/// The counter of files imported as game assets.
+pub const ASSETS_IMPORTED: &str = "assets.imported";
+
+/// The counter of tilesets built.
+pub const TILESETS_BUILT: &str = "assets.tilesets";
+
+/// The prefix of the counters of commands run, which are followed by the ID of
+/// the command.
+pub const COMMAND_PREFIX: &str = "commands.";
+
+/// The interval, in seconds, between flushes of the usage counters to the game
+/// database.
+const FLUSH_INTERVAL: f32 = 30.0;
+
+/// Plugin that sets up the usage counters.
+pub struct TelemetryPlugin;
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<UsageCounters>()
+            .add_systems(Startup, load_usage_counters)
+            .add_systems(Last, flush_usage_counters)
+            .add_observer(count_commands)
+            .register_command(
+                PaletteCommand::new("telemetry.toggle", "Toggle Usage Counters"),
+                toggle_usage_counters,
+            );
+    }
+}
+
+/// A resource that holds the usage counters of the current session.
+#[derive(Debug, Resource)]
+pub struct UsageCounters {
+    /// Whether the user has opted in to usage counters.
+    enabled: bool,
+
+    /// The ID of the current session in the game database, once the first
+    /// counts have been flushed.
+    session: Option<i64>,
+
+    /// The counts of the current session.
+    counts: BTreeMap<String, u64>,
+
+    /// The counts that have not been flushed to the game database yet.
+    pending: BTreeMap<String, u64>,
+
+    /// The timer that controls when the counts are flushed.
+    timer: Timer,
+}
+
+impl Default for UsageCounters {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            session: None,
+            counts: BTreeMap::new(),
+            pending: BTreeMap::new(),
+            timer: Timer::from_seconds(FLUSH_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+impl UsageCounters {
+    /// Returns true if the user has opted in to usage counters.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Opts in to or out of usage counters, storing the choice in the game
+    /// database. Pending counts are flushed before the counters are disabled.
+    pub fn set_enabled(&mut self, enabled: bool, database: &Database) -> Result<(), DatabaseError> {
+        if !enabled {
+            self.flush(database)?;
+        }
+
+        database.set_usage_counters_enabled(enabled)?;
+        self.enabled = enabled;
+        Ok(())
+    }
+
+    /// Adds an amount to the counter with the given name. This does nothing
+    /// unless the counters are enabled.
+    pub fn add(&mut self, name: &str, amount: u64) {
+        if !self.enabled || amount == 0 {
+            return;
+        }
+
+        *self.counts.entry(name.to_string()).or_default() += amount;
+        *self.pending.entry(name.to_string()).or_default() += amount;
+    }
+
+    /// Increments the counter with the given name.
+    pub fn increment(&mut self, name: &str) {
+        self.add(name, 1);
+    }
+
+    /// Gets the counts of the current session, sorted by name.
+    pub fn session_counts(&self) -> &BTreeMap<String, u64> {
+        &self.counts
+    }
+
+    /// Gets the ID of the current session in the game database, if any counts
+    /// have been flushed yet.
+    pub fn session(&self) -> Option<i64> {
+        self.session
+    }
+
+    /// Clears the counts of the current session, so that the next counts start
+    /// a new session. This is used after the stored counts have been cleared.
+    pub fn reset(&mut self) {
+        self.session = None;
+        self.counts.clear();
+        self.pending.clear();
+    }
+
+    /// Writes the pending counts to the game database, starting a new session
+    /// for the first counts.
+    ///
+    /// The pending counts are only cleared once they have been stored. If
+    /// storing them fails, or the database is read-only, they are kept and
+    /// written by a later flush.
+    pub fn flush(&mut self, database: &Database) -> Result<(), DatabaseError> {
+        if self.pending.is_empty() || database.is_read_only() {
+            return Ok(());
+        }
+
+        let session = match self.session {
+            Some(session) => session,
+            None => {
+                let started = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |time| time.as_secs() as i64);
+                let session = database.start_usage_session(started)?;
+                self.session = Some(session);
+                session
+            }
+        };
+
+        let counts = self
+            .pending
+            .iter()
+            .map(|(name, count)| UsageCount {
+                name: name.clone(),
+                count: *count,
+            })
+            .collect::<Vec<_>>();
+        database.add_usage_counts(session, &counts)?;
+
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+/// Loads whether the user has opted in to usage counters.
+fn load_usage_counters(database: Res<GameDatabase>, mut counters: ResMut<UsageCounters>) {
+    match database.usage_counters_enabled() {
+        Ok(enabled) => counters.enabled = enabled,
+        Err(err) => warn!("Failed to load the usage counter settings: {}", err),
+    }
+}
+
+/// Flushes the usage counters to the game database periodically, and when the
+/// app exits.
+fn flush_usage_counters(
+    time: Res<Time>,
+    mut exit: MessageReader<AppExit>,
+    database: Res<GameDatabase>,
+    mut counters: ResMut<UsageCounters>,
+) {
+    let exiting = !exit.is_empty();
+    exit.clear();
+
+    if !counters.timer.tick(time.delta()).just_finished() && !exiting {
+        return;
+    }
+
+    if let Err(err) = counters.flush(&database) {
+        warn!("Failed to store the usage counters: {}", err);
+    }
+}
+
+/// Counts the commands that are run, whether from the command palette or with
+/// a hotkey.
+fn count_commands(trigger: On<CommandInvoked>, mut counters: ResMut<UsageCounters>) {
+    counters.increment(&format!("{COMMAND_PREFIX}{}", trigger.id));
+}
+
+/// Opts in to or out of usage counters.
+fn toggle_usage_counters(database: Res<GameDatabase>, mut counters: ResMut<UsageCounters>) {
+    let enabled = !counters.is_enabled();
+    match counters.set_enabled(enabled, &database) {
+        Ok(()) if enabled => info!("Usage counters enabled. Counts are only stored locally."),
+        Ok(()) => info!("Usage counters disabled"),
+        Err(err) => error!("Failed to change the usage counter settings: {}", err),
+    }
+}
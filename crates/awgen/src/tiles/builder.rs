@@ -2,10 +2,11 @@
 
 use std::path::PathBuf;
 
+use awgen_asset_db::loaders::MipmapQuality;
 use bevy::prelude::*;
 use image::ImageReader;
 
-use crate::tiles::tileset::{Tileset, TilesetError};
+use crate::tiles::{Tileset, TilesetError};
 
 /// Creates a new tileset file from a list of provided tile image paths.
 ///
@@ -14,14 +15,36 @@ use crate::tiles::tileset::{Tileset, TilesetError};
 pub fn create_tileset(
     tile_paths: Vec<PathBuf>,
     output_path: PathBuf,
+) -> Result<Image, TilesetBuilderError> {
+    create_tileset_with_progress(tile_paths, output_path, MipmapQuality::default(), |_| true)
+}
+
+/// Creates a new tileset file from a list of provided tile image paths, with
+/// mipmaps generated by the given filter, reporting the fraction of tiles that
+/// have been added so far.
+///
+/// The progress callback is called after each tile is added. If it returns
+/// false, the tileset is not written and [`TilesetBuilderError::Cancelled`]
+/// is returned.
+pub fn create_tileset_with_progress(
+    tile_paths: Vec<PathBuf>,
+    output_path: PathBuf,
+    mipmap_quality: MipmapQuality,
+    mut on_progress: impl FnMut(f32) -> bool,
 ) -> Result<Image, TilesetBuilderError> {
     let mut tileset = Tileset::new();
+    tileset.set_mipmap_quality(mipmap_quality);
+    let tile_count = tile_paths.len();
 
-    for tile in tile_paths {
+    for (index, tile) in tile_paths.into_iter().enumerate() {
         let img = ImageReader::open(&tile)?.decode()?;
         tileset
             .append_tile(img)
             .map_err(|e| TilesetBuilderError::TileError(tile.clone(), e))?;
+
+        if !on_progress((index + 1) as f32 / tile_count as f32) {
+            return Err(TilesetBuilderError::Cancelled);
+        }
     }
 
     std::fs::write(output_path, tileset.as_binary())?;
@@ -42,4 +65,8 @@ pub enum TilesetBuilderError {
     /// An error that occurs when adding a tile to a tileset.
     #[error("Failed to add tile: {0}")]
     TileError(PathBuf, TilesetError),
+
+    /// The tileset was cancelled before it was finished.
+    #[error("Tileset creation was cancelled")]
+    Cancelled,
 }
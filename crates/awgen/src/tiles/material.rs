@@ -8,7 +8,7 @@ use bevy::render::render_resource::{
     RenderPipelineDescriptor,
     SpecializedMeshPipelineError,
 };
-use bevy::shader::ShaderRef;
+use bevy::shader::{ShaderDefVal, ShaderRef};
 
 use crate::tiles::mesh::ATTRIBUTE_UV_LAYER;
 
@@ -17,6 +17,7 @@ pub const TILESET_SHADER_PATH: &str = "embedded://awgen/tiles/shader.wgsl";
 
 /// TilesetMaterial is a Bevy material that uses a shader to render tilesets.
 #[derive(Debug, Default, Clone, Asset, TypePath, AsBindGroup)]
+#[bind_group_data(TilesetMaterialKey)]
 pub struct TilesetMaterial {
     /// The tileset texture, which is a 2D array of textures.
     #[texture(0, dimension = "2d_array")]
@@ -25,6 +26,56 @@ pub struct TilesetMaterial {
 
     /// The alpha mode of the material.
     pub alpha_mode: AlphaMode,
+
+    /// The debug render mode of the material. Each mode is rendered by its own
+    /// variant of the tileset shader.
+    pub debug_mode: TerrainDebugMode,
+}
+
+/// The debug render modes of terrain, which help to spot broken normals and
+/// face winding.
+///
+/// This is also a resource, which holds the mode applied to every
+/// [`TilesetMaterial`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Resource)]
+pub enum TerrainDebugMode {
+    /// Terrain is rendered normally.
+    #[default]
+    Off,
+
+    /// The edges of the block faces are drawn over the terrain.
+    Wireframe,
+
+    /// Terrain is colored by the direction of its normals, with each axis
+    /// mapped to a color channel.
+    Normals,
+}
+
+impl TerrainDebugMode {
+    /// Gets the shader definition that selects this mode in the tileset
+    /// shader, if any.
+    fn shader_def(self) -> Option<ShaderDefVal> {
+        match self {
+            TerrainDebugMode::Off => None,
+            TerrainDebugMode::Wireframe => Some("TERRAIN_DEBUG_WIREFRAME".into()),
+            TerrainDebugMode::Normals => Some("TERRAIN_DEBUG_NORMALS".into()),
+        }
+    }
+}
+
+/// The pipeline key of a [`TilesetMaterial`], which selects its shader variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TilesetMaterialKey {
+    /// The debug render mode of the material.
+    debug_mode: TerrainDebugMode,
+}
+
+impl From<&TilesetMaterial> for TilesetMaterialKey {
+    fn from(material: &TilesetMaterial) -> Self {
+        Self {
+            debug_mode: material.debug_mode,
+        }
+    }
 }
 
 impl Material for TilesetMaterial {
@@ -52,7 +103,7 @@ impl Material for TilesetMaterial {
         _pipeline: &MaterialPipeline,
         descriptor: &mut RenderPipelineDescriptor,
         layout: &MeshVertexBufferLayoutRef,
-        _key: MaterialPipelineKey<Self>,
+        key: MaterialPipelineKey<Self>,
     ) -> Result<(), SpecializedMeshPipelineError> {
         let vertex_layout = layout.0.get_layout(&[
             Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
@@ -61,6 +112,47 @@ impl Material for TilesetMaterial {
             ATTRIBUTE_UV_LAYER.at_shader_location(3),
         ])?;
         descriptor.vertex.buffers = vec![vertex_layout];
+
+        if let Some(shader_def) = key.bind_group_data.debug_mode.shader_def() {
+            // Back faces are drawn in debug modes, so that faces with a broken
+            // winding show up instead of disappearing.
+            descriptor.primitive.cull_mode = None;
+
+            let shader_defs = [ShaderDefVal::from("TERRAIN_DEBUG"), shader_def];
+            descriptor.vertex.shader_defs.extend(shader_defs.clone());
+            if let Some(fragment) = descriptor.fragment.as_mut() {
+                fragment.shader_defs.extend(shader_defs);
+            }
+        }
+
         Ok(())
     }
 }
+
+/// System to apply the [`TerrainDebugMode`] resource to all tileset materials,
+/// including the materials that were added since it last changed.
+pub(super) fn apply_terrain_debug_mode(
+    mode: Res<TerrainDebugMode>,
+    mut material_events: MessageReader<AssetEvent<TilesetMaterial>>,
+    mut materials: ResMut<Assets<TilesetMaterial>>,
+) {
+    let added = material_events
+        .read()
+        .any(|event| matches!(event, AssetEvent::Added { .. }));
+
+    if !mode.is_changed() && !added {
+        return;
+    }
+
+    let stale = materials
+        .iter()
+        .filter(|(_, material)| material.debug_mode != *mode)
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+
+    for id in stale {
+        if let Some(material) = materials.get_mut(id) {
+            material.debug_mode = *mode;
+        }
+    }
+}
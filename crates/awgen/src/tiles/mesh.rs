@@ -321,6 +321,27 @@ pub trait TerrainPoly {
             }
         }
     }
+
+    /// Maps the UV coordinates of the polygon into the region of the texture
+    /// with the given offset and scale.
+    ///
+    /// The UV coordinates are first shifted back into the unit square, as
+    /// rotating them may have moved them outside of it, so this should be
+    /// called after [`TerrainPoly::rotate_uv`].
+    fn set_uv_window(&mut self, offset: Vec2, scale: Vec2) {
+        let min = (0 .. self.tri_count() + 2)
+            .filter_map(|i| self.get_vertex(i))
+            .map(|vertex| vertex.uv)
+            .reduce(Vec2::min)
+            .unwrap_or_default()
+            .floor();
+
+        for i in 0 .. self.tri_count() + 2 {
+            if let Some(vertex) = self.get_vertex_mut(i) {
+                vertex.uv = offset + (vertex.uv - min) * scale;
+            }
+        }
+    }
 }
 
 impl TerrainPoly for TerrainTriangle {
@@ -7,13 +7,23 @@ mod asset_loader;
 pub mod builder;
 mod material;
 mod mesh;
+mod palette;
 mod resource;
-mod tileset;
 
-pub use material::TilesetMaterial;
+pub use awgen_asset_db::tileset::{TileImage, Tileset, TilesetError, extract_tile};
+pub use material::{TerrainDebugMode, TilesetMaterial};
 pub use mesh::{TerrainMesh, TerrainPoly, TerrainQuad};
-pub use resource::{ActiveTilesets, GeneratingTilesets};
+pub use palette::TilePalette;
+pub use resource::{
+    ActiveTilesets,
+    GeneratingTilesets,
+    TilesetBuilt,
+    TilesetLayout,
+    TilesetReloaded,
+};
 
+use crate::jobs::{BackgroundJobSystems, BackgroundJobsAppExt};
+use crate::map::MapSystemSets;
 use crate::tiles::asset_loader::TilesetAssetLoader;
 
 /// TilesetPlugin is a Bevy plugin that provides tileset functionality. This
@@ -24,14 +34,30 @@ impl Plugin for TilesetPlugin {
         app_.init_asset_loader::<TilesetAssetLoader>()
             .init_resource::<ActiveTilesets>()
             .init_resource::<GeneratingTilesets>()
+            .init_resource::<TilePalette>()
+            .init_resource::<TerrainDebugMode>()
+            .register_job_output::<TilesetBuilt>()
+            .add_message::<TilesetReloaded>()
             .add_plugins(MaterialPlugin::<TilesetMaterial>::default())
+            .add_systems(Startup, palette::load_tile_palette)
             .add_systems(
                 Update,
                 (
+                    palette::reload_palette_on_restore.before(palette::remesh_on_palette_change),
+                    palette::remesh_on_palette_change
+                        .after(TilesetSystemSets::FinishTasks)
+                        .before(MapSystemSets::RedrawChunks),
+                    resource::reload_active_tilesets
+                        .in_set(TilesetSystemSets::ReloadTilesets)
+                        .after(TilesetSystemSets::FinishTasks)
+                        .before(TilesetSystemSets::UpdateActiveTilesets),
                     resource::update_chunk_models
                         .in_set(TilesetSystemSets::UpdateActiveTilesets)
                         .run_if(resource_changed::<ActiveTilesets>),
-                    resource::finish_tileset_tasks.in_set(TilesetSystemSets::FinishTasks),
+                    resource::finish_tileset_tasks
+                        .in_set(TilesetSystemSets::FinishTasks)
+                        .after(BackgroundJobSystems::Poll),
+                    material::apply_terrain_debug_mode,
                 ),
             );
 
@@ -48,4 +74,8 @@ pub enum TilesetSystemSets {
     /// System set for polling the task process of tilesets actively being
     /// generated.
     FinishTasks,
+
+    /// System set for applying tilesets that were loaded or reloaded to the
+    /// active tilesets.
+    ReloadTilesets,
 }
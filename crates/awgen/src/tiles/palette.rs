@@ -0,0 +1,174 @@
+//! This module implements the [`TilePalette`] resource, which keeps the tile
+//! indices stored in block models stable when the tileset changes.
+//!
+//! Block faces store a palette ID rather than a tileset layer. Each palette ID
+//! names the tile image it was created for, and is resolved to the layer that
+//! tile currently occupies in the tileset. When the tileset is rebuilt with
+//! tiles inserted, removed, or reordered, only the palette is remapped, and the
+//! stored chunks do not need to change.
+//!
+//! The palette is stored in the settings table of the game database.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::database::backup::GameDatabaseRestored;
+use crate::database::{Database, DatabaseError, GameDatabase};
+use crate::map::VoxelChunk;
+
+/// The settings key for the tile palette.
+const TILE_PALETTE_KEY: &str = "tile_palette";
+
+/// A resource that maps the palette IDs stored in block models to the layers
+/// of the active tileset.
+///
+/// Palette IDs that are not part of the palette, such as in maps that were
+/// created before a tileset was built, map onto the layer with the same index.
+#[derive(Debug, Clone, Default, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TilePalette {
+    /// The asset path of the tileset that the palette maps onto, once one has
+    /// been built.
+    tileset: Option<String>,
+
+    /// The asset path of the tile image for each palette ID.
+    ids: Vec<String>,
+
+    /// The asset path of the tile image in each layer of the tileset.
+    tiles: Vec<String>,
+
+    /// The tileset layer of each palette ID, resolved from the tile paths.
+    #[serde(skip)]
+    layers: Vec<u32>,
+}
+
+impl TilePalette {
+    /// Loads the tile palette from the game database, if it has been stored.
+    pub fn load(database: &Database) -> Result<Option<Self>, DatabaseError> {
+        let Some(value) = database.get_setting(TILE_PALETTE_KEY)? else {
+            return Ok(None);
+        };
+
+        let mut palette: Self = serde_json::from_str(&value)?;
+        palette.resolve();
+        Ok(Some(palette))
+    }
+
+    /// Stores the tile palette in the game database.
+    pub fn save(&self, database: &Database) -> Result<(), DatabaseError> {
+        database.set_setting(TILE_PALETTE_KEY, &serde_json::to_string(self)?)
+    }
+
+    /// Gets the asset path of the tileset that the palette maps onto.
+    pub fn tileset(&self) -> Option<&str> {
+        self.tileset.as_deref()
+    }
+
+    /// Gets the tileset layer for the given palette ID.
+    pub fn layer(&self, id: u32) -> u32 {
+        self.layers.get(id as usize).copied().unwrap_or(id)
+    }
+
+    /// Gets the palette ID that refers to the given tileset layer.
+    pub fn id_of_layer(&self, layer: u32) -> u32 {
+        self.tiles
+            .get(layer as usize)
+            .and_then(|path| self.ids.iter().position(|id| id == path))
+            .map(|id| id as u32)
+            .unwrap_or(layer)
+    }
+
+    /// Remaps the palette after the tileset at the given asset path was built
+    /// from the given tiles, in layer order.
+    ///
+    /// Palette IDs keep pointing at the same tile images, wherever those tiles
+    /// now are in the tileset, and new tiles are given new palette IDs. The
+    /// first tileset that is built is adopted by the palette, and tilesets at
+    /// other paths are ignored. Returns true if the palette changed.
+    pub fn remap(&mut self, tileset: &str, tiles: Vec<String>) -> bool {
+        match &self.tileset {
+            Some(current) if current != tileset => return false,
+            Some(_) => {}
+            None => {
+                info!("Tile palette adopted tileset {}", tileset);
+                self.tileset = Some(tileset.to_string());
+            }
+        }
+
+        if self.tiles == tiles {
+            return false;
+        }
+
+        for tile in tiles.iter() {
+            if !self.ids.contains(tile) {
+                self.ids.push(tile.clone());
+            }
+        }
+
+        self.tiles = tiles;
+        self.resolve();
+        true
+    }
+
+    /// Resolves the tileset layer of each palette ID.
+    ///
+    /// Palette IDs whose tile was removed from the tileset fall back to the
+    /// first layer, and are restored if the tile is added back later.
+    fn resolve(&mut self) {
+        self.layers = self
+            .ids
+            .iter()
+            .enumerate()
+            .map(
+                |(id, path)| match self.tiles.iter().position(|tile| tile == path) {
+                    Some(layer) => layer as u32,
+                    None => {
+                        warn!("Tile {} of palette ID {} is not in the tileset", path, id);
+                        0
+                    }
+                },
+            )
+            .collect();
+    }
+}
+
+/// Loads the tile palette from the game database.
+pub(super) fn load_tile_palette(database: Res<GameDatabase>, mut palette: ResMut<TilePalette>) {
+    match TilePalette::load(&database) {
+        Ok(Some(loaded)) => *palette = loaded,
+        Ok(None) => {}
+        Err(err) => error!("Failed to load the tile palette: {}", err),
+    }
+}
+
+/// Reloads the tile palette when the game database has been restored from a
+/// backup.
+pub(super) fn reload_palette_on_restore(
+    mut restored: MessageReader<GameDatabaseRestored>,
+    database: Res<GameDatabase>,
+    mut palette: ResMut<TilePalette>,
+) {
+    if restored.read().count() == 0 {
+        return;
+    }
+
+    match TilePalette::load(&database) {
+        Ok(loaded) => *palette = loaded.unwrap_or_default(),
+        Err(err) => error!("Failed to reload the tile palette: {}", err),
+    }
+}
+
+/// Marks every chunk for remeshing when the tile palette changes, as the
+/// tileset layers of their faces may have moved.
+pub(super) fn remesh_on_palette_change(
+    palette: Res<TilePalette>,
+    mut chunks: Query<&mut VoxelChunk>,
+) {
+    if !palette.is_changed() || palette.is_added() {
+        return;
+    }
+
+    for mut chunk in chunks.iter_mut() {
+        chunk.mark_dirty();
+    }
+}
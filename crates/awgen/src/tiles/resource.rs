@@ -1,17 +1,131 @@
 //! This module implements the [`ActiveTilesets`] resource to Awgen.
 
 use bevy::prelude::*;
-use bevy::tasks::{Task, block_on, poll_once};
 
+use crate::database::GameDatabase;
+use crate::jobs::{BackgroundJobs, JobFinished, JobHandle, JobResult};
 use crate::map::VoxelChunk;
-use crate::tiles::TilesetMaterial;
+use crate::telemetry::{TILESETS_BUILT, UsageCounters};
 use crate::tiles::builder::TilesetBuilderError;
+use crate::tiles::{TilePalette, TilesetMaterial};
 
 /// This resource contains the currently active tilesets in the application.
 #[derive(Debug, Default, Resource)]
 pub struct ActiveTilesets {
     /// The opaque tileset material handle.
     pub opaque: Handle<TilesetMaterial>,
+
+    /// The layout of the opaque tileset image, once it has been loaded.
+    opaque_layout: Option<TilesetLayout>,
+}
+
+/// The dimensions of a tileset image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TilesetLayout {
+    /// The width and height of each tile, in pixels.
+    pub tile_size: u32,
+
+    /// The number of tiles in the tileset.
+    pub tile_count: u32,
+}
+
+impl TilesetLayout {
+    /// Gets the layout of the given tileset image.
+    pub fn of(image: &Image) -> Self {
+        let size = image.texture_descriptor.size;
+        Self {
+            tile_size: size.width,
+            tile_count: size.depth_or_array_layers,
+        }
+    }
+}
+
+/// A message that is written when the image of an active tileset is loaded or
+/// reloaded, such as when its file is changed on disk.
+#[derive(Debug, Clone, Message)]
+pub struct TilesetReloaded {
+    /// The tileset image that was reloaded.
+    pub image: AssetId<Image>,
+
+    /// Whether the tile size or tile count of the tileset changed, in which
+    /// case all chunks are remeshed.
+    pub layout_changed: bool,
+}
+
+/// System to apply tileset images and materials that were loaded or reloaded
+/// to the active tilesets.
+///
+/// Chunks are only remeshed when the layout of the tileset changed, as the
+/// meshes do not depend on the tile images themselves.
+pub(super) fn reload_active_tilesets(
+    mut image_events: MessageReader<AssetEvent<Image>>,
+    mut material_events: MessageReader<AssetEvent<TilesetMaterial>>,
+    images: Res<Assets<Image>>,
+    mut materials: ResMut<Assets<TilesetMaterial>>,
+    mut tilesets: ResMut<ActiveTilesets>,
+    mut chunks: Query<&mut VoxelChunk>,
+    mut reloaded: MessageWriter<TilesetReloaded>,
+) {
+    let material_changed = material_events
+        .read()
+        .filter(|event| event.is_modified(&tilesets.opaque))
+        .count()
+        > 0;
+
+    let changed_images = image_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let Some(image_id) = materials
+        .get(&tilesets.opaque)
+        .map(|material| material.texture.id())
+    else {
+        return;
+    };
+
+    let image_changed = changed_images.contains(&image_id);
+
+    if !image_changed {
+        if material_changed {
+            tilesets.set_changed();
+        }
+        return;
+    }
+
+    let Some(image) = images.get(image_id) else {
+        return;
+    };
+
+    let layout = TilesetLayout::of(image);
+    let layout_changed = tilesets
+        .opaque_layout
+        .is_some_and(|old_layout| old_layout != layout);
+    tilesets.opaque_layout = Some(layout);
+
+    // Touching the material forces its bind group to be rebuilt with the new
+    // texture.
+    materials.get_mut(&tilesets.opaque);
+
+    if layout_changed {
+        info!(
+            "Tileset layout changed to {} tiles of {}px; remeshing chunks.",
+            layout.tile_count, layout.tile_size
+        );
+        for mut chunk in chunks.iter_mut() {
+            chunk.mark_dirty();
+        }
+    } else {
+        debug!("Reloaded active tileset image.");
+    }
+
+    reloaded.write(TilesetReloaded {
+        image: image_id,
+        layout_changed,
+    });
 }
 
 /// System to update chunk models with the active tileset materials.
@@ -31,48 +145,104 @@ pub(super) fn update_chunk_models(
     }
 }
 
+/// The output of a tileset generation job.
+#[derive(Debug)]
+pub struct TilesetBuilt {
+    /// The handle of the image that the tileset is written to.
+    pub handle: Handle<Image>,
+
+    /// The asset path that the tileset is written to.
+    pub output_path: String,
+
+    /// The asset paths of the tiles in the tileset, in layer order.
+    pub tile_paths: Vec<String>,
+
+    /// The generated tileset image, or the error that prevented it from being
+    /// generated.
+    pub result: Result<Image, TilesetBuilderError>,
+}
+
 /// This resource tracks tilesets that are currently being generated.
 #[derive(Debug, Default, Resource)]
 pub struct GeneratingTilesets {
-    /// The tasks that are currently being processed to generate tilesets.
-    #[allow(clippy::type_complexity)]
-    tasks: Vec<Task<(Handle<Image>, Result<Image, TilesetBuilderError>)>>,
+    /// The background jobs that are currently generating tilesets, along with
+    /// the asset path of the tileset that each job writes to.
+    jobs: Vec<(String, JobHandle<TilesetBuilt>)>,
 }
 
 impl GeneratingTilesets {
-    /// Add a new tileset generation task.
-    pub fn add_task(&mut self, task: Task<(Handle<Image>, Result<Image, TilesetBuilderError>)>) {
-        self.tasks.push(task);
+    /// Add a new tileset generation job that writes to the given asset path.
+    ///
+    /// Any job that is still generating a tileset at the same path is
+    /// cancelled, as its result would be overwritten anyway.
+    pub fn add_job(
+        &mut self,
+        path: impl Into<String>,
+        job: JobHandle<TilesetBuilt>,
+        background_jobs: &BackgroundJobs,
+    ) {
+        let path = path.into();
+        for (_, old_job) in self.jobs.iter().filter(|(old_path, _)| *old_path == path) {
+            if background_jobs.cancel(old_job.id()) {
+                info!("Cancelling superseded tileset creation task for {}", path);
+            }
+        }
+
+        self.jobs.push((path, job));
+    }
+
+    /// Returns the number of tilesets that are currently being generated.
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    /// Returns true if no tilesets are currently being generated.
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
     }
 }
 
-/// System to poll and finish tileset generation tasks.
+/// System to finish tileset generation jobs.
 pub(super) fn finish_tileset_tasks(
+    mut finished: MessageMutator<JobFinished<TilesetBuilt>>,
     mut generating: ResMut<GeneratingTilesets>,
+    mut palette: ResMut<TilePalette>,
+    database: Res<GameDatabase>,
     mut images: ResMut<Assets<Image>>,
     mut materials: ResMut<Assets<TilesetMaterial>>,
+    mut counters: ResMut<UsageCounters>,
 ) {
-    generating.tasks.retain_mut(|task| {
-        if let Some((handle, result)) = block_on(poll_once(task)) {
-            match result {
-                Ok(image) => {
-                    info!("Tileset creation task completed successfully.");
-
-                    if let Some(img_asset) = images.get_mut(&handle) {
-                        *img_asset = image;
-
-                        // iter_mut() will force all materials to be updated
-                        for _ in materials.iter_mut() {}
-                    };
-                }
-                Err(err) => {
-                    error!("Failed to create tileset: {}", err);
+    for job in finished.read() {
+        generating.jobs.retain(|(_, handle)| *handle != job.handle);
+
+        let JobResult::Completed(built) = &mut job.result else {
+            info!("Tileset creation task was cancelled.");
+            continue;
+        };
+
+        match &mut built.result {
+            Ok(image) => {
+                info!("Tileset creation task completed successfully.");
+                counters.increment(TILESETS_BUILT);
+
+                let tile_paths = std::mem::take(&mut built.tile_paths);
+                if palette.remap(&built.output_path, tile_paths) {
+                    info!("Remapped the tile palette for {}", built.output_path);
+                    if let Err(err) = palette.save(&database) {
+                        error!("Failed to store the tile palette: {}", err);
+                    }
                 }
-            }
 
-            return false;
-        }
+                if let Some(img_asset) = images.get_mut(&built.handle) {
+                    *img_asset = std::mem::take(image);
 
-        true
-    });
+                    // iter_mut() will force all materials to be updated
+                    for _ in materials.iter_mut() {}
+                };
+            }
+            Err(err) => {
+                error!("Failed to create tileset: {}", err);
+            }
+        }
+    }
 }
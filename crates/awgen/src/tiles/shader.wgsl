@@ -4,6 +4,16 @@
     mesh_functions::mesh_normal_local_to_world,
 }
 
+#ifndef PREPASS_PIPELINE
+#ifdef DISTANCE_FOG
+#import bevy_pbr::{
+    mesh_view_bindings as view_bindings,
+    mesh_view_types::FOG_MODE_OFF,
+    pbr_functions::apply_fog,
+}
+#endif
+#endif
+
 struct VertexInput {
     @builtin(instance_index) instance_index: u32,
     @location(0) position: vec3<f32>,
@@ -17,6 +27,7 @@ struct VertexOutput {
     @location(0) normal: vec3<f32>,
     @location(1) uv: vec3<f32>,
     @location(2) color: vec4<f32>,
+    @location(3) world_position: vec3<f32>,
 };
 
 struct FragmentOutput {
@@ -26,13 +37,21 @@ struct FragmentOutput {
 @group(#{MATERIAL_BIND_GROUP}) @binding(0) var texture: texture_2d_array<f32>;
 @group(#{MATERIAL_BIND_GROUP}) @binding(1) var texture_sampler: sampler;
 
+// The color of back faces in debug modes, which have a broken winding.
+const BACK_FACE_COLOR: vec4<f32> = vec4<f32>(1.0, 0.0, 1.0, 1.0);
+
+// The color of the face edges drawn in the wireframe debug mode.
+const WIREFRAME_COLOR: vec4<f32> = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+
 @vertex
 fn vertex(input: VertexInput) -> VertexOutput {
     var output: VertexOutput;
+    let world_from_local = get_world_from_local(input.instance_index);
     output.position = mesh_position_local_to_clip(
-        get_world_from_local(input.instance_index),
+        world_from_local,
         vec4<f32>(input.position, 1.0),
     );
+    output.world_position = (world_from_local * vec4<f32>(input.position, 1.0)).xyz;
     output.normal = mesh_normal_local_to_world(
         input.normal,
         input.instance_index
@@ -43,7 +62,10 @@ fn vertex(input: VertexInput) -> VertexOutput {
 }
 
 @fragment
-fn fragment(input: VertexOutput) -> FragmentOutput {
+fn fragment(
+    input: VertexOutput,
+    @builtin(front_facing) front_facing: bool,
+) -> FragmentOutput {
     var output: FragmentOutput;
     output.color = textureSample(
         texture,
@@ -51,5 +73,42 @@ fn fragment(input: VertexOutput) -> FragmentOutput {
         input.uv.xy,
         i32(input.uv.z)
     ) * input.color;
+
+#ifndef PREPASS_PIPELINE
+#ifdef DISTANCE_FOG
+    // Distant terrain fades into the fog, rather than ending abruptly at the
+    // edge of the loaded chunks.
+    if view_bindings::fog.mode != FOG_MODE_OFF {
+        output.color = apply_fog(
+            view_bindings::fog,
+            output.color,
+            input.world_position,
+            view_bindings::view.world_position,
+        );
+    }
+#endif
+#endif
+
+#ifdef TERRAIN_DEBUG_NORMALS
+    output.color = vec4<f32>(normalize(input.normal) * 0.5 + 0.5, 1.0);
+#endif
+
+#ifdef TERRAIN_DEBUG_WIREFRAME
+    // Block faces are aligned to the block grid, so their edges are where the
+    // position along either axis of the face is a whole number.
+    let grid = fract(input.world_position);
+    let grid_distance = min(grid, 1.0 - grid) / fwidth(input.world_position);
+    let face_axis = step(vec3<f32>(0.5), abs(input.normal));
+    let edge = grid_distance + face_axis * 1000.0;
+    let line = 1.0 - smoothstep(0.5, 1.5, min(edge.x, min(edge.y, edge.z)));
+    output.color = mix(output.color, WIREFRAME_COLOR, line);
+#endif
+
+#ifdef TERRAIN_DEBUG
+    if !front_facing {
+        output.color = BACK_FACE_COLOR;
+    }
+#endif
+
     return output;
 }
@@ -0,0 +1,159 @@
+//! This module implements the accessibility settings of the editor.
+//!
+//! The accessibility options of the UI are changed with the "Toggle High
+//! Contrast", "Toggle Reduced Motion", "Toggle Stronger Interaction States",
+//! and "Cycle Color-Blind Palette" commands. Open menus are restyled as soon as
+//! an option changes.
+//!
+//! The options are stored in the settings table of the game database, and are
+//! loaded when the game starts.
+
+use awgen_ui::accessibility::{AccessibilityOptions, ColorBlindPalette, InteractionDeltas};
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::database::{Database, DatabaseError, GameDatabase};
+
+/// The settings key for the accessibility options.
+const ACCESSIBILITY_KEY: &str = "accessibility";
+
+/// The interaction deltas used while stronger interaction states are enabled,
+/// which spread the states further apart than the editor theme does.
+const STRONG_INTERACTION_DELTAS: InteractionDeltas = InteractionDeltas {
+    hovered: 0.25,
+    pressed: 0.25,
+    checked: 0.2,
+};
+
+/// Plugin that loads, stores, and changes the accessibility options.
+pub struct AccessibilitySettingsPlugin;
+impl Plugin for AccessibilitySettingsPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_systems(Startup, load_options)
+            .add_systems(
+                Update,
+                save_options.run_if(resource_changed::<AccessibilityOptions>),
+            )
+            .register_command(
+                PaletteCommand::new("settings.high_contrast", "Toggle High Contrast"),
+                toggle_high_contrast,
+            )
+            .register_command(
+                PaletteCommand::new("settings.reduce_motion", "Toggle Reduced Motion"),
+                toggle_reduce_motion,
+            )
+            .register_command(
+                PaletteCommand::new(
+                    "settings.interaction_states",
+                    "Toggle Stronger Interaction States",
+                ),
+                toggle_interaction_deltas,
+            )
+            .register_command(
+                PaletteCommand::new("settings.color_palette", "Cycle Color-Blind Palette"),
+                cycle_palette,
+            );
+    }
+}
+
+/// The accessibility options, as they are stored in the game database.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct StoredOptions {
+    /// Whether containers are replaced by their high-contrast variant.
+    high_contrast: bool,
+
+    /// The name of the color palette that all theme colors are adapted to.
+    palette: String,
+
+    /// Whether the interaction states of interactive colors are spread
+    /// further apart.
+    stronger_interactions: bool,
+
+    /// Whether color, scroll, and spinner animations are disabled.
+    reduce_motion: bool,
+}
+
+impl StoredOptions {
+    /// Loads the accessibility options from the game database, falling back to
+    /// the default options if none have been stored.
+    fn load(database: &Database) -> Result<AccessibilityOptions, DatabaseError> {
+        let Some(value) = database.get_setting(ACCESSIBILITY_KEY)? else {
+            return Ok(AccessibilityOptions::default());
+        };
+
+        let stored: StoredOptions = serde_json::from_str(&value)?;
+        let palette = ColorBlindPalette::ALL
+            .into_iter()
+            .find(|palette| palette.name() == stored.palette)
+            .unwrap_or_default();
+
+        Ok(AccessibilityOptions {
+            high_contrast: stored.high_contrast,
+            palette,
+            interaction_deltas: stored
+                .stronger_interactions
+                .then_some(STRONG_INTERACTION_DELTAS),
+            reduce_motion: stored.reduce_motion,
+        })
+    }
+
+    /// Stores the given accessibility options in the game database.
+    fn save(options: &AccessibilityOptions, database: &Database) -> Result<(), DatabaseError> {
+        let stored = StoredOptions {
+            high_contrast: options.high_contrast,
+            palette: options.palette.name().to_string(),
+            stronger_interactions: options.interaction_deltas.is_some(),
+            reduce_motion: options.reduce_motion,
+        };
+
+        let value = serde_json::to_string(&stored)?;
+        database.set_setting(ACCESSIBILITY_KEY, &value)
+    }
+}
+
+/// Loads the accessibility options from the game database.
+fn load_options(database: Res<GameDatabase>, mut options: ResMut<AccessibilityOptions>) {
+    match StoredOptions::load(&database) {
+        Ok(loaded) => *options = loaded,
+        Err(err) => warn!("Failed to load the accessibility options: {}", err),
+    }
+}
+
+/// Stores the accessibility options in the game database when they change.
+fn save_options(database: Res<GameDatabase>, options: Res<AccessibilityOptions>) {
+    // The options were just initialized or loaded, so they are already stored.
+    if options.is_added() || database.is_read_only() {
+        return;
+    }
+
+    if let Err(err) = StoredOptions::save(&options, &database) {
+        warn!("Accessibility options will not persist: {}", err);
+    }
+}
+
+/// Toggles whether containers are replaced by their high-contrast variant.
+fn toggle_high_contrast(mut options: ResMut<AccessibilityOptions>) {
+    options.high_contrast = !options.high_contrast;
+}
+
+/// Toggles whether color, scroll, and spinner animations are disabled.
+fn toggle_reduce_motion(mut options: ResMut<AccessibilityOptions>) {
+    options.reduce_motion = !options.reduce_motion;
+}
+
+/// Toggles whether the interaction states of interactive colors are spread
+/// further apart than the editor theme spreads them.
+fn toggle_interaction_deltas(mut options: ResMut<AccessibilityOptions>) {
+    options.interaction_deltas = match options.interaction_deltas {
+        Some(_) => None,
+        None => Some(STRONG_INTERACTION_DELTAS),
+    };
+}
+
+/// Switches to the next color-blind palette.
+fn cycle_palette(mut options: ResMut<AccessibilityOptions>) {
+    options.palette = options.palette.next();
+    info!("Using the {} color palette", options.palette.name());
+}
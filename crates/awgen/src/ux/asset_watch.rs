@@ -0,0 +1,320 @@
+//! This module implements watching the project assets for changes on behalf of
+//! the script engine.
+//!
+//! Scripts in editor mode subscribe to an asset path, such as
+//! `project://tiles/` for every asset in a folder, and are sent a
+//! [`PacketOut::AssetChanged`] packet whenever a record of the project asset
+//! database under that path is added, modified, or removed. This lets editor
+//! scripts react to imports and edits, such as by rebuilding a tileset whenever
+//! one of its tile images changes.
+//!
+//! The changes are read from the [`AssetRecordChanged`] messages of the project
+//! asset database. The asset path of each record is kept while at least one
+//! subscription exists, so that removed and renamed assets are reported under
+//! the path they had. Every subscription is dropped when leaving the editor.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use awgen_asset_db::param::AwgenAssets;
+use awgen_asset_db::prelude::{AssetDatabaseName, AssetRecordID};
+use awgen_asset_db::watcher::{AssetRecordChange, AssetRecordChanged};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::app::AwgenState;
+use crate::database::ProjectAssets;
+use crate::scripts::{PacketOut, ScriptEngine};
+
+/// Plugin that sets up watching the project assets for scripts.
+pub struct AssetWatchPlugin;
+impl Plugin for AssetWatchPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<AssetWatch>()
+            .add_systems(
+                Update,
+                send_asset_changes.run_if(in_state(AwgenState::Editor)),
+            )
+            .add_systems(OnExit(AwgenState::Editor), clear_subscriptions);
+    }
+}
+
+/// How an asset changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum AssetChangeKind {
+    /// The asset was created, or another asset was renamed to its path.
+    Added,
+
+    /// The data or metadata of the asset was modified.
+    Modified,
+
+    /// The asset was removed, or renamed to another path.
+    Removed,
+}
+
+/// A resource that holds the asset paths that scripts subscribed to, along
+/// with the asset path of each record of the project asset database.
+#[derive(Debug, Default, Resource)]
+pub struct AssetWatch {
+    /// The asset paths that scripts subscribed to.
+    subscriptions: BTreeSet<String>,
+
+    /// The asset path of each asset record, while there are any
+    /// subscriptions, or `None` until they are listed.
+    asset_paths: Option<HashMap<AssetRecordID, String>>,
+}
+
+impl AssetWatch {
+    /// Returns true if the given string is an asset path that can be
+    /// subscribed to, which is any path within the project asset database.
+    pub fn is_valid_path(path: &str) -> bool {
+        path.strip_prefix(ProjectAssets::database_name())
+            .is_some_and(|p| p.starts_with("://"))
+    }
+
+    /// Subscribes to changes of the assets at or within the given asset path.
+    ///
+    /// Returns false if the path is already subscribed to.
+    pub fn subscribe(&mut self, path: String) -> bool {
+        self.subscriptions.insert(path)
+    }
+
+    /// Unsubscribes from changes of the given asset path, forgetting the asset
+    /// paths of the records once no subscriptions are left.
+    ///
+    /// Returns false if the path was not subscribed to.
+    pub fn unsubscribe(&mut self, path: &str) -> bool {
+        let removed = self.subscriptions.remove(path);
+        if self.subscriptions.is_empty() {
+            self.asset_paths = None;
+        }
+        removed
+    }
+
+    /// Returns true if any subscription matches the given asset path.
+    fn is_subscribed(&self, path: &str) -> bool {
+        self.subscriptions
+            .iter()
+            .any(|subscription| path_matches(subscription, path))
+    }
+
+    /// Records the asset path that the asset record with the given ID now has,
+    /// or `None` if it was removed, and returns the changes of the subscribed
+    /// asset paths.
+    ///
+    /// An asset whose path changed is reported as removed from its old path
+    /// and added at its new path.
+    fn record_change(
+        &mut self,
+        id: AssetRecordID,
+        path: Option<String>,
+    ) -> Vec<(String, AssetChangeKind)> {
+        let Some(asset_paths) = self.asset_paths.as_mut() else {
+            return Vec::new();
+        };
+
+        let old = asset_paths.remove(&id);
+        if let Some(path) = &path {
+            asset_paths.insert(id, path.clone());
+        }
+
+        let changes = match (old, path) {
+            (None, None) => Vec::new(),
+            (None, Some(new)) => vec![(new, AssetChangeKind::Added)],
+            (Some(old), None) => vec![(old, AssetChangeKind::Removed)],
+            (Some(old), Some(new)) if old == new => vec![(new, AssetChangeKind::Modified)],
+            (Some(old), Some(new)) => vec![
+                (old, AssetChangeKind::Removed),
+                (new, AssetChangeKind::Added),
+            ],
+        };
+
+        changes
+            .into_iter()
+            .filter(|(path, _)| self.is_subscribed(path))
+            .collect()
+    }
+}
+
+/// Returns true if the given asset path is the subscribed path, or lies within
+/// it. Whole path segments are matched, so `project://tiles` matches
+/// `project://tiles/grass.png` but not `project://tiles2/grass.png`.
+fn path_matches(subscription: &str, path: &str) -> bool {
+    if path == subscription {
+        return true;
+    }
+
+    let folder = subscription.trim_end_matches('/');
+    path.strip_prefix(folder)
+        .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Gets the asset path of a record of the project asset database with the
+/// given pathname.
+fn asset_path(pathname: &Path) -> String {
+    let pathname = pathname.to_string_lossy().replace('\\', "/");
+    format!("{}://{}", ProjectAssets::database_name(), pathname)
+}
+
+/// System that sends the changes of subscribed assets to the script engine.
+///
+/// The asset paths of the records are listed once a subscription exists, so
+/// that changes made before then are not reported.
+fn send_asset_changes(
+    mut changes: MessageReader<AssetRecordChanged<ProjectAssets>>,
+    assets: AwgenAssets<ProjectAssets>,
+    mut watch: ResMut<AssetWatch>,
+    engine: Res<ScriptEngine>,
+) {
+    if watch.subscriptions.is_empty() {
+        changes.clear();
+        return;
+    }
+
+    if watch.asset_paths.is_none() {
+        changes.clear();
+        match assets.list_assets() {
+            Ok(records) => {
+                let asset_paths = records
+                    .into_iter()
+                    .map(|record| (record.id, asset_path(&record.pathname)))
+                    .collect();
+                watch.asset_paths = Some(asset_paths);
+            }
+            Err(err) => error!("Failed to list the assets to watch: {}", err),
+        }
+        return;
+    }
+
+    for change in changes.read() {
+        let path = match &change.change {
+            AssetRecordChange::Removed => None,
+            // Renamed assets are also reported as modified, which moves them
+            // to their new path.
+            AssetRecordChange::Renamed { .. } => continue,
+            AssetRecordChange::Added | AssetRecordChange::Modified => {
+                match assets.get_asset(change.id) {
+                    Ok(record) => record.map(|record| asset_path(&record.pathname)),
+                    Err(err) => {
+                        error!("Failed to read asset {}: {}", change.id, err);
+                        continue;
+                    }
+                }
+            }
+        };
+
+        for (id, kind) in watch.record_change(change.id, path) {
+            debug!("Asset {} changed: {:?}", id, kind);
+            if let Err(err) = engine.send(PacketOut::AssetChanged { id, kind }) {
+                error!("Failed to send asset change to script engine: {}", err);
+            }
+        }
+    }
+}
+
+/// Drops every subscription when leaving the editor.
+fn clear_subscriptions(mut watch: ResMut<AssetWatch>) {
+    watch.subscriptions.clear();
+    watch.asset_paths = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a watch subscribed to the given paths, with the asset paths of
+    /// the records listed.
+    fn watch(subscriptions: &[&str]) -> AssetWatch {
+        let mut watch = AssetWatch {
+            asset_paths: Some(HashMap::new()),
+            ..default()
+        };
+        for subscription in subscriptions {
+            watch.subscribe(subscription.to_string());
+        }
+        watch
+    }
+
+    #[test]
+    fn subscriptions_match_whole_path_segments() {
+        assert!(path_matches("project://tiles", "project://tiles"));
+        assert!(path_matches("project://tiles", "project://tiles/grass.png"));
+        assert!(path_matches(
+            "project://tiles/",
+            "project://tiles/grass.png"
+        ));
+        assert!(path_matches("project://", "project://tiles/grass.png"));
+        assert!(!path_matches(
+            "project://tiles",
+            "project://tiles2/grass.png"
+        ));
+        assert!(!path_matches(
+            "project://tiles/",
+            "project://tiles2/grass.png"
+        ));
+        assert!(!path_matches(
+            "project://tiles/grass",
+            "project://tiles/grass.png"
+        ));
+    }
+
+    #[test]
+    fn only_project_paths_are_valid() {
+        assert!(AssetWatch::is_valid_path("project://"));
+        assert!(AssetWatch::is_valid_path("project://tiles/"));
+        assert!(!AssetWatch::is_valid_path("project:/tiles"));
+        assert!(!AssetWatch::is_valid_path("game://tiles/"));
+    }
+
+    #[test]
+    fn record_changes_are_reported_by_path() {
+        let mut watch = watch(&["project://tiles"]);
+        let grass = AssetRecordID::new();
+        let path = |p: &str| Some(format!("project://{p}"));
+
+        assert_eq!(
+            watch.record_change(grass, path("tiles/grass.png")),
+            vec![("project://tiles/grass.png".into(), AssetChangeKind::Added)]
+        );
+        assert_eq!(
+            watch.record_change(grass, path("tiles/grass.png")),
+            vec![(
+                "project://tiles/grass.png".into(),
+                AssetChangeKind::Modified
+            )]
+        );
+        assert_eq!(
+            watch.record_change(grass, path("tiles/moss.png")),
+            vec![
+                ("project://tiles/grass.png".into(), AssetChangeKind::Removed),
+                ("project://tiles/moss.png".into(), AssetChangeKind::Added),
+            ]
+        );
+        assert_eq!(
+            watch.record_change(grass, None),
+            vec![("project://tiles/moss.png".into(), AssetChangeKind::Removed)]
+        );
+        assert!(watch.record_change(grass, None).is_empty());
+    }
+
+    #[test]
+    fn unsubscribed_changes_are_not_reported() {
+        let mut watch = watch(&["project://tiles"]);
+        let stone = AssetRecordID::new();
+
+        let changes = watch.record_change(stone, Some("project://tiles2/stone.png".into()));
+        assert!(changes.is_empty());
+
+        // Moving the asset into the watched folder reports it as added there.
+        assert_eq!(
+            watch.record_change(stone, Some("project://tiles/stone.png".into())),
+            vec![("project://tiles/stone.png".into(), AssetChangeKind::Added)]
+        );
+
+        assert!(watch.unsubscribe("project://tiles"));
+        assert!(watch.asset_paths.is_none());
+        assert!(watch.record_change(stone, None).is_empty());
+    }
+}
@@ -0,0 +1,250 @@
+//! This module implements the [`AtmosphereSettings`] resource, which configures
+//! the distance fog over the terrain and the sky gradient drawn behind it.
+//!
+//! Without fog, the terrain ends abruptly at the edge of the loaded chunks.
+//! Fog fades distant blocks into the fog color, which usually matches the
+//! bottom of the sky, so that the map blends into the background instead.
+//!
+//! The settings are loaded from the settings table of the game database when
+//! the game starts. Scripts may replace them with the `setAtmosphere` packet,
+//! which only changes the atmosphere until the game is closed, such as for
+//! weather or the time of day. The current settings are only stored in the
+//! project by the "Save Atmosphere to Project Settings" command.
+
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::database::{Database, DatabaseError, GameDatabase};
+use crate::ux::CameraController;
+
+/// The settings key for the atmosphere settings.
+const ATMOSPHERE_KEY: &str = "atmosphere";
+
+/// The render order of the camera that draws the sky, which must be drawn
+/// before the terrain.
+const SKY_CAMERA_ORDER: isize = -1;
+
+/// Plugin that sets up the distance fog and the sky gradient.
+pub struct AtmospherePlugin;
+impl Plugin for AtmospherePlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<AtmosphereSettings>()
+            .add_systems(Startup, (load_atmosphere, setup_sky).chain())
+            .add_systems(Update, apply_atmosphere)
+            .register_command(
+                PaletteCommand::new("settings.atmosphere", "Save Atmosphere to Project Settings"),
+                save_atmosphere,
+            );
+    }
+}
+
+/// A resource that describes the distance fog and the sky of the terrain view.
+///
+/// Colors are given as red, green, and blue components from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct AtmosphereSettings {
+    /// Whether distance fog is drawn over the terrain.
+    pub fog_enabled: bool,
+
+    /// The color that distant terrain fades into.
+    pub fog_color: [f32; 3],
+
+    /// The distance from the camera, in blocks, at which the fog starts.
+    pub fog_start: f32,
+
+    /// The distance from the camera, in blocks, at which the terrain is fully
+    /// hidden by the fog.
+    pub fog_end: f32,
+
+    /// The color of the sky at the top of the screen.
+    pub sky_top: [f32; 3],
+
+    /// The color of the sky at the bottom of the screen.
+    pub sky_bottom: [f32; 3],
+}
+
+impl Default for AtmosphereSettings {
+    fn default() -> Self {
+        Self {
+            fog_enabled: false,
+            fog_color: [0.0; 3],
+            fog_start: 64.0,
+            fog_end: 128.0,
+            sky_top: [0.0; 3],
+            sky_bottom: [0.0; 3],
+        }
+    }
+}
+
+impl AtmosphereSettings {
+    /// Loads and validates the atmosphere settings from the game database,
+    /// falling back to the default settings if none have been stored.
+    pub fn load(database: &Database) -> Result<Self, AtmosphereError> {
+        let Some(value) = database.get_setting(ATMOSPHERE_KEY)? else {
+            return Ok(Self::default());
+        };
+
+        let settings: Self = serde_json::from_str(&value).map_err(DatabaseError::from)?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Stores the atmosphere settings in the game database.
+    pub fn save(&self, database: &Database) -> Result<(), AtmosphereError> {
+        self.validate()?;
+        let value = serde_json::to_string(self).map_err(DatabaseError::from)?;
+        Ok(database.set_setting(ATMOSPHERE_KEY, &value)?)
+    }
+
+    /// Checks that the fog distances are in order and that every color
+    /// component is between 0 and 1.
+    pub fn validate(&self) -> Result<(), AtmosphereError> {
+        let start = self.fog_start;
+        let end = self.fog_end;
+        if !start.is_finite() || !end.is_finite() || start < 0.0 || end <= start {
+            return Err(AtmosphereError::InvalidFogDistance(start, end));
+        }
+
+        for color in [self.fog_color, self.sky_top, self.sky_bottom] {
+            if !color.iter().all(|c| (0.0 ..= 1.0).contains(c)) {
+                return Err(AtmosphereError::InvalidColor(color));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the distance fog described by these settings, or `None` if fog is
+    /// disabled.
+    pub fn fog(&self) -> Option<DistanceFog> {
+        if !self.fog_enabled {
+            return None;
+        }
+
+        Some(DistanceFog {
+            color: rgb(self.fog_color),
+            falloff: FogFalloff::Linear {
+                start: self.fog_start,
+                end: self.fog_end,
+            },
+            ..default()
+        })
+    }
+
+    /// Gets the gradient of the sky described by these settings.
+    pub fn sky(&self) -> BackgroundGradient {
+        BackgroundGradient::from(LinearGradient::to_bottom(vec![
+            ColorStop::auto(rgb(self.sky_top)),
+            ColorStop::auto(rgb(self.sky_bottom)),
+        ]))
+    }
+}
+
+/// An error that can occur while loading or storing the atmosphere settings.
+#[derive(Debug, thiserror::Error)]
+pub enum AtmosphereError {
+    /// The settings could not be read from or written to the database.
+    #[error("{0}")]
+    Database(#[from] DatabaseError),
+
+    /// The fog starts at a negative distance, or does not start before it ends.
+    #[error("Invalid fog distances: start {0} must not be negative, and must be before end {1}")]
+    InvalidFogDistance(f32, f32),
+
+    /// A color component is not between 0 and 1.
+    #[error("Invalid color {0:?}: components must be between 0 and 1")]
+    InvalidColor([f32; 3]),
+}
+
+/// A marker component for the node that draws the sky gradient.
+#[derive(Debug, Component)]
+struct SkyGradient;
+
+/// Converts the given red, green, and blue components to a color.
+fn rgb([r, g, b]: [f32; 3]) -> Color {
+    Color::srgb(r, g, b)
+}
+
+/// Loads the atmosphere settings from the game database.
+fn load_atmosphere(database: Res<GameDatabase>, mut settings: ResMut<AtmosphereSettings>) {
+    match AtmosphereSettings::load(&database) {
+        Ok(loaded) => *settings = loaded,
+        Err(err) => error!("Failed to load atmosphere settings: {}", err),
+    }
+}
+
+/// Stores the current atmosphere settings in the game database, so that the
+/// project starts with them.
+fn save_atmosphere(database: Res<GameDatabase>, settings: Res<AtmosphereSettings>) {
+    if database.is_read_only() {
+        warn!("Cannot save the atmosphere settings: the project is read-only");
+        return;
+    }
+
+    match settings.save(&database) {
+        Ok(()) => info!("Saved the atmosphere settings to the project settings"),
+        Err(err) => error!("Failed to save the atmosphere settings: {}", err),
+    }
+}
+
+/// Creates the camera and the full-screen node that draw the sky gradient
+/// behind the terrain.
+fn setup_sky(settings: Res<AtmosphereSettings>, mut commands: Commands) {
+    let camera = commands
+        .spawn((
+            Camera2d,
+            Camera {
+                order: SKY_CAMERA_ORDER,
+                ..default()
+            },
+        ))
+        .id();
+
+    commands.spawn((
+        SkyGradient,
+        UiTargetCamera(camera),
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        settings.sky(),
+        Pickable::IGNORE,
+    ));
+}
+
+/// Applies the atmosphere settings to the main camera and the sky whenever
+/// they change.
+///
+/// The main camera is drawn over the sky, so it must not clear the window.
+fn apply_atmosphere(
+    settings: Res<AtmosphereSettings>,
+    mut cameras: Query<(Entity, &mut Camera, Ref<CameraController>)>,
+    mut sky: Query<&mut BackgroundGradient, With<SkyGradient>>,
+    mut commands: Commands,
+) {
+    for (entity, mut camera, controller) in cameras.iter_mut() {
+        if !settings.is_changed() && !controller.is_added() {
+            continue;
+        }
+
+        camera.clear_color = ClearColorConfig::None;
+        match settings.fog() {
+            Some(fog) => {
+                commands.entity(entity).insert(fog);
+            }
+            None => {
+                commands.entity(entity).remove::<DistanceFog>();
+            }
+        }
+    }
+
+    if settings.is_changed() {
+        for mut gradient in sky.iter_mut() {
+            *gradient = settings.sky();
+        }
+    }
+}
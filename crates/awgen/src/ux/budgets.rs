@@ -0,0 +1,289 @@
+//! This module implements performance budget warnings for the editor.
+//!
+//! Each budget is a threshold for one of the engine diagnostics. When a
+//! diagnostic stays over its budget for a number of consecutive frames, a
+//! flashing warning chip naming the offending subsystem is shown in the corner
+//! of the screen, until the diagnostic is back within its budget.
+//!
+//! The budgets are set by scripts with the set performance budgets packet. They
+//! are stored in the settings table of the game database, and are loaded when
+//! the game starts.
+
+use std::f32::consts::PI;
+
+use awgen_ui::menus::layers::UiLayer;
+use awgen_ui::menus::overlay::ScreenAnchor;
+use bevy::diagnostic::{DiagnosticPath, DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::app::AwgenState;
+use crate::database::{Database, DatabaseError, GameDatabase};
+use crate::map::REMESH_TIME;
+use crate::scripts::PACKET_QUEUE_DEPTH;
+
+/// The settings key for the performance budgets.
+const PERFORMANCE_BUDGETS_KEY: &str = "performance_budgets";
+
+/// The text color of the warning chip.
+const WARNING_COLOR: Color = Color::srgb(1.0, 0.75, 0.2);
+
+/// The background color of the warning chip.
+const WARNING_BACKGROUND: Color = Color::srgba(0.3, 0.05, 0.05, 0.85);
+
+/// The number of times per second that the warning chip flashes.
+const FLASH_RATE: f32 = 2.0;
+
+/// Plugin that sets up the performance budget warnings.
+pub struct PerformanceBudgetPlugin;
+impl Plugin for PerformanceBudgetPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<PerformanceBudgets>()
+            .init_resource::<BudgetMonitor>()
+            .add_systems(Startup, load_budgets)
+            .add_systems(Update, monitor_budgets.run_if(in_state(AwgenState::Editor)))
+            .add_systems(OnExit(AwgenState::Editor), hide_warning);
+    }
+}
+
+/// A resource that holds the performance budgets of the engine diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PerformanceBudgets {
+    /// The budget for the frame time, in milliseconds.
+    pub frame_time: f64,
+
+    /// The budget for the time the main thread spends on chunk meshing each
+    /// frame, in milliseconds.
+    pub remesh_time: f64,
+
+    /// The budget for the number of script packets waiting to be processed at
+    /// the start of a frame.
+    pub packet_queue_depth: f64,
+
+    /// The number of consecutive frames that a diagnostic must be over its
+    /// budget before a warning is shown.
+    pub frames: u32,
+}
+
+impl Default for PerformanceBudgets {
+    fn default() -> Self {
+        Self {
+            frame_time: 33.3,
+            remesh_time: 8.0,
+            packet_queue_depth: 256.0,
+            frames: 30,
+        }
+    }
+}
+
+impl PerformanceBudgets {
+    /// Loads the performance budgets from the game database, falling back to
+    /// the default budgets if none have been stored.
+    pub fn load(database: &Database) -> Result<Self, DatabaseError> {
+        let Some(value) = database.get_setting(PERFORMANCE_BUDGETS_KEY)? else {
+            return Ok(Self::default());
+        };
+
+        Ok(serde_json::from_str(&value)?)
+    }
+
+    /// Stores the performance budgets in the game database.
+    pub fn save(&self, database: &Database) -> Result<(), DatabaseError> {
+        let value = serde_json::to_string(self)?;
+        database.set_setting(PERFORMANCE_BUDGETS_KEY, &value)
+    }
+
+    /// Checks that every budget is a positive number, and that warnings
+    /// require at least one frame over budget.
+    pub fn validate(&self) -> Result<(), BudgetError> {
+        for limit in [self.frame_time, self.remesh_time, self.packet_queue_depth] {
+            if !limit.is_finite() || limit <= 0.0 {
+                return Err(BudgetError::InvalidLimit(limit));
+            }
+        }
+
+        if self.frames == 0 {
+            return Err(BudgetError::NoFrames);
+        }
+
+        Ok(())
+    }
+}
+
+/// An error returned when performance budgets are invalid.
+#[derive(Debug, thiserror::Error)]
+pub enum BudgetError {
+    /// A budget is not a positive number.
+    #[error("Invalid budget {0}: budgets must be positive")]
+    InvalidLimit(f64),
+
+    /// Warnings would be shown without any frame over budget.
+    #[error("The number of frames over budget must be at least 1")]
+    NoFrames,
+}
+
+/// A subsystem whose diagnostic is checked against a budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Budget {
+    /// The frame time.
+    Frame,
+
+    /// The chunk meshing time.
+    Meshing,
+
+    /// The script packet queue depth.
+    Scripts,
+}
+
+impl Budget {
+    /// All budgets, in the order they are listed in the warning chip.
+    const ALL: [Budget; 3] = [Budget::Frame, Budget::Meshing, Budget::Scripts];
+
+    /// Gets the display name of the subsystem.
+    fn name(self) -> &'static str {
+        match self {
+            Budget::Frame => "Frame time",
+            Budget::Meshing => "Chunk meshing",
+            Budget::Scripts => "Script packets",
+        }
+    }
+
+    /// Gets the diagnostic that is checked against this budget.
+    fn diagnostic(self) -> &'static DiagnosticPath {
+        match self {
+            Budget::Frame => &FrameTimeDiagnosticsPlugin::FRAME_TIME,
+            Budget::Meshing => &REMESH_TIME,
+            Budget::Scripts => &PACKET_QUEUE_DEPTH,
+        }
+    }
+
+    /// Gets the threshold of this budget.
+    fn limit(self, budgets: &PerformanceBudgets) -> f64 {
+        match self {
+            Budget::Frame => budgets.frame_time,
+            Budget::Meshing => budgets.remesh_time,
+            Budget::Scripts => budgets.packet_queue_depth,
+        }
+    }
+
+    /// Formats a value of the diagnostic of this budget with its unit.
+    fn format(self, value: f64) -> String {
+        match self {
+            Budget::Frame | Budget::Meshing => format!("{:.1} ms", value),
+            Budget::Scripts => format!("{:.0} queued", value),
+        }
+    }
+}
+
+/// The state of the performance budget checks.
+#[derive(Debug, Default, Resource)]
+struct BudgetMonitor {
+    /// The number of consecutive frames that each budget has been exceeded, in
+    /// the order of [`Budget::ALL`].
+    streaks: [u32; 3],
+
+    /// The warning chip, while it is shown.
+    chip: Option<Entity>,
+}
+
+/// A marker component for the budget warning chip.
+#[derive(Debug, Component)]
+struct BudgetWarningChip;
+
+/// Loads the performance budgets from the game database.
+fn load_budgets(database: Res<GameDatabase>, mut budgets: ResMut<PerformanceBudgets>) {
+    match PerformanceBudgets::load(&database) {
+        Ok(loaded) => *budgets = loaded,
+        Err(err) => warn!("Failed to load the performance budgets: {}", err),
+    }
+}
+
+/// Checks the diagnostics against their budgets, and shows the warning chip
+/// while any budget has been exceeded for enough consecutive frames.
+fn monitor_budgets(
+    time: Res<Time>,
+    store: Res<DiagnosticsStore>,
+    budgets: Res<PerformanceBudgets>,
+    mut monitor: ResMut<BudgetMonitor>,
+    mut chips: Query<(&mut Text, &mut TextColor), With<BudgetWarningChip>>,
+    mut commands: Commands,
+) {
+    let mut warnings = Vec::new();
+    for (budget, streak) in Budget::ALL.into_iter().zip(monitor.streaks.iter_mut()) {
+        let limit = budget.limit(&budgets);
+        let value = store
+            .get(budget.diagnostic())
+            .and_then(|diagnostic| diagnostic.value())
+            .filter(|value| *value > limit);
+
+        let Some(value) = value else {
+            *streak = 0;
+            continue;
+        };
+
+        *streak = streak.saturating_add(1);
+        if *streak >= budgets.frames {
+            warnings.push(format!(
+                "{} over budget: {} / {}",
+                budget.name(),
+                budget.format(value),
+                budget.format(limit)
+            ));
+        }
+    }
+
+    if warnings.is_empty() {
+        hide_chip(&mut monitor, &mut commands);
+        return;
+    }
+
+    let text = warnings.join("\n");
+    let flash = (time.elapsed_secs() * FLASH_RATE * PI).sin().abs();
+    let color = WARNING_COLOR.with_alpha(0.5 + flash * 0.5);
+
+    if let Some((mut chip_text, mut chip_color)) =
+        monitor.chip.and_then(|chip| chips.get_mut(chip).ok())
+    {
+        if chip_text.0 != text {
+            chip_text.0 = text;
+        }
+        chip_color.0 = color;
+        return;
+    }
+
+    let chip = commands
+        .spawn((
+            BudgetWarningChip,
+            ScreenAnchor::BottomRight,
+            Node {
+                margin: UiRect::all(px(8.0)),
+                ..default()
+            },
+            UiLayer::Toasts,
+            Text::new(text),
+            TextColor(color),
+            TextBackgroundColor(WARNING_BACKGROUND),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+        ))
+        .id();
+    monitor.chip = Some(chip);
+}
+
+/// Hides the warning chip and resets the budget checks when leaving the
+/// editor.
+fn hide_warning(mut monitor: ResMut<BudgetMonitor>, mut commands: Commands) {
+    monitor.streaks = [0; 3];
+    hide_chip(&mut monitor, &mut commands);
+}
+
+/// Despawns the warning chip, if it is shown.
+fn hide_chip(monitor: &mut BudgetMonitor, commands: &mut Commands) {
+    if let Some(chip) = monitor.chip.take() {
+        commands.entity(chip).despawn();
+    }
+}
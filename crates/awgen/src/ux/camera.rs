@@ -1,8 +1,20 @@
 //! This module implements camera functionality to the game engine.
 
+use awgen_ui::hotkeys::{BindHotkeyExt, Hotkey, HotkeyContext};
+use awgen_ui::ime::ImeFocus;
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
 use bevy::input::keyboard::KeyboardInput;
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::database::bookmarks::Bookmark;
+use crate::map::{BlockModel, ChunkTable, VoxelChunk, WorldPos, raycast_blocks};
+
+/// The vertical field of view of the camera in perspective mode, in radians.
+const PERSPECTIVE_FOV: f32 = std::f32::consts::FRAC_PI_4;
 
 /// This plugin implements camera functionality to the game engine.
 pub struct CameraPlugin;
@@ -21,6 +33,15 @@ impl Plugin for CameraPlugin {
             .configure_sets(
                 Update,
                 CameraSystems::Controls.before(CameraSystems::UpdatePosition),
+            )
+            .register_command(
+                PaletteCommand::new("camera.projection", "Toggle Camera Projection"),
+                toggle_projection,
+            )
+            .bind_hotkey(
+                "camera.projection",
+                Hotkey::new(KeyCode::KeyP),
+                HotkeyContext::Global,
             );
     }
 }
@@ -42,6 +63,19 @@ pub enum CameraSystems {
     UpdatePosition,
 }
 
+/// The kind of projection used by the camera.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum CameraProjection {
+    /// An orthographic projection, where objects keep their size regardless of
+    /// their distance from the camera.
+    #[default]
+    Orthographic,
+
+    /// A perspective projection, where distant objects appear smaller.
+    Perspective,
+}
+
 /// This component is used to control the camera position, rotation, scale, and
 /// distance.
 #[derive(Debug, Component)]
@@ -71,6 +105,10 @@ pub struct CameraController {
 
     /// Current distance of the camera from the origin.
     ///
+    /// This is the height of the view at the origin, so the framing of the
+    /// origin is the same in every projection. In perspective mode, the camera
+    /// is placed further away to match.
+    ///
     /// In most situations, this value should not be modified directly. It is
     /// recommended to modify the `targetDist` instead, letting the camera
     /// smoothly interpolate to the new distance.
@@ -99,6 +137,25 @@ pub struct CameraController {
 
     /// Sensitivity for rotating the camera with the mouse.
     pub pan_sensitivity: f32,
+
+    /// The kind of projection used by the camera.
+    pub projection: CameraProjection,
+
+    /// Whether the camera is pulled in front of terrain that blocks its view of
+    /// the origin.
+    pub collision: bool,
+
+    /// The distance to keep between the camera and terrain it collides with.
+    pub collision_padding: f32,
+
+    /// Smoothing factor for the camera moving back out after a collision.
+    pub collision_smoothing: f32,
+
+    /// Current distance of the camera from the origin, after collision with
+    /// terrain is applied.
+    ///
+    /// This value is updated every frame, and should not be modified directly.
+    pub collision_dist: f32,
 }
 
 impl Default for CameraController {
@@ -123,6 +180,13 @@ impl Default for CameraController {
 
             zoom_sensitivity: 1.0,
             pan_sensitivity: 1.0,
+
+            projection: CameraProjection::Orthographic,
+
+            collision: false,
+            collision_padding: 0.5,
+            collision_smoothing: 0.01,
+            collision_dist: 16.0,
         }
     }
 }
@@ -151,10 +215,58 @@ impl CameraController {
         )
     }
 
+    /// Updates the distance of the camera after collision with terrain, given
+    /// the distance from the origin to the nearest obstruction between the
+    /// origin and the camera, if any.
+    ///
+    /// The camera is pulled in front of an obstruction immediately, so terrain
+    /// never hides the origin, and smoothly moves back out once the view is
+    /// clear.
+    pub fn update_collision(&mut self, delta: f32, obstruction: Option<f32>) {
+        let target = match obstruction {
+            Some(distance) => (distance - self.collision_padding).clamp(0.0, self.camera_dist()),
+            None => self.camera_dist(),
+        };
+
+        if target < self.collision_dist {
+            self.collision_dist = target;
+        } else {
+            let t = (1.0 - self.collision_smoothing.powf(delta)).clamp(0.0, 1.0);
+            self.collision_dist = self.collision_dist.lerp(target, t);
+        }
+    }
+
+    /// Gets the distance of the camera from the origin that frames the view
+    /// height given by `dist` in the current projection, ignoring collision.
+    pub fn camera_dist(&self) -> f32 {
+        match self.projection {
+            CameraProjection::Orthographic => self.dist,
+            CameraProjection::Perspective => self.dist / (2.0 * (PERSPECTIVE_FOV * 0.5).tan()),
+        }
+    }
+
+    /// Gets the current distance of the camera from the origin, accounting for
+    /// the projection and for collision with terrain if it is enabled.
+    pub fn view_dist(&self) -> f32 {
+        if self.collision {
+            self.collision_dist.min(self.camera_dist())
+        } else {
+            self.camera_dist()
+        }
+    }
+
+    /// Switches between the orthographic and perspective projections.
+    pub fn toggle_projection(&mut self) {
+        self.projection = match self.projection {
+            CameraProjection::Orthographic => CameraProjection::Perspective,
+            CameraProjection::Perspective => CameraProjection::Orthographic,
+        };
+    }
+
     /// Gets the current true position of the camera, accounting for
     /// rotation and distance.
     pub fn translation(&self) -> Vec3 {
-        self.pos + self.rotation() * Vec3::new(0.0, 0.0, -self.dist)
+        self.pos + self.rotation() * Vec3::new(0.0, 0.0, -self.view_dist())
     }
 
     /// Gets the origin point of the camera, which is the position
@@ -198,6 +310,32 @@ impl CameraController {
     pub fn rotate_ccw(&mut self) {
         self.target_rot.y -= 90.0;
     }
+
+    /// Creates a bookmark with the given name from the target position,
+    /// rotation, and distance of the camera.
+    pub fn bookmark(&self, name: impl Into<String>) -> Bookmark {
+        let pos = self.target_pos.floor().as_ivec3();
+        Bookmark {
+            name: name.into(),
+            pos: WorldPos::new(pos.x, pos.y, pos.z),
+            pitch: self.target_rot.x,
+            yaw: self.target_rot.y.rem_euclid(360.0),
+            distance: self.target_dist,
+        }
+    }
+
+    /// Moves the camera to the given bookmark.
+    ///
+    /// The camera smoothly interpolates to the new location, turning the
+    /// shortest way around to reach the bookmarked yaw.
+    pub fn jump_to(&mut self, bookmark: &Bookmark) {
+        let yaw_delta = (bookmark.yaw - self.target_rot.y + 180.0).rem_euclid(360.0) - 180.0;
+
+        self.target_pos = bookmark.pos.as_vec3();
+        self.target_rot.x = bookmark.pitch;
+        self.target_rot.y += yaw_delta;
+        self.target_dist = bookmark.distance.clamp(self.min_zoom, self.max_zoom);
+    }
 }
 
 /// Creates the main camera on startup.
@@ -207,33 +345,78 @@ fn setup_camera(mut commands: Commands) {
         Camera3d::default(),
         CameraController::default(),
         Transform::default(),
-        Projection::Orthographic(OrthographicProjection {
-            near: -1000.0,
-            far: 1000.0,
-            scaling_mode: bevy::camera::ScalingMode::FixedVertical {
-                viewport_height: 1.0,
-            },
-            scale: 1.0,
-            viewport_origin: Vec2::new(0.5, 0.5),
-            area: Rect::new(-1.0, -1.0, 1.0, 1.0),
-        }),
+        orthographic_projection(),
     ));
 }
 
+/// Creates the projection used by the camera in orthographic mode.
+fn orthographic_projection() -> Projection {
+    Projection::Orthographic(OrthographicProjection {
+        near: -1000.0,
+        far: 1000.0,
+        scaling_mode: bevy::camera::ScalingMode::FixedVertical {
+            viewport_height: 1.0,
+        },
+        scale: 1.0,
+        viewport_origin: Vec2::new(0.5, 0.5),
+        area: Rect::new(-1.0, -1.0, 1.0, 1.0),
+    })
+}
+
+/// Creates the projection used by the camera in perspective mode.
+fn perspective_projection() -> Projection {
+    Projection::Perspective(PerspectiveProjection {
+        fov: PERSPECTIVE_FOV,
+        far: 1000.0,
+        ..default()
+    })
+}
+
 /// Smoothly moves the camera to the target position, rotation, scale, and
 /// distance based on the `CameraController` component.
 fn lerp_camera(
     mut query: Query<(&mut CameraController, &mut Transform, &mut Projection)>,
     time: Res<Time>,
+    chunk_table: Res<ChunkTable>,
+    chunks: Query<&VoxelChunk>,
 ) {
+    let is_solid = |pos: WorldPos| {
+        chunk_table
+            .get_chunk(pos.as_chunk_pos())
+            .and_then(|chunk_id| chunks.get(chunk_id).ok())
+            .is_some_and(|chunk| !matches!(chunk.get_models().get(pos), BlockModel::Empty))
+    };
+
     for (mut controller, mut transform, mut projection) in query.iter_mut() {
         controller.update(time.delta_secs());
+
+        if controller.collision {
+            let ray = Ray3d::new(controller.origin(), controller.rotation() * Dir3::NEG_Z);
+            let obstruction = raycast_blocks(ray, controller.camera_dist(), is_solid);
+            controller.update_collision(time.delta_secs(), obstruction.map(|hit| hit.distance));
+        }
+
         transform.translation = controller.translation();
         transform.rotation = controller.rotation();
         transform.look_at(controller.origin(), controller.up());
 
-        if let Projection::Orthographic(ortho) = &mut *projection {
-            ortho.scale = controller.dist;
+        match (controller.projection, &mut *projection) {
+            (CameraProjection::Orthographic, Projection::Orthographic(ortho)) => {
+                ortho.scale = controller.dist;
+            }
+            (CameraProjection::Perspective, Projection::Perspective(_)) => {}
+            (CameraProjection::Orthographic, _) => *projection = orthographic_projection(),
+            (CameraProjection::Perspective, _) => *projection = perspective_projection(),
+        }
+    }
+}
+
+/// Switches the projection of the active cameras, when run from the command
+/// palette or with its hotkey.
+fn toggle_projection(mut camera_controllers: Query<&mut CameraController>) {
+    for mut controller in camera_controllers.iter_mut() {
+        if controller.active {
+            controller.toggle_projection();
         }
     }
 }
@@ -242,7 +425,14 @@ fn lerp_camera(
 fn rotate_camera(
     mut camera_controllers: Query<&mut CameraController>,
     mut key_presses: MessageReader<KeyboardInput>,
+    text_focus: Query<(), With<ImeFocus>>,
 ) {
+    // Keys typed into a text input should not move the camera.
+    if !text_focus.is_empty() {
+        key_presses.clear();
+        return;
+    }
+
     for key_ev in key_presses.read() {
         if !key_ev.state.is_pressed() {
             continue;
@@ -286,7 +476,7 @@ fn pan_camera_mouse(
     mut last_mouse_pos: Local<Vec2>,
     mut camera_controllers: Query<&mut CameraController>,
     buttons: Res<ButtonInput<MouseButton>>,
-    windows: Query<&Window>,
+    windows: Query<&Window, With<PrimaryWindow>>,
 ) {
     let Ok(window) = windows.single() else {
         return;
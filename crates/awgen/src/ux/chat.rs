@@ -0,0 +1,110 @@
+//! This module implements the chat console overlay for running games, which
+//! lets scripts print messages to the player and receive typed commands.
+//!
+//! The console can be moved into its own window with the "Detach Chat Console"
+//! command.
+
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::menus::windows::{DetachPanel, Detached, DockPanel};
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use awgen_ui::widgets::console::{ChatConsole, ConsolePrint, ConsoleSubmit};
+use bevy::prelude::*;
+
+use crate::app::AwgenState;
+use crate::scripts::{PacketOut, ScriptEngine};
+
+/// Plugin that sets up the chat console overlay.
+pub struct ChatPlugin;
+impl Plugin for ChatPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_systems(OnEnter(AwgenState::Game), setup)
+            .add_systems(OnExit(AwgenState::Game), cleanup)
+            .add_observer(on_submit)
+            .register_command(
+                PaletteCommand::new("game.chat.detach", "Detach Chat Console"),
+                toggle_detached,
+            );
+    }
+}
+
+/// A marker component for the game chat console.
+#[derive(Debug, Component)]
+pub struct GameChat;
+
+/// Sets up the chat console.
+fn setup(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.spawn((
+        GameChat,
+        ScreenAnchor::BottomLeft,
+        Node {
+            width: px(480.0),
+            height: px(240.0),
+            margin: UiRect::all(px(8.0)),
+            ..default()
+        },
+        ChatConsole::new(hearth_theme(&asset_server)),
+    ));
+}
+
+/// Cleans up the chat console.
+fn cleanup(chat: Query<Entity, With<GameChat>>, mut commands: Commands) {
+    for entity in chat.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Moves the chat console into its own window, or docks it back into the main
+/// window if it is already detached.
+fn toggle_detached(chat: Query<(Entity, Has<Detached>), With<GameChat>>, mut commands: Commands) {
+    let Ok((entity, detached)) = chat.single() else {
+        return;
+    };
+
+    if detached {
+        commands.trigger(DockPanel { entity });
+    } else {
+        commands.trigger(DetachPanel {
+            entity,
+            title: "Chat Console".to_string(),
+        });
+    }
+}
+
+/// Forwards lines submitted in the chat console to the script engine.
+fn on_submit(
+    trigger: On<ConsoleSubmit>,
+    chat: Query<(), With<GameChat>>,
+    engine: Res<ScriptEngine>,
+) {
+    if !chat.contains(trigger.entity) {
+        return;
+    }
+
+    let packet = PacketOut::ConsoleCommand {
+        text: trigger.text.clone(),
+    };
+
+    if let Err(err) = engine.send(packet) {
+        error!(
+            "Failed to send console command to the script engine: {}",
+            err
+        );
+    }
+}
+
+/// Prints a line of text to the chat console.
+///
+/// Text printed while no game is running is discarded.
+pub fn print(world: &mut World, text: String) {
+    let Some(entity) = world
+        .query_filtered::<Entity, With<GameChat>>()
+        .iter(world)
+        .next()
+    else {
+        warn!("Cannot print to the chat console: no game is running");
+        return;
+    };
+
+    world.trigger(ConsolePrint { entity, text });
+}
@@ -0,0 +1,167 @@
+//! This module implements the chunk debug view, which draws the borders of all
+//! loaded chunks, the occluding faces of the blocks near the camera, and
+//! highlights the chunks that were recently marked as dirty.
+//!
+//! Occluding faces are only drawn where they are exposed, and are colored by
+//! their axis: red for X, green for Y, and blue for Z.
+
+use std::collections::HashMap;
+
+use awgen_ui::hotkeys::{BindHotkeyExt, Hotkey, HotkeyContext};
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use bevy::prelude::*;
+
+use crate::map::{CHUNK_SIZE, ChunkPos, MapSystemSets, Occluder, Occlusion, VoxelChunk};
+use crate::ux::CameraController;
+
+/// The color of chunk borders.
+const BORDER_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+
+/// The color of the highlight drawn around dirty chunks.
+const DIRTY_COLOR: Color = Color::srgb(1.0, 0.8, 0.0);
+
+/// How long, in seconds, a chunk stays highlighted after it was marked dirty.
+const DIRTY_HIGHLIGHT_SECS: f32 = 1.0;
+
+/// The maximum distance, in blocks, from the camera to the center of a chunk
+/// for its occluding faces to be drawn.
+const OCCLUDER_RANGE: f32 = 48.0;
+
+/// The size of the occluding face overlays, relative to a block face. They are
+/// drawn smaller than the face so that adjacent overlays stay apart.
+const OCCLUDER_FACE_SIZE: f32 = 0.8;
+
+/// How far occluding face overlays are drawn in front of their face, to avoid
+/// z-fighting with the terrain.
+const OCCLUDER_FACE_OFFSET: f32 = 0.01;
+
+/// The occluder flag, occlusion flag, and normal of each face of a block.
+const FACES: [(Occluder, Occlusion, IVec3); 6] = [
+    (Occluder::PosX, Occlusion::PosX, IVec3::X),
+    (Occluder::NegX, Occlusion::NegX, IVec3::NEG_X),
+    (Occluder::PosY, Occlusion::PosY, IVec3::Y),
+    (Occluder::NegY, Occlusion::NegY, IVec3::NEG_Y),
+    (Occluder::PosZ, Occlusion::PosZ, IVec3::Z),
+    (Occluder::NegZ, Occlusion::NegZ, IVec3::NEG_Z),
+];
+
+/// The plugin that adds the chunk debug view.
+pub struct ChunkDebugPlugin;
+impl Plugin for ChunkDebugPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<ChunkDebug>()
+            .add_systems(
+                Update,
+                draw_chunk_debug
+                    .before(MapSystemSets::RedrawChunks)
+                    .run_if(|debug: Res<ChunkDebug>| debug.enabled),
+            )
+            .register_command(
+                PaletteCommand::new("debug.chunks", "Toggle Chunk Debug View"),
+                toggle_chunk_debug,
+            )
+            .bind_hotkey(
+                "debug.chunks",
+                Hotkey::new(KeyCode::F6),
+                HotkeyContext::Global,
+            );
+    }
+}
+
+/// The resource which contains the settings for the chunk debug view.
+#[derive(Debug, Default, Resource)]
+pub struct ChunkDebug {
+    /// Whether the chunk debug view is drawn.
+    pub enabled: bool,
+}
+
+/// This system toggles the chunk debug view.
+fn toggle_chunk_debug(mut chunk_debug: ResMut<ChunkDebug>) {
+    chunk_debug.enabled = !chunk_debug.enabled;
+}
+
+/// This system draws the chunk borders, the occluding faces near the camera,
+/// and the highlights of recently dirtied chunks.
+///
+/// It runs before chunks are redrawn, as redrawing marks them as clean.
+fn draw_chunk_debug(
+    time: Res<Time>,
+    camera: Query<&GlobalTransform, With<CameraController>>,
+    chunks: Query<(Entity, Ref<VoxelChunk>)>,
+    mut dirtied: Local<HashMap<Entity, f32>>,
+    mut gizmos: Gizmos,
+) {
+    let now = time.elapsed_secs();
+    let camera_pos = camera.single().ok().map(GlobalTransform::translation);
+    let size = CHUNK_SIZE as f32;
+
+    dirtied.retain(|entity, since| chunks.contains(*entity) && now - *since < DIRTY_HIGHLIGHT_SECS);
+
+    for (entity, chunk) in chunks.iter() {
+        let transform = chunk_transform(chunk.pos());
+        gizmos.cuboid(transform, BORDER_COLOR);
+
+        if chunk.is_dirty() || chunk.is_changed() {
+            dirtied.insert(entity, now);
+        }
+
+        if let Some(since) = dirtied.get(&entity) {
+            let fade = 1.0 - (now - since) / DIRTY_HIGHLIGHT_SECS;
+            let highlight = transform.with_scale(Vec3::splat(size * 0.98));
+            gizmos.cuboid(highlight, DIRTY_COLOR.with_alpha(fade));
+        }
+
+        if camera_pos.is_some_and(|pos| pos.distance(transform.translation) <= OCCLUDER_RANGE) {
+            draw_occluders(&chunk, &mut gizmos);
+        }
+    }
+}
+
+/// Draws the exposed occluding faces of all blocks in the given chunk.
+fn draw_occluders(chunk: &VoxelChunk, gizmos: &mut Gizmos) {
+    let models = chunk.get_models();
+
+    for (index, model) in models.as_slice().iter().enumerate() {
+        let occluder = model.get_occluder_flags();
+        if occluder.is_empty() {
+            continue;
+        }
+
+        let block = chunk.pos().block_at_index(index);
+        let occlusion = Occlusion::from_chunk_models(models, block.as_local_pos());
+        let center = block.as_vec3() + Vec3::splat(0.5);
+
+        for (flag, occluded, normal) in FACES {
+            if !occluder.contains(flag) || occlusion.contains(occluded) {
+                continue;
+            }
+
+            let normal = normal.as_vec3();
+            let face_center = center + normal * (0.5 + OCCLUDER_FACE_OFFSET);
+            let rotation = Quat::from_rotation_arc(Vec3::Z, normal);
+            gizmos.rect(
+                Isometry3d::new(face_center, rotation),
+                Vec2::splat(OCCLUDER_FACE_SIZE),
+                axis_color(normal),
+            );
+        }
+    }
+}
+
+/// Gets the transform of a unit cube scaled to cover the given chunk.
+fn chunk_transform(pos: ChunkPos) -> Transform {
+    let size = CHUNK_SIZE as f32;
+    let min = pos.origin().as_vec3();
+    Transform::from_translation(min + Vec3::splat(size * 0.5)).with_scale(Vec3::splat(size))
+}
+
+/// Gets the debug color of the axis of the given face normal.
+fn axis_color(normal: Vec3) -> Color {
+    if normal.x != 0.0 {
+        Color::srgb(1.0, 0.2, 0.2)
+    } else if normal.y != 0.0 {
+        Color::srgb(0.2, 1.0, 0.2)
+    } else {
+        Color::srgb(0.2, 0.4, 1.0)
+    }
+}
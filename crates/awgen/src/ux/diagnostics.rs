@@ -1,6 +1,10 @@
 //! This module implements the diagnostics overlay for the Awgen game engine.
 
+use awgen_ui::contrast::ContrastDebug;
+use awgen_ui::hotkeys::{BindHotkeyExt, Hotkey, HotkeyContext};
+use awgen_ui::layout_debug::LayoutInspector;
 use awgen_ui::menus::overlay::{Node3D, ScreenAnchor};
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
 use bevy::camera::visibility::RenderLayers;
 use bevy::diagnostic::{
     DiagnosticsStore,
@@ -12,6 +16,7 @@ use bevy::prelude::*;
 use bevy::render::diagnostic::RenderDiagnosticsPlugin;
 use lazy_static::lazy_static;
 
+use crate::tiles::TerrainDebugMode;
 use crate::ux::CameraController;
 
 /// The length of the axis indicator in the overlay.
@@ -53,7 +58,6 @@ impl Plugin for DiagnosticsOverlayPlugin {
         .add_systems(
             Update,
             (
-                toggle_diagnostics_overlay.in_set(DiagnosticsOverlaySystems::Toggle),
                 build_diagnostics_overlay
                     .in_set(DiagnosticsOverlaySystems::BuildUI)
                     .run_if(resource_changed::<DiagnosticsOverlay>),
@@ -63,14 +67,50 @@ impl Plugin for DiagnosticsOverlayPlugin {
                 update_axis_indicator.in_set(DiagnosticsOverlaySystems::UpdateAxisIndicator),
             ),
         )
-        .configure_sets(
-            Update,
-            (
-                DiagnosticsOverlaySystems::BuildUI.after(DiagnosticsOverlaySystems::Toggle),
-                DiagnosticsOverlaySystems::UpdateText.after(DiagnosticsOverlaySystems::Toggle),
-                DiagnosticsOverlaySystems::UpdateAxisIndicator
-                    .after(DiagnosticsOverlaySystems::Toggle),
-            ),
+        .register_command(
+            PaletteCommand::new("debug.diagnostics", "Toggle Diagnostics Overlay"),
+            toggle_diagnostics_overlay,
+        )
+        .register_command(
+            PaletteCommand::new("debug.contrast", "Toggle Contrast Debug"),
+            toggle_contrast_debug,
+        )
+        .register_command(
+            PaletteCommand::new("debug.layout", "Toggle Layout Inspector"),
+            toggle_layout_inspector,
+        )
+        .register_command(
+            PaletteCommand::new("debug.terrain_wireframe", "Toggle Terrain Wireframe"),
+            toggle_terrain_wireframe,
+        )
+        .register_command(
+            PaletteCommand::new("debug.terrain_normals", "Toggle Terrain Normals"),
+            toggle_terrain_normals,
+        )
+        .bind_hotkey(
+            "debug.diagnostics",
+            Hotkey::new(KeyCode::F3),
+            HotkeyContext::Global,
+        )
+        .bind_hotkey(
+            "debug.contrast",
+            Hotkey::new(KeyCode::F4),
+            HotkeyContext::Global,
+        )
+        .bind_hotkey(
+            "debug.layout",
+            Hotkey::new(KeyCode::F5),
+            HotkeyContext::Global,
+        )
+        .bind_hotkey(
+            "debug.terrain_wireframe",
+            Hotkey::new(KeyCode::F7),
+            HotkeyContext::Global,
+        )
+        .bind_hotkey(
+            "debug.terrain_normals",
+            Hotkey::new(KeyCode::F8),
+            HotkeyContext::Global,
         );
     }
 }
@@ -78,9 +118,6 @@ impl Plugin for DiagnosticsOverlayPlugin {
 /// The SystemSets for the diagnostics overlay.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 pub enum DiagnosticsOverlaySystems {
-    /// The system set for toggling the diagnostics overlay.
-    Toggle,
-
     /// The system set for building (or destroying) the diagnostics overlay UI.
     BuildUI,
 
@@ -119,15 +156,40 @@ pub struct DiagnosticsText;
 #[derive(Debug, Default, Component)]
 pub struct WorldAxisIndicator;
 
-/// This system toggles the visibility of the diagnostics overlay when the F3
-/// key is pressed.
-fn toggle_diagnostics_overlay(
-    mut diagnostics_overlay: ResMut<DiagnosticsOverlay>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-) {
-    if keyboard_input.just_pressed(KeyCode::F3) {
-        diagnostics_overlay.visible = !diagnostics_overlay.visible;
-    }
+/// This system toggles the visibility of the diagnostics overlay. It is bound
+/// to the F3 key.
+fn toggle_diagnostics_overlay(mut diagnostics_overlay: ResMut<DiagnosticsOverlay>) {
+    diagnostics_overlay.visible = !diagnostics_overlay.visible;
+}
+
+/// This system toggles the UI contrast debug mode, which outlines and reports
+/// widgets whose colors are hard to read. It is bound to the F4 key.
+fn toggle_contrast_debug(mut contrast_debug: ResMut<ContrastDebug>) {
+    contrast_debug.enabled = !contrast_debug.enabled;
+}
+
+/// This system toggles the UI layout inspector, which highlights the hovered
+/// UI node and shows the UI hierarchy. It is bound to the F5 key.
+fn toggle_layout_inspector(mut layout_inspector: ResMut<LayoutInspector>) {
+    layout_inspector.enabled = !layout_inspector.enabled;
+}
+
+/// This system toggles the wireframe terrain debug mode. It is bound to the F7
+/// key.
+fn toggle_terrain_wireframe(mut mode: ResMut<TerrainDebugMode>) {
+    *mode = match *mode {
+        TerrainDebugMode::Wireframe => TerrainDebugMode::Off,
+        _ => TerrainDebugMode::Wireframe,
+    };
+}
+
+/// This system toggles the normals terrain debug mode. It is bound to the F8
+/// key.
+fn toggle_terrain_normals(mut mode: ResMut<TerrainDebugMode>) {
+    *mode = match *mode {
+        TerrainDebugMode::Normals => TerrainDebugMode::Off,
+        _ => TerrainDebugMode::Normals,
+    };
 }
 
 /// This system builds or destroys the diagnostics overlay UI based on the
@@ -292,7 +354,19 @@ fn compute_text(store: &Res<DiagnosticsStore>) -> String {
             .unwrap_or(0)
     );
 
-    format!("{system}\n{fps}\n{geometry}")
+    let load = format!(
+        "Remesh: {:.1}ms / Script queue: {:.0} packets",
+        store
+            .get(&crate::map::REMESH_TIME)
+            .and_then(|remesh_time| remesh_time.smoothed())
+            .unwrap_or(0.0),
+        store
+            .get(&crate::scripts::PACKET_QUEUE_DEPTH)
+            .and_then(|queue_depth| queue_depth.value())
+            .unwrap_or(0.0)
+    );
+
+    format!("{system}\n{fps}\n{load}\n{geometry}")
 }
 
 /// This system updates the rotation of the world axis indicator to reflect the
@@ -0,0 +1,383 @@
+//! This module implements the area tool for the editor, which draws and edits
+//! the tagged regions of the world stored in the game database.
+//!
+//! The tool is toggled with `T`. While it is active, all areas are outlined and
+//! listed in a panel. Clicking two blocks draws a box between them, which
+//! either creates a new area, or replaces the region of the area selected in
+//! the panel. Right-clicking cancels a box that is being drawn.
+
+use awgen_ui::hotkeys::{BindHotkeyExt, Hotkey, HotkeyContext};
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::button::{ButtonBuilder, ButtonContent, button};
+use awgen_ui::widgets::command_palette::PaletteCommand;
+use bevy::ecs::system::SystemParamItem;
+use bevy::picking::hover::HoverMap;
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+use bevy::window::PrimaryWindow;
+
+use crate::app::AwgenState;
+use crate::database::GameDatabase;
+use crate::database::areas::Area;
+use crate::map::{
+    AreaIndex,
+    BlockModel,
+    BlockRegion,
+    ChunkTable,
+    VoxelChunk,
+    WorldPos,
+    raycast_blocks,
+};
+use crate::ux::CameraController;
+use crate::ux::editor::panel::{AddEditorPanelExt, EditorPanel, EditorPanelSystems, PanelContent};
+use crate::ux::editor::picking::{cursor_ray, pointer_over};
+
+/// The maximum distance, in blocks, at which area corners can be picked.
+const PICK_DISTANCE: f32 = 256.0;
+
+/// The outline color of unselected areas.
+const AREA_COLOR: Color = Color::srgb(0.2, 0.8, 1.0);
+
+/// The outline color of the selected area.
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+/// The outline color of the first corner of a box being drawn.
+const CORNER_COLOR: Color = Color::WHITE;
+
+/// Plugin that sets up the area tool.
+pub struct AreaToolPlugin;
+impl Plugin for AreaToolPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_editor_panel::<AreaTool>(PaletteCommand::new("editor.areas", "Toggle Area Tool"))
+            .add_systems(
+                Update,
+                (refresh_panel, draw_region, draw_areas)
+                    .chain()
+                    .after(EditorPanelSystems)
+                    .run_if(in_state(AwgenState::Editor)),
+            )
+            .add_observer(on_area_activate)
+            .bind_hotkey(
+                "editor.areas",
+                Hotkey::new(KeyCode::KeyT),
+                HotkeyContext::Editor,
+            );
+    }
+}
+
+/// The state of the area tool, which is active while its panel is open.
+#[derive(Debug, Default)]
+pub struct AreaTool {
+    /// The name of the area whose region is replaced by the next drawn box.
+    selected: Option<String>,
+
+    /// The first corner of the box being drawn.
+    corner: Option<WorldPos>,
+}
+
+impl AreaTool {
+    /// Gets the name of the selected area, if any.
+    pub fn selected(&self) -> Option<&str> {
+        self.selected.as_deref()
+    }
+}
+
+impl PanelContent for AreaTool {
+    type Params = (Res<'static, AssetServer>, Res<'static, GameDatabase>);
+
+    fn spawn(
+        &mut self,
+        (asset_server, database): &mut SystemParamItem<Self::Params>,
+        commands: &mut Commands,
+    ) -> (Entity, Entity) {
+        let theme = hearth_theme(asset_server);
+        let panel = commands
+            .spawn((
+                AreaPanel,
+                ScreenAnchor::BottomRight,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(8.0),
+                    min_width: px(240.0),
+                    max_height: percent(60.0),
+                    margin: UiRect::all(px(8.0)),
+                    ..default()
+                },
+                theme.outer_window.clone(),
+                children![(Text::new("Areas"), theme.outer_window.text.clone())],
+            ))
+            .id();
+
+        let list = commands
+            .spawn((
+                ChildOf(panel),
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(4.0),
+                    overflow: Overflow::scroll_y(),
+                    scrollbar_width: 4.0,
+                    ..default()
+                },
+            ))
+            .id();
+
+        if !database.is_read_only() {
+            commands.spawn((
+                ChildOf(panel),
+                AreaAction::Deselect,
+                button(ButtonBuilder {
+                    node: Node::default(),
+                    content: ButtonContent::text("Draw New Area"),
+                    theme,
+                }),
+            ));
+        }
+
+        (panel, list)
+    }
+
+    fn close(&mut self) {
+        self.corner = None;
+    }
+}
+
+/// A marker component for the area panel.
+#[derive(Debug, Component)]
+struct AreaPanel;
+
+/// An action performed when a button in the area panel is clicked.
+#[derive(Debug, Component)]
+enum AreaAction {
+    /// Clears the selection, so the next drawn box creates a new area.
+    Deselect,
+
+    /// Selects the area with the given name.
+    Select(String),
+
+    /// Deletes the area with the given name.
+    Delete(String),
+}
+
+/// Rebuilds the area rows when the areas or the selection have changed.
+fn refresh_panel(
+    asset_server: Res<AssetServer>,
+    database: Res<GameDatabase>,
+    index: Res<AreaIndex>,
+    mut tool: ResMut<EditorPanel<AreaTool>>,
+    mut commands: Commands,
+) {
+    if index.is_changed() {
+        tool.mark_dirty();
+    }
+
+    let Some(list) = tool.take_dirty() else {
+        return;
+    };
+
+    commands.entity(list).despawn_related::<Children>();
+
+    let mut areas = index.iter().collect::<Vec<_>>();
+    areas.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let theme = hearth_theme(&asset_server);
+    for area in areas {
+        let row = commands
+            .spawn((
+                ChildOf(list),
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: px(4.0),
+                    ..default()
+                },
+            ))
+            .id();
+
+        let marker = if tool.selected() == Some(area.name.as_str()) {
+            "> "
+        } else {
+            ""
+        };
+
+        commands.spawn((
+            ChildOf(row),
+            AreaAction::Select(area.name.clone()),
+            button(ButtonBuilder {
+                node: Node {
+                    flex_grow: 1.0,
+                    ..default()
+                },
+                content: ButtonContent::text(format!(
+                    "{}{} [{}]",
+                    marker,
+                    area.name,
+                    area.tags.join(", ")
+                )),
+                theme: theme.clone(),
+            }),
+        ));
+
+        if !database.is_read_only() {
+            commands.spawn((
+                ChildOf(row),
+                AreaAction::Delete(area.name.clone()),
+                button(ButtonBuilder {
+                    node: Node::default(),
+                    content: ButtonContent::text("X"),
+                    theme: theme.clone(),
+                }),
+            ));
+        }
+    }
+}
+
+/// Performs the action of an area panel button when it is clicked.
+fn on_area_activate(
+    trigger: On<Activate>,
+    actions: Query<&AreaAction>,
+    database: Res<GameDatabase>,
+    mut index: ResMut<AreaIndex>,
+    mut tool: ResMut<EditorPanel<AreaTool>>,
+) {
+    let Ok(action) = actions.get(trigger.event().entity) else {
+        return;
+    };
+
+    match action {
+        AreaAction::Deselect => {
+            tool.selected = None;
+        }
+        AreaAction::Select(name) => {
+            tool.selected = Some(name.clone());
+        }
+        AreaAction::Delete(name) => {
+            if let Err(err) = database.delete_area(name) {
+                error!("Failed to delete area \"{}\": {}", name, err);
+                return;
+            }
+
+            index.remove(name);
+            if tool.selected() == Some(name.as_str()) {
+                tool.selected = None;
+            }
+        }
+    }
+
+    tool.mark_dirty();
+}
+
+/// Picks the corners of a box when blocks are clicked, and stores the box as
+/// an area once both corners are picked.
+#[allow(clippy::too_many_arguments)]
+fn draw_region(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    hover_map: Res<HoverMap>,
+    parents: Query<&ChildOf>,
+    panels: Query<(), With<AreaPanel>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<CameraController>>,
+    chunk_table: Res<ChunkTable>,
+    chunks: Query<&VoxelChunk>,
+    database: Res<GameDatabase>,
+    mut index: ResMut<AreaIndex>,
+    mut tool: ResMut<EditorPanel<AreaTool>>,
+) {
+    if !tool.is_open() || database.is_read_only() {
+        return;
+    }
+
+    if mouse_buttons.just_pressed(MouseButton::Right) {
+        tool.corner = None;
+        return;
+    }
+
+    if !mouse_buttons.just_pressed(MouseButton::Left) || pointer_over(&hover_map, &parents, &panels)
+    {
+        return;
+    }
+
+    let Some(ray) = cursor_ray(&windows, &cameras) else {
+        return;
+    };
+
+    let is_solid = |pos: WorldPos| {
+        chunk_table
+            .get_chunk(pos.as_chunk_pos())
+            .and_then(|chunk_id| chunks.get(chunk_id).ok())
+            .is_some_and(|chunk| !matches!(chunk.get_models().get(pos), BlockModel::Empty))
+    };
+
+    let Some(hit) = raycast_blocks(ray, PICK_DISTANCE, is_solid) else {
+        return;
+    };
+
+    let Some(corner) = tool.corner.take() else {
+        tool.corner = Some(hit.pos);
+        return;
+    };
+
+    let region = BlockRegion::new(corner, hit.pos);
+    let area = match tool.selected().and_then(|name| index.get(name)) {
+        Some(area) => Area {
+            region,
+            ..area.clone()
+        },
+        None => {
+            let name = (1 ..)
+                .map(|number| format!("Area {}", number))
+                .find(|name| index.get(name).is_none())
+                .unwrap();
+
+            Area {
+                name,
+                region,
+                tags: Vec::new(),
+            }
+        }
+    };
+
+    if let Err(err) = database.set_area(&area) {
+        error!("Failed to store area \"{}\": {}", area.name, err);
+        return;
+    }
+
+    info!(
+        "Stored area \"{}\" covering {} to {}",
+        area.name, region.min, region.max
+    );
+    tool.selected = Some(area.name.clone());
+    tool.mark_dirty();
+    if let Err(err) = index.insert(area) {
+        error!("Failed to index area: {}", err);
+    }
+}
+
+/// Outlines all areas while the tool is active.
+fn draw_areas(tool: Res<EditorPanel<AreaTool>>, index: Res<AreaIndex>, mut gizmos: Gizmos) {
+    if !tool.is_open() {
+        return;
+    }
+
+    for area in index.iter() {
+        let color = if tool.selected() == Some(area.name.as_str()) {
+            SELECTED_COLOR
+        } else {
+            AREA_COLOR
+        };
+        gizmos.cuboid(region_transform(area.region), color);
+    }
+
+    if let Some(corner) = tool.corner {
+        gizmos.cuboid(
+            region_transform(BlockRegion::new(corner, corner)),
+            CORNER_COLOR,
+        );
+    }
+}
+
+/// Gets the transform of a unit cube scaled to cover the given region.
+fn region_transform(region: BlockRegion) -> Transform {
+    let min = region.min.as_vec3();
+    let max = region.max.as_vec3() + Vec3::ONE;
+    Transform::from_translation((min + max) * 0.5).with_scale(max - min)
+}
@@ -0,0 +1,289 @@
+//! This module implements the block brush tool for the editor, which places
+//! and erases blocks by clicking and dragging.
+//!
+//! The tool is toggled with `V`. Dragging with the left mouse button places
+//! cubes against the faces under the cursor, using the tile of the face paint
+//! brush, or the block model chosen in the block model editor. Dragging with
+//! the right mouse button erases blocks. Holding `Shift` locks a drag to the
+//! layer of the face it started on, and holding `Ctrl` locks it to a single
+//! axis within that layer.
+
+use awgen_ui::hotkeys::{BindHotkeyExt, Hotkey, HotkeyContext};
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use bevy::picking::hover::HoverMap;
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::app::AwgenState;
+use crate::map::{BlockModel, ChunkTable, Cube, VoxelChunk, WorldPos, raycast_blocks, set_block};
+use crate::ux::CameraController;
+use crate::ux::editor::paint::FacePaintTool;
+use crate::ux::editor::picking::{
+    CursorDrag,
+    DragLock,
+    block_line,
+    lock_axis,
+    plane_block,
+    pointer_over,
+};
+
+/// The maximum distance, in blocks, at which blocks can be edited.
+const BRUSH_DISTANCE: f32 = 256.0;
+
+/// Plugin that sets up the block brush tool.
+pub struct BlockBrushPlugin;
+impl Plugin for BlockBrushPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<BlockBrushTool>()
+            .add_systems(
+                Update,
+                (toggle_tool, brush_blocks)
+                    .chain()
+                    .run_if(in_state(AwgenState::Editor)),
+            )
+            .add_systems(OnExit(AwgenState::Editor), close_tool)
+            .register_command(
+                PaletteCommand::new("editor.block_brush", "Toggle Block Brush"),
+                request_toggle,
+            )
+            .bind_hotkey(
+                "editor.block_brush",
+                Hotkey::new(KeyCode::KeyV),
+                HotkeyContext::Editor,
+            );
+    }
+}
+
+/// The state of the block brush tool.
+#[derive(Debug, Default, Resource)]
+pub struct BlockBrushTool {
+    /// Whether the tool is active.
+    active: bool,
+
+    /// The block model placed by the tool, such as one chosen in the block
+    /// model editor. If not set, cubes are placed using the tile of the face
+    /// paint brush.
+    pub model: Option<BlockModel>,
+
+    /// The current drag, while a mouse button is held.
+    stroke: Option<BrushStroke>,
+
+    /// The cursor path of the current drag.
+    cursor: CursorDrag,
+
+    /// Whether the tool should be toggled on the next update.
+    toggle: bool,
+}
+
+impl BlockBrushTool {
+    /// Returns true if the tool is active.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Requests the tool to be toggled on the next update.
+    pub fn request_toggle(&mut self) {
+        self.toggle = true;
+    }
+}
+
+/// A single drag of the block brush.
+#[derive(Debug)]
+struct BrushStroke {
+    /// Whether the drag erases blocks, rather than placing them.
+    erase: bool,
+
+    /// The first block edited by the drag.
+    anchor: WorldPos,
+
+    /// The normal of the block face the drag started on.
+    normal: IVec3,
+
+    /// The last block edited by the drag.
+    last: WorldPos,
+
+    /// The blocks edited by the drag.
+    edited: HashSet<WorldPos>,
+}
+
+/// Toggles the block brush tool on the next update, when run from the command
+/// palette or with its hotkey.
+fn request_toggle(mut tool: ResMut<BlockBrushTool>) {
+    tool.toggle = true;
+}
+
+/// Toggles the block brush tool.
+fn toggle_tool(mut tool: ResMut<BlockBrushTool>) {
+    if !tool.toggle {
+        return;
+    }
+    tool.toggle = false;
+
+    tool.active = !tool.active;
+    end_stroke(&mut tool);
+}
+
+/// Places or erases blocks along the path of the cursor while a mouse button
+/// is dragged.
+#[allow(clippy::too_many_arguments)]
+fn brush_blocks(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    paint_tool: Res<FacePaintTool>,
+    hover_map: Res<HoverMap>,
+    parents: Query<&ChildOf>,
+    ui_nodes: Query<(), With<Node>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<CameraController>>,
+    chunk_table: Res<ChunkTable>,
+    chunks: Query<&VoxelChunk>,
+    mut tool: ResMut<BlockBrushTool>,
+    mut commands: Commands,
+) {
+    if !tool.active {
+        return;
+    }
+
+    let erase = if mouse_buttons.pressed(MouseButton::Left) {
+        false
+    } else if mouse_buttons.pressed(MouseButton::Right) {
+        true
+    } else {
+        if tool.stroke.is_some() {
+            end_stroke(&mut tool);
+        }
+        return;
+    };
+
+    if tool
+        .stroke
+        .as_ref()
+        .is_some_and(|stroke| stroke.erase != erase)
+    {
+        end_stroke(&mut tool);
+    }
+
+    // Drags must start with a click on the map, not on a UI panel.
+    let starting = tool.stroke.is_none();
+    if starting
+        && (!mouse_buttons.any_just_pressed([MouseButton::Left, MouseButton::Right])
+            || pointer_over(&hover_map, &parents, &ui_nodes))
+    {
+        return;
+    }
+
+    let is_solid = |pos: WorldPos| {
+        chunk_table
+            .get_chunk(pos.as_chunk_pos())
+            .and_then(|chunk_id| chunks.get(chunk_id).ok())
+            .is_some_and(|chunk| !matches!(chunk.get_models().get(pos), BlockModel::Empty))
+    };
+
+    let lock = DragLock::from_keys(&keyboard_input);
+    let tool = &mut *tool;
+    let mut edits = Vec::new();
+
+    for ray in tool.cursor.rays(&windows, &cameras) {
+        let Some(stroke) = &mut tool.stroke else {
+            // Blocks edited earlier in the drag are ignored, so placed blocks
+            // do not pile up towards the camera.
+            let Some(hit) = raycast_blocks(ray, BRUSH_DISTANCE, is_solid) else {
+                continue;
+            };
+
+            let normal = hit.face.normal();
+            let target = if erase {
+                hit.pos
+            } else {
+                hit.pos + WorldPos::new(normal.x, normal.y, normal.z)
+            };
+
+            tool.stroke = Some(BrushStroke {
+                erase,
+                anchor: target,
+                normal,
+                last: target,
+                edited: HashSet::from_iter([target]),
+            });
+            edits.push(target);
+            continue;
+        };
+
+        let target = match lock {
+            DragLock::Free => {
+                let edited = &stroke.edited;
+                let is_unedited = |pos: WorldPos| is_solid(pos) && !edited.contains(&pos);
+                let Some(hit) = raycast_blocks(ray, BRUSH_DISTANCE, is_unedited) else {
+                    continue;
+                };
+
+                let normal = hit.face.normal();
+                if erase {
+                    hit.pos
+                } else {
+                    hit.pos + WorldPos::new(normal.x, normal.y, normal.z)
+                }
+            }
+            DragLock::Plane => match plane_block(ray, stroke.anchor, stroke.normal) {
+                Some(target) => target,
+                None => continue,
+            },
+            DragLock::Axis => match plane_block(ray, stroke.anchor, stroke.normal) {
+                Some(target) => lock_axis(stroke.anchor, target),
+                None => continue,
+            },
+        };
+
+        // Locked drags fill in the line from the last block, so that no gaps
+        // are left when the cursor skips over blocks.
+        let path = match lock {
+            DragLock::Free => vec![target],
+            DragLock::Plane | DragLock::Axis => block_line(stroke.last, target).collect(),
+        };
+
+        for pos in path {
+            if stroke.edited.insert(pos) && (!erase || is_solid(pos)) {
+                edits.push(pos);
+            }
+        }
+        stroke.last = target;
+    }
+
+    if edits.is_empty() {
+        return;
+    }
+
+    let model = if erase {
+        BlockModel::Empty
+    } else if let Some(model) = &tool.model {
+        model.clone()
+    } else {
+        let brush = paint_tool.brush;
+        BlockModel::Cube(Cube {
+            pos_y: brush,
+            pos_z: brush,
+            neg_z: brush,
+            pos_x: brush,
+            neg_x: brush,
+        })
+    };
+
+    commands.queue(move |world: &mut World| {
+        for pos in edits {
+            set_block(world, pos, model.clone());
+        }
+    });
+}
+
+/// Deactivates the block brush tool when leaving the editor.
+fn close_tool(mut tool: ResMut<BlockBrushTool>) {
+    tool.active = false;
+    end_stroke(&mut tool);
+}
+
+/// Ends the current drag, if any.
+fn end_stroke(tool: &mut BlockBrushTool) {
+    tool.stroke = None;
+    tool.cursor.end();
+}
@@ -0,0 +1,331 @@
+//! This module implements the bookmark panel for the editor, which lists the
+//! named camera locations stored in the game database.
+//!
+//! The panel is toggled with `M`. Clicking a bookmark moves the camera to it,
+//! and bookmarks can be added at the current camera location or deleted.
+//! Adding a bookmark prompts for its name, which is confirmed with Enter and
+//! cancelled with Escape.
+
+use awgen_ui::hotkeys::{BindHotkeyExt, Hotkey, HotkeyContext};
+use awgen_ui::ime::ImeFocus;
+use awgen_ui::menus::maximize::Maximizable;
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::button::{ButtonBuilder, ButtonContent, button};
+use awgen_ui::widgets::command_palette::PaletteCommand;
+use awgen_ui::widgets::text_input::{TextInput, TextInputSubmit};
+use bevy::ecs::system::SystemParamItem;
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+
+use crate::app::AwgenState;
+use crate::database::GameDatabase;
+use crate::database::backup::GameDatabaseRestored;
+use crate::ux::CameraController;
+use crate::ux::editor::layout::{LayoutPanel, PanelKind};
+use crate::ux::editor::panel::{AddEditorPanelExt, EditorPanel, EditorPanelSystems, PanelContent};
+
+/// Plugin that sets up the editor bookmark panel.
+pub struct BookmarkPanelPlugin;
+impl Plugin for BookmarkPanelPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_editor_panel::<BookmarkPanel>(PaletteCommand::new(
+            "editor.bookmarks",
+            "Toggle Bookmark Panel",
+        ))
+        .add_systems(
+            Update,
+            (refresh_on_restore, refresh_panel)
+                .chain()
+                .after(EditorPanelSystems)
+                .run_if(in_state(AwgenState::Editor)),
+        )
+        .add_observer(on_bookmark_activate)
+        .add_observer(on_name_submit)
+        .add_observer(on_name_blur)
+        .bind_hotkey(
+            "editor.bookmarks",
+            Hotkey::new(KeyCode::KeyM),
+            HotkeyContext::Editor,
+        );
+    }
+}
+
+/// The contents of the bookmark panel.
+#[derive(Debug, Default)]
+pub struct BookmarkPanel;
+
+impl PanelContent for BookmarkPanel {
+    type Params = (Res<'static, AssetServer>, Res<'static, GameDatabase>);
+
+    fn spawn(
+        &mut self,
+        (asset_server, database): &mut SystemParamItem<Self::Params>,
+        commands: &mut Commands,
+    ) -> (Entity, Entity) {
+        let theme = hearth_theme(asset_server);
+        let panel = commands
+            .spawn((
+                ScreenAnchor::CenterLeft,
+                LayoutPanel(PanelKind::Bookmarks),
+                Maximizable,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(8.0),
+                    min_width: px(200.0),
+                    max_height: percent(80.0),
+                    margin: UiRect::all(px(8.0)),
+                    ..default()
+                },
+                theme.outer_window.clone(),
+                children![(Text::new("Bookmarks"), theme.outer_window.text.clone())],
+            ))
+            .id();
+
+        let list = commands
+            .spawn((
+                ChildOf(panel),
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(4.0),
+                    overflow: Overflow::scroll_y(),
+                    scrollbar_width: 4.0,
+                    ..default()
+                },
+            ))
+            .id();
+
+        if !database.is_read_only() {
+            commands.spawn((
+                ChildOf(panel),
+                BookmarkAction::Add,
+                button(ButtonBuilder {
+                    node: Node::default(),
+                    content: ButtonContent::text("Add Bookmark"),
+                    theme,
+                }),
+            ));
+        }
+
+        (panel, list)
+    }
+}
+
+/// An action performed when a button in the bookmark panel is clicked.
+#[derive(Debug, Component)]
+enum BookmarkAction {
+    /// Adds a bookmark at the current camera location.
+    Add,
+
+    /// Moves the camera to the bookmark with the given name.
+    Jump(String),
+
+    /// Deletes the bookmark with the given name.
+    Delete(String),
+}
+
+/// A text input that prompts for the name of a new bookmark, shown in place of
+/// the "Add Bookmark" button.
+#[derive(Debug, Component)]
+struct BookmarkNamePrompt {
+    /// The "Add Bookmark" button, which is hidden while the prompt is shown.
+    button: Entity,
+}
+
+/// Marks the bookmark rows for rebuilding when the game database has been
+/// restored from a backup.
+fn refresh_on_restore(
+    mut restored: MessageReader<GameDatabaseRestored>,
+    mut state: ResMut<EditorPanel<BookmarkPanel>>,
+) {
+    if restored.read().count() > 0 {
+        state.mark_dirty();
+    }
+}
+
+/// Rebuilds the bookmark rows when the stored bookmarks have changed.
+fn refresh_panel(
+    asset_server: Res<AssetServer>,
+    database: Res<GameDatabase>,
+    mut state: ResMut<EditorPanel<BookmarkPanel>>,
+    mut commands: Commands,
+) {
+    let Some(list) = state.take_dirty() else {
+        return;
+    };
+
+    let bookmarks = match database.list_bookmarks() {
+        Ok(bookmarks) => bookmarks,
+        Err(err) => {
+            error!("Failed to list bookmarks: {}", err);
+            return;
+        }
+    };
+
+    commands.entity(list).despawn_related::<Children>();
+
+    let theme = hearth_theme(&asset_server);
+    for bookmark in bookmarks {
+        let row = commands
+            .spawn((
+                ChildOf(list),
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: px(4.0),
+                    ..default()
+                },
+            ))
+            .id();
+
+        commands.spawn((
+            ChildOf(row),
+            BookmarkAction::Jump(bookmark.name.clone()),
+            button(ButtonBuilder {
+                node: Node {
+                    flex_grow: 1.0,
+                    ..default()
+                },
+                content: ButtonContent::text(bookmark.name.clone()),
+                theme: theme.clone(),
+            }),
+        ));
+
+        if !database.is_read_only() {
+            commands.spawn((
+                ChildOf(row),
+                BookmarkAction::Delete(bookmark.name),
+                button(ButtonBuilder {
+                    node: Node::default(),
+                    content: ButtonContent::text("X"),
+                    theme: theme.clone(),
+                }),
+            ));
+        }
+    }
+}
+
+/// Performs the action of a bookmark panel button when it is clicked.
+#[allow(clippy::too_many_arguments)]
+fn on_bookmark_activate(
+    trigger: On<Activate>,
+    actions: Query<(&BookmarkAction, &ChildOf)>,
+    focused: Query<Entity, With<ImeFocus>>,
+    asset_server: Res<AssetServer>,
+    database: Res<GameDatabase>,
+    mut cameras: Query<&mut CameraController>,
+    mut nodes: Query<&mut Node>,
+    mut state: ResMut<EditorPanel<BookmarkPanel>>,
+    mut commands: Commands,
+) {
+    let button_id = trigger.event().entity;
+    let Ok((action, parent)) = actions.get(button_id) else {
+        return;
+    };
+
+    match action {
+        BookmarkAction::Add => {
+            let bookmarks = match database.list_bookmarks() {
+                Ok(bookmarks) => bookmarks,
+                Err(err) => {
+                    error!("Failed to list bookmarks: {}", err);
+                    return;
+                }
+            };
+
+            let name = (1 ..)
+                .map(|index| format!("Bookmark {}", index))
+                .find(|name| bookmarks.iter().all(|bookmark| &bookmark.name != name))
+                .unwrap();
+
+            if let Ok(mut node) = nodes.get_mut(button_id) {
+                node.display = Display::None;
+            }
+
+            for other in focused.iter() {
+                commands.entity(other).remove::<ImeFocus>();
+            }
+
+            let mut input = TextInput::new(hearth_theme(&asset_server))
+                .with_value(name)
+                .with_placeholder("Bookmark name");
+            input.select_all();
+
+            commands.spawn((
+                ChildOf(parent.parent()),
+                BookmarkNamePrompt { button: button_id },
+                input,
+                ImeFocus,
+            ));
+        }
+        BookmarkAction::Jump(name) => match database.get_bookmark(name) {
+            Ok(Some(bookmark)) => {
+                for mut camera in cameras.iter_mut() {
+                    camera.jump_to(&bookmark);
+                }
+            }
+            Ok(None) => {
+                warn!("Bookmark \"{}\" no longer exists", name);
+                state.mark_dirty();
+            }
+            Err(err) => error!("Failed to read bookmark \"{}\": {}", name, err),
+        },
+        BookmarkAction::Delete(name) => {
+            if let Err(err) = database.delete_bookmark(name) {
+                error!("Failed to delete bookmark \"{}\": {}", name, err);
+            }
+            state.mark_dirty();
+        }
+    }
+}
+
+/// Adds a bookmark at the current camera location with the name entered in
+/// the name prompt, replacing any bookmark with the same name.
+fn on_name_submit(
+    trigger: On<TextInputSubmit>,
+    prompts: Query<(), With<BookmarkNamePrompt>>,
+    cameras: Query<&CameraController>,
+    database: Res<GameDatabase>,
+    mut state: ResMut<EditorPanel<BookmarkPanel>>,
+    mut commands: Commands,
+) {
+    let input = trigger.event().entity;
+    if !prompts.contains(input) {
+        return;
+    }
+
+    commands.entity(input).remove::<ImeFocus>();
+
+    let name = trigger.event().value.trim();
+    if name.is_empty() {
+        return;
+    }
+
+    let Ok(camera) = cameras.single() else {
+        return;
+    };
+
+    if let Err(err) = database.set_bookmark(&camera.bookmark(name.to_string())) {
+        error!("Failed to add bookmark \"{}\": {}", name, err);
+    }
+    state.mark_dirty();
+}
+
+/// Removes the name prompt when it loses focus, such as after Enter or Escape
+/// is pressed, showing the "Add Bookmark" button again.
+fn on_name_blur(
+    trigger: On<Remove, ImeFocus>,
+    prompts: Query<&BookmarkNamePrompt>,
+    mut nodes: Query<&mut Node>,
+    mut commands: Commands,
+) {
+    let input = trigger.event().entity;
+    let Ok(prompt) = prompts.get(input) else {
+        return;
+    };
+
+    if let Ok(mut node) = nodes.get_mut(prompt.button) {
+        node.display = Display::Flex;
+    }
+
+    commands.entity(input).try_despawn();
+}
@@ -0,0 +1,84 @@
+//! This module sets up the command palette of the editor, which is opened with
+//! Ctrl+P or Ctrl+Shift+P, and enables the editor hotkeys while the editor is
+//! open.
+//!
+//! Editor tools register their own commands. Editor scripts may register
+//! commands as well, which are stored with the [`SCRIPT_COMMAND_PREFIX`] and
+//! reported back to the script engine when invoked.
+
+use awgen_ui::hotkeys::HotkeyRegistry;
+use awgen_ui::menus::layers::UiLayer;
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::command_palette::{CommandInvoked, CommandPalette};
+use bevy::prelude::*;
+
+use crate::app::AwgenState;
+use crate::scripts::{PacketOut, ScriptEngine};
+
+/// The prefix of the IDs of commands registered by scripts, which keeps them
+/// from replacing the built-in commands.
+pub const SCRIPT_COMMAND_PREFIX: &str = "script.";
+
+/// Plugin that sets up the editor command palette.
+pub struct CommandPalettePlugin;
+impl Plugin for CommandPalettePlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_systems(OnEnter(AwgenState::Editor), setup)
+            .add_systems(OnExit(AwgenState::Editor), cleanup)
+            .add_observer(forward_script_commands);
+    }
+}
+
+/// A marker component for the editor command palette.
+#[derive(Debug, Component)]
+pub struct EditorCommandPalette;
+
+/// Sets up the command palette and enables the editor hotkeys.
+fn setup(
+    asset_server: Res<AssetServer>,
+    mut hotkeys: ResMut<HotkeyRegistry>,
+    mut commands: Commands,
+) {
+    hotkeys.set_editor_enabled(true);
+    commands.spawn((
+        EditorCommandPalette,
+        ScreenAnchor::TopCenter,
+        Node {
+            width: px(480.0),
+            margin: UiRect::top(px(48.0)),
+            ..default()
+        },
+        UiLayer::Menus,
+        CommandPalette::new(hearth_theme(&asset_server)),
+    ));
+}
+
+/// Cleans up the command palette and disables the editor hotkeys.
+fn cleanup(
+    palette: Query<Entity, With<EditorCommandPalette>>,
+    mut hotkeys: ResMut<HotkeyRegistry>,
+    mut commands: Commands,
+) {
+    hotkeys.set_editor_enabled(false);
+    for entity in palette.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Reports commands registered by scripts to the script engine when they are
+/// invoked.
+fn forward_script_commands(trigger: On<CommandInvoked>, engine: Option<Res<ScriptEngine>>) {
+    let Some(id) = trigger.id.strip_prefix(SCRIPT_COMMAND_PREFIX) else {
+        return;
+    };
+
+    let Some(engine) = engine else {
+        return;
+    };
+
+    let packet = PacketOut::CommandInvoked { id: id.to_string() };
+    if let Err(err) = engine.send(packet) {
+        error!("Failed to send command to the script engine: {}", err);
+    }
+}
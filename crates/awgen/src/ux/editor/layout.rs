@@ -0,0 +1,579 @@
+//! This module persists the layout of the editor, so that it reopens with the
+//! same panels open, the same tools active, and the camera framing the same
+//! view as when it was last closed.
+//!
+//! The layout is stored in the settings table of the game database, so each
+//! project remembers its own workspace. Besides whether each panel is open,
+//! the layout stores where each panel is docked, its size, whether it is
+//! maximized, and the position and size of its window while it is detached.
+//! Panels are placed again when they are next opened.
+//!
+//! The layout is stored shortly after it changes, once it has stayed the same
+//! for [`SAVE_DELAY`] seconds, and again when the app exits.
+
+use awgen_ui::menus::maximize::{Maximized, ToggleMaximized};
+use awgen_ui::menus::overlay::{Anchored, ScreenAnchor};
+use awgen_ui::menus::windows::{DetachPanel, Detached};
+use bevy::app::AppExit;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::app::AwgenState;
+use crate::database::{Database, DatabaseError, GameDatabase};
+use crate::ux::editor::areas::AreaTool;
+use crate::ux::editor::blocks::BlockBrushTool;
+use crate::ux::editor::bookmarks::BookmarkPanel;
+use crate::ux::editor::lights::LightTool;
+use crate::ux::editor::models::BlockModelEditor;
+use crate::ux::editor::outliner::SceneOutliner;
+use crate::ux::editor::paint::FacePaintTool;
+use crate::ux::editor::panel::EditorPanel;
+use crate::ux::editor::stats::UsageStatsPanel;
+use crate::ux::{CameraController, CameraProjection};
+
+/// The settings key for the editor layout.
+const EDITOR_LAYOUT_KEY: &str = "editor_layout";
+
+/// The time, in seconds, that the layout must stay unchanged before it is
+/// stored, so that moving the camera or dragging a window does not write to
+/// the game database every frame.
+const SAVE_DELAY: f32 = 1.0;
+
+/// Plugin that restores the editor layout when the editor opens and stores it
+/// when it changes.
+pub struct EditorLayoutPlugin;
+impl Plugin for EditorLayoutPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<EditorLayout>()
+            .add_systems(Startup, load_editor_layout)
+            .add_systems(OnEnter(AwgenState::Editor), restore_editor_layout)
+            .add_systems(
+                Update,
+                (restore_panel_layouts, restore_panel_windows).run_if(in_state(AwgenState::Editor)),
+            )
+            .add_systems(
+                Last,
+                (
+                    track_editor_layout.run_if(in_state(AwgenState::Editor)),
+                    save_editor_layout,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// A resource that holds the last known layout of the editor.
+#[derive(Debug, Clone, Default, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditorLayout {
+    /// The editor panels that are open.
+    pub panels: Vec<PanelKind>,
+
+    /// The editor tools that are active.
+    pub tools: Vec<EditorTool>,
+
+    /// The state of the camera, if it is known.
+    pub camera: Option<CameraLayout>,
+
+    /// The placement of each panel that has been opened, whether or not it is
+    /// still open.
+    #[serde(default)]
+    pub docks: Vec<PanelLayout>,
+}
+
+/// A panel of the editor that can be opened and closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PanelKind {
+    /// The scene outliner.
+    Outliner,
+
+    /// The usage stats panel.
+    UsageStats,
+
+    /// The block model editor.
+    BlockModels,
+
+    /// The camera bookmark panel.
+    Bookmarks,
+
+    /// The panel of the light tool, which is open while the tool is active.
+    Lights,
+}
+
+impl PanelKind {
+    /// Gets the title of the window that the panel is detached into.
+    pub fn title(self) -> &'static str {
+        match self {
+            PanelKind::Outliner => "Scene Outliner",
+            PanelKind::UsageStats => "Usage Stats",
+            PanelKind::BlockModels => "Block Models",
+            PanelKind::Bookmarks => "Bookmarks",
+            PanelKind::Lights => "Lights",
+        }
+    }
+}
+
+/// A component on the root node of an editor panel, whose placement is stored
+/// in the editor layout.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct LayoutPanel(pub PanelKind);
+
+/// The stored placement of an editor panel.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanelLayout {
+    /// The panel.
+    pub panel: PanelKind,
+
+    /// The corner of the screen that the panel is docked to.
+    pub anchor: DockAnchor,
+
+    /// The logical size of the panel while it is docked, if it has been laid
+    /// out. The panel is never restored smaller than this.
+    pub size: Option<Vec2>,
+
+    /// Whether the panel is maximized.
+    pub maximized: bool,
+
+    /// The window that the panel is detached into, if it is detached.
+    pub window: Option<WindowLayout>,
+}
+
+/// The stored geometry of the window of a detached panel.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowLayout {
+    /// The physical position of the top-left corner of the window, if it is
+    /// known.
+    pub position: Option<IVec2>,
+
+    /// The logical size of the window.
+    pub size: Vec2,
+}
+
+/// The stored form of the [`ScreenAnchor`] that a panel is docked to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DockAnchor {
+    /// Top left corner of the screen.
+    TopLeft,
+
+    /// Top center of the screen.
+    TopCenter,
+
+    /// Top right corner of the screen.
+    TopRight,
+
+    /// Center left of the screen.
+    CenterLeft,
+
+    /// Center of the screen.
+    Center,
+
+    /// Center right of the screen.
+    CenterRight,
+
+    /// Bottom left corner of the screen.
+    BottomLeft,
+
+    /// Bottom center of the screen.
+    BottomCenter,
+
+    /// Bottom right corner of the screen.
+    BottomRight,
+
+    /// The entire screen.
+    Fullscreen,
+}
+
+impl From<ScreenAnchor> for DockAnchor {
+    fn from(anchor: ScreenAnchor) -> Self {
+        match anchor {
+            ScreenAnchor::TopLeft => DockAnchor::TopLeft,
+            ScreenAnchor::TopCenter => DockAnchor::TopCenter,
+            ScreenAnchor::TopRight => DockAnchor::TopRight,
+            ScreenAnchor::CenterLeft => DockAnchor::CenterLeft,
+            ScreenAnchor::Center => DockAnchor::Center,
+            ScreenAnchor::CenterRight => DockAnchor::CenterRight,
+            ScreenAnchor::BottomLeft => DockAnchor::BottomLeft,
+            ScreenAnchor::BottomCenter => DockAnchor::BottomCenter,
+            ScreenAnchor::BottomRight => DockAnchor::BottomRight,
+            ScreenAnchor::Fullscreen => DockAnchor::Fullscreen,
+        }
+    }
+}
+
+impl From<DockAnchor> for ScreenAnchor {
+    fn from(anchor: DockAnchor) -> Self {
+        match anchor {
+            DockAnchor::TopLeft => ScreenAnchor::TopLeft,
+            DockAnchor::TopCenter => ScreenAnchor::TopCenter,
+            DockAnchor::TopRight => ScreenAnchor::TopRight,
+            DockAnchor::CenterLeft => ScreenAnchor::CenterLeft,
+            DockAnchor::Center => ScreenAnchor::Center,
+            DockAnchor::CenterRight => ScreenAnchor::CenterRight,
+            DockAnchor::BottomLeft => ScreenAnchor::BottomLeft,
+            DockAnchor::BottomCenter => ScreenAnchor::BottomCenter,
+            DockAnchor::BottomRight => ScreenAnchor::BottomRight,
+            DockAnchor::Fullscreen => ScreenAnchor::Fullscreen,
+        }
+    }
+}
+
+/// A component on a panel that is being detached to restore its layout, which
+/// holds the geometry to give its window once it has been opened.
+#[derive(Debug, Component)]
+struct PendingWindow(WindowLayout);
+
+/// A tool of the editor that can be activated and deactivated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EditorTool {
+    /// The block brush tool.
+    BlockBrush,
+
+    /// The face paint tool.
+    FacePaint,
+
+    /// The light placement tool.
+    Lights,
+
+    /// The area tool.
+    Areas,
+}
+
+/// The stored state of the editor camera.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraLayout {
+    /// The position that the camera orbits around.
+    pub target: Vec3,
+
+    /// The rotation of the camera in Euler angles.
+    pub rotation: Vec3,
+
+    /// The distance of the camera from its target.
+    pub distance: f32,
+
+    /// The kind of projection used by the camera.
+    pub projection: CameraProjection,
+}
+
+impl EditorLayout {
+    /// Loads the editor layout from the game database, if it has been stored.
+    pub fn load(database: &Database) -> Result<Option<Self>, DatabaseError> {
+        let Some(value) = database.get_setting(EDITOR_LAYOUT_KEY)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(serde_json::from_str(&value)?))
+    }
+
+    /// Stores the editor layout in the game database.
+    pub fn save(&self, database: &Database) -> Result<(), DatabaseError> {
+        database.set_setting(EDITOR_LAYOUT_KEY, &serde_json::to_string(self)?)
+    }
+
+    /// Gets the stored placement of the given panel, if it has been opened.
+    pub fn dock(&self, panel: PanelKind) -> Option<&PanelLayout> {
+        self.docks.iter().find(|dock| dock.panel == panel)
+    }
+}
+
+/// The editor panels and tools whose open state is part of the layout.
+#[derive(SystemParam)]
+struct LayoutElements<'w> {
+    /// The scene outliner.
+    outliner: ResMut<'w, SceneOutliner>,
+
+    /// The usage stats panel.
+    usage_stats: ResMut<'w, UsageStatsPanel>,
+
+    /// The block model editor.
+    block_models: ResMut<'w, EditorPanel<BlockModelEditor>>,
+
+    /// The camera bookmark panel.
+    bookmarks: ResMut<'w, EditorPanel<BookmarkPanel>>,
+
+    /// The block brush tool.
+    block_brush: ResMut<'w, BlockBrushTool>,
+
+    /// The face paint tool.
+    face_paint: ResMut<'w, FacePaintTool>,
+
+    /// The light placement tool.
+    lights: ResMut<'w, EditorPanel<LightTool>>,
+
+    /// The area tool.
+    areas: ResMut<'w, EditorPanel<AreaTool>>,
+}
+
+impl LayoutElements<'_> {
+    /// Gets the editor panels that are currently open.
+    fn open_panels(&self) -> Vec<PanelKind> {
+        [
+            (PanelKind::Outliner, self.outliner.is_open()),
+            (PanelKind::UsageStats, self.usage_stats.is_open()),
+            (PanelKind::BlockModels, self.block_models.is_open()),
+            (PanelKind::Bookmarks, self.bookmarks.is_open()),
+        ]
+        .into_iter()
+        .filter_map(|(panel, open)| open.then_some(panel))
+        .collect()
+    }
+
+    /// Gets the editor tools that are currently active.
+    fn active_tools(&self) -> Vec<EditorTool> {
+        [
+            (EditorTool::BlockBrush, self.block_brush.is_active()),
+            (EditorTool::FacePaint, self.face_paint.is_active()),
+            (EditorTool::Lights, self.lights.is_open()),
+            (EditorTool::Areas, self.areas.is_open()),
+        ]
+        .into_iter()
+        .filter_map(|(tool, active)| active.then_some(tool))
+        .collect()
+    }
+
+    /// Requests the given panel to be opened, if it is closed.
+    fn open_panel(&mut self, panel: PanelKind) {
+        match panel {
+            PanelKind::Outliner if !self.outliner.is_open() => self.outliner.request_toggle(),
+            PanelKind::UsageStats if !self.usage_stats.is_open() => {
+                self.usage_stats.request_toggle()
+            }
+            PanelKind::BlockModels if !self.block_models.is_open() => {
+                self.block_models.request_toggle()
+            }
+            PanelKind::Bookmarks if !self.bookmarks.is_open() => self.bookmarks.request_toggle(),
+            _ => {}
+        }
+    }
+
+    /// Requests the given tool to be activated, if it is inactive.
+    fn activate_tool(&mut self, tool: EditorTool) {
+        match tool {
+            EditorTool::BlockBrush if !self.block_brush.is_active() => {
+                self.block_brush.request_toggle()
+            }
+            EditorTool::FacePaint if !self.face_paint.is_active() => {
+                self.face_paint.request_toggle()
+            }
+            EditorTool::Lights if !self.lights.is_open() => self.lights.request_toggle(),
+            EditorTool::Areas if !self.areas.is_open() => self.areas.request_toggle(),
+            _ => {}
+        }
+    }
+}
+
+/// Loads the stored editor layout from the game database.
+fn load_editor_layout(database: Res<GameDatabase>, mut layout: ResMut<EditorLayout>) {
+    match EditorLayout::load(&database) {
+        Ok(Some(stored)) => *layout = stored,
+        Ok(None) => {}
+        Err(err) => warn!("Failed to load the editor layout: {}", err),
+    }
+}
+
+/// Reopens the panels and tools of the editor layout, and moves the camera
+/// back to where it was.
+fn restore_editor_layout(
+    layout: Res<EditorLayout>,
+    mut elements: LayoutElements,
+    mut cameras: Query<&mut CameraController>,
+) {
+    for panel in &layout.panels {
+        elements.open_panel(*panel);
+    }
+
+    for tool in &layout.tools {
+        elements.activate_tool(*tool);
+    }
+
+    let Some(stored) = layout.camera else {
+        return;
+    };
+
+    let Ok(mut camera) = cameras.single_mut() else {
+        return;
+    };
+
+    // The camera is moved directly, rather than smoothly flying over from
+    // wherever it was.
+    camera.target_pos = stored.target;
+    camera.target_rot = stored.rotation;
+    camera.target_dist = stored.distance;
+    camera.pos = stored.target;
+    camera.rot = stored.rotation;
+    camera.dist = stored.distance;
+    camera.projection = stored.projection;
+}
+
+/// Places panels that were just opened as they were placed when the layout
+/// was stored: docked to the same corner, at least as large as they were, and
+/// maximized or detached if they were.
+fn restore_panel_layouts(
+    layout: Res<EditorLayout>,
+    mut panels: Query<(Entity, &LayoutPanel, &mut Node, Option<&Anchored>), Added<LayoutPanel>>,
+    mut commands: Commands,
+) {
+    for (entity, panel, mut node, anchored) in panels.iter_mut() {
+        let Some(stored) = layout.dock(panel.0) else {
+            continue;
+        };
+
+        if let Some(size) = stored.size {
+            node.min_width = px(size.x);
+            node.min_height = px(size.y);
+        }
+
+        let anchor = ScreenAnchor::from(stored.anchor);
+        if anchored.map(|anchored| anchored.0) != Some(anchor) {
+            commands.entity(entity).insert(anchor);
+        }
+
+        if let Some(window) = stored.window {
+            commands.entity(entity).insert(PendingWindow(window));
+            commands.trigger(DetachPanel {
+                entity,
+                title: panel.0.title().to_string(),
+            });
+        } else if stored.maximized {
+            commands.trigger(ToggleMaximized { entity });
+        }
+    }
+}
+
+/// Moves and resizes the windows of panels that were detached to restore their
+/// layout.
+fn restore_panel_windows(
+    panels: Query<(Entity, &Detached, &PendingWindow)>,
+    mut windows: Query<&mut Window>,
+    mut commands: Commands,
+) {
+    for (entity, detached, pending) in panels.iter() {
+        commands.entity(entity).remove::<PendingWindow>();
+
+        let Ok(mut window) = windows.get_mut(detached.window()) else {
+            continue;
+        };
+
+        window.resolution.set(pending.0.size.x, pending.0.size.y);
+        if let Some(position) = pending.0.position {
+            window.position = WindowPosition::At(position);
+        }
+    }
+}
+
+/// Records the layout of the editor whenever it changes.
+#[allow(clippy::type_complexity)]
+fn track_editor_layout(
+    mut layout: ResMut<EditorLayout>,
+    elements: LayoutElements,
+    cameras: Query<&CameraController>,
+    panels: Query<(
+        &LayoutPanel,
+        Option<&Anchored>,
+        Option<&ComputedNode>,
+        Option<&Detached>,
+        Has<Maximized>,
+    )>,
+    windows: Query<&Window>,
+) {
+    let mut docks = layout.docks.clone();
+    for (panel, anchored, computed, detached, maximized) in panels.iter() {
+        let previous = layout.dock(panel.0);
+        let Some(anchor) = anchored
+            .map(|anchored| DockAnchor::from(anchored.0))
+            .or(previous.map(|dock| dock.anchor))
+        else {
+            continue;
+        };
+
+        // The size of a maximized or detached panel is not its docked size.
+        let size = match (computed, detached, maximized) {
+            (Some(computed), None, false) => {
+                Some((computed.size() * computed.inverse_scale_factor()).round())
+                    .filter(|size| size.min_element() > 0.0)
+            }
+            _ => None,
+        }
+        .or(previous.and_then(|dock| dock.size));
+
+        let window = detached
+            .and_then(|detached| windows.get(detached.window()).ok())
+            .map(|window| WindowLayout {
+                position: match window.position {
+                    WindowPosition::At(position) => Some(position),
+                    _ => None,
+                },
+                size: Vec2::new(window.resolution.width(), window.resolution.height()),
+            });
+
+        let dock = PanelLayout {
+            panel: panel.0,
+            anchor,
+            size,
+            maximized,
+            window,
+        };
+
+        match docks.iter_mut().find(|stored| stored.panel == panel.0) {
+            Some(stored) => *stored = dock,
+            None => docks.push(dock),
+        }
+    }
+
+    let current = EditorLayout {
+        panels: elements.open_panels(),
+        tools: elements.active_tools(),
+        camera: cameras.single().ok().map(|camera| CameraLayout {
+            target: camera.target_pos,
+            rotation: camera.target_rot,
+            distance: camera.target_dist,
+            projection: camera.projection,
+        }),
+        docks,
+    };
+
+    if *layout != current {
+        *layout = current;
+    }
+}
+
+/// Stores the editor layout in the game database once it has stayed the same
+/// for [`SAVE_DELAY`] seconds after changing, or when the app exits.
+fn save_editor_layout(
+    mut exit: MessageReader<AppExit>,
+    layout: Res<EditorLayout>,
+    database: Res<GameDatabase>,
+    time: Res<Time<Real>>,
+    mut save_at: Local<Option<f32>>,
+) {
+    // The layout was just initialized or loaded, so it is already stored.
+    if layout.is_changed() && !layout.is_added() {
+        *save_at = Some(time.elapsed_secs() + SAVE_DELAY);
+    }
+
+    let exiting = !exit.is_empty();
+    exit.clear();
+
+    let Some(at) = *save_at else {
+        return;
+    };
+
+    if !exiting && time.elapsed_secs() < at {
+        return;
+    }
+    *save_at = None;
+
+    if database.is_read_only() {
+        return;
+    }
+
+    if let Err(err) = layout.save(&database) {
+        error!("Failed to store the editor layout: {}", err);
+    }
+}
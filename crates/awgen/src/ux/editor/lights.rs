@@ -0,0 +1,657 @@
+//! This module implements the light tool for the editor, which places, moves,
+//! and edits the point and spot lights stored in the game database.
+//!
+//! The tool is toggled with `L`, or with the Lights button of the toolbar.
+//! While it is active, every light is drawn as a gizmo and listed in a panel,
+//! which also holds an inspector for the parameters of the selected light. The
+//! Add buttons of the panel place a new light against the next clicked block.
+//! Lights are moved by dragging their gizmo, which moves them horizontally, or
+//! vertically while `Shift` is held. Lights locked in the scene outliner
+//! cannot be grabbed. The panel can be moved into its own window with the
+//! "Detach Light Panel" command.
+
+use awgen_ui::hotkeys::{BindHotkeyExt, Hotkey, HotkeyContext};
+use awgen_ui::menus::maximize::Maximizable;
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::menus::windows::{DetachPanel, Detached, DockPanel};
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::button::{ButtonBuilder, ButtonContent, button};
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use awgen_ui::widgets::inspector::{Inspector, InspectorChanged};
+use bevy::ecs::system::SystemParamItem;
+use bevy::picking::hover::HoverMap;
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+use bevy::window::PrimaryWindow;
+
+use crate::app::AwgenState;
+use crate::database::GameDatabase;
+use crate::database::lights::{LightKind, PlacedLight};
+use crate::map::{
+    BlockModel,
+    ChunkTable,
+    LightTable,
+    SceneLight,
+    VoxelChunk,
+    WorldPos,
+    raycast_blocks,
+};
+use crate::ux::CameraController;
+use crate::ux::editor::layout::{LayoutPanel, PanelKind};
+use crate::ux::editor::outliner::EditorLocked;
+use crate::ux::editor::panel::{AddEditorPanelExt, EditorPanel, EditorPanelSystems, PanelContent};
+use crate::ux::editor::picking::{cursor_ray, pointer_over};
+
+/// The maximum distance, in blocks, at which lights can be placed.
+const PICK_DISTANCE: f32 = 256.0;
+
+/// The radius of the gizmo drawn at the position of each light, which is also
+/// the radius within which the gizmo can be grabbed.
+const GIZMO_RADIUS: f32 = 0.3;
+
+/// The length of the arrow that shows the direction of a spot light.
+const ARROW_LENGTH: f32 = 1.5;
+
+/// The gizmo color of the selected light.
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+/// The gizmo color of lights that are switched off.
+const DISABLED_COLOR: Color = Color::srgb(0.4, 0.4, 0.4);
+
+/// Plugin that sets up the light tool.
+pub struct LightToolPlugin;
+impl Plugin for LightToolPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_editor_panel::<LightTool>(PaletteCommand::new(
+            "editor.lights",
+            "Toggle Light Tool",
+        ))
+        .add_systems(
+            Update,
+            (refresh_panel, edit_lights, draw_lights)
+                .chain()
+                .after(EditorPanelSystems)
+                .run_if(in_state(AwgenState::Editor)),
+        )
+        .add_observer(on_light_activate)
+        .add_observer(on_inspector_changed)
+        .register_command(
+            PaletteCommand::new("editor.lights.detach", "Detach Light Panel"),
+            toggle_detached,
+        )
+        .bind_hotkey(
+            "editor.lights",
+            Hotkey::new(KeyCode::KeyL),
+            HotkeyContext::Editor,
+        );
+    }
+}
+
+/// The state of the light tool, which is active while its panel is open.
+#[derive(Debug, Default)]
+pub struct LightTool {
+    /// The name of the selected light.
+    selected: Option<String>,
+
+    /// The kind of light placed by the next click, if any.
+    placing: Option<LightKind>,
+
+    /// The light being dragged, if any.
+    drag: Option<LightDrag>,
+}
+
+impl LightTool {
+    /// Gets the name of the selected light, if any.
+    pub fn selected(&self) -> Option<&str> {
+        self.selected.as_deref()
+    }
+}
+
+impl EditorPanel<LightTool> {
+    /// Selects the light with the given name, or clears the selection.
+    pub fn select(&mut self, name: Option<String>) {
+        if self.selected != name {
+            self.selected = name;
+            self.mark_dirty();
+        }
+    }
+}
+
+impl PanelContent for LightTool {
+    type Params = (Res<'static, AssetServer>, Res<'static, GameDatabase>);
+
+    fn spawn(
+        &mut self,
+        (asset_server, database): &mut SystemParamItem<Self::Params>,
+        commands: &mut Commands,
+    ) -> (Entity, Entity) {
+        let theme = hearth_theme(asset_server);
+        let panel = commands
+            .spawn((
+                LightPanel,
+                ScreenAnchor::BottomRight,
+                LayoutPanel(PanelKind::Lights),
+                Maximizable,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(8.0),
+                    min_width: px(280.0),
+                    max_height: percent(70.0),
+                    margin: UiRect::all(px(8.0)),
+                    ..default()
+                },
+                theme.outer_window.clone(),
+                children![(Text::new("Lights"), theme.outer_window.text.clone())],
+            ))
+            .id();
+
+        let list = commands
+            .spawn((
+                ChildOf(panel),
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(4.0),
+                    overflow: Overflow::scroll_y(),
+                    scrollbar_width: 4.0,
+                    ..default()
+                },
+            ))
+            .id();
+
+        if !database.is_read_only() {
+            let buttons = commands
+                .spawn((
+                    ChildOf(panel),
+                    Node {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: px(4.0),
+                        ..default()
+                    },
+                ))
+                .id();
+
+            for (kind, label) in [
+                (LightKind::Point, "Add Point Light"),
+                (LightKind::Spot, "Add Spot Light"),
+            ] {
+                commands.spawn((
+                    ChildOf(buttons),
+                    LightAction::Place(kind),
+                    button(ButtonBuilder {
+                        node: Node::default(),
+                        content: ButtonContent::text(label),
+                        theme: theme.clone(),
+                    }),
+                ));
+            }
+        }
+
+        (panel, list)
+    }
+
+    fn close(&mut self) {
+        self.placing = None;
+        self.drag = None;
+    }
+}
+
+/// A drag of a light gizmo.
+#[derive(Debug, Clone, Copy)]
+struct LightDrag {
+    /// The light entity being dragged.
+    entity: Entity,
+
+    /// Whether the light is moved vertically, rather than horizontally.
+    vertical: bool,
+}
+
+/// A marker component for the light panel.
+#[derive(Debug, Component)]
+struct LightPanel;
+
+/// A marker component for the toolbar button that toggles the light tool.
+#[derive(Debug, Component)]
+pub(super) struct LightToolToggle;
+
+/// An action performed when a button in the light panel is clicked.
+#[derive(Debug, Component)]
+enum LightAction {
+    /// Places a light of the given kind against the next clicked block.
+    Place(LightKind),
+
+    /// Selects the light with the given name.
+    Select(String),
+
+    /// Deletes the light with the given name.
+    Delete(String),
+}
+
+/// A component on the inspector of the light with the given name.
+#[derive(Debug, Component)]
+struct LightInspector(String);
+
+/// The parameters of a light that are edited with the inspector.
+///
+/// Parameters with large ranges are whole numbers, so each step of the
+/// inspector makes a visible difference.
+#[derive(Debug, Clone, Reflect)]
+struct LightParams {
+    /// The kind of the light.
+    kind: LightKind,
+
+    /// Whether the light is switched on.
+    enabled: bool,
+
+    /// The color of the light.
+    color: Color,
+
+    /// The luminous power of the light, in thousands of lumens.
+    kilolumens: u32,
+
+    /// The distance, in blocks, beyond which the light has no effect.
+    range: u32,
+
+    /// The cone angle of a spot light, in degrees.
+    angle: u32,
+
+    /// The pitch of a spot light, in degrees.
+    pitch: i32,
+
+    /// The yaw of a spot light, in degrees.
+    yaw: i32,
+
+    /// Whether the light casts shadows.
+    shadows: bool,
+}
+
+impl LightParams {
+    /// Gets the inspected parameters of the given light.
+    fn from_light(light: &PlacedLight) -> Self {
+        let [red, green, blue] = light.color;
+        Self {
+            kind: light.kind,
+            enabled: light.enabled,
+            color: Color::srgb(red, green, blue),
+            kilolumens: (light.intensity / 1000.0).round() as u32,
+            range: light.range.round() as u32,
+            angle: light.angle.round() as u32,
+            pitch: light.pitch.round() as i32,
+            yaw: light.yaw.round() as i32,
+            shadows: light.shadows,
+        }
+    }
+
+    /// Applies these parameters to the given light.
+    fn apply(&self, light: &mut PlacedLight) {
+        let color = self.color.to_srgba();
+        light.kind = self.kind;
+        light.enabled = self.enabled;
+        light.color = [color.red, color.green, color.blue];
+        light.intensity = self.kilolumens as f32 * 1000.0;
+        light.range = self.range.max(1) as f32;
+        light.angle = self.angle.clamp(1, 89) as f32;
+        light.pitch = self.pitch.clamp(-90, 90) as f32;
+        light.yaw = self.yaw.rem_euclid(360) as f32;
+        light.shadows = self.shadows;
+    }
+}
+
+/// Moves the light panel into its own window, or docks it back into the main
+/// window if it is already detached.
+fn toggle_detached(
+    tool: Res<EditorPanel<LightTool>>,
+    detached: Query<(), With<Detached>>,
+    mut commands: Commands,
+) {
+    let Some(panel) = tool.panel() else {
+        return;
+    };
+
+    if detached.contains(panel) {
+        commands.trigger(DockPanel { entity: panel });
+    } else {
+        commands.trigger(DetachPanel {
+            entity: panel,
+            title: "Lights".to_string(),
+        });
+    }
+}
+
+/// Rebuilds the light rows and the inspector when the lights or the selection
+/// have changed.
+fn refresh_panel(
+    asset_server: Res<AssetServer>,
+    database: Res<GameDatabase>,
+    table: Res<LightTable>,
+    lights: Query<&SceneLight>,
+    mut tool: ResMut<EditorPanel<LightTool>>,
+    mut commands: Commands,
+) {
+    if table.is_changed() {
+        tool.mark_dirty();
+    }
+
+    let Some(list) = tool.take_dirty() else {
+        return;
+    };
+
+    commands.entity(list).despawn_related::<Children>();
+
+    let mut placed = table
+        .iter()
+        .filter_map(|entity| lights.get(entity).ok())
+        .map(|light| &light.0)
+        .collect::<Vec<_>>();
+    placed.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let theme = hearth_theme(&asset_server);
+    for light in placed.iter() {
+        let row = commands
+            .spawn((
+                ChildOf(list),
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: px(4.0),
+                    ..default()
+                },
+            ))
+            .id();
+
+        let marker = if tool.selected() == Some(light.name.as_str()) {
+            "> "
+        } else {
+            ""
+        };
+
+        let kind = match light.kind {
+            LightKind::Point => "point",
+            LightKind::Spot => "spot",
+        };
+
+        commands.spawn((
+            ChildOf(row),
+            LightAction::Select(light.name.clone()),
+            button(ButtonBuilder {
+                node: Node {
+                    flex_grow: 1.0,
+                    ..default()
+                },
+                content: ButtonContent::text(format!("{}{} [{}]", marker, light.name, kind)),
+                theme: theme.clone(),
+            }),
+        ));
+
+        if !database.is_read_only() {
+            commands.spawn((
+                ChildOf(row),
+                LightAction::Delete(light.name.clone()),
+                button(ButtonBuilder {
+                    node: Node::default(),
+                    content: ButtonContent::text("X"),
+                    theme: theme.clone(),
+                }),
+            ));
+        }
+    }
+
+    let selected = placed
+        .iter()
+        .find(|light| tool.selected() == Some(light.name.as_str()));
+
+    if let Some(light) = selected {
+        if !database.is_read_only() {
+            commands.spawn((
+                ChildOf(list),
+                LightInspector(light.name.clone()),
+                Inspector::new(theme, LightParams::from_light(light)),
+            ));
+        }
+    }
+}
+
+/// Performs the action of a light panel or toolbar button when it is clicked.
+fn on_light_activate(
+    trigger: On<Activate>,
+    actions: Query<&LightAction>,
+    toggles: Query<(), With<LightToolToggle>>,
+    database: Res<GameDatabase>,
+    table: Res<LightTable>,
+    mut tool: ResMut<EditorPanel<LightTool>>,
+    mut commands: Commands,
+) {
+    let entity = trigger.event().entity;
+    if toggles.contains(entity) {
+        tool.request_toggle();
+        return;
+    }
+
+    let Ok(action) = actions.get(entity) else {
+        return;
+    };
+
+    match action {
+        LightAction::Place(kind) => {
+            tool.placing = Some(*kind);
+        }
+        LightAction::Select(name) => {
+            tool.selected = Some(name.clone());
+        }
+        LightAction::Delete(name) => {
+            if let Err(err) = database.delete_light(name) {
+                error!("Failed to delete light \"{}\": {}", name, err);
+                return;
+            }
+
+            if let Some(entity) = table.get(name) {
+                commands.entity(entity).despawn();
+            }
+
+            if tool.selected() == Some(name.as_str()) {
+                tool.selected = None;
+            }
+        }
+    }
+
+    tool.mark_dirty();
+}
+
+/// Applies the parameters edited in the inspector to the selected light, and
+/// stores the light.
+fn on_inspector_changed(
+    trigger: On<InspectorChanged>,
+    inspectors: Query<(&Inspector, &LightInspector)>,
+    database: Res<GameDatabase>,
+    table: Res<LightTable>,
+    mut lights: Query<&mut SceneLight>,
+) {
+    let Ok((inspector, LightInspector(name))) = inspectors.get(trigger.event().entity) else {
+        return;
+    };
+
+    let Some(params) = inspector.value_as::<LightParams>() else {
+        return;
+    };
+
+    let Some(mut light) = table
+        .get(name)
+        .and_then(|entity| lights.get_mut(entity).ok())
+    else {
+        return;
+    };
+
+    params.apply(&mut light.0);
+    if let Err(err) = database.set_light(&light.0) {
+        error!("Failed to store light \"{}\": {}", name, err);
+    }
+}
+
+/// Places new lights against clicked blocks, and moves lights when their
+/// gizmos are dragged.
+#[allow(clippy::too_many_arguments)]
+fn edit_lights(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    hover_map: Res<HoverMap>,
+    parents: Query<&ChildOf>,
+    panels: Query<(), With<LightPanel>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<CameraController>>,
+    chunk_table: Res<ChunkTable>,
+    chunks: Query<&VoxelChunk>,
+    database: Res<GameDatabase>,
+    table: Res<LightTable>,
+    mut lights: Query<(Entity, &mut SceneLight, Has<EditorLocked>)>,
+    mut tool: ResMut<EditorPanel<LightTool>>,
+    mut commands: Commands,
+) {
+    if !tool.is_open() || database.is_read_only() {
+        return;
+    }
+
+    if let Some(drag) = tool.drag {
+        let Ok((_, mut light, _)) = lights.get_mut(drag.entity) else {
+            tool.drag = None;
+            return;
+        };
+
+        if !mouse_buttons.pressed(MouseButton::Left) {
+            if let Err(err) = database.set_light(&light.0) {
+                error!("Failed to store light \"{}\": {}", light.0.name, err);
+            }
+            tool.drag = None;
+            return;
+        }
+
+        if let Some(ray) = cursor_ray(&windows, &cameras) {
+            let position = Vec3::from(light.0.position);
+            if let Some(moved) = drag_position(ray, position, drag.vertical) {
+                light.0.position = moved.to_array();
+            }
+        }
+        return;
+    }
+
+    if mouse_buttons.just_pressed(MouseButton::Right) {
+        tool.placing = None;
+        return;
+    }
+
+    if !mouse_buttons.just_pressed(MouseButton::Left) || pointer_over(&hover_map, &parents, &panels)
+    {
+        return;
+    }
+
+    let Some(ray) = cursor_ray(&windows, &cameras) else {
+        return;
+    };
+
+    let Some(kind) = tool.placing.take() else {
+        let picked = lights
+            .iter()
+            .filter(|(_, _, locked)| !locked)
+            .filter_map(|(entity, light, _)| {
+                let distance = ray_sphere(ray, Vec3::from(light.0.position), GIZMO_RADIUS)?;
+                Some((distance, entity, light.0.name.clone()))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        if let Some((_, entity, name)) = picked {
+            tool.select(Some(name));
+
+            tool.drag = Some(LightDrag {
+                entity,
+                vertical: keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            });
+        }
+        return;
+    };
+
+    let is_solid = |pos: WorldPos| {
+        chunk_table
+            .get_chunk(pos.as_chunk_pos())
+            .and_then(|chunk_id| chunks.get(chunk_id).ok())
+            .is_some_and(|chunk| !matches!(chunk.get_models().get(pos), BlockModel::Empty))
+    };
+
+    let Some(hit) = raycast_blocks(ray, PICK_DISTANCE, is_solid) else {
+        return;
+    };
+
+    let name = (1 ..)
+        .map(|number| format!("Light {}", number))
+        .find(|name| table.get(name).is_none())
+        .unwrap();
+
+    let position = hit.pos.as_vec3() + hit.face.normal().as_vec3() + Vec3::splat(0.5);
+    let light = PlacedLight::new(name, kind, position.to_array());
+    if let Err(err) = database.set_light(&light) {
+        error!("Failed to store light \"{}\": {}", light.name, err);
+        return;
+    }
+
+    info!("Placed light \"{}\" at {}", light.name, position);
+    tool.selected = Some(light.name.clone());
+    tool.mark_dirty();
+    commands.spawn(SceneLight(light));
+}
+
+/// Gets the new position of a dragged light that follows the given ray.
+///
+/// Horizontal drags move the light within the horizontal plane through it.
+/// Vertical drags move the light along the vertical line through it, using the
+/// vertical plane that faces the ray.
+fn drag_position(ray: Ray3d, position: Vec3, vertical: bool) -> Option<Vec3> {
+    if !vertical {
+        let distance = ray.intersect_plane(position, InfinitePlane3d::new(Vec3::Y))?;
+        let point = ray.get_point(distance);
+        return Some(Vec3::new(point.x, position.y, point.z));
+    }
+
+    let normal = Vec3::new(ray.direction.x, 0.0, ray.direction.z);
+    let normal = Dir3::new(normal).ok()?;
+    let distance = ray.intersect_plane(position, InfinitePlane3d::new(normal))?;
+    let point = ray.get_point(distance);
+    Some(Vec3::new(position.x, point.y, position.z))
+}
+
+/// Gets the distance along the ray to the closest point of the ray to the
+/// center of the given sphere, if the ray passes through the sphere.
+fn ray_sphere(ray: Ray3d, center: Vec3, radius: f32) -> Option<f32> {
+    let distance = (center - ray.origin).dot(*ray.direction);
+    if distance < 0.0 || ray.get_point(distance).distance(center) > radius {
+        return None;
+    }
+
+    Some(distance)
+}
+
+/// Draws a gizmo for every light while the tool is active.
+fn draw_lights(tool: Res<EditorPanel<LightTool>>, lights: Query<&SceneLight>, mut gizmos: Gizmos) {
+    if !tool.is_open() {
+        return;
+    }
+
+    for light in lights.iter() {
+        let selected = tool.selected() == Some(light.0.name.as_str());
+        let [red, green, blue] = light.0.color;
+        let color = if selected {
+            SELECTED_COLOR
+        } else if light.0.enabled {
+            Color::srgb(red, green, blue)
+        } else {
+            DISABLED_COLOR
+        };
+
+        let transform = light.transform();
+        gizmos.sphere(transform.translation, GIZMO_RADIUS, color);
+
+        if light.0.kind == LightKind::Spot {
+            let tip = transform.translation + transform.forward() * ARROW_LENGTH;
+            gizmos.arrow(transform.translation, tip, color);
+        }
+
+        if selected {
+            gizmos.sphere(transform.translation, light.0.range, color.with_alpha(0.25));
+        }
+    }
+}
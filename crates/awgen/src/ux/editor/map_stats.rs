@@ -0,0 +1,583 @@
+//! This module implements the map statistics panel for the editor, which
+//! reports how many blocks of each block model and tile make up the map.
+//!
+//! The panel is toggled with the "Toggle Map Statistics" command. Either the
+//! loaded chunks or the chunks stored in a save slot are scanned in a
+//! background job, counting the blocks of each block model, the cube faces of
+//! each tile, the total filled volume, and the filled blocks of each layer.
+//! The results can be exported as JSON.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+
+use awgen_asset_db::param::AwgenAssets;
+use awgen_ui::menus::maximize::Maximizable;
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::button::{ButtonBuilder, ButtonContent, button};
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+use serde::Serialize;
+
+use crate::app::{AwgenState, ProjectSettings};
+use crate::database::block_models::{BlockModelAssets, NamedBlockModel};
+use crate::database::savegame::{SaveGame, list_saves};
+use crate::database::{DatabaseError, ProjectAssets};
+use crate::jobs::{
+    BackgroundJobSystems,
+    BackgroundJobs,
+    BackgroundJobsAppExt,
+    JobContext,
+    JobFinished,
+    JobHandle,
+    JobResult,
+};
+use crate::map::{BlockModel, ChunkModels, ChunkPos, VoxelChunk};
+
+/// The file name suggested when exporting the statistics.
+const EXPORT_FILE_NAME: &str = "map_stats.json";
+
+/// Plugin that sets up the editor map statistics panel.
+pub struct MapStatsPlugin;
+impl Plugin for MapStatsPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<MapStatsPanel>()
+            .register_job_output::<MapScan>()
+            .register_job_output::<StatsExported>()
+            .add_systems(
+                Update,
+                (
+                    toggle_panel,
+                    finish_scans,
+                    finish_exports,
+                    refresh_buttons,
+                    refresh_text,
+                )
+                    .chain()
+                    .after(BackgroundJobSystems::Poll)
+                    .run_if(in_state(AwgenState::Editor)),
+            )
+            .add_systems(OnExit(AwgenState::Editor), close_panel)
+            .add_observer(on_map_stats_activate)
+            .register_command(
+                PaletteCommand::new("editor.map_stats", "Toggle Map Statistics"),
+                request_toggle,
+            );
+    }
+}
+
+/// Statistics about the blocks that make up a map.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapStats {
+    /// A description of the chunks that were scanned.
+    pub source: String,
+
+    /// The number of chunks that were scanned.
+    pub chunks: u64,
+
+    /// The number of non-empty blocks.
+    pub volume: u64,
+
+    /// The number of non-empty blocks of each block model.
+    ///
+    /// Blocks matching a named block model are counted under its name, and
+    /// other blocks under the type of their model.
+    pub models: BTreeMap<String, u64>,
+
+    /// The number of cube faces drawn with each tile, by tile palette ID.
+    pub tiles: BTreeMap<u32, u64>,
+
+    /// The number of non-empty blocks in each layer, by world Y coordinate.
+    pub layers: BTreeMap<i32, u64>,
+}
+
+impl MapStats {
+    /// Creates empty statistics for the given source.
+    fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            ..default()
+        }
+    }
+
+    /// Adds the blocks of a chunk to the statistics.
+    fn add_chunk(&mut self, pos: ChunkPos, models: &ChunkModels, named: &[NamedBlockModel]) {
+        self.chunks += 1;
+
+        for (index, model) in models.as_slice().iter().enumerate() {
+            let BlockModel::Cube(cube) = model else {
+                continue;
+            };
+
+            self.volume += 1;
+            *self.layers.entry(pos.block_at_index(index).y).or_default() += 1;
+
+            let name = named
+                .iter()
+                .find(|named| named.model == *model)
+                .map_or("Unnamed Cube", |named| named.name.as_str());
+            match self.models.get_mut(name) {
+                Some(count) => *count += 1,
+                None => {
+                    self.models.insert(name.to_string(), 1);
+                }
+            }
+
+            for face in [
+                &cube.pos_y,
+                &cube.pos_z,
+                &cube.neg_z,
+                &cube.pos_x,
+                &cube.neg_x,
+            ] {
+                *self.tiles.entry(face.tile_index).or_default() += 1;
+            }
+        }
+    }
+}
+
+/// The output of a map scan job.
+#[derive(Debug)]
+pub struct MapScan(pub Result<MapStats, DatabaseError>);
+
+/// The output of a map statistics export job, with the path the statistics
+/// were written to, or `None` if the file dialog was closed.
+#[derive(Debug)]
+pub struct StatsExported(pub io::Result<Option<PathBuf>>);
+
+/// The state of the map statistics panel.
+#[derive(Debug, Default, Resource)]
+pub struct MapStatsPanel {
+    /// The panel, while it is open.
+    panel: Option<Entity>,
+
+    /// The text listing the statistics, while the panel is open.
+    text: Option<Entity>,
+
+    /// The container of the panel buttons, while the panel is open.
+    buttons: Option<Entity>,
+
+    /// The running scan, if any.
+    scan: Option<JobHandle<MapScan>>,
+
+    /// The statistics of the last completed scan.
+    stats: Option<MapStats>,
+
+    /// The error of the last failed scan, if it failed.
+    error: Option<String>,
+
+    /// Whether the panel buttons and text need to be rebuilt.
+    dirty: bool,
+
+    /// Whether the panel should be toggled on the next update.
+    toggle: bool,
+}
+
+impl MapStatsPanel {
+    /// Returns true if the panel is open.
+    pub fn is_open(&self) -> bool {
+        self.panel.is_some()
+    }
+
+    /// Requests the panel to be opened or closed on the next update.
+    pub fn request_toggle(&mut self) {
+        self.toggle = true;
+    }
+
+    /// Gets the statistics of the last completed scan, if any.
+    pub fn stats(&self) -> Option<&MapStats> {
+        self.stats.as_ref()
+    }
+}
+
+/// An action performed when a button in the map statistics panel is clicked.
+#[derive(Debug, Component)]
+enum MapStatsAction {
+    /// Scans the loaded chunks.
+    ScanLoaded,
+
+    /// Scans the chunks stored in the save slot with the given name.
+    ScanSave(String),
+
+    /// Cancels the running scan.
+    Cancel,
+
+    /// Exports the statistics of the last scan as JSON.
+    Export,
+}
+
+/// Toggles the panel on the next update, when run from the command palette.
+fn request_toggle(mut state: ResMut<MapStatsPanel>) {
+    state.toggle = true;
+}
+
+/// Opens or closes the map statistics panel.
+fn toggle_panel(
+    asset_server: Res<AssetServer>,
+    jobs: Res<BackgroundJobs>,
+    mut state: ResMut<MapStatsPanel>,
+    mut commands: Commands,
+) {
+    if !state.toggle {
+        return;
+    }
+    state.toggle = false;
+
+    if let Some(panel) = state.panel.take() {
+        commands.entity(panel).despawn();
+        state.text = None;
+        state.buttons = None;
+        if let Some(scan) = state.scan.take() {
+            jobs.cancel(scan.id());
+        }
+        return;
+    }
+
+    let theme = hearth_theme(&asset_server);
+    let panel = commands
+        .spawn((
+            ScreenAnchor::Center,
+            Maximizable,
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: px(8.0),
+                min_width: px(280.0),
+                max_width: px(400.0),
+                max_height: percent(80.0),
+                ..default()
+            },
+            theme.outer_window.clone(),
+            children![(Text::new("Map Statistics"), theme.outer_window.text.clone())],
+        ))
+        .id();
+
+    let text = commands
+        .spawn((
+            ChildOf(panel),
+            Node {
+                overflow: Overflow::scroll_y(),
+                scrollbar_width: 4.0,
+                ..default()
+            },
+            Text::default(),
+            theme.outer_window.text.clone(),
+        ))
+        .id();
+
+    let buttons = commands
+        .spawn((
+            ChildOf(panel),
+            Node {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                column_gap: px(4.0),
+                row_gap: px(4.0),
+                ..default()
+            },
+        ))
+        .id();
+
+    state.panel = Some(panel);
+    state.text = Some(text);
+    state.buttons = Some(buttons);
+    state.dirty = true;
+}
+
+/// Closes the map statistics panel when leaving the editor, cancelling the
+/// running scan.
+fn close_panel(
+    jobs: Res<BackgroundJobs>,
+    mut state: ResMut<MapStatsPanel>,
+    mut commands: Commands,
+) {
+    if let Some(panel) = state.panel.take() {
+        commands.entity(panel).despawn();
+    }
+    if let Some(scan) = state.scan.take() {
+        jobs.cancel(scan.id());
+    }
+    state.text = None;
+    state.buttons = None;
+    state.dirty = false;
+}
+
+/// Stores the statistics of finished scans.
+fn finish_scans(
+    mut finished: MessageReader<JobFinished<MapScan>>,
+    mut state: ResMut<MapStatsPanel>,
+) {
+    for job in finished.read() {
+        if state.scan != Some(job.handle) {
+            continue;
+        }
+        state.scan = None;
+        state.dirty = true;
+
+        match &job.result {
+            JobResult::Completed(MapScan(Ok(stats))) => {
+                info!(
+                    "Scanned {} chunks of the {}: {} blocks",
+                    stats.chunks, stats.source, stats.volume
+                );
+                state.stats = Some(stats.clone());
+                state.error = None;
+            }
+            JobResult::Completed(MapScan(Err(err))) => {
+                error!("Failed to scan the map: {}", err);
+                state.error = Some(err.to_string());
+            }
+            JobResult::Cancelled => info!("Map scan was cancelled."),
+        }
+    }
+}
+
+/// Reports the result of finished exports.
+fn finish_exports(mut finished: MessageReader<JobFinished<StatsExported>>) {
+    for job in finished.read() {
+        match &job.result {
+            JobResult::Completed(StatsExported(Ok(Some(path)))) => {
+                info!("Exported the map statistics to {}", path.display());
+            }
+            JobResult::Completed(StatsExported(Err(err))) => {
+                error!("Failed to export the map statistics: {}", err);
+            }
+            JobResult::Completed(StatsExported(Ok(None))) | JobResult::Cancelled => {}
+        }
+    }
+}
+
+/// Rebuilds the panel buttons when a scan starts or finishes.
+fn refresh_buttons(
+    asset_server: Res<AssetServer>,
+    project: Res<ProjectSettings>,
+    mut state: ResMut<MapStatsPanel>,
+    mut commands: Commands,
+) {
+    if !state.dirty {
+        return;
+    }
+    state.dirty = false;
+
+    let Some(buttons) = state.buttons else {
+        return;
+    };
+
+    commands.entity(buttons).despawn_related::<Children>();
+
+    let theme = hearth_theme(&asset_server);
+    let mut actions = Vec::new();
+    if state.scan.is_some() {
+        actions.push((MapStatsAction::Cancel, "Cancel Scan".to_string()));
+    } else {
+        actions.push((MapStatsAction::ScanLoaded, "Scan Loaded Chunks".to_string()));
+
+        match list_saves(project.project_folder()) {
+            Ok(saves) => {
+                for save in saves {
+                    let label = format!("Scan Save \"{}\"", save.slot);
+                    actions.push((MapStatsAction::ScanSave(save.slot), label));
+                }
+            }
+            Err(err) => error!("Failed to list the save slots: {}", err),
+        }
+
+        if state.stats.is_some() {
+            actions.push((MapStatsAction::Export, "Export JSON".to_string()));
+        }
+    }
+
+    for (action, label) in actions {
+        commands.spawn((
+            ChildOf(buttons),
+            action,
+            button(ButtonBuilder {
+                node: Node::default(),
+                content: ButtonContent::text(label),
+                theme: theme.clone(),
+            }),
+        ));
+    }
+}
+
+/// Refreshes the panel text, showing the progress of the running scan or the
+/// statistics of the last scan.
+fn refresh_text(jobs: Res<BackgroundJobs>, state: Res<MapStatsPanel>, mut texts: Query<&mut Text>) {
+    let Some(text) = state.text else {
+        return;
+    };
+
+    let Ok(mut text) = texts.get_mut(text) else {
+        return;
+    };
+
+    if let Some(scan) = state.scan {
+        let progress = jobs.progress(scan.id()).unwrap_or(1.0);
+        let message = format!("Scanning... {:.0}%", progress * 100.0);
+        text.set_if_neq(Text(message));
+        return;
+    }
+
+    if !state.is_changed() {
+        return;
+    }
+
+    let message = match (&state.stats, &state.error) {
+        (_, Some(err)) => format!("The last scan failed: {err}"),
+        (Some(stats), None) => stats_text(stats),
+        (None, None) => "Scan the loaded chunks, or the chunks stored in a save slot, to \
+                         count the blocks that make up the map."
+            .to_string(),
+    };
+    text.set_if_neq(Text(message));
+}
+
+/// Describes the given map statistics.
+fn stats_text(stats: &MapStats) -> String {
+    let mut text = format!(
+        "Source: {}\nChunks: {}\nFilled volume: {} blocks",
+        stats.source, stats.chunks, stats.volume
+    );
+
+    text += "\n\nBlock models:";
+    if stats.models.is_empty() {
+        text += "\n  None";
+    }
+    for (name, count) in &stats.models {
+        text += &format!("\n  {name}: {count}");
+    }
+
+    text += "\n\nTiles (cube faces):";
+    if stats.tiles.is_empty() {
+        text += "\n  None";
+    }
+    for (tile, count) in &stats.tiles {
+        text += &format!("\n  #{tile}: {count}");
+    }
+
+    text += "\n\nLayers (filled blocks):";
+    if stats.layers.is_empty() {
+        text += "\n  None";
+    }
+    for (y, count) in stats.layers.iter().rev() {
+        text += &format!("\n  y = {y}: {count}");
+    }
+
+    text
+}
+
+/// Performs the action of a map statistics panel button when it is clicked.
+fn on_map_stats_activate(
+    trigger: On<Activate>,
+    actions: Query<&MapStatsAction>,
+    chunks: Query<&VoxelChunk>,
+    project: Res<ProjectSettings>,
+    // The asset database queues previews as background jobs, so it cannot be
+    // borrowed alongside them.
+    mut assets_and_jobs: ParamSet<(AwgenAssets<ProjectAssets>, ResMut<BackgroundJobs>)>,
+    mut state: ResMut<MapStatsPanel>,
+) {
+    let Ok(action) = actions.get(trigger.event().entity) else {
+        return;
+    };
+
+    match action {
+        MapStatsAction::ScanLoaded => {
+            let loaded = chunks
+                .iter()
+                .map(|chunk| (chunk.pos(), chunk.get_models().clone()))
+                .collect::<Vec<_>>();
+            let named = named_models(&assets_and_jobs.p0());
+
+            let handle = assets_and_jobs
+                .p1()
+                .spawn("Scanning loaded chunks", |ctx| async move {
+                    let mut stats = MapStats::new("loaded chunks");
+                    scan_chunks(&ctx, &mut stats, &loaded, &named);
+                    MapScan(Ok(stats))
+                });
+            state.scan = Some(handle);
+        }
+        MapStatsAction::ScanSave(slot) => {
+            let project_folder = project.project_folder().to_path_buf();
+            let slot = slot.clone();
+            let named = named_models(&assets_and_jobs.p0());
+
+            let label = format!("Scanning save \"{}\"", slot);
+            let handle = assets_and_jobs.p1().spawn(label, |ctx| async move {
+                let result = SaveGame::open(&project_folder, &slot)
+                    .and_then(|save| save.read_chunks())
+                    .map(|stored| {
+                        let mut stats = MapStats::new(format!("save \"{slot}\""));
+                        scan_chunks(&ctx, &mut stats, &stored, &named);
+                        stats
+                    });
+                MapScan(result)
+            });
+            state.scan = Some(handle);
+        }
+        MapStatsAction::Cancel => {
+            if let Some(scan) = state.scan.take() {
+                assets_and_jobs.p1().cancel(scan.id());
+            }
+        }
+        MapStatsAction::Export => {
+            let Some(stats) = state.stats.clone() else {
+                return;
+            };
+
+            assets_and_jobs
+                .p1()
+                .spawn("Exporting map statistics", |_| async move {
+                    let dialog = rfd::AsyncFileDialog::new()
+                        .set_title("Export Map Statistics")
+                        .set_file_name(EXPORT_FILE_NAME)
+                        .add_filter("JSON", &["json"]);
+
+                    let Some(file) = dialog.save_file().await else {
+                        return StatsExported(Ok(None));
+                    };
+
+                    let path = file.path().to_path_buf();
+                    let result = serde_json::to_string_pretty(&stats)
+                        .map_err(io::Error::from)
+                        .and_then(|json| std::fs::write(&path, json))
+                        .map(|()| Some(path));
+                    StatsExported(result)
+                });
+        }
+    }
+
+    state.dirty = true;
+}
+
+/// Lists the named block models, which blocks are matched against so that they
+/// can be counted by name.
+fn named_models(assets: &AwgenAssets<ProjectAssets>) -> Vec<NamedBlockModel> {
+    assets.list_block_models().unwrap_or_else(|err| {
+        warn!("Failed to list the block models: {}", err);
+        Vec::new()
+    })
+}
+
+/// Adds the given chunks to the statistics, reporting the progress of the
+/// scan to the job.
+///
+/// Stops early if the job is cancelled, as its output is discarded anyway.
+fn scan_chunks(
+    ctx: &JobContext,
+    stats: &mut MapStats,
+    chunks: &[(ChunkPos, ChunkModels)],
+    named: &[NamedBlockModel],
+) {
+    for (index, (pos, models)) in chunks.iter().enumerate() {
+        if ctx.is_cancelled() {
+            return;
+        }
+
+        stats.add_chunk(*pos, models, named);
+        ctx.set_progress((index + 1) as f32 / chunks.len() as f32);
+    }
+}
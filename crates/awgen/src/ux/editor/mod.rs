@@ -2,12 +2,40 @@
 
 use bevy::prelude::*;
 
+pub mod areas;
+pub mod blocks;
+pub mod bookmarks;
+pub mod commands;
+pub mod layout;
+pub mod lights;
+pub mod map_stats;
+pub mod models;
+pub mod outliner;
+pub mod paint;
+pub mod panel;
+pub(crate) mod picking;
+pub mod stats;
+pub mod terrain_export;
 pub mod toolbar;
 
 /// Plugin that sets up the editor UX.
 pub struct EditorUXPlugin;
 impl Plugin for EditorUXPlugin {
     fn build(&self, app_: &mut App) {
-        app_.add_plugins(toolbar::EditorToolbarPlugin);
+        app_.add_plugins((
+            areas::AreaToolPlugin,
+            blocks::BlockBrushPlugin,
+            bookmarks::BookmarkPanelPlugin,
+            commands::CommandPalettePlugin,
+            layout::EditorLayoutPlugin,
+            lights::LightToolPlugin,
+            map_stats::MapStatsPlugin,
+            models::BlockModelEditorPlugin,
+            outliner::SceneOutlinerPlugin,
+            paint::FacePaintPlugin,
+            stats::UsageStatsPlugin,
+            terrain_export::TerrainExportPlugin,
+            toolbar::EditorToolbarPlugin,
+        ));
     }
 }
@@ -0,0 +1,739 @@
+//! This module implements the block model editor, which authors block models
+//! and stores them as assets in the project asset database.
+//!
+//! The editor is toggled with the "Toggle Block Model Editor" command. The type
+//! of the model is picked from the type buttons. The tile of each face is
+//! picked from the active tileset, either by stepping through the tiles or by
+//! copying the tile of the face paint brush, and the model is shown as a
+//! rotating preview. Stored models can be loaded back into the
+//! editor, or placed with the block brush tool.
+
+use awgen_asset_db::param::AwgenAssets;
+use awgen_asset_db::watcher::AssetRecordChanged;
+use awgen_ui::menus::maximize::Maximizable;
+use awgen_ui::menus::overlay::{Node3D, ScreenAnchor};
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::button::{ButtonBuilder, ButtonContent, button};
+use awgen_ui::widgets::command_palette::PaletteCommand;
+use bevy::camera::visibility::RenderLayers;
+use bevy::ecs::system::SystemParamItem;
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+
+use crate::app::AwgenState;
+use crate::database::ProjectAssets;
+use crate::database::backup::GameDatabaseRestored;
+use crate::database::block_models::{BlockModelAssets, NamedBlockModel};
+use crate::map::{BlockFace, BlockModel, Cube, Occlusion, TileFace};
+use crate::tiles::{
+    ActiveTilesets,
+    TerrainMesh,
+    TilePalette,
+    TilesetMaterial,
+    TilesetReloaded,
+    extract_tile,
+};
+use crate::ux::editor::blocks::BlockBrushTool;
+use crate::ux::editor::layout::{LayoutPanel, PanelKind};
+use crate::ux::editor::paint::{FacePaintTool, ROTATE_CW};
+use crate::ux::editor::panel::{AddEditorPanelExt, EditorPanel, EditorPanelSystems, PanelContent};
+
+/// The width and height of the preview, in pixels.
+const PREVIEW_SIZE: f32 = 128.0;
+
+/// The edge length of the previewed block, in pixels.
+const PREVIEW_SCALE: f32 = 56.0;
+
+/// The angle, in degrees, that the preview is tilted towards the camera, so
+/// that the top face is visible.
+const PREVIEW_TILT: f32 = 25.0;
+
+/// The speed at which the preview rotates, in radians per second.
+const PREVIEW_SPIN_SPEED: f32 = 0.8;
+
+/// The faces of a cube that can be edited, with their display names.
+const FACES: [(BlockFace, &str); 5] = [
+    (BlockFace::PosY, "Top"),
+    (BlockFace::PosZ, "North"),
+    (BlockFace::NegZ, "South"),
+    (BlockFace::PosX, "East"),
+    (BlockFace::NegX, "West"),
+];
+
+/// The types of block models that can be authored, with their display names.
+const MODEL_TYPES: [(ModelType, &str); 2] =
+    [(ModelType::Empty, "Empty"), (ModelType::Cube, "Cube")];
+
+/// Plugin that sets up the block model editor.
+pub struct BlockModelEditorPlugin;
+impl Plugin for BlockModelEditorPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_editor_panel::<BlockModelEditor>(PaletteCommand::new(
+            "editor.block_models",
+            "Toggle Block Model Editor",
+        ))
+        .add_systems(
+            Update,
+            (
+                mark_dirty,
+                refresh_faces,
+                refresh_models,
+                refresh_preview,
+                spin_preview,
+            )
+                .chain()
+                .after(EditorPanelSystems)
+                .run_if(in_state(AwgenState::Editor)),
+        )
+        .add_observer(on_model_activate);
+    }
+}
+
+/// The state of the block model editor.
+#[derive(Debug)]
+pub struct BlockModelEditor {
+    /// The model being edited.
+    model: BlockModel,
+
+    /// The name the model is stored under, once it has been saved or loaded.
+    name: Option<String>,
+
+    /// The label showing the name of the model, while the panel is open.
+    label: Option<Entity>,
+
+    /// The label showing the type of the model, while the panel is open.
+    type_label: Option<Entity>,
+
+    /// The container of the face rows, while the panel is open.
+    faces: Option<Entity>,
+
+    /// The previewed block, while the panel is open.
+    preview: Option<Entity>,
+
+    /// Whether the face rows, labels, and preview need to be rebuilt.
+    model_dirty: bool,
+}
+
+impl Default for BlockModelEditor {
+    fn default() -> Self {
+        Self {
+            model: ModelType::Cube.create(),
+            name: None,
+            label: None,
+            type_label: None,
+            faces: None,
+            preview: None,
+            model_dirty: false,
+        }
+    }
+}
+
+impl PanelContent for BlockModelEditor {
+    type Params = (
+        Res<'static, AssetServer>,
+        AwgenAssets<'static, ProjectAssets>,
+    );
+
+    fn spawn(
+        &mut self,
+        (asset_server, assets): &mut SystemParamItem<Self::Params>,
+        commands: &mut Commands,
+    ) -> (Entity, Entity) {
+        let theme = hearth_theme(asset_server);
+        let panel = commands
+            .spawn((
+                ScreenAnchor::BottomLeft,
+                LayoutPanel(PanelKind::BlockModels),
+                Maximizable,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(8.0),
+                    min_width: px(280.0),
+                    max_height: percent(80.0),
+                    margin: UiRect::all(px(8.0)),
+                    ..default()
+                },
+                theme.outer_window.clone(),
+                children![(
+                    Text::new("Block Model Editor"),
+                    theme.outer_window.text.clone()
+                ),],
+            ))
+            .id();
+
+        let type_label = commands
+            .spawn((
+                ChildOf(panel),
+                Text::default(),
+                theme.outer_window.text.clone(),
+            ))
+            .id();
+
+        let types = commands
+            .spawn((
+                ChildOf(panel),
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: px(4.0),
+                    ..default()
+                },
+            ))
+            .id();
+
+        for (model_type, text) in MODEL_TYPES {
+            commands.spawn((
+                ChildOf(types),
+                ModelAction::SetType(model_type),
+                button(ButtonBuilder {
+                    node: Node::default(),
+                    content: ButtonContent::text(text),
+                    theme: theme.clone(),
+                }),
+            ));
+        }
+
+        let label = commands
+            .spawn((
+                ChildOf(panel),
+                Text::default(),
+                theme.outer_window.text.clone(),
+            ))
+            .id();
+
+        // The preview is drawn by the overlay camera, on top of the panel.
+        let preview = commands
+            .spawn((
+                RenderLayers::layer(1),
+                Transform::from_scale(Vec3::splat(PREVIEW_SCALE)),
+                Visibility::default(),
+            ))
+            .id();
+
+        commands.spawn((
+            ChildOf(panel),
+            Node {
+                width: px(PREVIEW_SIZE),
+                height: px(PREVIEW_SIZE),
+                align_self: AlignSelf::Center,
+                ..default()
+            },
+            Node3D(preview),
+        ));
+
+        let faces = commands
+            .spawn((
+                ChildOf(panel),
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(4.0),
+                    ..default()
+                },
+            ))
+            .id();
+
+        let buttons = commands
+            .spawn((
+                ChildOf(panel),
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    flex_wrap: FlexWrap::Wrap,
+                    column_gap: px(4.0),
+                    row_gap: px(4.0),
+                    ..default()
+                },
+            ))
+            .id();
+
+        let mut actions = vec![
+            (ModelAction::New, "New"),
+            (ModelAction::FillWithBrush, "Fill with Brush"),
+            (ModelAction::UseAsBrush, "Use as Brush"),
+        ];
+        if !assets.is_read_only() {
+            actions.push((ModelAction::Save, "Save"));
+        }
+
+        for (action, text) in actions {
+            commands.spawn((
+                ChildOf(buttons),
+                action,
+                button(ButtonBuilder {
+                    node: Node::default(),
+                    content: ButtonContent::text(text),
+                    theme: theme.clone(),
+                }),
+            ));
+        }
+
+        commands.spawn((
+            ChildOf(panel),
+            Text::new("Stored Models"),
+            theme.outer_window.text.clone(),
+        ));
+
+        let list = commands
+            .spawn((
+                ChildOf(panel),
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(4.0),
+                    overflow: Overflow::scroll_y(),
+                    scrollbar_width: 4.0,
+                    ..default()
+                },
+            ))
+            .id();
+
+        self.label = Some(label);
+        self.type_label = Some(type_label);
+        self.faces = Some(faces);
+        self.preview = Some(preview);
+        self.model_dirty = true;
+
+        (panel, list)
+    }
+
+    fn close(&mut self) {
+        self.label = None;
+        self.type_label = None;
+        self.faces = None;
+        self.preview = None;
+    }
+}
+
+/// A type of block model that can be authored in the editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModelType {
+    /// An empty block, which erases blocks when placed.
+    Empty,
+
+    /// A unit cube.
+    Cube,
+}
+
+impl ModelType {
+    /// Gets the type of the given block model.
+    fn of(model: &BlockModel) -> Self {
+        match model {
+            BlockModel::Empty => ModelType::Empty,
+            BlockModel::Cube(_) => ModelType::Cube,
+        }
+    }
+
+    /// Creates a new block model of this type.
+    fn create(self) -> BlockModel {
+        match self {
+            ModelType::Empty => BlockModel::Empty,
+            ModelType::Cube => BlockModel::Cube(Cube::default()),
+        }
+    }
+
+    /// Gets the display name of this type.
+    fn name(self) -> &'static str {
+        MODEL_TYPES
+            .iter()
+            .find(|(model_type, _)| *model_type == self)
+            .map_or("Unknown", |(_, name)| name)
+    }
+}
+
+/// An action performed when a button in the block model editor is clicked.
+#[derive(Debug, Component)]
+enum ModelAction {
+    /// Changes the model to a new model of the given type, unless it already
+    /// has that type.
+    SetType(ModelType),
+
+    /// Sets the tile of the given face to the tile of the face paint brush.
+    UseBrush(BlockFace),
+
+    /// Moves the given face to the previous tile of the tileset.
+    PreviousTile(BlockFace),
+
+    /// Moves the given face to the next tile of the tileset.
+    NextTile(BlockFace),
+
+    /// Rotates the tile of the given face clockwise.
+    Rotate(BlockFace),
+
+    /// Sets the tiles of all faces to the tile of the face paint brush.
+    FillWithBrush,
+
+    /// Starts a new, unsaved model.
+    New,
+
+    /// Stores the model in the project asset database.
+    Save,
+
+    /// Places the model with the block brush tool.
+    UseAsBrush,
+
+    /// Loads the stored model with the given name into the editor.
+    Load(String),
+
+    /// Deletes the stored model with the given name.
+    Delete(String),
+}
+
+/// Marks the panel for rebuilding when the tileset, the tile palette, or the
+/// stored assets have changed.
+fn mark_dirty(
+    mut reloaded: MessageReader<TilesetReloaded>,
+    mut restored: MessageReader<GameDatabaseRestored>,
+    mut changes: MessageReader<AssetRecordChanged<ProjectAssets>>,
+    palette: Res<TilePalette>,
+    mut state: ResMut<EditorPanel<BlockModelEditor>>,
+) {
+    if reloaded.read().count() > 0 || palette.is_changed() {
+        state.model_dirty = true;
+    }
+
+    if restored.read().count() + changes.read().count() > 0 {
+        state.mark_dirty();
+    }
+}
+
+/// Rebuilds the face rows and the labels when the model has changed.
+#[allow(clippy::too_many_arguments)]
+fn refresh_faces(
+    asset_server: Res<AssetServer>,
+    active_tilesets: Res<ActiveTilesets>,
+    materials: Res<Assets<TilesetMaterial>>,
+    palette: Res<TilePalette>,
+    state: Res<EditorPanel<BlockModelEditor>>,
+    mut images: ResMut<Assets<Image>>,
+    mut texts: Query<&mut Text>,
+    mut commands: Commands,
+) {
+    if !state.model_dirty {
+        return;
+    }
+
+    let (Some(faces), Some(label), Some(type_label)) = (state.faces, state.label, state.type_label)
+    else {
+        return;
+    };
+
+    if let Ok(mut text) = texts.get_mut(label) {
+        text.0 = match &state.name {
+            Some(name) => format!("Editing \"{}\"", name),
+            None => "Unsaved model".to_string(),
+        };
+    }
+
+    if let Ok(mut text) = texts.get_mut(type_label) {
+        text.0 = format!("Type: {}", ModelType::of(&state.model).name());
+    }
+
+    commands.entity(faces).despawn_related::<Children>();
+
+    // Only cubes have faces to edit.
+    let BlockModel::Cube(cube) = &state.model else {
+        return;
+    };
+
+    // The tiles are shown once the tileset has loaded.
+    let tiles = FACES.map(|(face, _)| {
+        let tile = cube.face(face)?;
+        let tileset = materials
+            .get(&active_tilesets.opaque)
+            .and_then(|material| images.get(&material.texture))?;
+        extract_tile(tileset, palette.layer(tile.tile_index))
+    });
+
+    let theme = hearth_theme(&asset_server);
+    for ((face, name), tile) in FACES.into_iter().zip(tiles) {
+        let row = commands
+            .spawn((
+                ChildOf(faces),
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: px(4.0),
+                    ..default()
+                },
+            ))
+            .id();
+
+        let content = match tile {
+            Some(tile) => ButtonContent::Both(images.add(tile), name.to_string()),
+            None => ButtonContent::text(name),
+        };
+
+        commands.spawn((
+            ChildOf(row),
+            ModelAction::UseBrush(face),
+            button(ButtonBuilder {
+                node: Node {
+                    flex_grow: 1.0,
+                    ..default()
+                },
+                content,
+                theme: theme.clone(),
+            }),
+        ));
+
+        for (action, text) in [
+            (ModelAction::PreviousTile(face), "<"),
+            (ModelAction::NextTile(face), ">"),
+            (ModelAction::Rotate(face), "R"),
+        ] {
+            commands.spawn((
+                ChildOf(row),
+                action,
+                button(ButtonBuilder {
+                    node: Node::default(),
+                    content: ButtonContent::text(text),
+                    theme: theme.clone(),
+                }),
+            ));
+        }
+    }
+}
+
+/// Rebuilds the stored model rows when the stored models have changed.
+fn refresh_models(
+    asset_server: Res<AssetServer>,
+    assets: AwgenAssets<ProjectAssets>,
+    mut state: ResMut<EditorPanel<BlockModelEditor>>,
+    mut commands: Commands,
+) {
+    let Some(list) = state.take_dirty() else {
+        return;
+    };
+
+    let models = match assets.list_block_models() {
+        Ok(models) => models,
+        Err(err) => {
+            error!("Failed to list block models: {}", err);
+            return;
+        }
+    };
+
+    commands.entity(list).despawn_related::<Children>();
+
+    let theme = hearth_theme(&asset_server);
+    for model in models {
+        let row = commands
+            .spawn((
+                ChildOf(list),
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: px(4.0),
+                    ..default()
+                },
+            ))
+            .id();
+
+        commands.spawn((
+            ChildOf(row),
+            ModelAction::Load(model.name.clone()),
+            button(ButtonBuilder {
+                node: Node {
+                    flex_grow: 1.0,
+                    ..default()
+                },
+                content: ButtonContent::text(model.name.clone()),
+                theme: theme.clone(),
+            }),
+        ));
+
+        if !assets.is_read_only() {
+            commands.spawn((
+                ChildOf(row),
+                ModelAction::Delete(model.name),
+                button(ButtonBuilder {
+                    node: Node::default(),
+                    content: ButtonContent::text("X"),
+                    theme: theme.clone(),
+                }),
+            ));
+        }
+    }
+}
+
+/// Rebuilds the mesh of the previewed block when the model has changed.
+fn refresh_preview(
+    active_tilesets: Res<ActiveTilesets>,
+    palette: Res<TilePalette>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut state: ResMut<EditorPanel<BlockModelEditor>>,
+    mut commands: Commands,
+) {
+    if !state.model_dirty {
+        return;
+    }
+    state.model_dirty = false;
+
+    let Some(preview) = state.preview else {
+        return;
+    };
+
+    // The cube spans from 0 to 1 on the Y axis, so it is shifted down to
+    // rotate around its center.
+    let mut mesh = TerrainMesh::new();
+    let transform = Transform::from_xyz(0.0, -0.5, 0.0);
+    state
+        .model
+        .draw(&mut mesh, transform, Occlusion::empty(), &palette);
+
+    commands.entity(preview).insert((
+        Mesh3d(meshes.add(Mesh::from(mesh))),
+        MeshMaterial3d(active_tilesets.opaque.clone()),
+    ));
+}
+
+/// Rotates the previewed block around its vertical axis.
+fn spin_preview(
+    time: Res<Time>,
+    state: Res<EditorPanel<BlockModelEditor>>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let Some(preview) = state.preview else {
+        return;
+    };
+
+    if let Ok(mut transform) = transforms.get_mut(preview) {
+        let spin = Quat::from_rotation_y(time.elapsed_secs() * PREVIEW_SPIN_SPEED);
+        transform.rotation = Quat::from_rotation_x(PREVIEW_TILT.to_radians()) * spin;
+    }
+}
+
+/// Performs the action of a block model editor button when it is clicked.
+#[allow(clippy::too_many_arguments)]
+fn on_model_activate(
+    trigger: On<Activate>,
+    actions: Query<&ModelAction>,
+    mut assets: AwgenAssets<ProjectAssets>,
+    active_tilesets: Res<ActiveTilesets>,
+    materials: Res<Assets<TilesetMaterial>>,
+    images: Res<Assets<Image>>,
+    palette: Res<TilePalette>,
+    paint_tool: Res<FacePaintTool>,
+    mut brush_tool: ResMut<BlockBrushTool>,
+    mut state: ResMut<EditorPanel<BlockModelEditor>>,
+) {
+    let Ok(action) = actions.get(trigger.event().entity) else {
+        return;
+    };
+
+    let tile_count = materials
+        .get(&active_tilesets.opaque)
+        .and_then(|material| images.get(&material.texture))
+        .map_or(0, |tileset| {
+            tileset.texture_descriptor.size.depth_or_array_layers
+        });
+
+    let state = &mut *state;
+    match action {
+        ModelAction::SetType(model_type) => {
+            if ModelType::of(&state.model) != *model_type {
+                state.model = model_type.create();
+            }
+        }
+        ModelAction::UseBrush(face) => {
+            if let Some(tile) = face_mut(&mut state.model, *face) {
+                *tile = paint_tool.brush;
+            }
+        }
+        ModelAction::PreviousTile(face) | ModelAction::NextTile(face) => {
+            let Some(tile) = face_mut(&mut state.model, *face) else {
+                return;
+            };
+
+            if tile_count == 0 {
+                warn!("Cannot change the tile of a face before a tileset is loaded");
+                return;
+            }
+
+            let step = match action {
+                ModelAction::NextTile(_) => 1,
+                _ => tile_count - 1,
+            };
+            let layer = (palette.layer(tile.tile_index) + step) % tile_count;
+            tile.tile_index = palette.id_of_layer(layer);
+        }
+        ModelAction::Rotate(face) => {
+            if let Some(tile) = face_mut(&mut state.model, *face) {
+                tile.rotation = ROTATE_CW * tile.rotation;
+            }
+        }
+        ModelAction::FillWithBrush => {
+            for (face, _) in FACES {
+                if let Some(tile) = face_mut(&mut state.model, face) {
+                    *tile = paint_tool.brush;
+                }
+            }
+        }
+        ModelAction::New => {
+            state.model = ModelType::of(&state.model).create();
+            state.name = None;
+        }
+        ModelAction::Save => {
+            let name = match &state.name {
+                Some(name) => name.clone(),
+                None => {
+                    let models = match assets.list_block_models() {
+                        Ok(models) => models,
+                        Err(err) => {
+                            error!("Failed to list block models: {}", err);
+                            return;
+                        }
+                    };
+
+                    (1 ..)
+                        .map(|index| format!("Model {}", index))
+                        .find(|name| models.iter().all(|model| &model.name != name))
+                        .unwrap()
+                }
+            };
+
+            let model = NamedBlockModel {
+                name: name.clone(),
+                model: state.model.clone(),
+            };
+            if let Err(err) = assets.set_block_model(&model) {
+                error!("Failed to save block model \"{}\": {}", name, err);
+                return;
+            }
+
+            info!("Saved block model \"{}\"", name);
+            state.name = Some(name);
+            state.mark_dirty();
+        }
+        ModelAction::UseAsBrush => {
+            brush_tool.model = Some(state.model.clone());
+        }
+        ModelAction::Load(name) => match assets.get_block_model(name) {
+            Ok(Some(stored)) => {
+                state.model = stored.model;
+                state.name = Some(name.clone());
+            }
+            Ok(None) => {
+                warn!("Block model \"{}\" no longer exists", name);
+                state.mark_dirty();
+            }
+            Err(err) => error!("Failed to read block model \"{}\": {}", name, err),
+        },
+        ModelAction::Delete(name) => {
+            if let Err(err) = assets.delete_block_model(name) {
+                error!("Failed to delete block model \"{}\": {}", name, err);
+            }
+
+            if state.name.as_ref() == Some(name) {
+                state.name = None;
+            }
+            state.mark_dirty();
+        }
+    }
+
+    state.model_dirty = true;
+}
+
+/// Gets the tile of the given face of a block model, if the model has that
+/// face.
+fn face_mut(model: &mut BlockModel, face: BlockFace) -> Option<&mut TileFace> {
+    match model {
+        BlockModel::Empty => None,
+        BlockModel::Cube(cube) => cube.face_mut(face),
+    }
+}
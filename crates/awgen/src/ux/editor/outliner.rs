@@ -0,0 +1,769 @@
+//! This module implements the scene outliner for the editor, which lists the
+//! lights and sprites placed in the world, grouped by type.
+//!
+//! The outliner is toggled with the "Toggle Scene Outliner" command, and can
+//! be moved into its own window with the "Detach Scene Outliner" command. The
+//! checkbox of each row shows or hides its entity, and clicking a row selects
+//! the entity, which is kept in sync with the selection of the light tool. The
+//! selected entity can be focused by the camera, locked against edits,
+//! duplicated, or deleted. Sprites are owned by scripts, so sprite edits are
+//! applied as sprite packets and reported to the script engine.
+
+use awgen_ui::menus::maximize::Maximizable;
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::menus::windows::{DetachPanel, Detached, DockPanel};
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::button::{ButtonBuilder, ButtonContent, button};
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use awgen_ui::widgets::tree_view::{
+    TreeChecksChanged,
+    TreeNode,
+    TreeNodeBuilder,
+    TreeNodeContent,
+    TreeView,
+};
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+
+use crate::app::AwgenState;
+use crate::database::GameDatabase;
+use crate::database::lights::LightKind;
+use crate::map::{Billboard, BillboardTable, LightTable, SceneLight, billboard};
+use crate::scripts::{PacketIn, PacketOut, ScriptEngine};
+use crate::ux::CameraController;
+use crate::ux::editor::layout::{LayoutPanel, PanelKind};
+use crate::ux::editor::lights::LightTool;
+use crate::ux::editor::panel::EditorPanel;
+
+/// The offset, in blocks, of a duplicated entity from its original.
+const DUPLICATE_OFFSET: Vec3 = Vec3::X;
+
+/// The gizmo color of the selected entity.
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+/// The radius of the gizmo drawn around a selected light.
+const LIGHT_GIZMO_RADIUS: f32 = 0.5;
+
+/// Plugin that sets up the scene outliner.
+pub struct SceneOutlinerPlugin;
+impl Plugin for SceneOutlinerPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<SceneOutliner>()
+            .add_systems(
+                Update,
+                (
+                    toggle_panel,
+                    sync_selection,
+                    refresh_panel,
+                    tag_rows,
+                    draw_selection,
+                )
+                    .chain()
+                    .run_if(in_state(AwgenState::Editor)),
+            )
+            .add_systems(OnExit(AwgenState::Editor), close_outliner)
+            .add_observer(on_row_click)
+            .add_observer(on_checks_changed)
+            .add_observer(on_outliner_activate)
+            .register_command(
+                PaletteCommand::new("editor.outliner", "Toggle Scene Outliner"),
+                request_toggle,
+            )
+            .register_command(
+                PaletteCommand::new("editor.outliner.detach", "Detach Scene Outliner"),
+                toggle_detached,
+            );
+    }
+}
+
+/// A marker component for entities that are locked in the scene outliner.
+/// Locked entities cannot be moved with gizmos, duplicated, or deleted.
+#[derive(Debug, Component)]
+pub struct EditorLocked;
+
+/// An entity listed in the scene outliner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutlinerEntry {
+    /// The placed light with the given name.
+    Light(String),
+
+    /// The sprite with the given script-defined ID.
+    Sprite(String),
+}
+
+impl OutlinerEntry {
+    /// Gets the entity of this entry, if it still exists.
+    fn entity(&self, lights: &LightTable, sprites: &BillboardTable) -> Option<Entity> {
+        match self {
+            OutlinerEntry::Light(name) => lights.get(name),
+            OutlinerEntry::Sprite(id) => sprites.get(id),
+        }
+    }
+}
+
+/// The state of the scene outliner.
+#[derive(Debug, Default, Resource)]
+pub struct SceneOutliner {
+    /// The outliner panel, while the outliner is open.
+    panel: Option<Entity>,
+
+    /// The container of the tree view, while the outliner is open.
+    list: Option<Entity>,
+
+    /// The container of the action buttons, while the outliner is open.
+    actions: Option<Entity>,
+
+    /// The current tree view, while the outliner is open.
+    tree: Option<Entity>,
+
+    /// The entries of each group of the current tree view, in display order.
+    groups: Vec<Vec<OutlinerEntry>>,
+
+    /// Whether the rows of the current tree view still need to be tagged with
+    /// their entries.
+    untagged: bool,
+
+    /// The selected entity.
+    selected: Option<OutlinerEntry>,
+
+    /// Whether the panel contents need to be rebuilt.
+    dirty: bool,
+
+    /// Whether the outliner should be toggled on the next update.
+    toggle: bool,
+}
+
+impl SceneOutliner {
+    /// Returns true if the outliner is open.
+    pub fn is_open(&self) -> bool {
+        self.panel.is_some()
+    }
+
+    /// Requests the outliner to be opened or closed on the next update.
+    pub fn request_toggle(&mut self) {
+        self.toggle = true;
+    }
+
+    /// Gets the selected entity, if any.
+    pub fn selected(&self) -> Option<&OutlinerEntry> {
+        self.selected.as_ref()
+    }
+}
+
+/// A component on the tree node of an outliner entry.
+#[derive(Debug, Component)]
+struct OutlinerRow(OutlinerEntry);
+
+/// An action performed on the selected entity when a button in the outliner
+/// is clicked.
+#[derive(Debug, Component)]
+enum OutlinerAction {
+    /// Moves the camera to the entity.
+    Focus,
+
+    /// Locks or unlocks the entity.
+    ToggleLock,
+
+    /// Duplicates the entity.
+    Duplicate,
+
+    /// Deletes the entity.
+    Delete,
+}
+
+/// Toggles the outliner on the next update, when run from the command palette.
+fn request_toggle(mut outliner: ResMut<SceneOutliner>) {
+    outliner.toggle = true;
+}
+
+/// Moves the outliner panel into its own window, or docks it back into the
+/// main window if it is already detached.
+fn toggle_detached(
+    outliner: Res<SceneOutliner>,
+    detached: Query<(), With<Detached>>,
+    mut commands: Commands,
+) {
+    let Some(panel) = outliner.panel else {
+        return;
+    };
+
+    if detached.contains(panel) {
+        commands.trigger(DockPanel { entity: panel });
+    } else {
+        commands.trigger(DetachPanel {
+            entity: panel,
+            title: "Scene Outliner".to_string(),
+        });
+    }
+}
+
+/// Opens or closes the outliner panel.
+fn toggle_panel(
+    asset_server: Res<AssetServer>,
+    mut outliner: ResMut<SceneOutliner>,
+    mut commands: Commands,
+) {
+    if !outliner.toggle {
+        return;
+    }
+    outliner.toggle = false;
+
+    if outliner.is_open() {
+        close_panel(&mut outliner, &mut commands);
+        return;
+    }
+
+    let theme = hearth_theme(&asset_server);
+    let panel = commands
+        .spawn((
+            ScreenAnchor::BottomCenter,
+            LayoutPanel(PanelKind::Outliner),
+            Maximizable,
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: px(8.0),
+                min_width: px(240.0),
+                max_height: percent(60.0),
+                margin: UiRect::all(px(8.0)),
+                ..default()
+            },
+            theme.outer_window.clone(),
+            children![(Text::new("Scene Outliner"), theme.outer_window.text.clone())],
+        ))
+        .id();
+
+    let list = commands
+        .spawn((
+            ChildOf(panel),
+            Node {
+                flex_direction: FlexDirection::Column,
+                overflow: Overflow::scroll_y(),
+                ..default()
+            },
+        ))
+        .id();
+
+    let actions = commands
+        .spawn((
+            ChildOf(panel),
+            Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: px(4.0),
+                ..default()
+            },
+        ))
+        .id();
+
+    outliner.panel = Some(panel);
+    outliner.list = Some(list);
+    outliner.actions = Some(actions);
+    outliner.dirty = true;
+}
+
+/// Selects the light selected in the light tool, so that picking a light gizmo
+/// selects its row.
+fn sync_selection(light_tool: Res<EditorPanel<LightTool>>, mut outliner: ResMut<SceneOutliner>) {
+    if !light_tool.is_changed() {
+        return;
+    }
+
+    let selected = match (light_tool.selected(), &outliner.selected) {
+        (Some(name), _) => Some(OutlinerEntry::Light(name.to_string())),
+        (None, Some(OutlinerEntry::Light(_))) => None,
+        (None, selected) => selected.clone(),
+    };
+
+    if outliner.selected != selected {
+        outliner.selected = selected;
+        outliner.dirty = true;
+    }
+}
+
+/// Rebuilds the tree view and the action buttons when the listed entities or
+/// the selection have changed.
+#[allow(clippy::too_many_arguments)]
+fn refresh_panel(
+    asset_server: Res<AssetServer>,
+    database: Res<GameDatabase>,
+    light_table: Res<LightTable>,
+    sprite_table: Res<BillboardTable>,
+    lights: Query<(&SceneLight, Has<EditorLocked>)>,
+    sprites: Query<(&Billboard, &Visibility, Has<EditorLocked>)>,
+    mut outliner: ResMut<SceneOutliner>,
+    mut commands: Commands,
+) {
+    if !outliner.dirty && !light_table.is_changed() && !sprite_table.is_changed() {
+        return;
+    }
+    outliner.dirty = false;
+
+    let (Some(list), Some(actions)) = (outliner.list, outliner.actions) else {
+        return;
+    };
+
+    commands.entity(list).despawn_related::<Children>();
+    commands.entity(actions).despawn_related::<Children>();
+
+    let mut placed = light_table
+        .iter()
+        .filter_map(|entity| lights.get(entity).ok())
+        .collect::<Vec<_>>();
+    placed.sort_by(|a, b| a.0.0.name.cmp(&b.0.0.name));
+
+    let mut spawned = sprites.iter().collect::<Vec<_>>();
+    spawned.sort_by(|a, b| a.0.id().cmp(b.0.id()));
+
+    let marker = |entry: &OutlinerEntry| {
+        if outliner.selected.as_ref() == Some(entry) {
+            "> "
+        } else {
+            ""
+        }
+    };
+
+    let lock = |locked: bool| if locked { " (locked)" } else { "" };
+
+    let mut light_rows = Vec::new();
+    let mut light_entries = Vec::new();
+    for (light, locked) in placed {
+        let entry = OutlinerEntry::Light(light.0.name.clone());
+        let kind = match light.0.kind {
+            LightKind::Point => "point",
+            LightKind::Spot => "spot",
+        };
+
+        light_rows.push(TreeNodeBuilder {
+            content: TreeNodeContent {
+                text: format!(
+                    "{}{} [{}]{}",
+                    marker(&entry),
+                    light.0.name,
+                    kind,
+                    lock(locked)
+                ),
+                icon: None,
+                checked: light.0.enabled,
+                overflow: None,
+                badge: None,
+                copy_text: Some(light.0.name.clone()),
+                collapsed: false,
+            },
+            children: Vec::new(),
+        });
+        light_entries.push(entry);
+    }
+
+    let mut sprite_rows = Vec::new();
+    let mut sprite_entries = Vec::new();
+    for (sprite, visibility, locked) in spawned {
+        let entry = OutlinerEntry::Sprite(sprite.id().to_string());
+        sprite_rows.push(TreeNodeBuilder {
+            content: TreeNodeContent {
+                text: format!("{}{}{}", marker(&entry), sprite.id(), lock(locked)),
+                icon: None,
+                checked: *visibility != Visibility::Hidden,
+                overflow: None,
+                badge: None,
+                copy_text: Some(sprite.id().to_string()),
+                collapsed: false,
+            },
+            children: Vec::new(),
+        });
+        sprite_entries.push(entry);
+    }
+
+    let root = TreeNodeBuilder {
+        content: TreeNodeContent::default(),
+        children: vec![
+            TreeNodeBuilder {
+                content: format!("Lights ({})", light_rows.len()).into(),
+                children: light_rows,
+            },
+            TreeNodeBuilder {
+                content: format!("Sprites ({})", sprite_rows.len()).into(),
+                children: sprite_rows,
+            },
+        ],
+    };
+
+    let theme = hearth_theme(&asset_server);
+    let tree = commands
+        .spawn((
+            ChildOf(list),
+            TreeView::from_builder(theme.clone(), root).with_checkboxes(),
+        ))
+        .id();
+
+    outliner.tree = Some(tree);
+    outliner.groups = vec![light_entries, sprite_entries];
+    outliner.untagged = true;
+
+    let Some(selected) = outliner.selected.clone() else {
+        return;
+    };
+
+    let locked = match &selected {
+        OutlinerEntry::Light(name) => light_table
+            .get(name)
+            .and_then(|entity| lights.get(entity).ok())
+            .map(|(_, locked)| locked),
+        OutlinerEntry::Sprite(id) => sprite_table
+            .get(id)
+            .and_then(|entity| sprites.get(entity).ok())
+            .map(|(_, _, locked)| locked),
+    };
+
+    let Some(locked) = locked else {
+        outliner.selected = None;
+        return;
+    };
+
+    let lock_label = if locked { "Unlock" } else { "Lock" };
+    let mut buttons = vec![
+        (OutlinerAction::Focus, "Focus"),
+        (OutlinerAction::ToggleLock, lock_label),
+    ];
+
+    let editable = match selected {
+        OutlinerEntry::Light(_) => !database.is_read_only(),
+        OutlinerEntry::Sprite(_) => true,
+    };
+
+    if editable && !locked {
+        buttons.push((OutlinerAction::Duplicate, "Duplicate"));
+        buttons.push((OutlinerAction::Delete, "Delete"));
+    }
+
+    for (action, label) in buttons {
+        commands.spawn((
+            ChildOf(actions),
+            action,
+            button(ButtonBuilder {
+                node: Node::default(),
+                content: ButtonContent::text(label),
+                theme: theme.clone(),
+            }),
+        ));
+    }
+}
+
+/// Tags the rows of a newly built tree view with their entries, once the tree
+/// view has spawned its nodes.
+fn tag_rows(
+    trees: Query<&TreeView>,
+    children: Query<&Children>,
+    nodes: Query<(), With<TreeNode>>,
+    mut outliner: ResMut<SceneOutliner>,
+    mut commands: Commands,
+) {
+    if !outliner.untagged {
+        return;
+    }
+
+    let Some(root) = outliner
+        .tree
+        .and_then(|tree| trees.get(tree).ok())
+        .and_then(TreeView::root_node)
+    else {
+        return;
+    };
+
+    // The first child of each tree node is its label row, so only the
+    // children that are tree nodes themselves are rows of the tree.
+    let child_nodes = |entity: Entity| {
+        children
+            .get(entity)
+            .map(|children| {
+                children
+                    .iter()
+                    .filter(|child| nodes.contains(*child))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+
+    let groups = child_nodes(root);
+    if groups.len() != outliner.groups.len() {
+        return;
+    }
+
+    let mut rows = Vec::new();
+    for (group, entries) in groups.into_iter().zip(outliner.groups.iter()) {
+        let nodes = child_nodes(group);
+        if nodes.len() != entries.len() {
+            return;
+        }
+        rows.extend(nodes.into_iter().zip(entries.iter().cloned()));
+    }
+
+    for (node, entry) in rows {
+        commands.entity(node).insert(OutlinerRow(entry));
+    }
+    outliner.untagged = false;
+}
+
+/// Selects the entity of an outliner row when the row is clicked.
+fn on_row_click(
+    mut trigger: On<Pointer<Click>>,
+    rows: Query<&OutlinerRow>,
+    mut outliner: ResMut<SceneOutliner>,
+    mut light_tool: ResMut<EditorPanel<LightTool>>,
+) {
+    let Ok(OutlinerRow(entry)) = rows.get(trigger.event().entity) else {
+        return;
+    };
+    trigger.propagate(false);
+
+    match entry {
+        OutlinerEntry::Light(name) => light_tool.select(Some(name.clone())),
+        OutlinerEntry::Sprite(_) => light_tool.select(None),
+    }
+
+    if outliner.selected.as_ref() != Some(entry) {
+        outliner.selected = Some(entry.clone());
+        outliner.dirty = true;
+    }
+}
+
+/// Shows or hides the entities of outliner rows when their checkboxes are
+/// toggled. Lights are switched on or off, which is stored in the database.
+fn on_checks_changed(
+    trigger: On<TreeChecksChanged>,
+    rows: Query<&OutlinerRow>,
+    outliner: Res<SceneOutliner>,
+    database: Res<GameDatabase>,
+    light_table: Res<LightTable>,
+    sprite_table: Res<BillboardTable>,
+    mut lights: Query<&mut SceneLight>,
+    mut sprites: Query<&mut Visibility, With<Billboard>>,
+) {
+    if outliner.tree != Some(trigger.event().entity) {
+        return;
+    }
+
+    for change in trigger.event().changes.iter() {
+        let Ok(OutlinerRow(entry)) = rows.get(change.node) else {
+            continue;
+        };
+
+        let Some(entity) = entry.entity(&light_table, &sprite_table) else {
+            continue;
+        };
+
+        let visible = change.state.is_checked();
+        if let Ok(mut light) = lights.get_mut(entity) {
+            light.0.enabled = visible;
+            if database.is_read_only() {
+                continue;
+            }
+
+            if let Err(err) = database.set_light(&light.0) {
+                error!("Failed to store light \"{}\": {}", light.0.name, err);
+            }
+        } else if let Ok(mut visibility) = sprites.get_mut(entity) {
+            *visibility = if visible {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
+
+/// Performs the action of an outliner button on the selected entity when it
+/// is clicked.
+#[allow(clippy::too_many_arguments)]
+fn on_outliner_activate(
+    trigger: On<Activate>,
+    actions: Query<&OutlinerAction>,
+    database: Res<GameDatabase>,
+    light_table: Res<LightTable>,
+    sprite_table: Res<BillboardTable>,
+    lights: Query<&SceneLight>,
+    sprites: Query<(&Billboard, &Transform)>,
+    transforms: Query<(&GlobalTransform, Has<EditorLocked>)>,
+    mut cameras: Query<&mut CameraController>,
+    engine: Option<Res<ScriptEngine>>,
+    mut outliner: ResMut<SceneOutliner>,
+    mut light_tool: ResMut<EditorPanel<LightTool>>,
+    mut commands: Commands,
+) {
+    let Ok(action) = actions.get(trigger.event().entity) else {
+        return;
+    };
+
+    let Some(selected) = outliner.selected.clone() else {
+        return;
+    };
+
+    let Some(entity) = selected.entity(&light_table, &sprite_table) else {
+        return;
+    };
+
+    let Ok((transform, locked)) = transforms.get(entity) else {
+        return;
+    };
+
+    match action {
+        OutlinerAction::Focus => {
+            for mut camera in cameras.iter_mut() {
+                camera.target_pos = transform.translation();
+            }
+            return;
+        }
+        OutlinerAction::ToggleLock => {
+            if locked {
+                commands.entity(entity).remove::<EditorLocked>();
+            } else {
+                commands.entity(entity).insert(EditorLocked);
+            }
+        }
+        _ if locked => return,
+        OutlinerAction::Duplicate => match &selected {
+            OutlinerEntry::Light(name) => {
+                let Ok(light) = lights.get(entity) else {
+                    return;
+                };
+
+                let mut copy = light.0.clone();
+                copy.name = (2 ..)
+                    .map(|number| format!("{} {}", name, number))
+                    .find(|name| light_table.get(name).is_none())
+                    .unwrap();
+                copy.position = (Vec3::from(copy.position) + DUPLICATE_OFFSET).to_array();
+
+                if let Err(err) = database.set_light(&copy) {
+                    error!("Failed to store light \"{}\": {}", copy.name, err);
+                    return;
+                }
+
+                light_tool.select(Some(copy.name.clone()));
+                outliner.selected = Some(OutlinerEntry::Light(copy.name.clone()));
+                commands.spawn(SceneLight(copy));
+            }
+            OutlinerEntry::Sprite(id) => {
+                let Ok((sprite, transform)) = sprites.get(entity) else {
+                    return;
+                };
+
+                let copy = (2 ..)
+                    .map(|number| format!("{} {}", id, number))
+                    .find(|id| sprite_table.get(id).is_none())
+                    .unwrap();
+
+                let packet = PacketIn::SpawnSprite {
+                    id: copy.clone(),
+                    texture: sprite.texture().to_string(),
+                    position: (transform.translation + DUPLICATE_OFFSET).to_array(),
+                    size: [transform.scale.x, transform.scale.y],
+                    mode: Some(sprite.mode),
+                    clickable: Some(sprite.clickable),
+                    hoverable: Some(sprite.hoverable),
+                };
+                commands.queue(move |world: &mut World| billboard::handle(world, packet));
+
+                notify_scripts(
+                    engine.as_deref(),
+                    PacketOut::SpriteDuplicated {
+                        source: id.clone(),
+                        id: copy.clone(),
+                    },
+                );
+                outliner.selected = Some(OutlinerEntry::Sprite(copy));
+            }
+        },
+        OutlinerAction::Delete => match &selected {
+            OutlinerEntry::Light(name) => {
+                if let Err(err) = database.delete_light(name) {
+                    error!("Failed to delete light \"{}\": {}", name, err);
+                    return;
+                }
+
+                commands.entity(entity).despawn();
+                light_tool.select(None);
+                outliner.selected = None;
+            }
+            OutlinerEntry::Sprite(id) => {
+                let packet = PacketIn::DespawnSprite { id: id.clone() };
+                commands.queue(move |world: &mut World| billboard::handle(world, packet));
+
+                notify_scripts(
+                    engine.as_deref(),
+                    PacketOut::SpriteDeleted { id: id.clone() },
+                );
+                outliner.selected = None;
+            }
+        },
+    }
+
+    outliner.dirty = true;
+}
+
+/// Sends a packet to the script engine, if it is running.
+fn notify_scripts(engine: Option<&ScriptEngine>, packet: PacketOut) {
+    let Some(engine) = engine else {
+        return;
+    };
+
+    if let Err(err) = engine.send(packet) {
+        error!("Failed to send sprite edit to the script engine: {}", err);
+    }
+}
+
+/// Draws a gizmo around the selected entity while the outliner is open.
+fn draw_selection(
+    outliner: Res<SceneOutliner>,
+    light_table: Res<LightTable>,
+    sprite_table: Res<BillboardTable>,
+    transforms: Query<&GlobalTransform>,
+    mut gizmos: Gizmos,
+) {
+    if !outliner.is_open() {
+        return;
+    }
+
+    let Some(selected) = outliner.selected() else {
+        return;
+    };
+
+    let Some(transform) = selected
+        .entity(&light_table, &sprite_table)
+        .and_then(|entity| transforms.get(entity).ok())
+    else {
+        return;
+    };
+
+    match selected {
+        OutlinerEntry::Light(_) => {
+            gizmos.sphere(transform.translation(), LIGHT_GIZMO_RADIUS, SELECTED_COLOR);
+        }
+        OutlinerEntry::Sprite(_) => {
+            // Sprites are anchored at the bottom center of their quad.
+            let (scale, _, translation) = transform.to_scale_rotation_translation();
+            let center = translation + Vec3::Y * scale.y * 0.5;
+            let size = Vec3::new(scale.x, scale.y, scale.x);
+            gizmos.cuboid(
+                Transform::from_translation(center).with_scale(size),
+                SELECTED_COLOR,
+            );
+        }
+    }
+}
+
+/// Closes the outliner when leaving the editor.
+fn close_outliner(mut outliner: ResMut<SceneOutliner>, mut commands: Commands) {
+    close_panel(&mut outliner, &mut commands);
+}
+
+/// Despawns the outliner panel and clears the outliner state.
+fn close_panel(outliner: &mut SceneOutliner, commands: &mut Commands) {
+    if let Some(panel) = outliner.panel.take() {
+        commands.entity(panel).despawn();
+    }
+    outliner.list = None;
+    outliner.actions = None;
+    outliner.tree = None;
+    outliner.groups.clear();
+    outliner.untagged = false;
+    outliner.dirty = false;
+}
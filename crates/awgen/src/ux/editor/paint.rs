@@ -0,0 +1,390 @@
+//! This module implements the face paint tool for the editor, which changes
+//! the tile of a single face of an existing cube block.
+//!
+//! The tool is toggled with `B`. While it is active, a tile palette is shown
+//! for picking the tile to paint, `R` rotates the tile clockwise, and clicking
+//! or dragging over block faces paints them.
+
+use awgen_ui::hotkeys::{BindHotkeyExt, Hotkey, HotkeyContext};
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::button::{ButtonBuilder, ButtonContent, button};
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use bevy::picking::hover::HoverMap;
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+use bevy::window::PrimaryWindow;
+
+use crate::app::AwgenState;
+use crate::map::{
+    BlockFace,
+    BlockHit,
+    BlockModel,
+    ChunkTable,
+    MapSettings,
+    TileFace,
+    VoxelChunk,
+    WorldPos,
+    raycast_blocks,
+};
+use crate::tiles::{ActiveTilesets, TilePalette, TilesetMaterial, TilesetReloaded, extract_tile};
+use crate::ux::CameraController;
+use crate::ux::editor::picking::{CursorDrag, pointer_over};
+
+/// The maximum distance, in blocks, at which faces can be painted.
+const PAINT_DISTANCE: f32 = 256.0;
+
+/// A clockwise quarter turn of a tile.
+pub(super) const ROTATE_CW: Mat2 = Mat2::from_cols_array(&[0.0, -1.0, 1.0, 0.0]);
+
+/// Plugin that sets up the face paint tool.
+pub struct FacePaintPlugin;
+impl Plugin for FacePaintPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<FacePaintTool>()
+            .add_systems(
+                Update,
+                (toggle_tool, update_palette, paint_face)
+                    .chain()
+                    .run_if(in_state(AwgenState::Editor)),
+            )
+            .add_systems(OnExit(AwgenState::Editor), close_tool)
+            .add_observer(on_palette_activate)
+            .register_command(
+                PaletteCommand::new("editor.paint", "Toggle Face Paint Tool"),
+                request_toggle,
+            )
+            .register_command(
+                PaletteCommand::new("editor.paint.rotate", "Rotate Paint Brush"),
+                rotate_brush,
+            )
+            .bind_hotkey(
+                "editor.paint",
+                Hotkey::new(KeyCode::KeyB),
+                HotkeyContext::Editor,
+            )
+            .bind_hotkey(
+                "editor.paint.rotate",
+                Hotkey::new(KeyCode::KeyR),
+                HotkeyContext::Editor,
+            );
+    }
+}
+
+/// The state of the face paint tool.
+#[derive(Debug, Default, Resource)]
+pub struct FacePaintTool {
+    /// Whether the tool is active.
+    active: bool,
+
+    /// The tile that is painted onto block faces.
+    pub brush: TileFace,
+
+    /// The palette panel, while the tool is active.
+    palette: Option<Entity>,
+
+    /// The tileset image the palette was built from.
+    palette_source: Option<AssetId<Image>>,
+
+    /// The faces painted by the current drag, while the mouse button is held.
+    stroke: Option<HashSet<(WorldPos, BlockFace)>>,
+
+    /// The cursor path of the current drag.
+    cursor: CursorDrag,
+
+    /// Whether the tool should be toggled on the next update.
+    toggle: bool,
+}
+
+impl FacePaintTool {
+    /// Returns true if the tool is active.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Requests the tool to be toggled on the next update.
+    pub fn request_toggle(&mut self) {
+        self.toggle = true;
+    }
+}
+
+/// A marker component for the tile palette panel.
+#[derive(Debug, Component)]
+struct PalettePanel;
+
+/// A component for a tile button in the palette, containing the tile index.
+#[derive(Debug, Component)]
+struct PaletteTile(u32);
+
+/// A marker component for the label showing the current brush.
+#[derive(Debug, Component)]
+struct BrushLabel;
+
+/// Toggles the face paint tool on the next update, when run from the command
+/// palette or with its hotkey.
+fn request_toggle(mut tool: ResMut<FacePaintTool>) {
+    tool.toggle = true;
+}
+
+/// Toggles the face paint tool.
+fn toggle_tool(mut tool: ResMut<FacePaintTool>, mut commands: Commands) {
+    if !tool.toggle {
+        return;
+    }
+    tool.toggle = false;
+
+    tool.active = !tool.active;
+    if !tool.active {
+        close_palette(&mut tool, &mut commands);
+    }
+}
+
+/// Rotates the brush tile clockwise.
+fn rotate_brush(mut tool: ResMut<FacePaintTool>) {
+    if !tool.active {
+        return;
+    }
+
+    tool.brush.rotation = ROTATE_CW * tool.brush.rotation;
+}
+
+/// Builds the tile palette for the active tileset, and keeps the brush label
+/// up to date.
+fn update_palette(
+    mut reloaded: MessageReader<TilesetReloaded>,
+    asset_server: Res<AssetServer>,
+    active_tilesets: Res<ActiveTilesets>,
+    materials: Res<Assets<TilesetMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    mut tool: ResMut<FacePaintTool>,
+    mut labels: Query<&mut Text, With<BrushLabel>>,
+    mut commands: Commands,
+) {
+    // A reloaded tileset keeps its image ID, so the palette is rebuilt from
+    // scratch.
+    if reloaded.read().count() > 0 {
+        tool.palette_source = None;
+    }
+
+    if !tool.active {
+        return;
+    }
+
+    if tool.is_changed() {
+        for mut label in labels.iter_mut() {
+            label.0 = brush_label(&tool.brush);
+        }
+    }
+
+    let Some(source) = materials
+        .get(&active_tilesets.opaque)
+        .map(|material| material.texture.id())
+    else {
+        return;
+    };
+
+    if tool.palette.is_some() && tool.palette_source == Some(source) {
+        return;
+    }
+
+    let Some(tileset) = images.get(source) else {
+        // The tileset is still loading.
+        return;
+    };
+
+    let tiles = (0 .. tileset.texture_descriptor.size.depth_or_array_layers)
+        .map_while(|index| extract_tile(tileset, index))
+        .collect::<Vec<_>>();
+
+    close_palette(&mut tool, &mut commands);
+
+    let theme = hearth_theme(&asset_server);
+    let palette = commands
+        .spawn((
+            PalettePanel,
+            ScreenAnchor::CenterRight,
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: px(8.0),
+                max_width: px(280.0),
+                max_height: percent(80.0),
+                margin: UiRect::all(px(8.0)),
+                ..default()
+            },
+            theme.outer_window.clone(),
+            children![(
+                BrushLabel,
+                Text::new(brush_label(&tool.brush)),
+                theme.outer_window.text.clone(),
+            )],
+        ))
+        .id();
+
+    let grid = commands
+        .spawn((
+            ChildOf(palette),
+            Node {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                row_gap: px(4.0),
+                column_gap: px(4.0),
+                overflow: Overflow::scroll_y(),
+                scrollbar_width: 4.0,
+                ..default()
+            },
+        ))
+        .id();
+
+    for (index, tile) in tiles.into_iter().enumerate() {
+        commands.spawn((
+            ChildOf(grid),
+            PaletteTile(index as u32),
+            button(ButtonBuilder {
+                node: Node::default(),
+                content: ButtonContent::Icon(images.add(tile)),
+                theme: theme.clone(),
+            }),
+        ));
+    }
+
+    tool.palette = Some(palette);
+    tool.palette_source = Some(source);
+}
+
+/// Selects the brush tile when a palette tile is clicked.
+fn on_palette_activate(
+    trigger: On<Activate>,
+    tiles: Query<&PaletteTile>,
+    tile_palette: Res<TilePalette>,
+    mut tool: ResMut<FacePaintTool>,
+) {
+    if let Ok(tile) = tiles.get(trigger.event().entity) {
+        tool.brush.tile_index = tile_palette.id_of_layer(tile.0);
+    }
+}
+
+/// Paints the block faces under the cursor while the left mouse button is
+/// dragged.
+#[allow(clippy::too_many_arguments)]
+fn paint_face(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut tool: ResMut<FacePaintTool>,
+    hover_map: Res<HoverMap>,
+    parents: Query<&ChildOf>,
+    palettes: Query<(), With<PalettePanel>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<CameraController>>,
+    chunk_table: Res<ChunkTable>,
+    map_settings: Res<MapSettings>,
+    mut chunks: Query<&mut VoxelChunk>,
+) {
+    if !tool.active {
+        return;
+    }
+
+    if !mouse_buttons.pressed(MouseButton::Left) {
+        if tool.stroke.is_some() {
+            tool.stroke = None;
+            tool.cursor.end();
+        }
+        return;
+    }
+
+    if tool.stroke.is_none() {
+        // Clicks on the palette should not paint the block behind it.
+        if !mouse_buttons.just_pressed(MouseButton::Left)
+            || pointer_over(&hover_map, &parents, &palettes)
+        {
+            return;
+        }
+
+        tool.stroke = Some(HashSet::new());
+    }
+
+    let tool = &mut *tool;
+    let Some(stroke) = &mut tool.stroke else {
+        return;
+    };
+
+    for ray in tool.cursor.rays(&windows, &cameras) {
+        let is_solid = |pos: WorldPos| {
+            chunk_table
+                .get_chunk(pos.as_chunk_pos())
+                .and_then(|chunk_id| chunks.get(chunk_id).ok())
+                .is_some_and(|chunk| !matches!(chunk.get_models().get(pos), BlockModel::Empty))
+        };
+
+        let Some(hit) = raycast_blocks(ray, PAINT_DISTANCE, is_solid) else {
+            continue;
+        };
+
+        if !map_settings.contains(hit.pos) {
+            debug!(
+                "Cannot paint block at {}: outside of the world height bounds",
+                hit.pos
+            );
+            continue;
+        }
+
+        // Each face is only painted once per stroke.
+        if stroke.insert((hit.pos, hit.face)) {
+            paint(&chunk_table, &mut chunks, hit, tool.brush);
+        }
+    }
+}
+
+/// Paints the hit block face with the given tile.
+fn paint(
+    chunk_table: &ChunkTable,
+    chunks: &mut Query<&mut VoxelChunk>,
+    hit: BlockHit,
+    brush: TileFace,
+) {
+    let Some(chunk_id) = chunk_table.get_chunk(hit.pos.as_chunk_pos()) else {
+        return;
+    };
+
+    let Ok(mut chunk) = chunks.get_mut(chunk_id) else {
+        return;
+    };
+
+    let BlockModel::Cube(cube) = chunk.get_models().get(hit.pos) else {
+        debug!("Cannot paint block at {}: not a cube", hit.pos);
+        return;
+    };
+
+    let mut cube = cube.clone();
+    let Some(face) = cube.face_mut(hit.face) else {
+        debug!("Cannot paint {:?} face of block at {}", hit.face, hit.pos);
+        return;
+    };
+
+    *face = brush;
+    chunk.set_model(hit.pos, BlockModel::Cube(cube));
+}
+
+/// Closes the face paint tool when leaving the editor.
+fn close_tool(mut tool: ResMut<FacePaintTool>, mut commands: Commands) {
+    tool.active = false;
+    close_palette(&mut tool, &mut commands);
+}
+
+/// Despawns the tile palette, if it is open.
+fn close_palette(tool: &mut FacePaintTool, commands: &mut Commands) {
+    if let Some(palette) = tool.palette.take() {
+        commands.entity(palette).despawn();
+    }
+    tool.palette_source = None;
+}
+
+/// Formats the label describing the current brush.
+fn brush_label(brush: &TileFace) -> String {
+    let x_axis = brush.rotation.x_axis;
+    let degrees = -x_axis.y.atan2(x_axis.x).to_degrees().round() as i32;
+    format!(
+        "Tile {} ({}° CW)",
+        brush.tile_index,
+        degrees.rem_euclid(360)
+    )
+}
@@ -0,0 +1,152 @@
+//! This module implements the state shared by the editor panels that are
+//! toggled with a command, such as the bookmark panel or the light tool.
+//!
+//! Each panel is described by a [`PanelContent`] type, which spawns the panel
+//! and holds the state of the tool it belongs to. [`AddEditorPanelExt`] adds
+//! the [`EditorPanel`] resource that wraps it, along with the systems that
+//! open and close the panel on request and close it when leaving the editor.
+//! The systems of each panel that rebuild its contents run after
+//! [`EditorPanelSystems`].
+
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use bevy::ecs::system::{StaticSystemParam, SystemParam, SystemParamItem};
+use bevy::prelude::*;
+
+use crate::app::AwgenState;
+
+/// The contents of an editor panel, along with the state of the tool it
+/// belongs to, which is kept while the panel is closed.
+pub trait PanelContent: Default + Send + Sync + 'static {
+    /// The system parameters used to spawn the panel.
+    type Params: SystemParam + 'static;
+
+    /// Spawns the panel, returning the panel and the container whose contents
+    /// are rebuilt whenever the panel is marked dirty.
+    fn spawn(
+        &mut self,
+        params: &mut SystemParamItem<Self::Params>,
+        commands: &mut Commands,
+    ) -> (Entity, Entity);
+
+    /// Clears the state that only applies while the panel is open, when the
+    /// panel is closed.
+    fn close(&mut self) {}
+}
+
+/// The state of an editor panel with the contents `T`, which dereferences to
+/// the contents.
+#[derive(Debug, Default, Resource, Deref, DerefMut)]
+pub struct EditorPanel<T: PanelContent> {
+    /// The panel, while it is open.
+    panel: Option<Entity>,
+
+    /// The container of the panel contents, while the panel is open.
+    list: Option<Entity>,
+
+    /// Whether the panel contents need to be rebuilt.
+    dirty: bool,
+
+    /// Whether the panel should be toggled on the next update.
+    toggle: bool,
+
+    /// The panel contents.
+    #[deref]
+    content: T,
+}
+
+impl<T: PanelContent> EditorPanel<T> {
+    /// Returns true if the panel is open.
+    pub fn is_open(&self) -> bool {
+        self.panel.is_some()
+    }
+
+    /// Gets the panel, while it is open.
+    pub fn panel(&self) -> Option<Entity> {
+        self.panel
+    }
+
+    /// Requests the panel to be opened or closed on the next update.
+    pub fn request_toggle(&mut self) {
+        self.toggle = true;
+    }
+
+    /// Marks the panel contents for rebuilding.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Clears the dirty flag, returning the container of the panel contents if
+    /// the panel is open and its contents need to be rebuilt.
+    pub fn take_dirty(&mut self) -> Option<Entity> {
+        let dirty = std::mem::take(&mut self.dirty);
+        self.list.filter(|_| dirty)
+    }
+
+    /// Despawns the panel, if it is open.
+    fn close(&mut self, commands: &mut Commands) {
+        if let Some(panel) = self.panel.take() {
+            commands.entity(panel).despawn();
+        }
+        self.list = None;
+        self.dirty = false;
+        self.content.close();
+    }
+}
+
+/// The system set in which the editor panels are opened and closed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct EditorPanelSystems;
+
+/// An extension trait for adding editor panels to the app.
+pub trait AddEditorPanelExt {
+    /// Adds the [`EditorPanel`] resource with the contents `T`, and registers
+    /// the given command to toggle it.
+    fn add_editor_panel<T: PanelContent>(&mut self, command: PaletteCommand) -> &mut Self;
+}
+
+impl AddEditorPanelExt for App {
+    fn add_editor_panel<T: PanelContent>(&mut self, command: PaletteCommand) -> &mut Self {
+        self.init_resource::<EditorPanel<T>>()
+            .add_systems(
+                Update,
+                toggle_panel::<T>
+                    .in_set(EditorPanelSystems)
+                    .run_if(in_state(AwgenState::Editor)),
+            )
+            .add_systems(OnExit(AwgenState::Editor), close_panel::<T>)
+            .register_command(command, request_toggle::<T>)
+    }
+}
+
+/// Toggles the panel on the next update, when run from the command palette
+/// or with its hotkey.
+fn request_toggle<T: PanelContent>(mut panel: ResMut<EditorPanel<T>>) {
+    panel.request_toggle();
+}
+
+/// Opens or closes the panel.
+fn toggle_panel<T: PanelContent>(
+    mut params: StaticSystemParam<T::Params>,
+    mut panel: ResMut<EditorPanel<T>>,
+    mut commands: Commands,
+) {
+    if !panel.toggle {
+        return;
+    }
+    panel.toggle = false;
+
+    if panel.is_open() {
+        panel.close(&mut commands);
+        return;
+    }
+
+    let (root, list) = panel.content.spawn(&mut *params, &mut commands);
+    panel.panel = Some(root);
+    panel.list = Some(list);
+    panel.dirty = true;
+}
+
+/// Closes the panel when leaving the editor.
+fn close_panel<T: PanelContent>(mut panel: ResMut<EditorPanel<T>>, mut commands: Commands) {
+    panel.close(&mut commands);
+}
@@ -0,0 +1,161 @@
+//! This module implements helpers shared by the editor tools for picking blocks
+//! with the mouse cursor.
+
+use bevy::picking::hover::HoverMap;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::map::WorldPos;
+use crate::ux::CameraController;
+
+/// The spacing, in logical pixels, between the cursor positions sampled along
+/// a drag.
+const DRAG_SAMPLE_SPACING: f32 = 4.0;
+
+/// Returns true if any pointer is hovering over an entity with the component
+/// `M`, or over one of its descendants.
+///
+/// This is used to keep clicks on a tool's panel from also editing the block
+/// behind it.
+pub(crate) fn pointer_over<M: Component>(
+    hover_map: &HoverMap,
+    parents: &Query<&ChildOf>,
+    panels: &Query<(), With<M>>,
+) -> bool {
+    hover_map
+        .values()
+        .flat_map(|pointer_map| pointer_map.keys().copied())
+        .any(|entity| {
+            panels.contains(entity)
+                || parents
+                    .iter_ancestors(entity)
+                    .any(|ancestor| panels.contains(ancestor))
+        })
+}
+
+/// Gets the ray from the main camera through the mouse cursor, if the cursor
+/// is within the primary window.
+pub(crate) fn cursor_ray(
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    cameras: &Query<(&Camera, &GlobalTransform), With<CameraController>>,
+) -> Option<Ray3d> {
+    let cursor = windows.single().ok()?.cursor_position()?;
+    let (camera, camera_transform) = cameras.single().ok()?;
+    camera.viewport_to_world(camera_transform, cursor).ok()
+}
+
+/// How the blocks edited by a drag are constrained.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DragLock {
+    /// Blocks follow the cursor freely.
+    #[default]
+    Free,
+
+    /// Blocks are locked to the layer of the block face the drag started on.
+    Plane,
+
+    /// Blocks are locked to a single axis through the first block of the drag,
+    /// within the layer of the face the drag started on.
+    Axis,
+}
+
+impl DragLock {
+    /// Gets the lock selected by the held modifier keys. `Ctrl` locks to an
+    /// axis, and `Shift` locks to a plane.
+    pub(crate) fn from_keys(keys: &ButtonInput<KeyCode>) -> Self {
+        if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+            DragLock::Axis
+        } else if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            DragLock::Plane
+        } else {
+            DragLock::Free
+        }
+    }
+}
+
+/// Tracks the mouse cursor across the frames of a drag, so that fast drags can
+/// be interpolated without leaving gaps.
+#[derive(Debug, Default)]
+pub(crate) struct CursorDrag {
+    /// The cursor position on the previous frame of the drag.
+    last: Option<Vec2>,
+}
+
+impl CursorDrag {
+    /// Gets the rays through the cursor positions passed since the previous
+    /// frame of the drag, in order, ending with the current cursor position.
+    ///
+    /// Returns no rays while the cursor is outside of the primary window.
+    pub(crate) fn rays(
+        &mut self,
+        windows: &Query<&Window, With<PrimaryWindow>>,
+        cameras: &Query<(&Camera, &GlobalTransform), With<CameraController>>,
+    ) -> Vec<Ray3d> {
+        let Ok(window) = windows.single() else {
+            return Vec::new();
+        };
+
+        let Some(cursor) = window.cursor_position() else {
+            return Vec::new();
+        };
+
+        let Ok((camera, camera_transform)) = cameras.single() else {
+            return Vec::new();
+        };
+
+        let from = self.last.replace(cursor).unwrap_or(cursor);
+        let steps = (from.distance(cursor) / DRAG_SAMPLE_SPACING)
+            .ceil()
+            .max(1.0) as u32;
+
+        (1 ..= steps)
+            .map(|step| from.lerp(cursor, step as f32 / steps as f32))
+            .filter_map(|pos| camera.viewport_to_world(camera_transform, pos).ok())
+            .collect()
+    }
+
+    /// Ends the drag, so the next drag starts at the cursor.
+    pub(crate) fn end(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Gets the block that the ray passes through within the layer of blocks that
+/// contains `anchor` and is perpendicular to the unit `normal`.
+pub(crate) fn plane_block(ray: Ray3d, anchor: WorldPos, normal: IVec3) -> Option<WorldPos> {
+    let center = anchor.as_vec3() + Vec3::splat(0.5);
+    let distance = ray.intersect_plane(center, InfinitePlane3d::new(normal.as_vec3()))?;
+    let block = ray.get_point(distance).floor().as_ivec3();
+
+    // Snap to the layer, as the intersection may round into a neighbor.
+    let mask = normal.abs();
+    let block = block * (IVec3::ONE - mask) + *anchor * mask;
+    Some(WorldPos::new(block.x, block.y, block.z))
+}
+
+/// Moves `target` onto the axis through `anchor` that it is furthest along.
+pub(crate) fn lock_axis(anchor: WorldPos, target: WorldPos) -> WorldPos {
+    let delta = *target - *anchor;
+    let abs = delta.abs();
+    let delta = if abs.x >= abs.y && abs.x >= abs.z {
+        IVec3::new(delta.x, 0, 0)
+    } else if abs.y >= abs.z {
+        IVec3::new(0, delta.y, 0)
+    } else {
+        IVec3::new(0, 0, delta.z)
+    };
+    anchor + WorldPos::new(delta.x, delta.y, delta.z)
+}
+
+/// Iterates over the blocks on the line from `from` to `to`, excluding `from`
+/// and including `to`.
+pub(crate) fn block_line(from: WorldPos, to: WorldPos) -> impl Iterator<Item = WorldPos> {
+    let delta = (*to - *from).as_vec3();
+    let steps = delta.abs().max_element() as i32;
+
+    (1 ..= steps).map(move |step| {
+        let pos = from.as_vec3() + delta * (step as f32 / steps as f32);
+        let pos = pos.round().as_ivec3();
+        WorldPos::new(pos.x, pos.y, pos.z)
+    })
+}
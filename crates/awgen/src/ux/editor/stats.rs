@@ -0,0 +1,324 @@
+//! This module implements the usage stats panel for the editor, which shows
+//! the local usage counters of the current session and of all sessions.
+//!
+//! The panel is toggled with the "Toggle Usage Stats" command. It also lets
+//! the user opt in to or out of usage counters, and clear the stored counts.
+
+use awgen_ui::menus::maximize::Maximizable;
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::button::{ButtonBuilder, ButtonContent, button};
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+
+use crate::app::AwgenState;
+use crate::database::GameDatabase;
+use crate::telemetry::UsageCounters;
+use crate::ux::editor::layout::{LayoutPanel, PanelKind};
+
+/// The interval, in seconds, between refreshes of the panel text.
+const REFRESH_INTERVAL: f32 = 1.0;
+
+/// Plugin that sets up the editor usage stats panel.
+pub struct UsageStatsPlugin;
+impl Plugin for UsageStatsPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<UsageStatsPanel>()
+            .add_systems(
+                Update,
+                (toggle_panel, refresh_buttons, refresh_text)
+                    .chain()
+                    .run_if(in_state(AwgenState::Editor)),
+            )
+            .add_systems(OnExit(AwgenState::Editor), close_panel)
+            .add_observer(on_stats_activate)
+            .register_command(
+                PaletteCommand::new("editor.usage_stats", "Toggle Usage Stats"),
+                request_toggle,
+            );
+    }
+}
+
+/// The state of the usage stats panel.
+#[derive(Debug, Resource)]
+pub struct UsageStatsPanel {
+    /// The panel, while it is open.
+    panel: Option<Entity>,
+
+    /// The text listing the counts, while the panel is open.
+    text: Option<Entity>,
+
+    /// The container of the panel buttons, while the panel is open.
+    buttons: Option<Entity>,
+
+    /// Whether the panel buttons need to be rebuilt.
+    dirty: bool,
+
+    /// Whether the panel should be toggled on the next update.
+    toggle: bool,
+
+    /// The timer that controls when the panel text is refreshed.
+    timer: Timer,
+}
+
+impl Default for UsageStatsPanel {
+    fn default() -> Self {
+        Self {
+            panel: None,
+            text: None,
+            buttons: None,
+            dirty: false,
+            toggle: false,
+            timer: Timer::from_seconds(REFRESH_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+impl UsageStatsPanel {
+    /// Returns true if the panel is open.
+    pub fn is_open(&self) -> bool {
+        self.panel.is_some()
+    }
+
+    /// Requests the panel to be opened or closed on the next update.
+    pub fn request_toggle(&mut self) {
+        self.toggle = true;
+    }
+}
+
+/// An action performed when a button in the usage stats panel is clicked.
+#[derive(Debug, Component)]
+enum UsageStatsAction {
+    /// Opts in to or out of usage counters.
+    ToggleCounting,
+
+    /// Clears all stored counts.
+    Clear,
+}
+
+/// Toggles the panel on the next update, when run from the command palette.
+fn request_toggle(mut state: ResMut<UsageStatsPanel>) {
+    state.toggle = true;
+}
+
+/// Opens or closes the usage stats panel.
+fn toggle_panel(
+    asset_server: Res<AssetServer>,
+    mut state: ResMut<UsageStatsPanel>,
+    mut commands: Commands,
+) {
+    if !state.toggle {
+        return;
+    }
+    state.toggle = false;
+
+    if let Some(panel) = state.panel.take() {
+        commands.entity(panel).despawn();
+        state.text = None;
+        state.buttons = None;
+        return;
+    }
+
+    let theme = hearth_theme(&asset_server);
+    let panel = commands
+        .spawn((
+            ScreenAnchor::Center,
+            LayoutPanel(PanelKind::UsageStats),
+            Maximizable,
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: px(8.0),
+                min_width: px(280.0),
+                max_width: px(400.0),
+                max_height: percent(80.0),
+                ..default()
+            },
+            theme.outer_window.clone(),
+            children![(Text::new("Usage Stats"), theme.outer_window.text.clone())],
+        ))
+        .id();
+
+    let text = commands
+        .spawn((
+            ChildOf(panel),
+            Node {
+                overflow: Overflow::scroll_y(),
+                scrollbar_width: 4.0,
+                ..default()
+            },
+            Text::default(),
+            theme.outer_window.text.clone(),
+        ))
+        .id();
+
+    let buttons = commands
+        .spawn((
+            ChildOf(panel),
+            Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: px(4.0),
+                ..default()
+            },
+        ))
+        .id();
+
+    // Show the text on the next update, rather than after the first interval.
+    let duration = state.timer.duration();
+    state.timer.set_elapsed(duration);
+
+    state.panel = Some(panel);
+    state.text = Some(text);
+    state.buttons = Some(buttons);
+    state.dirty = true;
+}
+
+/// Closes the usage stats panel when leaving the editor.
+fn close_panel(mut state: ResMut<UsageStatsPanel>, mut commands: Commands) {
+    if let Some(panel) = state.panel.take() {
+        commands.entity(panel).despawn();
+    }
+    state.text = None;
+    state.buttons = None;
+}
+
+/// Rebuilds the panel buttons when the usage counters are enabled or
+/// disabled.
+fn refresh_buttons(
+    asset_server: Res<AssetServer>,
+    database: Res<GameDatabase>,
+    counters: Res<UsageCounters>,
+    mut state: ResMut<UsageStatsPanel>,
+    mut commands: Commands,
+) {
+    if !state.dirty {
+        return;
+    }
+    state.dirty = false;
+
+    let Some(buttons) = state.buttons else {
+        return;
+    };
+
+    commands.entity(buttons).despawn_related::<Children>();
+
+    // The opt-in choice and the counts are stored in the game database.
+    if database.is_read_only() {
+        return;
+    }
+
+    let theme = hearth_theme(&asset_server);
+    let label = if counters.is_enabled() {
+        "Stop Counting"
+    } else {
+        "Start Counting"
+    };
+
+    commands.spawn((
+        ChildOf(buttons),
+        UsageStatsAction::ToggleCounting,
+        button(ButtonBuilder {
+            node: Node::default(),
+            content: ButtonContent::text(label),
+            theme: theme.clone(),
+        }),
+    ));
+
+    commands.spawn((
+        ChildOf(buttons),
+        UsageStatsAction::Clear,
+        button(ButtonBuilder {
+            node: Node::default(),
+            content: ButtonContent::text("Clear Stats"),
+            theme,
+        }),
+    ));
+}
+
+/// Refreshes the panel text periodically, flushing the pending counts so that
+/// the totals are up to date.
+fn refresh_text(
+    time: Res<Time>,
+    database: Res<GameDatabase>,
+    mut counters: ResMut<UsageCounters>,
+    mut state: ResMut<UsageStatsPanel>,
+    mut texts: Query<&mut Text>,
+) {
+    let Some(text) = state.text else {
+        return;
+    };
+
+    if !state.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    if let Err(err) = counters.flush(&database) {
+        warn!("Failed to store the usage counters: {}", err);
+    }
+
+    if let Ok(mut text) = texts.get_mut(text) {
+        text.set_if_neq(Text(stats_text(&counters, &database)));
+    }
+}
+
+/// Describes the counts of the current session and of all sessions.
+fn stats_text(counters: &UsageCounters, database: &GameDatabase) -> String {
+    if !counters.is_enabled() {
+        return "Usage counters are off.\n\nWhen they are on, commands run, packets \
+                processed, and assets created are counted in the project database. \
+                Nothing is sent over the network."
+            .to_string();
+    }
+
+    let mut text = "This session:".to_string();
+    if counters.session_counts().is_empty() {
+        text += "\n  Nothing counted yet";
+    }
+    for (name, count) in counters.session_counts() {
+        text += &format!("\n  {name}: {count}");
+    }
+
+    let sessions = database.usage_session_count().unwrap_or(0);
+    text += &format!("\n\nAll sessions ({sessions}):");
+    match database.list_usage_counts(None) {
+        Ok(counts) => {
+            for count in counts {
+                text += &format!("\n  {}: {}", count.name, count.count);
+            }
+        }
+        Err(err) => text += &format!("\n  Failed to read the stored counts: {err}"),
+    }
+
+    text
+}
+
+/// Performs the action of a usage stats panel button when it is clicked.
+fn on_stats_activate(
+    trigger: On<Activate>,
+    actions: Query<&UsageStatsAction>,
+    database: Res<GameDatabase>,
+    mut counters: ResMut<UsageCounters>,
+    mut state: ResMut<UsageStatsPanel>,
+) {
+    let Ok(action) = actions.get(trigger.event().entity) else {
+        return;
+    };
+
+    match action {
+        UsageStatsAction::ToggleCounting => {
+            let enabled = !counters.is_enabled();
+            if let Err(err) = counters.set_enabled(enabled, &database) {
+                error!("Failed to change the usage counter settings: {}", err);
+            }
+        }
+        UsageStatsAction::Clear => match database.clear_usage_counts() {
+            Ok(()) => counters.reset(),
+            Err(err) => error!("Failed to clear the usage counters: {}", err),
+        },
+    }
+
+    // Refresh the buttons and the text on the next update.
+    let duration = state.timer.duration();
+    state.timer.set_elapsed(duration);
+    state.dirty = true;
+}
@@ -0,0 +1,300 @@
+//! This module implements exporting the loaded terrain as a mesh, for use in
+//! external modeling tools and for rendering outside of the engine.
+//!
+//! The export is started with the "Export Terrain Mesh" command. The meshes of
+//! the loaded chunks are baked into a single Wavefront OBJ file in a
+//! background job, along with a material file and a texture atlas image.
+//!
+//! Terrain is drawn with a texture array, which most tools cannot read, so the
+//! tiles used by the terrain are flattened into a grid in the atlas image and
+//! the texture coordinates of each vertex are remapped into the cell of its
+//! tile.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use bevy::prelude::*;
+use image::RgbaImage;
+
+use crate::app::AwgenState;
+use crate::jobs::{
+    BackgroundJobSystems,
+    BackgroundJobs,
+    BackgroundJobsAppExt,
+    JobContext,
+    JobFinished,
+    JobResult,
+};
+use crate::map::{ChunkModels, SectionMask, SectionMeshes, VoxelChunk};
+use crate::tiles::{ActiveTilesets, TerrainMesh, TilePalette, TilesetMaterial, extract_tile};
+
+/// The file name suggested when exporting the terrain.
+const EXPORT_FILE_NAME: &str = "terrain.obj";
+
+/// The name of the terrain material in the exported material file.
+const MATERIAL_NAME: &str = "terrain";
+
+/// The share of the export progress spent baking the chunk meshes, with the
+/// rest spent writing the files.
+const BAKE_PROGRESS: f32 = 0.8;
+
+/// Plugin that sets up the terrain mesh export.
+pub struct TerrainExportPlugin;
+impl Plugin for TerrainExportPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.register_job_output::<TerrainExported>()
+            .add_systems(
+                Update,
+                finish_exports
+                    .after(BackgroundJobSystems::Poll)
+                    .run_if(in_state(AwgenState::Editor)),
+            )
+            .register_command(
+                PaletteCommand::new("editor.export_terrain", "Export Terrain Mesh"),
+                export_terrain,
+            );
+    }
+}
+
+/// The output of a terrain export job, with the path the mesh was written to,
+/// or `None` if the file dialog was closed.
+#[derive(Debug)]
+pub struct TerrainExported(pub io::Result<Option<PathBuf>>);
+
+/// The mesh of a loaded chunk, as it is handed to the export job.
+enum ChunkSource {
+    /// The cached section meshes of a chunk that has been drawn.
+    Drawn(SectionMeshes),
+
+    /// The block models of a chunk that has not been drawn yet, which are
+    /// meshed by the job instead.
+    Undrawn(ChunkModels),
+}
+
+/// Starts exporting the loaded terrain, asking for the file to write first.
+fn export_terrain(
+    chunks: Query<(&VoxelChunk, &GlobalTransform)>,
+    active_tilesets: Res<ActiveTilesets>,
+    materials: Res<Assets<TilesetMaterial>>,
+    images: Res<Assets<Image>>,
+    palette: Res<TilePalette>,
+    mut jobs: ResMut<BackgroundJobs>,
+) {
+    let loaded = chunks
+        .iter()
+        .map(|(chunk, transform)| {
+            let source = match chunk.section_meshes() {
+                Some(meshes) => ChunkSource::Drawn(meshes.clone()),
+                None => ChunkSource::Undrawn(chunk.get_models().clone()),
+            };
+            (transform.compute_transform(), source)
+        })
+        .collect::<Vec<_>>();
+
+    let tileset = materials
+        .get(&active_tilesets.opaque)
+        .and_then(|material| images.get(&material.texture))
+        .cloned();
+    if tileset.is_none() {
+        warn!("No tileset is loaded, so the terrain will be exported without a texture.");
+    }
+
+    let palette = palette.clone();
+    jobs.spawn("Exporting terrain mesh", |ctx| async move {
+        let dialog = rfd::AsyncFileDialog::new()
+            .set_title("Export Terrain Mesh")
+            .set_file_name(EXPORT_FILE_NAME)
+            .add_filter("Wavefront OBJ", &["obj"]);
+
+        let Some(file) = dialog.save_file().await else {
+            return TerrainExported(Ok(None));
+        };
+
+        let Some(terrain) = bake_chunks(&ctx, &loaded, &palette) else {
+            return TerrainExported(Ok(None));
+        };
+
+        let path = file.path().with_extension("obj");
+        let result = write_export(&path, &terrain, tileset.as_ref()).map(|()| Some(path));
+        ctx.set_progress(1.0);
+        TerrainExported(result)
+    });
+}
+
+/// Reports the result of finished exports.
+fn finish_exports(mut finished: MessageReader<JobFinished<TerrainExported>>) {
+    for job in finished.read() {
+        match &job.result {
+            JobResult::Completed(TerrainExported(Ok(Some(path)))) => {
+                info!("Exported the terrain mesh to {}", path.display());
+            }
+            JobResult::Completed(TerrainExported(Err(err))) => {
+                error!("Failed to export the terrain mesh: {}", err);
+            }
+            JobResult::Completed(TerrainExported(Ok(None))) => {}
+            JobResult::Cancelled => info!("Terrain export was cancelled."),
+        }
+    }
+}
+
+/// Merges the meshes of the given chunks into a single terrain mesh in world
+/// space, reporting the progress to the job.
+///
+/// Returns `None` if the job is cancelled, as its output is discarded anyway.
+fn bake_chunks(
+    ctx: &JobContext,
+    chunks: &[(Transform, ChunkSource)],
+    palette: &TilePalette,
+) -> Option<TerrainMesh> {
+    let mut terrain = TerrainMesh::new();
+    for (index, (transform, source)) in chunks.iter().enumerate() {
+        if ctx.is_cancelled() {
+            return None;
+        }
+
+        let mesh = match source {
+            ChunkSource::Drawn(meshes) => meshes.terrain_mesh(),
+            ChunkSource::Undrawn(models) => {
+                let mut meshes = SectionMeshes::default();
+                meshes.rebuild(models, palette, SectionMask::ALL);
+                meshes.terrain_mesh()
+            }
+        };
+
+        terrain.append(&mesh, *transform);
+        ctx.set_progress((index + 1) as f32 / chunks.len() as f32 * BAKE_PROGRESS);
+    }
+
+    Some(terrain)
+}
+
+/// A grid of tiles flattened from a tileset, which the texture coordinates of
+/// the exported terrain point into.
+struct TileAtlas {
+    /// The cell of each tileset layer used by the terrain.
+    cells: BTreeMap<u32, u32>,
+
+    /// The number of cells in each row of the atlas.
+    columns: u32,
+
+    /// The number of rows in the atlas.
+    rows: u32,
+}
+
+impl TileAtlas {
+    /// Lays out a cell for each tileset layer used by the given terrain.
+    fn new(terrain: &TerrainMesh) -> Self {
+        let mut cells = BTreeMap::new();
+        for uv in terrain.tex_coords() {
+            cells.insert(uv[2] as u32, 0);
+        }
+        for (cell, index) in cells.values_mut().zip(0 ..) {
+            *cell = index;
+        }
+
+        let count = cells.len().max(1) as u32;
+        let columns = (count as f32).sqrt().ceil() as u32;
+        let rows = count.div_ceil(columns);
+        Self {
+            cells,
+            columns,
+            rows,
+        }
+    }
+
+    /// Maps the texture coordinates of a vertex into the cell of its tile.
+    ///
+    /// The vertical coordinate is flipped, as OBJ texture coordinates start at
+    /// the bottom of the image.
+    fn map(&self, [u, v, layer]: [f32; 3]) -> [f32; 2] {
+        let cell = self.cells.get(&(layer as u32)).copied().unwrap_or(0);
+        let column = (cell % self.columns) as f32 + u.clamp(0.0, 1.0);
+        let row = (cell / self.columns) as f32 + v.clamp(0.0, 1.0);
+        [column / self.columns as f32, 1.0 - row / self.rows as f32]
+    }
+
+    /// Draws the used tiles of the given tileset into the atlas image.
+    fn draw(&self, tileset: &Image) -> RgbaImage {
+        let size = tileset.texture_descriptor.size.width;
+        let mut atlas = RgbaImage::new(size * self.columns, size * self.rows);
+
+        for (&layer, &cell) in &self.cells {
+            let Some(data) = extract_tile(tileset, layer).and_then(|tile| tile.data) else {
+                warn!("Tile {} is missing from the tileset.", layer);
+                continue;
+            };
+
+            let Some(tile) = RgbaImage::from_raw(size, size, data) else {
+                continue;
+            };
+
+            let x = (cell % self.columns) * size;
+            let y = (cell / self.columns) * size;
+            image::imageops::replace(&mut atlas, &tile, x as i64, y as i64);
+        }
+
+        atlas
+    }
+}
+
+/// Writes the terrain mesh to the OBJ file at the given path, along with its
+/// material file and, if a tileset is given, its texture atlas.
+///
+/// The material file and atlas are written next to the OBJ file, with the
+/// same name and the `mtl` and `png` extensions.
+fn write_export(path: &Path, terrain: &TerrainMesh, tileset: Option<&Image>) -> io::Result<()> {
+    let atlas = TileAtlas::new(terrain);
+    let mtl_path = path.with_extension("mtl");
+    let png_path = path.with_extension("png");
+
+    let mut mtl = BufWriter::new(File::create(&mtl_path)?);
+    writeln!(mtl, "newmtl {MATERIAL_NAME}")?;
+    writeln!(mtl, "Kd 1 1 1")?;
+    if let Some(tileset) = tileset {
+        atlas
+            .draw(tileset)
+            .save(&png_path)
+            .map_err(io::Error::other)?;
+        writeln!(mtl, "map_Kd {}", file_name(&png_path))?;
+    }
+    mtl.flush()?;
+
+    let mut obj = BufWriter::new(File::create(path)?);
+    writeln!(obj, "# Terrain exported from Awgen")?;
+    writeln!(obj, "mtllib {}", file_name(&mtl_path))?;
+    writeln!(obj, "o terrain")?;
+
+    // Vertex colors tint the terrain. OBJ has no vertex color element, but
+    // most tools read colors written after the vertex position.
+    for (position, color) in terrain.positions().iter().zip(terrain.colors()) {
+        let [x, y, z] = position;
+        let [r, g, b, _] = color;
+        writeln!(obj, "v {x} {y} {z} {r} {g} {b}")?;
+    }
+    for uv in terrain.tex_coords() {
+        let [u, v] = atlas.map(*uv);
+        writeln!(obj, "vt {u} {v}")?;
+    }
+    for [x, y, z] in terrain.normals() {
+        writeln!(obj, "vn {x} {y} {z}")?;
+    }
+
+    writeln!(obj, "usemtl {MATERIAL_NAME}")?;
+    for triangle in terrain.indices().chunks_exact(3) {
+        // OBJ indices start at 1.
+        let [a, b, c] = [triangle[0] + 1, triangle[1] + 1, triangle[2] + 1];
+        writeln!(obj, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}")?;
+    }
+    obj.flush()
+}
+
+/// Gets the file name of the given path, for referencing a file written next
+/// to another.
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -1,15 +1,53 @@
 //! This module implements the toolbar for the editor UX.
+//!
+//! The toolbar holds the File menu, which snapshots the game database into the
+//! backups folder of the project, or restores the latest snapshot, and the
+//! Lights button, which toggles the light tool. The hotkeys of both actions
+//! are shown as shortcut hints.
+//!
+//! Restoring a snapshot reopens the whole project, so it runs as a blocking
+//! background job while a busy overlay is shown.
 
+use std::path::PathBuf;
+
+use awgen_ui::hotkeys::{BindHotkeyExt, Hotkey, HotkeyContext, ShortcutHint};
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::button::{ButtonBuilder, ButtonContent, button};
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
 use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
 
-use crate::app::AwgenState;
+use crate::app::{AwgenState, ProjectSettings};
+use crate::database::backup::{self, GameDatabaseRestored};
+use crate::database::{DatabaseError, GameDatabase};
+use crate::jobs::{
+    BackgroundJobSystems,
+    BackgroundJobs,
+    BackgroundJobsAppExt,
+    JobFinished,
+    JobResult,
+};
+use crate::ux::editor::lights::LightToolToggle;
 
 /// Plugin that sets up the editor toolbar.
 pub struct EditorToolbarPlugin;
 impl Plugin for EditorToolbarPlugin {
     fn build(&self, app_: &mut App) {
-        app_.add_systems(OnEnter(AwgenState::Editor), setup)
-            .add_systems(OnExit(AwgenState::Editor), cleanup);
+        app_.register_job_output::<ProjectRestored>()
+            .add_systems(OnEnter(AwgenState::Editor), setup)
+            .add_systems(OnExit(AwgenState::Editor), cleanup)
+            .add_systems(Update, finish_restores.after(BackgroundJobSystems::Poll))
+            .add_observer(on_file_menu_activate)
+            .register_command(
+                PaletteCommand::new("editor.backup", "Back Up Project"),
+                back_up_project,
+            )
+            .bind_hotkey(
+                "editor.backup",
+                Hotkey::new(KeyCode::KeyS).ctrl().shift(),
+                HotkeyContext::Editor,
+            );
     }
 }
 
@@ -17,8 +55,122 @@ impl Plugin for EditorToolbarPlugin {
 #[derive(Debug, Component)]
 pub struct EditorToolbar;
 
+/// A marker component for the entries of the File menu, which are hidden
+/// until the menu is opened.
+#[derive(Debug, Component)]
+struct FileMenuEntries;
+
+/// An action performed when a button in the File menu is clicked.
+#[derive(Debug, Component)]
+enum FileMenuAction {
+    /// Opens or closes the File menu.
+    Toggle,
+
+    /// Writes a backup of the game database.
+    Backup,
+
+    /// Restores the game database from the latest backup.
+    RestoreLatest,
+}
+
 /// Sets up the editor toolbar.
-fn setup() {}
+fn setup(project: Res<ProjectSettings>, asset_server: Res<AssetServer>, mut commands: Commands) {
+    let theme = hearth_theme(&asset_server);
+    let toolbar = commands
+        .spawn((
+            EditorToolbar,
+            ScreenAnchor::TopRight,
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::End,
+                row_gap: px(4.0),
+                margin: UiRect::all(px(8.0)),
+                ..default()
+            },
+        ))
+        .id();
+
+    if project.is_read_only() {
+        commands.spawn((
+            ChildOf(toolbar),
+            Text::new("Read-only"),
+            TextColor::from(Color::WHITE),
+            TextBackgroundColor(Color::srgba(0.6, 0.1, 0.1, 0.8)),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+        ));
+    }
+
+    commands.spawn((
+        ChildOf(toolbar),
+        LightToolToggle,
+        ShortcutHint::tooltip("editor.lights", theme.clone()),
+        button(ButtonBuilder {
+            node: Node::default(),
+            content: ButtonContent::text("Lights"),
+            theme: theme.clone(),
+        }),
+    ));
+
+    commands.spawn((
+        ChildOf(toolbar),
+        FileMenuAction::Toggle,
+        button(ButtonBuilder {
+            node: Node::default(),
+            content: ButtonContent::text("File"),
+            theme: theme.clone(),
+        }),
+    ));
+
+    let entries = commands
+        .spawn((
+            ChildOf(toolbar),
+            FileMenuEntries,
+            Node {
+                display: Display::None,
+                flex_direction: FlexDirection::Column,
+                row_gap: px(4.0),
+                ..default()
+            },
+            theme.outer_window.clone(),
+        ))
+        .id();
+
+    commands.spawn((
+        ChildOf(entries),
+        FileMenuAction::Backup,
+        ShortcutHint::inline("editor.backup", theme.clone()),
+        button(ButtonBuilder {
+            node: Node::default(),
+            content: ButtonContent::text("Back Up Project"),
+            theme: theme.clone(),
+        }),
+    ));
+
+    if !project.is_read_only() {
+        commands.spawn((
+            ChildOf(entries),
+            FileMenuAction::RestoreLatest,
+            button(ButtonBuilder {
+                node: Node::default(),
+                content: ButtonContent::text("Restore Latest Backup"),
+                theme,
+            }),
+        ));
+    }
+}
+
+/// The output of a job that restores the game database from a backup.
+struct ProjectRestored {
+    /// The backup file that the game database was restored from.
+    path: PathBuf,
+
+    /// The result of restoring the game database, or `None` if the job was
+    /// cancelled before the restore started.
+    result: Option<Result<(), DatabaseError>>,
+}
 
 /// Cleans up the editor toolbar.
 fn cleanup(toolbar: Query<Entity, With<EditorToolbar>>, mut commands: Commands) {
@@ -26,3 +178,102 @@ fn cleanup(toolbar: Query<Entity, With<EditorToolbar>>, mut commands: Commands)
         commands.entity(entity).despawn();
     }
 }
+
+/// Performs the action of a File menu button when it is clicked.
+fn on_file_menu_activate(
+    trigger: On<Activate>,
+    actions: Query<&FileMenuAction>,
+    mut entries: Query<&mut Node, With<FileMenuEntries>>,
+    project: Res<ProjectSettings>,
+    database: Res<GameDatabase>,
+    mut jobs: ResMut<BackgroundJobs>,
+) {
+    let Ok(action) = actions.get(trigger.event().entity) else {
+        return;
+    };
+
+    if let FileMenuAction::Toggle = action {
+        for mut node in entries.iter_mut() {
+            node.display = match node.display {
+                Display::None => Display::Flex,
+                _ => Display::None,
+            };
+        }
+        return;
+    }
+
+    for mut node in entries.iter_mut() {
+        node.display = Display::None;
+    }
+
+    match action {
+        FileMenuAction::Toggle => {}
+        FileMenuAction::Backup => write_backup(&project, &database),
+        FileMenuAction::RestoreLatest => {
+            let path = match backup::latest_backup(project.project_folder()) {
+                Ok(Some(path)) => path,
+                Ok(None) => {
+                    warn!("There are no backups to restore");
+                    return;
+                }
+                Err(err) => {
+                    error!("Failed to find the latest backup: {}", err);
+                    return;
+                }
+            };
+
+            let database = database.0.clone();
+            jobs.spawn_blocking("Restoring project", |ctx| async move {
+                // The restore cannot be stopped once it has started.
+                let result = (!ctx.is_cancelled()).then(|| database.restore_from(&path));
+                ProjectRestored { path, result }
+            });
+        }
+    }
+}
+
+/// Reports finished restores, and reloads the data loaded from the game
+/// database.
+fn finish_restores(
+    mut finished: MessageReader<JobFinished<ProjectRestored>>,
+    mut restored: MessageWriter<GameDatabaseRestored>,
+) {
+    for job in finished.read() {
+        match &job.result {
+            JobResult::Completed(ProjectRestored {
+                path,
+                result: Some(Ok(())),
+            }) => {
+                info!("Restored the game database from {}", path.display());
+                restored.write(GameDatabaseRestored);
+            }
+            JobResult::Completed(ProjectRestored {
+                result: Some(Err(err)),
+                ..
+            }) => error!("Failed to restore the game database: {}", err),
+            JobResult::Completed(ProjectRestored { result: None, .. }) => {
+                info!("Restoring the project was cancelled.");
+            }
+            JobResult::Cancelled => {
+                // The job may have been cancelled after the restore started,
+                // so the data loaded from the game database is reloaded anyway.
+                info!("Restoring the project was cancelled.");
+                restored.write(GameDatabaseRestored);
+            }
+        }
+    }
+}
+
+/// Backs up the game database, when run from the command palette.
+fn back_up_project(project: Res<ProjectSettings>, database: Res<GameDatabase>) {
+    write_backup(&project, &database);
+}
+
+/// Writes a backup of the game database into the backups folder of the
+/// project.
+fn write_backup(project: &ProjectSettings, database: &GameDatabase) {
+    match backup::create_backup(database, project.project_folder()) {
+        Ok(path) => info!("Backed up the game database to {}", path.display()),
+        Err(err) => error!("Failed to back up the game database: {}", err),
+    }
+}
@@ -0,0 +1,135 @@
+//! This module implements the native file picker, which lets the user choose a
+//! file using the file dialog of the operating system.
+//!
+//! File dialogs are requested by scripts in editor mode, so that import
+//! workflows do not need hard-coded paths. Each dialog runs as a background job
+//! so that the editor keeps rendering while it is open, and the chosen path is
+//! sent back to the script engine once the dialog is closed.
+
+use std::path::PathBuf;
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::jobs::{
+    BackgroundJobSystems,
+    BackgroundJobs,
+    BackgroundJobsAppExt,
+    JobFinished,
+    JobId,
+    JobResult,
+};
+use crate::scripts::{PacketOut, ScriptEngine};
+
+/// Plugin that sets up native file dialogs.
+pub struct FilePickerPlugin;
+impl Plugin for FilePickerPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<PendingFileDialogs>()
+            .register_job_output::<PickedFile>()
+            .add_systems(Update, reply_picked_files.after(BackgroundJobSystems::Poll));
+    }
+}
+
+/// A filter that limits the files shown in a file dialog to those with one of
+/// the given extensions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FileFilter {
+    /// The name of the filter, such as `"Images"`.
+    pub name: String,
+
+    /// The file extensions matched by the filter, without the leading dot,
+    /// such as `"png"`.
+    pub extensions: Vec<String>,
+}
+
+/// The output of a file dialog job.
+#[derive(Debug)]
+pub struct PickedFile(pub Option<PathBuf>);
+
+/// A resource that maps the running file dialog jobs to the IDs of the script
+/// requests that opened them.
+#[derive(Debug, Default, Resource)]
+pub struct PendingFileDialogs(HashMap<JobId, String>);
+
+impl PendingFileDialogs {
+    /// Returns the number of file dialogs that are open.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if no file dialogs are open.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Opens a native file dialog with the given title and filters.
+///
+/// Once the dialog is closed, the chosen path is sent to the script engine in a
+/// [`PacketOut::FileDialogClosed`] packet with the given request ID.
+pub fn open_file_dialog(
+    world: &mut World,
+    id: String,
+    title: Option<String>,
+    filters: Vec<FileFilter>,
+) {
+    let mut dialog = rfd::AsyncFileDialog::new();
+    if let Some(title) = title {
+        dialog = dialog.set_title(title);
+    }
+
+    for filter in filters {
+        dialog = dialog.add_filter(filter.name, filter.extensions.as_slice());
+    }
+
+    let label = format!("Waiting for file dialog \"{}\"", id);
+    let handle = world
+        .resource_mut::<BackgroundJobs>()
+        .spawn(label, |_| async move {
+            let file = dialog.pick_file().await;
+            PickedFile(file.map(|file| file.path().to_path_buf()))
+        });
+
+    world
+        .resource_mut::<PendingFileDialogs>()
+        .0
+        .insert(handle.id(), id);
+}
+
+/// System that sends the chosen path of each closed file dialog to the script
+/// engine.
+///
+/// Dialogs whose job was cancelled are reported as closed without a path.
+fn reply_picked_files(
+    mut finished: MessageReader<JobFinished<PickedFile>>,
+    mut pending: ResMut<PendingFileDialogs>,
+    engine: Res<ScriptEngine>,
+) {
+    for job in finished.read() {
+        let Some(id) = pending.0.remove(&job.handle.id()) else {
+            continue;
+        };
+
+        let path = match &job.result {
+            JobResult::Completed(PickedFile(path)) => path.as_ref(),
+            JobResult::Cancelled => None,
+        };
+
+        debug!("File dialog \"{}\" closed with {:?}", id, path);
+        let packet = PacketOut::FileDialogClosed {
+            id,
+            path: path.map(|path| path.to_string_lossy().into_owned()),
+        };
+
+        if let Err(err) = engine.send(packet) {
+            error!(
+                "Failed to send file dialog result to script engine: {}",
+                err
+            );
+        }
+    }
+}
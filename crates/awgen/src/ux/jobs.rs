@@ -0,0 +1,273 @@
+//! This module implements the background jobs panel, which lists the jobs
+//! that are currently running along with their progress.
+//!
+//! The panel is shown automatically while any jobs are running, and each job
+//! can be cancelled from it.
+//!
+//! While a blocking job is running, a busy overlay with its label and progress
+//! covers the screen instead, so that the user can only wait for the job or
+//! cancel it.
+
+use awgen_ui::menus::overlay::ScreenAnchor;
+use awgen_ui::themes::hearth_theme;
+use awgen_ui::widgets::busy::{BusyCancelPressed, BusyOverlay, BusyOverlayBuilder, busy_overlay};
+use awgen_ui::widgets::button::{ButtonBuilder, ButtonContent, button};
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+
+use crate::jobs::{BackgroundJobSystems, BackgroundJobs, JobId};
+
+/// Plugin that sets up the background jobs panel.
+pub struct JobsPanelPlugin;
+impl Plugin for JobsPanelPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<JobsPanel>()
+            .init_resource::<BusyJob>()
+            .add_systems(
+                Update,
+                (refresh_panel, update_progress, update_busy_overlay)
+                    .chain()
+                    .after(BackgroundJobSystems::Poll),
+            )
+            .add_observer(on_cancel_activate)
+            .add_observer(on_busy_cancel);
+    }
+}
+
+/// The state of the background jobs panel.
+#[derive(Debug, Default, Resource)]
+struct JobsPanel {
+    /// The panel, while it is shown.
+    panel: Option<Entity>,
+
+    /// The container of the job rows, while the panel is shown.
+    list: Option<Entity>,
+
+    /// The jobs that the rows were built for.
+    jobs: Vec<JobId>,
+}
+
+/// The busy overlay shown while a blocking job is running.
+#[derive(Debug, Default, Resource)]
+struct BusyJob {
+    /// The overlay, while it is shown.
+    overlay: Option<Entity>,
+
+    /// The blocking job whose progress is shown.
+    job: Option<JobId>,
+}
+
+/// A marker component for the text showing the progress of a job.
+#[derive(Debug, Component)]
+struct JobProgressText(JobId);
+
+/// A marker component for a button that cancels a job.
+#[derive(Debug, Component)]
+struct CancelJobButton(JobId);
+
+/// Shows or hides the panel, and rebuilds the job rows when the set of
+/// running jobs has changed.
+fn refresh_panel(
+    jobs: Res<BackgroundJobs>,
+    asset_server: Res<AssetServer>,
+    mut state: ResMut<JobsPanel>,
+    mut commands: Commands,
+) {
+    if jobs.is_empty() {
+        if let Some(panel) = state.panel.take() {
+            commands.entity(panel).despawn();
+            state.list = None;
+            state.jobs.clear();
+        }
+        return;
+    }
+
+    if jobs.iter().map(|job| job.id).eq(state.jobs.iter().copied()) {
+        return;
+    }
+
+    let theme = hearth_theme(&asset_server);
+    let list = match state.list {
+        Some(list) => {
+            commands.entity(list).despawn_related::<Children>();
+            list
+        }
+        None => {
+            let panel = commands
+                .spawn((
+                    ScreenAnchor::TopLeft,
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: px(8.0),
+                        min_width: px(240.0),
+                        max_height: percent(40.0),
+                        margin: UiRect::all(px(8.0)),
+                        ..default()
+                    },
+                    theme.outer_window.clone(),
+                    children![(
+                        Text::new("Background Jobs"),
+                        theme.outer_window.text.clone()
+                    )],
+                ))
+                .id();
+
+            let list = commands
+                .spawn((
+                    ChildOf(panel),
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: px(4.0),
+                        overflow: Overflow::scroll_y(),
+                        scrollbar_width: 4.0,
+                        ..default()
+                    },
+                ))
+                .id();
+
+            state.panel = Some(panel);
+            state.list = Some(list);
+            list
+        }
+    };
+
+    state.jobs.clear();
+    for job in jobs.iter() {
+        let row = commands
+            .spawn((
+                ChildOf(list),
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: px(4.0),
+                    ..default()
+                },
+            ))
+            .id();
+
+        commands.spawn((
+            ChildOf(row),
+            Node {
+                flex_grow: 1.0,
+                ..default()
+            },
+            Text::new(job.label),
+            theme.outer_window.text.clone(),
+        ));
+
+        commands.spawn((
+            ChildOf(row),
+            JobProgressText(job.id),
+            Text::new(format_progress(job.progress, job.cancelled)),
+            theme.outer_window.text.clone(),
+        ));
+
+        commands.spawn((
+            ChildOf(row),
+            CancelJobButton(job.id),
+            button(ButtonBuilder {
+                node: Node::default(),
+                content: ButtonContent::text("X"),
+                theme: theme.clone(),
+            }),
+        ));
+
+        state.jobs.push(job.id);
+    }
+}
+
+/// Updates the progress text of each job row.
+fn update_progress(jobs: Res<BackgroundJobs>, mut texts: Query<(&JobProgressText, &mut Text)>) {
+    for job in jobs.iter() {
+        for (_, mut text) in texts
+            .iter_mut()
+            .filter(|(progress, _)| progress.0 == job.id)
+        {
+            let progress = format_progress(job.progress, job.cancelled);
+            if text.0 != progress {
+                text.0 = progress;
+            }
+        }
+    }
+}
+
+/// Formats the progress of a job for display.
+fn format_progress(progress: f32, cancelled: bool) -> String {
+    if cancelled {
+        "Cancelling".to_string()
+    } else {
+        format!("{:.0}%", progress * 100.0)
+    }
+}
+
+/// Cancels a job when its cancel button is clicked.
+fn on_cancel_activate(
+    trigger: On<Activate>,
+    buttons: Query<&CancelJobButton>,
+    jobs: Res<BackgroundJobs>,
+) {
+    let Ok(button) = buttons.get(trigger.event().entity) else {
+        return;
+    };
+
+    if jobs.cancel(button.0) {
+        info!("Cancelling background job {}", button.0);
+    }
+}
+
+/// Shows the busy overlay while a blocking job is running, and updates it with
+/// the label and progress of the oldest running blocking job.
+fn update_busy_overlay(
+    jobs: Res<BackgroundJobs>,
+    asset_server: Res<AssetServer>,
+    mut busy: ResMut<BusyJob>,
+    mut overlays: Query<&mut BusyOverlay>,
+    mut commands: Commands,
+) {
+    let Some(job) = jobs.iter().find(|job| job.blocking) else {
+        if let Some(overlay) = busy.overlay.take() {
+            commands.entity(overlay).despawn();
+            busy.job = None;
+        }
+        return;
+    };
+
+    let status = format!(
+        "{} ({})",
+        job.label,
+        format_progress(job.progress, job.cancelled)
+    );
+    busy.job = Some(job.id);
+
+    if let Some(mut overlay) = busy
+        .overlay
+        .and_then(|overlay| overlays.get_mut(overlay).ok())
+    {
+        if overlay.status != status {
+            overlay.status = status;
+        }
+        return;
+    }
+
+    let theme = hearth_theme(&asset_server);
+    let overlay = commands
+        .spawn(busy_overlay(BusyOverlayBuilder {
+            status,
+            cancel: Some("Cancel".to_string()),
+            theme,
+        }))
+        .id();
+    busy.overlay = Some(overlay);
+}
+
+/// Cancels the blocking job shown by the busy overlay when its cancel button is
+/// clicked.
+fn on_busy_cancel(_: On<BusyCancelPressed>, busy: Res<BusyJob>, jobs: Res<BackgroundJobs>) {
+    let Some(job) = busy.job else {
+        return;
+    };
+
+    if jobs.cancel(job) {
+        info!("Cancelling background job {}", job);
+    }
+}
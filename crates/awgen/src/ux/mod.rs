@@ -3,22 +3,45 @@
 use awgen_ui::AwgenUiPlugin;
 use bevy::prelude::*;
 
+mod accessibility;
+pub mod asset_watch;
+mod atmosphere;
+mod budgets;
 mod camera;
+pub mod chat;
+mod chunk_debug;
 mod diagnostics;
 mod editor;
+pub mod file_picker;
 mod filedrop;
+mod jobs;
+mod sprite_picking;
 
-pub use camera::CameraController;
+pub use atmosphere::{AtmosphereError, AtmosphereSettings};
+pub use budgets::{BudgetError, PerformanceBudgets};
+pub use camera::{CameraController, CameraProjection};
+pub use chunk_debug::ChunkDebug;
+pub use editor::commands::SCRIPT_COMMAND_PREFIX;
+pub use sprite_picking::ClickButton;
 
 /// The plugin that manages user interface interactions.
 pub struct UxPlugin;
 impl Plugin for UxPlugin {
     fn build(&self, app_: &mut App) {
         app_.add_plugins((
+            accessibility::AccessibilitySettingsPlugin,
+            asset_watch::AssetWatchPlugin,
+            atmosphere::AtmospherePlugin,
             diagnostics::DiagnosticsOverlayPlugin,
+            budgets::PerformanceBudgetPlugin,
             camera::CameraPlugin,
+            chat::ChatPlugin,
+            chunk_debug::ChunkDebugPlugin,
             AwgenUiPlugin,
             editor::EditorUXPlugin,
+            file_picker::FilePickerPlugin,
+            jobs::JobsPanelPlugin,
+            sprite_picking::SpritePickingPlugin,
         ))
         .add_systems(Update, filedrop::handle_file_drop);
     }
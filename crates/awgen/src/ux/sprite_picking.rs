@@ -0,0 +1,164 @@
+//! This module implements picking of script-spawned sprites with the mouse
+//! cursor.
+//!
+//! Sprites only take part in picking when a script opts them into click or
+//! hover events. The sprite nearest to the camera under the cursor is picked,
+//! unless it is hidden behind terrain or the cursor is over a UI panel. Clicks
+//! and hover changes are forwarded to the script engine, so that interactive
+//! props can be implemented entirely in scripts.
+
+use bevy::picking::hover::HoverMap;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::map::{Billboard, BlockModel, ChunkTable, VoxelChunk, WorldPos, raycast_blocks};
+use crate::scripts::{PacketOut, ScriptEngine};
+use crate::ux::CameraController;
+use crate::ux::editor::picking::{cursor_ray, pointer_over};
+
+/// The mouse buttons that are reported when a sprite is clicked.
+const BUTTONS: [(MouseButton, ClickButton); 3] = [
+    (MouseButton::Left, ClickButton::Left),
+    (MouseButton::Right, ClickButton::Right),
+    (MouseButton::Middle, ClickButton::Middle),
+];
+
+/// The plugin that forwards sprite clicks and hover changes to the script
+/// engine.
+pub(super) struct SpritePickingPlugin;
+impl Plugin for SpritePickingPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_systems(Update, pick_sprites.run_if(resource_exists::<ScriptEngine>));
+    }
+}
+
+/// A mouse button that clicked a sprite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum ClickButton {
+    /// The left mouse button.
+    Left,
+
+    /// The right mouse button.
+    Right,
+
+    /// The middle mouse button.
+    Middle,
+}
+
+/// This system picks the interactive sprite under the cursor, and sends click
+/// and hover change packets for it to the script engine.
+#[allow(clippy::too_many_arguments)]
+fn pick_sprites(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    hover_map: Res<HoverMap>,
+    parents: Query<&ChildOf>,
+    ui_nodes: Query<(), With<Node>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<CameraController>>,
+    chunk_table: Res<ChunkTable>,
+    chunks: Query<&VoxelChunk>,
+    billboards: Query<(Entity, &Billboard, &GlobalTransform)>,
+    engine: Res<ScriptEngine>,
+    mut hovered: Local<Option<Entity>>,
+) {
+    let is_solid = |pos: WorldPos| {
+        chunk_table
+            .get_chunk(pos.as_chunk_pos())
+            .and_then(|chunk_id| chunks.get(chunk_id).ok())
+            .is_some_and(|chunk| !matches!(chunk.get_models().get(pos), BlockModel::Empty))
+    };
+
+    let picked = cursor_ray(&windows, &cameras)
+        .filter(|_| !pointer_over(&hover_map, &parents, &ui_nodes))
+        .and_then(|ray| {
+            let (entity, billboard, distance) = billboards
+                .iter()
+                .filter(|(_, billboard, _)| billboard.clickable || billboard.hoverable)
+                .filter_map(|(entity, billboard, transform)| {
+                    Some((entity, billboard, ray_hit(ray, transform)?))
+                })
+                .min_by(|a, b| a.2.total_cmp(&b.2))?;
+
+            // The nearest sprite is only hidden if a block is nearer still, in
+            // which case the block also hides every other sprite.
+            if raycast_blocks(ray, distance, &is_solid).is_some() {
+                return None;
+            }
+
+            Some((entity, billboard, ray.get_point(distance)))
+        });
+
+    let hover_target = picked
+        .filter(|(_, billboard, _)| billboard.hoverable)
+        .map(|(entity, _, _)| entity);
+
+    if *hovered != hover_target {
+        for (entity, is_hovered) in [(*hovered, false), (hover_target, true)] {
+            let Some((_, billboard, _)) = entity.and_then(|entity| billboards.get(entity).ok())
+            else {
+                continue;
+            };
+
+            notify_scripts(
+                &engine,
+                PacketOut::EntityHoverChanged {
+                    id: billboard.id().to_string(),
+                    hovered: is_hovered,
+                },
+            );
+        }
+
+        *hovered = hover_target;
+    }
+
+    let Some((_, billboard, hit_pos)) = picked.filter(|(_, billboard, _)| billboard.clickable)
+    else {
+        return;
+    };
+
+    for (mouse_button, button) in BUTTONS {
+        if mouse_buttons.just_pressed(mouse_button) {
+            notify_scripts(
+                &engine,
+                PacketOut::EntityClicked {
+                    id: billboard.id().to_string(),
+                    button,
+                    hit_pos: hit_pos.to_array(),
+                },
+            );
+        }
+    }
+}
+
+/// Gets the distance along the ray to the point where it passes through the
+/// quad of a billboard with the given transform, if it does.
+///
+/// The quad spans from -0.5 to 0.5 along its local X axis, and from 0 to 1
+/// along its local Y axis, as it is anchored at its bottom center.
+fn ray_hit(ray: Ray3d, transform: &GlobalTransform) -> Option<f32> {
+    let inverse = transform.affine().inverse();
+    let origin = inverse.transform_point3(ray.origin);
+    let dir = inverse.transform_vector3(*ray.direction);
+
+    if dir.z.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let distance = -origin.z / dir.z;
+    let hit = origin + dir * distance;
+    let inside = hit.x.abs() <= 0.5 && (0.0 ..= 1.0).contains(&hit.y);
+    (distance >= 0.0 && inside).then_some(distance)
+}
+
+/// Sends a packet to the script engine.
+fn notify_scripts(engine: &ScriptEngine, packet: PacketOut) {
+    if let Err(err) = engine.send(packet) {
+        error!(
+            "Failed to send sprite interaction to the script engine: {}",
+            err
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! This module persists the geometry of the game window, so that it reopens
+//! with the same size, position, and mode that it had when it was last
+//! closed.
+//!
+//! The window state is stored in the settings table of the game database, so
+//! each project remembers its own window layout.
+
+use bevy::app::AppExit;
+use bevy::ecs::system::NonSendMarker;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowMode};
+use bevy::winit::WINIT_WINDOWS;
+use serde::{Deserialize, Serialize};
+
+use crate::database::{Database, DatabaseError, GameDatabase};
+
+/// The settings key for the window state.
+const WINDOW_STATE_KEY: &str = "window_state";
+
+/// Plugin that restores the window state on startup and stores it on exit.
+pub struct WindowStatePlugin;
+impl Plugin for WindowStatePlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<WindowState>()
+            .add_systems(Startup, restore_window_state)
+            .add_systems(Last, (track_window_state, save_window_state).chain());
+    }
+}
+
+/// A resource that holds the last known state of the primary window.
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowState {
+    /// The logical width of the window, while it is not maximized.
+    pub width: f32,
+
+    /// The logical height of the window, while it is not maximized.
+    pub height: f32,
+
+    /// The physical position of the top-left corner of the window, while it
+    /// is not maximized, if it is known.
+    pub position: Option<IVec2>,
+
+    /// Whether the window is maximized.
+    pub maximized: bool,
+
+    /// Whether the window is fullscreen.
+    pub fullscreen: bool,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        let resolution = Window::default().resolution;
+        Self {
+            width: resolution.width(),
+            height: resolution.height(),
+            position: None,
+            maximized: false,
+            fullscreen: false,
+        }
+    }
+}
+
+impl WindowState {
+    /// Loads the window state from the game database, if it has been stored.
+    pub fn load(database: &Database) -> Result<Option<Self>, DatabaseError> {
+        let Some(value) = database.get_setting(WINDOW_STATE_KEY)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(serde_json::from_str(&value)?))
+    }
+
+    /// Stores the window state in the game database.
+    pub fn save(&self, database: &Database) -> Result<(), DatabaseError> {
+        database.set_setting(WINDOW_STATE_KEY, &serde_json::to_string(self)?)
+    }
+}
+
+/// Applies the stored window state to the primary window.
+fn restore_window_state(
+    database: Res<GameDatabase>,
+    mut state: ResMut<WindowState>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = windows.single_mut() else {
+        return;
+    };
+
+    let stored = match WindowState::load(&database) {
+        Ok(Some(stored)) => stored,
+        Ok(None) => return,
+        Err(err) => {
+            warn!("Failed to load the window state: {}", err);
+            return;
+        }
+    };
+
+    window.resolution.set(stored.width, stored.height);
+    if let Some(position) = stored.position {
+        window.position = WindowPosition::At(position);
+    }
+
+    if stored.maximized {
+        window.set_maximized(true);
+    }
+
+    // Fullscreen from the command line takes priority over the stored state.
+    if stored.fullscreen && window.mode == WindowMode::Windowed {
+        window.mode =
+            WindowMode::Fullscreen(MonitorSelection::Current, VideoModeSelection::Current);
+    }
+
+    *state = stored;
+}
+
+/// Records the state of the primary window whenever it changes.
+fn track_window_state(
+    mut state: ResMut<WindowState>,
+    windows: Query<(Entity, &Window), (With<PrimaryWindow>, Changed<Window>)>,
+    _main_thread: NonSendMarker,
+) {
+    let Ok((entity, window)) = windows.single() else {
+        return;
+    };
+
+    let (maximized, minimized) = WINIT_WINDOWS.with_borrow(|winit_windows| {
+        winit_windows
+            .get_window(entity)
+            .map(|winit_window| {
+                let minimized = winit_window.is_minimized().unwrap_or(false);
+                (winit_window.is_maximized(), minimized)
+            })
+            .unwrap_or_default()
+    });
+
+    if minimized {
+        return;
+    }
+
+    state.maximized = maximized;
+    state.fullscreen = window.mode != WindowMode::Windowed;
+
+    // The windowed geometry is kept while maximized or fullscreen, so the
+    // window returns to it once restored.
+    if !maximized && !state.fullscreen {
+        state.width = window.resolution.width();
+        state.height = window.resolution.height();
+        if let WindowPosition::At(position) = window.position {
+            state.position = Some(position);
+        }
+    }
+}
+
+/// Stores the window state in the game database when the app exits.
+fn save_window_state(
+    mut exit: MessageReader<AppExit>,
+    state: Res<WindowState>,
+    database: Res<GameDatabase>,
+) {
+    if exit.is_empty() {
+        return;
+    }
+    exit.clear();
+
+    if database.is_read_only() {
+        return;
+    }
+
+    if let Err(err) = state.save(&database) {
+        error!("Failed to store the window state: {}", err);
+    }
+}
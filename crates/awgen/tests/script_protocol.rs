@@ -0,0 +1,939 @@
+//! Integration tests for the script protocol.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use awgen::database::areas::Area;
+use awgen::database::block_models::{BlockModelAssets, NamedBlockModel};
+use awgen::database::bookmarks::Bookmark;
+use awgen::database::lights::{LightKind, PlacedLight};
+use awgen::database::telemetry::UsageCount;
+use awgen::database::{Database, GameDatabase, ProjectAssets};
+use awgen::framepace::FramePacing;
+use awgen::map::{
+    AreaIndex,
+    BlockFace,
+    BlockModel,
+    BlockRegion,
+    ChunkPos,
+    ChunkTable,
+    Cube,
+    LightTable,
+    MAX_AREA_CHUNKS,
+    MAX_RAYCAST_DISTANCE,
+    MapPatch,
+    MapSettings,
+    MapSnapshot,
+    MapSnapshots,
+    SceneLight,
+    VoxelChunk,
+    WorldPos,
+    chunk_count,
+    get_block,
+    raycast_blocks,
+};
+use awgen::scripts::harness::ScriptHarness;
+use awgen::scripts::{
+    PacketIn,
+    PacketOut,
+    ScriptEnginePlugin,
+    ScriptLimit,
+    ScriptLimits,
+    ScriptSockets,
+    StampedPacket,
+};
+use awgen::shutdown::UnsavedChanges;
+use awgen::telemetry::{PACKETS_PROCESSED, UsageCounters};
+use awgen::ux::{AtmosphereSettings, PerformanceBudgets};
+use awgen_asset_db::param::AwgenAssets;
+use awgen_ui::widgets::command_palette::CommandRegistry;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+
+/// A script that initializes the game, then answers each file drop with a
+/// block placed at the origin.
+const ECHO_SCRIPT: &str = r#"
+export async function main() {
+  // @ts-ignore
+  const fns = rustyscript.functions;
+  // @ts-ignore
+  const ops = Deno.core.ops;
+
+  fns["setSetting"]("game_name", "Harness");
+  fns["sendPackets"]({ type: "init", name: "Harness", version: "1.0.0" });
+
+  while (true) {
+    const packet = await ops.op_fetch_packet();
+    if (packet.type === "shutdown") {
+      break;
+    }
+
+    if (packet.type === "fileDrop") {
+      fns["sendPackets"]({
+        type: "setBlock",
+        pos: [1, 2, 3],
+        model: { type: "cube" },
+      });
+    }
+  }
+}
+"#;
+
+/// A script that initializes the game, then never yields back to the engine.
+const RUNAWAY_SCRIPT: &str = r#"
+export async function main() {
+  // @ts-ignore
+  const fns = rustyscript.functions;
+
+  fns["sendPackets"]({ type: "init", name: "Runaway", version: "1.0.0" });
+
+  while (true) {}
+}
+"#;
+
+/// A script that negotiates the MessagePack encoding and runs the codec tests
+/// of the scripting API, then echoes the path of each dropped file back as a
+/// console message.
+const FRAME_SCRIPT: &str = r#"
+import { testCodec } from "./API/Packets/Codec.test.ts";
+import { fetchPacket, sendPackets } from "./API/Packets/Sockets.ts";
+
+export async function main() {
+  sendPackets({
+    type: "init",
+    name: "Frames",
+    version: "1.0.0",
+    encoding: "messagePack",
+  });
+
+  let result = "ok";
+  try {
+    testCodec();
+  } catch (error) {
+    result = String(error);
+  }
+  sendPackets({ type: "consolePrint", text: result });
+
+  while (true) {
+    const packet: any = await fetchPacket();
+    if (packet.type === "shutdown") {
+      break;
+    }
+
+    if (packet.type === "fileDrop") {
+      sendPackets({ type: "consolePrint", text: packet.path });
+    }
+  }
+}
+"#;
+
+#[test]
+fn init_packet_is_sent_first() {
+    let harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    let (name, version) = harness.expect_init().unwrap();
+
+    assert_eq!(name, "Harness");
+    assert_eq!(version, "1.0.0");
+    assert_eq!(
+        harness.database().get_setting("game_name").unwrap(),
+        Some("Harness".to_string())
+    );
+}
+
+#[test]
+fn file_drop_places_block() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    harness
+        .send(PacketOut::FileDrop {
+            path: "image.png".to_string(),
+        })
+        .unwrap();
+
+    let packet = harness.recv_and_apply().unwrap();
+    assert!(matches!(packet, PacketIn::SetBlock { .. }));
+
+    let pos = WorldPos::new(1, 2, 3);
+    let world = harness.world();
+    let chunk_id = world
+        .resource::<ChunkTable>()
+        .get_chunk(pos.as_chunk_pos())
+        .unwrap();
+    let chunk = world.get::<VoxelChunk>(chunk_id).unwrap();
+    assert!(matches!(chunk.get_models().get(pos), BlockModel::Cube(_)));
+}
+
+#[test]
+fn large_packets_round_trip_as_frames() {
+    let harness = ScriptHarness::new(FRAME_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    match harness.recv().unwrap() {
+        PacketIn::ConsolePrint { text } => assert_eq!(text, "ok"),
+        packet => panic!("expected a console message, got {packet:?}"),
+    }
+
+    // Small paths stay JSON, large paths are framed, and huge paths are
+    // compressed, in both directions.
+    let paths = [
+        "image.png".to_string(),
+        "tiles/".repeat(1000) + "grass.png",
+        "tiles/".repeat(10_000) + "\u{1F600} ünïcödé.png",
+    ];
+    for path in paths {
+        harness
+            .send(PacketOut::FileDrop { path: path.clone() })
+            .unwrap();
+
+        match harness.recv().unwrap() {
+            PacketIn::ConsolePrint { text } => assert_eq!(text, path),
+            packet => panic!("expected a console message, got {packet:?}"),
+        }
+    }
+}
+
+#[test]
+fn runaway_script_exceeds_time_limit() {
+    let limits = ScriptLimits {
+        callback_budget: Some(Duration::from_millis(100)),
+        ..Default::default()
+    };
+
+    let harness = ScriptHarness::new_with_limits(RUNAWAY_SCRIPT, limits).unwrap();
+    harness.expect_init().unwrap();
+
+    let packet = harness.recv().unwrap();
+    assert!(matches!(
+        packet,
+        PacketIn::LimitExceeded {
+            limit: ScriptLimit::Time,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn snapshot_diff_round_trips_through_patch() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    harness.apply(PacketIn::TakeSnapshot {
+        name: "before".to_string(),
+        region: None,
+    });
+    harness.apply(PacketIn::SetBlock {
+        pos: WorldPos::new(20, 0, -3),
+        model: Box::new(BlockModel::Cube(Cube::default())),
+    });
+
+    let world = harness.world();
+    let before = world.resource::<MapSnapshots>().get("before").unwrap();
+    let patch = before.diff(&MapSnapshot::capture(world, None));
+
+    assert_eq!(patch.changes.len(), 1);
+    assert_eq!(patch.changes[0].pos, WorldPos::new(20, 0, -3));
+
+    let mut other = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    other.expect_init().unwrap();
+    patch.apply(other.world_mut());
+
+    let patched = MapSnapshot::capture(other.world(), None);
+    assert!(
+        MapSnapshot::capture(harness.world(), None)
+            .diff(&patched)
+            .is_empty()
+    );
+}
+
+#[test]
+fn snapshot_diff_distinguishes_absent_and_empty_chunks() {
+    let mut empty = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    empty.expect_init().unwrap();
+
+    // Placing and clearing a block leaves an empty chunk behind.
+    let mut cleared = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    cleared.expect_init().unwrap();
+    let pos = WorldPos::new(4, 5, 6);
+    cleared.apply(PacketIn::SetBlock {
+        pos,
+        model: Box::new(BlockModel::Cube(Cube::default())),
+    });
+    cleared.apply(PacketIn::SetBlock {
+        pos,
+        model: Box::new(BlockModel::Empty),
+    });
+
+    let absent = MapSnapshot::capture(empty.world(), None);
+    let present = MapSnapshot::capture(cleared.world(), None);
+
+    let added = absent.diff(&present);
+    assert_eq!(added.added_chunks, vec![pos.as_chunk_pos()]);
+    assert!(added.removed_chunks.is_empty());
+    assert!(added.changes.is_empty());
+
+    let removed = present.diff(&absent);
+    assert_eq!(removed.removed_chunks, vec![pos.as_chunk_pos()]);
+    assert!(removed.added_chunks.is_empty());
+    assert!(removed.changes.is_empty());
+
+    added.apply(empty.world_mut());
+    assert_eq!(empty.world().resource::<ChunkTable>().len(), 1);
+    assert!(
+        MapSnapshot::capture(empty.world(), None)
+            .diff(&present)
+            .is_empty()
+    );
+
+    removed.apply(cleared.world_mut());
+    assert_eq!(cleared.world().resource::<ChunkTable>().len(), 0);
+    assert!(
+        MapSnapshot::capture(cleared.world(), None)
+            .diff(&absent)
+            .is_empty()
+    );
+}
+
+#[test]
+fn loading_a_save_replaces_the_chunk_table() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    let saved = WorldPos::new(1, 2, 3);
+    let unsaved = WorldPos::new(100, 2, 3);
+    let cube = || Box::new(BlockModel::Cube(Cube::default()));
+
+    harness.apply(PacketIn::SetBlock {
+        pos: saved,
+        model: cube(),
+    });
+    harness.apply(PacketIn::CreateSave {
+        slot: "table".to_string(),
+        data: serde_json::Value::Null,
+    });
+    harness.apply(PacketIn::SetBlock {
+        pos: unsaved,
+        model: cube(),
+    });
+    harness.apply(PacketIn::LoadSave {
+        slot: "table".to_string(),
+    });
+
+    let world = harness.world_mut();
+    let chunks = world
+        .query_filtered::<Entity, With<VoxelChunk>>()
+        .iter(world)
+        .count();
+    let table = world.resource::<ChunkTable>();
+    assert_eq!(table.len(), chunks);
+    assert_eq!(table.get_chunk(unsaved.as_chunk_pos()), None);
+
+    let chunk_id = table.get_chunk(saved.as_chunk_pos()).unwrap();
+    assert!(world.get::<VoxelChunk>(chunk_id).is_some());
+}
+
+#[test]
+fn height_bounds_apply_to_loaded_saves_and_patches() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    let inside = WorldPos::new(1, 2, 3);
+    let clipped = WorldPos::new(1, 10, 3);
+    let outside = WorldPos::new(1, 40, 3);
+    for pos in [inside, clipped, outside] {
+        harness.apply(PacketIn::SetBlock {
+            pos,
+            model: Box::new(BlockModel::Cube(Cube::default())),
+        });
+    }
+    harness.apply(PacketIn::CreateSave {
+        slot: "bounds".to_string(),
+        data: serde_json::Value::Null,
+    });
+
+    harness.world_mut().insert_resource(MapSettings {
+        max_height: 8,
+        ..default()
+    });
+    harness.apply(PacketIn::LoadSave {
+        slot: "bounds".to_string(),
+    });
+
+    let world = harness.world();
+    assert!(matches!(
+        get_block(world, inside),
+        Some(BlockModel::Cube(_))
+    ));
+    assert!(matches!(get_block(world, clipped), Some(BlockModel::Empty)));
+    assert_eq!(
+        world
+            .resource::<ChunkTable>()
+            .get_chunk(outside.as_chunk_pos()),
+        None
+    );
+
+    let patch = MapPatch {
+        added_chunks: vec![outside.as_chunk_pos()],
+        ..default()
+    };
+    patch.apply(harness.world_mut());
+    assert_eq!(
+        harness
+            .world()
+            .resource::<ChunkTable>()
+            .get_chunk(outside.as_chunk_pos()),
+        None
+    );
+}
+
+#[test]
+fn bookmark_packets_update_database() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    let bookmark = Bookmark {
+        name: "Spawn".to_string(),
+        pos: WorldPos::new(4, 8, -15),
+        pitch: 45.0,
+        yaw: 135.0,
+        distance: 32.0,
+    };
+
+    harness.apply(PacketIn::SetBookmark {
+        bookmark: bookmark.clone(),
+    });
+    assert_eq!(
+        harness.database().get_bookmark("Spawn").unwrap(),
+        Some(bookmark)
+    );
+
+    harness.apply(PacketIn::DeleteBookmark {
+        name: "Spawn".to_string(),
+    });
+    assert!(harness.database().list_bookmarks().unwrap().is_empty());
+}
+
+#[test]
+fn stored_block_models_are_placed_by_name() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    let model = NamedBlockModel {
+        name: "Stone".to_string(),
+        model: BlockModel::Cube(Cube::default()),
+    };
+    let stored = harness
+        .world_mut()
+        .run_system_once(move |mut assets: AwgenAssets<ProjectAssets>| {
+            assets.set_block_model(&model).unwrap();
+            assets.get_block_model("Stone").unwrap()
+        })
+        .unwrap();
+    assert_eq!(
+        stored.map(|stored| stored.model),
+        Some(BlockModel::Cube(Cube::default()))
+    );
+
+    let pos = WorldPos::new(-3, 5, 7);
+    harness.apply(PacketIn::PlaceBlockModel {
+        pos,
+        name: "Stone".to_string(),
+    });
+
+    let missing = WorldPos::new(64, 0, 64);
+    harness.apply(PacketIn::PlaceBlockModel {
+        pos: missing,
+        name: "Missing".to_string(),
+    });
+
+    let world = harness.world();
+    let chunk_id = world
+        .resource::<ChunkTable>()
+        .get_chunk(pos.as_chunk_pos())
+        .unwrap();
+    let chunk = world.get::<VoxelChunk>(chunk_id).unwrap();
+    assert!(matches!(chunk.get_models().get(pos), BlockModel::Cube(_)));
+
+    let chunk_table = world.resource::<ChunkTable>();
+    assert!(chunk_table.get_chunk(missing.as_chunk_pos()).is_none());
+}
+
+#[test]
+fn frame_rate_limits_persist() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    harness.apply(PacketIn::SetFrameRate {
+        target_fps: Some(30.0),
+        unfocused_fps: Some(5.0),
+    });
+
+    let pacing = *harness.world().resource::<FramePacing>();
+    assert_eq!(pacing.target_fps, Some(30.0));
+    assert_eq!(pacing.unfocused_fps, 5.0);
+    assert_eq!(FramePacing::load(harness.database()).unwrap(), pacing);
+}
+
+#[test]
+fn performance_budgets_persist_and_reject_invalid_budgets() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    let budgets = PerformanceBudgets {
+        frame_time: 16.7,
+        remesh_time: 4.0,
+        packet_queue_depth: 64.0,
+        frames: 10,
+    };
+    harness.apply(PacketIn::SetPerformanceBudgets { budgets });
+    assert_eq!(*harness.world().resource::<PerformanceBudgets>(), budgets);
+    assert_eq!(
+        PerformanceBudgets::load(harness.database()).unwrap(),
+        budgets
+    );
+
+    for invalid in [
+        PerformanceBudgets {
+            frame_time: -1.0,
+            ..budgets
+        },
+        PerformanceBudgets {
+            remesh_time: f64::NAN,
+            ..budgets
+        },
+        PerformanceBudgets {
+            frames: 0,
+            ..budgets
+        },
+    ] {
+        harness.apply(PacketIn::SetPerformanceBudgets { budgets: invalid });
+    }
+    assert_eq!(*harness.world().resource::<PerformanceBudgets>(), budgets);
+    assert_eq!(
+        PerformanceBudgets::load(harness.database()).unwrap(),
+        budgets
+    );
+}
+
+#[test]
+fn atmosphere_applies_without_persisting_and_rejects_invalid_fog() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    let atmosphere = AtmosphereSettings {
+        fog_enabled: true,
+        fog_color: [0.6, 0.7, 0.9],
+        fog_start: 32.0,
+        fog_end: 96.0,
+        sky_top: [0.2, 0.4, 0.8],
+        sky_bottom: [0.6, 0.7, 0.9],
+    };
+    harness.apply(PacketIn::SetAtmosphere { atmosphere });
+
+    assert_eq!(
+        *harness.world().resource::<AtmosphereSettings>(),
+        atmosphere
+    );
+    assert_eq!(
+        AtmosphereSettings::load(harness.database()).unwrap(),
+        AtmosphereSettings::default()
+    );
+
+    harness.apply(PacketIn::SetAtmosphere {
+        atmosphere: AtmosphereSettings {
+            fog_start: 96.0,
+            fog_end: 32.0,
+            ..atmosphere
+        },
+    });
+
+    assert_eq!(
+        *harness.world().resource::<AtmosphereSettings>(),
+        atmosphere
+    );
+}
+
+#[test]
+fn areas_are_indexed_by_position() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    harness.apply(PacketIn::SetArea {
+        area: Area {
+            name: "town".to_string(),
+            region: BlockRegion::new(WorldPos::new(40, 0, 40), WorldPos::new(-10, 8, 0)),
+            tags: vec!["town".to_string()],
+        },
+    });
+
+    let index = harness.world().resource::<AreaIndex>();
+    assert_eq!(index.areas_at(WorldPos::new(-10, 0, 20)).len(), 1);
+    assert_eq!(index.areas_at(WorldPos::new(40, 8, 40)).len(), 1);
+    assert!(index.areas_at(WorldPos::new(41, 8, 40)).is_empty());
+    assert_eq!(harness.database().list_areas().unwrap().len(), 1);
+
+    harness.apply(PacketIn::DeleteArea {
+        name: "town".to_string(),
+    });
+    let index = harness.world().resource::<AreaIndex>();
+    assert!(index.areas_at(WorldPos::new(0, 0, 0)).is_empty());
+}
+
+#[test]
+fn huge_areas_are_rejected() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    let area = Area {
+        name: "everything".to_string(),
+        region: BlockRegion::new(
+            WorldPos::new(i32::MIN, i32::MIN, i32::MIN),
+            WorldPos::new(i32::MAX, i32::MAX, i32::MAX),
+        ),
+        tags: Vec::new(),
+    };
+    assert!(chunk_count(area.region) > MAX_AREA_CHUNKS);
+    assert!(AreaIndex::default().insert(area.clone()).is_err());
+
+    harness.apply(PacketIn::SetArea { area });
+    assert!(harness.world().resource::<AreaIndex>().is_empty());
+    assert!(harness.database().list_areas().unwrap().is_empty());
+}
+
+#[test]
+fn light_packets_toggle_scene_lights_only() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    let light = PlacedLight::new("Torch", LightKind::Spot, [1.5, 4.5, -2.5]);
+    harness.database().set_light(&light).unwrap();
+    harness.world_mut().spawn(SceneLight(light.clone()));
+
+    harness.apply(PacketIn::SetLightEnabled {
+        name: "Torch".to_string(),
+        enabled: false,
+    });
+    let world = harness.world();
+    let entity = world.resource::<LightTable>().get("Torch").unwrap();
+    assert!(!world.get::<SceneLight>(entity).unwrap().0.enabled);
+
+    // Scripts only switch the light in the running scene.
+    assert_eq!(harness.database().list_lights().unwrap(), vec![light]);
+
+    harness.apply(PacketIn::SetLightEnabled {
+        name: "Missing".to_string(),
+        enabled: true,
+    });
+    assert_eq!(harness.world().resource::<LightTable>().len(), 1);
+}
+
+#[test]
+fn command_packets_update_the_command_palette() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    harness.apply(PacketIn::RegisterCommand {
+        id: "build".to_string(),
+        name: "Build Project".to_string(),
+        shortcut: Some("Ctrl+B".to_string()),
+    });
+    let registry = harness.world().resource::<CommandRegistry>();
+    let command = registry.get("script.build").unwrap();
+    assert_eq!(command.name, "Build Project");
+    assert_eq!(command.shortcut.as_deref(), Some("Ctrl+B"));
+    assert_eq!(registry.search("bld")[0].id, "script.build");
+
+    harness.apply(PacketIn::UnregisterCommand {
+        id: "build".to_string(),
+    });
+    let registry = harness.world().resource::<CommandRegistry>();
+    assert!(registry.get("script.build").is_none());
+
+    // Reloading the scripts removes the commands of the previous scripts.
+    harness.apply(PacketIn::RegisterCommand {
+        id: "test".to_string(),
+        name: "Run Tests".to_string(),
+        shortcut: None,
+    });
+    harness.apply(PacketIn::Init {
+        name: "Reloaded".to_string(),
+        version: "1.0.0".to_string(),
+        encoding: None,
+    });
+    let registry = harness.world().resource::<CommandRegistry>();
+    assert!(registry.get("script.test").is_none());
+}
+
+#[test]
+fn usage_counters_are_opt_in_and_stored_locally() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+
+    // Nothing is counted until the user opts in.
+    harness.apply(PacketIn::ListLights);
+    let counters = harness.world().resource::<UsageCounters>();
+    assert!(counters.session_counts().is_empty());
+
+    harness
+        .world_mut()
+        .resource_scope(|world, mut counters: Mut<UsageCounters>| {
+            let database = world.resource::<GameDatabase>();
+            counters.set_enabled(true, database).unwrap();
+        });
+    harness.apply(PacketIn::Set {
+        packets: vec![PacketIn::ListLights, PacketIn::ListLights],
+    });
+
+    harness
+        .world_mut()
+        .resource_scope(|world, mut counters: Mut<UsageCounters>| {
+            let database = world.resource::<GameDatabase>();
+            counters.flush(database).unwrap();
+        });
+
+    let expected = vec![UsageCount {
+        name: PACKETS_PROCESSED.to_string(),
+        count: 2,
+    }];
+    assert!(harness.database().usage_counters_enabled().unwrap());
+    assert_eq!(harness.database().usage_session_count().unwrap(), 1);
+    assert_eq!(
+        harness.database().list_usage_counts(None).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn usage_counts_are_kept_until_they_can_be_stored() {
+    let folder = std::env::temp_dir().join(format!("awgen_usage_{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    let database = Database::new(&folder).unwrap();
+    let read_only = Database::new_read_only(&folder).unwrap();
+
+    let mut counters = UsageCounters::default();
+    counters.set_enabled(true, &database).unwrap();
+    counters.increment(PACKETS_PROCESSED);
+
+    // A read-only database cannot store the counts, so they stay pending.
+    counters.flush(&read_only).unwrap();
+    assert_eq!(database.usage_session_count().unwrap(), 0);
+
+    counters.flush(&database).unwrap();
+    counters.flush(&database).unwrap();
+    let expected = vec![UsageCount {
+        name: PACKETS_PROCESSED.to_string(),
+        count: 1,
+    }];
+    assert_eq!(database.list_usage_counts(None).unwrap(), expected);
+
+    drop(read_only);
+    drop(database);
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn fake_engine_drives_packet_systems() {
+    let (sockets, engine) = ScriptSockets::fake();
+    let database = Arc::new(Database::in_memory().unwrap());
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ScriptEnginePlugin::new(sockets)))
+        .insert_resource(GameDatabase(database.clone()))
+        .init_resource::<AreaIndex>();
+
+    engine
+        .send(PacketIn::SetArea {
+            area: Area {
+                name: "dungeon".to_string(),
+                region: BlockRegion::new(WorldPos::new(0, 0, 0), WorldPos::new(3, 3, 3)),
+                tags: vec!["dungeon_1".to_string()],
+            },
+        })
+        .unwrap();
+    app.update();
+
+    let index = app.world().resource::<AreaIndex>();
+    assert_eq!(index.areas_at(WorldPos::new(2, 2, 2)).len(), 1);
+    assert_eq!(database.list_areas().unwrap().len(), 1);
+
+    let replies = engine.drain();
+    assert!(matches!(replies.as_slice(), [PacketOut::AreaList { areas }] if areas.len() == 1));
+}
+
+#[test]
+fn packets_are_stamped_with_the_active_tick() {
+    let (sockets, engine) = ScriptSockets::fake();
+    let database = Arc::new(Database::in_memory().unwrap());
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        ScriptEnginePlugin::new(sockets).with_fixed_tick(20.0),
+    ))
+    .insert_resource(GameDatabase(database))
+    .init_resource::<AreaIndex>();
+
+    app.world_mut().run_schedule(FixedUpdate);
+    let stamped = engine.drain_stamped();
+    assert!(matches!(
+        stamped.as_slice(),
+        [StampedPacket {
+            tick: None,
+            packet: PacketOut::Tick { tick: 1 }
+        }]
+    ));
+
+    engine.send(PacketIn::ListAreas).unwrap();
+    engine.send(PacketIn::TickDone { tick: 1 }).unwrap();
+    app.world_mut().run_schedule(FixedUpdate);
+
+    let stamped = engine.drain_stamped();
+    assert!(matches!(
+        stamped.as_slice(),
+        [
+            StampedPacket {
+                tick: Some(1),
+                packet: PacketOut::AreaList { .. }
+            },
+            StampedPacket {
+                tick: None,
+                packet: PacketOut::Tick { tick: 2 }
+            },
+        ]
+    ));
+
+    let json = serde_json::to_value(&stamped[0]).unwrap();
+    assert_eq!(json["type"], "areaList");
+    assert_eq!(json["tick"], 1);
+}
+
+#[test]
+fn block_queries_reply_with_map_state() {
+    let (sockets, engine) = ScriptSockets::fake();
+    let database = Arc::new(Database::in_memory().unwrap());
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ScriptEnginePlugin::new(sockets)))
+        .insert_resource(GameDatabase(database))
+        .init_resource::<ChunkTable>();
+
+    let pos = WorldPos::new(1, 2, 3);
+    for packet in [
+        PacketIn::SetBlock {
+            pos,
+            model: Box::new(BlockModel::Cube(Cube::default())),
+        },
+        PacketIn::GetBlock { pos },
+        PacketIn::RaycastBlocks {
+            origin: [1.5, 2.5, -2.0],
+            dir: [0.0, 0.0, 1.0],
+            max_dist: 10.0,
+        },
+        PacketIn::GetChunkSummary {
+            pos: pos.as_chunk_pos(),
+        },
+        PacketIn::GetChunkSummary {
+            pos: ChunkPos::new(5, 5, 5),
+        },
+    ] {
+        engine.send(packet).unwrap();
+    }
+    app.update();
+
+    let replies = engine.drain();
+    let [block, raycast, summary, missing] = replies.as_slice() else {
+        panic!("unexpected replies: {replies:?}");
+    };
+
+    let PacketOut::Block { model, .. } = block else {
+        panic!("expected a block, got {block:?}");
+    };
+    assert!(matches!(**model, BlockModel::Cube(_)));
+
+    let PacketOut::RaycastResult { hit: Some(hit), .. } = raycast else {
+        panic!("expected a raycast hit, got {raycast:?}");
+    };
+    assert_eq!(hit.pos, pos);
+    assert_eq!(hit.face, BlockFace::NegZ);
+    assert!((hit.distance - 5.0).abs() < 1e-4);
+
+    assert!(matches!(
+        summary,
+        PacketOut::ChunkSummary {
+            loaded: true,
+            solid_blocks: 1,
+            ..
+        }
+    ));
+    assert!(matches!(
+        missing,
+        PacketOut::ChunkSummary {
+            loaded: false,
+            solid_blocks: 0,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn raycasts_reject_distances_that_would_never_end() {
+    let (sockets, engine) = ScriptSockets::fake();
+    let database = Arc::new(Database::in_memory().unwrap());
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ScriptEnginePlugin::new(sockets)))
+        .insert_resource(GameDatabase(database))
+        .init_resource::<ChunkTable>();
+
+    for max_dist in [1.0e8, f32::INFINITY, f32::NAN, -1.0] {
+        engine
+            .send(PacketIn::RaycastBlocks {
+                origin: [0.5, 0.5, 0.5],
+                dir: [1.0, 0.0, 0.0],
+                max_dist,
+            })
+            .unwrap();
+    }
+    app.update();
+
+    let replies = engine.drain();
+    assert_eq!(replies.len(), 4);
+    assert!(
+        replies
+            .iter()
+            .all(|reply| matches!(reply, PacketOut::RaycastResult { hit: None, .. }))
+    );
+
+    let ray = Ray3d::new(Vec3::splat(0.5), Dir3::X);
+    assert_eq!(raycast_blocks(ray, 1.0e8, |_| false), None);
+    let beyond_limit = |pos: WorldPos| pos.x as f32 > MAX_RAYCAST_DISTANCE;
+    assert_eq!(raycast_blocks(ray, f32::INFINITY, beyond_limit), None);
+}
+
+#[test]
+fn saves_and_loads_set_the_active_slot() {
+    let mut harness = ScriptHarness::new(ECHO_SCRIPT).unwrap();
+    harness.expect_init().unwrap();
+    assert_eq!(harness.world().resource::<UnsavedChanges>().slot(), None);
+
+    harness.apply(PacketIn::CreateSave {
+        slot: "first".to_string(),
+        data: serde_json::Value::Null,
+    });
+    harness.apply(PacketIn::CreateSave {
+        slot: "second".to_string(),
+        data: serde_json::Value::Null,
+    });
+    assert_eq!(
+        harness.world().resource::<UnsavedChanges>().slot(),
+        Some("second")
+    );
+
+    harness.apply(PacketIn::LoadSave {
+        slot: "first".to_string(),
+    });
+    assert_eq!(
+        harness.world().resource::<UnsavedChanges>().slot(),
+        Some("first")
+    );
+}
@@ -0,0 +1,21 @@
+import { BlockRegion } from "./MapPatch.ts";
+
+/**
+ * A named, tagged region of the world, stored in the game database.
+ */
+export interface Area {
+  /**
+   * The unique name of the area.
+   */
+  name: string;
+
+  /**
+   * The blocks covered by the area.
+   */
+  region: BlockRegion;
+
+  /**
+   * The tags of the area, such as "town" or "dungeon_1".
+   */
+  tags: string[];
+}
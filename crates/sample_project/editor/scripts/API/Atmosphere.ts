@@ -0,0 +1,38 @@
+import { Vec3 } from "./Units.ts";
+
+/**
+ * The distance fog and the sky gradient of the terrain view. Colors are given
+ * as red, green, and blue components, from 0 to 1.
+ */
+export interface AtmosphereSettings {
+  /**
+   * Whether distance fog is drawn over the terrain.
+   */
+  fogEnabled: boolean;
+
+  /**
+   * The color that distant terrain fades into.
+   */
+  fogColor: Vec3;
+
+  /**
+   * The distance from the camera, in blocks, at which the fog starts.
+   */
+  fogStart: number;
+
+  /**
+   * The distance from the camera, in blocks, at which the terrain is fully
+   * hidden by the fog.
+   */
+  fogEnd: number;
+
+  /**
+   * The color of the sky at the top of the screen.
+   */
+  skyTop: Vec3;
+
+  /**
+   * The color of the sky at the bottom of the screen.
+   */
+  skyBottom: Vec3;
+}
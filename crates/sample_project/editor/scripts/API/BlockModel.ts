@@ -96,3 +96,18 @@ export class Cube {
     this.negZ.rotateClockwise();
   }
 }
+
+/**
+ * A block model stored in the project database under a unique name.
+ */
+export interface NamedBlockModel {
+  /**
+   * The unique name of the block model.
+   */
+  name: string;
+
+  /**
+   * The block model.
+   */
+  model: BlockModel;
+}
@@ -0,0 +1,31 @@
+import { WorldPos } from "./Units.ts";
+
+/**
+ * A named camera location in the world, stored in the game database.
+ */
+export interface Bookmark {
+  /**
+   * The unique name of the bookmark.
+   */
+  name: string;
+
+  /**
+   * The block the camera looks at.
+   */
+  pos: WorldPos;
+
+  /**
+   * The camera pitch, in degrees.
+   */
+  pitch: number;
+
+  /**
+   * The camera yaw, in degrees.
+   */
+  yaw: number;
+
+  /**
+   * The distance of the camera from the position it looks at.
+   */
+  distance: number;
+}
@@ -0,0 +1,29 @@
+/**
+ * The thresholds of the engine diagnostics that the editor warns about. A
+ * warning is shown when a diagnostic stays over its budget for a number of
+ * consecutive frames.
+ */
+export interface PerformanceBudgets {
+  /**
+   * The budget for the frame time, in milliseconds.
+   */
+  frameTime: number;
+
+  /**
+   * The budget for the time the main thread spends on chunk meshing each
+   * frame, in milliseconds.
+   */
+  remeshTime: number;
+
+  /**
+   * The budget for the number of script packets waiting to be processed at
+   * the start of a frame.
+   */
+  packetQueueDepth: number;
+
+  /**
+   * The number of consecutive frames that a diagnostic must be over its
+   * budget before a warning is shown.
+   */
+  frames: number;
+}
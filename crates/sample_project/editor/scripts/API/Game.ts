@@ -1,9 +1,28 @@
 import * as PacketToClient from "./Packets/PacketToClient.ts";
+import { FileFilter } from "./Packets/PacketToClient.ts";
+import {
+  AssetChangeKind,
+  ChunkSummary,
+  ClickButton,
+  SaveInfo,
+} from "./Packets/PacketFromClient.ts";
 import { handlePacket } from "./Packets/PacketHandler.ts";
 import { fetchPacket, sendPackets } from "./Packets/Sockets.ts";
+import { PacketEncoding } from "./Packets/Codec.ts";
 import { GameSettings } from "./Settings.ts";
 import { TilesetList } from "./Tilesets.ts";
+import { ChunkPos, Size2, Vec3, WorldPos } from "./Units.ts";
 import { Events } from "./Events.ts";
+import { BlockRegion, MapPatch } from "./MapPatch.ts";
+import { Bookmark } from "./Bookmark.ts";
+import { BlockModel, NamedBlockModel } from "./BlockModel.ts";
+import { Area } from "./Area.ts";
+import { AtmosphereSettings } from "./Atmosphere.ts";
+import { PerformanceBudgets } from "./Budgets.ts";
+import { PlacedLight } from "./Light.ts";
+import { NetMessage } from "./Net.ts";
+import { BlockHit } from "./Raycast.ts";
+import { captureConsole } from "./Log.ts";
 
 /**
  * The key used to store the game name in the settings.
@@ -26,6 +45,7 @@ export class Game {
   private readonly settings: GameSettings;
   private readonly tilesets: TilesetList;
   private running: boolean = true;
+  private currentTick: number = 0;
 
   /**
    * Initializes the game engine. This method should be called once at the start
@@ -33,19 +53,27 @@ export class Game {
    * @param title The title of the game, as it will be displayed in the client.
    * @param version The version of the game, as it will be displayed in the
    * client.
+   * @param encoding The encoding to use for large packets. MessagePack is much
+   * faster for large chunk and asset packets, but JSON is easier to debug.
    * @returns A promise that resolves when the game exits.
    */
-  public static async start(title: string, version: string): Promise<void> {
+  public static async start(
+    title: string,
+    version: string,
+    encoding?: PacketEncoding
+  ): Promise<void> {
     if (Game.instance) {
       console.warn("Cannot initialize the game more than once.");
       return;
     }
-    Game.instance = new Game(title, version);
+    captureConsole();
+    Game.instance = new Game(title, version, encoding);
     await Game.emit("ready");
 
     while (Game.instance.running) {
       try {
         let packet = await fetchPacket();
+        if (packet.tick !== undefined) Game.instance.currentTick = packet.tick;
         await handlePacket(packet);
       } catch (error) {
         console.error(error);
@@ -61,8 +89,13 @@ export class Game {
    * @param title The title of the game, as it will be displayed in the client.
    * @param version The version of the game, as it will be displayed in the
    * client.
+   * @param encoding The encoding to use for large packets.
    */
-  private constructor(title: string, version: string) {
+  private constructor(
+    title: string,
+    version: string,
+    encoding?: PacketEncoding
+  ) {
     //Construct private API helpers
 
     //@ts-expect-error
@@ -74,7 +107,7 @@ export class Game {
     // Init settings and send packet
     this.settings.setSetting(GAME_NAME_KEY, title);
     this.settings.setSetting(GAME_VERSION_KEY, version);
-    sendPackets(new PacketToClient.Init(title, version));
+    sendPackets(new PacketToClient.Init(title, version, encoding));
   }
 
   /**
@@ -135,6 +168,20 @@ export class Game {
     // TODO: Send a packet to the server to update the title in the client.
   }
 
+  /**
+   * Gets the simulation tick of the most recently received packet, in
+   * fixed-tick mode.
+   * @returns The tick number, or 0 if no tick has started yet.
+   * @throws Will throw an error if the game has not been initialized.
+   */
+  public static get tick(): number {
+    if (!Game.instance) {
+      throw new Error("Game has not been started. Call Game.start() first.");
+    }
+
+    return Game.instance.currentTick;
+  }
+
   /**
    * Shuts down the game client. This will send a shutdown packet to the
    * server and stop the game loop.
@@ -204,6 +251,444 @@ export class Game {
     Game.instance.settings.setSetting(key, value);
   }
 
+  /**
+   * Requests a list of all save slots. The result is delivered through the
+   * "saveList" event.
+   */
+  public static listSaves(): void {
+    sendPackets(new PacketToClient.ListSaves());
+  }
+
+  /**
+   * Saves the current map state, along with the given data, to a save slot.
+   * Emits "saveCreated" on success, or "saveFailed" on failure.
+   * @param slot The name of the save slot.
+   * @param data Arbitrary JSON data to store with the save.
+   */
+  public static createSave(slot: string, data: unknown = null): void {
+    sendPackets(new PacketToClient.CreateSave(slot, data));
+  }
+
+  /**
+   * Replaces the current map state with the state stored in a save slot. Emits
+   * "saveLoaded" with the stored data on success, or "saveFailed" on failure.
+   * @param slot The name of the save slot.
+   */
+  public static loadSave(slot: string): void {
+    sendPackets(new PacketToClient.LoadSave(slot));
+  }
+
+  /**
+   * Deletes a save slot. Emits "saveDeleted" on success, or "saveFailed" on
+   * failure.
+   * @param slot The name of the save slot.
+   */
+  public static deleteSave(slot: string): void {
+    sendPackets(new PacketToClient.DeleteSave(slot));
+  }
+
+  /**
+   * Captures a snapshot of the map, which may later be compared against
+   * another snapshot with `Game.diffSnapshots()`. If a snapshot with the same
+   * name already exists, it is replaced. Snapshots are only kept for the
+   * current session.
+   * @param name The name of the snapshot.
+   * @param region The region of the map to capture. If omitted, the whole map
+   * is captured.
+   */
+  public static takeSnapshot(name: string, region?: BlockRegion): void {
+    sendPackets(new PacketToClient.TakeSnapshot(name, region));
+  }
+
+  /**
+   * Removes a stored map snapshot.
+   * @param name The name of the snapshot.
+   */
+  public static dropSnapshot(name: string): void {
+    sendPackets(new PacketToClient.DropSnapshot(name));
+  }
+
+  /**
+   * Compares two map snapshots. Emits "mapDiff" with a patch that transforms
+   * the first snapshot into the second, or "snapshotNotFound" if either
+   * snapshot does not exist.
+   * @param from The name of the snapshot to compare from.
+   * @param to The name of the snapshot to compare to. If omitted, the current
+   * state of the map is used.
+   */
+  public static diffSnapshots(from: string, to?: string): void {
+    sendPackets(new PacketToClient.DiffSnapshots(from, to));
+  }
+
+  /**
+   * Applies a map patch, setting each block it contains.
+   * @param patch The patch to apply.
+   */
+  public static applyPatch(patch: MapPatch): void {
+    sendPackets(new PacketToClient.ApplyPatch(patch));
+  }
+
+  /**
+   * Requests the list of bookmarks stored in the game database. Emits
+   * "bookmarkList" with the stored bookmarks.
+   */
+  public static listBookmarks(): void {
+    sendPackets(new PacketToClient.ListBookmarks());
+  }
+
+  /**
+   * Stores a bookmark in the game database, replacing any existing bookmark
+   * with the same name. Emits "bookmarkList" on success, or "bookmarkFailed"
+   * on failure.
+   * @param bookmark The bookmark to store.
+   */
+  public static setBookmark(bookmark: Bookmark): void {
+    sendPackets(new PacketToClient.SetBookmark(bookmark));
+  }
+
+  /**
+   * Deletes a bookmark from the game database. Emits "bookmarkList" on
+   * success, or "bookmarkFailed" on failure.
+   * @param name The name of the bookmark.
+   */
+  public static deleteBookmark(name: string): void {
+    sendPackets(new PacketToClient.DeleteBookmark(name));
+  }
+
+  /**
+   * Moves the camera to a bookmark stored in the game database. Emits
+   * "bookmarkFailed" if the bookmark does not exist.
+   * @param name The name of the bookmark.
+   */
+  public static jumpToBookmark(name: string): void {
+    sendPackets(new PacketToClient.JumpToBookmark(name));
+  }
+
+  /**
+   * Requests the list of block models stored in the project database. Emits
+   * "blockModelList" with the stored block models.
+   */
+  public static listBlockModels(): void {
+    sendPackets(new PacketToClient.ListBlockModels());
+  }
+
+  /**
+   * Sets the block at the given position to a block model stored in the game
+   * database. Emits "blockModelFailed" if the block model does not exist.
+   * @param pos The position of the block in the game world.
+   * @param name The name of the block model.
+   */
+  public static placeBlockModel(pos: WorldPos, name: string): void {
+    sendPackets(new PacketToClient.PlaceBlockModel(pos, name));
+  }
+
+  /**
+   * Enables or disables camera collision. While enabled, the camera is pulled
+   * in front of terrain that blocks its view.
+   * @param enabled Whether camera collision is enabled.
+   */
+  public static setCameraCollision(enabled: boolean): void {
+    sendPackets(new PacketToClient.SetCameraCollision(enabled));
+  }
+
+  /**
+   * Switches the camera between an orthographic and a perspective projection.
+   * The focus point and framing of the camera are kept the same.
+   * @param projection The projection to use.
+   */
+  public static setCameraProjection(
+    projection: PacketToClient.CameraProjection
+  ): void {
+    sendPackets(new PacketToClient.SetCameraProjection(projection));
+  }
+
+  /**
+   * Replaces the distance fog and the sky gradient of the terrain view until
+   * the game is closed. The settings stored in the project are not changed.
+   * @param atmosphere The new atmosphere settings.
+   */
+  public static setAtmosphere(atmosphere: AtmosphereSettings): void {
+    sendPackets(new PacketToClient.SetAtmosphere(atmosphere));
+  }
+
+  /**
+   * Sets the frame rate limits of the game window. The limits are stored in
+   * the game database, so they persist between launches.
+   * @param targetFps The maximum frame rate while the window is focused. If
+   * omitted, the frame rate is not limited.
+   * @param unfocusedFps The maximum frame rate while the window is unfocused.
+   * If omitted, the current limit is kept.
+   */
+  public static setFrameRate(targetFps?: number, unfocusedFps?: number): void {
+    sendPackets(new PacketToClient.SetFrameRate(targetFps, unfocusedFps));
+  }
+
+  /**
+   * Sets the performance budgets that the editor warns about when they are
+   * exceeded. The budgets are stored in the game database, so they persist
+   * between launches.
+   * @param budgets The new performance budgets.
+   */
+  public static setPerformanceBudgets(budgets: PerformanceBudgets): void {
+    sendPackets(new PacketToClient.SetPerformanceBudgets(budgets));
+  }
+
+  /**
+   * Replaces the engine log filter. The filter uses the same syntax as
+   * `RUST_LOG`, such as "info,script=debug", and persists between launches.
+   * @param filter The filter directives.
+   */
+  public static setLogFilter(filter: string): void {
+    sendPackets(new PacketToClient.SetLogFilter(filter));
+  }
+
+  /**
+   * Requests the list of areas stored in the game database. Emits "areaList"
+   * with the stored areas.
+   */
+  public static listAreas(): void {
+    sendPackets(new PacketToClient.ListAreas());
+  }
+
+  /**
+   * Stores an area in the game database, replacing any existing area with the
+   * same name. Emits "areaList" on success, or "areaFailed" on failure.
+   * @param area The area to store.
+   */
+  public static setArea(area: Area): void {
+    sendPackets(new PacketToClient.SetArea(area));
+  }
+
+  /**
+   * Deletes an area from the game database. Emits "areaList" on success, or
+   * "areaFailed" on failure.
+   * @param name The name of the area.
+   */
+  public static deleteArea(name: string): void {
+    sendPackets(new PacketToClient.DeleteArea(name));
+  }
+
+  /**
+   * Requests the areas that contain a block. Emits "areasAt" with the areas,
+   * sorted by name.
+   * @param pos The position of the block.
+   */
+  public static getAreasAt(pos: WorldPos): void {
+    sendPackets(new PacketToClient.GetAreasAt(pos));
+  }
+
+  /**
+   * Requests the list of lights placed in the world. Emits "lightList" with
+   * the placed lights.
+   */
+  public static listLights(): void {
+    sendPackets(new PacketToClient.ListLights());
+  }
+
+  /**
+   * Switches a placed light on or off in the running scene. The change is not
+   * stored in the game database. Emits "lightList" on success, or
+   * "lightFailed" on failure.
+   * @param name The name of the light.
+   * @param enabled Whether the light should be switched on.
+   */
+  public static setLightEnabled(name: string, enabled: boolean): void {
+    sendPackets(new PacketToClient.SetLightEnabled(name, enabled));
+  }
+
+  /**
+   * Requests the block model at a world position. Emits "block" with the
+   * position and the block model.
+   * @param pos The world position.
+   */
+  public static getBlock(pos: WorldPos): void {
+    sendPackets(new PacketToClient.GetBlock(pos));
+  }
+
+  /**
+   * Casts a ray through the blocks of the map. Emits "raycastResult" with the
+   * ray and the first non-empty block that it entered, or null if it did not
+   * hit any block.
+   * @param origin The world position that the ray starts from.
+   * @param dir The direction of the ray. It must not be zero.
+   * @param maxDist The maximum distance, in blocks, that the ray travels,
+   * between 0 and 65536.
+   */
+  public static raycastBlocks(origin: Vec3, dir: Vec3, maxDist: number): void {
+    sendPackets(new PacketToClient.RaycastBlocks(origin, dir, maxDist));
+  }
+
+  /**
+   * Requests a summary of a chunk. Emits "chunkSummary" with the summary.
+   * @param pos The chunk position.
+   */
+  public static getChunkSummary(pos: ChunkPos): void {
+    sendPackets(new PacketToClient.GetChunkSummary(pos));
+  }
+
+  /**
+   * Registers a command in the editor command palette. Emits
+   * "commandInvoked" with the ID of the command whenever the player runs it.
+   * If a command with the same ID is already registered, it is replaced.
+   * @param id The unique ID of the command.
+   * @param name The display name of the command.
+   * @param shortcut A hint of the keyboard shortcut of the command.
+   */
+  public static registerCommand(
+    id: string,
+    name: string,
+    shortcut?: string,
+  ): void {
+    sendPackets(new PacketToClient.RegisterCommand(id, name, shortcut));
+  }
+
+  /**
+   * Removes a command from the editor command palette.
+   * @param id The ID of the command.
+   */
+  public static unregisterCommand(id: string): void {
+    sendPackets(new PacketToClient.UnregisterCommand(id));
+  }
+
+  /**
+   * Watches the project assets at or within the given asset path, such as
+   * "project://tiles/" for every asset in a folder. Emits "assetChanged"
+   * with the asset path and the kind of change whenever a matching asset is
+   * added, modified, or removed. Assets can only be watched in editor mode.
+   * @param path The asset path to watch.
+   */
+  public static watchAssets(path: string): void {
+    sendPackets(new PacketToClient.WatchAssets(path));
+  }
+
+  /**
+   * Stops watching an asset path previously watched with `watchAssets`.
+   * @param path The asset path to stop watching.
+   */
+  public static unwatchAssets(path: string): void {
+    sendPackets(new PacketToClient.UnwatchAssets(path));
+  }
+
+  /**
+   * Opens a native file dialog, so that the user can choose a file to import.
+   * Emits "fileDialogClosed" with the given ID and the chosen file path, or
+   * null if the dialog was cancelled. File dialogs can only be opened in
+   * editor mode.
+   * @param id An ID that is sent back with the result.
+   * @param title The title of the dialog window.
+   * @param filters The filters limiting which files may be chosen.
+   */
+  public static openFileDialog(
+    id: string,
+    title?: string,
+    filters?: FileFilter[],
+  ): void {
+    sendPackets(new PacketToClient.RequestFileDialog(id, title, filters));
+  }
+
+  /**
+   * Hosts a network session on the given port, closing any session that is
+   * already active. Emits "peerConnected" and "peerDisconnected" as other
+   * instances join and leave, or "netError" if the port cannot be bound.
+   * Requires the client to be built with the "networking" feature.
+   * @param port The TCP port to listen on.
+   */
+  public static hostGame(port: number): void {
+    sendPackets(new PacketToClient.HostGame(port));
+  }
+
+  /**
+   * Joins the network session hosted at the given address, closing any session
+   * that is already active. Emits "netJoined" with the assigned peer ID once
+   * the host has accepted the connection, or "netError" on failure. Requires
+   * the client to be built with the "networking" feature.
+   * @param address The address of the host, such as "127.0.0.1:7777".
+   */
+  public static joinGame(address: string): void {
+    sendPackets(new PacketToClient.JoinGame(address));
+  }
+
+  /**
+   * Leaves the active network session, if any.
+   */
+  public static leaveGame(): void {
+    sendPackets(new PacketToClient.LeaveGame());
+  }
+
+  /**
+   * Sends a message to other peers in the active network session. Received
+   * messages are emitted as "netReceived" on the other peers. The host may
+   * send a message to a single client, or to every client if no peer is
+   * given. Clients may only send messages to the host.
+   * @param message The message to send.
+   * @param to The peer ID to send the message to.
+   */
+  public static netSend(message: NetMessage, to?: number): void {
+    sendPackets(new PacketToClient.NetSend(message, to));
+  }
+
+  /**
+   * Prints a line of text to the player's chat console. Lines typed by the
+   * player are received through the "consoleCommand" event.
+   * @param text The text to print.
+   */
+  public static print(text: string): void {
+    sendPackets(new PacketToClient.ConsolePrint(text));
+  }
+
+  /**
+   * Spawns a billboard sprite in the world. If a sprite with the same ID
+   * already exists, it is replaced.
+   * @param id The ID of the sprite.
+   * @param texture The asset path of the sprite texture.
+   * @param position The world position of the bottom center of the sprite.
+   * @param size The width and height of the sprite, in blocks.
+   * @param mode How the sprite is oriented towards the camera.
+   * @param interactions The interaction events that the sprite reports. A
+   * clickable sprite emits "entityClicked", and a hoverable sprite emits
+   * "entityHoverChanged".
+   */
+  public static spawnSprite(
+    id: string,
+    texture: string,
+    position: Vec3,
+    size: Size2 = [1, 1],
+    mode: PacketToClient.BillboardMode = "camera",
+    interactions: PacketToClient.SpriteInteractions = {}
+  ): void {
+    sendPackets(
+      new PacketToClient.SpawnSprite(
+        id,
+        texture,
+        position,
+        size,
+        mode,
+        interactions
+      )
+    );
+  }
+
+  /**
+   * Updates an existing billboard sprite. Omitted properties are left
+   * unchanged.
+   * @param id The ID of the sprite.
+   * @param changes The properties to change.
+   */
+  public static updateSprite(
+    id: string,
+    changes: PacketToClient.SpriteChanges
+  ): void {
+    sendPackets(new PacketToClient.UpdateSprite(id, changes));
+  }
+
+  /**
+   * Removes a billboard sprite from the world.
+   * @param id The ID of the sprite.
+   */
+  public static despawnSprite(id: string): void {
+    sendPackets(new PacketToClient.DespawnSprite(id));
+  }
+
   /**
    * Call an event handler, if it exists. Events are called in the order they
    * were registered, with temporary handlers always being called last.
@@ -292,4 +777,47 @@ export class Game {
  */
 export type GameEvents = {
   ready: () => Promise<void>;
+  shutdown: () => Promise<void>;
+  tick: (tick: number) => Promise<void>;
+  fileDialogClosed: (id: string, path: string | null) => Promise<void>;
+  saveList: (saves: SaveInfo[]) => Promise<void>;
+  saveCreated: (slot: string) => Promise<void>;
+  saveLoaded: (slot: string, data: unknown) => Promise<void>;
+  saveDeleted: (slot: string) => Promise<void>;
+  saveFailed: (slot: string, error: string) => Promise<void>;
+  mapDiff: (from: string, to: string | null, patch: MapPatch) => Promise<void>;
+  snapshotNotFound: (name: string) => Promise<void>;
+  bookmarkList: (bookmarks: Bookmark[]) => Promise<void>;
+  bookmarkFailed: (name: string, error: string) => Promise<void>;
+  blockModelList: (models: NamedBlockModel[]) => Promise<void>;
+  blockModelFailed: (name: string, error: string) => Promise<void>;
+  areaList: (areas: Area[]) => Promise<void>;
+  areasAt: (pos: WorldPos, areas: Area[]) => Promise<void>;
+  areaFailed: (name: string, error: string) => Promise<void>;
+  lightList: (lights: PlacedLight[]) => Promise<void>;
+  lightFailed: (name: string, error: string) => Promise<void>;
+  block: (pos: WorldPos, model: BlockModel) => Promise<void>;
+  raycastResult: (
+    origin: Vec3,
+    dir: Vec3,
+    hit: BlockHit | null,
+  ) => Promise<void>;
+  chunkSummary: (summary: ChunkSummary) => Promise<void>;
+  consoleCommand: (text: string) => Promise<void>;
+  commandInvoked: (id: string) => Promise<void>;
+  spriteDeleted: (id: string) => Promise<void>;
+  spriteDuplicated: (source: string, id: string) => Promise<void>;
+  entityClicked: (
+    id: string,
+    button: ClickButton,
+    hitPos: Vec3,
+  ) => Promise<void>;
+  entityHoverChanged: (id: string, hovered: boolean) => Promise<void>;
+  assetChanged: (id: string, kind: AssetChangeKind) => Promise<void>;
+  netJoined: (peer: number) => Promise<void>;
+  peerConnected: (peer: number) => Promise<void>;
+  peerDisconnected: (peer: number) => Promise<void>;
+  netReceived: (from: number, message: NetMessage) => Promise<void>;
+  netClosed: (error: string | null) => Promise<void>;
+  netError: (error: string) => Promise<void>;
 };
@@ -0,0 +1,33 @@
+// This file is generated by `awgen --emit-types`. Do not edit it by hand.
+
+import type { PacketIn, PacketOut } from "./Packets.d.ts";
+
+/**
+ * The packet variant of the given type, that may be sent to the engine.
+ */
+export type PacketInOf<T extends PacketIn["type"]> = Extract<PacketIn, { type: T }>;
+
+/**
+ * The packet variant of the given type, that may be received from the engine.
+ */
+export type PacketOutOf<T extends PacketOut["type"]> = Extract<PacketOut, { type: T }>;
+
+/**
+ * Creates a packet to send to the engine.
+ */
+export function packetIn<T extends PacketIn["type"]>(
+  type: T,
+  fields: Omit<PacketInOf<T>, "type">,
+): PacketInOf<T> {
+  return { ...fields, type } as PacketInOf<T>;
+}
+
+/**
+ * Checks whether a packet received from the engine is of the given type.
+ */
+export function isPacketOut<T extends PacketOut["type"]>(
+  packet: PacketOut,
+  type: T,
+): packet is PacketOutOf<T> {
+  return packet.type === type;
+}
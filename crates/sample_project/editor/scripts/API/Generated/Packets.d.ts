@@ -0,0 +1,633 @@
+// This file is generated by `awgen --emit-types`. Do not edit it by hand.
+
+export type Mat2 = [number, number, number, number];
+
+export type JsonValue = number | string | boolean | Array<JsonValue> | { [key in string]?: JsonValue } | null;
+
+export type WorldPos = [x: number, y: number, z: number];
+
+export type ChunkPos = [x: number, y: number, z: number];
+
+export type UvWindow = { /**
+ * The texture coordinates of the top left corner of the region.
+ */
+offset: [number, number], /**
+ * The size of the region, in texture coordinates.
+ */
+scale: [number, number], };
+
+export type TileFace = { /**
+ * The tile palette ID for the block face. The [`TilePalette`] resolves it
+ * to a layer of the active tileset, so it stays stable when tiles are
+ * added to or removed from the tileset.
+ */
+tile_index: number, /**
+ * The rotation matrix for the tile.
+ */
+rotation: Mat2, /**
+ * The region of the tileset layer that is drawn on the face. If not
+ * provided, the whole layer is drawn.
+ */
+uv_window?: UvWindow, };
+
+export type Cube = { /**
+ * The tile information for the top (Y+) face of the cube.
+ */
+posY: TileFace, /**
+ * The tile information for the north (Z+) face of the cube.
+ */
+posZ: TileFace, /**
+ * The tile information for the south (Z-) face of the cube.
+ */
+negZ: TileFace, /**
+ * The tile information for the east (X+) face of the cube.
+ */
+posX: TileFace, /**
+ * The tile information for the west (X-) face of the cube.
+ */
+negX: TileFace, };
+
+export type BlockModel = { "type": "empty" } | { "type": "cube" } & Cube;
+
+export type BillboardMode = "camera" | "axisLocked";
+
+export type BlockRegion = { /**
+ * The minimum corner of the region.
+ */
+min: WorldPos, /**
+ * The maximum corner of the region.
+ */
+max: WorldPos, };
+
+export type BlockChange = { /**
+ * The world position of the block.
+ */
+pos: WorldPos, /**
+ * The new block model.
+ */
+model: BlockModel, };
+
+export type MapPatch = { /**
+ * The chunks that are created by this patch, even if they stay empty.
+ */
+addedChunks: Array<ChunkPos>, /**
+ * The chunks that are removed by this patch, along with all of their
+ * blocks.
+ */
+removedChunks: Array<ChunkPos>, /**
+ * The block changes, ordered by chunk.
+ */
+changes: Array<BlockChange>, };
+
+export type BlockFace = "posY" | "negY" | "posZ" | "negZ" | "posX" | "negX";
+
+export type BlockHit = { /**
+ * The position of the block that was hit.
+ */
+pos: WorldPos, /**
+ * The face of the block that was hit.
+ */
+face: BlockFace, /**
+ * The distance along the ray to the hit point.
+ */
+distance: number, };
+
+export type ScriptLimit = "memory" | "time";
+
+export type PacketEncoding = "json" | "messagePack";
+
+export type SaveInfo = { /**
+ * The name of the save slot.
+ */
+slot: string, /**
+ * The time the save was last written, in seconds since the Unix epoch.
+ */
+modified: bigint, };
+
+export type NamedBlockModel = { /**
+ * The unique name of the block model.
+ */
+name: string, /**
+ * The block model.
+ */
+model: BlockModel, };
+
+export type Bookmark = { /**
+ * The unique name of the bookmark.
+ */
+name: string, /**
+ * The block the camera looks at.
+ */
+pos: WorldPos, /**
+ * The camera pitch, in degrees.
+ */
+pitch: number, /**
+ * The camera yaw, in degrees.
+ */
+yaw: number, /**
+ * The distance of the camera from the position it looks at.
+ */
+distance: number, };
+
+export type Area = { /**
+ * The unique name of the area.
+ */
+name: string, /**
+ * The blocks covered by the area.
+ */
+region: BlockRegion, /**
+ * The tags of the area.
+ */
+tags: Array<string>, };
+
+export type LightKind = "point" | "spot";
+
+export type PlacedLight = { /**
+ * The unique name of the light.
+ */
+name: string, /**
+ * The kind of the light.
+ */
+kind: LightKind, /**
+ * The world position of the light.
+ */
+position: [number, number, number], /**
+ * The pitch of a spot light, in degrees. Zero points the light at the
+ * horizon, and -90 points it straight down.
+ */
+pitch: number, /**
+ * The yaw of a spot light, in degrees.
+ */
+yaw: number, /**
+ * The red, green, and blue components of the light color, from 0 to 1.
+ */
+color: [number, number, number], /**
+ * The luminous power of the light, in lumens.
+ */
+intensity: number, /**
+ * The distance, in blocks, beyond which the light has no effect.
+ */
+range: number, /**
+ * The angle, in degrees, between the center and the edge of the cone of
+ * a spot light.
+ */
+angle: number, /**
+ * Whether the light casts shadows.
+ */
+shadows: boolean, /**
+ * Whether the light is switched on.
+ */
+enabled: boolean, };
+
+export type CameraProjection = "orthographic" | "perspective";
+
+export type AtmosphereSettings = { /**
+ * Whether distance fog is drawn over the terrain.
+ */
+fogEnabled: boolean, /**
+ * The color that distant terrain fades into.
+ */
+fogColor: [number, number, number], /**
+ * The distance from the camera, in blocks, at which the fog starts.
+ */
+fogStart: number, /**
+ * The distance from the camera, in blocks, at which the terrain is fully
+ * hidden by the fog.
+ */
+fogEnd: number, /**
+ * The color of the sky at the top of the screen.
+ */
+skyTop: [number, number, number], /**
+ * The color of the sky at the bottom of the screen.
+ */
+skyBottom: [number, number, number], };
+
+export type PerformanceBudgets = { /**
+ * The budget for the frame time, in milliseconds.
+ */
+frameTime: number, /**
+ * The budget for the time the main thread spends on chunk meshing each
+ * frame, in milliseconds.
+ */
+remeshTime: number, /**
+ * The budget for the number of script packets waiting to be processed at
+ * the start of a frame.
+ */
+packetQueueDepth: number, /**
+ * The number of consecutive frames that a diagnostic must be over its
+ * budget before a warning is shown.
+ */
+frames: number, };
+
+export type ClickButton = "left" | "right" | "middle";
+
+export type FileFilter = { /**
+ * The name of the filter, such as `"Images"`.
+ */
+name: string, /**
+ * The file extensions matched by the filter, without the leading dot,
+ * such as `"png"`.
+ */
+extensions: Array<string>, };
+
+export type AssetChangeKind = "added" | "modified" | "removed";
+
+export type PacketIn = { "type": "init", /**
+ * The name of the game.
+ */
+name: string, /**
+ * The game version.
+ */
+version: string, /**
+ * The encoding to use for large packets from now on. Defaults to
+ * JSON.
+ */
+encoding?: PacketEncoding, } | { "type": "set", /**
+ * The packets that should be processed.
+ */
+packets: Array<PacketIn>, } | { "type": "shutdown" } | { "type": "crashed", /**
+ * The error message associated with the crash.
+ */
+error: string, } | { "type": "limitExceeded", /**
+ * The resource limit that was exceeded.
+ */
+limit: ScriptLimit, /**
+ * The error message associated with the failure.
+ */
+error: string, } | { "type": "importAsset", /**
+ * The OS filepath of the asset file to import.
+ */
+file: string, /**
+ * The local asset path to use within the project.
+ */
+assetPath: string, } | { "type": "requestFileDialog", /**
+ * A script-defined ID that is sent back with the result.
+ */
+id: string, /**
+ * The title of the dialog window.
+ */
+title?: string, /**
+ * The filters limiting which files may be chosen. If empty, all files
+ * are shown.
+ */
+filters?: Array<FileFilter>, } | { "type": "watchAssets", /**
+ * The asset path to watch, starting with `project://`.
+ */
+path: string, } | { "type": "unwatchAssets", /**
+ * The asset path to stop watching.
+ */
+path: string, } | { "type": "createTileset", /**
+ * The list of asset paths for the corresponding tiles.
+ */
+tilePaths: Array<string>, /**
+ * The output asset path for the tileset.
+ */
+outputPath: string, /**
+ * If true, mipmaps are generated with a faster filter that averages
+ * raw sRGB values, rather than the default gamma-correct filter.
+ */
+fastMipmaps?: boolean, } | { "type": "setTilesets", /**
+ * The asset path of the tileset to use for the world.
+ */
+opaqueTilesetPath: string, } | { "type": "setBlock", /**
+ * The world position.
+ */
+pos: WorldPos, /**
+ * The block model.
+ */
+model: BlockModel, } | { "type": "getBlock", /**
+ * The world position.
+ */
+pos: WorldPos, } | { "type": "raycastBlocks", /**
+ * The world position that the ray starts from. A block the ray starts
+ * inside of is never hit.
+ */
+origin: [number, number, number], /**
+ * The direction of the ray. It does not need to be normalized, but
+ * must not be zero.
+ */
+dir: [number, number, number], /**
+ * The maximum distance, in blocks, that the ray travels. It must be
+ * between 0 and 65536.
+ */
+maxDist: number, } | { "type": "getChunkSummary", /**
+ * The chunk position.
+ */
+pos: ChunkPos, } | { "type": "tickDone", /**
+ * The tick that was finished.
+ */
+tick: number, } | { "type": "listSaves" } | { "type": "createSave", /**
+ * The name of the save slot.
+ */
+slot: string, /**
+ * Arbitrary data to store with the save.
+ */
+data: JsonValue, } | { "type": "loadSave", /**
+ * The name of the save slot.
+ */
+slot: string, } | { "type": "deleteSave", /**
+ * The name of the save slot.
+ */
+slot: string, } | { "type": "consolePrint", /**
+ * The text to print.
+ */
+text: string, } | { "type": "spawnSprite", /**
+ * The script-defined ID of the sprite.
+ */
+id: string, /**
+ * The asset path of the sprite texture, such as
+ * `"game://sprites/player.png"`.
+ */
+texture: string, /**
+ * The world position of the bottom center of the sprite.
+ */
+position: [number, number, number], /**
+ * The width and height of the sprite, in blocks.
+ */
+size: [number, number], /**
+ * How the sprite is oriented towards the camera. Defaults to facing
+ * the camera directly.
+ */
+mode?: BillboardMode, /**
+ * Whether clicking the sprite sends a [`PacketOut::EntityClicked`]
+ * packet. Defaults to false.
+ *
+ * [`PacketOut::EntityClicked`]: crate::scripts::PacketOut::EntityClicked
+ */
+clickable?: boolean, /**
+ * Whether the cursor entering or leaving the sprite sends a
+ * [`PacketOut::EntityHoverChanged`] packet. Defaults to false.
+ *
+ * [`PacketOut::EntityHoverChanged`]: crate::scripts::PacketOut::EntityHoverChanged
+ */
+hoverable?: boolean, } | { "type": "updateSprite", /**
+ * The script-defined ID of the sprite.
+ */
+id: string, /**
+ * The asset path of the new sprite texture.
+ */
+texture?: string, /**
+ * The new world position of the bottom center of the sprite.
+ */
+position?: [number, number, number], /**
+ * The new width and height of the sprite, in blocks.
+ */
+size?: [number, number], /**
+ * The new orientation mode of the sprite.
+ */
+mode?: BillboardMode, /**
+ * Whether clicking the sprite is reported to the script engine.
+ */
+clickable?: boolean, /**
+ * Whether hovering over the sprite is reported to the script engine.
+ */
+hoverable?: boolean, } | { "type": "despawnSprite", /**
+ * The script-defined ID of the sprite.
+ */
+id: string, } | { "type": "takeSnapshot", /**
+ * The name of the snapshot.
+ */
+name: string, /**
+ * The region of the map to capture. If omitted, the whole map is
+ * captured.
+ */
+region?: BlockRegion, } | { "type": "dropSnapshot", /**
+ * The name of the snapshot.
+ */
+name: string, } | { "type": "diffSnapshots", /**
+ * The name of the snapshot to compare from.
+ */
+from: string, /**
+ * The name of the snapshot to compare to. If omitted, the current
+ * state of the map is used.
+ */
+to?: string, } | { "type": "applyPatch", /**
+ * The patch to apply.
+ */
+patch: MapPatch, } | { "type": "listBookmarks" } | { "type": "setBookmark", /**
+ * The bookmark to store.
+ */
+bookmark: Bookmark, } | { "type": "deleteBookmark", /**
+ * The name of the bookmark.
+ */
+name: string, } | { "type": "jumpToBookmark", /**
+ * The name of the bookmark.
+ */
+name: string, } | { "type": "listBlockModels" } | { "type": "placeBlockModel", /**
+ * The position of the block.
+ */
+pos: WorldPos, /**
+ * The name of the block model.
+ */
+name: string, } | { "type": "setCameraCollision", /**
+ * Whether camera collision is enabled.
+ */
+enabled: boolean, } | { "type": "setCameraProjection", /**
+ * The projection to use.
+ */
+projection: CameraProjection, } | { "type": "setAtmosphere", /**
+ * The new atmosphere settings.
+ */
+atmosphere: AtmosphereSettings, } | { "type": "setFrameRate", /**
+ * The maximum frame rate while the window is focused. If not
+ * provided, the frame rate is not limited.
+ */
+targetFps?: number, /**
+ * The maximum frame rate while the window is unfocused. If not
+ * provided, the current limit is kept.
+ */
+unfocusedFps?: number, } | { "type": "setPerformanceBudgets", /**
+ * The new performance budgets.
+ */
+budgets: PerformanceBudgets, } | { "type": "setLogFilter", /**
+ * The filter directives.
+ */
+filter: string, } | { "type": "listAreas" } | { "type": "setArea", /**
+ * The area to store.
+ */
+area: Area, } | { "type": "deleteArea", /**
+ * The name of the area.
+ */
+name: string, } | { "type": "getAreasAt", /**
+ * The position of the block.
+ */
+pos: WorldPos, } | { "type": "listLights" } | { "type": "setLightEnabled", /**
+ * The name of the light.
+ */
+name: string, /**
+ * Whether the light should be switched on.
+ */
+enabled: boolean, } | { "type": "registerCommand", /**
+ * The ID of the command.
+ */
+id: string, /**
+ * The name of the command shown in the command palette.
+ */
+name: string, /**
+ * A description of the keyboard shortcut of the command, shown in the
+ * command palette.
+ */
+shortcut?: string, } | { "type": "unregisterCommand", /**
+ * The ID of the command.
+ */
+id: string, };
+
+export type PacketOut = { "type": "shutdown" } | { "type": "fileDrop", /**
+ * The file path of the dropped file.
+ */
+path: string, } | { "type": "fileDialogClosed", /**
+ * The ID given in the request.
+ */
+id: string, /**
+ * The OS file path of the chosen file, or `None` if the dialog was
+ * cancelled or could not be opened.
+ */
+path: string | null, } | { "type": "assetChanged", /**
+ * The asset path of the changed asset, such as
+ * `project://tiles/grass.png`.
+ */
+id: string, /**
+ * How the asset changed.
+ */
+kind: AssetChangeKind, } | { "type": "tick", /**
+ * The tick number, starting at 1 and increasing by one each tick.
+ */
+tick: number, } | { "type": "saveList", /**
+ * The available save slots, sorted by name.
+ */
+saves: Array<SaveInfo>, } | { "type": "saveCreated", /**
+ * The name of the save slot.
+ */
+slot: string, } | { "type": "saveLoaded", /**
+ * The name of the save slot.
+ */
+slot: string, /**
+ * The data that was stored with the save.
+ */
+data: JsonValue, } | { "type": "saveDeleted", /**
+ * The name of the save slot.
+ */
+slot: string, } | { "type": "saveFailed", /**
+ * The name of the save slot.
+ */
+slot: string, /**
+ * The reason the operation failed.
+ */
+error: string, } | { "type": "mapDiff", /**
+ * The name of the snapshot compared from.
+ */
+from: string, /**
+ * The name of the snapshot compared to, or `None` if the snapshot was
+ * compared to the current state of the map.
+ */
+to: string | null, /**
+ * The patch that transforms the first snapshot into the second.
+ */
+patch: MapPatch, } | { "type": "snapshotNotFound", /**
+ * The name of the snapshot.
+ */
+name: string, } | { "type": "bookmarkList", /**
+ * The stored bookmarks.
+ */
+bookmarks: Array<Bookmark>, } | { "type": "bookmarkFailed", /**
+ * The name of the bookmark.
+ */
+name: string, /**
+ * A description of the error.
+ */
+error: string, } | { "type": "blockModelList", /**
+ * The stored block models.
+ */
+models: Array<NamedBlockModel>, } | { "type": "blockModelFailed", /**
+ * The name of the block model.
+ */
+name: string, /**
+ * A description of the error.
+ */
+error: string, } | { "type": "areaList", /**
+ * The stored areas.
+ */
+areas: Array<Area>, } | { "type": "areasAt", /**
+ * The position of the block.
+ */
+pos: WorldPos, /**
+ * The areas containing the block.
+ */
+areas: Array<Area>, } | { "type": "areaFailed", /**
+ * The name of the area.
+ */
+name: string, /**
+ * A description of the error.
+ */
+error: string, } | { "type": "lightList", /**
+ * The placed lights.
+ */
+lights: Array<PlacedLight>, } | { "type": "lightFailed", /**
+ * The name of the light.
+ */
+name: string, /**
+ * A description of the error.
+ */
+error: string, } | { "type": "block", /**
+ * The world position.
+ */
+pos: WorldPos, /**
+ * The block model.
+ */
+model: BlockModel, } | { "type": "raycastResult", /**
+ * The world position that the ray started from.
+ */
+origin: [number, number, number], /**
+ * The direction of the ray.
+ */
+dir: [number, number, number], /**
+ * The first non-empty block that the ray entered, or `None` if it
+ * did not hit any block within its maximum distance.
+ */
+hit: BlockHit | null, } | { "type": "chunkSummary", /**
+ * The chunk position.
+ */
+pos: ChunkPos, /**
+ * The world position of the block at the minimum corner of the chunk.
+ */
+origin: WorldPos, /**
+ * The number of blocks along each axis of the chunk.
+ */
+size: number, /**
+ * Whether the chunk exists. Chunks are created when the first block
+ * is placed in them, and are otherwise empty.
+ */
+loaded: boolean, /**
+ * The number of non-empty blocks in the chunk.
+ */
+solidBlocks: number, } | { "type": "consoleCommand", /**
+ * The submitted text.
+ */
+text: string, } | { "type": "commandInvoked", /**
+ * The ID of the command.
+ */
+id: string, } | { "type": "spriteDeleted", /**
+ * The ID of the deleted sprite.
+ */
+id: string, } | { "type": "spriteDuplicated", /**
+ * The ID of the duplicated sprite.
+ */
+source: string, /**
+ * The ID of the new sprite.
+ */
+id: string, } | { "type": "entityClicked", /**
+ * The ID of the clicked sprite.
+ */
+id: string, /**
+ * The mouse button that was pressed.
+ */
+button: ClickButton, /**
+ * The world position on the sprite that was clicked.
+ */
+hitPos: [number, number, number], } | { "type": "entityHoverChanged", /**
+ * The ID of the sprite.
+ */
+id: string, /**
+ * Whether the cursor is now hovering over the sprite.
+ */
+hovered: boolean, };
@@ -0,0 +1,69 @@
+import { Vec3 } from "./Units.ts";
+
+/**
+ * The kind of a placed light.
+ */
+export type LightKind = "point" | "spot";
+
+/**
+ * A named light placed in the world from the editor, stored in the game
+ * database.
+ */
+export interface PlacedLight {
+  /**
+   * The unique name of the light.
+   */
+  name: string;
+
+  /**
+   * The kind of the light.
+   */
+  kind: LightKind;
+
+  /**
+   * The world position of the light.
+   */
+  position: Vec3;
+
+  /**
+   * The pitch of a spot light, in degrees. Zero points the light at the
+   * horizon, and -90 points it straight down.
+   */
+  pitch: number;
+
+  /**
+   * The yaw of a spot light, in degrees.
+   */
+  yaw: number;
+
+  /**
+   * The red, green, and blue components of the light color, from 0 to 1.
+   */
+  color: Vec3;
+
+  /**
+   * The luminous power of the light, in lumens.
+   */
+  intensity: number;
+
+  /**
+   * The distance, in blocks, beyond which the light has no effect.
+   */
+  range: number;
+
+  /**
+   * The angle, in degrees, between the center and the edge of the cone of a
+   * spot light.
+   */
+  angle: number;
+
+  /**
+   * Whether the light casts shadows.
+   */
+  shadows: boolean;
+
+  /**
+   * Whether the light is switched on.
+   */
+  enabled: boolean;
+}
@@ -0,0 +1,43 @@
+const log =
+  // @ts-ignore
+  rustyscript.functions["log"] as (level: LogLevel, message: string) => void;
+
+/**
+ * The severity of a log message.
+ */
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+/**
+ * Writes a message to the engine log, under the "script" target. Script logs
+ * are written to the same stream and log files as the engine logs.
+ * @param level The severity of the message.
+ * @param args The values to log. Non-string values are converted to JSON.
+ */
+export function writeLog(level: LogLevel, ...args: unknown[]): void {
+  const message = args
+    .map((arg) => {
+      if (typeof arg === "string") return arg;
+      if (arg instanceof Error) return arg.stack ?? arg.message;
+      try {
+        return JSON.stringify(arg);
+      } catch {
+        return String(arg);
+      }
+    })
+    .join(" ");
+
+  log(level, message);
+}
+
+/**
+ * Redirects the console methods to the engine log, so that script output is
+ * written to the engine log files.
+ */
+export function captureConsole(): void {
+  console.error = (...args: unknown[]) => writeLog("error", ...args);
+  console.warn = (...args: unknown[]) => writeLog("warn", ...args);
+  console.info = (...args: unknown[]) => writeLog("info", ...args);
+  console.log = (...args: unknown[]) => writeLog("info", ...args);
+  console.debug = (...args: unknown[]) => writeLog("debug", ...args);
+  console.trace = (...args: unknown[]) => writeLog("trace", ...args);
+}
@@ -0,0 +1,55 @@
+import { BlockModel } from "./BlockModel.ts";
+import { ChunkPos, WorldPos } from "./Units.ts";
+
+/**
+ * An inclusive, axis-aligned box of block positions.
+ */
+export interface BlockRegion {
+  /**
+   * The minimum corner of the region.
+   */
+  min: WorldPos;
+
+  /**
+   * The maximum corner of the region.
+   */
+  max: WorldPos;
+}
+
+/**
+ * A change to a single block in a map patch.
+ */
+export interface BlockChange {
+  /**
+   * The world position of the block.
+   */
+  pos: WorldPos;
+
+  /**
+   * The new block model.
+   */
+  model: BlockModel;
+}
+
+/**
+ * A list of block changes that transforms one map state into another. A chunk
+ * that does not exist is different from an empty chunk, so patches also list
+ * the chunks they create and remove. Patches are plain JSON, so they can be
+ * saved, shared, and applied later with `Game.applyPatch()`.
+ */
+export interface MapPatch {
+  /**
+   * The chunks that are created by this patch, even if they stay empty.
+   */
+  addedChunks: ChunkPos[];
+
+  /**
+   * The chunks that are removed by this patch, along with all of their blocks.
+   */
+  removedChunks: ChunkPos[];
+
+  /**
+   * The block changes, ordered by chunk.
+   */
+  changes: BlockChange[];
+}
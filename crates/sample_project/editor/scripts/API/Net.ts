@@ -0,0 +1,70 @@
+import { BlockModel } from "./BlockModel.ts";
+import { WorldPos } from "./Units.ts";
+
+/**
+ * The peer ID of the host of a network session. Clients are assigned IDs
+ * starting at 1.
+ */
+export const HOST_PEER = 0;
+
+/**
+ * A message that changes a block in the world.
+ */
+export interface SetBlockMessage {
+  /**
+   * The type of the message, which is "setBlock" in this case.
+   */
+  type: "setBlock";
+
+  /**
+   * The world position of the block.
+   */
+  pos: WorldPos;
+
+  /**
+   * The block model.
+   */
+  model: BlockModel;
+}
+
+/**
+ * A message that updates a script-defined entity.
+ */
+export interface EntityUpdateMessage {
+  /**
+   * The type of the message, which is "entityUpdate" in this case.
+   */
+  type: "entityUpdate";
+
+  /**
+   * The script-defined ID of the entity.
+   */
+  entity: string;
+
+  /**
+   * The updated entity state.
+   */
+  data: unknown;
+}
+
+/**
+ * A chat message.
+ */
+export interface ChatMessage {
+  /**
+   * The type of the message, which is "chat" in this case.
+   */
+  type: "chat";
+
+  /**
+   * The message text.
+   */
+  text: string;
+}
+
+/**
+ * A message that may be sent between the peers of a network session. Only
+ * these messages are relayed, so scripts cannot send arbitrary packets to
+ * other peers.
+ */
+export type NetMessage = SetBlockMessage | EntityUpdateMessage | ChatMessage;
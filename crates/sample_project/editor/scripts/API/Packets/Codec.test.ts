@@ -0,0 +1,195 @@
+import {
+  COMPRESSION_THRESHOLD,
+  compressLz4,
+  decodeFrame,
+  decodePacket,
+  decompressLz4,
+  encodeFrame,
+  encodeMessagePack,
+  encodePackets,
+  FRAME_LZ4,
+  FRAME_MESSAGE_PACK,
+  FRAME_THRESHOLD,
+} from "./Codec.ts";
+
+/**
+ * Runs every packet codec test, throwing an error describing the first test
+ * that fails.
+ *
+ * The tests are run by the script protocol tests of the game engine, so that
+ * they run in the same script runtime as the game.
+ */
+export function testCodec(): void {
+  const tests: Record<string, () => void> = {
+    valuesRoundTrip,
+    smallPacketsStayJson,
+    largePacketsAreFramedOnlyWhenNegotiated,
+    hugeFramesAreCompressed,
+    lz4RoundTrips,
+    malformedFramesAreRejected,
+  };
+
+  for (const [name, test] of Object.entries(tests)) {
+    try {
+      test();
+    } catch (error) {
+      throw new Error(`Codec test ${name} failed: ${error}`);
+    }
+  }
+}
+
+/**
+ * Asserts that two values are deeply equal.
+ * @param actual The value that was produced.
+ * @param expected The value that was expected.
+ */
+function assertEqual(actual: unknown, expected: unknown): void {
+  const a = JSON.stringify(actual);
+  const b = JSON.stringify(expected);
+  if (a !== b) {
+    throw new Error(`Expected ${b.slice(0, 200)}, got ${a.slice(0, 200)}`);
+  }
+}
+
+/**
+ * Asserts that a condition holds.
+ * @param condition The condition.
+ * @param message The message of the error thrown if the condition is false.
+ */
+function assert(condition: boolean, message: string): void {
+  if (!condition) {
+    throw new Error(message);
+  }
+}
+
+/**
+ * Creates an array of pseudo-random bytes, which do not compress.
+ * @param length The number of bytes.
+ * @returns The bytes.
+ */
+function noise(length: number): Uint8Array {
+  const out = new Uint8Array(length);
+  let state = 0x2545f491;
+  for (let i = 0; i < length; i++) {
+    state ^= state << 13;
+    state ^= state >>> 17;
+    state ^= state << 5;
+    out[i] = state & 255;
+  }
+  return out;
+}
+
+function valuesRoundTrip(): void {
+  const values = [
+    null,
+    true,
+    false,
+    0,
+    127,
+    128,
+    -32,
+    -33,
+    65535,
+    0xffffffff,
+    -0x80000000,
+    2 ** 40,
+    -(2 ** 40),
+    Number.MAX_SAFE_INTEGER,
+    Number.MIN_SAFE_INTEGER,
+    0.5,
+    -1.25e300,
+    "",
+    "Awgen",
+    "\u{1F600} ünïcödé",
+    "x".repeat(70_000),
+    [],
+    [1, [2, [3, "four"]]],
+    Array.from({ length: 70_000 }, (_, i) => i),
+    {},
+    { type: "init", name: "Awgen", version: "1.0.0" },
+    Object.fromEntries(
+      Array.from({ length: 20 }, (_, i) => [`key${i}`, { value: i }])
+    ),
+  ];
+
+  for (const value of values) {
+    assertEqual(decodeFrame(encodeFrame(value)), value);
+  }
+
+  // Undefined properties are skipped, the same way they are in JSON.
+  assertEqual(decodeFrame(encodeFrame({ a: 1, b: undefined })), { a: 1 });
+}
+
+function smallPacketsStayJson(): void {
+  const packets = [{ type: "consolePrint", text: "Hello" }];
+  assert(
+    encodePackets(packets, "json") === null,
+    "JSON packets were framed"
+  );
+  assert(
+    encodePackets(packets, "messagePack") === null,
+    "Small packets were framed"
+  );
+  assertEqual(decodePacket(packets[0]), packets[0]);
+}
+
+function largePacketsAreFramedOnlyWhenNegotiated(): void {
+  const packets = [{ type: "consolePrint", text: "a1b2c3".repeat(1000) }];
+  assert(
+    encodeMessagePack(packets).length >= FRAME_THRESHOLD,
+    "Packet is too small for the test"
+  );
+  assert(
+    encodePackets(packets, "json") === null,
+    "JSON packets were framed"
+  );
+
+  const frame = encodePackets(packets, "messagePack");
+  assert(frame !== null, "Large packets were not framed");
+  assert(frame![0] === FRAME_MESSAGE_PACK, "Small frame was compressed");
+  assertEqual(decodePacket(frame), packets);
+}
+
+function hugeFramesAreCompressed(): void {
+  const text = "tiles/".repeat(10_000) + "\u{1F600} ünïcödé.png";
+  const packets = [{ type: "consolePrint", text }];
+
+  const frame = encodePackets(packets, "messagePack")!;
+  assert(frame[0] === FRAME_LZ4, "Huge frame was not compressed");
+  assert(frame.length < text.length / 10, "Huge frame barely compressed");
+  assertEqual(decodePacket(frame), packets);
+
+  // Frames that do not shrink are sent uncompressed.
+  const data = noise(COMPRESSION_THRESHOLD * 2);
+  const raw = encodeFrame(Array.from(data));
+  assert(raw[0] === FRAME_MESSAGE_PACK, "Incompressible frame was compressed");
+}
+
+function lz4RoundTrips(): void {
+  const inputs = [
+    new Uint8Array(0),
+    new Uint8Array([1, 2, 3]),
+    new Uint8Array(100).fill(7),
+    noise(1000),
+    encodeMessagePack({ text: "Awgen ".repeat(5000) }),
+    encodeMessagePack(Array.from({ length: 10_000 }, (_, i) => i % 300)),
+  ];
+
+  for (const input of inputs) {
+    const compressed = compressLz4(input);
+    assertEqual(Array.from(decompressLz4(compressed)), Array.from(input));
+  }
+}
+
+function malformedFramesAreRejected(): void {
+  const frames = [new Uint8Array(0), new Uint8Array([7, 0xc0])];
+  for (const frame of frames) {
+    let failed = false;
+    try {
+      decodeFrame(frame);
+    } catch {
+      failed = true;
+    }
+    assert(failed, `Frame ${Array.from(frame)} was not rejected`);
+  }
+}
@@ -0,0 +1,652 @@
+/**
+ * The encoding used for large packets passed between the scripts and the
+ * client. Every packet is sent as JSON by default. With "messagePack", packets
+ * whose MessagePack form is at least {@link FRAME_THRESHOLD} bytes are sent as
+ * binary frames, and smaller packets are still sent as JSON so that they stay
+ * easy to debug.
+ *
+ * The encoding is negotiated by the init packet.
+ */
+export type PacketEncoding = "json" | "messagePack";
+
+/**
+ * The size in bytes of the MessagePack form of a packet, above which the packet
+ * is sent as a binary frame. This matches the threshold used by the client.
+ */
+export const FRAME_THRESHOLD = 4096;
+
+/**
+ * The size in bytes of the MessagePack form of a packet, above which its frame
+ * is compressed. This matches the threshold used by the client.
+ */
+export const COMPRESSION_THRESHOLD = 16 * 1024;
+
+/**
+ * The header of a frame holding an uncompressed MessagePack packet.
+ */
+export const FRAME_MESSAGE_PACK = 0;
+
+/**
+ * The header of a frame holding an LZ4-compressed MessagePack packet, prefixed
+ * by its uncompressed size as a 32-bit little-endian integer.
+ */
+export const FRAME_LZ4 = 1;
+
+/**
+ * Encodes the given packets as a binary frame, if the MessagePack encoding was
+ * negotiated and the packets are large enough to be worth it.
+ * @param packets The packets to encode.
+ * @param encoding The negotiated packet encoding.
+ * @returns The frame holding the packets, or null if they should be sent as
+ * JSON.
+ */
+export function encodePackets(
+  packets: unknown[],
+  encoding: PacketEncoding
+): Uint8Array | null {
+  if (encoding === "json") {
+    return null;
+  }
+
+  const data = encodeMessagePack(packets);
+  if (data.length < FRAME_THRESHOLD) {
+    return null;
+  }
+
+  return wrapFrame(data);
+}
+
+/**
+ * Decodes a value passed by the client, which is either a packet or a binary
+ * frame holding one.
+ * @param value The value received from the client.
+ * @returns The decoded packet.
+ */
+export function decodePacket(value: unknown): any {
+  if (value instanceof Uint8Array) {
+    return decodeFrame(value);
+  }
+  return value;
+}
+
+/**
+ * Encodes a value as a binary frame, compressing it if it is large and
+ * compression makes it smaller.
+ * @param value The value to encode.
+ * @returns The binary frame.
+ */
+export function encodeFrame(value: unknown): Uint8Array {
+  return wrapFrame(encodeMessagePack(value));
+}
+
+/**
+ * Decodes the value held by a binary frame.
+ * @param frame The binary frame.
+ * @returns The decoded value.
+ */
+export function decodeFrame(frame: Uint8Array): any {
+  if (frame.length === 0) {
+    throw new Error("Empty packet frame");
+  }
+
+  const data = frame.subarray(1);
+  switch (frame[0]) {
+    case FRAME_MESSAGE_PACK:
+      return new MessagePackReader(data).read();
+    case FRAME_LZ4:
+      return new MessagePackReader(decompressLz4(data)).read();
+    default:
+      throw new Error(`Unknown packet frame header: ${frame[0]}`);
+  }
+}
+
+/**
+ * Encodes a value as MessagePack data.
+ * @param value The value to encode.
+ * @returns The MessagePack data.
+ */
+export function encodeMessagePack(value: unknown): Uint8Array {
+  const writer = new MessagePackWriter();
+  writer.write(value);
+  return writer.finish();
+}
+
+/**
+ * Wraps MessagePack data in a binary frame, compressing it if it is large and
+ * compression makes it smaller.
+ * @param data The MessagePack data.
+ * @returns The binary frame.
+ */
+function wrapFrame(data: Uint8Array): Uint8Array {
+  if (data.length >= COMPRESSION_THRESHOLD) {
+    const compressed = compressLz4(data);
+    if (compressed.length < data.length) {
+      return withHeader(FRAME_LZ4, compressed);
+    }
+  }
+
+  return withHeader(FRAME_MESSAGE_PACK, data);
+}
+
+/**
+ * Prepends a frame header to the given data.
+ * @param header The frame header.
+ * @param data The frame data.
+ * @returns The binary frame.
+ */
+function withHeader(header: number, data: Uint8Array): Uint8Array {
+  const frame = new Uint8Array(data.length + 1);
+  frame[0] = header;
+  frame.set(data, 1);
+  return frame;
+}
+
+/**
+ * The number of bits of the hashes used to find matches while compressing.
+ */
+const LZ4_HASH_BITS = 16;
+
+/**
+ * Compresses data as an LZ4 block, prefixed by the size of the uncompressed
+ * data as a 32-bit little-endian integer.
+ *
+ * Matches are found greedily with a single hash table, which is fast and
+ * compresses the repetitive packets of chunk and asset data well.
+ * @param data The data to compress.
+ * @returns The compressed data.
+ */
+export function compressLz4(data: Uint8Array): Uint8Array {
+  const out: number[] = [
+    data.length & 255,
+    (data.length >>> 8) & 255,
+    (data.length >>> 16) & 255,
+    (data.length >>> 24) & 255,
+  ];
+
+  const read32 = (i: number) =>
+    (data[i] | (data[i + 1] << 8) | (data[i + 2] << 16) | (data[i + 3] << 24)) >>>
+    0;
+  const hash = (i: number) =>
+    Math.imul(read32(i), 2654435761) >>> (32 - LZ4_HASH_BITS);
+  const table = new Int32Array(1 << LZ4_HASH_BITS).fill(-1);
+
+  // The last match must start at least 12 bytes before the end of the block,
+  // and the last 5 bytes of the block are always literals.
+  const matchLimit = data.length - 12;
+  const matchEnd = data.length - 5;
+
+  let anchor = 0;
+  let i = 0;
+  while (i < matchLimit) {
+    const h = hash(i);
+    const candidate = table[h];
+    table[h] = i;
+
+    if (
+      candidate < 0 ||
+      i - candidate > 0xffff ||
+      read32(candidate) !== read32(i)
+    ) {
+      i++;
+      continue;
+    }
+
+    let length = 4;
+    while (i + length < matchEnd && data[candidate + length] === data[i + length]) {
+      length++;
+    }
+
+    const literals = i - anchor;
+    const matchLength = length - 4;
+    out.push((Math.min(literals, 15) << 4) | Math.min(matchLength, 15));
+    writeLz4Length(out, literals);
+    for (let k = anchor; k < i; k++) {
+      out.push(data[k]);
+    }
+
+    const offset = i - candidate;
+    out.push(offset & 255, offset >> 8);
+    writeLz4Length(out, matchLength);
+
+    i += length;
+    anchor = i;
+  }
+
+  // The last sequence of a block only holds literals.
+  const literals = data.length - anchor;
+  out.push(Math.min(literals, 15) << 4);
+  writeLz4Length(out, literals);
+  for (let k = anchor; k < data.length; k++) {
+    out.push(data[k]);
+  }
+
+  return new Uint8Array(out);
+}
+
+/**
+ * Writes the remainder of a literal or match length that did not fit in the
+ * token of an LZ4 sequence.
+ * @param out The compressed data written so far.
+ * @param length The full length, which fits in the token if it is below 15.
+ */
+function writeLz4Length(out: number[], length: number): void {
+  if (length < 15) {
+    return;
+  }
+
+  let rest = length - 15;
+  while (rest >= 255) {
+    out.push(255);
+    rest -= 255;
+  }
+  out.push(rest);
+}
+
+/**
+ * Decompresses an LZ4 block, prefixed by the size of the uncompressed data as a
+ * 32-bit little-endian integer.
+ * @param data The compressed data.
+ * @returns The decompressed data.
+ */
+export function decompressLz4(data: Uint8Array): Uint8Array {
+  const size =
+    (data[0] | (data[1] << 8) | (data[2] << 16) | (data[3] << 24)) >>> 0;
+  const out = new Uint8Array(size);
+
+  let i = 4;
+  let o = 0;
+  while (i < data.length) {
+    const token = data[i++];
+
+    let literals = token >> 4;
+    if (literals === 15) {
+      let byte;
+      do {
+        byte = data[i++];
+        literals += byte;
+      } while (byte === 255);
+    }
+
+    out.set(data.subarray(i, i + literals), o);
+    i += literals;
+    o += literals;
+
+    // The last sequence of a block only holds literals.
+    if (i >= data.length) break;
+
+    const offset = data[i] | (data[i + 1] << 8);
+    i += 2;
+
+    let length = token & 15;
+    if (length === 15) {
+      let byte;
+      do {
+        byte = data[i++];
+        length += byte;
+      } while (byte === 255);
+    }
+    length += 4;
+
+    // Matches may overlap their own output, so they are copied bytewise.
+    for (let k = 0; k < length; k++) {
+      out[o] = out[o - offset];
+      o++;
+    }
+  }
+
+  return out;
+}
+
+/**
+ * Encodes a string as UTF-8.
+ * @param text The string to encode.
+ * @returns The UTF-8 bytes.
+ */
+function encodeUtf8(text: string): number[] {
+  const out: number[] = [];
+  for (const char of text) {
+    const code = char.codePointAt(0)!;
+    if (code < 0x80) {
+      out.push(code);
+    } else if (code < 0x800) {
+      out.push(0xc0 | (code >> 6), 0x80 | (code & 63));
+    } else if (code < 0x10000) {
+      out.push(
+        0xe0 | (code >> 12),
+        0x80 | ((code >> 6) & 63),
+        0x80 | (code & 63)
+      );
+    } else {
+      out.push(
+        0xf0 | (code >> 18),
+        0x80 | ((code >> 12) & 63),
+        0x80 | ((code >> 6) & 63),
+        0x80 | (code & 63)
+      );
+    }
+  }
+  return out;
+}
+
+/**
+ * Decodes UTF-8 bytes into a string.
+ * @param data The UTF-8 bytes.
+ * @returns The decoded string.
+ */
+function decodeUtf8(data: Uint8Array): string {
+  let out = "";
+  let i = 0;
+  while (i < data.length) {
+    const byte = data[i++];
+    let code;
+    if (byte < 0x80) {
+      code = byte;
+    } else if (byte < 0xe0) {
+      code = ((byte & 31) << 6) | (data[i++] & 63);
+    } else if (byte < 0xf0) {
+      code = ((byte & 15) << 12) | ((data[i++] & 63) << 6) | (data[i++] & 63);
+    } else {
+      code =
+        ((byte & 7) << 18) |
+        ((data[i++] & 63) << 12) |
+        ((data[i++] & 63) << 6) |
+        (data[i++] & 63);
+    }
+    out += String.fromCodePoint(code);
+  }
+  return out;
+}
+
+/**
+ * Writes JavaScript values as MessagePack data.
+ *
+ * Objects are written as maps of their enumerable properties, skipping
+ * properties that are undefined, the same way they are passed to the client as
+ * JSON.
+ */
+class MessagePackWriter {
+  /**
+   * The bytes written so far.
+   */
+  private bytes: number[] = [];
+
+  /**
+   * Writes a value.
+   * @param value The value to write.
+   */
+  public write(value: unknown): void {
+    if (value === null || value === undefined) {
+      this.bytes.push(0xc0);
+    } else if (typeof value === "boolean") {
+      this.bytes.push(value ? 0xc3 : 0xc2);
+    } else if (typeof value === "number") {
+      this.writeNumber(value);
+    } else if (typeof value === "string") {
+      const data = encodeUtf8(value);
+      this.writeHeader(data.length, 0xa0, 31, 0xd9, 0xda, 0xdb);
+      for (const byte of data) {
+        this.bytes.push(byte);
+      }
+    } else if (Array.isArray(value)) {
+      this.writeHeader(value.length, 0x90, 15, null, 0xdc, 0xdd);
+      for (const item of value) {
+        this.write(item);
+      }
+    } else if (typeof value === "object") {
+      const entries = Object.entries(value).filter(([, v]) => v !== undefined);
+      this.writeHeader(entries.length, 0x80, 15, null, 0xde, 0xdf);
+      for (const [key, item] of entries) {
+        this.write(key);
+        this.write(item);
+      }
+    } else {
+      throw new Error(`Cannot encode value of type ${typeof value}`);
+    }
+  }
+
+  /**
+   * Gets the bytes that have been written.
+   * @returns The MessagePack data.
+   */
+  public finish(): Uint8Array {
+    return new Uint8Array(this.bytes);
+  }
+
+  /**
+   * Writes a number, as an integer if it is a safe integer.
+   * @param value The number to write.
+   */
+  private writeNumber(value: number): void {
+    if (!Number.isSafeInteger(value)) {
+      const view = new DataView(new ArrayBuffer(8));
+      view.setFloat64(0, value);
+      this.bytes.push(0xcb, ...new Uint8Array(view.buffer));
+    } else if (value >= 0 && value < 128) {
+      this.bytes.push(value);
+    } else if (value < 0 && value >= -32) {
+      this.bytes.push(value & 255);
+    } else if (value >= 0 && value <= 0xffffffff) {
+      this.bytes.push(0xce);
+      this.writeUint32(value);
+    } else if (value < 0 && value >= -0x80000000) {
+      this.bytes.push(0xd2);
+      this.writeUint32(value >>> 0);
+    } else {
+      const view = new DataView(new ArrayBuffer(8));
+      if (value >= 0) {
+        view.setBigUint64(0, BigInt(value));
+        this.bytes.push(0xcf);
+      } else {
+        view.setBigInt64(0, BigInt(value));
+        this.bytes.push(0xd3);
+      }
+      this.bytes.push(...new Uint8Array(view.buffer));
+    }
+  }
+
+  /**
+   * Writes the header of a string, array or map of the given length.
+   * @param length The number of bytes or items.
+   * @param fix The tag of the compact form, which stores the length in its
+   * lower bits.
+   * @param fixMax The largest length that fits in the compact form.
+   * @param tag8 The tag of the form with an 8-bit length, if any.
+   * @param tag16 The tag of the form with a 16-bit length.
+   * @param tag32 The tag of the form with a 32-bit length.
+   */
+  private writeHeader(
+    length: number,
+    fix: number,
+    fixMax: number,
+    tag8: number | null,
+    tag16: number,
+    tag32: number
+  ): void {
+    if (length <= fixMax) {
+      this.bytes.push(fix | length);
+    } else if (tag8 !== null && length < 0x100) {
+      this.bytes.push(tag8, length);
+    } else if (length < 0x10000) {
+      this.bytes.push(tag16, length >> 8, length & 255);
+    } else {
+      this.bytes.push(tag32);
+      this.writeUint32(length);
+    }
+  }
+
+  /**
+   * Writes a big-endian 32-bit unsigned integer.
+   * @param value The integer to write.
+   */
+  private writeUint32(value: number): void {
+    this.bytes.push(
+      (value >>> 24) & 255,
+      (value >>> 16) & 255,
+      (value >>> 8) & 255,
+      value & 255
+    );
+  }
+}
+
+/**
+ * Reads JavaScript values from MessagePack data.
+ */
+class MessagePackReader {
+  /**
+   * A view over the data being read.
+   */
+  private view: DataView;
+
+  /**
+   * The offset of the next byte to read.
+   */
+  private offset = 0;
+
+  /**
+   * Creates a new reader over the given data.
+   * @param data The MessagePack data.
+   */
+  public constructor(private data: Uint8Array) {
+    this.view = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  }
+
+  /**
+   * Reads the next value.
+   * @returns The value that was read.
+   */
+  public read(): any {
+    const tag = this.data[this.offset++];
+
+    if (tag < 0x80) return tag;
+    if (tag >= 0xe0) return tag - 0x100;
+    if ((tag & 0xf0) === 0x80) return this.readMap(tag & 15);
+    if ((tag & 0xf0) === 0x90) return this.readArray(tag & 15);
+    if ((tag & 0xe0) === 0xa0) return this.readString(tag & 31);
+
+    switch (tag) {
+      case 0xc0:
+        return null;
+      case 0xc2:
+        return false;
+      case 0xc3:
+        return true;
+      case 0xc4:
+        return this.readBytes(this.readUint(1));
+      case 0xc5:
+        return this.readBytes(this.readUint(2));
+      case 0xc6:
+        return this.readBytes(this.readUint(4));
+      case 0xca:
+        return this.advance(4, this.view.getFloat32(this.offset));
+      case 0xcb:
+        return this.advance(8, this.view.getFloat64(this.offset));
+      case 0xcc:
+        return this.readUint(1);
+      case 0xcd:
+        return this.readUint(2);
+      case 0xce:
+        return this.readUint(4);
+      case 0xcf:
+        return this.advance(8, Number(this.view.getBigUint64(this.offset)));
+      case 0xd0:
+        return this.advance(1, this.view.getInt8(this.offset));
+      case 0xd1:
+        return this.advance(2, this.view.getInt16(this.offset));
+      case 0xd2:
+        return this.advance(4, this.view.getInt32(this.offset));
+      case 0xd3:
+        return this.advance(8, Number(this.view.getBigInt64(this.offset)));
+      case 0xd9:
+        return this.readString(this.readUint(1));
+      case 0xda:
+        return this.readString(this.readUint(2));
+      case 0xdb:
+        return this.readString(this.readUint(4));
+      case 0xdc:
+        return this.readArray(this.readUint(2));
+      case 0xdd:
+        return this.readArray(this.readUint(4));
+      case 0xde:
+        return this.readMap(this.readUint(2));
+      case 0xdf:
+        return this.readMap(this.readUint(4));
+      default:
+        throw new Error(`Unsupported MessagePack tag: 0x${tag.toString(16)}`);
+    }
+  }
+
+  /**
+   * Advances the reader by the given number of bytes.
+   * @param size The number of bytes that were read.
+   * @param value The value that was read.
+   * @returns The value that was read.
+   */
+  private advance<T>(size: number, value: T): T {
+    this.offset += size;
+    return value;
+  }
+
+  /**
+   * Reads a big-endian unsigned integer of the given size.
+   * @param size The size of the integer in bytes.
+   * @returns The integer that was read.
+   */
+  private readUint(size: 1 | 2 | 4): number {
+    switch (size) {
+      case 1:
+        return this.advance(1, this.view.getUint8(this.offset));
+      case 2:
+        return this.advance(2, this.view.getUint16(this.offset));
+      case 4:
+        return this.advance(4, this.view.getUint32(this.offset));
+    }
+  }
+
+  /**
+   * Reads the given number of raw bytes.
+   * @param length The number of bytes.
+   * @returns The bytes that were read.
+   */
+  private readBytes(length: number): Uint8Array {
+    return this.advance(
+      length,
+      this.data.slice(this.offset, this.offset + length)
+    );
+  }
+
+  /**
+   * Reads a UTF-8 string of the given length in bytes.
+   * @param length The number of bytes.
+   * @returns The string that was read.
+   */
+  private readString(length: number): string {
+    return decodeUtf8(this.readBytes(length));
+  }
+
+  /**
+   * Reads an array with the given number of items.
+   * @param length The number of items.
+   * @returns The array that was read.
+   */
+  private readArray(length: number): any[] {
+    const out = [];
+    for (let i = 0; i < length; i++) {
+      out.push(this.read());
+    }
+    return out;
+  }
+
+  /**
+   * Reads a map with the given number of entries as an object.
+   * @param length The number of entries.
+   * @returns The object that was read.
+   */
+  private readMap(length: number): Record<string, any> {
+    const out: Record<string, any> = {};
+    for (let i = 0; i < length; i++) {
+      const key = this.read();
+      out[String(key)] = this.read();
+    }
+    return out;
+  }
+}
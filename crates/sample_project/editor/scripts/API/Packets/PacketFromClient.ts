@@ -1,3 +1,12 @@
+import { Area } from "../Area.ts";
+import { BlockModel, NamedBlockModel } from "../BlockModel.ts";
+import { Bookmark } from "../Bookmark.ts";
+import { PlacedLight } from "../Light.ts";
+import { MapPatch } from "../MapPatch.ts";
+import { NetMessage } from "../Net.ts";
+import { BlockHit } from "../Raycast.ts";
+import { ChunkPos, Vec3, WorldPos } from "../Units.ts";
+
 /**
  * A packet that contains a shutdown request.
  */
@@ -24,7 +33,728 @@ export interface FileDrop {
   path: string;
 }
 
+/**
+ * A packet that is sent when a file dialog opened with a "requestFileDialog"
+ * packet has been closed.
+ */
+export interface FileDialogClosed {
+  /**
+   * The type of the packet, which is "fileDialogClosed" in this case.
+   */
+  type: "fileDialogClosed";
+
+  /**
+   * The ID given in the request.
+   */
+  id: string;
+
+  /**
+   * The OS file path of the chosen file, or null if the dialog was cancelled
+   * or could not be opened.
+   */
+  path: string | null;
+}
+
+/**
+ * A packet that starts a new tick in fixed-tick mode. A "tickDone" packet must
+ * be sent once the tick has been processed.
+ */
+export interface Tick {
+  /**
+   * The type of the packet, which is "tick" in this case.
+   */
+  type: "tick";
+
+  /**
+   * The tick number, starting at 1 and increasing by one each tick.
+   */
+  tick: number;
+}
+
+/**
+ * Information about a save slot.
+ */
+export interface SaveInfo {
+  /**
+   * The name of the save slot.
+   */
+  slot: string;
+
+  /**
+   * The time the save was last written, in seconds since the Unix epoch.
+   */
+  modified: number;
+}
+
+/**
+ * A packet that contains the list of save slots, sent in response to a
+ * "listSaves" packet.
+ */
+export interface SaveList {
+  /**
+   * The type of the packet, which is "saveList" in this case.
+   */
+  type: "saveList";
+
+  /**
+   * The available save slots, sorted by name.
+   */
+  saves: SaveInfo[];
+}
+
+/**
+ * A packet that is sent when a save slot has been written successfully.
+ */
+export interface SaveCreated {
+  /**
+   * The type of the packet, which is "saveCreated" in this case.
+   */
+  type: "saveCreated";
+
+  /**
+   * The name of the save slot.
+   */
+  slot: string;
+}
+
+/**
+ * A packet that is sent when a save slot has been loaded successfully.
+ */
+export interface SaveLoaded {
+  /**
+   * The type of the packet, which is "saveLoaded" in this case.
+   */
+  type: "saveLoaded";
+
+  /**
+   * The name of the save slot.
+   */
+  slot: string;
+
+  /**
+   * The data that was stored with the save.
+   */
+  data: unknown;
+}
+
+/**
+ * A packet that is sent when a save slot has been deleted successfully.
+ */
+export interface SaveDeleted {
+  /**
+   * The type of the packet, which is "saveDeleted" in this case.
+   */
+  type: "saveDeleted";
+
+  /**
+   * The name of the save slot.
+   */
+  slot: string;
+}
+
+/**
+ * A packet that is sent when a save operation has failed.
+ */
+export interface SaveFailed {
+  /**
+   * The type of the packet, which is "saveFailed" in this case.
+   */
+  type: "saveFailed";
+
+  /**
+   * The name of the save slot.
+   */
+  slot: string;
+
+  /**
+   * The reason the operation failed.
+   */
+  error: string;
+}
+
+/**
+ * A packet that contains the result of comparing two map snapshots.
+ */
+export interface MapDiff {
+  /**
+   * The type of the packet, which is "mapDiff" in this case.
+   */
+  type: "mapDiff";
+
+  /**
+   * The name of the snapshot compared from.
+   */
+  from: string;
+
+  /**
+   * The name of the snapshot compared to, or null if the snapshot was compared
+   * to the current state of the map.
+   */
+  to: string | null;
+
+  /**
+   * The patch that transforms the first snapshot into the second.
+   */
+  patch: MapPatch;
+}
+
+/**
+ * A packet that is sent when a requested map snapshot does not exist.
+ */
+export interface SnapshotNotFound {
+  /**
+   * The type of the packet, which is "snapshotNotFound" in this case.
+   */
+  type: "snapshotNotFound";
+
+  /**
+   * The name of the snapshot.
+   */
+  name: string;
+}
+
+/**
+ * A packet that contains the bookmarks stored in the game database, sorted by
+ * name.
+ */
+export interface BookmarkList {
+  /**
+   * The type of the packet, which is "bookmarkList" in this case.
+   */
+  type: "bookmarkList";
+
+  /**
+   * The stored bookmarks.
+   */
+  bookmarks: Bookmark[];
+}
+
+/**
+ * A packet that is sent when a bookmark operation has failed.
+ */
+export interface BookmarkFailed {
+  /**
+   * The type of the packet, which is "bookmarkFailed" in this case.
+   */
+  type: "bookmarkFailed";
+
+  /**
+   * The name of the bookmark.
+   */
+  name: string;
+
+  /**
+   * The reason the operation failed.
+   */
+  error: string;
+}
+
+/**
+ * A packet that contains the block models stored in the project database,
+ * sorted by name.
+ */
+export interface BlockModelList {
+  /**
+   * The type of the packet, which is "blockModelList" in this case.
+   */
+  type: "blockModelList";
+
+  /**
+   * The stored block models.
+   */
+  models: NamedBlockModel[];
+}
+
+/**
+ * A packet that is sent when a block model operation has failed.
+ */
+export interface BlockModelFailed {
+  /**
+   * The type of the packet, which is "blockModelFailed" in this case.
+   */
+  type: "blockModelFailed";
+
+  /**
+   * The name of the block model.
+   */
+  name: string;
+
+  /**
+   * The reason the operation failed.
+   */
+  error: string;
+}
+
+/**
+ * A packet that contains the areas stored in the game database, sorted by
+ * name.
+ */
+export interface AreaList {
+  /**
+   * The type of the packet, which is "areaList" in this case.
+   */
+  type: "areaList";
+
+  /**
+   * The stored areas.
+   */
+  areas: Area[];
+}
+
+/**
+ * A packet that contains the areas that contain a block, sorted by name.
+ */
+export interface AreasAt {
+  /**
+   * The type of the packet, which is "areasAt" in this case.
+   */
+  type: "areasAt";
+
+  /**
+   * The position of the block.
+   */
+  pos: WorldPos;
+
+  /**
+   * The areas containing the block.
+   */
+  areas: Area[];
+}
+
+/**
+ * A packet that is sent when an area operation has failed.
+ */
+export interface AreaFailed {
+  /**
+   * The type of the packet, which is "areaFailed" in this case.
+   */
+  type: "areaFailed";
+
+  /**
+   * The name of the area.
+   */
+  name: string;
+
+  /**
+   * The reason the operation failed.
+   */
+  error: string;
+}
+
+/**
+ * A packet that contains the lights placed in the world, sorted by name.
+ */
+export interface LightList {
+  /**
+   * The type of the packet, which is "lightList" in this case.
+   */
+  type: "lightList";
+
+  /**
+   * The placed lights.
+   */
+  lights: PlacedLight[];
+}
+
+/**
+ * A packet that is sent when a light operation has failed.
+ */
+export interface LightFailed {
+  /**
+   * The type of the packet, which is "lightFailed" in this case.
+   */
+  type: "lightFailed";
+
+  /**
+   * The name of the light.
+   */
+  name: string;
+
+  /**
+   * The reason the operation failed.
+   */
+  error: string;
+}
+
+/**
+ * A packet that contains the block model at a world position, sent in response
+ * to a get block request. Blocks in chunks that do not exist are empty.
+ */
+export interface Block {
+  /**
+   * The type of the packet, which is "block" in this case.
+   */
+  type: "block";
+
+  /**
+   * The world position.
+   */
+  pos: WorldPos;
+
+  /**
+   * The block model.
+   */
+  model: BlockModel;
+}
+
+/**
+ * A packet that contains the result of a ray cast, sent in response to a
+ * raycast blocks request.
+ */
+export interface RaycastResult {
+  /**
+   * The type of the packet, which is "raycastResult" in this case.
+   */
+  type: "raycastResult";
+
+  /**
+   * The world position that the ray started from.
+   */
+  origin: Vec3;
+
+  /**
+   * The direction of the ray.
+   */
+  dir: Vec3;
+
+  /**
+   * The first non-empty block that the ray entered, or null if it did not hit
+   * any block within its maximum distance.
+   */
+  hit: BlockHit | null;
+}
+
+/**
+ * A packet that contains a summary of a chunk, sent in response to a get chunk
+ * summary request.
+ */
+export interface ChunkSummary {
+  /**
+   * The type of the packet, which is "chunkSummary" in this case.
+   */
+  type: "chunkSummary";
+
+  /**
+   * The chunk position.
+   */
+  pos: ChunkPos;
+
+  /**
+   * The world position of the block at the minimum corner of the chunk.
+   */
+  origin: WorldPos;
+
+  /**
+   * The number of blocks along each axis of the chunk.
+   */
+  size: number;
+
+  /**
+   * Whether the chunk exists. Chunks are created when the first block is
+   * placed in them, and are otherwise empty.
+   */
+  loaded: boolean;
+
+  /**
+   * The number of non-empty blocks in the chunk.
+   */
+  solidBlocks: number;
+}
+
+/**
+ * A packet that is sent when the player submits a line of text in the chat
+ * console.
+ */
+export interface ConsoleCommand {
+  /**
+   * The type of the packet, which is "consoleCommand" in this case.
+   */
+  type: "consoleCommand";
+
+  /**
+   * The submitted text.
+   */
+  text: string;
+}
+
+/**
+ * A packet that is sent when the player runs a command registered by a
+ * "registerCommand" packet from the editor command palette.
+ */
+export interface CommandInvoked {
+  /**
+   * The type of the packet, which is "commandInvoked" in this case.
+   */
+  type: "commandInvoked";
+
+  /**
+   * The ID of the command.
+   */
+  id: string;
+}
+
+/**
+ * A packet that is sent when the player deletes a sprite from the editor scene
+ * outliner.
+ */
+export interface SpriteDeleted {
+  /**
+   * The type of the packet, which is "spriteDeleted" in this case.
+   */
+  type: "spriteDeleted";
+
+  /**
+   * The ID of the deleted sprite.
+   */
+  id: string;
+}
+
+/**
+ * A packet that is sent when the player duplicates a sprite from the editor
+ * scene outliner.
+ */
+export interface SpriteDuplicated {
+  /**
+   * The type of the packet, which is "spriteDuplicated" in this case.
+   */
+  type: "spriteDuplicated";
+
+  /**
+   * The ID of the duplicated sprite.
+   */
+  source: string;
+
+  /**
+   * The ID of the new sprite.
+   */
+  id: string;
+}
+
+/**
+ * A mouse button that clicked a sprite.
+ */
+export type ClickButton = "left" | "right" | "middle";
+
+/**
+ * A packet that is sent when the player clicks a sprite that is clickable.
+ */
+export interface EntityClicked {
+  /**
+   * The type of the packet, which is "entityClicked" in this case.
+   */
+  type: "entityClicked";
+
+  /**
+   * The ID of the clicked sprite.
+   */
+  id: string;
+
+  /**
+   * The mouse button that was pressed.
+   */
+  button: ClickButton;
+
+  /**
+   * The world position on the sprite that was clicked.
+   */
+  hitPos: Vec3;
+}
+
+/**
+ * A packet that is sent when the cursor starts or stops hovering over a sprite
+ * that is hoverable.
+ */
+export interface EntityHoverChanged {
+  /**
+   * The type of the packet, which is "entityHoverChanged" in this case.
+   */
+  type: "entityHoverChanged";
+
+  /**
+   * The ID of the sprite.
+   */
+  id: string;
+
+  /**
+   * Whether the cursor is now hovering over the sprite.
+   */
+  hovered: boolean;
+}
+
+/**
+ * How a watched asset changed.
+ */
+export type AssetChangeKind = "added" | "modified" | "removed";
+
+/**
+ * A packet that is sent when a project asset matching a path watched with a
+ * "watchAssets" packet is added, modified, or removed.
+ */
+export interface AssetChanged {
+  /**
+   * The type of the packet, which is "assetChanged" in this case.
+   */
+  type: "assetChanged";
+
+  /**
+   * The asset path of the changed asset, such as
+   * "project://tiles/grass.png".
+   */
+  id: string;
+
+  /**
+   * How the asset changed.
+   */
+  kind: AssetChangeKind;
+}
+
+/**
+ * A packet that is sent to a client when it has joined a network session.
+ */
+export interface NetJoined {
+  /**
+   * The type of the packet, which is "netJoined" in this case.
+   */
+  type: "netJoined";
+
+  /**
+   * The peer ID assigned to this client.
+   */
+  peer: number;
+}
+
+/**
+ * A packet that is sent to the host when a client has connected.
+ */
+export interface PeerConnected {
+  /**
+   * The type of the packet, which is "peerConnected" in this case.
+   */
+  type: "peerConnected";
+
+  /**
+   * The peer ID of the client.
+   */
+  peer: number;
+}
+
+/**
+ * A packet that is sent to the host when a client has disconnected.
+ */
+export interface PeerDisconnected {
+  /**
+   * The type of the packet, which is "peerDisconnected" in this case.
+   */
+  type: "peerDisconnected";
+
+  /**
+   * The peer ID of the client.
+   */
+  peer: number;
+}
+
+/**
+ * A packet that contains a message received from another peer. Messages from
+ * the host have a peer ID of 0.
+ */
+export interface NetReceived {
+  /**
+   * The type of the packet, which is "netReceived" in this case.
+   */
+  type: "netReceived";
+
+  /**
+   * The peer ID of the sender.
+   */
+  from: number;
+
+  /**
+   * The message.
+   */
+  message: NetMessage;
+}
+
+/**
+ * A packet that is sent to a client when its connection to the host was
+ * closed.
+ */
+export interface NetClosed {
+  /**
+   * The type of the packet, which is "netClosed" in this case.
+   */
+  type: "netClosed";
+
+  /**
+   * The reason the connection was closed, if it was closed due to an error.
+   */
+  error: string | null;
+}
+
+/**
+ * A packet that is sent when a networking operation has failed.
+ */
+export interface NetError {
+  /**
+   * The type of the packet, which is "netError" in this case.
+   */
+  type: "netError";
+
+  /**
+   * The reason the operation failed.
+   */
+  error: string;
+}
+
+/**
+ * The tick that every packet is stamped with in fixed-tick mode.
+ */
+export interface Stamped {
+  /**
+   * The tick that was active when the packet was sent. This is omitted outside
+   * of fixed-tick mode, and on "tick" packets, which carry their own tick.
+   */
+  tick?: number;
+}
+
 /**
  * A union type representing all packets that can be received from the client.
  */
-export type Any = Shutdown | FileDrop;
+export type Any = Stamped &
+  (
+    | Shutdown
+    | FileDrop
+    | FileDialogClosed
+    | Tick
+    | SaveList
+    | SaveCreated
+    | SaveLoaded
+    | SaveDeleted
+    | SaveFailed
+    | MapDiff
+    | SnapshotNotFound
+    | BookmarkList
+    | BookmarkFailed
+    | BlockModelList
+    | BlockModelFailed
+    | AreaList
+    | AreasAt
+    | AreaFailed
+    | LightList
+    | LightFailed
+    | Block
+    | RaycastResult
+    | ChunkSummary
+    | ConsoleCommand
+    | CommandInvoked
+    | SpriteDeleted
+    | SpriteDuplicated
+    | EntityClicked
+    | EntityHoverChanged
+    | AssetChanged
+    | NetJoined
+    | PeerConnected
+    | PeerDisconnected
+    | NetReceived
+    | NetClosed
+    | NetError
+  );
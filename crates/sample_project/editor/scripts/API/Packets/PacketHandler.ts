@@ -15,7 +15,10 @@ export async function handlePacket(
 ): Promise<void> {
   switch (packet.type) {
     case "shutdown":
-      Game.shutdown(false);
+      // Replying with a shutdown packet acknowledges that the scripts have
+      // finished cleaning up, allowing the client to exit.
+      await Game.emit("shutdown");
+      Game.shutdown();
       break;
 
     case "fileDrop":
@@ -28,5 +31,158 @@ export async function handlePacket(
         )
       );
       break;
+
+    case "fileDialogClosed":
+      await Game.emit("fileDialogClosed", packet.id, packet.path);
+      break;
+
+    case "tick":
+      await Game.emit("tick", packet.tick);
+      sendPackets(new PacketToClient.TickDone(packet.tick));
+      break;
+
+    case "saveList":
+      await Game.emit("saveList", packet.saves);
+      break;
+
+    case "saveCreated":
+      await Game.emit("saveCreated", packet.slot);
+      break;
+
+    case "saveLoaded":
+      await Game.emit("saveLoaded", packet.slot, packet.data);
+      break;
+
+    case "saveDeleted":
+      await Game.emit("saveDeleted", packet.slot);
+      break;
+
+    case "saveFailed":
+      console.error(
+        `Save operation on slot ${packet.slot} failed:`,
+        packet.error
+      );
+      await Game.emit("saveFailed", packet.slot, packet.error);
+      break;
+
+    case "mapDiff":
+      await Game.emit("mapDiff", packet.from, packet.to, packet.patch);
+      break;
+
+    case "snapshotNotFound":
+      console.error(`Map snapshot ${packet.name} does not exist.`);
+      await Game.emit("snapshotNotFound", packet.name);
+      break;
+
+    case "bookmarkList":
+      await Game.emit("bookmarkList", packet.bookmarks);
+      break;
+
+    case "bookmarkFailed":
+      console.error(
+        `Bookmark operation on ${packet.name} failed:`,
+        packet.error
+      );
+      await Game.emit("bookmarkFailed", packet.name, packet.error);
+      break;
+
+    case "blockModelList":
+      await Game.emit("blockModelList", packet.models);
+      break;
+
+    case "blockModelFailed":
+      console.error(
+        `Block model operation on ${packet.name} failed:`,
+        packet.error
+      );
+      await Game.emit("blockModelFailed", packet.name, packet.error);
+      break;
+
+    case "areaList":
+      await Game.emit("areaList", packet.areas);
+      break;
+
+    case "areasAt":
+      await Game.emit("areasAt", packet.pos, packet.areas);
+      break;
+
+    case "areaFailed":
+      console.error(`Area operation on ${packet.name} failed:`, packet.error);
+      await Game.emit("areaFailed", packet.name, packet.error);
+      break;
+
+    case "lightList":
+      await Game.emit("lightList", packet.lights);
+      break;
+
+    case "lightFailed":
+      console.error(`Light operation on ${packet.name} failed:`, packet.error);
+      await Game.emit("lightFailed", packet.name, packet.error);
+      break;
+
+    case "block":
+      await Game.emit("block", packet.pos, packet.model);
+      break;
+
+    case "raycastResult":
+      await Game.emit("raycastResult", packet.origin, packet.dir, packet.hit);
+      break;
+
+    case "chunkSummary":
+      await Game.emit("chunkSummary", packet);
+      break;
+
+    case "consoleCommand":
+      await Game.emit("consoleCommand", packet.text);
+      break;
+
+    case "commandInvoked":
+      await Game.emit("commandInvoked", packet.id);
+      break;
+
+    case "spriteDeleted":
+      await Game.emit("spriteDeleted", packet.id);
+      break;
+
+    case "spriteDuplicated":
+      await Game.emit("spriteDuplicated", packet.source, packet.id);
+      break;
+
+    case "entityClicked":
+      await Game.emit("entityClicked", packet.id, packet.button, packet.hitPos);
+      break;
+
+    case "entityHoverChanged":
+      await Game.emit("entityHoverChanged", packet.id, packet.hovered);
+      break;
+
+    case "assetChanged":
+      await Game.emit("assetChanged", packet.id, packet.kind);
+      break;
+
+    case "netJoined":
+      await Game.emit("netJoined", packet.peer);
+      break;
+
+    case "peerConnected":
+      await Game.emit("peerConnected", packet.peer);
+      break;
+
+    case "peerDisconnected":
+      await Game.emit("peerDisconnected", packet.peer);
+      break;
+
+    case "netReceived":
+      await Game.emit("netReceived", packet.from, packet.message);
+      break;
+
+    case "netClosed":
+      await Game.emit("netClosed", packet.error);
+      break;
+
+    case "netError":
+      console.error("Networking error:", packet.error);
+      await Game.emit("netError", packet.error);
+      break;
   }
 }
@@ -1,5 +1,12 @@
+import { Area } from "../Area.ts";
+import { AtmosphereSettings } from "../Atmosphere.ts";
 import { BlockModel } from "../BlockModel.ts";
-import { WorldPos } from "../Units.ts";
+import { Bookmark } from "../Bookmark.ts";
+import { PerformanceBudgets } from "../Budgets.ts";
+import { BlockRegion, MapPatch } from "../MapPatch.ts";
+import { NetMessage } from "../Net.ts";
+import { ChunkPos, Size2, Vec3, WorldPos } from "../Units.ts";
+import { PacketEncoding } from "./Codec.ts";
 
 /**
  * A packet that initializes the script engine with a name. This packet should
@@ -28,15 +35,22 @@ export class Init {
    */
   public version: string;
 
+  /**
+   * The encoding to use for large packets from now on. Defaults to JSON.
+   */
+  public encoding?: PacketEncoding;
+
   /**
    * Creates a new initialization packet.
    *
    * @param name The name of the game.
    * @param version The version of the game engine.
+   * @param encoding The encoding to use for large packets.
    */
-  public constructor(name: string, version: string) {
+  public constructor(name: string, version: string, encoding?: PacketEncoding) {
     this.name = name;
     this.version = version;
+    this.encoding = encoding;
   }
 }
 
@@ -83,6 +97,64 @@ export class ImportAsset {
   }
 }
 
+/**
+ * A filter for the files shown in a file dialog, matching the files with any
+ * of the given extensions.
+ */
+export interface FileFilter {
+  /**
+   * The name of the filter, such as "Images".
+   */
+  name: string;
+
+  /**
+   * The file extensions matched by the filter, without the leading dot, such
+   * as "png".
+   */
+  extensions: string[];
+}
+
+/**
+ * A packet that opens a native file dialog, so that the user can choose a file
+ * to import. The client sends a "fileDialogClosed" packet once the dialog is
+ * closed. File dialogs can only be opened in editor mode.
+ */
+export class RequestFileDialog {
+  /**
+   * The type of the packet, which is always "requestFileDialog" for this
+   * packet.
+   */
+  public readonly type: "requestFileDialog" = "requestFileDialog";
+
+  /**
+   * A script-defined ID that is sent back with the result.
+   */
+  public id: string;
+
+  /**
+   * The title of the dialog window.
+   */
+  public title?: string;
+
+  /**
+   * The filters limiting which files may be chosen. If empty, all files are
+   * shown.
+   */
+  public filters?: FileFilter[];
+
+  /**
+   * Creates a new request file dialog packet.
+   * @param id A script-defined ID that is sent back with the result.
+   * @param title The title of the dialog window.
+   * @param filters The filters limiting which files may be chosen.
+   */
+  public constructor(id: string, title?: string, filters?: FileFilter[]) {
+    this.id = id;
+    this.title = title;
+    this.filters = filters;
+  }
+}
+
 /**
  * A packet that contains a request to create a tileset from a set of tile
  * assets. Each tile asset must be a square image with a size that is a power
@@ -108,16 +180,28 @@ export class CreateTileset {
    */
   public outputPath: string;
 
+  /**
+   * If true, mipmaps are generated with a faster filter that averages raw sRGB
+   * values, rather than the default gamma-correct filter.
+   */
+  public fastMipmaps?: boolean;
+
   /**
    * Creates a new create tileset packet.
    * @param tilePaths An array of strings representing the paths of the tiles
    * that should be included in the tileset.
    * @param outputPath The path where the tileset should be stored in the game
    * assets. This must be a valid asset path.
+   * @param fastMipmaps Whether to generate mipmaps with the faster filter.
    */
-  public constructor(tilePaths: string[], outputPath: string) {
+  public constructor(
+    tilePaths: string[],
+    outputPath: string,
+    fastMipmaps?: boolean,
+  ) {
     this.tilePaths = tilePaths;
     this.outputPath = outputPath;
+    this.fastMipmaps = fastMipmaps;
   }
 }
 
@@ -177,6 +261,1179 @@ export class SetBlock {
   }
 }
 
+/**
+ * A packet that requests the block model at a world position. The engine
+ * responds with a block packet.
+ */
+export class GetBlock {
+  /**
+   * The type of the packet, which is always "getBlock" for this packet.
+   */
+  public readonly type: "getBlock" = "getBlock";
+
+  /**
+   * The world position.
+   */
+  public pos: WorldPos;
+
+  /**
+   * Creates a new get block packet.
+   * @param pos The world position.
+   */
+  public constructor(pos: WorldPos) {
+    this.pos = pos;
+  }
+}
+
+/**
+ * A packet that casts a ray through the blocks of the map. The engine responds
+ * with a raycast result packet holding the first non-empty block that the ray
+ * enters, if any.
+ */
+export class RaycastBlocks {
+  /**
+   * The type of the packet, which is always "raycastBlocks" for this packet.
+   */
+  public readonly type: "raycastBlocks" = "raycastBlocks";
+
+  /**
+   * The world position that the ray starts from. A block the ray starts inside
+   * of is never hit.
+   */
+  public origin: Vec3;
+
+  /**
+   * The direction of the ray. It does not need to be normalized, but must not
+   * be zero.
+   */
+  public dir: Vec3;
+
+  /**
+   * The maximum distance, in blocks, that the ray travels. It must be between
+   * 0 and 65536.
+   */
+  public maxDist: number;
+
+  /**
+   * Creates a new raycast blocks packet.
+   * @param origin The world position that the ray starts from.
+   * @param dir The direction of the ray.
+   * @param maxDist The maximum distance, in blocks, that the ray travels.
+   */
+  public constructor(origin: Vec3, dir: Vec3, maxDist: number) {
+    this.origin = origin;
+    this.dir = dir;
+    this.maxDist = maxDist;
+  }
+}
+
+/**
+ * A packet that requests a summary of a chunk. The engine responds with a
+ * chunk summary packet.
+ */
+export class GetChunkSummary {
+  /**
+   * The type of the packet, which is always "getChunkSummary" for this packet.
+   */
+  public readonly type: "getChunkSummary" = "getChunkSummary";
+
+  /**
+   * The chunk position.
+   */
+  public pos: ChunkPos;
+
+  /**
+   * Creates a new get chunk summary packet.
+   * @param pos The chunk position.
+   */
+  public constructor(pos: ChunkPos) {
+    this.pos = pos;
+  }
+}
+
+/**
+ * A packet that finishes a tick in fixed-tick mode. All packets sent since the
+ * tick started are applied together once this packet is received.
+ */
+export class TickDone {
+  /**
+   * The type of the packet, which is always "tickDone" for this packet.
+   */
+  public readonly type: "tickDone" = "tickDone";
+
+  /**
+   * The tick that was finished.
+   */
+  public tick: number;
+
+  /**
+   * Creates a new tick done packet.
+   * @param tick The tick that was finished.
+   */
+  public constructor(tick: number) {
+    this.tick = tick;
+  }
+}
+
+/**
+ * A packet that requests a list of all save slots. The client responds with a
+ * "saveList" packet.
+ */
+export class ListSaves {
+  /**
+   * The type of the packet, which is always "listSaves" for this packet.
+   */
+  public readonly type: "listSaves" = "listSaves";
+}
+
+/**
+ * A packet that saves the current map state, along with the given data, to a
+ * save slot. Any existing save in the slot is replaced.
+ */
+export class CreateSave {
+  /**
+   * The type of the packet, which is always "createSave" for this packet.
+   */
+  public readonly type: "createSave" = "createSave";
+
+  /**
+   * The name of the save slot. May only contain letters, digits, underscores
+   * and hyphens.
+   */
+  public slot: string;
+
+  /**
+   * Arbitrary JSON data to store with the save.
+   */
+  public data: unknown;
+
+  /**
+   * Creates a new create save packet.
+   * @param slot The name of the save slot.
+   * @param data Arbitrary JSON data to store with the save.
+   */
+  public constructor(slot: string, data: unknown) {
+    this.slot = slot;
+    this.data = data;
+  }
+}
+
+/**
+ * A packet that replaces the current map state with the state stored in a save
+ * slot. The client responds with a "saveLoaded" packet.
+ */
+export class LoadSave {
+  /**
+   * The type of the packet, which is always "loadSave" for this packet.
+   */
+  public readonly type: "loadSave" = "loadSave";
+
+  /**
+   * The name of the save slot.
+   */
+  public slot: string;
+
+  /**
+   * Creates a new load save packet.
+   * @param slot The name of the save slot.
+   */
+  public constructor(slot: string) {
+    this.slot = slot;
+  }
+}
+
+/**
+ * A packet that deletes a save slot.
+ */
+export class DeleteSave {
+  /**
+   * The type of the packet, which is always "deleteSave" for this packet.
+   */
+  public readonly type: "deleteSave" = "deleteSave";
+
+  /**
+   * The name of the save slot.
+   */
+  public slot: string;
+
+  /**
+   * Creates a new delete save packet.
+   * @param slot The name of the save slot.
+   */
+  public constructor(slot: string) {
+    this.slot = slot;
+  }
+}
+
+/**
+ * A packet that prints a line of text to the player's chat console.
+ */
+export class ConsolePrint {
+  /**
+   * The type of the packet, which is always "consolePrint" for this packet.
+   */
+  public readonly type: "consolePrint" = "consolePrint";
+
+  /**
+   * The text to print.
+   */
+  public text: string;
+
+  /**
+   * Creates a new console print packet.
+   * @param text The text to print.
+   */
+  public constructor(text: string) {
+    this.text = text;
+  }
+}
+
+/**
+ * How a billboard sprite is oriented towards the camera. A "camera" sprite
+ * always faces the camera directly, while an "axisLocked" sprite stays upright
+ * and only rotates around the vertical axis.
+ */
+export type BillboardMode = "camera" | "axisLocked";
+
+/**
+ * The interaction events that a billboard sprite reports to the script.
+ */
+export interface SpriteInteractions {
+  /**
+   * Whether clicking the sprite sends an "entityClicked" packet.
+   */
+  clickable?: boolean;
+
+  /**
+   * Whether the cursor entering or leaving the sprite sends an
+   * "entityHoverChanged" packet.
+   */
+  hoverable?: boolean;
+}
+
+/**
+ * A packet that spawns a billboard sprite in the world. If a sprite with the
+ * same ID already exists, it is replaced.
+ */
+export class SpawnSprite {
+  /**
+   * The type of the packet, which is always "spawnSprite" for this packet.
+   */
+  public readonly type: "spawnSprite" = "spawnSprite";
+
+  /**
+   * The ID of the sprite.
+   */
+  public id: string;
+
+  /**
+   * The asset path of the sprite texture.
+   */
+  public texture: string;
+
+  /**
+   * The world position of the bottom center of the sprite.
+   */
+  public position: Vec3;
+
+  /**
+   * The width and height of the sprite, in blocks.
+   */
+  public size: Size2;
+
+  /**
+   * How the sprite is oriented towards the camera.
+   */
+  public mode: BillboardMode;
+
+  /**
+   * Whether clicking the sprite is reported to the script.
+   */
+  public clickable?: boolean;
+
+  /**
+   * Whether hovering over the sprite is reported to the script.
+   */
+  public hoverable?: boolean;
+
+  /**
+   * Creates a new spawn sprite packet.
+   * @param id The ID of the sprite.
+   * @param texture The asset path of the sprite texture.
+   * @param position The world position of the bottom center of the sprite.
+   * @param size The width and height of the sprite, in blocks.
+   * @param mode How the sprite is oriented towards the camera.
+   * @param interactions The interaction events that the sprite reports.
+   */
+  public constructor(
+    id: string,
+    texture: string,
+    position: Vec3,
+    size: Size2,
+    mode: BillboardMode,
+    interactions: SpriteInteractions = {}
+  ) {
+    this.id = id;
+    this.texture = texture;
+    this.position = position;
+    this.size = size;
+    this.mode = mode;
+    this.clickable = interactions.clickable;
+    this.hoverable = interactions.hoverable;
+  }
+}
+
+/**
+ * The properties of a billboard sprite that may be changed after it has been
+ * spawned.
+ */
+export interface SpriteChanges extends SpriteInteractions {
+  /**
+   * The asset path of the new sprite texture.
+   */
+  texture?: string;
+
+  /**
+   * The new world position of the bottom center of the sprite.
+   */
+  position?: Vec3;
+
+  /**
+   * The new width and height of the sprite, in blocks.
+   */
+  size?: Size2;
+
+  /**
+   * The new orientation mode of the sprite.
+   */
+  mode?: BillboardMode;
+}
+
+/**
+ * A packet that updates an existing billboard sprite. Omitted properties are
+ * left unchanged.
+ */
+export class UpdateSprite {
+  /**
+   * The type of the packet, which is always "updateSprite" for this packet.
+   */
+  public readonly type: "updateSprite" = "updateSprite";
+
+  /**
+   * The ID of the sprite.
+   */
+  public id: string;
+
+  /**
+   * The asset path of the new sprite texture.
+   */
+  public texture?: string;
+
+  /**
+   * The new world position of the bottom center of the sprite.
+   */
+  public position?: Vec3;
+
+  /**
+   * The new width and height of the sprite, in blocks.
+   */
+  public size?: Size2;
+
+  /**
+   * The new orientation mode of the sprite.
+   */
+  public mode?: BillboardMode;
+
+  /**
+   * Whether clicking the sprite is reported to the script.
+   */
+  public clickable?: boolean;
+
+  /**
+   * Whether hovering over the sprite is reported to the script.
+   */
+  public hoverable?: boolean;
+
+  /**
+   * Creates a new update sprite packet.
+   * @param id The ID of the sprite.
+   * @param changes The properties to change.
+   */
+  public constructor(id: string, changes: SpriteChanges) {
+    this.id = id;
+    this.texture = changes.texture;
+    this.position = changes.position;
+    this.size = changes.size;
+    this.mode = changes.mode;
+    this.clickable = changes.clickable;
+    this.hoverable = changes.hoverable;
+  }
+}
+
+/**
+ * A packet that removes a billboard sprite from the world.
+ */
+export class DespawnSprite {
+  /**
+   * The type of the packet, which is always "despawnSprite" for this packet.
+   */
+  public readonly type: "despawnSprite" = "despawnSprite";
+
+  /**
+   * The ID of the sprite.
+   */
+  public id: string;
+
+  /**
+   * Creates a new despawn sprite packet.
+   * @param id The ID of the sprite.
+   */
+  public constructor(id: string) {
+    this.id = id;
+  }
+}
+
+/**
+ * A packet that captures a snapshot of the map, which may later be compared
+ * against another snapshot. Snapshots are only kept for the current session.
+ */
+export class TakeSnapshot {
+  /**
+   * The type of the packet, which is always "takeSnapshot" for this packet.
+   */
+  public readonly type: "takeSnapshot" = "takeSnapshot";
+
+  /**
+   * The name of the snapshot.
+   */
+  public name: string;
+
+  /**
+   * The region of the map to capture. If omitted, the whole map is captured.
+   */
+  public region?: BlockRegion;
+
+  /**
+   * Creates a new take snapshot packet.
+   * @param name The name of the snapshot.
+   * @param region The region of the map to capture.
+   */
+  public constructor(name: string, region?: BlockRegion) {
+    this.name = name;
+    this.region = region;
+  }
+}
+
+/**
+ * A packet that removes a stored map snapshot.
+ */
+export class DropSnapshot {
+  /**
+   * The type of the packet, which is always "dropSnapshot" for this packet.
+   */
+  public readonly type: "dropSnapshot" = "dropSnapshot";
+
+  /**
+   * The name of the snapshot.
+   */
+  public name: string;
+
+  /**
+   * Creates a new drop snapshot packet.
+   * @param name The name of the snapshot.
+   */
+  public constructor(name: string) {
+    this.name = name;
+  }
+}
+
+/**
+ * A packet that compares two map snapshots. The client replies with a
+ * "mapDiff" packet containing the patch between them.
+ */
+export class DiffSnapshots {
+  /**
+   * The type of the packet, which is always "diffSnapshots" for this packet.
+   */
+  public readonly type: "diffSnapshots" = "diffSnapshots";
+
+  /**
+   * The name of the snapshot to compare from.
+   */
+  public from: string;
+
+  /**
+   * The name of the snapshot to compare to. If omitted, the current state of
+   * the map is used.
+   */
+  public to?: string;
+
+  /**
+   * Creates a new diff snapshots packet.
+   * @param from The name of the snapshot to compare from.
+   * @param to The name of the snapshot to compare to.
+   */
+  public constructor(from: string, to?: string) {
+    this.from = from;
+    this.to = to;
+  }
+}
+
+/**
+ * A packet that applies a map patch, setting each block it contains.
+ */
+export class ApplyPatch {
+  /**
+   * The type of the packet, which is always "applyPatch" for this packet.
+   */
+  public readonly type: "applyPatch" = "applyPatch";
+
+  /**
+   * The patch to apply.
+   */
+  public patch: MapPatch;
+
+  /**
+   * Creates a new apply patch packet.
+   * @param patch The patch to apply.
+   */
+  public constructor(patch: MapPatch) {
+    this.patch = patch;
+  }
+}
+
+/**
+ * A packet that requests the list of bookmarks stored in the game database.
+ * The client will respond with a "bookmarkList" packet.
+ */
+export class ListBookmarks {
+  /**
+   * The type of the packet, which is always "listBookmarks" for this packet.
+   */
+  public readonly type: "listBookmarks" = "listBookmarks";
+}
+
+/**
+ * A packet that stores a bookmark in the game database, replacing any existing
+ * bookmark with the same name. The client will respond with a "bookmarkList"
+ * packet on success, or a "bookmarkFailed" packet on failure.
+ */
+export class SetBookmark {
+  /**
+   * The type of the packet, which is always "setBookmark" for this packet.
+   */
+  public readonly type: "setBookmark" = "setBookmark";
+
+  /**
+   * The bookmark to store.
+   */
+  public bookmark: Bookmark;
+
+  /**
+   * Creates a new set bookmark packet.
+   * @param bookmark The bookmark to store.
+   */
+  public constructor(bookmark: Bookmark) {
+    this.bookmark = bookmark;
+  }
+}
+
+/**
+ * A packet that deletes a bookmark from the game database. The client will
+ * respond with a "bookmarkList" packet on success, or a "bookmarkFailed" packet
+ * on failure.
+ */
+export class DeleteBookmark {
+  /**
+   * The type of the packet, which is always "deleteBookmark" for this packet.
+   */
+  public readonly type: "deleteBookmark" = "deleteBookmark";
+
+  /**
+   * The name of the bookmark.
+   */
+  public name: string;
+
+  /**
+   * Creates a new delete bookmark packet.
+   * @param name The name of the bookmark.
+   */
+  public constructor(name: string) {
+    this.name = name;
+  }
+}
+
+/**
+ * A packet that moves the camera to a bookmark stored in the game database.
+ * The client will respond with a "bookmarkFailed" packet if the bookmark does
+ * not exist.
+ */
+export class JumpToBookmark {
+  /**
+   * The type of the packet, which is always "jumpToBookmark" for this packet.
+   */
+  public readonly type: "jumpToBookmark" = "jumpToBookmark";
+
+  /**
+   * The name of the bookmark.
+   */
+  public name: string;
+
+  /**
+   * Creates a new jump to bookmark packet.
+   * @param name The name of the bookmark.
+   */
+  public constructor(name: string) {
+    this.name = name;
+  }
+}
+
+/**
+ * A packet that requests the list of block models stored in the game
+ * database. The client will respond with a "blockModelList" packet.
+ */
+export class ListBlockModels {
+  /**
+   * The type of the packet, which is always "listBlockModels" for this packet.
+   */
+  public readonly type: "listBlockModels" = "listBlockModels";
+}
+
+/**
+ * A packet that sets the block at the given position to a block model stored
+ * in the game database. The client will respond with a "blockModelFailed"
+ * packet if the block model does not exist.
+ */
+export class PlaceBlockModel {
+  /**
+   * The type of the packet, which is always "placeBlockModel" for this packet.
+   */
+  public readonly type: "placeBlockModel" = "placeBlockModel";
+
+  /**
+   * The position of the block in the game world.
+   */
+  public pos: WorldPos;
+
+  /**
+   * The name of the block model.
+   */
+  public name: string;
+
+  /**
+   * Creates a new place block model packet.
+   * @param position The position of the block in the game world.
+   * @param name The name of the block model.
+   */
+  public constructor(position: WorldPos, name: string) {
+    this.pos = position;
+    this.name = name;
+  }
+}
+
+/**
+ * A packet that enables or disables camera collision, which pulls the camera
+ * in front of terrain that blocks its view.
+ */
+export class SetCameraCollision {
+  /**
+   * The type of the packet, which is always "setCameraCollision" for this
+   * packet.
+   */
+  public readonly type: "setCameraCollision" = "setCameraCollision";
+
+  /**
+   * Whether camera collision is enabled.
+   */
+  public enabled: boolean;
+
+  /**
+   * Creates a new set camera collision packet.
+   * @param enabled Whether camera collision is enabled.
+   */
+  public constructor(enabled: boolean) {
+    this.enabled = enabled;
+  }
+}
+
+/**
+ * How the camera projects the world onto the screen. An "orthographic" camera
+ * keeps objects the same size at any distance, while a "perspective" camera
+ * makes distant objects appear smaller.
+ */
+export type CameraProjection = "orthographic" | "perspective";
+
+/**
+ * A packet that switches the camera between an orthographic and a perspective
+ * projection, keeping the same focus point and framing.
+ */
+export class SetCameraProjection {
+  /**
+   * The type of the packet, which is always "setCameraProjection" for this
+   * packet.
+   */
+  public readonly type: "setCameraProjection" = "setCameraProjection";
+
+  /**
+   * The projection to use.
+   */
+  public projection: CameraProjection;
+
+  /**
+   * Creates a new set camera projection packet.
+   * @param projection The projection to use.
+   */
+  public constructor(projection: CameraProjection) {
+    this.projection = projection;
+  }
+}
+
+/**
+ * A packet that replaces the distance fog and the sky gradient of the terrain
+ * view until the game is closed. The settings stored in the project are not
+ * changed.
+ */
+export class SetAtmosphere {
+  /**
+   * The type of the packet, which is always "setAtmosphere" for this packet.
+   */
+  public readonly type: "setAtmosphere" = "setAtmosphere";
+
+  /**
+   * The new atmosphere settings.
+   */
+  public atmosphere: AtmosphereSettings;
+
+  /**
+   * Creates a new set atmosphere packet.
+   * @param atmosphere The new atmosphere settings.
+   */
+  public constructor(atmosphere: AtmosphereSettings) {
+    this.atmosphere = atmosphere;
+  }
+}
+
+/**
+ * A packet that sets the frame rate limits of the game window. The limits are
+ * stored in the game database, so they persist between launches.
+ */
+export class SetFrameRate {
+  /**
+   * The type of the packet, which is always "setFrameRate" for this packet.
+   */
+  public readonly type: "setFrameRate" = "setFrameRate";
+
+  /**
+   * The maximum frame rate while the window is focused. If omitted, the frame
+   * rate is not limited.
+   */
+  public targetFps?: number;
+
+  /**
+   * The maximum frame rate while the window is unfocused. If omitted, the
+   * current limit is kept.
+   */
+  public unfocusedFps?: number;
+
+  /**
+   * Creates a new set frame rate packet.
+   * @param targetFps The maximum frame rate while the window is focused.
+   * @param unfocusedFps The maximum frame rate while the window is unfocused.
+   */
+  public constructor(targetFps?: number, unfocusedFps?: number) {
+    this.targetFps = targetFps;
+    this.unfocusedFps = unfocusedFps;
+  }
+}
+
+/**
+ * A packet that sets the performance budgets that the editor warns about when
+ * they are exceeded. The budgets are stored in the game database, so they
+ * persist between launches.
+ */
+export class SetPerformanceBudgets {
+  /**
+   * The type of the packet, which is always "setPerformanceBudgets" for this
+   * packet.
+   */
+  public readonly type: "setPerformanceBudgets" = "setPerformanceBudgets";
+
+  /**
+   * The new performance budgets.
+   */
+  public budgets: PerformanceBudgets;
+
+  /**
+   * Creates a new set performance budgets packet.
+   * @param budgets The new performance budgets.
+   */
+  public constructor(budgets: PerformanceBudgets) {
+    this.budgets = budgets;
+  }
+}
+
+/**
+ * A packet that replaces the engine log filter. The filter uses the same syntax
+ * as `RUST_LOG`, such as "info,script=debug", and persists between launches.
+ */
+export class SetLogFilter {
+  /**
+   * The type of the packet, which is always "setLogFilter" for this packet.
+   */
+  public readonly type: "setLogFilter" = "setLogFilter";
+
+  /**
+   * The filter directives.
+   */
+  public filter: string;
+
+  /**
+   * Creates a new set log filter packet.
+   * @param filter The filter directives.
+   */
+  public constructor(filter: string) {
+    this.filter = filter;
+  }
+}
+
+/**
+ * A packet that requests the list of areas stored in the game database. The
+ * client will respond with an "areaList" packet.
+ */
+export class ListAreas {
+  /**
+   * The type of the packet, which is always "listAreas" for this packet.
+   */
+  public readonly type: "listAreas" = "listAreas";
+}
+
+/**
+ * A packet that stores an area in the game database, replacing any existing
+ * area with the same name. The client will respond with an "areaList" packet
+ * on success, or an "areaFailed" packet on failure.
+ */
+export class SetArea {
+  /**
+   * The type of the packet, which is always "setArea" for this packet.
+   */
+  public readonly type: "setArea" = "setArea";
+
+  /**
+   * The area to store.
+   */
+  public area: Area;
+
+  /**
+   * Creates a new set area packet.
+   * @param area The area to store.
+   */
+  public constructor(area: Area) {
+    this.area = area;
+  }
+}
+
+/**
+ * A packet that deletes an area from the game database. The client will
+ * respond with an "areaList" packet on success, or an "areaFailed" packet on
+ * failure.
+ */
+export class DeleteArea {
+  /**
+   * The type of the packet, which is always "deleteArea" for this packet.
+   */
+  public readonly type: "deleteArea" = "deleteArea";
+
+  /**
+   * The name of the area.
+   */
+  public name: string;
+
+  /**
+   * Creates a new delete area packet.
+   * @param name The name of the area.
+   */
+  public constructor(name: string) {
+    this.name = name;
+  }
+}
+
+/**
+ * A packet that requests the areas containing a block. The client will respond
+ * with an "areasAt" packet.
+ */
+export class GetAreasAt {
+  /**
+   * The type of the packet, which is always "getAreasAt" for this packet.
+   */
+  public readonly type: "getAreasAt" = "getAreasAt";
+
+  /**
+   * The position of the block.
+   */
+  public pos: WorldPos;
+
+  /**
+   * Creates a new get areas at packet.
+   * @param pos The position of the block.
+   */
+  public constructor(pos: WorldPos) {
+    this.pos = pos;
+  }
+}
+
+/**
+ * A packet that requests the list of lights placed in the world. The client
+ * will respond with a "lightList" packet.
+ */
+export class ListLights {
+  /**
+   * The type of the packet, which is always "listLights" for this packet.
+   */
+  public readonly type: "listLights" = "listLights";
+}
+
+/**
+ * A packet that switches a placed light on or off in the running scene. The
+ * change is not stored in the game database. The client will respond with a
+ * "lightList" packet on success, or a "lightFailed" packet on failure.
+ */
+export class SetLightEnabled {
+  /**
+   * The type of the packet, which is always "setLightEnabled" for this packet.
+   */
+  public readonly type: "setLightEnabled" = "setLightEnabled";
+
+  /**
+   * The name of the light.
+   */
+  public name: string;
+
+  /**
+   * Whether the light should be switched on.
+   */
+  public enabled: boolean;
+
+  /**
+   * Creates a new set light enabled packet.
+   * @param name The name of the light.
+   * @param enabled Whether the light should be switched on.
+   */
+  public constructor(name: string, enabled: boolean) {
+    this.name = name;
+    this.enabled = enabled;
+  }
+}
+
+/**
+ * A packet that registers a command in the editor command palette. When the
+ * player runs the command, the client will send a "commandInvoked" packet. If
+ * a command with the same ID is already registered, it is replaced.
+ */
+export class RegisterCommand {
+  /**
+   * The type of the packet, which is always "registerCommand" for this packet.
+   */
+  public readonly type: "registerCommand" = "registerCommand";
+
+  /**
+   * The unique ID of the command.
+   */
+  public id: string;
+
+  /**
+   * The display name of the command, which is searched in the palette.
+   */
+  public name: string;
+
+  /**
+   * A hint of the keyboard shortcut of the command, shown in the palette.
+   */
+  public shortcut?: string;
+
+  /**
+   * Creates a new register command packet.
+   * @param id The unique ID of the command.
+   * @param name The display name of the command.
+   * @param shortcut A hint of the keyboard shortcut of the command.
+   */
+  public constructor(id: string, name: string, shortcut?: string) {
+    this.id = id;
+    this.name = name;
+    this.shortcut = shortcut;
+  }
+}
+
+/**
+ * A packet that removes a command previously registered with a
+ * "registerCommand" packet from the editor command palette.
+ */
+export class UnregisterCommand {
+  /**
+   * The type of the packet, which is always "unregisterCommand" for this
+   * packet.
+   */
+  public readonly type: "unregisterCommand" = "unregisterCommand";
+
+  /**
+   * The ID of the command.
+   */
+  public id: string;
+
+  /**
+   * Creates a new unregister command packet.
+   * @param id The ID of the command.
+   */
+  public constructor(id: string) {
+    this.id = id;
+  }
+}
+
+/**
+ * A packet that subscribes to changes of the project assets at or within the
+ * given asset path, such as "project://tiles/" for every asset in a folder.
+ * The client sends an "assetChanged" packet whenever a matching asset is
+ * added, modified, or removed. Assets can only be watched in editor mode.
+ */
+export class WatchAssets {
+  /**
+   * The type of the packet, which is always "watchAssets" for this packet.
+   */
+  public readonly type: "watchAssets" = "watchAssets";
+
+  /**
+   * The asset path to watch, starting with "project://".
+   */
+  public path: string;
+
+  /**
+   * Creates a new watch assets packet.
+   * @param path The asset path to watch.
+   */
+  public constructor(path: string) {
+    this.path = path;
+  }
+}
+
+/**
+ * A packet that stops watching an asset path previously watched with a
+ * "watchAssets" packet.
+ */
+export class UnwatchAssets {
+  /**
+   * The type of the packet, which is always "unwatchAssets" for this packet.
+   */
+  public readonly type: "unwatchAssets" = "unwatchAssets";
+
+  /**
+   * The asset path to stop watching.
+   */
+  public path: string;
+
+  /**
+   * Creates a new unwatch assets packet.
+   * @param path The asset path to stop watching.
+   */
+  public constructor(path: string) {
+    this.path = path;
+  }
+}
+
+/**
+ * A packet that hosts a network session on the given port, closing any session
+ * that is already active. The client sends "peerConnected" packets as other
+ * instances join. Requires the "networking" feature.
+ */
+export class HostGame {
+  /**
+   * The type of the packet, which is always "hostGame" for this packet.
+   */
+  public readonly type: "hostGame" = "hostGame";
+
+  /**
+   * The TCP port to listen on.
+   */
+  public port: number;
+
+  /**
+   * Creates a new host game packet.
+   * @param port The TCP port to listen on.
+   */
+  public constructor(port: number) {
+    this.port = port;
+  }
+}
+
+/**
+ * A packet that joins the network session hosted at the given address,
+ * closing any session that is already active. The client sends a "netJoined"
+ * packet once the host has accepted the connection. Requires the "networking"
+ * feature.
+ */
+export class JoinGame {
+  /**
+   * The type of the packet, which is always "joinGame" for this packet.
+   */
+  public readonly type: "joinGame" = "joinGame";
+
+  /**
+   * The address of the host, such as "127.0.0.1:7777".
+   */
+  public address: string;
+
+  /**
+   * Creates a new join game packet.
+   * @param address The address of the host.
+   */
+  public constructor(address: string) {
+    this.address = address;
+  }
+}
+
+/**
+ * A packet that leaves the active network session. Requires the "networking"
+ * feature.
+ */
+export class LeaveGame {
+  /**
+   * The type of the packet, which is always "leaveGame" for this packet.
+   */
+  public readonly type: "leaveGame" = "leaveGame";
+}
+
+/**
+ * A packet that sends a message to other peers in the active network session.
+ * The host may send a message to a single client, or to all clients if no peer
+ * is given. Clients may only send messages to the host. Requires the
+ * "networking" feature.
+ */
+export class NetSend {
+  /**
+   * The type of the packet, which is always "netSend" for this packet.
+   */
+  public readonly type: "netSend" = "netSend";
+
+  /**
+   * The message to send.
+   */
+  public message: NetMessage;
+
+  /**
+   * The peer ID to send the message to.
+   */
+  public to?: number;
+
+  /**
+   * Creates a new net send packet.
+   * @param message The message to send.
+   * @param to The peer ID to send the message to, or undefined to send it to
+   * every peer.
+   */
+  public constructor(message: NetMessage, to?: number) {
+    this.message = message;
+    this.to = to;
+  }
+}
+
 /**
  * A union type representing all packets that can be sent to the client.
  */
@@ -184,6 +1441,49 @@ export type Any =
   | Init
   | Shutdown
   | ImportAsset
+  | RequestFileDialog
   | CreateTileset
   | SetTilesets
-  | SetBlock;
+  | SetBlock
+  | GetBlock
+  | RaycastBlocks
+  | GetChunkSummary
+  | TickDone
+  | ListSaves
+  | CreateSave
+  | LoadSave
+  | DeleteSave
+  | ConsolePrint
+  | SpawnSprite
+  | UpdateSprite
+  | DespawnSprite
+  | TakeSnapshot
+  | DropSnapshot
+  | DiffSnapshots
+  | ApplyPatch
+  | ListBookmarks
+  | SetBookmark
+  | DeleteBookmark
+  | JumpToBookmark
+  | ListBlockModels
+  | PlaceBlockModel
+  | SetCameraCollision
+  | SetCameraProjection
+  | SetAtmosphere
+  | SetFrameRate
+  | SetPerformanceBudgets
+  | SetLogFilter
+  | ListAreas
+  | SetArea
+  | DeleteArea
+  | GetAreasAt
+  | ListLights
+  | SetLightEnabled
+  | RegisterCommand
+  | UnregisterCommand
+  | WatchAssets
+  | UnwatchAssets
+  | HostGame
+  | JoinGame
+  | LeaveGame
+  | NetSend;
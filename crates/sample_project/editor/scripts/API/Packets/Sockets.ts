@@ -1,19 +1,40 @@
 import { Any as PacketToClient } from "./PacketToClient.ts";
 import { Any as PacketFromClient } from "./PacketFromClient.ts";
+import { decodePacket, encodePackets, PacketEncoding } from "./Codec.ts";
+
+/**
+ * The encoding used for large packets, as negotiated by the last init packet
+ * that was sent to the client.
+ */
+let encoding: PacketEncoding = "json";
 
 /**
  * Fetches the next packet from the client.
  * @returns A promise that resolves with the packet data.
  */
-export const fetchPacket = () =>
+export const fetchPacket = async (): Promise<PacketFromClient> =>
   // @ts-ignore
-  rustyscript.async_functions["fetchPacket"]() as PacketFromClient;
+  decodePacket(await Deno.core.ops.op_fetch_packet());
 
 /**
  * Sends packets to the client.
+ *
+ * Once the MessagePack encoding has been negotiated, large batches of packets
+ * are sent as a single binary frame, and the rest are sent as JSON.
  * @param packets - The packets to send to the client. Multiple packets can be
  * sent at once by passing them as separate arguments.
  */
-export const sendPackets = (...packets: PacketToClient[]): void =>
-  // @ts-ignore
-  rustyscript.functions["sendPackets"](...packets);
+export const sendPackets = (...packets: PacketToClient[]): void => {
+  for (const packet of packets) {
+    if (packet.type === "init") encoding = packet.encoding ?? "json";
+  }
+
+  const frame = encodePackets(packets, encoding);
+  if (frame) {
+    // @ts-ignore
+    Deno.core.ops.op_send_frame(frame);
+  } else {
+    // @ts-ignore
+    rustyscript.functions["sendPackets"](...packets);
+  }
+};
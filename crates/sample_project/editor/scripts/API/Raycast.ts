@@ -0,0 +1,26 @@
+import { WorldPos } from "./Units.ts";
+
+/**
+ * One of the six faces of a block.
+ */
+export type BlockFace = "posY" | "negY" | "posZ" | "negZ" | "posX" | "negX";
+
+/**
+ * The result of a successful block ray cast.
+ */
+export interface BlockHit {
+  /**
+   * The position of the block that was hit.
+   */
+  pos: WorldPos;
+
+  /**
+   * The face of the block that the ray entered through.
+   */
+  face: BlockFace;
+
+  /**
+   * The distance along the ray to the point where it entered the block.
+   */
+  distance: number;
+}
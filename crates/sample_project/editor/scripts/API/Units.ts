@@ -3,6 +3,22 @@
  */
 export type WorldPos = [x: number, y: number, z: number];
 
+/**
+ * The 3D position of a chunk, measured in chunks rather than blocks,
+ * represented as a tuple of three numbers [x, y, z].
+ */
+export type ChunkPos = [x: number, y: number, z: number];
+
+/**
+ * A 3D vector represented as a tuple of three numbers [x, y, z].
+ */
+export type Vec3 = [x: number, y: number, z: number];
+
+/**
+ * A 2D size represented as a tuple of two numbers [width, height].
+ */
+export type Size2 = [width: number, height: number];
+
 /**
  * A 2x2 matrix represented as a tuple of four numbers [m00, m01, m10, m11].
  */
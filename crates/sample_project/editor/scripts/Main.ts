@@ -45,5 +45,13 @@ export async function main() {
     sendPackets(...packets);
   });
 
-  await Game.start("Awgen Game Engine", "0.0.1");
+  Game.on("consoleCommand", async (text) => {
+    if (text.startsWith("/log ")) {
+      const filter = text.slice("/log ".length).trim();
+      Game.setLogFilter(filter);
+      Game.print(`Log filter set to "${filter}".`);
+    }
+  });
+
+  await Game.start("Awgen Game Engine", "0.0.1", "messagePack");
 }
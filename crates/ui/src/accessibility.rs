@@ -0,0 +1,302 @@
+//! This module implements accessibility options for themed widgets.
+//!
+//! The [`AccessibilityOptions`] resource adapts every themed container and text
+//! node as it is styled. Interaction states can be spread further apart so that
+//! hovered and pressed widgets are easier to tell apart, containers can be
+//! replaced by a high-contrast variant, and all colors can be adapted for a
+//! color vision deficiency. Whenever the options change, the colors of all
+//! themed widgets that are already open are styled again.
+//!
+//! The reduce motion option disables smooth color and scroll transitions, and
+//! the spinning of loading placeholders.
+
+use bevy::prelude::*;
+
+use crate::color::ColorSystems;
+use crate::contrast::contrast_ratio;
+use crate::interaction::InteractionReceiver;
+use crate::theme::{
+    ColorTheme,
+    ContainerTheme,
+    FontTheme,
+    apply_container_colors,
+    apply_text_color,
+};
+
+/// The amount that the background of a high-contrast container is mixed
+/// towards the opposite of its text color.
+const HIGH_CONTRAST_MIX: f32 = 0.5;
+
+/// The fraction of the color error lost to a color vision deficiency that is
+/// shifted into the color channels that remain distinguishable.
+const DALTONIZE_SHIFT: f32 = 0.7;
+
+/// Converts RGB colors to the LMS color space, which models the responses of
+/// the three types of cones of the eye.
+const RGB_TO_LMS: Mat3 = Mat3::from_cols(
+    Vec3::new(17.8824, 3.45565, 0.0299566),
+    Vec3::new(43.5161, 27.1554, 0.184309),
+    Vec3::new(4.11935, 3.86714, 1.46709),
+);
+
+/// Converts colors from the LMS color space back to RGB.
+const LMS_TO_RGB: Mat3 = Mat3::from_cols(
+    Vec3::new(0.08094445, -0.01024853, -0.0003652969),
+    Vec3::new(-0.1305044, 0.05401933, -0.004121615),
+    Vec3::new(0.1167211, -0.1136147, 0.6935114),
+);
+
+/// A plugin that adds the accessibility options.
+pub struct AccessibilityPlugin;
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<AccessibilityOptions>().add_systems(
+            Update,
+            restyle_themed
+                .run_if(resource_changed::<AccessibilityOptions>)
+                .before(ColorSystems::InteractionChanged),
+        );
+    }
+}
+
+/// A resource that holds the accessibility options applied to themed widgets.
+#[derive(Debug, Clone, Default, Resource)]
+pub struct AccessibilityOptions {
+    /// Whether containers are replaced by their high-contrast variant.
+    pub high_contrast: bool,
+
+    /// The color palette that all theme colors are adapted to.
+    pub palette: ColorBlindPalette,
+
+    /// The deltas used to derive the interaction states of interactive colors
+    /// from their default color. If `None`, the states of the theme are kept.
+    pub interaction_deltas: Option<InteractionDeltas>,
+
+    /// Whether color, scroll, and spinner animations are disabled.
+    pub reduce_motion: bool,
+}
+
+impl AccessibilityOptions {
+    /// Adapts a color theme to these options.
+    pub fn adapt_color(&self, theme: &ColorTheme) -> ColorTheme {
+        let theme = match (&self.interaction_deltas, theme) {
+            (Some(deltas), ColorTheme::Interactive { default, .. }) => deltas.interactive(*default),
+            _ => theme.clone(),
+        };
+
+        theme.map(|color| self.palette.adapt(color))
+    }
+
+    /// Adapts a container theme, including its text theme, to these options.
+    pub fn adapt_container(&self, theme: &ContainerTheme) -> ContainerTheme {
+        let theme = if self.high_contrast {
+            theme.high_contrast()
+        } else {
+            theme.clone()
+        };
+
+        ContainerTheme {
+            background_color: self.adapt_color(&theme.background_color),
+            border_color: self.adapt_color(&theme.border_color),
+            text: FontTheme {
+                color: self.adapt_color(&theme.text.color),
+                ..theme.text.clone()
+            },
+            icon_color: self.adapt_color(&theme.icon_color),
+            ..theme
+        }
+    }
+}
+
+/// The lightness deltas between the default color of an interactive color and
+/// its other interaction states.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InteractionDeltas {
+    /// How much lighter the hovered state is.
+    pub hovered: f32,
+
+    /// How much darker the pressed state is.
+    pub pressed: f32,
+
+    /// How much darker the checked state is.
+    pub checked: f32,
+}
+
+impl Default for InteractionDeltas {
+    fn default() -> Self {
+        Self {
+            hovered: 0.1,
+            pressed: 0.1,
+            checked: 0.1,
+        }
+    }
+}
+
+impl InteractionDeltas {
+    /// Creates an interactive color theme with states derived from the given
+    /// default color. The disabled state is the default color without
+    /// saturation.
+    pub fn interactive(&self, default: Color) -> ColorTheme {
+        ColorTheme::Interactive {
+            default,
+            hovered: default.lighter(self.hovered),
+            pressed: default.darker(self.pressed),
+            disable: default.with_saturation(0.0),
+            checked: default.darker(self.checked),
+        }
+    }
+}
+
+/// A color palette that adapts theme colors for a color vision deficiency.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorBlindPalette {
+    /// Theme colors are used as they are.
+    #[default]
+    Standard,
+
+    /// Adapted for protanopia, where red cones are missing.
+    Protanopia,
+
+    /// Adapted for deuteranopia, where green cones are missing.
+    Deuteranopia,
+
+    /// Adapted for tritanopia, where blue cones are missing.
+    Tritanopia,
+}
+
+impl ColorBlindPalette {
+    /// All palettes, in the order they are cycled through.
+    pub const ALL: [ColorBlindPalette; 4] = [
+        ColorBlindPalette::Standard,
+        ColorBlindPalette::Protanopia,
+        ColorBlindPalette::Deuteranopia,
+        ColorBlindPalette::Tritanopia,
+    ];
+
+    /// Gets the palette after this one in [`ColorBlindPalette::ALL`], wrapping
+    /// around to the first.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|palette| *palette == self);
+        Self::ALL[index.map_or(0, |index| (index + 1) % Self::ALL.len())]
+    }
+
+    /// Gets the display name of this palette.
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorBlindPalette::Standard => "Standard",
+            ColorBlindPalette::Protanopia => "Protanopia",
+            ColorBlindPalette::Deuteranopia => "Deuteranopia",
+            ColorBlindPalette::Tritanopia => "Tritanopia",
+        }
+    }
+
+    /// Adapts a color to this palette.
+    ///
+    /// The color is daltonized: the difference between the color and how it is
+    /// seen with the deficiency is shifted into the color channels that remain
+    /// distinguishable, so that colors differing mainly in the lost hues stay
+    /// apart.
+    pub fn adapt(self, color: Color) -> Color {
+        let simulate: fn(Vec3) -> Vec3 = match self {
+            ColorBlindPalette::Standard => return color,
+            ColorBlindPalette::Protanopia => {
+                |lms: Vec3| Vec3::new(2.02344 * lms.y - 2.52581 * lms.z, lms.y, lms.z)
+            }
+            ColorBlindPalette::Deuteranopia => {
+                |lms: Vec3| Vec3::new(lms.x, 0.494207 * lms.x + 1.24827 * lms.z, lms.z)
+            }
+            ColorBlindPalette::Tritanopia => {
+                |lms: Vec3| Vec3::new(lms.x, lms.y, -0.395913 * lms.x + 0.801109 * lms.y)
+            }
+        };
+
+        let srgba = color.to_srgba();
+        let rgb = Vec3::new(srgba.red, srgba.green, srgba.blue);
+        let error = rgb - LMS_TO_RGB * simulate(RGB_TO_LMS * rgb);
+        let shift = Vec3::new(
+            0.0,
+            DALTONIZE_SHIFT * error.x + error.y,
+            DALTONIZE_SHIFT * error.x + error.z,
+        );
+
+        let adapted = (rgb + shift).clamp(Vec3::ZERO, Vec3::ONE);
+        Color::srgba(adapted.x, adapted.y, adapted.z, srgba.alpha)
+    }
+}
+
+impl ContainerTheme {
+    /// Creates a high-contrast variant of this container theme.
+    ///
+    /// The text and border are drawn in black or white, whichever contrasts
+    /// most with the background, and the background is pushed towards the
+    /// opposite extreme. Icons keep their colors.
+    pub fn high_contrast(&self) -> ContainerTheme {
+        let background = self.background_color.default_color();
+        let dark_text =
+            contrast_ratio(Color::BLACK, background) >= contrast_ratio(Color::WHITE, background);
+        let (foreground, opposite) = if dark_text {
+            (Color::BLACK, Color::WHITE)
+        } else {
+            (Color::WHITE, Color::BLACK)
+        };
+
+        ContainerTheme {
+            background_color: self
+                .background_color
+                .map(|color| color.mix(&opposite, HIGH_CONTRAST_MIX)),
+            border_color: foreground.into(),
+            text: FontTheme {
+                color: foreground.into(),
+                ..self.text.clone()
+            },
+            ..self.clone()
+        }
+    }
+}
+
+/// Styles the colors of every themed container and text node again when the
+/// [`AccessibilityOptions`] change.
+///
+/// Every interaction receiver is marked as changed, so that the new interactive
+/// colors are applied in the current interaction state of each widget.
+fn restyle_themed(
+    options: Res<AccessibilityOptions>,
+    mut containers: Query<(
+        Entity,
+        &mut BackgroundColor,
+        &mut BorderColor,
+        &ContainerTheme,
+    )>,
+    mut texts: Query<(Entity, &mut TextColor, &FontTheme)>,
+    parents: Query<&ChildOf>,
+    container_themes: Query<&ContainerTheme>,
+    mut receivers: Query<&mut InteractionReceiver>,
+    mut commands: Commands,
+) {
+    for (entity, mut bg_color, mut border_color, theme) in &mut containers {
+        apply_container_colors(
+            entity,
+            theme,
+            &options,
+            &mut bg_color,
+            &mut border_color,
+            &mut commands,
+        );
+    }
+
+    for (entity, mut text_color, theme) in &mut texts {
+        apply_text_color(
+            entity,
+            theme,
+            &options,
+            &parents,
+            &container_themes,
+            &mut text_color,
+            &mut commands,
+        );
+    }
+
+    for mut receiver in &mut receivers {
+        receiver.set_changed();
+    }
+}
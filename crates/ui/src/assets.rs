@@ -0,0 +1,153 @@
+//! This module implements the `awgen_ui` asset source, which serves the fonts
+//! and icons used by the built-in UI themes.
+//!
+//! Theme assets are first looked up in an optional override folder, such as a
+//! folder within a game project, and fall back to the copies embedded in this
+//! crate. Games can ship their own UI font and icon skin by placing files with
+//! the same relative paths, such as `fonts/quiver.ttf`, in the override folder.
+//!
+//! The asset source must be registered with
+//! [`register_ui_assets`](AwgenUiAssetsExt::register_ui_assets) before the
+//! [`AssetPlugin`] is added, and the [`AwgenUiPlugin`](crate::AwgenUiPlugin)
+//! must be added after it:
+//!
+//! ```ignore
+//! app.register_ui_assets(None)
+//!     .add_plugins((DefaultPlugins, AwgenUiPlugin));
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use bevy::asset::io::memory::{Dir, MemoryAssetReader};
+use bevy::asset::io::{
+    AssetReader,
+    AssetReaderError,
+    AssetSource,
+    ErasedAssetReader,
+    PathStream,
+    Reader,
+};
+use bevy::prelude::*;
+
+/// The name of the asset source that serves the UI theme assets.
+pub const UI_ASSET_SOURCE: &str = "awgen_ui";
+
+/// The theme assets embedded in this crate, by their path within the asset
+/// source.
+const EMBEDDED_ASSETS: &[(&str, &[u8])] = &[
+    ("fonts/quiver.ttf", include_bytes!("fonts/quiver.ttf")),
+    (
+        "icons/right_arrow.png",
+        include_bytes!("icons/right_arrow.png"),
+    ),
+    (
+        "icons/down_arrow.png",
+        include_bytes!("icons/down_arrow.png"),
+    ),
+    (
+        "icons/vert_spacer.png",
+        include_bytes!("icons/vert_spacer.png"),
+    ),
+    ("icons/folder.png", include_bytes!("icons/folder.png")),
+    ("icons/loading.png", include_bytes!("icons/loading.png")),
+    ("icons/error.png", include_bytes!("icons/error.png")),
+];
+
+/// Extension trait for registering the `awgen_ui` asset source.
+pub trait AwgenUiAssetsExt {
+    /// Registers the asset source that serves the UI theme assets, looking up
+    /// assets in the given override folder before falling back to the embedded
+    /// copies.
+    ///
+    /// Like all asset sources, this must be registered before the
+    /// [`AssetPlugin`] is added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`AssetPlugin`] has already been added.
+    fn register_ui_assets(&mut self, overrides: Option<PathBuf>) -> &mut Self;
+}
+
+impl AwgenUiAssetsExt for App {
+    fn register_ui_assets(&mut self, overrides: Option<PathBuf>) -> &mut Self {
+        assert!(
+            !self.is_plugin_added::<AssetPlugin>(),
+            "The awgen_ui asset source must be registered before the AssetPlugin is added"
+        );
+
+        if let Some(folder) = &overrides {
+            info!(
+                "UI theme assets may be overridden from {}",
+                folder.display()
+            );
+        }
+
+        self.register_asset_source(
+            UI_ASSET_SOURCE,
+            AssetSource::build()
+                .with_reader(move || Box::new(ThemeAssetReader::new(overrides.as_deref()))),
+        )
+    }
+}
+
+/// An asset reader that reads theme assets from an override folder, falling
+/// back to the embedded theme assets.
+struct ThemeAssetReader {
+    /// The reader for the override folder, if any.
+    overrides: Option<Box<dyn ErasedAssetReader>>,
+
+    /// The reader for the embedded theme assets.
+    embedded: Box<dyn ErasedAssetReader>,
+}
+
+impl ThemeAssetReader {
+    /// Creates a new reader with the given override folder.
+    fn new(overrides: Option<&Path>) -> Self {
+        let root = Dir::default();
+        for (path, bytes) in EMBEDDED_ASSETS {
+            root.insert_asset(Path::new(path), *bytes);
+        }
+
+        Self {
+            overrides: overrides.map(|folder| {
+                AssetSource::get_default_reader(folder.to_string_lossy().into_owned())()
+            }),
+            embedded: Box::new(MemoryAssetReader { root }),
+        }
+    }
+}
+
+impl AssetReader for ThemeAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        if let Some(overrides) = &self.overrides {
+            match overrides.read(path).await {
+                Err(AssetReaderError::NotFound(_)) => {}
+                result => return result,
+            }
+        }
+
+        self.embedded.read(path).await
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        if let Some(overrides) = &self.overrides {
+            match overrides.read_meta(path).await {
+                Err(AssetReaderError::NotFound(_)) => {}
+                result => return result,
+            }
+        }
+
+        self.embedded.read_meta(path).await
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        self.embedded.read_directory(path).await
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        self.embedded.is_directory(path).await
+    }
+}
@@ -0,0 +1,186 @@
+//! This module implements a clipboard service with a pluggable backend.
+//!
+//! By default, the clipboard is stored in memory and is only shared within
+//! the running application. Enabling the `system_clipboard` feature will use
+//! the operating system clipboard instead.
+
+use bevy::picking::hover::HoverMap;
+use bevy::prelude::*;
+
+use crate::ime::ImeFocus;
+
+/// A plugin that adds clipboard support to the UI.
+pub struct ClipboardPlugin;
+impl Plugin for ClipboardPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<Clipboard>()
+            .add_systems(Update, copy_hovered_on_shortcut);
+    }
+}
+
+/// A backend that can store and retrieve clipboard contents.
+pub trait ClipboardBackend: Send + Sync + 'static {
+    /// Gets the current text contents of the clipboard, if any.
+    fn get_text(&mut self) -> Result<Option<String>, ClipboardError>;
+
+    /// Replaces the contents of the clipboard with the given text.
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError>;
+}
+
+/// Errors that can occur when accessing the clipboard.
+#[derive(Debug, thiserror::Error)]
+pub enum ClipboardError {
+    /// The clipboard backend is not available on this platform.
+    #[error("Clipboard is unavailable: {0}")]
+    Unavailable(String),
+
+    /// The clipboard backend failed to read or write the clipboard.
+    #[error("Clipboard access failed: {0}")]
+    Access(String),
+}
+
+/// A clipboard backend that stores its contents in memory.
+#[derive(Debug, Default)]
+pub struct MemoryClipboard {
+    /// The current clipboard contents.
+    text: Option<String>,
+}
+
+impl ClipboardBackend for MemoryClipboard {
+    fn get_text(&mut self) -> Result<Option<String>, ClipboardError> {
+        Ok(self.text.clone())
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        self.text = Some(text.to_string());
+        Ok(())
+    }
+}
+
+/// A clipboard backend that uses the operating system clipboard.
+#[cfg(feature = "system_clipboard")]
+pub struct SystemClipboard {
+    /// The underlying clipboard handle.
+    inner: arboard::Clipboard,
+}
+
+#[cfg(feature = "system_clipboard")]
+impl SystemClipboard {
+    /// Opens a handle to the operating system clipboard.
+    pub fn new() -> Result<Self, ClipboardError> {
+        let inner =
+            arboard::Clipboard::new().map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+        Ok(Self { inner })
+    }
+}
+
+#[cfg(feature = "system_clipboard")]
+impl ClipboardBackend for SystemClipboard {
+    fn get_text(&mut self) -> Result<Option<String>, ClipboardError> {
+        match self.inner.get_text() {
+            Ok(text) => Ok(Some(text)),
+            Err(arboard::Error::ContentNotAvailable) => Ok(None),
+            Err(e) => Err(ClipboardError::Access(e.to_string())),
+        }
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        self.inner
+            .set_text(text)
+            .map_err(|e| ClipboardError::Access(e.to_string()))
+    }
+}
+
+/// The clipboard resource, shared by all widgets and tools.
+#[derive(Resource)]
+pub struct Clipboard {
+    /// The active clipboard backend.
+    backend: Box<dyn ClipboardBackend>,
+}
+
+impl Clipboard {
+    /// Creates a new clipboard using the given backend.
+    pub fn new(backend: impl ClipboardBackend) -> Self {
+        Self {
+            backend: Box::new(backend),
+        }
+    }
+
+    /// Replaces the clipboard backend. The contents of the previous backend are
+    /// not carried over.
+    pub fn set_backend(&mut self, backend: impl ClipboardBackend) {
+        self.backend = Box::new(backend);
+    }
+
+    /// Copies the given text to the clipboard.
+    pub fn copy(&mut self, text: impl AsRef<str>) -> Result<(), ClipboardError> {
+        self.backend.set_text(text.as_ref())
+    }
+
+    /// Pastes the current text from the clipboard, if any.
+    pub fn paste(&mut self) -> Result<Option<String>, ClipboardError> {
+        self.backend.get_text()
+    }
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        #[cfg(feature = "system_clipboard")]
+        match SystemClipboard::new() {
+            Ok(backend) => return Self::new(backend),
+            Err(e) => warn!("{e}; falling back to in-memory clipboard"),
+        }
+
+        Self::new(MemoryClipboard::default())
+    }
+}
+
+/// A component that provides text that can be copied to the clipboard by
+/// pressing Ctrl+C while the entity, or any of its descendants, is hovered.
+/// This is used for context actions such as copying an asset ID or path from
+/// a tree or grid widget.
+#[derive(Debug, Default, Component, Clone)]
+pub struct ClipboardText(pub String);
+
+/// Copies the [`ClipboardText`] of the hovered entity when Ctrl+C is pressed.
+/// If the hovered entity has none, the text of its nearest ancestor with one
+/// is copied instead.
+///
+/// Nothing is copied while a text field has focus, as Ctrl+C copies the
+/// selected text of the field instead.
+fn copy_hovered_on_shortcut(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    text_focus: Query<(), With<ImeFocus>>,
+    hover_map: Res<HoverMap>,
+    sources: Query<&ClipboardText>,
+    parents: Query<&ChildOf>,
+    mut clipboard: ResMut<Clipboard>,
+) {
+    if !text_focus.is_empty() {
+        return;
+    }
+
+    if !keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        || !keyboard_input.just_pressed(KeyCode::KeyC)
+    {
+        return;
+    }
+
+    let hovered = hover_map
+        .values()
+        .flat_map(|pointer_map| pointer_map.keys().copied())
+        .find_map(|entity| {
+            std::iter::once(entity)
+                .chain(parents.iter_ancestors(entity))
+                .find_map(|entity| sources.get(entity).ok())
+        });
+
+    let Some(text) = hovered else {
+        return;
+    };
+
+    match clipboard.copy(&text.0) {
+        Ok(()) => debug!("Copied to clipboard: {}", text.0),
+        Err(e) => error!("Failed to copy to clipboard: {e}"),
+    }
+}
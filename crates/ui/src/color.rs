@@ -5,6 +5,7 @@ use std::marker::PhantomData;
 use bevy::ecs::component::Mutable;
 use bevy::prelude::*;
 
+use crate::accessibility::AccessibilityOptions;
 use crate::prelude::InteractionReceiver;
 use crate::theme::ColorTheme;
 
@@ -240,15 +241,21 @@ impl Colorable for ImageNode {
 #[require(BorderColor)]
 pub struct InsetBorder(pub Color);
 
-/// System that updates smooth color transitions.
+/// System that updates smooth color transitions. Transitions complete
+/// immediately while [`AccessibilityOptions::reduce_motion`] is set.
 fn update_smooth_color<C>(
     time: Res<Time>,
+    options: Res<AccessibilityOptions>,
     mut query: Query<(&mut C, &SmoothColor<C>), With<ColorTarget<C>>>,
 ) where
     C: Component<Mutability = Mutable> + Colorable,
 {
     let delta = time.delta_secs();
-    let t = (1.0 - 0.01f32.powf(4.0 * delta)).clamp(0.0, 1.0);
+    let t = if options.reduce_motion {
+        1.0
+    } else {
+        (1.0 - 0.01f32.powf(4.0 * delta)).clamp(0.0, 1.0)
+    };
 
     for (mut colorable, maybe_smooth) in query.iter_mut() {
         let Some(target_color) = maybe_smooth.color else {
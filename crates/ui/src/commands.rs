@@ -0,0 +1,233 @@
+//! This module implements the command registry, which holds the commands that
+//! can be run from the command palette and bound to hotkeys.
+//!
+//! Commands are registered in the [`CommandRegistry`] resource, either
+//! directly or with [`RegisterCommandExt::register_command`]. Running a command
+//! runs its system, if it has one, and triggers a global [`CommandInvoked`]
+//! event, so commands may also be handled by observers.
+
+use bevy::ecs::system::SystemId;
+use bevy::prelude::*;
+
+/// The number of recently used commands that are remembered.
+const RECENT_LIMIT: usize = 10;
+
+/// The score bonus for a matched character that directly follows the previous
+/// matched character.
+const CONSECUTIVE_BONUS: i32 = 5;
+
+/// The score bonus for a matched character at the start of a word.
+const WORD_START_BONUS: i32 = 8;
+
+/// The score bonus for the most recently used command. Less recent commands
+/// receive a proportionally smaller bonus.
+const RECENT_BONUS: i32 = 20;
+
+/// A command that can be run from the command palette.
+#[derive(Debug, Clone)]
+pub struct PaletteCommand {
+    /// The unique ID of the command, such as `editor.backup`.
+    pub id: String,
+
+    /// The name of the command shown in the palette.
+    pub name: String,
+
+    /// A description of the keyboard shortcut that runs the command, if any,
+    /// such as `Ctrl+S`. This is only displayed in the palette, and only if no
+    /// hotkeys are bound to the command in the
+    /// [`HotkeyRegistry`](crate::hotkeys::HotkeyRegistry).
+    pub shortcut: Option<String>,
+
+    /// The system that is run when the command is invoked, if any.
+    pub system: Option<SystemId>,
+}
+
+impl PaletteCommand {
+    /// Creates a new command with the given ID and name.
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            shortcut: None,
+            system: None,
+        }
+    }
+
+    /// Sets the keyboard shortcut displayed for this command.
+    pub fn with_shortcut(mut self, shortcut: impl Into<String>) -> Self {
+        self.shortcut = Some(shortcut.into());
+        self
+    }
+
+    /// Sets the system that is run when this command is invoked.
+    pub fn with_system(mut self, system: SystemId) -> Self {
+        self.system = Some(system);
+        self
+    }
+}
+
+/// A global event that is triggered when a command is invoked.
+#[derive(Debug, Clone, Event)]
+pub struct CommandInvoked {
+    /// The ID of the command.
+    pub id: String,
+}
+
+/// A resource that holds the commands available in the command palette.
+#[derive(Debug, Default, Resource)]
+pub struct CommandRegistry {
+    /// The registered commands, in registration order.
+    commands: Vec<PaletteCommand>,
+
+    /// The IDs of the most recently used commands, most recent first.
+    recent: Vec<String>,
+}
+
+impl CommandRegistry {
+    /// Registers a command, replacing any existing command with the same ID.
+    pub fn register(&mut self, command: PaletteCommand) {
+        match self.commands.iter_mut().find(|c| c.id == command.id) {
+            Some(existing) => *existing = command,
+            None => self.commands.push(command),
+        }
+    }
+
+    /// Removes the command with the given ID, returning it if it existed.
+    pub fn unregister(&mut self, id: &str) -> Option<PaletteCommand> {
+        self.recent.retain(|recent| recent != id);
+        let index = self.commands.iter().position(|c| c.id == id)?;
+        Some(self.commands.remove(index))
+    }
+
+    /// Removes every command whose ID starts with the given prefix, returning
+    /// the number of commands removed.
+    pub fn unregister_prefix(&mut self, prefix: &str) -> usize {
+        self.recent.retain(|recent| !recent.starts_with(prefix));
+        let count = self.commands.len();
+        self.commands.retain(|c| !c.id.starts_with(prefix));
+        count - self.commands.len()
+    }
+
+    /// Gets the command with the given ID, if it exists.
+    pub fn get(&self, id: &str) -> Option<&PaletteCommand> {
+        self.commands.iter().find(|c| c.id == id)
+    }
+
+    /// Iterates over all registered commands, in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &PaletteCommand> {
+        self.commands.iter()
+    }
+
+    /// Gets the IDs of the most recently used commands, most recent first.
+    pub fn recent(&self) -> &[String] {
+        &self.recent
+    }
+
+    /// Searches the commands whose names fuzzy match the given query, best
+    /// matches first. Recently used commands are ranked higher.
+    ///
+    /// An empty query returns the recently used commands, followed by all other
+    /// commands sorted by name.
+    pub fn search(&self, query: &str) -> Vec<&PaletteCommand> {
+        let mut results = self
+            .commands
+            .iter()
+            .filter_map(|command| {
+                let score = fuzzy_score(query, &command.name)?;
+                let bonus = match self.recent.iter().position(|id| *id == command.id) {
+                    Some(rank) => RECENT_BONUS * (RECENT_LIMIT - rank) as i32 / RECENT_LIMIT as i32,
+                    None => 0,
+                };
+                Some((score + bonus, command))
+            })
+            .collect::<Vec<_>>();
+
+        results.sort_by(|(a_score, a), (b_score, b)| {
+            b_score.cmp(a_score).then_with(|| a.name.cmp(&b.name))
+        });
+        results.into_iter().map(|(_, command)| command).collect()
+    }
+
+    /// Runs the command with the given ID, marking it as recently used.
+    ///
+    /// Returns false if no command with the given ID is registered.
+    pub fn run(&mut self, id: &str, commands: &mut Commands) -> bool {
+        let Some(command) = self.get(id) else {
+            return false;
+        };
+
+        if let Some(system) = command.system {
+            commands.run_system(system);
+        }
+
+        commands.trigger(CommandInvoked { id: id.to_string() });
+
+        self.recent.retain(|recent| recent != id);
+        self.recent.insert(0, id.to_string());
+        self.recent.truncate(RECENT_LIMIT);
+        true
+    }
+}
+
+/// Extension trait for registering commands with a system to run.
+pub trait RegisterCommandExt {
+    /// Registers a command in the [`CommandRegistry`] that runs the given
+    /// system when invoked.
+    fn register_command<M>(
+        &mut self,
+        command: PaletteCommand,
+        system: impl IntoSystem<(), (), M> + 'static,
+    ) -> &mut Self;
+}
+
+impl RegisterCommandExt for App {
+    fn register_command<M>(
+        &mut self,
+        command: PaletteCommand,
+        system: impl IntoSystem<(), (), M> + 'static,
+    ) -> &mut Self {
+        let system = self.register_system(system);
+        self.init_resource::<CommandRegistry>();
+        self.world_mut()
+            .resource_mut::<CommandRegistry>()
+            .register(command.with_system(system));
+        self
+    }
+}
+
+/// Scores how well a query fuzzy matches a candidate string, ignoring case and
+/// whitespace in the query.
+///
+/// Every query character must appear in the candidate, in order. Matches at the
+/// start of words and runs of consecutive matches score higher, while gaps
+/// between matches score lower. Returns `None` if the query does not match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate = candidate.chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last = None;
+
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let index = (next .. candidate.len())
+            .find(|&i| candidate[i].to_lowercase().eq(wanted.to_lowercase()))?;
+
+        score += 1;
+        if index == 0
+            || !candidate[index - 1].is_alphanumeric()
+            || (candidate[index].is_uppercase() && candidate[index - 1].is_lowercase())
+        {
+            score += WORD_START_BONUS;
+        }
+
+        match last {
+            Some(last) if index == last + 1 => score += CONSECUTIVE_BONUS,
+            Some(last) => score -= (index - last - 1) as i32,
+            None => score -= index as i32,
+        }
+
+        last = Some(index);
+        next = index + 1;
+    }
+
+    Some(score)
+}
@@ -0,0 +1,347 @@
+//! This module implements a debug mode that checks the contrast of themed
+//! widgets, helping theme authors catch unreadable color combinations.
+//!
+//! While [`ContrastDebug`] is enabled, every text node and themed icon is
+//! compared against the nearest visible background behind it, in each of its
+//! interaction states. Nodes whose contrast ratio falls below the minimum are
+//! reported to the console and outlined by a debug overlay, which is drawn
+//! above the UI without changing the styles of the nodes themselves. Contrast
+//! ratios follow the WCAG 2 definition. Disabled states are not checked, as
+//! WCAG exempts inactive components.
+
+use bevy::ecs::component::Mutable;
+use bevy::prelude::*;
+
+use crate::color::{Colorable, InteractiveColor};
+use crate::menus::layers::UiLayer;
+
+/// The minimum contrast ratio recommended by WCAG for normal text.
+pub const MIN_CONTRAST_RATIO: f32 = 4.5;
+
+/// The color of the outline drawn around nodes with insufficient contrast.
+const OUTLINE_COLOR: Color = Color::srgb(1.0, 0.0, 1.0);
+
+/// The width, in pixels, of the outline drawn around nodes with insufficient
+/// contrast.
+const OUTLINE_WIDTH: f32 = 2.0;
+
+/// The names of the interaction states that are checked, in the order used by
+/// [`state_colors`].
+const STATES: [&str; 4] = ["default", "hovered", "pressed", "checked"];
+
+/// A plugin that adds the contrast debug mode.
+pub struct ContrastDebugPlugin;
+impl Plugin for ContrastDebugPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<ContrastDebug>()
+            .add_systems(Update, (check_contrast, update_contrast_overlay).chain());
+    }
+}
+
+/// A resource that controls the contrast debug mode.
+#[derive(Debug, Resource)]
+pub struct ContrastDebug {
+    /// Whether widgets are checked for insufficient contrast.
+    pub enabled: bool,
+
+    /// The contrast ratio below which a widget is reported.
+    pub min_ratio: f32,
+}
+
+impl Default for ContrastDebug {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_ratio: MIN_CONTRAST_RATIO,
+        }
+    }
+}
+
+/// A component added to nodes whose contrast is insufficient while the debug
+/// mode is enabled.
+#[derive(Debug, Component)]
+pub struct ContrastWarning {
+    /// The lowest contrast ratio of the node across its interaction states.
+    pub ratio: f32,
+
+    /// The name of the interaction state with the lowest contrast ratio.
+    pub state: &'static str,
+}
+
+/// A marker component for the root node of the contrast debug overlay.
+#[derive(Debug, Component)]
+pub struct ContrastOverlay;
+
+/// A component for the overlay nodes that outline a node with a
+/// [`ContrastWarning`].
+#[derive(Debug, Component)]
+struct ContrastHighlight {
+    /// The node that is outlined.
+    target: Entity,
+}
+
+/// Gets the relative luminance of a color, as defined by WCAG 2.
+pub fn relative_luminance(color: Color) -> f32 {
+    let linear = color.to_linear();
+    0.2126 * linear.red + 0.7152 * linear.green + 0.0722 * linear.blue
+}
+
+/// Gets the contrast ratio, from 1 to 21, of a foreground color drawn over a
+/// background color. A translucent foreground is blended over the background
+/// first.
+pub fn contrast_ratio(foreground: Color, background: Color) -> f32 {
+    let foreground = blend(foreground, background);
+    let a = relative_luminance(foreground);
+    let b = relative_luminance(background);
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// Blends a translucent foreground color over an opaque background color.
+fn blend(foreground: Color, background: Color) -> Color {
+    let fg = foreground.to_linear();
+    let bg = background.to_linear();
+    let alpha = fg.alpha;
+    LinearRgba::rgb(
+        bg.red + (fg.red - bg.red) * alpha,
+        bg.green + (fg.green - bg.green) * alpha,
+        bg.blue + (fg.blue - bg.blue) * alpha,
+    )
+    .into()
+}
+
+/// Gets the color of a component in each checked interaction state, in the
+/// order of [`STATES`].
+fn state_colors<C>(current: Color, interactive: Option<&InteractiveColor<C>>) -> [Color; 4]
+where
+    C: Component<Mutability = Mutable> + Colorable,
+{
+    match interactive {
+        Some(color) => [color.default, color.hovered, color.pressed, color.checked],
+        None => [current; 4],
+    }
+}
+
+/// Checks text nodes and themed icons for insufficient contrast while the
+/// debug mode is enabled, and clears the warnings when it is disabled.
+///
+/// Every node is checked when the debug mode changes, and nodes spawned later
+/// are checked as they are added.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn check_contrast(
+    debug: Res<ContrastDebug>,
+    clear_color: Option<Res<ClearColor>>,
+    texts: Query<(
+        Entity,
+        &Text,
+        Ref<TextColor>,
+        Option<&InteractiveColor<TextColor>>,
+    )>,
+    icons: Query<(Entity, Ref<ImageNode>, &InteractiveColor<ImageNode>)>,
+    backgrounds: Query<(&BackgroundColor, Option<&InteractiveColor<BackgroundColor>>)>,
+    parents: Query<&ChildOf>,
+    names: Query<&Name>,
+    warnings: Query<Entity, With<ContrastWarning>>,
+    mut commands: Commands,
+) {
+    let full_scan = debug.is_changed();
+    if full_scan {
+        for entity in warnings.iter() {
+            commands.entity(entity).remove::<ContrastWarning>();
+        }
+    }
+
+    if !debug.enabled {
+        return;
+    }
+
+    let fallback = clear_color.map_or(Color::BLACK, |color| color.0);
+    let background_of = |entity: Entity| {
+        std::iter::once(entity)
+            .chain(parents.iter_ancestors(entity))
+            .filter_map(|ancestor| backgrounds.get(ancestor).ok())
+            .find(|(color, interactive)| {
+                let default = interactive.map_or(color.0, |interactive| interactive.default);
+                default.alpha() > 0.0
+            })
+            .map_or([fallback; 4], |(color, interactive)| {
+                state_colors(color.0, interactive)
+            })
+    };
+
+    let mut candidates = Vec::new();
+
+    for (entity, text, color, interactive) in texts.iter() {
+        if !full_scan && !color.is_added() {
+            continue;
+        }
+
+        let foreground = state_colors(color.0, interactive);
+        let label = format!("text \"{}\"", text.0);
+        candidates.push((entity, label, foreground, background_of(entity)));
+    }
+
+    for (entity, image, interactive) in icons.iter() {
+        if !full_scan && !image.is_added() {
+            continue;
+        }
+
+        let foreground = state_colors(image.color, Some(interactive));
+        candidates.push((
+            entity,
+            "icon".to_string(),
+            foreground,
+            background_of(entity),
+        ));
+    }
+
+    let mut reported = 0;
+    for (entity, label, foreground, background) in candidates {
+        let (ratio, state) = foreground
+            .iter()
+            .zip(background.iter())
+            .zip(STATES)
+            .map(|((fg, bg), state)| (contrast_ratio(*fg, *bg), state))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap();
+
+        if ratio >= debug.min_ratio {
+            continue;
+        }
+
+        let name = names
+            .get(entity)
+            .map_or_else(|_| entity.to_string(), |name| name.to_string());
+        warn!(
+            "Low contrast of {:.2}:1 on {} ({}) while {}",
+            ratio, label, name, state
+        );
+        reported += 1;
+
+        commands
+            .entity(entity)
+            .insert(ContrastWarning { ratio, state });
+    }
+
+    if full_scan && reported == 0 {
+        info!(
+            "No widgets fall below a contrast ratio of {:.2}:1",
+            debug.min_ratio
+        );
+    }
+}
+
+/// Keeps the contrast debug overlay in sync with the nodes that have a
+/// [`ContrastWarning`], outlining each of them at its current position.
+///
+/// The overlay is spawned while the debug mode is enabled, and despawned when
+/// it is disabled.
+fn update_contrast_overlay(
+    debug: Res<ContrastDebug>,
+    overlays: Query<Entity, With<ContrastOverlay>>,
+    mut highlights: Query<(Entity, &ContrastHighlight, &mut Node)>,
+    warnings: Query<(Entity, &ComputedNode, &UiGlobalTransform), With<ContrastWarning>>,
+    mut commands: Commands,
+) {
+    if !debug.enabled {
+        for entity in overlays.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let Some(overlay) = overlays.iter().next() else {
+        commands.spawn((
+            ContrastOverlay,
+            Node {
+                position_type: PositionType::Absolute,
+                width: percent(100.0),
+                height: percent(100.0),
+                ..default()
+            },
+            UiLayer::Debug,
+            Pickable::IGNORE,
+        ));
+        return;
+    };
+
+    let mut outlined = Vec::new();
+    for (entity, highlight, mut node) in highlights.iter_mut() {
+        let Ok((_, computed, transform)) = warnings.get(highlight.target) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        let scale = computed.inverse_scale_factor();
+        let size = computed.size() * scale;
+        let min = transform.translation * scale - size / 2.0;
+
+        // Only write changes, to avoid recomputing the layout every frame.
+        node.set_if_neq(Node {
+            left: px(min.x),
+            top: px(min.y),
+            width: px(size.x),
+            height: px(size.y),
+            ..node.clone()
+        });
+        outlined.push(highlight.target);
+    }
+
+    for (target, _, _) in warnings.iter() {
+        if outlined.contains(&target) {
+            continue;
+        }
+
+        commands.spawn((
+            ContrastHighlight { target },
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            Outline::new(px(OUTLINE_WIDTH), Val::ZERO, OUTLINE_COLOR),
+            Pickable::IGNORE,
+            ChildOf(overlay),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contrast_ratio_spans_one_to_twenty_one() {
+        let ratio = contrast_ratio(Color::BLACK, Color::WHITE);
+        assert!((ratio - 21.0).abs() < 1e-3);
+
+        let ratio = contrast_ratio(Color::srgb(0.3, 0.6, 0.9), Color::srgb(0.3, 0.6, 0.9));
+        assert!((ratio - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn contrast_ratio_is_symmetric() {
+        let a = Color::srgb(0.9, 0.8, 0.1);
+        let b = Color::srgb(0.1, 0.2, 0.4);
+        assert!((contrast_ratio(a, b) - contrast_ratio(b, a)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn contrast_ratio_matches_wcag_reference() {
+        // #777777 on white is the well known borderline case of 4.48:1.
+        let gray = Color::srgb_u8(0x77, 0x77, 0x77);
+        let ratio = contrast_ratio(gray, Color::WHITE);
+        assert!((ratio - 4.48).abs() < 0.01);
+        assert!(ratio < MIN_CONTRAST_RATIO);
+    }
+
+    #[test]
+    fn translucent_foreground_is_blended_over_background() {
+        let transparent = Color::BLACK.with_alpha(0.0);
+        let ratio = contrast_ratio(transparent, Color::WHITE);
+        assert!((ratio - 1.0).abs() < 1e-6);
+
+        let half = Color::BLACK.with_alpha(0.5);
+        let ratio = contrast_ratio(half, Color::WHITE);
+        assert!(ratio > 1.0 && ratio < 21.0);
+    }
+}
@@ -0,0 +1,551 @@
+//! This module implements a hotkey registry, which binds keyboard shortcuts to
+//! the commands of the [`CommandRegistry`].
+//!
+//! Each binding belongs to a [`HotkeyContext`]. Global bindings are always
+//! active, editor bindings are active while the editor context is enabled, and
+//! dialog bindings are active while a [`HotkeyDialog`] is shown. Showing a
+//! dialog suppresses the editor bindings. When several active bindings match a
+//! key press, the binding of the most specific context wins. Bindings that may
+//! be active at the same time are reported as conflicts when they are bound.
+//!
+//! Shortcut hints are rendered automatically. The command palette lists the
+//! hotkeys bound to each command, and a [`ShortcutHint`] on a button shows the
+//! hotkeys of a command next to its label, as in menus, or in a tooltip while
+//! the button is hovered.
+
+use std::fmt;
+
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+
+use crate::commands::CommandRegistry;
+use crate::ime::ImeFocus;
+use crate::menus::layers::UiLayer;
+use crate::theme::UiTheme;
+
+/// The gap, in pixels, between a button and its shortcut tooltip.
+const TOOLTIP_OFFSET: f32 = 4.0;
+
+/// A plugin that adds support for hotkeys.
+pub(crate) struct HotkeysPlugin;
+impl Plugin for HotkeysPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<CommandRegistry>()
+            .init_resource::<HotkeyRegistry>()
+            .add_systems(
+                Update,
+                (dispatch_hotkeys, update_inline_hints, update_hint_tooltips),
+            );
+    }
+}
+
+/// The context in which a hotkey binding is active.
+///
+/// Contexts are ordered from the least to the most specific.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HotkeyContext {
+    /// The binding is always active.
+    Global,
+
+    /// The binding is active while the editor context is enabled and no dialog
+    /// is shown.
+    Editor,
+
+    /// The binding is active while a dialog is shown.
+    Dialog,
+}
+
+impl HotkeyContext {
+    /// Returns true if bindings in both contexts may be active at the same
+    /// time.
+    pub fn overlaps(self, other: HotkeyContext) -> bool {
+        self == other || self == HotkeyContext::Global || other == HotkeyContext::Global
+    }
+}
+
+/// A key combined with a set of modifier keys.
+///
+/// A hotkey only matches when exactly its modifiers are held, so `Ctrl+P` and
+/// `P` are different hotkeys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    /// The key that triggers the hotkey.
+    pub key: KeyCode,
+
+    /// Whether either Control key must be held.
+    pub ctrl: bool,
+
+    /// Whether either Shift key must be held.
+    pub shift: bool,
+
+    /// Whether either Alt key must be held.
+    pub alt: bool,
+}
+
+impl Hotkey {
+    /// Creates a new hotkey for the given key, without modifiers.
+    pub const fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    /// Requires the Control key to be held.
+    pub const fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    /// Requires the Shift key to be held.
+    pub const fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    /// Requires the Alt key to be held.
+    pub const fn alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// Returns true if this hotkey was pressed this frame.
+    pub fn just_pressed(&self, input: &ButtonInput<KeyCode>) -> bool {
+        input.just_pressed(self.key)
+            && self.ctrl == input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+            && self.shift == input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+            && self.alt == input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+    }
+
+    /// Returns true if this hotkey may be pressed while typing into a text
+    /// field, which is the case for hotkeys that hold Control or Alt.
+    pub fn works_while_typing(&self) -> bool {
+        self.ctrl || self.alt
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+
+        let name = format!("{:?}", self.key);
+        let name = name
+            .strip_prefix("Key")
+            .or_else(|| name.strip_prefix("Digit"))
+            .unwrap_or(&name);
+        write!(f, "{name}")
+    }
+}
+
+/// A hotkey bound to a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotkeyBinding {
+    /// The ID of the command that the hotkey runs.
+    pub command: String,
+
+    /// The hotkey.
+    pub hotkey: Hotkey,
+
+    /// The context in which the binding is active.
+    pub context: HotkeyContext,
+}
+
+/// Two bindings of the same hotkey that may be active at the same time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotkeyConflict {
+    /// The hotkey bound by both bindings.
+    pub hotkey: Hotkey,
+
+    /// The ID of the command of the earlier binding.
+    pub first: String,
+
+    /// The ID of the command of the later binding.
+    pub second: String,
+}
+
+/// A resource that holds the hotkey bindings of the commands in the
+/// [`CommandRegistry`].
+#[derive(Debug, Default, Resource)]
+pub struct HotkeyRegistry {
+    /// The bindings, in binding order.
+    bindings: Vec<HotkeyBinding>,
+
+    /// Whether the editor context is enabled.
+    editor_enabled: bool,
+}
+
+impl HotkeyRegistry {
+    /// Binds a hotkey to the command with the given ID in the given context.
+    ///
+    /// Returns the conflicts with existing bindings, which are also logged as
+    /// warnings. The binding is added either way; when conflicting bindings
+    /// match a key press, the earlier binding is run.
+    pub fn bind(
+        &mut self,
+        command: impl Into<String>,
+        hotkey: Hotkey,
+        context: HotkeyContext,
+    ) -> Vec<HotkeyConflict> {
+        let binding = HotkeyBinding {
+            command: command.into(),
+            hotkey,
+            context,
+        };
+
+        if self.bindings.contains(&binding) {
+            return Vec::new();
+        }
+
+        let conflicts = self
+            .bindings
+            .iter()
+            .filter(|other| conflicting(other, &binding))
+            .map(|other| HotkeyConflict {
+                hotkey,
+                first: other.command.clone(),
+                second: binding.command.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        for conflict in &conflicts {
+            warn!(
+                "Hotkey {} of \"{}\" conflicts with \"{}\"",
+                conflict.hotkey, conflict.second, conflict.first
+            );
+        }
+
+        self.bindings.push(binding);
+        conflicts
+    }
+
+    /// Removes all hotkeys bound to the command with the given ID, returning
+    /// true if any were bound.
+    pub fn unbind(&mut self, command: &str) -> bool {
+        let count = self.bindings.len();
+        self.bindings.retain(|binding| binding.command != command);
+        self.bindings.len() != count
+    }
+
+    /// Gets all bindings, in binding order.
+    pub fn bindings(&self) -> &[HotkeyBinding] {
+        &self.bindings
+    }
+
+    /// Finds every pair of bindings that conflict with each other.
+    pub fn conflicts(&self) -> Vec<HotkeyConflict> {
+        let mut conflicts = Vec::new();
+        for (index, first) in self.bindings.iter().enumerate() {
+            for second in &self.bindings[index + 1 ..] {
+                if conflicting(first, second) {
+                    conflicts.push(HotkeyConflict {
+                        hotkey: first.hotkey,
+                        first: first.command.clone(),
+                        second: second.command.clone(),
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Gets the shortcut hint of the command with the given ID, listing its
+    /// bound hotkeys, such as `Ctrl+B, F6`. Returns `None` if the command has
+    /// no hotkeys.
+    pub fn hint(&self, command: &str) -> Option<String> {
+        let hotkeys = self
+            .bindings
+            .iter()
+            .filter(|binding| binding.command == command)
+            .map(|binding| binding.hotkey.to_string())
+            .collect::<Vec<_>>();
+
+        if hotkeys.is_empty() {
+            None
+        } else {
+            Some(hotkeys.join(", "))
+        }
+    }
+
+    /// Returns true if the editor context is enabled.
+    pub fn editor_enabled(&self) -> bool {
+        self.editor_enabled
+    }
+
+    /// Enables or disables the editor context.
+    pub fn set_editor_enabled(&mut self, enabled: bool) {
+        self.editor_enabled = enabled;
+    }
+}
+
+/// Returns true if two bindings of different commands share a hotkey and may
+/// be active at the same time.
+fn conflicting(a: &HotkeyBinding, b: &HotkeyBinding) -> bool {
+    a.hotkey == b.hotkey && a.command != b.command && a.context.overlaps(b.context)
+}
+
+/// Extension trait for binding hotkeys while building an app.
+pub trait BindHotkeyExt {
+    /// Binds a hotkey to the command with the given ID in the
+    /// [`HotkeyRegistry`].
+    fn bind_hotkey(
+        &mut self,
+        command: impl Into<String>,
+        hotkey: Hotkey,
+        context: HotkeyContext,
+    ) -> &mut Self;
+}
+
+impl BindHotkeyExt for App {
+    fn bind_hotkey(
+        &mut self,
+        command: impl Into<String>,
+        hotkey: Hotkey,
+        context: HotkeyContext,
+    ) -> &mut Self {
+        self.init_resource::<HotkeyRegistry>();
+        self.world_mut()
+            .resource_mut::<HotkeyRegistry>()
+            .bind(command, hotkey, context);
+        self
+    }
+}
+
+/// A marker component for dialogs. While a visible entity with this component
+/// exists, the [`HotkeyContext::Dialog`] bindings are active and the
+/// [`HotkeyContext::Editor`] bindings are suppressed.
+#[derive(Debug, Default, Component)]
+pub struct HotkeyDialog;
+
+/// How a [`ShortcutHint`] is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintStyle {
+    /// The hint is shown after the label of the button, as in menus.
+    Inline,
+
+    /// The hint is shown in a tooltip while the button is hovered.
+    Tooltip,
+}
+
+/// A component that shows the hotkeys of a command on a button.
+///
+/// The hint follows the [`HotkeyRegistry`], so it is updated whenever the
+/// bindings of the command change.
+#[derive(Debug, Component)]
+#[require(Node, Hovered)]
+pub struct ShortcutHint {
+    /// The ID of the command.
+    command: String,
+
+    /// How the hint is shown.
+    style: HintStyle,
+
+    /// The theme for the hint.
+    theme: UiTheme,
+
+    /// The ID of the inline hint text.
+    ///
+    /// This value is assigned when the inline hint is first shown.
+    label_id: Option<Entity>,
+}
+
+impl ShortcutHint {
+    /// Creates a hint shown after the label of the button.
+    pub fn inline(command: impl Into<String>, theme: UiTheme) -> Self {
+        Self {
+            command: command.into(),
+            style: HintStyle::Inline,
+            theme,
+            label_id: None,
+        }
+    }
+
+    /// Creates a hint shown in a tooltip while the button is hovered.
+    pub fn tooltip(command: impl Into<String>, theme: UiTheme) -> Self {
+        Self {
+            command: command.into(),
+            style: HintStyle::Tooltip,
+            theme,
+            label_id: None,
+        }
+    }
+
+    /// Gets the ID of the command.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Gets how the hint is shown.
+    pub fn style(&self) -> HintStyle {
+        self.style
+    }
+}
+
+/// A component on the tooltip showing a [`ShortcutHint`].
+#[derive(Debug, Component)]
+struct HintTooltip {
+    /// The button entity whose hint is shown.
+    owner: Entity,
+}
+
+/// Runs the commands bound to the hotkeys pressed this frame.
+pub(crate) fn dispatch_hotkeys(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    text_focus: Query<(), With<ImeFocus>>,
+    dialogs: Query<&Node, With<HotkeyDialog>>,
+    hotkeys: Res<HotkeyRegistry>,
+    mut registry: ResMut<CommandRegistry>,
+    mut commands: Commands,
+) {
+    if keyboard_input.get_just_pressed().next().is_none() {
+        return;
+    }
+
+    let dialog_open = dialogs.iter().any(|node| node.display != Display::None);
+    let typing = !text_focus.is_empty();
+    let active = |context: HotkeyContext| match context {
+        HotkeyContext::Global => true,
+        HotkeyContext::Editor => hotkeys.editor_enabled && !dialog_open,
+        HotkeyContext::Dialog => dialog_open,
+    };
+
+    let mut matched = hotkeys
+        .bindings
+        .iter()
+        .filter(|binding| active(binding.context))
+        .filter(|binding| !typing || binding.hotkey.works_while_typing())
+        .filter(|binding| binding.hotkey.just_pressed(&keyboard_input))
+        .collect::<Vec<_>>();
+
+    // The sort is stable, so earlier bindings win within the same context.
+    matched.sort_by(|a, b| b.context.cmp(&a.context));
+
+    let mut pressed = Vec::new();
+    for binding in matched {
+        if pressed.contains(&binding.hotkey) {
+            continue;
+        }
+        pressed.push(binding.hotkey);
+
+        if !registry.run(&binding.command, &mut commands) {
+            warn!(
+                "Hotkey {} is bound to unknown command \"{}\"",
+                binding.hotkey, binding.command
+            );
+        }
+    }
+}
+
+/// Shows and updates the inline shortcut hints of buttons.
+fn update_inline_hints(
+    hotkeys: Res<HotkeyRegistry>,
+    mut hints: Query<(Entity, &mut ShortcutHint)>,
+    mut texts: Query<&mut Text>,
+    mut commands: Commands,
+) {
+    for (entity, mut hint) in hints.iter_mut() {
+        if hint.style != HintStyle::Inline || (!hotkeys.is_changed() && !hint.is_added()) {
+            continue;
+        }
+
+        let label = hotkeys.hint(&hint.command).unwrap_or_default();
+        if let Some(label_id) = hint.label_id {
+            if let Ok(mut text) = texts.get_mut(label_id) {
+                text.set_if_neq(Text(label));
+            }
+            continue;
+        }
+
+        let label_id = commands
+            .spawn((
+                ChildOf(entity),
+                Node {
+                    margin: UiRect::left(px(12.0)),
+                    ..default()
+                },
+                Text::new(label),
+                hint.theme.button.container.text.clone(),
+                Pickable::IGNORE,
+            ))
+            .id();
+        hint.label_id = Some(label_id);
+    }
+}
+
+/// Shows a tooltip with the name and hotkeys of the command of the hovered
+/// button, if it has a tooltip shortcut hint.
+fn update_hint_tooltips(
+    hotkeys: Res<HotkeyRegistry>,
+    registry: Res<CommandRegistry>,
+    hints: Query<(
+        Entity,
+        &ShortcutHint,
+        &Hovered,
+        &ComputedNode,
+        &UiGlobalTransform,
+    )>,
+    tooltips: Query<(Entity, &HintTooltip)>,
+    mut commands: Commands,
+) {
+    let hovered = hints.iter().find(|(_, hint, hovered, ..)| {
+        hint.style == HintStyle::Tooltip && hovered.get() && hotkeys.hint(&hint.command).is_some()
+    });
+
+    let owner = hovered.as_ref().map(|(entity, ..)| *entity);
+    let mut shown = false;
+    for (entity, tooltip) in tooltips.iter() {
+        if Some(tooltip.owner) == owner && !hotkeys.is_changed() {
+            shown = true;
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let Some((owner, hint, _, computed, transform)) = hovered else {
+        return;
+    };
+
+    if shown {
+        return;
+    }
+
+    let Some(shortcut) = hotkeys.hint(&hint.command) else {
+        return;
+    };
+
+    let label = match registry.get(&hint.command) {
+        Some(command) => format!("{} ({shortcut})", command.name),
+        None => shortcut,
+    };
+
+    let scale = computed.inverse_scale_factor();
+    let size = computed.size() * scale;
+    let center = transform.translation * scale;
+
+    commands.spawn((
+        HintTooltip { owner },
+        Node {
+            position_type: PositionType::Absolute,
+            left: px(center.x - size.x / 2.0),
+            top: px(center.y + size.y / 2.0 + TOOLTIP_OFFSET),
+            ..default()
+        },
+        hint.theme.inner_window.clone(),
+        UiLayer::Tooltips,
+        Pickable::IGNORE,
+        children![(
+            Text::new(label),
+            hint.theme.inner_window.text.clone(),
+            Pickable::IGNORE,
+        )],
+    ));
+}
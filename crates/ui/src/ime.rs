@@ -0,0 +1,185 @@
+//! This module implements IME (input method editor) support for text widgets.
+//!
+//! Text shaping, including bidirectional (RTL) text, is handled by Bevy's text
+//! pipeline, so labels within tree views and grid previews already render RTL
+//! strings correctly. This module only deals with IME composition, which must
+//! be explicitly forwarded to the widget that currently receives text input.
+
+use bevy::prelude::*;
+use bevy::window::{Ime, PrimaryWindow};
+
+/// A plugin that adds IME composition support to the UI.
+pub struct ImePlugin;
+impl Plugin for ImePlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<ImeComposition>().add_systems(
+            PreUpdate,
+            (update_ime_window, read_ime_messages)
+                .chain()
+                .in_set(ImeSystems::ReadComposition),
+        );
+    }
+}
+
+/// System sets for IME-related systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
+pub enum ImeSystems {
+    /// Reads IME messages from the window and updates the composition state.
+    ReadComposition,
+}
+
+/// A marker component for the UI node that currently receives text input.
+///
+/// While an entity with this component exists, IME input is enabled on the
+/// primary window and the candidate window is positioned at the node. Only one
+/// entity should have this component at a time.
+#[derive(Debug, Default, Component)]
+#[require(Node)]
+pub struct ImeFocus;
+
+/// The current IME composition state.
+#[derive(Debug, Default, Resource)]
+pub struct ImeComposition {
+    /// The text that is currently being composed, but not yet committed.
+    pub preedit: String,
+
+    /// The cursor position within the preedit text, as a byte range.
+    pub cursor: Option<(usize, usize)>,
+}
+
+impl ImeComposition {
+    /// Returns true if the user is currently composing text.
+    pub fn is_composing(&self) -> bool {
+        !self.preedit.is_empty()
+    }
+}
+
+/// An event that is triggered on the focused text widget when the IME commits
+/// a composed string.
+#[derive(Debug, EntityEvent)]
+pub struct ImeCommit {
+    /// The entity that received the committed text.
+    pub entity: Entity,
+
+    /// The committed text.
+    pub value: String,
+}
+
+/// A component that marks a text entity as the composition preview for a text
+/// widget. The text will be updated to mirror the current preedit string while
+/// its parent has [`ImeFocus`].
+#[derive(Debug, Default, Component)]
+#[require(Text)]
+pub struct ImePreview;
+
+/// Enables or disables IME on the primary window depending on whether a text
+/// widget is focused, and moves the candidate window to the focused widget.
+fn update_ime_window(
+    focus: Query<(&ComputedNode, &UiGlobalTransform), With<ImeFocus>>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = window.single_mut() else {
+        return;
+    };
+
+    match focus.single() {
+        Ok((computed, transform)) => {
+            if !window.ime_enabled {
+                window.ime_enabled = true;
+            }
+
+            let bottom_left =
+                transform.translation + Vec2::new(-computed.size().x, computed.size().y) * 0.5;
+            let position = bottom_left * computed.inverse_scale_factor();
+            if window.ime_position != position {
+                window.ime_position = position;
+            }
+        }
+        Err(_) => {
+            if window.ime_enabled {
+                window.ime_enabled = false;
+            }
+        }
+    }
+}
+
+/// Reads IME messages and forwards them to the focused text widget.
+///
+/// When the focus moves to another widget, the composition preview of the
+/// previously focused widget is cleared.
+fn read_ime_messages(
+    mut ime_reader: MessageReader<Ime>,
+    mut composition: ResMut<ImeComposition>,
+    mut last_focused: Local<Option<Entity>>,
+    focus: Query<Entity, With<ImeFocus>>,
+    children: Query<&Children>,
+    mut previews: Query<&mut Text, With<ImePreview>>,
+    mut commands: Commands,
+) {
+    let focused = focus.single().ok();
+
+    if *last_focused != focused {
+        if let Some(previous) = last_focused.take() {
+            set_preview(previous, "", &children, &mut previews);
+        }
+        *last_focused = focused;
+    }
+
+    for message in ime_reader.read() {
+        match message {
+            Ime::Preedit { value, cursor, .. } => {
+                composition.preedit = value.clone();
+                composition.cursor = *cursor;
+            }
+            Ime::Commit { value, .. } => {
+                composition.preedit.clear();
+                composition.cursor = None;
+
+                if let Some(entity) = focused {
+                    commands.trigger(ImeCommit {
+                        entity,
+                        value: value.clone(),
+                    });
+                }
+            }
+            Ime::Enabled { .. } | Ime::Disabled { .. } => {
+                composition.preedit.clear();
+                composition.cursor = None;
+            }
+        }
+    }
+
+    if focused.is_none() && composition.is_composing() {
+        composition.preedit.clear();
+        composition.cursor = None;
+    }
+
+    if !composition.is_changed() {
+        return;
+    }
+
+    if let Some(entity) = focused {
+        set_preview(entity, &composition.preedit, &children, &mut previews);
+    }
+}
+
+/// Sets the text of the composition previews among the children of the given
+/// text widget.
+fn set_preview(
+    entity: Entity,
+    preedit: &str,
+    children: &Query<&Children>,
+    previews: &mut Query<&mut Text, With<ImePreview>>,
+) {
+    let Ok(children) = children.get(entity) else {
+        return;
+    };
+
+    for child in children.iter() {
+        if let Ok(mut text) = previews.get_mut(child) {
+            if text.0 != preedit {
+                text.0 = preedit.to_string();
+            }
+        }
+    }
+}
@@ -6,6 +6,8 @@ use bevy::prelude::*;
 use bevy::reflect::Is;
 use bevy::ui::{InteractionDisabled, Pressed};
 
+use crate::widgets::button::RepeatHeld;
+
 /// A plugin that adds improved interaction support to the UI.
 pub struct InteractionPlugin;
 impl Plugin for InteractionPlugin {
@@ -16,6 +18,8 @@ impl Plugin for InteractionPlugin {
         .add_observer(update_interaction::<Insert, Hovered>)
         .add_observer(update_interaction::<Add, Pressed>)
         .add_observer(update_interaction::<Remove, Pressed>)
+        .add_observer(update_interaction::<Add, RepeatHeld>)
+        .add_observer(update_interaction::<Remove, RepeatHeld>)
         .add_observer(update_interaction::<Add, InteractionDisabled>)
         .add_observer(update_interaction::<Remove, InteractionDisabled>);
     }
@@ -52,6 +56,16 @@ impl InteractionReceiver {
             | InteractionReceiver::Disable(checked) => *checked,
         }
     }
+
+    /// Gets this interaction state with its checked/selected flag replaced.
+    pub fn with_checked(self, checked: bool) -> Self {
+        match self {
+            InteractionReceiver::Default(_) => InteractionReceiver::Default(checked),
+            InteractionReceiver::Hovered(_) => InteractionReceiver::Hovered(checked),
+            InteractionReceiver::Pressed(_) => InteractionReceiver::Pressed(checked),
+            InteractionReceiver::Disable(_) => InteractionReceiver::Disable(checked),
+        }
+    }
 }
 
 impl Default for InteractionReceiver {
@@ -78,6 +92,7 @@ fn update_interaction<E, A>(
         Option<&mut InteractionReceiver>,
         Option<&mut Propagate<InteractionReceiver>>,
         Has<Pressed>,
+        Has<RepeatHeld>,
         Has<InteractionDisabled>,
         &Hovered,
     )>,
@@ -85,13 +100,16 @@ fn update_interaction<E, A>(
     E: EntityEvent,
     A: Component,
 {
-    let Ok((maybe_interact, maybe_propagate, pressed, disabled, hovered)) =
+    let Ok((maybe_interact, maybe_propagate, pressed, held, disabled, hovered)) =
         query.get_mut(trigger.event_target())
     else {
         return;
     };
 
-    let pressed = pressed && !(E::is::<Remove>() && A::is::<Pressed>());
+    // Held buttons that started repeating are shown as pressed, even though
+    // they no longer have the pressed component.
+    let pressed = (pressed && !(E::is::<Remove>() && A::is::<Pressed>()))
+        || (held && !(E::is::<Remove>() && A::is::<RepeatHeld>()));
     let disabled = disabled && !(E::is::<Remove>() && A::is::<InteractionDisabled>());
     let checked = maybe_interact
         .as_ref()
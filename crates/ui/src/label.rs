@@ -0,0 +1,360 @@
+//! This module implements overflow handling for themed labels.
+//!
+//! The [`LabelOverflow`] of a [`FontTheme`] decides what happens to the text of
+//! a label that does not fit within its node. Labels may be clipped, shortened
+//! with an ellipsis, or wrapped onto a limited number of lines.
+//!
+//! Shortened labels are measured from their laid out glyphs, so the cut always
+//! matches the font in use. They are fitted again whenever their text changes,
+//! their node is restyled, or their node grows. While a label is cut short, its
+//! full text is shown in a tooltip when it is hovered.
+
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::text::{PositionedGlyph, TextLayoutInfo};
+
+use crate::menus::layers::UiLayer;
+use crate::theme::{ContainerTheme, FontTheme};
+
+/// The text appended to labels that are shortened.
+const ELLIPSIS: &str = "...";
+
+/// The estimated width of the [`ELLIPSIS`], relative to the font size. Labels
+/// that still overflow with the ellipsis are shortened further on the next
+/// frame.
+const ELLIPSIS_WIDTH: f32 = 0.9;
+
+/// The maximum width of the tooltip showing the full text of a label.
+const TOOLTIP_MAX_WIDTH: f32 = 480.0;
+
+/// The gap, in pixels, between a label and its full text tooltip.
+const TOOLTIP_OFFSET: f32 = 4.0;
+
+/// The background color of the full text tooltip of a label that is not drawn
+/// on a themed container.
+const TOOLTIP_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.8);
+
+/// A plugin that adds overflow handling for themed labels.
+pub(crate) struct LabelPlugin;
+impl Plugin for LabelPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_observer(on_label_added)
+            .add_systems(Update, (fit_labels, update_label_tooltips).chain());
+    }
+}
+
+/// How the text of a themed label behaves when it does not fit within its
+/// node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelOverflow {
+    /// The text is kept on a single line, and the part that does not fit is
+    /// cut off.
+    Clip,
+
+    /// The text is kept on a single line, and is shortened with an ellipsis
+    /// when it does not fit.
+    Ellipsis,
+
+    /// The text wraps onto new lines.
+    Wrap {
+        /// The maximum number of lines. If the text needs more lines, it is
+        /// shortened with an ellipsis at the end of the last line. If `None`,
+        /// the text may use as many lines as it needs.
+        max_lines: Option<usize>,
+    },
+}
+
+impl Default for LabelOverflow {
+    fn default() -> Self {
+        LabelOverflow::Wrap { max_lines: None }
+    }
+}
+
+impl LabelOverflow {
+    /// Gets the maximum number of lines that the text may be shown on before
+    /// it is shortened, or `None` if the text is never shortened.
+    fn max_lines(self) -> Option<usize> {
+        match self {
+            LabelOverflow::Clip => None,
+            LabelOverflow::Ellipsis => Some(1),
+            LabelOverflow::Wrap { max_lines } => max_lines,
+        }
+    }
+}
+
+/// A component on themed labels whose text may not fit within their node.
+#[derive(Debug, Component)]
+struct FittedLabel {
+    /// How the label behaves when its text does not fit.
+    overflow: LabelOverflow,
+
+    /// The full text of the label.
+    full: String,
+
+    /// The text that is currently shown. This is the full text, unless the
+    /// label has been shortened.
+    shown: String,
+
+    /// The width of the content box of the label node, in physical pixels,
+    /// when the label was last fitted.
+    width: f32,
+
+    /// Whether part of the full text is hidden, either because the label was
+    /// shortened or because it is clipped.
+    truncated: bool,
+}
+
+/// A component on the tooltip showing the full text of a truncated label.
+#[derive(Debug, Component)]
+struct LabelTooltip {
+    /// The label whose full text is shown.
+    owner: Entity,
+
+    /// The text shown in the tooltip.
+    text: String,
+}
+
+/// Applies the overflow option of a font theme to its label when the theme is
+/// added.
+fn on_label_added(
+    trigger: On<Add, FontTheme>,
+    mut query: Query<(&FontTheme, &Text, &mut Node, &mut TextLayout)>,
+    mut commands: Commands,
+) {
+    let Ok((theme, text, mut node, mut layout)) = query.get_mut(trigger.entity) else {
+        return;
+    };
+
+    match theme.overflow {
+        LabelOverflow::Clip | LabelOverflow::Ellipsis => {
+            node.min_width = px(0.0);
+            layout.linebreak = LineBreak::NoWrap;
+        }
+        LabelOverflow::Wrap { max_lines: Some(_) } => {}
+        LabelOverflow::Wrap { max_lines: None } => return,
+    }
+    node.overflow = Overflow::clip();
+
+    commands.entity(trigger.entity).insert((
+        FittedLabel {
+            overflow: theme.overflow,
+            full: text.0.clone(),
+            shown: text.0.clone(),
+            width: 0.0,
+            truncated: false,
+        },
+        Hovered::default(),
+    ));
+}
+
+/// Shortens the labels whose text does not fit within their node, using the
+/// glyphs laid out for the text shown on the previous frame.
+fn fit_labels(
+    mut labels: Query<(
+        &mut FittedLabel,
+        &mut Text,
+        &TextFont,
+        &TextLayoutInfo,
+        &ComputedNode,
+        Ref<Node>,
+    )>,
+) {
+    for (mut label, mut text, font, layout, computed, node) in labels.iter_mut() {
+        if text.0 != label.shown {
+            label.full = text.0.clone();
+            label.shown = text.0.clone();
+            label.truncated = false;
+            continue;
+        }
+
+        let inset = computed.content_inset();
+        let width = computed.size().x - inset.left - inset.right;
+        let grown = width > label.width + 0.5;
+        label.width = width;
+
+        if (grown || node.is_changed()) && label.shown != label.full {
+            label.shown = label.full.clone();
+            text.0 = label.full.clone();
+            label.truncated = false;
+            continue;
+        }
+
+        if layout.glyphs.is_empty() {
+            continue;
+        }
+
+        let Some(max_lines) = label.overflow.max_lines() else {
+            label.truncated = layout
+                .glyphs
+                .iter()
+                .any(|glyph| right_edge(glyph) > width + 0.5);
+            continue;
+        };
+
+        let kept = if label.shown == label.full {
+            label.full.len()
+        } else {
+            label.shown.len() - ELLIPSIS.len()
+        };
+        let reserve = font.font_size * ELLIPSIS_WIDTH / computed.inverse_scale_factor();
+
+        let Some(cut) = cut_point(layout, &label.shown, kept, width, max_lines, reserve) else {
+            continue;
+        };
+
+        let shown = format!("{}{ELLIPSIS}", label.full[.. cut].trim_end());
+        text.0 = shown.clone();
+        label.shown = shown;
+        label.truncated = true;
+    }
+}
+
+/// Finds the byte index at which the full text of a label must be cut so that
+/// it fits within the given width and number of lines, with room for the
+/// ellipsis. Only the first `kept` bytes of the shown text belong to the full
+/// text.
+///
+/// Returns `None` if the shown text already fits.
+fn cut_point(
+    layout: &TextLayoutInfo,
+    shown: &str,
+    kept: usize,
+    width: f32,
+    max_lines: usize,
+    reserve: f32,
+) -> Option<usize> {
+    let mut line_starts = vec![0];
+    line_starts.extend(shown.match_indices('\n').map(|(index, _)| index + 1));
+
+    let mut line = 0;
+    let mut previous: Option<&PositionedGlyph> = None;
+    let mut overflows = false;
+    let mut cut = 0;
+
+    for glyph in layout.glyphs.iter() {
+        if let Some(previous) = previous {
+            let wrapped =
+                glyph.position.x - glyph.size.x / 2.0 < previous.position.x - previous.size.x / 2.0;
+            if glyph.line_index != previous.line_index || wrapped {
+                line += 1;
+            }
+        }
+        previous = Some(glyph);
+
+        if line >= max_lines || right_edge(glyph) > width + 0.5 {
+            overflows = true;
+            continue;
+        }
+
+        let start = line_starts
+            .get(glyph.line_index)
+            .copied()
+            .unwrap_or_default();
+        let end = start + glyph.byte_index + glyph.byte_length;
+        if end <= kept && right_edge(glyph) <= width - reserve {
+            cut = cut.max(end);
+        }
+    }
+
+    if !overflows {
+        return None;
+    }
+
+    // Make sure that a label which still overflows after being shortened is
+    // shortened further.
+    if kept < shown.len() && cut >= kept {
+        cut = shown[.. kept]
+            .char_indices()
+            .last()
+            .map(|(index, _)| index)
+            .unwrap_or(0);
+    }
+
+    Some(cut)
+}
+
+/// Gets the position of the right edge of a laid out glyph.
+fn right_edge(glyph: &PositionedGlyph) -> f32 {
+    glyph.position.x + glyph.size.x / 2.0
+}
+
+/// Shows a tooltip with the full text of the hovered label, if the label is
+/// truncated.
+///
+/// The tooltip is styled like the nearest container that the label is drawn
+/// on.
+fn update_label_tooltips(
+    labels: Query<(
+        Entity,
+        &FittedLabel,
+        &FontTheme,
+        &Hovered,
+        &ComputedNode,
+        &UiGlobalTransform,
+    )>,
+    parents: Query<&ChildOf>,
+    containers: Query<&ContainerTheme>,
+    tooltips: Query<(Entity, &LabelTooltip)>,
+    mut commands: Commands,
+) {
+    let hovered = labels
+        .iter()
+        .find(|(_, label, _, hovered, ..)| label.truncated && hovered.get());
+
+    let mut shown = false;
+    for (entity, tooltip) in tooltips.iter() {
+        let current = hovered.as_ref().is_some_and(|(owner, label, ..)| {
+            tooltip.owner == *owner && tooltip.text == label.full
+        });
+
+        if current {
+            shown = true;
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let Some((owner, label, theme, _, computed, transform)) = hovered else {
+        return;
+    };
+
+    if shown {
+        return;
+    }
+
+    let scale = computed.inverse_scale_factor();
+    let size = computed.size() * scale;
+    let center = transform.translation * scale;
+
+    let mut tooltip = commands.spawn((
+        LabelTooltip {
+            owner,
+            text: label.full.clone(),
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            left: px(center.x - size.x / 2.0),
+            top: px(center.y + size.y / 2.0 + TOOLTIP_OFFSET),
+            max_width: px(TOOLTIP_MAX_WIDTH),
+            ..default()
+        },
+        UiLayer::Tooltips,
+        Pickable::IGNORE,
+        children![(
+            Text::new(label.full.clone()),
+            FontTheme {
+                overflow: LabelOverflow::default(),
+                ..theme.clone()
+            },
+            Pickable::IGNORE,
+        )],
+    ));
+
+    match parents
+        .iter_ancestors(owner)
+        .find_map(|entity| containers.get(entity).ok())
+    {
+        Some(container) => tooltip.insert(container.clone()),
+        None => tooltip.insert(BackgroundColor(TOOLTIP_BACKGROUND)),
+    };
+}
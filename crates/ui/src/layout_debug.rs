@@ -0,0 +1,401 @@
+//! This module implements a layout inspector overlay, which helps debug
+//! misbehaving UI layouts.
+//!
+//! While [`LayoutInspector`] is enabled, the UI node under the cursor has its
+//! bounds, border, and padding highlighted, and a panel lists the node's
+//! geometry, its component stack, and the UI hierarchy with the hovered node
+//! marked. The overlay itself is ignored by picking, so it never hides the
+//! nodes beneath it.
+
+use bevy::picking::hover::HoverMap;
+use bevy::prelude::*;
+
+use crate::menus::layers::UiLayer;
+
+/// The color of the highlighted border area of the hovered node.
+const BORDER_COLOR: Color = Color::srgba(1.0, 0.6, 0.0, 0.35);
+
+/// The color of the highlighted padding area of the hovered node.
+const PADDING_COLOR: Color = Color::srgba(0.3, 0.9, 0.3, 0.35);
+
+/// The color of the highlighted content area of the hovered node.
+const CONTENT_COLOR: Color = Color::srgba(0.3, 0.6, 1.0, 0.35);
+
+/// The background color of the inspector panel.
+const PANEL_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.8);
+
+/// The font size of the inspector panel text.
+const FONT_SIZE: f32 = 12.0;
+
+/// The maximum number of lines shown in the hierarchy tree.
+const MAX_TREE_LINES: usize = 60;
+
+/// The maximum number of characters of text shown for a text node in the
+/// hierarchy tree.
+const MAX_LABEL_LENGTH: usize = 24;
+
+/// A plugin that adds the layout inspector overlay.
+pub struct LayoutInspectorPlugin;
+impl Plugin for LayoutInspectorPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<LayoutInspector>().add_systems(
+            Update,
+            (
+                build_layout_inspector.run_if(resource_changed::<LayoutInspector>),
+                update_layout_inspector,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// A resource that controls the layout inspector overlay.
+#[derive(Debug, Default, Resource)]
+pub struct LayoutInspector {
+    /// Whether the layout inspector overlay is shown.
+    pub enabled: bool,
+}
+
+/// A marker component for the root node of the layout inspector overlay.
+#[derive(Debug, Component)]
+pub struct LayoutInspectorOverlay;
+
+/// A component for the overlay nodes that highlight an area of the hovered
+/// node.
+#[derive(Debug, Clone, Copy, Component)]
+enum HighlightArea {
+    /// The full bounds of the node, including its border.
+    Border,
+
+    /// The area within the border of the node, including its padding.
+    Padding,
+
+    /// The area within the padding of the node.
+    Content,
+}
+
+/// A marker component for the text listing the details of the hovered node.
+#[derive(Debug, Component)]
+struct NodeDetailsText;
+
+/// A marker component for the text showing the UI hierarchy.
+#[derive(Debug, Component)]
+struct HierarchyText;
+
+/// A rectangle in logical pixels, relative to the top-left of the window.
+#[derive(Debug, Clone, Copy)]
+struct LogicalRect {
+    /// The top-left corner of the rectangle.
+    min: Vec2,
+
+    /// The size of the rectangle.
+    size: Vec2,
+}
+
+impl LogicalRect {
+    /// Shrinks this rectangle by the given insets on each side.
+    fn inset(self, left: f32, right: f32, top: f32, bottom: f32) -> Self {
+        Self {
+            min: self.min + Vec2::new(left, top),
+            size: (self.size - Vec2::new(left + right, top + bottom)).max(Vec2::ZERO),
+        }
+    }
+}
+
+/// This system spawns or despawns the layout inspector overlay when the
+/// inspector is enabled or disabled.
+fn build_layout_inspector(
+    inspector: Res<LayoutInspector>,
+    overlays: Query<Entity, With<LayoutInspectorOverlay>>,
+    mut commands: Commands,
+) {
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if !inspector.enabled {
+        return;
+    }
+
+    let highlight = |area: HighlightArea, color: Color| {
+        (
+            area,
+            Node {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            BackgroundColor(color),
+            Pickable::IGNORE,
+        )
+    };
+
+    commands.spawn((
+        LayoutInspectorOverlay,
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100.0),
+            height: percent(100.0),
+            ..default()
+        },
+        UiLayer::Debug,
+        Pickable::IGNORE,
+        children![
+            highlight(HighlightArea::Border, BORDER_COLOR),
+            highlight(HighlightArea::Padding, PADDING_COLOR),
+            highlight(HighlightArea::Content, CONTENT_COLOR),
+            (
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: px(8.0),
+                    right: px(8.0),
+                    max_width: px(420.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(8.0),
+                    padding: UiRect::all(px(6.0)),
+                    ..default()
+                },
+                BackgroundColor(PANEL_COLOR),
+                Pickable::IGNORE,
+                children![panel_text(NodeDetailsText), panel_text(HierarchyText)],
+            ),
+        ],
+    ));
+}
+
+/// Creates a text node for the inspector panel with the given marker.
+fn panel_text(marker: impl Component) -> impl Bundle {
+    (
+        marker,
+        Text::default(),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Pickable::IGNORE,
+    )
+}
+
+/// This system highlights the hovered UI node and updates the inspector panel
+/// text while the layout inspector is enabled.
+fn update_layout_inspector(world: &mut World) {
+    if !world.resource::<LayoutInspector>().enabled {
+        return;
+    }
+
+    let hovered = hovered_node(world);
+    let areas = hovered.and_then(|entity| node_areas(world, entity));
+    let details = match hovered {
+        Some(entity) => node_details(world, entity),
+        None => "Hover over a UI node to inspect it".to_string(),
+    };
+
+    let mut roots = world.query_filtered::<Entity, (
+        With<Node>,
+        Without<ChildOf>,
+        Without<LayoutInspectorOverlay>,
+    )>();
+    let mut roots = roots.iter(world).collect::<Vec<_>>();
+    roots.sort();
+    let hierarchy = hierarchy_tree(world, &roots, hovered);
+
+    let mut highlights = world.query::<(&HighlightArea, &mut Node)>();
+    for (area, mut node) in highlights.iter_mut(world) {
+        let rect = areas.map(|[border, padding, content]| match area {
+            HighlightArea::Border => border,
+            HighlightArea::Padding => padding,
+            HighlightArea::Content => content,
+        });
+
+        // Only write changes, to avoid recomputing the layout every frame.
+        node.set_if_neq(match rect {
+            Some(rect) => Node {
+                display: Display::Flex,
+                left: px(rect.min.x),
+                top: px(rect.min.y),
+                width: px(rect.size.x),
+                height: px(rect.size.y),
+                ..node.clone()
+            },
+            None => Node {
+                display: Display::None,
+                ..node.clone()
+            },
+        });
+    }
+
+    let mut details_text = world.query_filtered::<&mut Text, With<NodeDetailsText>>();
+    for mut text in details_text.iter_mut(world) {
+        text.set_if_neq(Text(details.clone()));
+    }
+
+    let mut hierarchy_text = world.query_filtered::<&mut Text, With<HierarchyText>>();
+    for mut text in hierarchy_text.iter_mut(world) {
+        text.set_if_neq(Text(hierarchy.clone()));
+    }
+}
+
+/// Finds the deepest UI node under any pointer, if any.
+fn hovered_node(world: &World) -> Option<Entity> {
+    let hover_map = world.get_resource::<HoverMap>()?;
+    hover_map
+        .values()
+        .flat_map(|hits| hits.keys().copied())
+        .filter(|entity| world.get::<ComputedNode>(*entity).is_some())
+        .max_by_key(|entity| ancestors(world, *entity).count())
+}
+
+/// Iterates over the ancestors of an entity, from its parent to the root.
+fn ancestors(world: &World, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+    std::iter::successors(Some(entity), |entity| {
+        world.get::<ChildOf>(*entity).map(ChildOf::parent)
+    })
+    .skip(1)
+}
+
+/// Gets the border, padding, and content areas of a UI node, in logical
+/// pixels.
+fn node_areas(world: &World, entity: Entity) -> Option<[LogicalRect; 3]> {
+    let computed = world.get::<ComputedNode>(entity)?;
+    let transform = world.get::<UiGlobalTransform>(entity)?;
+
+    let scale = computed.inverse_scale_factor();
+    let size = computed.size() * scale;
+    let border_area = LogicalRect {
+        min: transform.translation * scale - size / 2.0,
+        size,
+    };
+
+    let border = computed.border();
+    let padding_area = border_area.inset(
+        border.left * scale,
+        border.right * scale,
+        border.top * scale,
+        border.bottom * scale,
+    );
+
+    let padding = computed.padding();
+    let content_area = padding_area.inset(
+        padding.left * scale,
+        padding.right * scale,
+        padding.top * scale,
+        padding.bottom * scale,
+    );
+
+    Some([border_area, padding_area, content_area])
+}
+
+/// Describes the geometry and component stack of a UI node.
+fn node_details(world: &World, entity: Entity) -> String {
+    let mut details = format!("{}\n", node_label(world, entity));
+
+    if let Some([border_area, padding_area, content_area]) = node_areas(world, entity) {
+        let insets = |outer: LogicalRect, inner: LogicalRect| {
+            let start = inner.min - outer.min;
+            let end = outer.size - inner.size - start;
+            format!("{:.0} {:.0} {:.0} {:.0}", start.y, end.x, end.y, start.x)
+        };
+
+        details += &format!(
+            "position: {:.0}, {:.0}\nsize: {:.0} x {:.0}\nborder: {}\npadding: {}\n",
+            border_area.min.x,
+            border_area.min.y,
+            border_area.size.x,
+            border_area.size.y,
+            insets(border_area, padding_area),
+            insets(padding_area, content_area),
+        );
+    }
+
+    details += "components:";
+    if let Ok(components) = world.inspect_entity(entity) {
+        let mut names = components
+            .map(|info| info.name().shortname().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+
+        for name in names {
+            details += &format!("\n  {name}");
+        }
+    }
+
+    details
+}
+
+/// Draws the UI hierarchy under the given root nodes as an indented tree,
+/// marking the hovered node and its ancestors.
+fn hierarchy_tree(world: &World, roots: &[Entity], hovered: Option<Entity>) -> String {
+    let path = hovered
+        .map(|entity| {
+            std::iter::once(entity)
+                .chain(ancestors(world, entity))
+                .collect()
+        })
+        .unwrap_or_else(Vec::new);
+
+    let mut lines = Vec::new();
+    let mut truncated = false;
+    for &root in roots {
+        truncated |= !push_tree_lines(world, root, 0, hovered, &path, &mut lines);
+    }
+
+    if truncated {
+        lines.push("...".to_string());
+    }
+
+    format!("hierarchy:\n{}", lines.join("\n"))
+}
+
+/// Pushes the lines of the hierarchy tree under the given node, returning
+/// false if the tree was truncated.
+fn push_tree_lines(
+    world: &World,
+    entity: Entity,
+    depth: usize,
+    hovered: Option<Entity>,
+    path: &[Entity],
+    lines: &mut Vec<String>,
+) -> bool {
+    if lines.len() >= MAX_TREE_LINES {
+        return false;
+    }
+
+    let marker = if Some(entity) == hovered {
+        "> "
+    } else if path.contains(&entity) {
+        "+ "
+    } else {
+        "  "
+    };
+    lines.push(format!(
+        "{}{}{}",
+        "  ".repeat(depth),
+        marker,
+        node_label(world, entity)
+    ));
+
+    let Some(children) = world.get::<Children>(entity) else {
+        return true;
+    };
+
+    children
+        .iter()
+        .filter(|child| world.get::<Node>(*child).is_some())
+        .all(|child| push_tree_lines(world, child, depth + 1, hovered, path, lines))
+}
+
+/// Gets a short label for a UI node: its name, the start of its text, or its
+/// entity id.
+fn node_label(world: &World, entity: Entity) -> String {
+    if let Some(name) = world.get::<Name>(entity) {
+        return format!("{name} ({entity})");
+    }
+
+    if let Some(text) = world.get::<Text>(entity) {
+        let mut label = text.0.chars().take(MAX_LABEL_LENGTH).collect::<String>();
+        if text.0.chars().count() > MAX_LABEL_LENGTH {
+            label += "...";
+        }
+        return format!("\"{label}\" ({entity})");
+    }
+
+    entity.to_string()
+}
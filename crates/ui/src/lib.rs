@@ -8,76 +8,190 @@
 use bevy::prelude::*;
 use bevy::ui_widgets::UiWidgetsPlugins;
 
+pub mod accessibility;
+#[cfg(feature = "editor")]
+pub mod assets;
+pub mod clipboard;
 pub mod color;
+#[cfg(feature = "hotkeys")]
+pub mod commands;
+pub mod contrast;
+#[cfg(feature = "hotkeys")]
+pub mod hotkeys;
+pub mod ime;
 pub mod interaction;
+pub mod label;
+pub mod layout_debug;
 pub mod menus;
 pub mod scroll;
+pub mod sounds;
 pub mod theme;
 pub mod themes;
 pub mod util;
 pub mod widgets;
 
 /// The path to the default Awgen UI font: "Quiver".
+///
+/// This and the other theme asset paths are served by the
+/// [`UI_ASSET_SOURCE`](assets::UI_ASSET_SOURCE) asset source, so that projects
+/// may override them.
 #[cfg(feature = "editor")]
-pub const QUIVER_FONT: &str = "embedded://awgen_ui/fonts/quiver.ttf";
+pub const QUIVER_FONT: &str = "awgen_ui://fonts/quiver.ttf";
 
 /// The path to the right arrow icon used in tree views.
 #[cfg(feature = "editor")]
-pub const RIGHT_ARROW_ICON: &str = "embedded://awgen_ui/icons/right_arrow.png";
+pub const RIGHT_ARROW_ICON: &str = "awgen_ui://icons/right_arrow.png";
 
 /// The path to the down arrow icon used in tree views.
 #[cfg(feature = "editor")]
-pub const DOWN_ARROW_ICON: &str = "embedded://awgen_ui/icons/down_arrow.png";
+pub const DOWN_ARROW_ICON: &str = "awgen_ui://icons/down_arrow.png";
 
 /// The path to the vertical spacer icon used in tree views.
 #[cfg(feature = "editor")]
-pub const SPACER_ICON: &str = "embedded://awgen_ui/icons/vert_spacer.png";
+pub const SPACER_ICON: &str = "awgen_ui://icons/vert_spacer.png";
 
 /// The path to the folder icon used in tree views.
 #[cfg(feature = "editor")]
-pub const FOLDER_ICON: &str = "embedded://awgen_ui/icons/folder.png";
+pub const FOLDER_ICON: &str = "awgen_ui://icons/folder.png";
+
+/// The path to the placeholder icon shown while a deferred grid preview icon
+/// is loading.
+#[cfg(feature = "editor")]
+pub const LOADING_ICON: &str = "awgen_ui://icons/loading.png";
+
+/// The path to the icon shown when a deferred grid preview icon fails to load.
+#[cfg(feature = "editor")]
+pub const ERROR_ICON: &str = "awgen_ui://icons/error.png";
 
 /// A prelude module for easy importing of common types.
 pub mod prelude {
     pub use bevy::ui_widgets::{Activate, observe};
 
     pub use super::AwgenUiPlugin;
+    pub use super::accessibility::*;
+    #[cfg(feature = "editor")]
+    pub use super::assets::AwgenUiAssetsExt;
+    pub use super::clipboard::*;
     pub use super::color::*;
+    #[cfg(feature = "hotkeys")]
+    pub use super::commands::*;
+    pub use super::contrast::*;
+    #[cfg(feature = "hotkeys")]
+    pub use super::hotkeys::*;
+    pub use super::ime::*;
     pub use super::interaction::*;
+    pub use super::label::*;
+    pub use super::layout_debug::*;
+    pub use super::menus::layers::*;
+    #[cfg(feature = "menus")]
+    pub use super::menus::maximize::*;
     pub use super::menus::overlay::*;
+    #[cfg(feature = "menus")]
+    pub use super::menus::windows::*;
     pub use super::scroll::*;
+    pub use super::sounds::*;
     pub use super::theme::*;
     pub use super::util::*;
+    pub use super::widgets::busy::*;
     pub use super::widgets::button::*;
+    #[cfg(feature = "command_palette")]
+    pub use super::widgets::command_palette::{CommandPalette, DEFAULT_MAX_RESULTS};
+    #[cfg(feature = "console")]
+    pub use super::widgets::console::*;
+    #[cfg(feature = "dialogs")]
+    pub use super::widgets::form::*;
+    #[cfg(feature = "grid_preview")]
     pub use super::widgets::grid_preview::*;
+    #[cfg(feature = "inspector")]
+    pub use super::widgets::inspector::*;
+    pub use super::widgets::placeholder::*;
+    pub use super::widgets::text_input::*;
+    #[cfg(feature = "tree_view")]
     pub use super::widgets::tree_view::*;
 }
 
 /// A plugin that adds support for common UI widgets.
+///
+/// This plugin must be added after the [`AssetPlugin`]. With the `editor`
+/// feature, the UI theme assets must also be registered with
+/// `register_ui_assets` before the [`AssetPlugin`] is added.
+///
+/// # Panics
+///
+/// Panics if the [`AssetPlugin`] has not been added yet.
 pub struct AwgenUiPlugin;
 impl Plugin for AwgenUiPlugin {
     fn build(&self, app_: &mut App) {
+        assert!(
+            app_.is_plugin_added::<AssetPlugin>(),
+            "The AwgenUiPlugin must be added after the AssetPlugin"
+        );
+
         app_.add_plugins((
             UiWidgetsPlugins,
+            accessibility::AccessibilityPlugin,
+            clipboard::ClipboardPlugin,
             interaction::InteractionPlugin,
+            ime::ImePlugin,
+            label::LabelPlugin,
             menus::overlay::OverlayPlugin,
             scroll::ScrollPlugin,
+            sounds::UiSoundsPlugin,
             color::ColorPlugin,
+            contrast::ContrastDebugPlugin,
+            layout_debug::LayoutInspectorPlugin,
+            widgets::placeholder::PlaceholderPlugin,
+            widgets::busy::BusyOverlayPlugin,
+            widgets::text_input::TextInputPlugin,
         ))
         .add_observer(theme::style_container)
         .add_observer(theme::style_text)
-        .add_observer(widgets::tree_view::on_tree_added)
-        .add_observer(widgets::grid_preview::on_grid_add);
+        .add_observer(widgets::button::release_repeat_button::<Release>)
+        .add_observer(widgets::button::release_repeat_button::<DragEnd>)
+        .add_observer(widgets::button::release_repeat_button::<Cancel>)
+        .add_systems(Update, widgets::button::repeat_held_buttons);
+
+        #[cfg(feature = "hotkeys")]
+        app_.add_plugins(hotkeys::HotkeysPlugin);
+
+        #[cfg(feature = "command_palette")]
+        app_.add_plugins(widgets::command_palette::CommandPalettePlugin);
+
+        #[cfg(feature = "console")]
+        app_.add_plugins(widgets::console::ConsolePlugin);
+
+        #[cfg(feature = "dialogs")]
+        app_.add_plugins(widgets::form::FormPlugin);
+
+        #[cfg(feature = "inspector")]
+        app_.add_plugins(widgets::inspector::InspectorPlugin);
+
+        #[cfg(feature = "tree_view")]
+        app_.add_observer(widgets::tree_view::on_tree_added)
+            .add_observer(widgets::tree_view::on_checkbox_activated)
+            .add_observer(widgets::tree_view::on_label_clicked)
+            .add_observer(widgets::tree_view::on_bind_tree_row)
+            .add_observer(widgets::tree_view::on_row_clicked)
+            .add_systems(
+                Update,
+                (
+                    widgets::tree_view::update_check_marks,
+                    widgets::tree_view::update_tree_placeholders,
+                    widgets::tree_view::show_selected_rows,
+                ),
+            );
+
+        #[cfg(feature = "grid_preview")]
+        app_.add_plugins(widgets::grid_preview::GridPreviewPlugin);
 
         #[cfg(feature = "editor")]
+        if app_
+            .world()
+            .resource::<AssetServer>()
+            .get_source(assets::UI_ASSET_SOURCE)
+            .is_err()
         {
-            use bevy::asset::embedded_asset;
-
-            embedded_asset!(app_, "crates/ui/src", "fonts/quiver.ttf");
-            embedded_asset!(app_, "crates/ui/src", "icons/right_arrow.png");
-            embedded_asset!(app_, "crates/ui/src", "icons/down_arrow.png");
-            embedded_asset!(app_, "crates/ui/src", "icons/vert_spacer.png");
-            embedded_asset!(app_, "crates/ui/src", "icons/folder.png");
+            warn!("The awgen_ui asset source is not registered, so UI themes will not load");
         }
     }
 }
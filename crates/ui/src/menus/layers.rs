@@ -0,0 +1,69 @@
+//! This module implements the z-index layers of overlay widgets.
+//!
+//! Overlay widgets are drawn in layers, so that for example a dialog is always
+//! drawn above the HUD, no matter which was spawned first. Adding a [`UiLayer`]
+//! to a UI node sets its [`GlobalZIndex`] to the z-index of the layer.
+
+use bevy::prelude::*;
+
+/// The plugin that applies z-index layers to overlay widgets.
+pub(crate) struct UiLayersPlugin;
+impl Plugin for UiLayersPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_observer(apply_layer);
+    }
+}
+
+/// The layer that an overlay widget is drawn in.
+///
+/// Layers are drawn from the bottom up in the order they are declared. Within
+/// a layer, widgets are drawn in the order they appear in the UI hierarchy.
+#[derive(Debug, Default, Component, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[require(Node)]
+pub enum UiLayer {
+    /// Panels and indicators that are always present on screen.
+    #[default]
+    Hud,
+
+    /// Menus and dropdowns that are opened over the HUD.
+    Menus,
+
+    /// Dialogs that ask the user for input, and overlays that block the rest
+    /// of the UI.
+    Dialogs,
+
+    /// Short-lived notifications, which should never be hidden.
+    Toasts,
+
+    /// Tooltips, which follow the cursor over every other widget.
+    Tooltips,
+
+    /// Debug overlays, which are drawn above everything else.
+    Debug,
+}
+
+impl UiLayer {
+    /// Gets the global z-index of this layer.
+    pub const fn z_index(self) -> i32 {
+        match self {
+            UiLayer::Hud => 0,
+            UiLayer::Menus => 1_000,
+            UiLayer::Dialogs => 2_000,
+            UiLayer::Toasts => 3_000,
+            UiLayer::Tooltips => i32::MAX - 1,
+            UiLayer::Debug => i32::MAX,
+        }
+    }
+}
+
+/// Sets the global z-index of a node to the z-index of its layer.
+fn apply_layer(trigger: On<Insert, UiLayer>, layers: Query<&UiLayer>, mut commands: Commands) {
+    let entity = trigger.event().entity;
+    let Ok(layer) = layers.get(entity) else {
+        return;
+    };
+
+    commands
+        .entity(entity)
+        .insert(GlobalZIndex(layer.z_index()));
+}
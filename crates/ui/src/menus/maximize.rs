@@ -0,0 +1,144 @@
+//! This module implements maximizing of individual panels over the whole
+//! window.
+//!
+//! Pressing Shift+Space while hovering a [`Maximizable`] panel stretches it
+//! over the window, drawn above the rest of the HUD. Pressing it again
+//! restores the panel to its place in the layout. The shortcut is bound to the
+//! `panel.maximize` command in the
+//! [`HotkeyRegistry`](crate::hotkeys::HotkeyRegistry), so it can be rebound
+//! like any other hotkey. Panels can also be toggled by triggering
+//! [`ToggleMaximized`] on them.
+//!
+//! Panels are stretched over their parent, so only panels placed directly in
+//! an overlay root, such as those placed with a
+//! [`ScreenAnchor`](super::overlay::ScreenAnchor), fill the whole window.
+
+use bevy::picking::hover::HoverMap;
+use bevy::prelude::*;
+
+use crate::commands::{PaletteCommand, RegisterCommandExt};
+use crate::hotkeys::{BindHotkeyExt, Hotkey, HotkeyContext};
+use crate::menus::layers::UiLayer;
+
+/// The plugin that adds support for maximizing panels.
+pub(crate) struct MaximizePlugin;
+impl Plugin for MaximizePlugin {
+    fn build(&self, app_: &mut App) {
+        app_.register_command(
+            PaletteCommand::new("panel.maximize", "Toggle Maximized Panel"),
+            toggle_hovered_panel,
+        )
+        .bind_hotkey(
+            "panel.maximize",
+            Hotkey::new(KeyCode::Space).shift(),
+            HotkeyContext::Global,
+        )
+        .add_observer(on_toggle_maximized);
+    }
+}
+
+/// A marker component for panels that can be maximized.
+#[derive(Debug, Default, Component, Clone, Copy)]
+#[require(Node)]
+pub struct Maximizable;
+
+/// A component on a panel that is currently maximized.
+#[derive(Debug, Component)]
+pub struct Maximized {
+    /// The layout of the panel before it was maximized.
+    node: Node,
+
+    /// The z-index of the panel before it was maximized, if it had one.
+    z_index: Option<GlobalZIndex>,
+}
+
+/// An event that maximizes a panel, or restores it if it is maximized.
+#[derive(Debug, EntityEvent)]
+pub struct ToggleMaximized {
+    /// The panel to toggle.
+    pub entity: Entity,
+}
+
+/// Toggles the hovered panel.
+///
+/// If a panel is already maximized, it is restored instead, as it covers the
+/// panels that could otherwise be hovered.
+fn toggle_hovered_panel(
+    hover_map: Res<HoverMap>,
+    parents: Query<&ChildOf>,
+    panels: Query<(), With<Maximizable>>,
+    maximized: Query<Entity, With<Maximized>>,
+    mut commands: Commands,
+) {
+    if !maximized.is_empty() {
+        for entity in maximized.iter() {
+            commands.trigger(ToggleMaximized { entity });
+        }
+        return;
+    }
+
+    let hovered = hover_map
+        .values()
+        .flat_map(|pointer_map| pointer_map.keys().copied())
+        .find_map(|entity| {
+            std::iter::once(entity)
+                .chain(parents.iter_ancestors(entity))
+                .find(|&entity| panels.contains(entity))
+        });
+
+    if let Some(entity) = hovered {
+        commands.trigger(ToggleMaximized { entity });
+    }
+}
+
+/// Maximizes or restores a panel.
+fn on_toggle_maximized(
+    trigger: On<ToggleMaximized>,
+    mut panels: Query<(&mut Node, Option<&GlobalZIndex>, Option<&Maximized>)>,
+    mut commands: Commands,
+) {
+    let entity = trigger.event().entity;
+    let Ok((mut node, z_index, maximized)) = panels.get_mut(entity) else {
+        return;
+    };
+
+    if let Some(maximized) = maximized {
+        *node = maximized.node.clone();
+        let mut panel = commands.entity(entity);
+        match maximized.z_index {
+            Some(z_index) => {
+                panel.insert(z_index);
+            }
+            None => {
+                panel.remove::<GlobalZIndex>();
+            }
+        }
+        panel.remove::<Maximized>();
+        return;
+    }
+
+    let previous = node.clone();
+    *node = Node {
+        position_type: PositionType::Absolute,
+        top: px(0.0),
+        bottom: px(0.0),
+        left: px(0.0),
+        right: px(0.0),
+        width: Val::Auto,
+        height: Val::Auto,
+        max_width: Val::Auto,
+        max_height: Val::Auto,
+        margin: UiRect::ZERO,
+        ..previous.clone()
+    };
+
+    // Maximized panels cover the rest of the HUD, but not the menus opened
+    // over it.
+    commands.entity(entity).insert((
+        Maximized {
+            node: previous,
+            z_index: z_index.copied(),
+        },
+        GlobalZIndex(UiLayer::Menus.z_index() - 1),
+    ));
+}
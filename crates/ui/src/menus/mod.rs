@@ -1,3 +1,8 @@
 //! The base menus implemented by the UI library.
 
+pub mod layers;
+#[cfg(feature = "menus")]
+pub mod maximize;
 pub mod overlay;
+#[cfg(feature = "menus")]
+pub mod windows;
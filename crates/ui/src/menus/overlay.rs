@@ -1,20 +1,43 @@
 //! This plugin handles the overlay UI logic.
+//!
+//! Nodes placed with a [`ScreenAnchor`] that share an anchor are stacked
+//! beside each other rather than overlapping, as configured by the
+//! [`AnchorStacking`] resource.
 
 use bevy::camera::visibility::RenderLayers;
+use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
+use bevy::ui::UiSystems;
 use bevy::window::PrimaryWindow;
 
+#[cfg(feature = "menus")]
+use crate::menus::maximize::Maximized;
+
 /// The plugin that adds an overlay to the application.
 pub struct OverlayPlugin;
 impl Plugin for OverlayPlugin {
     fn build(&self, app_: &mut App) {
-        app_.add_systems(Startup, setup)
+        app_.add_plugins(super::layers::UiLayersPlugin)
+            .init_resource::<AnchorStacking>()
+            .add_systems(Startup, setup)
             .add_systems(
                 Update,
                 update_3d_elements.in_set(OverlaySystems::Update3DPositions),
             )
+            .add_systems(
+                PostUpdate,
+                stack_anchored_nodes
+                    .in_set(OverlaySystems::StackAnchors)
+                    .before(UiSystems::Layout),
+            )
             .add_observer(clear_3d_model)
             .add_observer(replace_anchor);
+
+        #[cfg(feature = "menus")]
+        app_.add_plugins((
+            super::maximize::MaximizePlugin,
+            super::windows::DetachedWindowsPlugin,
+        ));
     }
 }
 
@@ -23,9 +46,12 @@ impl Plugin for OverlayPlugin {
 pub enum OverlaySystems {
     /// Updates the 3D positions of overlay elements.
     Update3DPositions,
+
+    /// Offsets anchored nodes so that nodes sharing an anchor are stacked.
+    StackAnchors,
 }
 
-/// Marker component for the overlay root node.
+/// Marker component for the overlay root node of the main window.
 #[derive(Component)]
 pub struct OverlayRoot;
 
@@ -240,6 +266,133 @@ impl ScreenAnchor {
     }
 }
 
+impl ScreenAnchor {
+    /// Gets the edge that nodes at this anchor are offset from to stack them
+    /// in the given direction.
+    ///
+    /// Anchors that are centered along an axis can only stack along the other
+    /// axis, so the direction is ignored for them. Nodes at the center of the
+    /// screen or filling it are never stacked.
+    fn stack_edge(self, direction: StackDirection) -> Option<StackEdge> {
+        match (self, direction) {
+            (ScreenAnchor::TopLeft | ScreenAnchor::TopRight, StackDirection::Vertical) => {
+                Some(StackEdge::Top)
+            }
+            (ScreenAnchor::BottomLeft | ScreenAnchor::BottomRight, StackDirection::Vertical) => {
+                Some(StackEdge::Bottom)
+            }
+            (ScreenAnchor::TopLeft | ScreenAnchor::BottomLeft, StackDirection::Horizontal) => {
+                Some(StackEdge::Left)
+            }
+            (ScreenAnchor::TopRight | ScreenAnchor::BottomRight, StackDirection::Horizontal) => {
+                Some(StackEdge::Right)
+            }
+            (ScreenAnchor::TopCenter, _) => Some(StackEdge::Top),
+            (ScreenAnchor::BottomCenter, _) => Some(StackEdge::Bottom),
+            (ScreenAnchor::CenterLeft, _) => Some(StackEdge::Left),
+            (ScreenAnchor::CenterRight, _) => Some(StackEdge::Right),
+            (ScreenAnchor::Center | ScreenAnchor::Fullscreen, _) => None,
+        }
+    }
+}
+
+/// A component that records the anchor a node was placed at.
+///
+/// This is inserted when the [`ScreenAnchor`] component is replaced, and is
+/// used to stack nodes that share an anchor.
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq)]
+pub struct Anchored(pub ScreenAnchor);
+
+/// The direction that nodes sharing an anchor are stacked in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StackDirection {
+    /// Nodes are stacked away from the top or bottom edge of the screen.
+    #[default]
+    Vertical,
+
+    /// Nodes are stacked away from the left or right edge of the screen.
+    Horizontal,
+}
+
+/// How the nodes sharing an anchor are stacked.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct AnchorStack {
+    /// The direction that nodes are stacked in.
+    pub direction: StackDirection,
+
+    /// The space, in logical pixels, between stacked nodes. This is added to
+    /// the margins of the nodes.
+    pub spacing: f32,
+}
+
+/// The edge of the screen that a stacked node is offset from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StackEdge {
+    /// The top edge of the screen.
+    Top,
+
+    /// The bottom edge of the screen.
+    Bottom,
+
+    /// The left edge of the screen.
+    Left,
+
+    /// The right edge of the screen.
+    Right,
+}
+
+/// A resource that configures how nodes sharing an anchor are stacked.
+///
+/// Nodes are stacked in the order they appear among the children of the
+/// [`OverlayRoot`], skipping nodes that are not displayed. By default, every
+/// anchor along the edges of the screen stacks its nodes without extra
+/// spacing, vertically at the top and bottom edges, and horizontally at the
+/// left and right centers.
+#[derive(Debug, Clone, Resource)]
+pub struct AnchorStacking {
+    /// The stacking of each anchor. Nodes at anchors without an entry overlap.
+    stacks: HashMap<ScreenAnchor, AnchorStack>,
+}
+
+impl Default for AnchorStacking {
+    fn default() -> Self {
+        let vertical = AnchorStack::default();
+        let horizontal = AnchorStack {
+            direction: StackDirection::Horizontal,
+            ..default()
+        };
+
+        Self {
+            stacks: HashMap::from_iter([
+                (ScreenAnchor::TopLeft, vertical),
+                (ScreenAnchor::TopCenter, vertical),
+                (ScreenAnchor::TopRight, vertical),
+                (ScreenAnchor::CenterLeft, horizontal),
+                (ScreenAnchor::CenterRight, horizontal),
+                (ScreenAnchor::BottomLeft, vertical),
+                (ScreenAnchor::BottomCenter, vertical),
+                (ScreenAnchor::BottomRight, vertical),
+            ]),
+        }
+    }
+}
+
+impl AnchorStacking {
+    /// Gets how nodes at the given anchor are stacked, if they are.
+    pub fn get(&self, anchor: ScreenAnchor) -> Option<AnchorStack> {
+        self.stacks.get(&anchor).copied()
+    }
+
+    /// Sets how nodes at the given anchor are stacked. If `None`, nodes at the
+    /// anchor overlap.
+    pub fn set(&mut self, anchor: ScreenAnchor, stack: Option<AnchorStack>) {
+        match stack {
+            Some(stack) => self.stacks.insert(anchor, stack),
+            None => self.stacks.remove(&anchor),
+        };
+    }
+}
+
 /// Replaces the ScreenAnchor component with appropriate positioning and
 /// parenting.
 fn replace_anchor(
@@ -264,5 +417,82 @@ fn replace_anchor(
     commands
         .entity(entity)
         .remove::<ScreenAnchor>()
-        .insert(ChildOf(overlay));
+        .insert((ChildOf(overlay), Anchored(*anchor)));
+}
+
+/// The filter for anchored nodes that take part in stacking. Maximized panels
+/// only exist with the `menus` feature.
+#[cfg(feature = "menus")]
+type Unmaximized = Without<Maximized>;
+
+/// The filter for anchored nodes that take part in stacking. Maximized panels
+/// only exist with the `menus` feature.
+#[cfg(not(feature = "menus"))]
+type Unmaximized = ();
+
+/// Offsets anchored nodes from the edge of the screen so that nodes sharing an
+/// anchor are stacked.
+///
+/// This uses the sizes computed by the previous layout, so newly added nodes
+/// settle into place on the next frame. Maximized nodes cover the whole screen,
+/// so they are left out of the stack.
+fn stack_anchored_nodes(
+    stacking: Res<AnchorStacking>,
+    overlay: Query<&Children, With<OverlayRoot>>,
+    mut nodes: Query<(&Anchored, &mut Node, &ComputedNode), Unmaximized>,
+) {
+    let Ok(children) = overlay.single() else {
+        return;
+    };
+
+    let mut offsets: HashMap<ScreenAnchor, f32> = HashMap::new();
+    for child in children.iter() {
+        let Ok((Anchored(anchor), mut node, computed)) = nodes.get_mut(child) else {
+            continue;
+        };
+
+        let Some(stack) = stacking.get(*anchor) else {
+            continue;
+        };
+
+        let Some(edge) = anchor.stack_edge(stack.direction) else {
+            continue;
+        };
+
+        if node.display == Display::None {
+            continue;
+        }
+
+        let offset = offsets.entry(*anchor).or_default();
+        let position = px(*offset);
+        let size = computed.size() * computed.inverse_scale_factor();
+        let (current, extent, margins) = match edge {
+            StackEdge::Top => (node.top, size.y, [node.margin.top, node.margin.bottom]),
+            StackEdge::Bottom => (node.bottom, size.y, [node.margin.top, node.margin.bottom]),
+            StackEdge::Left => (node.left, size.x, [node.margin.left, node.margin.right]),
+            StackEdge::Right => (node.right, size.x, [node.margin.left, node.margin.right]),
+        };
+
+        // Only pixel margins can be resolved without the size of the screen.
+        let margins: f32 = margins
+            .into_iter()
+            .map(|margin| match margin {
+                Val::Px(value) => value,
+                _ => 0.0,
+            })
+            .sum();
+
+        *offset += extent + margins + stack.spacing;
+
+        if current == position {
+            continue;
+        }
+
+        match edge {
+            StackEdge::Top => node.top = position,
+            StackEdge::Bottom => node.bottom = position,
+            StackEdge::Left => node.left = position,
+            StackEdge::Right => node.right = position,
+        }
+    }
 }
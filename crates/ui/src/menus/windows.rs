@@ -0,0 +1,329 @@
+//! This module implements detachable panels, which can be moved out of the
+//! main window into their own OS windows for multi-monitor workflows.
+//!
+//! Triggering [`DetachPanel`] on a UI node moves it into a new window, which
+//! has its own camera and [`WindowOverlayRoot`]. The panel is docked back into
+//! its previous place in the main window when [`DockPanel`] is triggered on
+//! it, when its window is closed, or when its window is dragged back over the
+//! main window.
+
+use bevy::camera::RenderTarget;
+use bevy::ecs::system::NonSendMarker;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowCloseRequested, WindowMoved, WindowRef};
+use bevy::winit::WINIT_WINDOWS;
+
+/// The logical size of a detached window, used when the panel has not been
+/// laid out yet.
+const DEFAULT_WINDOW_SIZE: Vec2 = Vec2::new(400.0, 300.0);
+
+/// The plugin that adds support for detachable panels.
+pub(crate) struct DetachedWindowsPlugin;
+impl Plugin for DetachedWindowsPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_systems(
+            Update,
+            (
+                dock_closed_windows,
+                dock_dragged_windows,
+                despawn_orphaned_panels,
+            ),
+        )
+        .add_observer(on_detach_panel)
+        .add_observer(on_dock_panel)
+        .add_observer(on_detached_removed);
+    }
+}
+
+/// Marker component for the overlay root node of a detached window.
+///
+/// This plays the role of the [`OverlayRoot`](super::overlay::OverlayRoot) for
+/// the window, and holds the detached panel.
+#[derive(Debug, Component)]
+pub struct WindowOverlayRoot {
+    /// The window that this root is drawn to.
+    pub window: Entity,
+}
+
+/// A component on a panel that has been detached into its own window.
+#[derive(Debug, Component)]
+pub struct Detached {
+    /// The window that holds the panel.
+    window: Entity,
+
+    /// The camera that draws the window.
+    camera: Entity,
+
+    /// The overlay root node of the window.
+    root: Entity,
+
+    /// The parent of the panel before it was detached, if it had one.
+    parent: Option<Entity>,
+
+    /// The index of the panel among the children of its parent.
+    index: usize,
+
+    /// The layout of the panel before it was detached.
+    node: Node,
+
+    /// Whether the window has been dragged off the main window since it was
+    /// opened. Windows are only docked by dragging after this happens, so that
+    /// a window opened over the main window is not docked right away.
+    left_main: bool,
+}
+
+impl Detached {
+    /// Gets the window that holds the panel.
+    pub fn window(&self) -> Entity {
+        self.window
+    }
+}
+
+/// An event that moves a panel into its own window.
+#[derive(Debug, EntityEvent)]
+pub struct DetachPanel {
+    /// The panel to detach.
+    pub entity: Entity,
+
+    /// The title of the new window.
+    pub title: String,
+}
+
+/// An event that docks a detached panel back into the main window.
+#[derive(Debug, EntityEvent)]
+pub struct DockPanel {
+    /// The panel to dock.
+    pub entity: Entity,
+}
+
+/// Moves a panel into a new window, keeping its size.
+fn on_detach_panel(
+    trigger: On<DetachPanel>,
+    mut panels: Query<(&mut Node, Option<&ComputedNode>, Option<&ChildOf>), Without<Detached>>,
+    children: Query<&Children>,
+    mut commands: Commands,
+) {
+    let event = trigger.event();
+    let Ok((mut node, computed, parent)) = panels.get_mut(event.entity) else {
+        return;
+    };
+
+    let size = computed
+        .map(|computed| computed.size() * computed.inverse_scale_factor())
+        .filter(|size| size.min_element() > 0.0)
+        .unwrap_or(DEFAULT_WINDOW_SIZE);
+
+    let mut window = Window {
+        title: event.title.clone(),
+        ..default()
+    };
+    window.resolution.set(size.x, size.y);
+    let window = commands.spawn(window).id();
+
+    let camera = commands
+        .spawn((
+            Camera2d,
+            Camera {
+                target: RenderTarget::Window(WindowRef::Entity(window)),
+                ..default()
+            },
+        ))
+        .id();
+
+    let root = commands
+        .spawn((
+            WindowOverlayRoot { window },
+            UiTargetCamera(camera),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+        ))
+        .id();
+
+    let parent = parent.map(ChildOf::parent);
+    let index = parent
+        .and_then(|parent| children.get(parent).ok())
+        .and_then(|children| children.iter().position(|child| child == event.entity))
+        .unwrap_or_default();
+
+    let previous = node.clone();
+    *node = Node {
+        position_type: PositionType::Relative,
+        top: Val::Auto,
+        bottom: Val::Auto,
+        left: Val::Auto,
+        right: Val::Auto,
+        margin: UiRect::ZERO,
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        max_width: Val::Auto,
+        max_height: Val::Auto,
+        ..previous.clone()
+    };
+
+    commands.entity(event.entity).insert((
+        Detached {
+            window,
+            camera,
+            root,
+            parent,
+            index,
+            node: previous,
+            left_main: false,
+        },
+        ChildOf(root),
+    ));
+}
+
+/// Moves a detached panel back to its previous place in the main window.
+///
+/// If the previous parent of the panel no longer exists, the panel is
+/// despawned instead.
+fn on_dock_panel(
+    trigger: On<DockPanel>,
+    mut panels: Query<(&mut Node, &Detached)>,
+    entities: Query<()>,
+    mut commands: Commands,
+) {
+    let entity = trigger.event().entity;
+    let Ok((mut node, detached)) = panels.get_mut(entity) else {
+        return;
+    };
+
+    match detached.parent {
+        Some(parent) if !entities.contains(parent) => {
+            commands.entity(entity).despawn();
+            return;
+        }
+        Some(parent) => {
+            commands
+                .entity(parent)
+                .insert_children(detached.index, &[entity]);
+        }
+        None => {
+            commands.entity(entity).remove::<ChildOf>();
+        }
+    }
+
+    *node = detached.node.clone();
+    commands.entity(entity).remove::<Detached>();
+}
+
+/// Closes the window of a panel when the panel is docked or despawned.
+fn on_detached_removed(
+    trigger: On<Remove, Detached>,
+    panels: Query<&Detached>,
+    mut commands: Commands,
+) {
+    let Ok(detached) = panels.get(trigger.event().entity) else {
+        return;
+    };
+
+    commands.entity(detached.root).try_despawn();
+    commands.entity(detached.camera).try_despawn();
+    commands.entity(detached.window).try_despawn();
+}
+
+/// Docks panels whose window is closed, or was asked to close.
+fn dock_closed_windows(
+    mut close_requests: MessageReader<WindowCloseRequested>,
+    windows: Query<(), With<Window>>,
+    panels: Query<(Entity, &Detached)>,
+    mut commands: Commands,
+) {
+    let requested: Vec<Entity> = close_requests
+        .read()
+        .map(|request| request.window)
+        .collect();
+
+    for (entity, detached) in panels.iter() {
+        if requested.contains(&detached.window) || !windows.contains(detached.window) {
+            commands.trigger(DockPanel { entity });
+        }
+    }
+}
+
+/// Docks panels whose window is dragged back over the main window.
+///
+/// A window counts as over the main window when the top center of its frame,
+/// where it is usually dragged from, is inside the main window.
+fn dock_dragged_windows(
+    mut moves: MessageReader<WindowMoved>,
+    primary: Query<(Entity, &Window), With<PrimaryWindow>>,
+    windows: Query<&Window>,
+    mut panels: Query<(Entity, &mut Detached)>,
+    mut commands: Commands,
+    _main_thread: NonSendMarker,
+) {
+    if moves.is_empty() {
+        return;
+    }
+
+    let main_rect = primary.single().ok().and_then(|(entity, window)| {
+        let position = main_window_position(entity, window)?;
+        Some(IRect::from_corners(
+            position,
+            position + window.physical_size().as_ivec2(),
+        ))
+    });
+
+    let Some(main_rect) = main_rect else {
+        moves.clear();
+        return;
+    };
+
+    for moved in moves.read() {
+        let Ok(window) = windows.get(moved.window) else {
+            continue;
+        };
+
+        let handle = moved.position + IVec2::new(window.physical_width() as i32 / 2, 0);
+        let over_main = main_rect.contains(handle);
+
+        for (entity, mut detached) in panels.iter_mut() {
+            if detached.window != moved.window {
+                continue;
+            }
+
+            if !over_main {
+                detached.left_main = true;
+            } else if detached.left_main {
+                commands.trigger(DockPanel { entity });
+            }
+        }
+    }
+}
+
+/// Gets the physical position of the main window.
+///
+/// A window that is centered or placed by the OS has no known position until
+/// it is moved, so its position is read from the windowing backend instead.
+fn main_window_position(entity: Entity, window: &Window) -> Option<IVec2> {
+    if let WindowPosition::At(position) = window.position {
+        return Some(position);
+    }
+
+    WINIT_WINDOWS.with_borrow(|winit_windows| {
+        let position = winit_windows.get_window(entity)?.outer_position().ok()?;
+        Some(IVec2::new(position.x, position.y))
+    })
+}
+
+/// Despawns detached panels whose previous parent was despawned, as the
+/// panel can no longer be docked.
+fn despawn_orphaned_panels(
+    panels: Query<(Entity, &Detached)>,
+    entities: Query<()>,
+    mut commands: Commands,
+) {
+    for (entity, detached) in panels.iter() {
+        if detached
+            .parent
+            .is_some_and(|parent| !entities.contains(parent))
+        {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -1,21 +1,49 @@
 //! This module forwards scrolling events through the UI hierarchy.
+//!
+//! Each wheel event is sent to the innermost node under the pointer, and
+//! bubbles up through its ancestors. The innermost scroll container consumes
+//! as much of the scroll as it can, and only the remainder bubbles to the
+//! containers around it once it reaches its end. A [`CaptureScroll`] node never
+//! lets scrolling bubble past it.
+//!
+//! Scroll containers with a [`VirtualScroll`] component only spawn nodes for
+//! the items scrolled into view, and recycle them as the container scrolls.
+//! This keeps lists of thousands of items as cheap as the handful on screen.
+
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
 
 use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
 use bevy::picking::hover::HoverMap;
+use bevy::platform::collections::HashSet;
 use bevy::prelude::*;
 
+use crate::accessibility::AccessibilityOptions;
+
 /// The multiplier for line-based scrolling.
 const LINE_HEIGHT: f32 = 21.0;
 
+/// The number of rows of items spawned above and below the view of a
+/// [`VirtualScroll`] by default, so that items are ready before they scroll
+/// into view.
+const DEFAULT_OVERSCAN: usize = 2;
+
 /// A plugin that adds scrolling support to the UI.
 pub struct ScrollPlugin;
 impl Plugin for ScrollPlugin {
     fn build(&self, app_: &mut App) {
         app_.add_systems(
             Update,
-            (send_scroll_events, update_smooth_scroll_positions).chain(),
+            (
+                send_scroll_events,
+                update_smooth_scroll_positions,
+                update_virtual_scrolls,
+            )
+                .chain(),
         )
-        .add_observer(on_scroll_handler);
+        .add_observer(on_scroll_handler)
+        .add_observer(on_virtual_scroll_added);
     }
 }
 
@@ -30,18 +58,319 @@ pub struct Scroll {
     pub delta: Vec2,
 }
 
+/// A component that stops scroll events from bubbling past this node, even
+/// once it cannot scroll any further.
+///
+/// This is useful for lists inside larger panels, such as popups, where
+/// reaching the end of the list should not scroll the panel behind it.
+#[derive(Debug, Default, Component, Clone, Copy)]
+pub struct CaptureScroll;
+
 /// Smooth scroll position component.
 #[derive(Debug, Default, Component, Clone, Copy, Deref, DerefMut)]
 #[require(ScrollPosition)]
 pub struct SmoothScrollPosition(pub Vec2);
 
+/// A callback that supplies the items of a virtualized widget by index.
+///
+/// Items are only requested while they are scrolled into view, and are
+/// requested again each time they scroll back into view, so the callback should
+/// be cheap to call.
+pub struct ItemProvider<T>(Arc<dyn Fn(usize) -> T + Send + Sync>);
+
+impl<T> ItemProvider<T> {
+    /// Creates a new item provider from the given callback.
+    pub fn new(provider: impl Fn(usize) -> T + Send + Sync + 'static) -> Self {
+        Self(Arc::new(provider))
+    }
+
+    /// Gets the item at the given index.
+    pub fn get(&self, index: usize) -> T {
+        (self.0)(index)
+    }
+}
+
+impl<T> Clone for ItemProvider<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> fmt::Debug for ItemProvider<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ItemProvider").finish_non_exhaustive()
+    }
+}
+
+/// A component for scroll containers that only spawn nodes for the items
+/// scrolled into view.
+///
+/// The container holds a single content node, sized to fit every item, and a
+/// pool of item nodes placed over the part of it that is in view. As the
+/// container scrolls, item nodes that leave the view are moved to the items
+/// that enter it, and [`BindVirtualItem`] is triggered on each of them so that
+/// the widget can fill them in.
+///
+/// Items are laid out in rows, or in a grid that fits as many columns as the
+/// width of the container allows. The size of the items is measured once they
+/// have been laid out, so the size given when creating the component is only
+/// an estimate used until then. All items are expected to have the same size.
+#[derive(Debug, Component)]
+#[require(Node)]
+pub struct VirtualScroll {
+    /// The number of items.
+    item_count: usize,
+
+    /// The size of each item. The width is unused for rows.
+    item_size: Vec2,
+
+    /// The spacing between items.
+    spacing: Vec2,
+
+    /// Whether items are laid out in a grid, rather than in rows.
+    grid: bool,
+
+    /// The number of rows spawned above and below the view.
+    overscan: usize,
+
+    /// The content node that holds the item nodes.
+    ///
+    /// This value is assigned when the component is added.
+    content: Option<Entity>,
+
+    /// The pool of item nodes.
+    items: Vec<Entity>,
+
+    /// The range of items that are currently bound to item nodes.
+    range: Range<usize>,
+
+    /// The number of columns that items are currently laid out in.
+    columns: usize,
+
+    /// Whether every item node must be bound again, such as when the items
+    /// have changed.
+    rebind: bool,
+}
+
+impl VirtualScroll {
+    /// Creates a virtual scroll that lays out its items in rows that fill the
+    /// width of the container, with the given estimated row height.
+    pub fn rows(row_height: f32) -> Self {
+        Self {
+            item_count: 0,
+            item_size: Vec2::new(0.0, row_height),
+            spacing: Vec2::ZERO,
+            grid: false,
+            overscan: DEFAULT_OVERSCAN,
+            content: None,
+            items: Vec::new(),
+            range: 0 .. 0,
+            columns: 1,
+            rebind: false,
+        }
+    }
+
+    /// Creates a virtual scroll that lays out its items in a grid, with the
+    /// given estimated cell size.
+    pub fn grid(cell_size: Vec2) -> Self {
+        Self {
+            item_size: cell_size,
+            grid: true,
+            ..Self::rows(cell_size.y)
+        }
+    }
+
+    /// Sets the number of items.
+    pub fn with_item_count(mut self, item_count: usize) -> Self {
+        self.item_count = item_count;
+        self
+    }
+
+    /// Sets the spacing between items.
+    pub fn with_spacing(mut self, spacing: Vec2) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Sets the number of rows spawned above and below the view.
+    pub fn with_overscan(mut self, overscan: usize) -> Self {
+        self.overscan = overscan;
+        self
+    }
+
+    /// Gets the number of items.
+    pub fn item_count(&self) -> usize {
+        self.item_count
+    }
+
+    /// Sets the number of items. Items that are already in view keep their
+    /// nodes, so [`VirtualScroll::refresh`] should be called as well if the
+    /// existing items have changed.
+    pub fn set_item_count(&mut self, item_count: usize) {
+        self.item_count = item_count;
+    }
+
+    /// Returns true if items are laid out in a grid, rather than in rows.
+    pub fn is_grid(&self) -> bool {
+        self.grid
+    }
+
+    /// Switches between laying out items in a grid and in rows. The items in
+    /// view are bound again, as their size is expected to change.
+    pub fn set_grid(&mut self, grid: bool) {
+        if self.grid == grid {
+            return;
+        }
+
+        // Rows have no width, so square cells are assumed until the cells
+        // have been measured.
+        if grid {
+            self.item_size.x = self.item_size.y;
+        }
+
+        self.grid = grid;
+        self.rebind = true;
+    }
+
+    /// Binds every item in view again, so that changes to the items are shown.
+    pub fn refresh(&mut self) {
+        self.rebind = true;
+    }
+
+    /// Gets the range of items that currently have a node, including those
+    /// spawned just outside the view.
+    pub fn visible_range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Gets the content node that holds the item nodes.
+    ///
+    /// If the component has not been added to an entity yet, this returns
+    /// `None`.
+    pub fn content(&self) -> Option<Entity> {
+        self.content
+    }
+
+    /// Gets the distance between the start of two neighboring items.
+    fn stride(&self) -> Vec2 {
+        (self.item_size + self.spacing).max(Vec2::ONE)
+    }
+
+    /// Lays out items of the given size in a view of the given size, scrolled
+    /// down by the given distance, and gets the items that should have a node.
+    fn layout(&self, viewport: Vec2, scroll_y: f32, item_size: Vec2) -> VirtualLayout {
+        let stride = (item_size + self.spacing).max(Vec2::ONE);
+        let columns = if self.grid {
+            ((viewport.x + self.spacing.x) / stride.x).floor().max(1.0) as usize
+        } else {
+            1
+        };
+
+        let rows = self.item_count.div_ceil(columns);
+        let first_row = ((scroll_y / stride.y).floor() as usize)
+            .saturating_sub(self.overscan)
+            .min(rows);
+        let last_row =
+            (((scroll_y + viewport.y) / stride.y).ceil() as usize + self.overscan).min(rows);
+
+        VirtualLayout {
+            columns,
+            rows,
+            range: first_row * columns .. (last_row * columns).min(self.item_count),
+        }
+    }
+
+    /// Gets the offset of the item at the given index from the top left of the
+    /// content node, such as for scrolling to that item.
+    pub fn item_offset(&self, index: usize) -> Vec2 {
+        let stride = self.stride();
+        let row = index / self.columns;
+        let column = index % self.columns;
+        Vec2::new(column as f32 * stride.x, row as f32 * stride.y)
+    }
+
+    /// Places an item node at the offset of the item at the given index.
+    fn place_item(&self, node: &mut Node, index: usize) {
+        let offset = self.item_offset(index);
+        node.display = Display::Flex;
+        node.position_type = PositionType::Absolute;
+        node.left = px(offset.x);
+        node.top = px(offset.y);
+        node.width = if self.grid { Val::Auto } else { percent(100.0) };
+    }
+}
+
+/// The layout of the items of a [`VirtualScroll`] for a given view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VirtualLayout {
+    /// The number of columns that items are laid out in.
+    columns: usize,
+
+    /// The number of rows that items are laid out in.
+    rows: usize,
+
+    /// The range of items that should have a node, including those just
+    /// outside the view.
+    range: Range<usize>,
+}
+
+/// A component on each item node of a [`VirtualScroll`].
+#[derive(Debug, Component)]
+pub struct VirtualItem {
+    /// The index of the item that the node is bound to, or `None` if the node
+    /// is unused.
+    index: Option<usize>,
+}
+
+impl VirtualItem {
+    /// Gets the index of the item that the node is bound to, or `None` if the
+    /// node is unused.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+}
+
+/// An event triggered on an item node of a [`VirtualScroll`] when it is bound
+/// to an item.
+///
+/// The event bubbles up to the container and the widget holding it, which
+/// should fill in the item node, the original target of the event, with the
+/// item at the given index. Item nodes are reused, so they may still hold the
+/// content of the item they were previously bound to.
+#[derive(Debug, EntityEvent)]
+#[entity_event(propagate, auto_propagate)]
+pub struct BindVirtualItem {
+    /// The entity that received the event.
+    pub entity: Entity,
+
+    /// The index of the item that the node is now bound to.
+    pub index: usize,
+}
+
 /// Injects scroll events into the UI hierarchy.
 fn send_scroll_events(
     mut mouse_wheel_reader: MessageReader<MouseWheel>,
     hover_map: Res<HoverMap>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    parents: Query<&ChildOf>,
     mut commands: Commands,
 ) {
+    if mouse_wheel_reader.is_empty() {
+        return;
+    }
+
+    // Only the innermost hovered node of each pointer receives the event, so
+    // that containers are not scrolled once for each hovered descendant.
+    let targets = hover_map
+        .values()
+        .filter_map(|pointer_map| {
+            pointer_map
+                .keys()
+                .copied()
+                .max_by_key(|&entity| parents.iter_ancestors(entity).count())
+        })
+        .collect::<Vec<_>>();
+
     for mouse_wheel in mouse_wheel_reader.read() {
         let mut delta = -Vec2::new(mouse_wheel.x, mouse_wheel.y);
 
@@ -53,10 +382,8 @@ fn send_scroll_events(
             std::mem::swap(&mut delta.x, &mut delta.y);
         }
 
-        for pointer_map in hover_map.values() {
-            for entity in pointer_map.keys().copied() {
-                commands.trigger(Scroll { entity, delta });
-            }
+        for &entity in targets.iter() {
+            commands.trigger(Scroll { entity, delta });
         }
     }
 }
@@ -69,9 +396,11 @@ fn on_scroll_handler(
         Option<&mut SmoothScrollPosition>,
         &Node,
         &ComputedNode,
+        Has<CaptureScroll>,
     )>,
 ) {
-    let Ok((mut scroll_position, mut smooth_scroll, node, computed)) = query.get_mut(scroll.entity)
+    let Ok((mut scroll_position, mut smooth_scroll, node, computed, capture)) =
+        query.get_mut(scroll.entity)
     else {
         return;
     };
@@ -82,47 +411,48 @@ fn on_scroll_handler(
         &mut scroll_position.0
     };
 
-    let max_offset = (computed.content_size() - computed.size()) * computed.inverse_scale_factor();
+    let overflow = computed.content_size() - computed.size();
+    let max_offset = (overflow * computed.inverse_scale_factor()).max(Vec2::ZERO);
 
     let delta = &mut scroll.delta;
-    if node.overflow.x == OverflowAxis::Scroll && delta.x != 0.0 {
-        let max = if delta.x > 0.0 {
-            pos.x >= max_offset.x
-        } else {
-            pos.x <= 0.0
-        };
-
-        if !max {
-            pos.x += delta.x;
-            delta.x = 0.0;
-        }
+    if node.overflow.x == OverflowAxis::Scroll {
+        delta.x = scroll_axis(&mut pos.x, delta.x, max_offset.x);
     }
 
-    if node.overflow.y == OverflowAxis::Scroll && delta.y != 0.0 {
-        let max = if delta.y > 0.0 {
-            pos.y >= max_offset.y
-        } else {
-            pos.y <= 0.0
-        };
-
-        if !max {
-            pos.y += delta.y;
-            delta.y = 0.0;
-        }
+    if node.overflow.y == OverflowAxis::Scroll {
+        delta.y = scroll_axis(&mut pos.y, delta.y, max_offset.y);
     }
 
-    if *delta == Vec2::ZERO {
+    if *delta == Vec2::ZERO || capture {
         scroll.propagate(false);
     }
 }
 
-/// Updates smooth scroll positions.
+/// Scrolls along a single axis by as much of the given delta as fits between
+/// the start and the given end of the container, and returns the remainder.
+fn scroll_axis(pos: &mut f32, delta: f32, max_offset: f32) -> f32 {
+    if delta == 0.0 {
+        return 0.0;
+    }
+
+    let target = *pos + delta;
+    *pos = target.clamp(0.0, max_offset);
+    target - *pos
+}
+
+/// Updates smooth scroll positions. Scrolling completes immediately while
+/// [`AccessibilityOptions::reduce_motion`] is set.
 fn update_smooth_scroll_positions(
     time: Res<Time>,
+    options: Res<AccessibilityOptions>,
     mut query: Query<(&mut ScrollPosition, &SmoothScrollPosition)>,
 ) {
     let delta = time.delta_secs();
-    let t = (1.0 - 0.01f32.powf(2.0 * delta)).clamp(0.0, 1.0);
+    let t = if options.reduce_motion {
+        1.0
+    } else {
+        (1.0 - 0.01f32.powf(2.0 * delta)).clamp(0.0, 1.0)
+    };
 
     for (mut scroll_position, smooth_scroll) in query.iter_mut() {
         let src = scroll_position.0;
@@ -130,3 +460,213 @@ fn update_smooth_scroll_positions(
         scroll_position.0 = src.lerp(dst, t);
     }
 }
+
+/// Spawns the content node of a [`VirtualScroll`] when it is added.
+fn on_virtual_scroll_added(
+    trigger: On<Add, VirtualScroll>,
+    mut containers: Query<&mut VirtualScroll>,
+    mut commands: Commands,
+) {
+    let Ok(mut virtual_scroll) = containers.get_mut(trigger.entity) else {
+        return;
+    };
+
+    let content = commands
+        .spawn((
+            ChildOf(trigger.entity),
+            Node {
+                width: percent(100.0),
+                height: px(0.0),
+                flex_shrink: 0.0,
+                ..default()
+            },
+        ))
+        .id();
+
+    virtual_scroll.content = Some(content);
+    virtual_scroll.rebind = true;
+}
+
+/// Binds the item nodes of each [`VirtualScroll`] to the items in view.
+///
+/// Item nodes whose item has left the view are reused for the items that
+/// entered it, and new item nodes are only spawned when the pool runs out.
+/// This uses the layout of the previous frame, so items that enter the view
+/// are filled in a frame before they are drawn.
+fn update_virtual_scrolls(
+    mut containers: Query<(&mut VirtualScroll, &ScrollPosition, &ComputedNode)>,
+    mut items: Query<(&mut VirtualItem, &mut Node, &ComputedNode), Without<VirtualScroll>>,
+    mut contents: Query<&mut Node, (Without<VirtualItem>, Without<VirtualScroll>)>,
+    mut commands: Commands,
+) {
+    for (mut virtual_scroll, scroll_position, computed) in containers.iter_mut() {
+        let Some(content) = virtual_scroll.content else {
+            continue;
+        };
+
+        let measured = items
+            .iter_many(&virtual_scroll.items)
+            .filter(|(item, ..)| item.index.is_some())
+            .map(|(.., computed)| computed.size() * computed.inverse_scale_factor())
+            .filter(|size| size.y > 0.0)
+            .reduce(Vec2::max)
+            .map(|size| {
+                if virtual_scroll.grid {
+                    size
+                } else {
+                    Vec2::new(0.0, size.y)
+                }
+            });
+
+        let resized = measured
+            .is_some_and(|size| (size - virtual_scroll.item_size).abs().max_element() > 0.5);
+
+        let viewport = computed.size() * computed.inverse_scale_factor();
+        let item_size = measured
+            .filter(|_| resized)
+            .unwrap_or(virtual_scroll.item_size);
+        let scroll_y = scroll_position.0.y.max(0.0);
+        let VirtualLayout {
+            columns,
+            rows,
+            range,
+        } = virtual_scroll.layout(viewport, scroll_y, item_size);
+
+        if !virtual_scroll.rebind
+            && !resized
+            && virtual_scroll.columns == columns
+            && virtual_scroll.range == range
+        {
+            continue;
+        }
+
+        let virtual_scroll = virtual_scroll.into_inner();
+        let rebind = std::mem::take(&mut virtual_scroll.rebind);
+        virtual_scroll.item_size = item_size;
+        virtual_scroll.columns = columns;
+        virtual_scroll.range = range.clone();
+
+        let height = (rows as f32 * virtual_scroll.stride().y - virtual_scroll.spacing.y).max(0.0);
+        if let Ok(mut node) = contents.get_mut(content) {
+            if node.height != px(height) {
+                node.height = px(height);
+            }
+        }
+
+        let mut bound = HashSet::new();
+        let mut unused = Vec::new();
+        for &entity in &virtual_scroll.items {
+            let Ok((mut item, mut node, _)) = items.get_mut(entity) else {
+                continue;
+            };
+
+            match item.index {
+                Some(index) if !rebind && range.contains(&index) => {
+                    virtual_scroll.place_item(&mut node, index);
+                    bound.insert(index);
+                }
+                _ => {
+                    item.index = None;
+                    node.display = Display::None;
+                    unused.push(entity);
+                }
+            }
+        }
+
+        for index in range.filter(|index| !bound.contains(index)) {
+            let entity = match unused.pop() {
+                Some(entity) => {
+                    if let Ok((mut item, mut node, _)) = items.get_mut(entity) {
+                        item.index = Some(index);
+                        virtual_scroll.place_item(&mut node, index);
+                    }
+                    entity
+                }
+                None => {
+                    let mut node = Node::default();
+                    virtual_scroll.place_item(&mut node, index);
+
+                    let entity = commands
+                        .spawn((ChildOf(content), VirtualItem { index: Some(index) }, node))
+                        .id();
+                    virtual_scroll.items.push(entity);
+                    entity
+                }
+            };
+
+            commands.trigger(BindVirtualItem { entity, index });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gets the layout of a virtual scroll in a view of 100 by 50 pixels.
+    fn layout(virtual_scroll: &VirtualScroll, scroll_y: f32) -> VirtualLayout {
+        virtual_scroll.layout(Vec2::new(100.0, 50.0), scroll_y, virtual_scroll.item_size)
+    }
+
+    #[test]
+    fn rows_cover_the_view_and_overscan() {
+        let rows = VirtualScroll::rows(10.0).with_item_count(100);
+
+        let top = layout(&rows, 0.0);
+        assert_eq!(top.columns, 1);
+        assert_eq!(top.rows, 100);
+        assert_eq!(top.range, 0 .. 7);
+
+        let middle = layout(&rows, 200.0);
+        assert_eq!(middle.range, 18 .. 27);
+
+        let partial = layout(&rows, 205.0);
+        assert_eq!(partial.range, 18 .. 28);
+    }
+
+    #[test]
+    fn overscan_is_clamped_to_the_items() {
+        let rows = VirtualScroll::rows(10.0)
+            .with_item_count(20)
+            .with_overscan(5);
+
+        assert_eq!(layout(&rows, 0.0).range, 0 .. 10);
+        assert_eq!(layout(&rows, 150.0).range, 10 .. 20);
+        assert_eq!(layout(&rows, 1000.0).range, 20 .. 20);
+    }
+
+    #[test]
+    fn grids_fit_columns_to_the_view() {
+        let grid = VirtualScroll::grid(Vec2::splat(20.0))
+            .with_item_count(23)
+            .with_spacing(Vec2::splat(5.0))
+            .with_overscan(0);
+
+        let top = layout(&grid, 0.0);
+        assert_eq!(top.columns, 4);
+        assert_eq!(top.rows, 6);
+        assert_eq!(top.range, 0 .. 8);
+
+        let bottom = layout(&grid, 100.0);
+        assert_eq!(bottom.range, 16 .. 23);
+    }
+
+    #[test]
+    fn narrow_grids_keep_one_column() {
+        let grid = VirtualScroll::grid(Vec2::splat(200.0)).with_item_count(3);
+
+        let layout = layout(&grid, 0.0);
+        assert_eq!(layout.columns, 1);
+        assert_eq!(layout.rows, 3);
+        assert_eq!(layout.range, 0 .. 3);
+    }
+
+    #[test]
+    fn empty_scrolls_have_no_items() {
+        let rows = VirtualScroll::rows(10.0);
+
+        let layout = layout(&rows, 0.0);
+        assert_eq!(layout.rows, 0);
+        assert_eq!(layout.range, 0 .. 0);
+    }
+}
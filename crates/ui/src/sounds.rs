@@ -0,0 +1,141 @@
+//! This module implements sound effect hooks for widget interactions.
+
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+
+use crate::interaction::InteractionSender;
+
+/// A plugin that plays sound effects when widgets are interacted with.
+pub struct UiSoundsPlugin;
+impl Plugin for UiSoundsPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_message::<PlayUiSound>()
+            .add_systems(Update, play_ui_sounds)
+            .add_observer(on_hover_sound)
+            .add_observer(on_activate_sound);
+    }
+}
+
+/// The different kinds of UI sound effects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UiSound {
+    /// Played when the cursor starts hovering over an interactive widget.
+    Hover,
+
+    /// Played when a widget is activated.
+    Click,
+
+    /// Played when a menu or popup is opened.
+    OpenMenu,
+
+    /// Played when an action fails.
+    Error,
+}
+
+/// A resource mapping UI sound effects to audio handles.
+///
+/// This resource is optional. If it is not present, or a sound is not
+/// assigned, no sound will be played.
+#[derive(Debug, Clone, Resource)]
+pub struct UiSounds {
+    /// The sound played when hovering over a widget.
+    pub hover: Option<Handle<AudioSource>>,
+
+    /// The sound played when a widget is activated.
+    pub click: Option<Handle<AudioSource>>,
+
+    /// The sound played when a menu is opened.
+    pub open_menu: Option<Handle<AudioSource>>,
+
+    /// The sound played when an action fails.
+    pub error: Option<Handle<AudioSource>>,
+
+    /// The volume to play sounds at.
+    pub volume: f32,
+}
+
+impl Default for UiSounds {
+    fn default() -> Self {
+        Self {
+            hover: None,
+            click: None,
+            open_menu: None,
+            error: None,
+            volume: 1.0,
+        }
+    }
+}
+
+impl UiSounds {
+    /// Gets the audio handle for the given sound, if assigned.
+    pub fn get(&self, sound: UiSound) -> Option<&Handle<AudioSource>> {
+        match sound {
+            UiSound::Hover => self.hover.as_ref(),
+            UiSound::Click => self.click.as_ref(),
+            UiSound::OpenMenu => self.open_menu.as_ref(),
+            UiSound::Error => self.error.as_ref(),
+        }
+    }
+}
+
+/// A message that requests a UI sound to be played.
+///
+/// Widgets send this for events that are not covered by the built-in hover
+/// and click hooks, such as opening a menu or reporting an error.
+#[derive(Debug, Clone, Copy, Message)]
+pub struct PlayUiSound(pub UiSound);
+
+/// A marker component that prevents an interactive widget from playing the
+/// built-in hover and click sounds.
+#[derive(Debug, Default, Component)]
+pub struct Silent;
+
+/// Plays the hover sound when an interactive widget becomes hovered.
+fn on_hover_sound(
+    trigger: On<Insert, Hovered>,
+    query: Query<&Hovered, (With<InteractionSender>, Without<Silent>)>,
+    mut sounds: MessageWriter<PlayUiSound>,
+) {
+    let Ok(hovered) = query.get(trigger.entity) else {
+        return;
+    };
+
+    if hovered.0 {
+        sounds.write(PlayUiSound(UiSound::Hover));
+    }
+}
+
+/// Plays the click sound when a widget is activated.
+fn on_activate_sound(
+    trigger: On<Activate>,
+    query: Query<(), Without<Silent>>,
+    mut sounds: MessageWriter<PlayUiSound>,
+) {
+    if query.contains(trigger.event().entity) {
+        sounds.write(PlayUiSound(UiSound::Click));
+    }
+}
+
+/// Plays all requested UI sounds.
+fn play_ui_sounds(
+    mut reader: MessageReader<PlayUiSound>,
+    sounds: Option<Res<UiSounds>>,
+    mut commands: Commands,
+) {
+    let Some(sounds) = sounds else {
+        reader.clear();
+        return;
+    };
+
+    for PlayUiSound(sound) in reader.read() {
+        let Some(handle) = sounds.get(*sound) else {
+            continue;
+        };
+
+        commands.spawn((
+            AudioPlayer(handle.clone()),
+            PlaybackSettings::DESPAWN.with_volume(bevy::audio::Volume::Linear(sounds.volume)),
+        ));
+    }
+}
@@ -4,7 +4,9 @@ use std::sync::Arc;
 
 use bevy::prelude::*;
 
+use crate::accessibility::AccessibilityOptions;
 use crate::color::InteractiveColor;
+use crate::label::LabelOverflow;
 
 /// The theme for the UI components.
 ///
@@ -31,11 +33,30 @@ pub struct GlobalTheme {
     /// The theme for buttons.
     pub button: ButtonTheme,
 
+    /// The theme for the empty and error placeholders of container widgets.
+    pub placeholder: PlaceholderTheme,
+
+    /// The theme for the busy overlay shown while blocking operations run.
+    pub busy: BusyTheme,
+
+    /// The theme for text inputs.
+    pub text_input: TextInputTheme,
+
     /// The theme for tree views.
+    #[cfg(feature = "tree_view")]
     pub tree_view: TreeViewTheme,
 
     /// The theme for grid previews.
+    #[cfg(feature = "grid_preview")]
     pub grid_preview: GridPreviewTheme,
+
+    /// The theme for forms.
+    #[cfg(feature = "dialogs")]
+    pub form: FormTheme,
+
+    /// The theme for chat consoles.
+    #[cfg(feature = "console")]
+    pub console: ConsoleTheme,
 }
 
 /// Theme for a generic container.
@@ -79,6 +100,9 @@ pub struct FontTheme {
 
     /// The default color of the font.
     pub color: ColorTheme,
+
+    /// How labels behave when their text does not fit within their node.
+    pub overflow: LabelOverflow,
 }
 
 /// Theme for different colors based on interaction state.
@@ -106,6 +130,42 @@ pub enum ColorTheme {
     Fixed(Color),
 }
 
+impl ColorTheme {
+    /// Gets the color of the default interaction state.
+    pub fn default_color(&self) -> Color {
+        match self {
+            ColorTheme::Interactive { default, .. } => *default,
+            ColorTheme::Fixed(color) => *color,
+        }
+    }
+
+    /// Returns true if this color changes with interaction.
+    pub fn is_interactive(&self) -> bool {
+        matches!(self, ColorTheme::Interactive { .. })
+    }
+
+    /// Creates a new color theme by applying the given function to the color
+    /// of every interaction state.
+    pub fn map(&self, f: impl Fn(Color) -> Color) -> ColorTheme {
+        match self {
+            ColorTheme::Interactive {
+                default,
+                hovered,
+                pressed,
+                disable,
+                checked,
+            } => ColorTheme::Interactive {
+                default: f(*default),
+                hovered: f(*hovered),
+                pressed: f(*pressed),
+                disable: f(*disable),
+                checked: f(*checked),
+            },
+            ColorTheme::Fixed(color) => ColorTheme::Fixed(f(*color)),
+        }
+    }
+}
+
 impl From<Color> for ColorTheme {
     fn from(color: Color) -> Self {
         ColorTheme::Fixed(color)
@@ -119,7 +179,55 @@ pub struct ButtonTheme {
     pub container: ContainerTheme,
 }
 
+/// Theme for the empty and error placeholders of container widgets.
+#[derive(Debug, Clone)]
+pub struct PlaceholderTheme {
+    /// The theme for the placeholder node, its icon, and the message of empty
+    /// placeholders.
+    pub container: ContainerTheme,
+
+    /// The theme for the message of error placeholders.
+    pub error_text: FontTheme,
+
+    /// The icon of empty placeholders that have no icon of their own.
+    pub empty_icon: Handle<Image>,
+
+    /// The icon of error placeholders that have no icon of their own.
+    pub error_icon: Handle<Image>,
+}
+
+/// Theme for the busy overlay shown while blocking operations run.
+#[derive(Debug, Clone)]
+pub struct BusyTheme {
+    /// The color of the backdrop that dims the screen behind the overlay.
+    pub backdrop: Color,
+
+    /// The theme for the panel of the overlay, its spinner, and its status
+    /// text.
+    pub container: ContainerTheme,
+
+    /// The icon of the spinner.
+    pub spinner_icon: Handle<Image>,
+}
+
+/// Theme for the text input widget.
+#[derive(Debug, Clone)]
+pub struct TextInputTheme {
+    /// The theme for the text input node and its text.
+    pub container: ContainerTheme,
+
+    /// The color of the placeholder text shown while the text input is empty.
+    pub placeholder_color: Color,
+
+    /// The background color of the selected text.
+    pub selection_color: Color,
+
+    /// The color of the cursor.
+    pub cursor_color: Color,
+}
+
 /// Theme for the tree view.
+#[cfg(feature = "tree_view")]
 #[derive(Debug, Clone)]
 pub struct TreeViewTheme {
     /// The theme for the container of the tree view.
@@ -128,6 +236,10 @@ pub struct TreeViewTheme {
     /// The theme for the label of each tree node.
     pub label: ContainerTheme,
 
+    /// The theme for the badge shown at the end of a tree node label, such as
+    /// the number of items in a folder.
+    pub badge: ContainerTheme,
+
     /// The icon for a collapsed node.
     pub right_arrow_icon: Handle<Image>,
 
@@ -139,6 +251,7 @@ pub struct TreeViewTheme {
 }
 
 /// Theme for the grid preview widget.
+#[cfg(feature = "grid_preview")]
 #[derive(Debug, Clone)]
 pub struct GridPreviewTheme {
     /// The theme for the container of the grid preview.
@@ -152,10 +265,43 @@ pub struct GridPreviewTheme {
 
     /// The theme for each cell in the grid.
     pub cell: ContainerTheme,
+
+    /// The placeholder icon shown, spinning, while a deferred cell icon is
+    /// loading.
+    pub loading_icon: Handle<Image>,
+
+    /// The icon shown when a deferred cell icon fails to load.
+    pub error_icon: Handle<Image>,
+
+    /// The color of the rectangle drawn while box-selecting cells. It is drawn
+    /// as the border of the rectangle, and faded for its background.
+    pub selection_color: Color,
+}
+
+/// Theme for form widgets.
+#[cfg(feature = "dialogs")]
+#[derive(Debug, Clone)]
+pub struct FormTheme {
+    /// The theme for inline validation error text.
+    pub error_text: FontTheme,
+}
+
+/// Theme for the chat console widget.
+#[cfg(feature = "console")]
+#[derive(Debug, Clone)]
+pub struct ConsoleTheme {
+    /// The theme for the message scrollback.
+    pub log: ContainerTheme,
+
+    /// The theme for the input line.
+    pub input: ContainerTheme,
 }
 
+/// Styles a container when its container theme is added, adapting the theme to
+/// the [`AccessibilityOptions`].
 pub(crate) fn style_container(
     trigger: On<Add, ContainerTheme>,
+    options: Res<AccessibilityOptions>,
     mut query: Query<(
         &mut Node,
         &mut BackgroundColor,
@@ -171,18 +317,48 @@ pub(crate) fn style_container(
         warn!("UiTheme component missing on entity added trigger");
         return;
     };
-
     node.border = UiRect::all(px(theme.border_thickness));
     node.padding = theme.padding;
     *border_radius = BorderRadius::all(px(theme.border_radius));
 
+    apply_container_colors(
+        trigger.entity,
+        theme,
+        &options,
+        &mut bg_color,
+        &mut border_color,
+        &mut commands,
+    );
+}
+
+/// Applies the background and border colors of a container theme, adapted to
+/// the [`AccessibilityOptions`], to the given container.
+///
+/// Interactive colors are inserted as [`InteractiveColor`] components. If an
+/// interactive color of the theme is adapted to a fixed color, such as in
+/// high-contrast mode, the interactive color inserted for it is removed.
+pub(crate) fn apply_container_colors(
+    entity: Entity,
+    base: &ContainerTheme,
+    options: &AccessibilityOptions,
+    bg_color: &mut BackgroundColor,
+    border_color: &mut BorderColor,
+    commands: &mut Commands,
+) {
+    let theme = options.adapt_container(base);
+
     match theme.background_color {
         ColorTheme::Fixed(color) => {
             *bg_color = BackgroundColor(color);
+            if base.background_color.is_interactive() {
+                commands
+                    .entity(entity)
+                    .remove::<InteractiveColor<BackgroundColor>>();
+            }
         }
         ColorTheme::Interactive { .. } => {
             commands
-                .entity(trigger.entity)
+                .entity(entity)
                 .insert(InteractiveColor::<BackgroundColor>::from(
                     &theme.background_color,
                 ));
@@ -192,18 +368,27 @@ pub(crate) fn style_container(
     match theme.border_color {
         ColorTheme::Fixed(color) => {
             *border_color = BorderColor::all(color);
+            if base.border_color.is_interactive() {
+                commands
+                    .entity(entity)
+                    .remove::<InteractiveColor<BorderColor>>();
+            }
         }
         ColorTheme::Interactive { .. } => {
             commands
-                .entity(trigger.entity)
+                .entity(entity)
                 .insert(InteractiveColor::<BorderColor>::from(&theme.border_color));
         }
     }
 }
 
-/// Styles a text component when its font theme is added.
+/// Styles a text component when its font theme is added, adapting the theme to
+/// the [`AccessibilityOptions`].
 pub(crate) fn style_text(
     trigger: On<Add, FontTheme>,
+    options: Res<AccessibilityOptions>,
+    parents: Query<&ChildOf>,
+    containers: Query<&ContainerTheme>,
     mut query: Query<(&mut TextFont, &mut TextColor, &FontTheme)>,
     mut commands: Commands,
 ) {
@@ -215,14 +400,59 @@ pub(crate) fn style_text(
     text_font.font = theme.font.clone();
     text_font.font_size = theme.font_size;
 
-    match &theme.color {
+    apply_text_color(
+        trigger.entity,
+        theme,
+        &options,
+        &parents,
+        &containers,
+        &mut text_color,
+        &mut commands,
+    );
+}
+
+/// Applies the color of a font theme, adapted to the [`AccessibilityOptions`],
+/// to the given text.
+///
+/// In high-contrast mode, the text takes the text color of the high-contrast
+/// variant of the nearest container it is drawn on. As with containers, the
+/// interactive color inserted for an interactive theme color is removed once
+/// it is adapted to a fixed color.
+pub(crate) fn apply_text_color(
+    entity: Entity,
+    theme: &FontTheme,
+    options: &AccessibilityOptions,
+    parents: &Query<&ChildOf>,
+    containers: &Query<&ContainerTheme>,
+    text_color: &mut TextColor,
+    commands: &mut Commands,
+) {
+    let container = if options.high_contrast {
+        parents
+            .iter_ancestors(entity)
+            .find_map(|entity| containers.get(entity).ok())
+    } else {
+        None
+    };
+
+    let color = match container {
+        Some(container) => options.adapt_color(&container.high_contrast().text.color),
+        None => options.adapt_color(&theme.color),
+    };
+
+    match &color {
         ColorTheme::Fixed(color) => {
             *text_color = TextColor(*color);
+            if theme.color.is_interactive() {
+                commands
+                    .entity(entity)
+                    .remove::<InteractiveColor<TextColor>>();
+            }
         }
         ColorTheme::Interactive { .. } => {
             commands
-                .entity(trigger.entity)
-                .insert(InteractiveColor::<TextColor>::from(&theme.color));
+                .entity(entity)
+                .insert(InteractiveColor::<TextColor>::from(&color));
         }
     }
 }
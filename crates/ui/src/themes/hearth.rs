@@ -2,18 +2,26 @@
 
 use bevy::prelude::*;
 
-use crate::theme::{ButtonTheme, ColorTheme, ContainerTheme, FontTheme, TreeViewTheme, UiTheme};
+use crate::label::LabelOverflow;
+use crate::theme::{ButtonTheme, ColorTheme, ContainerTheme, FontTheme, UiTheme};
 
 /// Creates a new instance of the `hearth` UI theme.
 #[cfg(feature = "editor")]
 pub fn hearth_theme(asset_server: &Res<AssetServer>) -> UiTheme {
-    use crate::theme::{GlobalTheme, GridPreviewTheme};
-    use crate::{DOWN_ARROW_ICON, QUIVER_FONT, RIGHT_ARROW_ICON, SPACER_ICON};
+    #[cfg(feature = "console")]
+    use crate::theme::ConsoleTheme;
+    #[cfg(feature = "dialogs")]
+    use crate::theme::FormTheme;
+    #[cfg(feature = "grid_preview")]
+    use crate::theme::GridPreviewTheme;
+    #[cfg(feature = "tree_view")]
+    use crate::theme::TreeViewTheme;
+    use crate::theme::{BusyTheme, GlobalTheme, PlaceholderTheme, TextInputTheme};
+    #[cfg(feature = "tree_view")]
+    use crate::{DOWN_ARROW_ICON, RIGHT_ARROW_ICON, SPACER_ICON};
+    use crate::{ERROR_ICON, FOLDER_ICON, LOADING_ICON, QUIVER_FONT};
 
     let font = asset_server.load(QUIVER_FONT);
-    let right_arrow_icon = asset_server.load(RIGHT_ARROW_ICON);
-    let down_arrow_icon = asset_server.load(DOWN_ARROW_ICON);
-    let spacer_icon = asset_server.load(SPACER_ICON);
 
     UiTheme::from(GlobalTheme {
         outer_window: ContainerTheme {
@@ -32,6 +40,7 @@ pub fn hearth_theme(asset_server: &Res<AssetServer>) -> UiTheme {
                     disable: Color::srgb_u8(97, 74, 49).with_saturation(0.0),
                     checked: Color::srgb_u8(97, 74, 49).darker(0.1),
                 },
+                overflow: LabelOverflow::default(),
             },
             icon_size: 32.0,
             icon_color: Color::srgb_u8(255, 255, 255).into(),
@@ -52,6 +61,7 @@ pub fn hearth_theme(asset_server: &Res<AssetServer>) -> UiTheme {
                     disable: Color::srgb_u8(97, 74, 49).with_saturation(0.0),
                     checked: Color::srgb_u8(97, 74, 49).darker(0.1),
                 },
+                overflow: LabelOverflow::default(),
             },
             icon_size: 24.0,
             icon_color: Color::srgb_u8(255, 255, 255).into(),
@@ -85,6 +95,7 @@ pub fn hearth_theme(asset_server: &Res<AssetServer>) -> UiTheme {
                         disable: Color::srgb_u8(97, 74, 49).with_saturation(0.0),
                         checked: Color::srgb_u8(97, 74, 49).darker(0.1),
                     },
+                    overflow: LabelOverflow::default(),
                 },
                 icon_size: 16.0,
                 icon_color: ColorTheme::Interactive {
@@ -96,6 +107,77 @@ pub fn hearth_theme(asset_server: &Res<AssetServer>) -> UiTheme {
                 },
             },
         },
+        placeholder: PlaceholderTheme {
+            container: ContainerTheme {
+                background_color: Color::NONE.into(),
+                border_color: Color::NONE.into(),
+                border_thickness: 0.0,
+                border_radius: 0.0,
+                padding: UiRect::all(px(16.0)),
+                text: FontTheme {
+                    font: font.clone(),
+                    font_size: 16.0,
+                    color: Color::srgb_u8(97, 74, 49).into(),
+                    overflow: LabelOverflow::default(),
+                },
+                icon_size: 48.0,
+                icon_color: Color::srgb_u8(97, 74, 49).into(),
+            },
+            error_text: FontTheme {
+                font: font.clone(),
+                font_size: 16.0,
+                color: Color::srgb_u8(158, 42, 28).into(),
+                overflow: LabelOverflow::default(),
+            },
+            empty_icon: asset_server.load(FOLDER_ICON),
+            error_icon: asset_server.load(ERROR_ICON),
+        },
+        busy: BusyTheme {
+            backdrop: Color::srgba(0.0, 0.0, 0.0, 0.6),
+            container: ContainerTheme {
+                background_color: Color::srgb_u8(213, 169, 110).into(),
+                border_color: Color::srgb_u8(91, 74, 49).into(),
+                border_thickness: 4.0,
+                border_radius: 8.0,
+                padding: UiRect::all(px(16.0)),
+                text: FontTheme {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    color: Color::srgb_u8(97, 74, 49).into(),
+                    overflow: LabelOverflow::default(),
+                },
+                icon_size: 48.0,
+                icon_color: Color::srgb_u8(97, 74, 49).into(),
+            },
+            spinner_icon: asset_server.load(LOADING_ICON),
+        },
+        text_input: TextInputTheme {
+            container: ContainerTheme {
+                background_color: ColorTheme::Interactive {
+                    default: Color::srgb_u8(240, 220, 185),
+                    hovered: Color::srgb_u8(240, 220, 185).lighter(0.05),
+                    pressed: Color::srgb_u8(240, 220, 185),
+                    disable: Color::srgb_u8(240, 220, 185).with_saturation(0.0),
+                    checked: Color::srgb_u8(240, 220, 185),
+                },
+                border_color: Color::srgb_u8(193, 147, 91).into(),
+                border_thickness: 2.0,
+                border_radius: 4.0,
+                padding: UiRect::axes(px(8.0), px(4.0)),
+                text: FontTheme {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    color: Color::srgb_u8(97, 74, 49).into(),
+                    overflow: LabelOverflow::default(),
+                },
+                icon_size: 20.0,
+                icon_color: Color::srgb_u8(97, 74, 49).into(),
+            },
+            placeholder_color: Color::srgb_u8(97, 74, 49).with_alpha(0.5),
+            selection_color: Color::srgba_u8(193, 147, 91, 160),
+            cursor_color: Color::srgb_u8(97, 74, 49),
+        },
+        #[cfg(feature = "tree_view")]
         tree_view: TreeViewTheme {
             container: ContainerTheme {
                 background_color: Color::srgb_u8(217, 173, 114).into(),
@@ -113,6 +195,7 @@ pub fn hearth_theme(asset_server: &Res<AssetServer>) -> UiTheme {
                         disable: Color::srgb_u8(97, 74, 49).with_saturation(0.0),
                         checked: Color::srgb_u8(97, 74, 49).darker(0.1),
                     },
+                    overflow: LabelOverflow::default(),
                 },
                 icon_size: 16.0,
                 icon_color: Color::srgb_u8(255, 255, 255).into(),
@@ -139,6 +222,7 @@ pub fn hearth_theme(asset_server: &Res<AssetServer>) -> UiTheme {
                         disable: Color::srgb_u8(97, 74, 49).with_saturation(0.0),
                         checked: Color::srgb_u8(97, 74, 49).darker(0.1),
                     },
+                    overflow: LabelOverflow::Ellipsis,
                 },
                 icon_size: 16.0,
                 icon_color: ColorTheme::Interactive {
@@ -149,10 +233,26 @@ pub fn hearth_theme(asset_server: &Res<AssetServer>) -> UiTheme {
                     checked: Color::srgb_u8(240, 240, 240).darker(0.1),
                 },
             },
-            right_arrow_icon,
-            down_arrow_icon,
-            spacer_icon,
+            badge: ContainerTheme {
+                background_color: Color::srgb_u8(193, 147, 91).into(),
+                border_color: Color::srgb_u8(193, 147, 91).into(),
+                border_thickness: 0.0,
+                border_radius: 6.0,
+                padding: UiRect::horizontal(px(5.0)),
+                text: FontTheme {
+                    font: font.clone(),
+                    font_size: 12.0,
+                    color: Color::srgb_u8(255, 255, 255).into(),
+                    overflow: LabelOverflow::default(),
+                },
+                icon_size: 12.0,
+                icon_color: Color::srgb_u8(255, 255, 255).into(),
+            },
+            right_arrow_icon: asset_server.load(RIGHT_ARROW_ICON),
+            down_arrow_icon: asset_server.load(DOWN_ARROW_ICON),
+            spacer_icon: asset_server.load(SPACER_ICON),
         },
+        #[cfg(feature = "grid_preview")]
         grid_preview: GridPreviewTheme {
             container: ContainerTheme {
                 background_color: Color::srgb_u8(217, 173, 114).into(),
@@ -170,6 +270,7 @@ pub fn hearth_theme(asset_server: &Res<AssetServer>) -> UiTheme {
                         disable: Color::srgb_u8(97, 74, 49).with_saturation(0.0),
                         checked: Color::srgb_u8(97, 74, 49).darker(0.1),
                     },
+                    overflow: LabelOverflow::default(),
                 },
                 icon_size: 24.0,
                 icon_color: Color::srgb_u8(255, 255, 255).into(),
@@ -204,6 +305,7 @@ pub fn hearth_theme(asset_server: &Res<AssetServer>) -> UiTheme {
                         disable: Color::srgb_u8(97, 74, 49).with_saturation(0.0),
                         checked: Color::srgb_u8(97, 74, 49).darker(0.1),
                     },
+                    overflow: LabelOverflow::Ellipsis,
                 },
                 icon_size: 16.0,
                 icon_color: ColorTheme::Interactive {
@@ -214,6 +316,51 @@ pub fn hearth_theme(asset_server: &Res<AssetServer>) -> UiTheme {
                     checked: Color::srgb_u8(240, 240, 240).darker(0.1),
                 },
             },
+            loading_icon: asset_server.load(LOADING_ICON),
+            error_icon: asset_server.load(ERROR_ICON),
+            selection_color: Color::srgb_u8(97, 74, 49),
+        },
+        #[cfg(feature = "dialogs")]
+        form: FormTheme {
+            error_text: FontTheme {
+                font: font.clone(),
+                font_size: 14.0,
+                color: Color::srgb_u8(158, 42, 28).into(),
+                overflow: LabelOverflow::default(),
+            },
+        },
+        #[cfg(feature = "console")]
+        console: ConsoleTheme {
+            log: ContainerTheme {
+                background_color: Color::srgba_u8(97, 74, 49, 160).into(),
+                border_color: Color::NONE.into(),
+                border_thickness: 0.0,
+                border_radius: 8.0,
+                padding: UiRect::all(px(8.0)),
+                text: FontTheme {
+                    font: font.clone(),
+                    font_size: 16.0,
+                    color: Color::srgb_u8(240, 240, 240).into(),
+                    overflow: LabelOverflow::default(),
+                },
+                icon_size: 16.0,
+                icon_color: Color::srgb_u8(240, 240, 240).into(),
+            },
+            input: ContainerTheme {
+                background_color: Color::srgb_u8(217, 173, 114).into(),
+                border_color: Color::srgb_u8(193, 147, 91).into(),
+                border_thickness: 2.0,
+                border_radius: 4.0,
+                padding: UiRect::axes(px(8.0), px(4.0)),
+                text: FontTheme {
+                    font: font.clone(),
+                    font_size: 16.0,
+                    color: Color::srgb_u8(97, 74, 49).into(),
+                    overflow: LabelOverflow::default(),
+                },
+                icon_size: 16.0,
+                icon_color: Color::srgb_u8(240, 240, 240).into(),
+            },
         },
     })
 }
@@ -0,0 +1,194 @@
+//! This module implements the busy overlay, which is shown while a blocking
+//! operation runs.
+//!
+//! The overlay covers the whole screen with a dimmed backdrop that blocks all
+//! pointer input, and shows a spinner along with the status of the operation.
+//! It may also show a cancel button, which triggers a [`BusyCancelPressed`]
+//! event on the overlay. The status can be changed while the overlay is shown
+//! by editing its [`BusyOverlay`] component.
+
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+
+use crate::accessibility::AccessibilityOptions;
+use crate::color::InteractiveColor;
+use crate::menus::layers::UiLayer;
+use crate::menus::overlay::ScreenAnchor;
+use crate::theme::UiTheme;
+use crate::widgets::button::{ButtonBuilder, ButtonContent, button};
+
+/// The speed, in radians per second, at which the spinner of a busy overlay
+/// spins.
+const SPINNER_SPEED: f32 = 4.0;
+
+/// A plugin that adds support for busy overlays.
+pub(crate) struct BusyOverlayPlugin;
+impl Plugin for BusyOverlayPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_observer(on_cancel_activated)
+            .add_systems(Update, (update_busy_status, spin_busy_spinners));
+    }
+}
+
+/// Builder for a busy overlay.
+#[derive(Debug, Clone)]
+pub struct BusyOverlayBuilder {
+    /// The status text shown below the spinner.
+    pub status: String,
+
+    /// The label of the cancel button, such as "Cancel". If `None`, the
+    /// operation cannot be cancelled and no button is shown.
+    pub cancel: Option<String>,
+
+    /// The theme for the overlay.
+    pub theme: UiTheme,
+}
+
+/// A component on the root node of a busy overlay.
+#[derive(Debug, Clone, PartialEq, Eq, Component)]
+pub struct BusyOverlay {
+    /// The status text shown below the spinner. Changing it updates the shown
+    /// text.
+    pub status: String,
+}
+
+/// An event that is triggered on a busy overlay when its cancel button is
+/// pressed.
+#[derive(Debug, EntityEvent)]
+pub struct BusyCancelPressed {
+    /// The busy overlay entity.
+    pub entity: Entity,
+}
+
+/// A component on the spinner of a busy overlay.
+#[derive(Debug, Component)]
+struct BusySpinner;
+
+/// A component on the status text of a busy overlay.
+#[derive(Debug, Component)]
+struct BusyStatusText;
+
+/// A component on the cancel button of a busy overlay.
+#[derive(Debug, Component)]
+struct BusyCancelButton;
+
+/// Creates a busy overlay that covers the whole screen.
+pub fn busy_overlay(builder: BusyOverlayBuilder) -> impl Bundle {
+    let theme = &builder.theme.busy;
+    let container = theme.container.clone();
+    let icon_size = container.icon_size;
+
+    let cancel = builder.cancel.map(|label| {
+        (
+            BusyCancelButton,
+            button(ButtonBuilder {
+                node: Node::default(),
+                content: ButtonContent::Label(label),
+                theme: builder.theme.clone(),
+            }),
+        )
+    });
+
+    (
+        BusyOverlay {
+            status: builder.status.clone(),
+        },
+        ScreenAnchor::Fullscreen,
+        Node {
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(theme.backdrop),
+        UiLayer::Dialogs,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: px(8.0),
+                min_width: px(240.0),
+                ..default()
+            },
+            container.clone(),
+            Children::spawn((
+                Spawn((
+                    BusySpinner,
+                    Node {
+                        width: px(icon_size),
+                        height: px(icon_size),
+                        ..default()
+                    },
+                    ImageNode::new(theme.spinner_icon.clone()),
+                    InteractiveColor::<ImageNode>::from(&container.icon_color),
+                )),
+                Spawn((
+                    BusyStatusText,
+                    Text::new(builder.status),
+                    container.text.clone(),
+                )),
+                SpawnIter(cancel.into_iter()),
+            )),
+        )],
+    )
+}
+
+/// Updates the status text of busy overlays whose status has changed.
+fn update_busy_status(
+    overlays: Query<(Entity, &BusyOverlay), Changed<BusyOverlay>>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text, With<BusyStatusText>>,
+) {
+    for (entity, overlay) in overlays.iter() {
+        for child in children.iter_descendants(entity) {
+            let Ok(mut text) = texts.get_mut(child) else {
+                continue;
+            };
+
+            if text.0 != overlay.status {
+                text.0 = overlay.status.clone();
+            }
+        }
+    }
+}
+
+/// Spins the spinners of busy overlays, unless
+/// [`AccessibilityOptions::reduce_motion`] is set.
+fn spin_busy_spinners(
+    time: Res<Time>,
+    options: Res<AccessibilityOptions>,
+    mut spinners: Query<&mut UiTransform, With<BusySpinner>>,
+) {
+    let rotation = if options.reduce_motion {
+        Rot2::IDENTITY
+    } else {
+        Rot2::radians(time.elapsed_secs() * SPINNER_SPEED)
+    };
+
+    for mut transform in spinners.iter_mut() {
+        transform.rotation = rotation;
+    }
+}
+
+/// Triggers a [`BusyCancelPressed`] event on the busy overlay of a cancel
+/// button when it is activated.
+fn on_cancel_activated(
+    trigger: On<Activate>,
+    buttons: Query<(), With<BusyCancelButton>>,
+    parents: Query<&ChildOf>,
+    overlays: Query<(), With<BusyOverlay>>,
+    mut commands: Commands,
+) {
+    let button = trigger.event().entity;
+    if !buttons.contains(button) {
+        return;
+    }
+
+    let Some(overlay) = parents
+        .iter_ancestors(button)
+        .find(|entity| overlays.contains(*entity))
+    else {
+        return;
+    };
+
+    commands.trigger(BusyCancelPressed { entity: overlay });
+}
@@ -2,12 +2,22 @@
 
 use bevy::ecs::relationship::RelatedSpawner;
 use bevy::prelude::*;
-use bevy::ui_widgets::Button;
+use bevy::ui::{InteractionDisabled, Pressed};
+use bevy::ui_widgets::{Activate, Button};
 
 use crate::color::{InsetBorder, InteractiveColor};
 use crate::prelude::InteractionSender;
 use crate::theme::UiTheme;
 
+/// The shortest interval, in seconds, between repeated activations of a held
+/// button, regardless of acceleration.
+const MIN_REPEAT_INTERVAL: f32 = 0.01;
+
+/// The largest number of repeated activations triggered on a held button in a
+/// single frame. Repeats beyond this are dropped, so a long frame does not
+/// cause a burst of activations.
+const MAX_REPEATS_PER_FRAME: u32 = 4;
+
 /// Builder for a button UI component.
 #[derive(Debug, Clone)]
 pub struct ButtonBuilder {
@@ -51,6 +61,75 @@ impl ButtonContent {
     }
 }
 
+/// A component that makes a button activate repeatedly while it is held down,
+/// such as for scrollbar arrows or numeric steppers.
+///
+/// Once the button has been held for the initial delay, an [`Activate`] event
+/// is triggered on it at each interval. The interval is multiplied by the
+/// acceleration after every repeat, down to the minimum interval, so holding
+/// the button longer repeats faster. Disabled buttons do not repeat. Once the
+/// button has repeated, releasing it does not activate it again.
+#[derive(Debug, Clone, Component)]
+pub struct ButtonRepeat {
+    /// The time, in seconds, that the button must be held before it starts
+    /// repeating.
+    pub delay: f32,
+
+    /// The initial time, in seconds, between repeated activations.
+    pub interval: f32,
+
+    /// The factor that the interval is multiplied by after each repeat. A
+    /// value of 1 repeats at a constant rate.
+    pub acceleration: f32,
+
+    /// The shortest time, in seconds, between repeated activations.
+    pub min_interval: f32,
+
+    /// The time until the next repeat, while the button is held.
+    countdown: Option<f32>,
+
+    /// The current time between repeats, while the button is held.
+    current_interval: f32,
+}
+
+impl ButtonRepeat {
+    /// Creates a new repeat configuration with the given initial delay and
+    /// constant interval, in seconds.
+    pub fn new(delay: f32, interval: f32) -> Self {
+        Self {
+            delay,
+            interval,
+            acceleration: 1.0,
+            min_interval: interval,
+            countdown: None,
+            current_interval: interval,
+        }
+    }
+
+    /// Speeds up the repeats by multiplying the interval by the given factor
+    /// after each repeat, down to the given minimum interval.
+    pub fn with_acceleration(mut self, acceleration: f32, min_interval: f32) -> Self {
+        self.acceleration = acceleration;
+        self.min_interval = min_interval;
+        self
+    }
+}
+
+impl Default for ButtonRepeat {
+    fn default() -> Self {
+        Self::new(0.4, 0.1).with_acceleration(0.9, 0.03)
+    }
+}
+
+/// A marker component for a button with a [`ButtonRepeat`] that is held down
+/// after it started repeating.
+///
+/// The button loses its [`Pressed`] component when it starts repeating, so
+/// that releasing it does not trigger another [`Activate`] event. This marker
+/// keeps it held, and shown as pressed, until it is released.
+#[derive(Debug, Component)]
+pub(crate) struct RepeatHeld;
+
 /// Creates a button UI component using the provided builder.
 pub fn button(builder: ButtonBuilder) -> impl Bundle {
     (
@@ -113,3 +192,73 @@ fn text(text: String, theme: &UiTheme) -> impl Bundle {
         )],
     )
 }
+
+/// Triggers repeated [`Activate`] events on held buttons with a
+/// [`ButtonRepeat`] component.
+#[allow(clippy::type_complexity)]
+pub(crate) fn repeat_held_buttons(
+    time: Res<Time>,
+    mut buttons: Query<(
+        Entity,
+        &mut ButtonRepeat,
+        Has<Pressed>,
+        Has<RepeatHeld>,
+        Has<InteractionDisabled>,
+    )>,
+    mut commands: Commands,
+) {
+    for (entity, mut repeat, pressed, held, disabled) in buttons.iter_mut() {
+        if !(pressed || held) || disabled {
+            if repeat.countdown.is_some() {
+                repeat.countdown = None;
+            }
+            if held {
+                commands.entity(entity).remove::<RepeatHeld>();
+            }
+            continue;
+        }
+
+        let Some(mut countdown) = repeat.countdown else {
+            repeat.countdown = Some(repeat.delay);
+            repeat.current_interval = repeat.interval;
+            continue;
+        };
+
+        countdown -= time.delta_secs();
+        let mut repeats = 0;
+        while countdown <= 0.0 && repeats < MAX_REPEATS_PER_FRAME {
+            commands.trigger(Activate { entity });
+            repeats += 1;
+
+            let interval = repeat.current_interval * repeat.acceleration;
+            repeat.current_interval = interval.max(repeat.min_interval).max(MIN_REPEAT_INTERVAL);
+            countdown += repeat.current_interval;
+        }
+
+        if countdown <= 0.0 {
+            countdown = repeat.current_interval;
+        }
+        repeat.countdown = Some(countdown);
+
+        if repeats > 0 && pressed {
+            commands
+                .entity(entity)
+                .insert(RepeatHeld)
+                .remove::<Pressed>();
+        }
+    }
+}
+
+/// Releases a held button that has started repeating when the pointer is
+/// released or the press is canceled.
+pub(crate) fn release_repeat_button<E>(
+    trigger: On<Pointer<E>>,
+    held: Query<(), With<RepeatHeld>>,
+    mut commands: Commands,
+) where
+    E: std::fmt::Debug + Clone + Reflect,
+{
+    if held.contains(trigger.entity) {
+        commands.entity(trigger.entity).remove::<RepeatHeld>();
+    }
+}
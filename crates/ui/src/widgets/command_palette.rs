@@ -0,0 +1,438 @@
+//! This module implements a command palette widget, which lets users search
+//! and run registered commands from the keyboard.
+//!
+//! The palette searches the commands of the [`CommandRegistry`], which are
+//! re-exported from [`crate::commands`] for convenience.
+//!
+//! The palette is opened with Ctrl+P or Ctrl+Shift+P and closed with Escape.
+//! Both shortcuts are bound to the `palette.toggle` command in the
+//! [`HotkeyRegistry`], so they can be rebound like any other hotkey.
+//! Typed text is fuzzy matched against the command names, and recently used
+//! commands are ranked first. The arrow keys move the selection, and Enter runs
+//! the selected command. The hotkeys bound to each command in the
+//! [`HotkeyRegistry`] are shown next to its name.
+
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+
+pub use crate::commands::{
+    CommandInvoked,
+    CommandRegistry,
+    PaletteCommand,
+    RegisterCommandExt,
+    fuzzy_score,
+};
+use crate::hotkeys::{BindHotkeyExt, Hotkey, HotkeyContext, HotkeyRegistry, dispatch_hotkeys};
+use crate::ime::{ImeCommit, ImeFocus, ImePreview};
+use crate::sounds::{PlayUiSound, UiSound};
+use crate::theme::UiTheme;
+use crate::widgets::button::{ButtonBuilder, ButtonContent, button};
+
+/// The default number of results shown in the command palette.
+pub const DEFAULT_MAX_RESULTS: usize = 12;
+
+/// A plugin that adds support for the command palette.
+pub(crate) struct CommandPalettePlugin;
+impl Plugin for CommandPalettePlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<CommandRegistry>()
+            .init_resource::<PaletteToggle>()
+            .add_systems(
+                Update,
+                (toggle_palette, type_query).chain().after(dispatch_hotkeys),
+            )
+            .register_command(
+                PaletteCommand::new("palette.toggle", "Toggle Command Palette"),
+                request_toggle,
+            )
+            .bind_hotkey(
+                "palette.toggle",
+                Hotkey::new(KeyCode::KeyP).ctrl(),
+                HotkeyContext::Global,
+            )
+            .bind_hotkey(
+                "palette.toggle",
+                Hotkey::new(KeyCode::KeyP).ctrl().shift(),
+                HotkeyContext::Global,
+            )
+            .add_observer(on_palette_add)
+            .add_observer(on_result_activate)
+            .add_observer(on_ime_commit);
+    }
+}
+
+/// A command palette widget.
+///
+/// The palette is hidden until it is opened with Ctrl+P or Ctrl+Shift+P. Only
+/// one command palette should exist at a time.
+#[derive(Debug, Component)]
+#[require(Node)]
+pub struct CommandPalette {
+    /// The theme for the palette.
+    theme: UiTheme,
+
+    /// The maximum number of results shown at once.
+    pub max_results: usize,
+
+    /// Whether the palette is open.
+    open: bool,
+
+    /// Whether the palette was opened this frame. Keyboard input on the frame
+    /// the palette is opened is ignored, so the shortcut is not typed.
+    just_opened: bool,
+
+    /// The text currently typed in the search field.
+    query: String,
+
+    /// The index of the selected result.
+    selected: usize,
+
+    /// The IDs of the commands currently shown as results.
+    results: Vec<String>,
+
+    /// The ID of the search field.
+    ///
+    /// This value is assigned when the palette is initialized.
+    input_id: Option<Entity>,
+
+    /// The ID of the search field text.
+    ///
+    /// This value is assigned when the palette is initialized.
+    input_text_id: Option<Entity>,
+
+    /// The ID of the result list.
+    ///
+    /// This value is assigned when the palette is initialized.
+    list_id: Option<Entity>,
+}
+
+impl CommandPalette {
+    /// Creates a new command palette with the given theme.
+    pub fn new(theme: UiTheme) -> Self {
+        Self {
+            theme,
+            max_results: DEFAULT_MAX_RESULTS,
+            open: false,
+            just_opened: false,
+            query: String::new(),
+            selected: 0,
+            results: Vec::new(),
+            input_id: None,
+            input_text_id: None,
+            list_id: None,
+        }
+    }
+
+    /// Returns true if the palette is open.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+/// A component on a result row of the command palette.
+#[derive(Debug, Component)]
+struct PaletteResult {
+    /// The command palette entity.
+    palette: Entity,
+
+    /// The ID of the command.
+    command: String,
+}
+
+/// Observer system that runs when a [`CommandPalette`] component is added.
+fn on_palette_add(
+    trigger: On<Add, CommandPalette>,
+    mut query: Query<(&mut Node, &mut CommandPalette)>,
+    mut commands: Commands,
+) {
+    let Ok((mut node, mut palette)) = query.get_mut(trigger.entity) else {
+        error!("CommandPalette added to entity without Node component");
+        return;
+    };
+
+    node.display = Display::None;
+    node.flex_direction = FlexDirection::Column;
+    node.row_gap = px(4.0);
+
+    let theme = &palette.theme;
+    commands
+        .entity(trigger.entity)
+        .insert(theme.outer_window.clone());
+
+    let input_text_id = commands
+        .spawn((Text::default(), theme.inner_window.text.clone()))
+        .id();
+
+    let input_id = commands
+        .spawn((
+            ChildOf(trigger.entity),
+            Node {
+                flex_direction: FlexDirection::Row,
+                min_height: px(theme.inner_window.text.font_size + 8.0),
+                ..default()
+            },
+            theme.inner_window.clone(),
+        ))
+        .add_children(&[input_text_id])
+        .with_child((ImePreview, theme.inner_window.text.clone()))
+        .id();
+
+    let list_id = commands
+        .spawn((
+            ChildOf(trigger.entity),
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: px(2.0),
+                ..default()
+            },
+        ))
+        .id();
+
+    palette.input_id = Some(input_id);
+    palette.input_text_id = Some(input_text_id);
+    palette.list_id = Some(list_id);
+}
+
+/// A resource that records whether the command palette should be opened or
+/// closed on the next update.
+#[derive(Debug, Default, Resource)]
+struct PaletteToggle(bool);
+
+/// Opens or closes the command palette on the next update, when run with its
+/// hotkey.
+fn request_toggle(mut toggle: ResMut<PaletteToggle>) {
+    toggle.0 = true;
+}
+
+/// Opens and closes command palettes based on keyboard input.
+fn toggle_palette(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut toggle: ResMut<PaletteToggle>,
+    text_focus: Query<Entity, With<ImeFocus>>,
+    registry: Res<CommandRegistry>,
+    hotkeys: Res<HotkeyRegistry>,
+    mut palettes: Query<(Entity, &mut CommandPalette, &mut Node)>,
+    mut sounds: MessageWriter<PlayUiSound>,
+    mut commands: Commands,
+) {
+    let shortcut = std::mem::take(&mut toggle.0);
+
+    for (entity, mut palette, mut node) in palettes.iter_mut() {
+        let open = if palette.open {
+            !shortcut && !keyboard_input.just_pressed(KeyCode::Escape)
+        } else {
+            // Do not steal the shortcut from another focused text widget.
+            let typing = text_focus
+                .iter()
+                .any(|focus| Some(focus) != palette.input_id);
+            shortcut && !typing
+        };
+
+        if open == palette.open {
+            continue;
+        }
+
+        palette.open = open;
+        palette.just_opened = open;
+        node.display = if open { Display::Flex } else { Display::None };
+
+        let Some(input_id) = palette.input_id else {
+            continue;
+        };
+
+        if open {
+            palette.query.clear();
+            palette.selected = 0;
+            commands.entity(input_id).insert(ImeFocus);
+            refresh_results(entity, &mut palette, &registry, &hotkeys, &mut commands);
+            sounds.write(PlayUiSound(UiSound::OpenMenu));
+        } else {
+            commands.entity(input_id).remove::<ImeFocus>();
+        }
+    }
+}
+
+/// Handles typing into open command palettes.
+fn type_query(
+    mut key_presses: MessageReader<KeyboardInput>,
+    mut registry: ResMut<CommandRegistry>,
+    hotkeys: Res<HotkeyRegistry>,
+    mut palettes: Query<(Entity, &mut CommandPalette, &mut Node)>,
+    mut texts: Query<&mut Text>,
+    mut commands: Commands,
+) {
+    let key_presses = key_presses
+        .read()
+        .filter(|ev| ev.state == ButtonState::Pressed)
+        .collect::<Vec<_>>();
+
+    for (entity, mut palette, mut node) in palettes.iter_mut() {
+        if !palette.open {
+            continue;
+        }
+
+        if palette.just_opened {
+            palette.just_opened = false;
+            continue;
+        }
+
+        let mut changed = false;
+        let mut run = None;
+        for ev in &key_presses {
+            match &ev.logical_key {
+                Key::Enter => {
+                    run = palette.results.get(palette.selected).cloned();
+                    break;
+                }
+                Key::ArrowDown if !palette.results.is_empty() => {
+                    palette.selected = (palette.selected + 1) % palette.results.len();
+                    changed = true;
+                }
+                Key::ArrowUp if !palette.results.is_empty() => {
+                    let len = palette.results.len();
+                    palette.selected = (palette.selected + len - 1) % len;
+                    changed = true;
+                }
+                Key::Backspace => {
+                    palette.query.pop();
+                    palette.selected = 0;
+                    changed = true;
+                }
+                Key::Space => {
+                    palette.query.push(' ');
+                    changed = true;
+                }
+                Key::Character(chars) => {
+                    palette
+                        .query
+                        .extend(chars.chars().filter(|c| !c.is_control()));
+                    palette.selected = 0;
+                    changed = true;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(id) = run {
+            close_palette(&mut palette, &mut node, &mut commands);
+            registry.run(&id, &mut commands);
+            continue;
+        }
+
+        if changed {
+            update_query_text(&palette, &mut texts);
+            refresh_results(entity, &mut palette, &registry, &hotkeys, &mut commands);
+        }
+    }
+}
+
+/// Appends text committed by the IME to the palette it was typed into.
+fn on_ime_commit(
+    trigger: On<ImeCommit>,
+    registry: Res<CommandRegistry>,
+    hotkeys: Res<HotkeyRegistry>,
+    mut palettes: Query<(Entity, &mut CommandPalette)>,
+    mut texts: Query<&mut Text>,
+    mut commands: Commands,
+) {
+    for (entity, mut palette) in palettes.iter_mut() {
+        if palette.open && palette.input_id == Some(trigger.entity) {
+            palette.query.push_str(&trigger.value);
+            palette.selected = 0;
+            update_query_text(&palette, &mut texts);
+            refresh_results(entity, &mut palette, &registry, &hotkeys, &mut commands);
+        }
+    }
+}
+
+/// Runs a command when its result row is clicked.
+fn on_result_activate(
+    trigger: On<Activate>,
+    results: Query<&PaletteResult>,
+    mut registry: ResMut<CommandRegistry>,
+    mut palettes: Query<(&mut CommandPalette, &mut Node)>,
+    mut commands: Commands,
+) {
+    let Ok(result) = results.get(trigger.event().entity) else {
+        return;
+    };
+
+    if let Ok((mut palette, mut node)) = palettes.get_mut(result.palette) {
+        close_palette(&mut palette, &mut node, &mut commands);
+    }
+
+    registry.run(&result.command, &mut commands);
+}
+
+/// Closes a command palette, removing the text input focus from it.
+fn close_palette(palette: &mut CommandPalette, node: &mut Node, commands: &mut Commands) {
+    palette.open = false;
+    node.display = Display::None;
+
+    if let Some(input_id) = palette.input_id {
+        commands.entity(input_id).remove::<ImeFocus>();
+    }
+}
+
+/// Updates the displayed search text of a palette.
+fn update_query_text(palette: &CommandPalette, texts: &mut Query<&mut Text>) {
+    let Some(input_text_id) = palette.input_text_id else {
+        return;
+    };
+
+    if let Ok(mut text) = texts.get_mut(input_text_id) {
+        if text.0 != palette.query {
+            text.0.clone_from(&palette.query);
+        }
+    }
+}
+
+/// Searches the registry for the current query and rebuilds the result rows of
+/// a palette.
+fn refresh_results(
+    entity: Entity,
+    palette: &mut CommandPalette,
+    registry: &CommandRegistry,
+    hotkeys: &HotkeyRegistry,
+    commands: &mut Commands,
+) {
+    let Some(list_id) = palette.list_id else {
+        return;
+    };
+
+    let results = registry
+        .search(&palette.query)
+        .into_iter()
+        .take(palette.max_results)
+        .collect::<Vec<_>>();
+
+    palette.results = results.iter().map(|command| command.id.clone()).collect();
+    palette.selected = palette.selected.min(results.len().saturating_sub(1));
+
+    commands.entity(list_id).despawn_children();
+    for (index, command) in results.into_iter().enumerate() {
+        let marker = if index == palette.selected { "> " } else { "" };
+        let label = match hotkeys
+            .hint(&command.id)
+            .or_else(|| command.shortcut.clone())
+        {
+            Some(shortcut) => format!("{marker}{}  ({shortcut})", command.name),
+            None => format!("{marker}{}", command.name),
+        };
+
+        commands.spawn((
+            ChildOf(list_id),
+            PaletteResult {
+                palette: entity,
+                command: command.id.clone(),
+            },
+            button(ButtonBuilder {
+                node: Node::default(),
+                content: ButtonContent::text(label),
+                theme: palette.theme.clone(),
+            }),
+        ));
+    }
+}
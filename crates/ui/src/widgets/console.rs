@@ -0,0 +1,357 @@
+//! This module implements a chat console widget, which shows a scrollback of
+//! messages and an input line for typing messages or commands.
+//!
+//! Messages are added to the console by triggering a [`ConsolePrint`] event on
+//! the console entity. When the user submits a line, a [`ConsoleSubmit`] event
+//! is triggered on the console entity. Pressing Ctrl+V while the console is
+//! open pastes the first line of the [`Clipboard`] into the input line.
+
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+
+use crate::clipboard::Clipboard;
+use crate::ime::{ImeCommit, ImeFocus, ImePreview};
+use crate::theme::UiTheme;
+
+/// The default number of lines kept in the console scrollback.
+pub const DEFAULT_MAX_LINES: usize = 100;
+
+/// A plugin that adds support for chat consoles.
+pub(crate) struct ConsolePlugin;
+impl Plugin for ConsolePlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_systems(Update, (toggle_console, type_input).chain())
+            .add_observer(on_console_add)
+            .add_observer(on_console_print)
+            .add_observer(on_ime_commit);
+    }
+}
+
+/// A chat console widget.
+///
+/// The scrollback is always visible, while the input line is only shown while
+/// the console is open. The console is opened with its toggle key and closed
+/// with Escape, or by submitting a line with Enter.
+#[derive(Debug, Component)]
+#[require(Node)]
+pub struct ChatConsole {
+    /// The theme for the console.
+    theme: UiTheme,
+
+    /// The key that opens the input line.
+    pub toggle_key: KeyCode,
+
+    /// The maximum number of lines kept in the scrollback. Older lines are
+    /// removed once this limit is reached.
+    pub max_lines: usize,
+
+    /// Whether the input line is open.
+    open: bool,
+
+    /// Whether the console was opened this frame. Keyboard input on the frame
+    /// the console is opened is ignored, so the toggle key is not typed.
+    just_opened: bool,
+
+    /// The text currently typed in the input line.
+    input: String,
+
+    /// The ID of the scrollback panel.
+    ///
+    /// This value is assigned when the console is initialized.
+    log_id: Option<Entity>,
+
+    /// The ID of the input line container.
+    ///
+    /// This value is assigned when the console is initialized.
+    input_id: Option<Entity>,
+
+    /// The ID of the input line text.
+    ///
+    /// This value is assigned when the console is initialized.
+    input_text_id: Option<Entity>,
+}
+
+impl ChatConsole {
+    /// Creates a new chat console with the given theme, opened with the Enter
+    /// key.
+    pub fn new(theme: UiTheme) -> Self {
+        Self {
+            theme,
+            toggle_key: KeyCode::Enter,
+            max_lines: DEFAULT_MAX_LINES,
+            open: false,
+            just_opened: false,
+            input: String::new(),
+            log_id: None,
+            input_id: None,
+            input_text_id: None,
+        }
+    }
+
+    /// Sets the key that opens the input line.
+    pub fn with_toggle_key(mut self, key: KeyCode) -> Self {
+        self.toggle_key = key;
+        self
+    }
+
+    /// Returns true if the input line is open.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+/// An event that adds a line of text to a [`ChatConsole`].
+#[derive(Debug, EntityEvent)]
+pub struct ConsolePrint {
+    /// The console entity.
+    pub entity: Entity,
+
+    /// The text to add.
+    pub text: String,
+}
+
+/// An event that is triggered on a [`ChatConsole`] when the user submits a
+/// line of text.
+#[derive(Debug, EntityEvent)]
+pub struct ConsoleSubmit {
+    /// The console entity.
+    pub entity: Entity,
+
+    /// The submitted text.
+    pub text: String,
+}
+
+/// Observer system that runs when a [`ChatConsole`] component is added.
+fn on_console_add(
+    trigger: On<Add, ChatConsole>,
+    mut query: Query<(&mut Node, &mut ChatConsole)>,
+    mut commands: Commands,
+) {
+    let Ok((mut node, mut console)) = query.get_mut(trigger.entity) else {
+        error!("ChatConsole added to entity without Node component");
+        return;
+    };
+
+    node.flex_direction = FlexDirection::Column;
+    node.row_gap = px(4.0);
+
+    let theme = &console.theme.console;
+
+    let log_id = commands
+        .spawn((
+            ChildOf(trigger.entity),
+            Node {
+                flex_direction: FlexDirection::Column,
+                flex_grow: 1.0,
+                overflow: Overflow::scroll_y(),
+                scrollbar_width: 4.0,
+                ..default()
+            },
+            theme.log.clone(),
+        ))
+        .id();
+
+    let input_text_id = commands
+        .spawn((Text::default(), theme.input.text.clone()))
+        .id();
+
+    let input_id = commands
+        .spawn((
+            ChildOf(trigger.entity),
+            Node {
+                display: Display::None,
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+            theme.input.clone(),
+        ))
+        .add_children(&[input_text_id])
+        .with_child((ImePreview, theme.input.text.clone()))
+        .id();
+
+    console.log_id = Some(log_id);
+    console.input_id = Some(input_id);
+    console.input_text_id = Some(input_text_id);
+}
+
+/// Adds a line of text to the console scrollback.
+fn on_console_print(
+    trigger: On<ConsolePrint>,
+    consoles: Query<&ChatConsole>,
+    mut logs: Query<(Option<&Children>, &mut ScrollPosition)>,
+    mut commands: Commands,
+) {
+    let Ok(console) = consoles.get(trigger.entity) else {
+        return;
+    };
+
+    let Some(log_id) = console.log_id else {
+        return;
+    };
+
+    let Ok((lines, mut scroll)) = logs.get_mut(log_id) else {
+        return;
+    };
+
+    if let Some(lines) = lines {
+        let excess = (lines.len() + 1).saturating_sub(console.max_lines);
+        for line in lines.iter().take(excess) {
+            commands.entity(line).despawn();
+        }
+    }
+
+    commands.spawn((
+        ChildOf(log_id),
+        Text::new(trigger.text.clone()),
+        console.theme.console.log.text.clone(),
+    ));
+
+    // Scroll to the newest line. The position is clamped during layout.
+    scroll.y = f32::MAX;
+}
+
+/// Opens and closes consoles based on keyboard input.
+fn toggle_console(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut consoles: Query<&mut ChatConsole>,
+    mut nodes: Query<&mut Node>,
+    mut commands: Commands,
+) {
+    for mut console in consoles.iter_mut() {
+        let open = if console.open {
+            !keyboard_input.just_pressed(KeyCode::Escape)
+        } else {
+            keyboard_input.just_pressed(console.toggle_key)
+        };
+
+        if open != console.open {
+            console.just_opened = open;
+            set_open(&mut console, open, &mut nodes, &mut commands);
+        }
+    }
+}
+
+/// Handles typing into open consoles.
+fn type_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut key_presses: MessageReader<KeyboardInput>,
+    mut clipboard: ResMut<Clipboard>,
+    mut consoles: Query<(Entity, &mut ChatConsole)>,
+    mut nodes: Query<&mut Node>,
+    mut texts: Query<&mut Text>,
+    mut commands: Commands,
+) {
+    let key_presses = key_presses
+        .read()
+        .filter(|ev| ev.state == ButtonState::Pressed)
+        .collect::<Vec<_>>();
+
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+
+    for (entity, mut console) in consoles.iter_mut() {
+        if !console.open {
+            continue;
+        }
+
+        if console.just_opened {
+            console.just_opened = false;
+            continue;
+        }
+
+        for ev in &key_presses {
+            if ctrl {
+                if ev.key_code == KeyCode::KeyV {
+                    match clipboard.paste() {
+                        Ok(Some(text)) => {
+                            let line = text.lines().next().unwrap_or_default();
+                            console
+                                .input
+                                .extend(line.chars().filter(|c| !c.is_control()));
+                        }
+                        Ok(None) => {}
+                        Err(e) => error!("Failed to paste from clipboard: {e}"),
+                    }
+                }
+                continue;
+            }
+
+            match &ev.logical_key {
+                Key::Enter => {
+                    let text = std::mem::take(&mut console.input);
+                    if !text.trim().is_empty() {
+                        commands.trigger(ConsoleSubmit { entity, text });
+                    }
+                    set_open(&mut console, false, &mut nodes, &mut commands);
+                    break;
+                }
+                Key::Backspace => {
+                    console.input.pop();
+                }
+                Key::Space => {
+                    console.input.push(' ');
+                }
+                Key::Character(chars) => {
+                    console
+                        .input
+                        .extend(chars.chars().filter(|c| !c.is_control()));
+                }
+                _ => {}
+            }
+        }
+
+        update_input_text(&console, &mut texts);
+    }
+}
+
+/// Appends text committed by the IME to the console it was typed into.
+fn on_ime_commit(
+    trigger: On<ImeCommit>,
+    mut consoles: Query<&mut ChatConsole>,
+    mut texts: Query<&mut Text>,
+) {
+    for mut console in consoles.iter_mut() {
+        if console.open && console.input_id == Some(trigger.entity) {
+            console.input.push_str(&trigger.value);
+            update_input_text(&console, &mut texts);
+        }
+    }
+}
+
+/// Shows or hides the input line of a console, moving the text input focus
+/// with it.
+fn set_open(
+    console: &mut ChatConsole,
+    open: bool,
+    nodes: &mut Query<&mut Node>,
+    commands: &mut Commands,
+) {
+    console.open = open;
+
+    let Some(input_id) = console.input_id else {
+        return;
+    };
+
+    if let Ok(mut node) = nodes.get_mut(input_id) {
+        node.display = if open { Display::Flex } else { Display::None };
+    }
+
+    if open {
+        commands.entity(input_id).insert(ImeFocus);
+    } else {
+        commands.entity(input_id).remove::<ImeFocus>();
+    }
+}
+
+/// Updates the displayed input line text of a console.
+fn update_input_text(console: &ChatConsole, texts: &mut Query<&mut Text>) {
+    let Some(input_text_id) = console.input_text_id else {
+        return;
+    };
+
+    if let Ok(mut text) = texts.get_mut(input_text_id) {
+        if text.0 != console.input {
+            text.0.clone_from(&console.input);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! This module implements the validation framework for form widgets.
+//!
+//! A form is any container with a [`Form`] component. Each input field within
+//! the form stores its current value in a [`FieldValue`] component, and may
+//! have a list of [`Validators`] attached. The validity of each field is
+//! stored in its [`FieldValidity`] component, and the form aggregates these
+//! into its [`FormValid`] component. Buttons marked with [`FormSubmit`] are
+//! automatically disabled while the form is invalid.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy::ui::InteractionDisabled;
+use regex::Regex;
+
+use crate::sounds::{PlayUiSound, UiSound};
+use crate::theme::UiTheme;
+
+/// A plugin that adds form validation support to the UI.
+pub struct FormPlugin;
+impl Plugin for FormPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_systems(
+            Update,
+            (validate_fields, update_error_text, update_forms)
+                .chain()
+                .in_set(FormSystems::Validate),
+        );
+    }
+}
+
+/// System sets for form-related systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
+pub enum FormSystems {
+    /// Validates changed fields and updates the aggregate form state.
+    Validate,
+}
+
+/// A marker component for a form container. All fields that are descendants of
+/// this entity are considered part of the form.
+#[derive(Debug, Default, Component)]
+#[require(Node, FormValid)]
+pub struct Form;
+
+/// Whether all fields within a [`Form`] are currently valid.
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct FormValid(pub bool);
+
+impl Default for FormValid {
+    fn default() -> Self {
+        FormValid(true)
+    }
+}
+
+/// A marker component for a button that submits a [`Form`]. The button is
+/// disabled while the form it belongs to is invalid.
+#[derive(Debug, Default, Component)]
+pub struct FormSubmit;
+
+/// The current value of a form field, as text.
+///
+/// Input widgets update this component when their contents change.
+#[derive(Debug, Default, Component, Clone, PartialEq, Eq, Deref, DerefMut)]
+#[require(FieldValidity)]
+pub struct FieldValue(pub String);
+
+/// The validation state of a form field.
+#[derive(Debug, Default, Component, Clone, PartialEq, Eq)]
+pub enum FieldValidity {
+    /// The field value is valid.
+    #[default]
+    Valid,
+
+    /// The field value is invalid, with the given error message.
+    Invalid(String),
+}
+
+impl FieldValidity {
+    /// Returns true if the field is valid.
+    pub fn is_valid(&self) -> bool {
+        matches!(self, FieldValidity::Valid)
+    }
+}
+
+/// The list of validators applied to a form field, in order. The first failing
+/// validator determines the error message.
+#[derive(Debug, Default, Component, Clone)]
+#[require(FieldValue)]
+pub struct Validators(pub Vec<Validator>);
+
+/// A single validation rule for a form field.
+#[derive(Clone)]
+pub enum Validator {
+    /// The value must not be empty or whitespace.
+    NonEmpty,
+
+    /// The value must match the given regular expression. The string is the
+    /// error message shown when it does not.
+    Pattern(Regex, String),
+
+    /// The value must be a number within the given inclusive range.
+    Range {
+        /// The minimum allowed value.
+        min: f64,
+
+        /// The maximum allowed value.
+        max: f64,
+    },
+
+    /// The value must not be any of the given existing values. This is used to
+    /// ensure, for example, that an asset path is unique within its module.
+    Unique(Arc<HashSet<String>>),
+
+    /// A custom validation function, returning an error message on failure.
+    Custom(Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>),
+}
+
+impl Validator {
+    /// Creates a validator that ensures an asset path is unique among the
+    /// given existing paths within a module.
+    pub fn unique_path_in_module<I, S>(existing_paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Validator::Unique(Arc::new(
+            existing_paths.into_iter().map(Into::into).collect(),
+        ))
+    }
+
+    /// Validates the given value, returning an error message on failure.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            Validator::NonEmpty => {
+                if value.trim().is_empty() {
+                    return Err("This field is required.".to_string());
+                }
+            }
+            Validator::Pattern(regex, message) => {
+                if !regex.is_match(value) {
+                    return Err(message.clone());
+                }
+            }
+            Validator::Range { min, max } => {
+                let Ok(number) = value.trim().parse::<f64>() else {
+                    return Err("Must be a number.".to_string());
+                };
+
+                if number < *min || number > *max {
+                    return Err(format!("Must be between {min} and {max}."));
+                }
+            }
+            Validator::Unique(existing) => {
+                if existing.contains(value) {
+                    return Err(format!("\"{value}\" already exists."));
+                }
+            }
+            Validator::Custom(validate) => validate(value)?,
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for Validator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Validator::NonEmpty => write!(f, "NonEmpty"),
+            Validator::Pattern(regex, _) => write!(f, "Pattern({})", regex.as_str()),
+            Validator::Range { min, max } => write!(f, "Range({min} ..= {max})"),
+            Validator::Unique(existing) => write!(f, "Unique({} values)", existing.len()),
+            Validator::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+/// A text node that displays the validation error of a form field. The text is
+/// hidden while the field is valid.
+#[derive(Debug, Component)]
+#[require(Text)]
+pub struct FieldErrorText {
+    /// The form field this error text belongs to.
+    pub field: Entity,
+}
+
+/// Creates an inline error text node for the given form field, styled using
+/// the form theme.
+pub fn field_error_text(field: Entity, theme: &UiTheme) -> impl Bundle {
+    (
+        FieldErrorText { field },
+        Node {
+            display: Display::None,
+            ..default()
+        },
+        theme.form.error_text.clone(),
+    )
+}
+
+/// Validates all fields whose values or validators have changed.
+///
+/// The error sound is played when a field becomes invalid.
+fn validate_fields(
+    mut fields: Query<
+        (&FieldValue, &Validators, &mut FieldValidity),
+        Or<(Changed<FieldValue>, Changed<Validators>)>,
+    >,
+    mut sounds: MessageWriter<PlayUiSound>,
+) {
+    for (value, validators, mut validity) in fields.iter_mut() {
+        let result = validators
+            .0
+            .iter()
+            .try_for_each(|validator| validator.validate(value));
+
+        let new_validity = match result {
+            Ok(()) => FieldValidity::Valid,
+            Err(message) => FieldValidity::Invalid(message),
+        };
+
+        let became_invalid = validity.is_valid() && !new_validity.is_valid();
+        if validity.set_if_neq(new_validity) && became_invalid {
+            sounds.write(PlayUiSound(UiSound::Error));
+        }
+    }
+}
+
+/// Updates the inline error text of fields whose validity has changed.
+fn update_error_text(
+    fields: Query<Ref<FieldValidity>>,
+    mut errors: Query<(Ref<FieldErrorText>, &mut Text, &mut Node)>,
+) {
+    for (error, mut text, node) in errors.iter_mut() {
+        let Ok(validity) = fields.get(error.field) else {
+            continue;
+        };
+
+        if !validity.is_changed() && !error.is_added() {
+            continue;
+        }
+
+        let mut display = node.map_unchanged(|node| &mut node.display);
+        match &*validity {
+            FieldValidity::Valid => {
+                display.set_if_neq(Display::None);
+            }
+            FieldValidity::Invalid(message) => {
+                display.set_if_neq(Display::Flex);
+                text.set_if_neq(Text(message.clone()));
+            }
+        }
+    }
+}
+
+/// Aggregates field validity into each form, and enables or disables the
+/// form's submit buttons.
+fn update_forms(
+    mut forms: Query<(Entity, &mut FormValid), With<Form>>,
+    children: Query<&Children>,
+    fields: Query<&FieldValidity>,
+    submits: Query<Has<InteractionDisabled>, With<FormSubmit>>,
+    mut commands: Commands,
+) {
+    for (form, mut form_valid) in forms.iter_mut() {
+        let valid = children
+            .iter_descendants(form)
+            .filter_map(|entity| fields.get(entity).ok())
+            .all(FieldValidity::is_valid);
+
+        form_valid.set_if_neq(FormValid(valid));
+
+        for entity in children.iter_descendants(form) {
+            let Ok(disabled) = submits.get(entity) else {
+                continue;
+            };
+
+            match (valid, disabled) {
+                (true, true) => {
+                    commands.entity(entity).remove::<InteractionDisabled>();
+                }
+                (false, false) => {
+                    commands.entity(entity).insert(InteractionDisabled);
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -1,25 +1,235 @@
 //! This module implements a widget that previews images in a grid layout. This
 //! can be used for thing such as a file explorer or asset explorer.
+//!
+//! The size of the cells can be changed at runtime with Ctrl+wheel or with the
+//! zoom slider above the grid, and the grid reflows its columns to match. At
+//! the largest zoom level, cells with a large icon request it in place of their
+//! regular icon.
+//!
+//! Hovering a cell for a moment shows a magnifier popup near the cursor, with a
+//! larger version of its image, its label and the dimensions of the image. The
+//! popup is placed in the overlay, and is dismissed once the cell is no longer
+//! hovered.
+//!
+//! Cells with a [`GridIcon::Deferred`] icon show the spinning loading
+//! placeholder of the theme until their icon has loaded, or the error icon of
+//! the theme if it fails to load.
+//!
+//! Grids created with [`GridPreview::with_sort`] show a sort dropdown next to
+//! the zoom slider, and keep their cells ordered by the name, type or modified
+//! date of the cells. Cells are reordered in place when the sort changes.
+//!
+//! The view button above the grid switches between the [`GridView::Icons`] and
+//! [`GridView::Details`] views. The details view lists the cells as rows, with
+//! a small icon, the label, the type and the modified date of each cell. The
+//! sort order is shared by both views, and the cell at the top of the view is
+//! scrolled back into place after switching.
+//!
+//! Clicking a cell selects it. Ctrl-clicking adds or removes a cell from the
+//! selection, shift-clicking selects the range of cells from the last clicked
+//! cell, and dragging over the empty space of the grid selects the cells within
+//! the dragged box. The selection of each grid is stored in its
+//! [`GridSelection`] component, and every change triggers a
+//! [`GridCellsSelected`] event on the grid. Double-clicking a cell triggers a
+//! [`GridCellActivated`] event instead.
+//!
+//! Triggering [`RenameGridCell`] on a grid, or pressing F2 while hovering a
+//! grid with a single selected cell, swaps the label of the cell for a text
+//! input. Pressing Enter triggers a [`GridCellRenamed`] event on the grid,
+//! while pressing Escape or clicking elsewhere cancels the rename. The shortcut
+//! is bound to the `grid.rename` command in the
+//! [`HotkeyRegistry`](crate::hotkeys::HotkeyRegistry), so it can be rebound.
+//!
+//! Cells are identified by their index, which is their position in the order
+//! they were added in, or their index in the provider for grids with a
+//! provider.
+//!
+//! Grid previews with a [`Placeholders`] component show the empty placeholder
+//! while they have no cells, and the error placeholder while it is set.
+//!
+//! Grids created with [`GridPreview::with_provider`] request their cells from a
+//! callback as they scroll into view, rather than spawning every cell up front.
+//! Only the cells in view have entities, which are reused for other cells as
+//! the grid scrolls. Such grids are not sorted in place; the provider should
+//! supply its cells in the order given by [`GridPreview::sort`].
 
+use std::cmp::Ordering;
+
+use bevy::app::Propagate;
+use bevy::asset::LoadState;
+use bevy::picking::hover::{HoverMap, Hovered};
+use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
+use bevy::ui_widgets::{
+    Activate,
+    Slider,
+    SliderRange,
+    SliderStep,
+    SliderThumb,
+    SliderValue,
+    TrackClick,
+    ValueChange,
+};
+use bevy::window::PrimaryWindow;
 
+use crate::accessibility::AccessibilityOptions;
+use crate::clipboard::ClipboardText;
+use crate::color::InteractiveColor;
+use crate::commands::{PaletteCommand, RegisterCommandExt};
+use crate::hotkeys::{BindHotkeyExt, Hotkey, HotkeyContext};
+use crate::ime::ImeFocus;
+use crate::interaction::InteractionReceiver;
+use crate::label::LabelOverflow;
+use crate::menus::layers::UiLayer;
+use crate::menus::overlay::OverlayRoot;
 use crate::prelude::InteractionSender;
-use crate::theme::UiTheme;
+use crate::scroll::{
+    BindVirtualItem,
+    ItemProvider,
+    Scroll,
+    SmoothScrollPosition,
+    VirtualItem,
+    VirtualScroll,
+};
+use crate::sounds::{PlayUiSound, UiSound};
+use crate::theme::{FontTheme, GridPreviewTheme, UiTheme};
+use crate::widgets::button::{ButtonBuilder, ButtonContent, button};
+use crate::widgets::placeholder::{Placeholders, sync_placeholder};
+use crate::widgets::text_input::{TextInput, TextInputSubmit};
+
+/// The width of the zoom slider.
+const SLIDER_WIDTH: f32 = 120.0;
+
+/// The size of the thumb of the zoom slider.
+const THUMB_SIZE: f32 = 12.0;
+
+/// The time, in seconds, that a cell must be hovered before its magnifier
+/// popup is shown.
+const MAGNIFIER_DELAY: f32 = 0.6;
+
+/// The size of the image shown in the magnifier popup.
+const MAGNIFIER_SIZE: f32 = 512.0;
+
+/// The approximate height of the labels below the image of the magnifier
+/// popup, used to keep the popup within the window.
+const MAGNIFIER_LABEL_HEIGHT: f32 = 48.0;
+
+/// The distance between the cursor and the magnifier popup.
+const MAGNIFIER_OFFSET: f32 = 16.0;
+
+/// The speed, in radians per second, at which the loading placeholder spins.
+const SPINNER_SPEED: f32 = 4.0;
+
+/// The width of the type and modified date columns of the details view.
+const DETAIL_WIDTH: f32 = 120.0;
+
+/// The number of frames that a grid waits after its view changes before
+/// scrolling back to the cell that was at the top of the view, so that the
+/// cells have been laid out and measured in the new view.
+const ANCHOR_DELAY: u8 = 2;
+
+/// The time, in seconds, within which a second click on the same cell counts
+/// as a double-click.
+const DOUBLE_CLICK_TIME: f32 = 0.4;
+
+/// A plugin that adds support for grid previews.
+pub(crate) struct GridPreviewPlugin;
+impl Plugin for GridPreviewPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<GridZoomSettings>()
+            .init_resource::<GridMagnifier>()
+            .add_systems(
+                Update,
+                (
+                    (apply_grid_view, apply_grid_zoom).chain(),
+                    restore_grid_anchors,
+                    poll_deferred_icons,
+                    update_grid_magnifier,
+                    update_grid_placeholders,
+                    sort_grid_cells,
+                    show_selected_cells,
+                ),
+            )
+            .register_command(
+                PaletteCommand::new("grid.rename", "Rename Selected Cell"),
+                rename_hovered_cell,
+            )
+            .bind_hotkey(
+                "grid.rename",
+                Hotkey::new(KeyCode::F2),
+                HotkeyContext::Global,
+            )
+            .add_observer(on_grid_add)
+            .add_observer(on_grid_scroll)
+            .add_observer(on_bind_grid_cell)
+            .add_observer(on_zoom_slider_change)
+            .add_observer(on_view_toggle_activate)
+            .add_observer(on_sort_dropdown_activate)
+            .add_observer(on_sort_option_activate)
+            .add_observer(on_cell_click)
+            .add_observer(on_panel_click)
+            .add_observer(on_box_select_start)
+            .add_observer(on_box_select_drag)
+            .add_observer(on_box_select_end)
+            .add_observer(on_rename_cell)
+            .add_observer(on_rename_submit)
+            .add_observer(on_rename_blur);
+    }
+}
 
 /// A builder for a grid cell node. This can be used when initializing a
 /// [`GridPreview`] with a set of initial cells.
 #[derive(Debug, Clone)]
 pub struct GridNodeBuilder {
     /// The image to display in the grid cell.
-    pub icon: Handle<Image>,
+    pub icon: GridIcon,
+
+    /// The asset path of a larger thumbnail tier of the image, for cells that
+    /// are too large for the regular icon. It is only loaded once the grid is
+    /// zoomed to [`GridZoom::Large`], and should stay small, as every visible
+    /// cell loads its own.
+    pub large_icon: Option<String>,
 
     /// The label to display below the image.
     pub label: String,
+
+    /// Optional text that is copied to the clipboard when Ctrl+C is pressed
+    /// while the cell is hovered, such as the ID of the asset it represents.
+    pub copy_text: Option<String>,
+
+    /// How the label behaves when it is wider than the cell. If `None`, the
+    /// overflow of the cell theme is used.
+    pub overflow: Option<LabelOverflow>,
+
+    /// The type of the item shown in the cell, such as an asset type, used
+    /// when sorting by [`GridSort::Type`].
+    pub kind: Option<String>,
+
+    /// The time that the item shown in the cell was last modified, as a Unix
+    /// timestamp, used when sorting by [`GridSort::Modified`].
+    pub modified: Option<i64>,
+}
+
+/// The icon of a grid cell.
+#[derive(Debug, Clone)]
+pub enum GridIcon {
+    /// An icon that is shown right away, such as one that is already loaded.
+    Ready(Handle<Image>),
+
+    /// An icon that may still be loading. The cell shows a loading placeholder
+    /// until the icon has loaded, or an error icon if it fails to load.
+    Deferred(Handle<Image>),
+}
+
+impl From<Handle<Image>> for GridIcon {
+    fn from(handle: Handle<Image>) -> Self {
+        GridIcon::Ready(handle)
+    }
 }
 
 /// A widget that displays a grid preview of images. Useful for asset explorers.
 #[derive(Debug, Component)]
-#[require(Node)]
+#[require(Node, GridSelection)]
 pub struct GridPreview {
     /// The theme for the grid preview.
     theme: UiTheme,
@@ -32,6 +242,35 @@ pub struct GridPreview {
     /// An optional list of initial cells to populate the grid with. This value
     /// will be discarded after the grid is initialized.
     init_cells: Option<Vec<GridNodeBuilder>>,
+
+    /// The current zoom level of the grid.
+    zoom: GridZoom,
+
+    /// The key that the zoom level is stored under in [`GridZoomSettings`].
+    zoom_key: Option<String>,
+
+    /// The ID of the zoom slider.
+    ///
+    /// This value is assigned when the preview is initialized.
+    slider_id: Option<Entity>,
+
+    /// The order of the cells, or `None` if they are kept in the order they
+    /// were added.
+    sort: Option<GridSort>,
+
+    /// The callback that supplies the cells of a virtualized grid.
+    provider: Option<ItemProvider<GridNodeBuilder>>,
+
+    /// The initial number of cells supplied by the provider. This value is
+    /// only used when the grid is initialized.
+    init_count: usize,
+
+    /// How the cells of the grid are shown.
+    view: GridView,
+
+    /// The view that the cells are currently laid out in, which differs from
+    /// `view` until the new view has been applied.
+    shown_view: GridView,
 }
 
 impl GridPreview {
@@ -41,6 +280,14 @@ impl GridPreview {
             theme,
             panel_id: None,
             init_cells: None,
+            zoom: GridZoom::default(),
+            zoom_key: None,
+            slider_id: None,
+            sort: None,
+            provider: None,
+            init_count: 0,
+            view: GridView::default(),
+            shown_view: GridView::default(),
         }
     }
 
@@ -48,16 +295,594 @@ impl GridPreview {
     /// initial cells.
     pub fn with_cells(theme: UiTheme, cells: Vec<GridNodeBuilder>) -> Self {
         Self {
-            theme,
-            panel_id: None,
             init_cells: Some(cells),
+            ..Self::new(theme)
+        }
+    }
+
+    /// Creates a new grid preview whose cells are requested from the given
+    /// provider as they scroll into view.
+    ///
+    /// The number of cells can be changed later through the [`VirtualScroll`]
+    /// of the panel of the grid, which should also be refreshed when the cells
+    /// change.
+    pub fn with_provider(
+        theme: UiTheme,
+        item_count: usize,
+        provider: ItemProvider<GridNodeBuilder>,
+    ) -> Self {
+        Self {
+            provider: Some(provider),
+            init_count: item_count,
+            ..Self::new(theme)
+        }
+    }
+
+    /// Gets the panel that holds the cells of the grid. For grids with a
+    /// provider, this is the entity with the [`VirtualScroll`] component.
+    ///
+    /// If the grid has not been initialized yet, this will return `None`.
+    pub fn panel(&self) -> Option<Entity> {
+        self.panel_id
+    }
+
+    /// Stores the zoom level of this grid in [`GridZoomSettings`] under the
+    /// given key, such as the name of the tool showing it, and restores it from
+    /// there when the grid is created.
+    pub fn with_zoom_key(mut self, key: impl Into<String>) -> Self {
+        self.zoom_key = Some(key.into());
+        self
+    }
+
+    /// Orders the cells of this grid by the given sort, and shows a dropdown
+    /// to change it.
+    pub fn with_sort(mut self, sort: GridSort) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    /// Gets the current order of the cells, or `None` if they are kept in the
+    /// order they were added.
+    pub fn sort(&self) -> Option<GridSort> {
+        self.sort
+    }
+
+    /// Sets the order of the cells. The cells are reordered in place.
+    pub fn set_sort(&mut self, sort: GridSort) {
+        self.sort = Some(sort);
+    }
+
+    /// Shows the cells of this grid in the given view when it is created.
+    pub fn with_view(mut self, view: GridView) -> Self {
+        self.view = view;
+        self.shown_view = view;
+        self
+    }
+
+    /// Gets how the cells of the grid are shown.
+    pub fn view(&self) -> GridView {
+        self.view
+    }
+
+    /// Sets how the cells of the grid are shown. The cells are laid out again
+    /// in place.
+    pub fn set_view(&mut self, view: GridView) {
+        self.view = view;
+    }
+
+    /// Gets the current zoom level of the grid.
+    pub fn zoom(&self) -> GridZoom {
+        self.zoom
+    }
+
+    /// Sets the zoom level of the grid.
+    pub fn set_zoom(&mut self, zoom: GridZoom) {
+        self.zoom = zoom;
+    }
+
+    /// Gets the size of a cell icon at the current zoom level.
+    fn cell_size(&self) -> Vec2 {
+        self.theme.grid_preview.cell_size * self.zoom.scale()
+    }
+
+    /// Gets the size of a cell icon in the view that the cells are laid out
+    /// in. Icons in the details view are the size of the icons of the theme,
+    /// regardless of the zoom level.
+    fn icon_size(&self) -> Vec2 {
+        match self.shown_view {
+            GridView::Icons => self.cell_size(),
+            GridView::Details => Vec2::splat(self.theme.grid_preview.cell.icon_size),
+        }
+    }
+}
+
+/// The ways that a [`GridPreview`] can show its cells.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GridView {
+    /// Cells are shown as icons in a grid, with their label below.
+    #[default]
+    Icons,
+
+    /// Cells are shown as rows, with a small icon followed by their label,
+    /// type and modified date.
+    Details,
+}
+
+impl GridView {
+    /// Gets the display name of the view.
+    pub fn name(self) -> &'static str {
+        match self {
+            GridView::Icons => "Icons",
+            GridView::Details => "Details",
+        }
+    }
+
+    /// Gets the other view.
+    fn toggled(self) -> Self {
+        match self {
+            GridView::Icons => GridView::Details,
+            GridView::Details => GridView::Icons,
+        }
+    }
+}
+
+/// The zoom levels of a [`GridPreview`], which scale the size of its cells.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GridZoom {
+    /// Cells are half of the size given by the theme.
+    Small,
+
+    /// Cells are the size given by the theme.
+    #[default]
+    Medium,
+
+    /// Cells are twice the size given by the theme, and show their large icon
+    /// if they have one.
+    Large,
+}
+
+impl GridZoom {
+    /// All zoom levels, from smallest to largest.
+    pub const ALL: [GridZoom; 3] = [GridZoom::Small, GridZoom::Medium, GridZoom::Large];
+
+    /// Gets the scale applied to the cell size given by the theme.
+    pub fn scale(self) -> f32 {
+        match self {
+            GridZoom::Small => 0.5,
+            GridZoom::Medium => 1.0,
+            GridZoom::Large => 2.0,
+        }
+    }
+
+    /// Gets the next larger zoom level, if any.
+    pub fn zoom_in(self) -> Self {
+        Self::from_index(self.index() + 1)
+    }
+
+    /// Gets the next smaller zoom level, if any.
+    pub fn zoom_out(self) -> Self {
+        Self::from_index(self.index().saturating_sub(1))
+    }
+
+    /// Gets the name of the zoom level, for storing it in settings.
+    pub fn name(self) -> &'static str {
+        match self {
+            GridZoom::Small => "small",
+            GridZoom::Medium => "medium",
+            GridZoom::Large => "large",
+        }
+    }
+
+    /// Gets the zoom level with the given name, as returned by
+    /// [`GridZoom::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|zoom| zoom.name() == name)
+    }
+
+    /// Gets the position of the zoom level in [`GridZoom::ALL`].
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&zoom| zoom == self).unwrap()
+    }
+
+    /// Gets the zoom level at the given position in [`GridZoom::ALL`], clamped
+    /// to the largest zoom level.
+    fn from_index(index: usize) -> Self {
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+}
+
+/// The orders that the cells of a [`GridPreview`] can be sorted in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GridSort {
+    /// Cells are sorted alphabetically by label.
+    #[default]
+    Name,
+
+    /// Cells are grouped by type, and sorted by label within each type.
+    Type,
+
+    /// Cells are sorted by modified date, most recently modified first.
+    Modified,
+}
+
+impl GridSort {
+    /// All sort orders, in the order they are listed in the sort dropdown.
+    pub const ALL: [GridSort; 3] = [GridSort::Name, GridSort::Type, GridSort::Modified];
+
+    /// Gets the display name of the sort order.
+    pub fn name(self) -> &'static str {
+        match self {
+            GridSort::Name => "Name",
+            GridSort::Type => "Type",
+            GridSort::Modified => "Modified",
+        }
+    }
+
+    /// Compares two cells in this sort order.
+    ///
+    /// Cells without the type or modified date being sorted by come last, and
+    /// ties are broken by label and then by the order the cells were added in,
+    /// so that sorting is stable.
+    fn compare(self, a: &GridCell, b: &GridCell) -> Ordering {
+        let by_name = |a: &GridCell, b: &GridCell| {
+            a.label
+                .to_lowercase()
+                .cmp(&b.label.to_lowercase())
+                .then(a.index.cmp(&b.index))
+        };
+
+        match self {
+            GridSort::Name => by_name(a, b),
+            GridSort::Type => match (&a.kind, &b.kind) {
+                (Some(x), Some(y)) => x.cmp(y).then_with(|| by_name(a, b)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => by_name(a, b),
+            },
+            GridSort::Modified => match (a.modified, b.modified) {
+                (Some(x), Some(y)) => y.cmp(&x).then_with(|| by_name(a, b)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => by_name(a, b),
+            },
+        }
+    }
+}
+
+/// A resource storing the zoom level of each [`GridPreview`] that has a zoom
+/// key, so that applications can persist it in their settings.
+#[derive(Debug, Default, Clone, Resource, Deref, DerefMut)]
+pub struct GridZoomSettings(pub HashMap<String, GridZoom>);
+
+/// The selected cells of a [`GridPreview`], stored on the grid entity.
+#[derive(Debug, Default, Component)]
+pub struct GridSelection {
+    /// The indices of the selected cells, in the order they were selected.
+    selected: Vec<usize>,
+
+    /// The index of the cell that range selections start from.
+    anchor: Option<usize>,
+
+    /// The index of the cell that was last clicked, and the time it was
+    /// clicked at, used to detect double-clicks.
+    last_click: Option<(usize, f32)>,
+}
+
+impl GridSelection {
+    /// Gets the indices of the selected cells, in the order they were
+    /// selected.
+    pub fn selected(&self) -> &[usize] {
+        &self.selected
+    }
+
+    /// Returns true if the cell with the given index is selected.
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected.contains(&index)
+    }
+
+    /// Gets the index of the cell that range selections start from, which is
+    /// the cell that was last clicked without Shift held.
+    pub fn anchor(&self) -> Option<usize> {
+        self.anchor
+    }
+}
+
+/// An event that is triggered on a [`GridPreview`] entity when its selection
+/// changes.
+#[derive(Debug, EntityEvent)]
+pub struct GridCellsSelected {
+    /// The grid preview entity.
+    pub entity: Entity,
+
+    /// The indices of the selected cells, in the order they were selected.
+    pub selected: Vec<usize>,
+}
+
+/// An event that is triggered on a [`GridPreview`] entity when one of its cells
+/// is double-clicked.
+#[derive(Debug, EntityEvent)]
+pub struct GridCellActivated {
+    /// The grid preview entity.
+    pub entity: Entity,
+
+    /// The index of the cell that was activated.
+    pub index: usize,
+}
+
+/// An event that swaps the label of a cell of a [`GridPreview`] for a text
+/// input, so that the user can rename it.
+///
+/// Nothing happens if the cell is not shown, such as a cell of a grid with a
+/// provider that is scrolled out of view.
+#[derive(Debug, EntityEvent)]
+pub struct RenameGridCell {
+    /// The grid preview entity.
+    pub entity: Entity,
+
+    /// The index of the cell to rename.
+    pub index: usize,
+}
+
+/// An event that is triggered on a [`GridPreview`] entity when the user
+/// confirms a new name for one of its cells.
+///
+/// Grids without a provider show the new name right away. Grids with a
+/// provider show it once the provider returns it and the grid is refreshed.
+#[derive(Debug, EntityEvent)]
+pub struct GridCellRenamed {
+    /// The grid preview entity.
+    pub entity: Entity,
+
+    /// The index of the cell that was renamed.
+    pub index: usize,
+
+    /// The new name of the cell.
+    pub name: String,
+}
+
+/// The icons of a grid cell, attached to the image node of the cell.
+#[derive(Debug, Component)]
+struct GridCellIcon {
+    /// The grid that the cell belongs to.
+    grid: Entity,
+
+    /// The regular icon of the cell.
+    icon: Handle<Image>,
+
+    /// The asset path of the large icon of the cell, if any.
+    large_icon: Option<String>,
+
+    /// The large icon, once it has been requested.
+    large_handle: Option<Handle<Image>>,
+
+    /// The loading status of the regular icon.
+    status: IconStatus,
+}
+
+/// The loading status of the icon of a grid cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IconStatus {
+    /// The icon is ready to be shown.
+    Ready,
+
+    /// The deferred icon is still loading.
+    Loading,
+
+    /// The deferred icon failed to load.
+    Failed,
+}
+
+impl GridCellIcon {
+    /// Gets the icon to show at the given zoom level, requesting the large icon
+    /// from the asset server when it is first needed.
+    ///
+    /// The placeholder or error icon of the theme is returned instead while a
+    /// deferred icon is loading or if it failed to load.
+    fn image(
+        &mut self,
+        zoom: GridZoom,
+        asset_server: &AssetServer,
+        theme: &GridPreviewTheme,
+    ) -> Handle<Image> {
+        match self.status {
+            IconStatus::Ready => {}
+            IconStatus::Loading => return theme.loading_icon.clone(),
+            IconStatus::Failed => return theme.error_icon.clone(),
+        }
+
+        let Some(path) = self.large_icon.as_ref().filter(|_| zoom == GridZoom::Large) else {
+            return self.icon.clone();
+        };
+
+        self.large_handle
+            .get_or_insert_with(|| asset_server.load(path.clone()))
+            .clone()
+    }
+}
+
+/// A component on each cell of a grid.
+///
+/// The index of a cell can be read to find the item that it shows.
+#[derive(Debug, Component)]
+pub struct GridCell {
+    /// The grid that the cell belongs to.
+    grid: Entity,
+
+    /// The label of the cell.
+    label: String,
+
+    /// The type of the item shown in the cell, if known.
+    kind: Option<String>,
+
+    /// The time that the item shown in the cell was last modified, if known.
+    modified: Option<i64>,
+
+    /// The position of the cell in the order the cells were added in.
+    index: usize,
+}
+
+impl GridCell {
+    /// Gets the grid that the cell belongs to.
+    pub fn grid(&self) -> Entity {
+        self.grid
+    }
+
+    /// Gets the index of the cell, which is its position in the order the
+    /// cells were added in, or its index in the provider for grids with a
+    /// provider.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+/// A component on the label of a grid cell, which is kept as wide as the
+/// cell icon.
+#[derive(Debug, Component)]
+struct GridCellLabel {
+    /// The grid that the cell belongs to.
+    grid: Entity,
+}
+
+/// A component on the type and modified date columns of a grid cell, which are
+/// only shown in the details view.
+#[derive(Debug, Component)]
+struct GridCellDetail {
+    /// The grid that the cell belongs to.
+    grid: Entity,
+
+    /// The column shown.
+    column: DetailColumn,
+}
+
+/// The columns of a grid cell in the details view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DetailColumn {
+    /// The type of the item shown in the cell.
+    Kind,
+
+    /// The modified date of the item shown in the cell.
+    Modified,
+}
+
+impl DetailColumn {
+    /// Gets the text of this column for the given cell.
+    fn text(self, cell: &GridNodeBuilder) -> String {
+        match self {
+            DetailColumn::Kind => cell.kind.clone().unwrap_or_default(),
+            DetailColumn::Modified => cell.modified.map(format_date).unwrap_or_default(),
         }
     }
 }
 
+/// A component on a grid whose view has changed, holding the cell to scroll
+/// back to once the cells have been laid out in the new view.
+#[derive(Debug, Component)]
+struct GridScrollAnchor {
+    /// The cell to scroll back to.
+    target: AnchorTarget,
+
+    /// The number of frames left to wait before scrolling.
+    frames: u8,
+}
+
+/// The cell that a [`GridScrollAnchor`] scrolls back to.
+#[derive(Debug, Clone, Copy)]
+enum AnchorTarget {
+    /// A cell of a grid without a provider.
+    Cell(Entity),
+
+    /// The index of a cell of a grid with a provider, whose node may be reused
+    /// for another cell in the meantime.
+    Index(usize),
+}
+
+/// A resource tracking the hovered grid cell and its magnifier popup.
+#[derive(Debug, Default, Resource)]
+struct GridMagnifier {
+    /// The cell that is currently hovered.
+    cell: Option<Entity>,
+
+    /// The time, in seconds, that the cell has been hovered for.
+    hover_time: f32,
+
+    /// The magnifier popup, once it is shown.
+    popup: Option<Entity>,
+
+    /// The image shown in the magnifier popup.
+    image: Option<Handle<Image>>,
+}
+
+/// A marker component for the text of the magnifier popup that shows the
+/// dimensions of the image.
+#[derive(Debug, Component)]
+struct MagnifierDimensions;
+
+/// A marker component for the zoom slider of a grid, holding the grid entity.
+#[derive(Debug, Component)]
+struct GridZoomSlider(Entity);
+
+/// A marker component for the panel that holds the cells of a grid, holding
+/// the grid entity.
+#[derive(Debug, Component)]
+struct GridPanel(Entity);
+
+/// A component on a grid while the user drags a box over it to select cells.
+#[derive(Debug, Component)]
+struct GridBoxSelect {
+    /// The position, in logical pixels, that the drag started at.
+    start: Vec2,
+
+    /// The node that draws the box.
+    box_id: Entity,
+
+    /// The cells that were selected before the drag started, which stay
+    /// selected when Ctrl is held.
+    base: Vec<usize>,
+}
+
+/// A component on the text input that replaces the label of a cell while it
+/// is being renamed.
+#[derive(Debug, Component)]
+struct GridCellRename {
+    /// The grid that the cell belongs to.
+    grid: Entity,
+
+    /// The index of the cell.
+    index: usize,
+
+    /// The label that is hidden while the cell is being renamed.
+    label: Entity,
+}
+
+/// A marker component for the view button of a grid, holding the grid entity.
+#[derive(Debug, Component)]
+struct GridViewToggle(Entity);
+
+/// A component on the sort dropdown button of a grid.
+#[derive(Debug, Component)]
+struct GridSortDropdown {
+    /// The grid that the dropdown sorts.
+    grid: Entity,
+
+    /// The list of sort orders, while it is open.
+    menu: Option<Entity>,
+}
+
+/// A component on each option in the open list of a sort dropdown.
+#[derive(Debug, Component)]
+struct GridSortOption {
+    /// The sort dropdown button.
+    dropdown: Entity,
+
+    /// The sort order that the option selects.
+    sort: GridSort,
+}
+
 /// Observer system that runs when a [`GridPreview`] component is added.
-pub(crate) fn on_grid_add(
+fn on_grid_add(
     trigger: On<Add, GridPreview>,
+    asset_server: Res<AssetServer>,
+    settings: Res<GridZoomSettings>,
     mut query: Query<(&mut Node, &mut GridPreview)>,
     mut commands: Commands,
 ) {
@@ -68,20 +893,105 @@ pub(crate) fn on_grid_add(
 
     node.flex_direction = FlexDirection::Column;
 
-    let panel_id = commands
+    if let Some(zoom) = grid.zoom_key.as_ref().and_then(|key| settings.get(key)) {
+        grid.zoom = *zoom;
+    }
+
+    let header_id = commands
         .spawn((
             ChildOf(trigger.entity),
             Node {
-                display: Display::Flex,
                 flex_direction: FlexDirection::Row,
-                flex_wrap: FlexWrap::Wrap,
-                row_gap: px(grid.theme.grid_preview.cell_spacing.y),
-                column_gap: px(grid.theme.grid_preview.cell_spacing.x),
-                overflow: Overflow::scroll_y(),
-                scrollbar_width: 4.0,
-                width: percent(100.0),
+                justify_content: JustifyContent::FlexEnd,
+                align_items: AlignItems::Center,
+                column_gap: px(8.0),
+                margin: UiRect::bottom(px(4.0)),
+                ..default()
+            },
+        ))
+        .id();
+
+    commands.spawn((
+        ChildOf(header_id),
+        GridViewToggle(trigger.entity),
+        button(ButtonBuilder {
+            node: Node::default(),
+            content: ButtonContent::text(view_label(grid.view)),
+            theme: grid.theme.clone(),
+        }),
+    ));
+
+    if let Some(sort) = grid.sort {
+        commands.spawn((
+            ChildOf(header_id),
+            Node::default(),
+            children![(
+                GridSortDropdown {
+                    grid: trigger.entity,
+                    menu: None,
+                },
+                button(ButtonBuilder {
+                    node: Node::default(),
+                    content: ButtonContent::text(sort_label(sort)),
+                    theme: grid.theme.clone(),
+                }),
+            )],
+        ));
+    }
+
+    let slider_id = commands
+        .spawn((
+            ChildOf(header_id),
+            GridZoomSlider(trigger.entity),
+            Slider {
+                track_click: TrackClick::Snap,
+            },
+            SliderValue(grid.zoom.index() as f32),
+            SliderRange::new(0.0, (GridZoom::ALL.len() - 1) as f32),
+            SliderStep(1.0),
+            Node {
+                width: px(SLIDER_WIDTH),
+                height: px(THUMB_SIZE),
                 ..default()
             },
+            BorderRadius::all(px(THUMB_SIZE / 2.0)),
+            InteractiveColor::<BackgroundColor>::from(
+                &grid.theme.grid_preview.cell.background_color,
+            ),
+            children![(
+                SliderThumb,
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: px(THUMB_SIZE),
+                    height: px(THUMB_SIZE),
+                    left: thumb_offset(grid.zoom),
+                    ..default()
+                },
+                BorderRadius::MAX,
+                InteractiveColor::<BackgroundColor>::from(
+                    &grid.theme.grid_preview.cell.border_color,
+                ),
+            )],
+        ))
+        .id();
+    grid.slider_id = Some(slider_id);
+
+    let mut panel_node = Node {
+        display: Display::Flex,
+        row_gap: px(grid.theme.grid_preview.cell_spacing.y),
+        column_gap: px(grid.theme.grid_preview.cell_spacing.x),
+        overflow: Overflow::scroll_y(),
+        scrollbar_width: 4.0,
+        width: percent(100.0),
+        ..default()
+    };
+    apply_panel_layout(&mut panel_node, grid.view);
+
+    let panel_id = commands
+        .spawn((
+            ChildOf(trigger.entity),
+            GridPanel(trigger.entity),
+            panel_node,
         ))
         .id();
     grid.panel_id = Some(panel_id);
@@ -90,37 +1000,1559 @@ pub(crate) fn on_grid_add(
         .entity(trigger.entity)
         .insert(grid.theme.inner_window.clone());
 
+    if grid.provider.is_some() {
+        let mut virtual_scroll = VirtualScroll::grid(grid.cell_size())
+            .with_item_count(grid.init_count)
+            .with_spacing(grid.theme.grid_preview.cell_spacing);
+        virtual_scroll.set_grid(grid.view == GridView::Icons);
+        commands.entity(panel_id).insert(virtual_scroll);
+    }
+
     if let Some(cells) = grid.init_cells.take() {
-        for cell in cells {
-            commands.spawn((
-                ChildOf(panel_id),
-                Node {
-                    flex_direction: FlexDirection::Column,
-                    align_items: AlignItems::Center,
-                    row_gap: px(4.0),
-                    ..default()
-                },
-                grid.theme.grid_preview.cell.clone(),
-                InteractionSender,
-                children![
-                    (
-                        Node {
-                            width: px(grid.theme.grid_preview.cell_size.x),
-                            height: px(grid.theme.grid_preview.cell_size.y),
-                            ..default()
-                        },
-                        ImageNode {
-                            image: cell.icon,
-                            ..default()
-                        },
-                        BorderRadius::all(px(grid.theme.grid_preview.cell.border_radius)),
-                    ),
-                    (
-                        Text::from(cell.label),
-                        grid.theme.grid_preview.cell.text.clone()
-                    )
-                ],
-            ));
+        for (index, cell) in cells.into_iter().enumerate() {
+            spawn_cell(
+                &mut commands,
+                &asset_server,
+                trigger.entity,
+                &grid,
+                panel_id,
+                cell,
+                index,
+            );
         }
     }
 }
+
+/// Spawns a grid cell as a child of the given parent.
+fn spawn_cell(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    grid_id: Entity,
+    grid: &GridPreview,
+    parent: Entity,
+    cell: GridNodeBuilder,
+    index: usize,
+) {
+    let icon_size = grid.icon_size();
+    let mut icon = cell_icon(grid_id, &cell);
+    let image = icon.image(grid.zoom, asset_server, &grid.theme.grid_preview);
+    let text_theme = FontTheme {
+        overflow: cell
+            .overflow
+            .unwrap_or(grid.theme.grid_preview.cell.text.overflow),
+        ..grid.theme.grid_preview.cell.text.clone()
+    };
+
+    let mut cell_node = Node::default();
+    apply_cell_layout(&mut cell_node, grid.shown_view);
+    let mut label_node = Node::default();
+    apply_label_layout(&mut label_node, grid.shown_view, icon_size);
+    let detail = |column: DetailColumn| {
+        (
+            GridCellDetail {
+                grid: grid_id,
+                column,
+            },
+            Text::from(column.text(&cell)),
+            detail_node(grid.shown_view),
+            grid.theme.grid_preview.cell.text.clone(),
+        )
+    };
+    let details = (detail(DetailColumn::Kind), detail(DetailColumn::Modified));
+
+    let mut cell_commands = commands.spawn((
+        ChildOf(parent),
+        cell_node,
+        grid.theme.grid_preview.cell.clone(),
+        InteractionSender,
+        GridCell {
+            grid: grid_id,
+            label: cell.label.clone(),
+            kind: cell.kind,
+            modified: cell.modified,
+            index,
+        },
+        children![
+            (
+                Node {
+                    width: px(icon_size.x),
+                    height: px(icon_size.y),
+                    ..default()
+                },
+                ImageNode { image, ..default() },
+                icon,
+                BorderRadius::all(px(grid.theme.grid_preview.cell.border_radius)),
+            ),
+            (
+                GridCellLabel { grid: grid_id },
+                Text::from(cell.label),
+                label_node,
+                text_theme,
+            ),
+            details.0,
+            details.1,
+        ],
+    ));
+
+    if let Some(copy_text) = cell.copy_text {
+        cell_commands.insert(ClipboardText(copy_text));
+    }
+}
+
+/// Creates the icon component of a grid cell.
+fn cell_icon(grid_id: Entity, cell: &GridNodeBuilder) -> GridCellIcon {
+    let (handle, status) = match &cell.icon {
+        GridIcon::Ready(handle) => (handle.clone(), IconStatus::Ready),
+        GridIcon::Deferred(handle) => (handle.clone(), IconStatus::Loading),
+    };
+
+    GridCellIcon {
+        grid: grid_id,
+        icon: handle,
+        large_icon: cell.large_icon.clone(),
+        large_handle: None,
+        status,
+    }
+}
+
+/// Fills in an item node of a grid with a provider, once it is bound to a
+/// cell.
+///
+/// Item nodes that already hold a cell are updated in place. If the label of
+/// the new cell overflows differently, the old cell is replaced instead, as
+/// the overflow of a label cannot be changed after it is spawned.
+#[allow(clippy::too_many_arguments)]
+fn on_bind_grid_cell(
+    mut trigger: On<BindVirtualItem>,
+    asset_server: Res<AssetServer>,
+    grids: Query<&GridPreview>,
+    children: Query<&Children>,
+    mut cells: Query<&mut GridCell>,
+    mut icons: Query<(&mut GridCellIcon, &mut ImageNode, &mut UiTransform)>,
+    mut labels: Query<(&mut Text, &FontTheme), With<GridCellLabel>>,
+    mut details: Query<(&mut Text, &GridCellDetail), Without<GridCellLabel>>,
+    renames: Query<(), With<GridCellRename>>,
+    mut commands: Commands,
+) {
+    let grid_id = trigger.event().entity;
+    let Ok(grid) = grids.get(grid_id) else {
+        return;
+    };
+
+    trigger.propagate(false);
+    let Some(provider) = &grid.provider else {
+        return;
+    };
+
+    let item = trigger.original_event_target();
+    let index = trigger.event().index;
+    let cell = provider.get(index);
+
+    // A rename in progress belongs to the cell that the item node held before.
+    for entity in children.iter_descendants(item) {
+        if renames.contains(entity) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let existing = children
+        .get(item)
+        .ok()
+        .and_then(|item_children| item_children.first().copied())
+        .filter(|&cell_id| cells.contains(cell_id));
+
+    let Some(cell_id) = existing else {
+        spawn_cell(
+            &mut commands,
+            &asset_server,
+            grid_id,
+            grid,
+            item,
+            cell,
+            index,
+        );
+        return;
+    };
+
+    let overflow = cell
+        .overflow
+        .unwrap_or(grid.theme.grid_preview.cell.text.overflow);
+    let mut label = labels.iter_many_mut(children.iter_descendants(cell_id));
+    let Some((mut text, text_theme)) = label.fetch_next() else {
+        return;
+    };
+
+    if text_theme.overflow != overflow {
+        commands.entity(cell_id).despawn();
+        spawn_cell(
+            &mut commands,
+            &asset_server,
+            grid_id,
+            grid,
+            item,
+            cell,
+            index,
+        );
+        return;
+    }
+
+    text.0 = cell.label.clone();
+
+    let mut columns = details.iter_many_mut(children.iter_descendants(cell_id));
+    while let Some((mut text, detail)) = columns.fetch_next() {
+        text.0 = detail.column.text(&cell);
+    }
+
+    let mut icon_nodes = icons.iter_many_mut(children.iter_descendants(cell_id));
+    if let Some((mut icon, mut image, mut transform)) = icon_nodes.fetch_next() {
+        *icon = cell_icon(grid_id, &cell);
+        image.image = icon.image(grid.zoom, &asset_server, &grid.theme.grid_preview);
+        *transform = UiTransform::IDENTITY;
+    }
+
+    if let Ok(mut grid_cell) = cells.get_mut(cell_id) {
+        *grid_cell = GridCell {
+            grid: grid_id,
+            label: cell.label,
+            kind: cell.kind,
+            modified: cell.modified,
+            index,
+        };
+    }
+
+    let mut cell_commands = commands.entity(cell_id);
+    match cell.copy_text {
+        Some(copy_text) => {
+            cell_commands.insert(ClipboardText(copy_text));
+        }
+        None => {
+            cell_commands.remove::<ClipboardText>();
+        }
+    }
+}
+
+/// Changes the zoom level of a grid when the wheel is scrolled over it while
+/// Ctrl is held.
+fn on_grid_scroll(
+    mut scroll: On<Scroll>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut grids: Query<&mut GridPreview>,
+) {
+    let Ok(mut grid) = grids.get_mut(scroll.entity) else {
+        return;
+    };
+
+    if !keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    // The scroll plugin swaps the scroll axes while Ctrl is held.
+    let zoom = match scroll.delta.x {
+        delta if delta < 0.0 => grid.zoom.zoom_in(),
+        delta if delta > 0.0 => grid.zoom.zoom_out(),
+        _ => return,
+    };
+
+    if grid.zoom != zoom {
+        grid.zoom = zoom;
+    }
+
+    scroll.delta = Vec2::ZERO;
+    scroll.propagate(false);
+}
+
+/// Changes the zoom level of a grid when its zoom slider is moved.
+fn on_zoom_slider_change(
+    change: On<ValueChange<f32>>,
+    sliders: Query<&GridZoomSlider>,
+    mut grids: Query<&mut GridPreview>,
+) {
+    let Ok(slider) = sliders.get(change.source) else {
+        return;
+    };
+
+    let Ok(mut grid) = grids.get_mut(slider.0) else {
+        return;
+    };
+
+    let zoom = GridZoom::from_index(change.value.round().max(0.0) as usize);
+    if grid.zoom != zoom {
+        grid.zoom = zoom;
+    }
+}
+
+/// Resizes the cells and their labels, swaps the cell icons and moves the zoom
+/// slider of each grid whose zoom level or view has changed, and stores the new
+/// zoom level in [`GridZoomSettings`].
+fn apply_grid_zoom(
+    asset_server: Res<AssetServer>,
+    grids: Query<(Entity, &GridPreview), Changed<GridPreview>>,
+    mut icons: Query<(&mut GridCellIcon, &mut Node, &mut ImageNode)>,
+    mut labels: Query<(&GridCellLabel, &mut Node), Without<GridCellIcon>>,
+    mut sliders: Query<(&mut SliderValue, &Children)>,
+    mut thumbs: Query<
+        &mut Node,
+        (
+            With<SliderThumb>,
+            Without<GridCellIcon>,
+            Without<GridCellLabel>,
+        ),
+    >,
+    mut settings: ResMut<GridZoomSettings>,
+) {
+    for (entity, grid) in grids.iter() {
+        let icon_size = grid.icon_size();
+        for (mut icon, mut node, mut image) in icons.iter_mut() {
+            if icon.grid != entity {
+                continue;
+            }
+
+            node.width = px(icon_size.x);
+            node.height = px(icon_size.y);
+
+            let handle = icon.image(grid.zoom, &asset_server, &grid.theme.grid_preview);
+            if image.image != handle {
+                image.image = handle;
+            }
+        }
+
+        for (label, mut node) in labels.iter_mut() {
+            if label.grid == entity {
+                apply_label_layout(&mut node, grid.shown_view, icon_size);
+            }
+        }
+
+        let slider = grid.slider_id.and_then(|id| sliders.get_mut(id).ok());
+        if let Some((mut value, children)) = slider {
+            value.0 = grid.zoom.index() as f32;
+
+            let mut thumb = thumbs.iter_many_mut(children);
+            while let Some(mut node) = thumb.fetch_next() {
+                node.left = thumb_offset(grid.zoom);
+            }
+        }
+
+        if let Some(key) = &grid.zoom_key {
+            if settings.get(key) != Some(&grid.zoom) {
+                settings.insert(key.clone(), grid.zoom);
+            }
+        }
+    }
+}
+
+/// Switches the view of a grid when its view button is activated.
+fn on_view_toggle_activate(
+    trigger: On<Activate>,
+    toggles: Query<&GridViewToggle>,
+    mut grids: Query<&mut GridPreview>,
+) {
+    let Ok(toggle) = toggles.get(trigger.event().entity) else {
+        return;
+    };
+
+    let Ok(mut grid) = grids.get_mut(toggle.0) else {
+        return;
+    };
+
+    grid.view = grid.view.toggled();
+}
+
+/// Lays out the panel and cells of each grid whose view has changed, and
+/// remembers the cell at the top of the view so that it can be scrolled back
+/// into place once the cells have been laid out in the new view.
+///
+/// The icons and labels of the cells are resized by [`apply_grid_zoom`].
+#[allow(clippy::too_many_arguments)]
+fn apply_grid_view(
+    mut grids: Query<(Entity, &mut GridPreview), Changed<GridPreview>>,
+    children: Query<&Children>,
+    items: Query<&VirtualItem>,
+    labels: Query<(&GridCellLabel, &ChildOf)>,
+    details: Query<(Entity, &GridCellDetail)>,
+    toggles: Query<(Entity, &GridViewToggle)>,
+    layout: Query<(&ComputedNode, &UiGlobalTransform)>,
+    mut virtual_scrolls: Query<&mut VirtualScroll>,
+    mut nodes: Query<&mut Node>,
+    mut texts: Query<&mut Text>,
+    mut commands: Commands,
+) {
+    for (entity, mut grid) in grids.iter_mut() {
+        if grid.view == grid.shown_view {
+            continue;
+        }
+
+        let Some(panel) = grid.panel_id else {
+            continue;
+        };
+
+        let view = grid.view;
+        grid.bypass_change_detection().shown_view = view;
+
+        let virtual_scroll = virtual_scrolls.get(panel).ok();
+        if let Some(target) = top_cell(panel, virtual_scroll, &children, &items, &layout) {
+            commands.entity(entity).insert(GridScrollAnchor {
+                target,
+                frames: ANCHOR_DELAY,
+            });
+        }
+
+        if let Ok(mut virtual_scroll) = virtual_scrolls.get_mut(panel) {
+            virtual_scroll.set_grid(view == GridView::Icons);
+        }
+
+        if let Ok(mut node) = nodes.get_mut(panel) {
+            apply_panel_layout(&mut node, view);
+        }
+
+        for (label, cell) in labels.iter() {
+            if label.grid != entity {
+                continue;
+            }
+
+            if let Ok(mut node) = nodes.get_mut(cell.parent()) {
+                apply_cell_layout(&mut node, view);
+            }
+        }
+
+        for (detail_id, detail) in details.iter() {
+            if detail.grid != entity {
+                continue;
+            }
+
+            if let Ok(mut node) = nodes.get_mut(detail_id) {
+                node.display = detail_node(view).display;
+            }
+        }
+
+        for (toggle_id, toggle) in toggles.iter() {
+            if toggle.0 != entity {
+                continue;
+            }
+
+            let mut toggle_texts = texts.iter_many_mut(children.iter_descendants(toggle_id));
+            while let Some(mut text) = toggle_texts.fetch_next() {
+                text.0 = view_label(view);
+            }
+        }
+    }
+}
+
+/// Finds the first cell of a grid panel that is at least partly in view.
+fn top_cell(
+    panel: Entity,
+    virtual_scroll: Option<&VirtualScroll>,
+    children: &Query<&Children>,
+    items: &Query<&VirtualItem>,
+    layout: &Query<(&ComputedNode, &UiGlobalTransform)>,
+) -> Option<AnchorTarget> {
+    let (panel_computed, panel_transform) = layout.get(panel).ok()?;
+    let panel_top = panel_transform.translation.y - panel_computed.size().y / 2.0;
+    let in_view = |entity: Entity| {
+        layout.get(entity).is_ok_and(|(computed, transform)| {
+            computed.size().y > 0.0 && transform.translation.y + computed.size().y / 2.0 > panel_top
+        })
+    };
+
+    match virtual_scroll {
+        Some(virtual_scroll) => children
+            .get(virtual_scroll.content()?)
+            .ok()?
+            .iter()
+            .filter(|&item| in_view(item))
+            .filter_map(|item| items.get(item).ok()?.index())
+            .min()
+            .map(AnchorTarget::Index),
+        None => children
+            .get(panel)
+            .ok()?
+            .iter()
+            .find(|&cell| in_view(cell))
+            .map(AnchorTarget::Cell),
+    }
+}
+
+/// Scrolls grids back to the cell that was at the top of the view before their
+/// view changed, once the cells have been laid out in the new view.
+fn restore_grid_anchors(
+    mut grids: Query<(Entity, &GridPreview, &mut GridScrollAnchor)>,
+    mut panels: Query<(
+        &mut ScrollPosition,
+        Option<&mut SmoothScrollPosition>,
+        Option<&VirtualScroll>,
+    )>,
+    layout: Query<(&ComputedNode, &UiGlobalTransform)>,
+    mut commands: Commands,
+) {
+    for (entity, grid, mut anchor) in grids.iter_mut() {
+        if anchor.frames > 0 {
+            anchor.frames -= 1;
+            continue;
+        }
+
+        commands.entity(entity).remove::<GridScrollAnchor>();
+
+        let Some(panel) = grid.panel_id else {
+            continue;
+        };
+
+        let Ok((mut scroll_position, smooth_scroll, virtual_scroll)) = panels.get_mut(panel) else {
+            continue;
+        };
+
+        let offset = match (anchor.target, virtual_scroll) {
+            (AnchorTarget::Index(index), Some(virtual_scroll)) => {
+                virtual_scroll.item_offset(index).y
+            }
+            (AnchorTarget::Cell(cell), None) => {
+                let (Ok((panel_computed, panel_transform)), Ok((cell_computed, cell_transform))) =
+                    (layout.get(panel), layout.get(cell))
+                else {
+                    continue;
+                };
+
+                let panel_top = panel_transform.translation.y - panel_computed.size().y / 2.0
+                    + panel_computed.content_inset().top;
+                let cell_top = cell_transform.translation.y - cell_computed.size().y / 2.0;
+                (cell_top - panel_top) * panel_computed.inverse_scale_factor() + scroll_position.0.y
+            }
+            _ => continue,
+        };
+
+        scroll_position.0.y = offset.max(0.0);
+        if let Some(mut smooth_scroll) = smooth_scroll {
+            smooth_scroll.0.y = offset.max(0.0);
+        }
+    }
+}
+
+/// Lays out a grid panel for the given view.
+fn apply_panel_layout(node: &mut Node, view: GridView) {
+    match view {
+        GridView::Icons => {
+            node.flex_direction = FlexDirection::Row;
+            node.flex_wrap = FlexWrap::Wrap;
+        }
+        GridView::Details => {
+            node.flex_direction = FlexDirection::Column;
+            node.flex_wrap = FlexWrap::NoWrap;
+        }
+    }
+}
+
+/// Lays out a grid cell for the given view. Only the layout is changed, so
+/// that the padding and border given by the cell theme are kept.
+fn apply_cell_layout(node: &mut Node, view: GridView) {
+    node.align_items = AlignItems::Center;
+    match view {
+        GridView::Icons => {
+            node.flex_direction = FlexDirection::Column;
+            node.row_gap = px(4.0);
+            node.column_gap = Val::ZERO;
+            node.width = Val::Auto;
+        }
+        GridView::Details => {
+            node.flex_direction = FlexDirection::Row;
+            node.row_gap = Val::ZERO;
+            node.column_gap = px(8.0);
+            node.width = percent(100.0);
+        }
+    }
+}
+
+/// Lays out the label of a grid cell for the given view. In the icons view,
+/// the label is kept as wide as the icon, while in the details view it takes
+/// up the space left by the other columns.
+fn apply_label_layout(node: &mut Node, view: GridView, icon_size: Vec2) {
+    match view {
+        GridView::Icons => {
+            node.max_width = px(icon_size.x);
+            node.flex_grow = 0.0;
+        }
+        GridView::Details => {
+            node.max_width = Val::Auto;
+            node.flex_grow = 1.0;
+        }
+    }
+}
+
+/// Gets the layout of the type and modified date columns of a grid cell,
+/// which are hidden in the icons view.
+fn detail_node(view: GridView) -> Node {
+    Node {
+        display: match view {
+            GridView::Icons => Display::None,
+            GridView::Details => Display::Flex,
+        },
+        width: px(DETAIL_WIDTH),
+        flex_shrink: 0.0,
+        ..default()
+    }
+}
+
+/// Gets the label of a view button showing the given view.
+fn view_label(view: GridView) -> String {
+    format!("View: {}", view.name())
+}
+
+/// Formats a Unix timestamp as a UTC date, such as `2024-03-15`.
+fn format_date(timestamp: i64) -> String {
+    // This converts the number of days since the Unix epoch to a date in the
+    // proleptic Gregorian calendar, counting in 400 year eras that start on
+    // the 1st of March, so that leap days fall at the end of each year.
+    let days = timestamp.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Resolves deferred cell icons once they have loaded or failed to load, and
+/// spins the placeholder of those still loading, unless
+/// [`AccessibilityOptions::reduce_motion`] is set.
+fn poll_deferred_icons(
+    time: Res<Time>,
+    options: Res<AccessibilityOptions>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    grids: Query<&GridPreview>,
+    mut icons: Query<(&mut GridCellIcon, &mut ImageNode, &mut UiTransform)>,
+) {
+    let rotation = if options.reduce_motion {
+        Rot2::IDENTITY
+    } else {
+        Rot2::radians(time.elapsed_secs() * SPINNER_SPEED)
+    };
+    for (mut icon, mut image, mut transform) in icons.iter_mut() {
+        if icon.status != IconStatus::Loading {
+            continue;
+        }
+
+        let status = if images.contains(&icon.icon) {
+            IconStatus::Ready
+        } else if let LoadState::Failed(err) = asset_server.load_state(&icon.icon) {
+            warn!("Failed to load grid cell icon: {}", err);
+            IconStatus::Failed
+        } else {
+            transform.rotation = rotation;
+            continue;
+        };
+
+        let Ok(grid) = grids.get(icon.grid) else {
+            continue;
+        };
+
+        icon.status = status;
+        image.image = icon.image(grid.zoom, &asset_server, &grid.theme.grid_preview);
+        *transform = UiTransform::IDENTITY;
+    }
+}
+
+/// Shows the placeholders of grid previews that have a [`Placeholders`]
+/// component, while they have no cells.
+fn update_grid_placeholders(
+    mut grids: Query<(Entity, &GridPreview, &mut Placeholders)>,
+    children: Query<&Children>,
+    virtual_scrolls: Query<&VirtualScroll>,
+    mut nodes: Query<&mut Node>,
+    mut commands: Commands,
+) {
+    for (entity, grid, mut placeholders) in grids.iter_mut() {
+        let Some(panel) = grid.panel_id else {
+            continue;
+        };
+
+        let is_empty = match virtual_scrolls.get(panel) {
+            Ok(virtual_scroll) => virtual_scroll.item_count() == 0,
+            Err(_) => children
+                .get(panel)
+                .map_or(true, |children| children.is_empty()),
+        };
+        sync_placeholder(
+            entity,
+            &mut placeholders,
+            panel,
+            is_empty,
+            &grid.theme,
+            &mut nodes,
+            &mut commands,
+        );
+    }
+}
+
+/// Reorders the cells of each sorted grid when its sort order or its cells
+/// change. The cells are moved within the panel rather than recreated.
+///
+/// Grids with a provider are skipped, as only the cells in view are spawned.
+fn sort_grid_cells(
+    grids: Query<Ref<GridPreview>>,
+    mut panels: Query<&mut Children>,
+    cells: Query<&GridCell>,
+) {
+    for grid in grids.iter() {
+        let (Some(sort), Some(panel)) = (grid.sort, grid.panel_id) else {
+            continue;
+        };
+
+        if grid.provider.is_some() {
+            continue;
+        }
+
+        let Ok(mut children) = panels.get_mut(panel) else {
+            continue;
+        };
+
+        if !grid.is_changed() && !children.is_changed() {
+            continue;
+        }
+
+        children.sort_by(|a, b| match (cells.get(*a), cells.get(*b)) {
+            (Ok(a), Ok(b)) => sort.compare(a, b),
+            _ => Ordering::Equal,
+        });
+    }
+}
+
+/// Opens or closes the list of sort orders when a sort dropdown is activated.
+fn on_sort_dropdown_activate(
+    trigger: On<Activate>,
+    mut dropdowns: Query<(&mut GridSortDropdown, &ChildOf)>,
+    grids: Query<&GridPreview>,
+    mut sounds: MessageWriter<PlayUiSound>,
+    mut commands: Commands,
+) {
+    let entity = trigger.event().entity;
+    let Ok((mut dropdown, parent)) = dropdowns.get_mut(entity) else {
+        return;
+    };
+
+    if let Some(menu) = dropdown.menu.take() {
+        commands.entity(menu).despawn();
+        return;
+    }
+
+    let Ok(grid) = grids.get(dropdown.grid) else {
+        return;
+    };
+
+    let menu = commands
+        .spawn((
+            ChildOf(parent.parent()),
+            Node {
+                position_type: PositionType::Absolute,
+                top: percent(100.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: px(2.0),
+                ..default()
+            },
+            grid.theme.inner_window.clone(),
+            UiLayer::Menus,
+        ))
+        .id();
+
+    for sort in GridSort::ALL {
+        commands.spawn((
+            ChildOf(menu),
+            GridSortOption {
+                dropdown: entity,
+                sort,
+            },
+            button(ButtonBuilder {
+                node: Node::default(),
+                content: ButtonContent::text(sort.name()),
+                theme: grid.theme.clone(),
+            }),
+        ));
+    }
+
+    dropdown.menu = Some(menu);
+    sounds.write(PlayUiSound(UiSound::OpenMenu));
+}
+
+/// Sorts the grid of a sort dropdown when one of its options is activated,
+/// and closes the list of options.
+fn on_sort_option_activate(
+    trigger: On<Activate>,
+    options: Query<&GridSortOption>,
+    mut dropdowns: Query<&mut GridSortDropdown>,
+    mut grids: Query<&mut GridPreview>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text>,
+    mut commands: Commands,
+) {
+    let Ok(option) = options.get(trigger.event().entity) else {
+        return;
+    };
+
+    let Ok(mut dropdown) = dropdowns.get_mut(option.dropdown) else {
+        return;
+    };
+
+    if let Some(menu) = dropdown.menu.take() {
+        commands.entity(menu).despawn();
+    }
+
+    let Ok(mut grid) = grids.get_mut(dropdown.grid) else {
+        return;
+    };
+
+    if grid.sort != Some(option.sort) {
+        grid.sort = Some(option.sort);
+    }
+
+    let mut labels = texts.iter_many_mut(children.iter_descendants(option.dropdown));
+    while let Some(mut text) = labels.fetch_next() {
+        text.0 = sort_label(option.sort);
+    }
+}
+
+/// Gets the label of a sort dropdown showing the given sort order.
+fn sort_label(sort: GridSort) -> String {
+    format!("Sort: {}", sort.name())
+}
+
+/// How a click on a grid cell changes the selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SelectMode {
+    /// Only the clicked cell is selected.
+    Replace,
+
+    /// The clicked cell is added to or removed from the selection.
+    Toggle,
+
+    /// The range of cells from the anchor to the clicked cell is selected.
+    Range,
+
+    /// The range of cells from the anchor to the clicked cell is added to the
+    /// selection.
+    ExtendRange,
+}
+
+/// Changes the selection of a grid when one of its cells is clicked, or
+/// activates the cell when it is clicked twice in quick succession.
+fn on_cell_click(
+    trigger: On<Pointer<Click>>,
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    cells: Query<&GridCell>,
+    children: Query<&Children>,
+    renames: Query<(), With<GridCellRename>>,
+    mut commands: Commands,
+) {
+    if trigger.event().button != PointerButton::Primary {
+        return;
+    }
+
+    let cell_id = trigger.event().entity;
+    let Ok(cell) = cells.get(cell_id) else {
+        return;
+    };
+
+    // Clicks on the text input of a cell being renamed only move its cursor.
+    if children
+        .iter_descendants(cell_id)
+        .any(|child| renames.contains(child))
+    {
+        return;
+    }
+
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let mode = match (ctrl, shift) {
+        (false, false) => SelectMode::Replace,
+        (true, false) => SelectMode::Toggle,
+        (false, true) => SelectMode::Range,
+        (true, true) => SelectMode::ExtendRange,
+    };
+
+    let grid = cell.grid;
+    let index = cell.index;
+    let now = time.elapsed_secs();
+    commands.queue(move |world: &mut World| {
+        let Some(mut selection) = world.get_mut::<GridSelection>(grid) else {
+            return;
+        };
+
+        let double_click = mode == SelectMode::Replace
+            && selection
+                .last_click
+                .is_some_and(|(last, at)| last == index && now - at <= DOUBLE_CLICK_TIME);
+
+        if double_click {
+            selection.last_click = None;
+            world.trigger(GridCellActivated {
+                entity: grid,
+                index,
+            });
+            return;
+        }
+
+        selection.last_click = Some((index, now));
+        select_cell(world, grid, index, mode);
+    });
+}
+
+/// Clears the selection of a grid when the empty space between its cells is
+/// clicked without Ctrl or Shift held.
+fn on_panel_click(
+    trigger: On<Pointer<Click>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    panels: Query<(&GridPanel, Option<&VirtualScroll>)>,
+    box_selects: Query<(), With<GridBoxSelect>>,
+    mut commands: Commands,
+) {
+    if trigger.event().button != PointerButton::Primary
+        || keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::ShiftLeft,
+            KeyCode::ShiftRight,
+        ])
+    {
+        return;
+    }
+
+    let panel = trigger.event().entity;
+    let Ok((grid_panel, virtual_scroll)) = panels.get(panel) else {
+        return;
+    };
+
+    // A drag that ends over the panel also clicks it, which should not clear
+    // the cells that were just box-selected.
+    let grid = grid_panel.0;
+    if !is_empty_space(panel, virtual_scroll, trigger.original_event_target())
+        || box_selects.contains(grid)
+    {
+        return;
+    }
+
+    commands.queue(move |world: &mut World| {
+        let anchor = world
+            .get::<GridSelection>(grid)
+            .and_then(GridSelection::anchor);
+        set_selection(world, grid, Vec::new(), anchor);
+    });
+}
+
+/// Starts box-selecting the cells of a grid when the empty space between its
+/// cells is dragged. The cells that were selected stay selected if Ctrl is
+/// held.
+fn on_box_select_start(
+    trigger: On<Pointer<DragStart>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    options: Res<AccessibilityOptions>,
+    overlay: Query<Entity, With<OverlayRoot>>,
+    panels: Query<(&GridPanel, Option<&VirtualScroll>)>,
+    grids: Query<(&GridPreview, &GridSelection)>,
+    mut commands: Commands,
+) {
+    if trigger.event().button != PointerButton::Primary {
+        return;
+    }
+
+    let panel = trigger.event().entity;
+    let Ok((grid_panel, virtual_scroll)) = panels.get(panel) else {
+        return;
+    };
+
+    if !is_empty_space(panel, virtual_scroll, trigger.original_event_target()) {
+        return;
+    }
+
+    let Ok((grid, selection)) = grids.get(grid_panel.0) else {
+        return;
+    };
+
+    let Ok(overlay) = overlay.single() else {
+        error!("Failed to start box selection: no OverlayRoot found");
+        return;
+    };
+
+    let start = trigger.event().pointer_location.position;
+    let color = options
+        .palette
+        .adapt(grid.theme.grid_preview.selection_color);
+
+    let box_id = commands
+        .spawn((
+            ChildOf(overlay),
+            Node {
+                position_type: PositionType::Absolute,
+                left: px(start.x),
+                top: px(start.y),
+                border: UiRect::all(px(1.0)),
+                ..default()
+            },
+            BackgroundColor(color.with_alpha(0.25)),
+            BorderColor::all(color),
+            UiLayer::Tooltips,
+            Pickable::IGNORE,
+        ))
+        .id();
+
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let base = if ctrl {
+        selection.selected.clone()
+    } else {
+        Vec::new()
+    };
+
+    commands.entity(grid_panel.0).insert(GridBoxSelect {
+        start,
+        box_id,
+        base,
+    });
+}
+
+/// Resizes the box of a grid being box-selected, and selects the cells within
+/// it.
+///
+/// Only the part of the box over the panel selects cells, so that cells
+/// scrolled out of view are not selected.
+fn on_box_select_drag(
+    trigger: On<Pointer<Drag>>,
+    panels: Query<(&GridPanel, &ComputedNode, &UiGlobalTransform)>,
+    box_selects: Query<&GridBoxSelect>,
+    cells: Query<(&GridCell, &ComputedNode, &UiGlobalTransform)>,
+    mut nodes: Query<&mut Node>,
+    mut commands: Commands,
+) {
+    let Ok((grid_panel, panel_computed, panel_transform)) = panels.get(trigger.event().entity)
+    else {
+        return;
+    };
+
+    let grid = grid_panel.0;
+    let Ok(box_select) = box_selects.get(grid) else {
+        return;
+    };
+
+    let rect = Rect::from_corners(box_select.start, trigger.event().pointer_location.position);
+    if let Ok(mut node) = nodes.get_mut(box_select.box_id) {
+        node.left = px(rect.min.x);
+        node.top = px(rect.min.y);
+        node.width = px(rect.width());
+        node.height = px(rect.height());
+    }
+
+    let area = rect.intersect(logical_rect(panel_computed, panel_transform));
+    let mut selected = box_select.base.clone();
+    for (cell, computed, transform) in cells.iter() {
+        // Cells of unused item nodes are hidden, so they have no size.
+        if cell.grid != grid
+            || computed.size().min_element() <= 0.0
+            || selected.contains(&cell.index)
+        {
+            continue;
+        }
+
+        if !logical_rect(computed, transform).intersect(area).is_empty() {
+            selected.push(cell.index);
+        }
+    }
+
+    commands.queue(move |world: &mut World| {
+        let anchor = world
+            .get::<GridSelection>(grid)
+            .and_then(GridSelection::anchor);
+        set_selection(world, grid, selected, anchor);
+    });
+}
+
+/// Stops box-selecting the cells of a grid when the drag ends.
+fn on_box_select_end(
+    trigger: On<Pointer<DragEnd>>,
+    panels: Query<&GridPanel>,
+    box_selects: Query<&GridBoxSelect>,
+    mut commands: Commands,
+) {
+    let Ok(grid_panel) = panels.get(trigger.event().entity) else {
+        return;
+    };
+
+    let Ok(box_select) = box_selects.get(grid_panel.0) else {
+        return;
+    };
+
+    commands.entity(box_select.box_id).try_despawn();
+    commands.entity(grid_panel.0).remove::<GridBoxSelect>();
+}
+
+/// Returns true if the given entity is the empty space of a grid panel, rather
+/// than one of its cells.
+fn is_empty_space(panel: Entity, virtual_scroll: Option<&VirtualScroll>, entity: Entity) -> bool {
+    entity == panel || virtual_scroll.and_then(VirtualScroll::content) == Some(entity)
+}
+
+/// Gets the area covered by a UI node, in logical pixels.
+fn logical_rect(computed: &ComputedNode, transform: &UiGlobalTransform) -> Rect {
+    let scale = computed.inverse_scale_factor();
+    Rect::from_center_size(transform.translation * scale, computed.size() * scale)
+}
+
+/// Changes the selection of a grid after a click on one of its cells.
+fn select_cell(world: &mut World, grid: Entity, index: usize, mode: SelectMode) {
+    let Some(selection) = world.get::<GridSelection>(grid) else {
+        return;
+    };
+
+    let mut selected = selection.selected.clone();
+    let mut anchor = index;
+
+    match mode {
+        SelectMode::Replace => selected = vec![index],
+        SelectMode::Toggle => match selected.iter().position(|&other| other == index) {
+            Some(position) => {
+                selected.remove(position);
+            }
+            None => selected.push(index),
+        },
+        SelectMode::Range | SelectMode::ExtendRange => {
+            anchor = selection.anchor.unwrap_or(index);
+            let range = cell_range(world, grid, anchor, index);
+
+            if mode == SelectMode::Range {
+                selected = range;
+            } else {
+                for other in range {
+                    if !selected.contains(&other) {
+                        selected.push(other);
+                    }
+                }
+            }
+        }
+    }
+
+    set_selection(world, grid, selected, Some(anchor));
+}
+
+/// Gets the indices of the cells shown between two cells of a grid, including
+/// both cells, in the order they are shown.
+fn cell_range(world: &World, grid: Entity, from: usize, to: usize) -> Vec<usize> {
+    let Some(panel) = world.get::<GridPreview>(grid).and_then(GridPreview::panel) else {
+        return Vec::new();
+    };
+
+    // The cells of grids with a provider are shown in the order of their index.
+    if world.get::<VirtualScroll>(panel).is_some() {
+        return if from <= to {
+            (from ..= to).collect()
+        } else {
+            (to ..= from).rev().collect()
+        };
+    }
+
+    let Some(children) = world.get::<Children>(panel) else {
+        return Vec::new();
+    };
+
+    let order: Vec<usize> = children
+        .iter()
+        .filter_map(|child| world.get::<GridCell>(child).map(|cell| cell.index))
+        .collect();
+
+    let (Some(start), Some(end)) = (
+        order.iter().position(|&index| index == from),
+        order.iter().position(|&index| index == to),
+    ) else {
+        return vec![to];
+    };
+
+    if start <= end {
+        order[start ..= end].to_vec()
+    } else {
+        order[end ..= start].iter().rev().copied().collect()
+    }
+}
+
+/// Replaces the selection of a grid, triggering [`GridCellsSelected`] if it
+/// changed.
+fn set_selection(world: &mut World, grid: Entity, selected: Vec<usize>, anchor: Option<usize>) {
+    let Some(mut selection) = world.get_mut::<GridSelection>(grid) else {
+        return;
+    };
+
+    selection.anchor = anchor;
+    if selection.selected == selected {
+        return;
+    }
+
+    selection.selected = selected.clone();
+    world.trigger(GridCellsSelected {
+        entity: grid,
+        selected,
+    });
+}
+
+/// Shows the cells of each grid in their checked color while they are
+/// selected.
+///
+/// Cells are updated when the selection of their grid changes, and when they
+/// are spawned or bound to another cell of a grid with a provider.
+fn show_selected_cells(
+    selections: Query<Ref<GridSelection>>,
+    mut cells: Query<(Ref<GridCell>, &mut Propagate<InteractionReceiver>)>,
+) {
+    for (cell, mut interaction) in cells.iter_mut() {
+        let Ok(selection) = selections.get(cell.grid) else {
+            continue;
+        };
+
+        if !selection.is_changed() && !cell.is_changed() {
+            continue;
+        }
+
+        let checked = selection.is_selected(cell.index);
+        if interaction.0.is_checked() != checked {
+            interaction.0 = interaction.0.with_checked(checked);
+        }
+    }
+}
+
+/// Renames the selected cell of the hovered grid, if a single cell is
+/// selected.
+fn rename_hovered_cell(
+    hover_map: Res<HoverMap>,
+    parents: Query<&ChildOf>,
+    selections: Query<&GridSelection>,
+    mut commands: Commands,
+) {
+    let hovered = hover_map
+        .values()
+        .flat_map(|pointer_map| pointer_map.keys().copied())
+        .find_map(|entity| {
+            std::iter::once(entity)
+                .chain(parents.iter_ancestors(entity))
+                .find(|&entity| selections.contains(entity))
+        });
+
+    let Some(grid) = hovered else {
+        return;
+    };
+
+    let Ok(selection) = selections.get(grid) else {
+        return;
+    };
+
+    if let [index] = selection.selected[..] {
+        commands.trigger(RenameGridCell {
+            entity: grid,
+            index,
+        });
+    }
+}
+
+/// Swaps the label of a grid cell for a focused text input holding its name.
+#[allow(clippy::too_many_arguments)]
+fn on_rename_cell(
+    trigger: On<RenameGridCell>,
+    grids: Query<&GridPreview>,
+    cells: Query<(Entity, &GridCell, &Children, &ChildOf)>,
+    items: Query<&VirtualItem>,
+    labels: Query<&Text, With<GridCellLabel>>,
+    focused: Query<Entity, With<ImeFocus>>,
+    mut nodes: Query<&mut Node>,
+    mut commands: Commands,
+) {
+    let grid_id = trigger.event().entity;
+    let index = trigger.event().index;
+    let Ok(grid) = grids.get(grid_id) else {
+        return;
+    };
+
+    // Unused item nodes of grids with a provider keep their last cell, which
+    // must not be mistaken for the cell being renamed.
+    let cell = cells.iter().find(|(_, cell, _, parent)| {
+        cell.grid == grid_id
+            && cell.index == index
+            && items
+                .get(parent.parent())
+                .map_or(true, |item| item.index().is_some())
+    });
+
+    let Some((cell_id, _, cell_children, _)) = cell else {
+        return;
+    };
+
+    let label = cell_children
+        .iter()
+        .enumerate()
+        .find_map(|(position, child)| {
+            let text = labels.get(child).ok()?;
+            Some((position, child, text.0.clone()))
+        });
+
+    let Some((position, label_id, name)) = label else {
+        return;
+    };
+
+    let Ok(mut label_node) = nodes.get_mut(label_id) else {
+        return;
+    };
+
+    // The label is already hidden while the cell is being renamed.
+    if label_node.display == Display::None {
+        return;
+    }
+    label_node.display = Display::None;
+
+    for other in focused.iter() {
+        commands.entity(other).remove::<ImeFocus>();
+    }
+
+    let mut input_node = Node::default();
+    apply_label_layout(&mut input_node, grid.shown_view, grid.icon_size());
+    input_node.width = input_node.max_width;
+
+    let mut input = TextInput::new(grid.theme.clone()).with_value(name);
+    input.select_all();
+
+    let input_id = commands
+        .spawn((
+            GridCellRename {
+                grid: grid_id,
+                index,
+                label: label_id,
+            },
+            input,
+            input_node,
+            ImeFocus,
+        ))
+        .id();
+    commands
+        .entity(cell_id)
+        .insert_children(position, &[input_id]);
+}
+
+/// Confirms the new name of a grid cell when Enter is pressed in its rename
+/// input.
+///
+/// Grids without a provider own their cells, so the label of the cell is
+/// updated and the grid is sorted again right away.
+fn on_rename_submit(
+    trigger: On<TextInputSubmit>,
+    renames: Query<(&GridCellRename, &ChildOf)>,
+    mut grids: Query<&mut GridPreview>,
+    mut cells: Query<&mut GridCell>,
+    mut labels: Query<&mut Text, With<GridCellLabel>>,
+    mut commands: Commands,
+) {
+    let input = trigger.event().entity;
+    let Ok((rename, parent)) = renames.get(input) else {
+        return;
+    };
+
+    commands.entity(input).remove::<ImeFocus>();
+
+    let Ok(mut label) = labels.get_mut(rename.label) else {
+        return;
+    };
+
+    let name = trigger.event().value.trim().to_string();
+    if name.is_empty() || name == label.0 {
+        return;
+    }
+
+    if let Ok(mut grid) = grids.get_mut(rename.grid) {
+        if grid.provider.is_none() {
+            label.0 = name.clone();
+            if let Ok(mut cell) = cells.get_mut(parent.parent()) {
+                cell.label = name.clone();
+            }
+            grid.set_changed();
+        }
+    }
+
+    commands.trigger(GridCellRenamed {
+        entity: rename.grid,
+        index: rename.index,
+        name,
+    });
+}
+
+/// Ends the rename of a grid cell when its text input loses focus, such as
+/// after Enter or Escape is pressed, showing the label of the cell again.
+fn on_rename_blur(
+    trigger: On<Remove, ImeFocus>,
+    renames: Query<&GridCellRename>,
+    mut nodes: Query<&mut Node>,
+    mut commands: Commands,
+) {
+    let input = trigger.event().entity;
+    let Ok(rename) = renames.get(input) else {
+        return;
+    };
+
+    if let Ok(mut node) = nodes.get_mut(rename.label) {
+        node.display = Display::Flex;
+    }
+
+    commands.entity(input).try_despawn();
+}
+
+/// Gets the offset of the zoom slider thumb for the given zoom level.
+fn thumb_offset(zoom: GridZoom) -> Val {
+    let steps = (GridZoom::ALL.len() - 1) as f32;
+    px(zoom.index() as f32 / steps * (SLIDER_WIDTH - THUMB_SIZE))
+}
+
+/// Shows a magnifier popup for the hovered grid cell once it has been hovered
+/// for a moment, and dismisses it when the cell is no longer hovered.
+#[allow(clippy::too_many_arguments)]
+fn update_grid_magnifier(
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    overlay: Query<Entity, With<OverlayRoot>>,
+    cells: Query<(Entity, &Hovered, &GridCell, &Children)>,
+    grids: Query<&GridPreview>,
+    mut icons: Query<&mut GridCellIcon>,
+    mut dimensions: Query<&mut Text, With<MagnifierDimensions>>,
+    mut magnifier: ResMut<GridMagnifier>,
+    mut commands: Commands,
+) {
+    let hovered = cells.iter().find(|(_, hovered, ..)| hovered.0);
+    let hovered_id = hovered.map(|(entity, ..)| entity);
+
+    if magnifier.cell != hovered_id {
+        if let Some(popup) = magnifier.popup.take() {
+            commands.entity(popup).try_despawn();
+        }
+
+        *magnifier = GridMagnifier {
+            cell: hovered_id,
+            ..default()
+        };
+        return;
+    }
+
+    let Some((_, _, cell, children)) = hovered else {
+        return;
+    };
+
+    if magnifier.popup.is_some() {
+        let size = magnifier
+            .image
+            .as_ref()
+            .and_then(|image| images.get(image))
+            .map(|image| image.size());
+
+        if let Some(size) = size {
+            let label = format!("{} x {}", size.x, size.y);
+            for mut text in dimensions.iter_mut() {
+                text.set_if_neq(Text(label.clone()));
+            }
+        }
+        return;
+    }
+
+    magnifier.hover_time += time.delta_secs();
+    if magnifier.hover_time < MAGNIFIER_DELAY {
+        return;
+    }
+
+    let Some(icon_id) = children.iter().find(|&child| icons.contains(child)) else {
+        return;
+    };
+
+    let Ok(mut icon) = icons.get_mut(icon_id) else {
+        return;
+    };
+
+    let Ok(grid) = grids.get(icon.grid) else {
+        return;
+    };
+
+    let Ok(window) = windows.single() else {
+        return;
+    };
+
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+
+    let Ok(overlay) = overlay.single() else {
+        error!("Failed to show grid magnifier: no OverlayRoot found");
+        return;
+    };
+
+    let image = icon.image(GridZoom::Large, &asset_server, &grid.theme.grid_preview);
+    let popup_size = Vec2::new(MAGNIFIER_SIZE, MAGNIFIER_SIZE + MAGNIFIER_LABEL_HEIGHT);
+    let window_size = window.size();
+
+    let mut position = cursor + Vec2::splat(MAGNIFIER_OFFSET);
+    if position.x + popup_size.x > window_size.x {
+        position.x = cursor.x - MAGNIFIER_OFFSET - popup_size.x;
+    }
+    if position.y + popup_size.y > window_size.y {
+        position.y = cursor.y - MAGNIFIER_OFFSET - popup_size.y;
+    }
+    let position = position.max(Vec2::ZERO);
+
+    // The magnifier shows the full label, so it is wrapped rather than cut.
+    let text_theme = FontTheme {
+        overflow: LabelOverflow::default(),
+        ..grid.theme.grid_preview.cell.text.clone()
+    };
+
+    let popup = commands
+        .spawn((
+            ChildOf(overlay),
+            Node {
+                position_type: PositionType::Absolute,
+                left: px(position.x),
+                top: px(position.y),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: px(4.0),
+                ..default()
+            },
+            UiLayer::Tooltips,
+            Pickable::IGNORE,
+            grid.theme.outer_window.clone(),
+            children![
+                (
+                    Node {
+                        width: px(MAGNIFIER_SIZE),
+                        height: px(MAGNIFIER_SIZE),
+                        ..default()
+                    },
+                    ImageNode {
+                        image: image.clone(),
+                        ..default()
+                    },
+                    Pickable::IGNORE,
+                ),
+                (
+                    Text::from(cell.label.clone()),
+                    Node {
+                        max_width: px(MAGNIFIER_SIZE),
+                        ..default()
+                    },
+                    text_theme.clone(),
+                    Pickable::IGNORE,
+                ),
+                (
+                    MagnifierDimensions,
+                    Text::from("..."),
+                    text_theme,
+                    Pickable::IGNORE,
+                ),
+            ],
+        ))
+        .id();
+
+    magnifier.popup = Some(popup);
+    magnifier.image = Some(image);
+}
@@ -0,0 +1,988 @@
+//! This module implements a property inspector widget, driven by reflection.
+//!
+//! The [`Inspector`] widget takes any reflected value and generates a labeled
+//! row for each of its fields, with an editor appropriate for the field type:
+//!
+//! - Numbers are typed into a number field, or stepped with the buttons beside
+//!   it, which repeat while held.
+//! - Booleans are toggled with a checkbox.
+//! - Colors are picked from a palette opened by clicking their swatch, or typed
+//!   as a hex code.
+//! - Enums with only unit variants are picked from a dropdown.
+//!
+//! Nested structs are displayed as indented groups, and any other field is
+//! displayed as read-only text. Whenever a field is edited, an
+//! [`InspectorChanged`] event is triggered on the inspector entity.
+
+use bevy::prelude::*;
+use bevy::reflect::{DynamicEnum, DynamicVariant, GetPath, ReflectRef, TypeInfo, VariantInfo};
+use bevy::ui_widgets::Activate;
+
+use crate::color::InteractiveColor;
+use crate::ime::ImeFocus;
+use crate::menus::layers::UiLayer;
+use crate::sounds::{PlayUiSound, UiSound};
+use crate::theme::UiTheme;
+use crate::widgets::button::{ButtonBuilder, ButtonContent, ButtonRepeat, button};
+use crate::widgets::text_input::{TextInput, TextInputSubmit};
+
+/// The indentation, in pixels, applied to each level of nested fields.
+const INDENT: f32 = 12.0;
+
+/// The step used for integer fields.
+const INTEGER_STEP: f64 = 1.0;
+
+/// The step used for floating point fields.
+const FLOAT_STEP: f64 = 0.1;
+
+/// The width of number and hex code fields.
+const FIELD_WIDTH: f32 = 64.0;
+
+/// The thickness of the border of checkboxes and color swatches, and of the
+/// gap between a checkbox and its mark.
+const CHECKBOX_BORDER: f32 = 2.0;
+
+/// The number of hues in each row of the color palette.
+const PALETTE_HUES: usize = 12;
+
+/// The lightness of each row of hues in the color palette.
+const PALETTE_LIGHTNESS: [f32; 4] = [0.8, 0.6, 0.45, 0.3];
+
+/// A plugin that adds support for the inspector widget.
+pub struct InspectorPlugin;
+impl Plugin for InspectorPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_systems(Update, update_inspector_editors)
+            .add_observer(on_inspector_added)
+            .add_observer(on_edit_activated)
+            .add_observer(on_dropdown_activated)
+            .add_observer(on_field_submitted);
+    }
+}
+
+/// A widget that displays and edits the fields of a reflected value.
+#[derive(Component)]
+#[require(Node)]
+pub struct Inspector {
+    /// The theme for the inspector.
+    theme: UiTheme,
+
+    /// The value being inspected.
+    value: Box<dyn PartialReflect>,
+}
+
+impl Inspector {
+    /// Creates a new inspector for the given value.
+    pub fn new(theme: UiTheme, value: impl Reflect) -> Self {
+        Self {
+            theme,
+            value: Box::new(value),
+        }
+    }
+
+    /// Gets the value being inspected.
+    pub fn value(&self) -> &dyn PartialReflect {
+        self.value.as_ref()
+    }
+
+    /// Gets a copy of the value being inspected as a concrete type, if it is of
+    /// that type.
+    pub fn value_as<T: FromReflect>(&self) -> Option<T> {
+        T::from_reflect(self.value.as_ref())
+    }
+}
+
+/// An event that is triggered on an [`Inspector`] entity when one of its fields
+/// is edited.
+#[derive(Debug, EntityEvent)]
+pub struct InspectorChanged {
+    /// The inspector entity.
+    pub entity: Entity,
+
+    /// The reflection path of the field that was changed, such as `.pos.x`.
+    pub path: String,
+}
+
+/// An edit action bound to an inspector editor button.
+#[derive(Debug, Clone, Copy)]
+enum EditAction {
+    /// Adds the given amount to a numeric field.
+    Step(f64),
+
+    /// Toggles a boolean field.
+    Toggle,
+
+    /// Switches an enum field to the unit variant with the given name.
+    SetVariant(&'static str),
+
+    /// Sets a color field to the given color, keeping its alpha.
+    SetColor(Color),
+}
+
+/// A component on an editor button that applies an edit to an inspector field.
+#[derive(Debug, Component)]
+struct InspectorEdit {
+    /// The inspector entity.
+    inspector: Entity,
+
+    /// The reflection path of the field.
+    path: String,
+
+    /// The edit to apply.
+    action: EditAction,
+
+    /// The dropdown whose menu this button is in, which is closed once the
+    /// edit is applied.
+    dropdown: Option<Entity>,
+}
+
+/// A component on a text node that displays the value of an inspector field.
+#[derive(Debug, Component)]
+#[require(Text)]
+struct InspectorValueText {
+    /// The inspector entity.
+    inspector: Entity,
+
+    /// The reflection path of the field.
+    path: String,
+}
+
+/// A component on a text input that edits the value of an inspector field as
+/// text.
+#[derive(Debug, Component)]
+struct InspectorField {
+    /// The inspector entity.
+    inspector: Entity,
+
+    /// The reflection path of the field.
+    path: String,
+}
+
+/// A component on the mark of a checkbox, which is shown while its boolean
+/// field is true.
+#[derive(Debug, Component)]
+struct InspectorCheckMark {
+    /// The inspector entity.
+    inspector: Entity,
+
+    /// The reflection path of the field.
+    path: String,
+}
+
+/// A component on a color swatch, which is filled with the color of its field.
+#[derive(Debug, Component)]
+struct InspectorSwatch {
+    /// The inspector entity.
+    inspector: Entity,
+
+    /// The reflection path of the field.
+    path: String,
+}
+
+/// A component on a button that opens a menu of values for an inspector field.
+#[derive(Debug, Component)]
+struct InspectorDropdown {
+    /// The inspector entity.
+    inspector: Entity,
+
+    /// The reflection path of the field.
+    path: String,
+
+    /// The values listed in the menu.
+    choices: DropdownChoices,
+
+    /// The open menu, if any.
+    menu: Option<Entity>,
+}
+
+/// The values listed in the menu of an [`InspectorDropdown`].
+#[derive(Debug, Clone, Copy)]
+enum DropdownChoices {
+    /// The unit variants of an enum field.
+    Variants,
+
+    /// A palette of colors for a color field.
+    Colors,
+}
+
+/// The editor kind used for a field.
+enum FieldKind {
+    /// A numeric field, edited with a number field and step buttons.
+    Number(f64),
+
+    /// A boolean field, edited with a checkbox.
+    Bool,
+
+    /// A color field, edited with a color picker and a hex code field.
+    Color,
+
+    /// An enum with only unit variants, edited with a dropdown.
+    Enum,
+
+    /// A struct, displayed as a group of nested fields.
+    Struct,
+
+    /// Any other field, displayed as read-only text.
+    ReadOnly,
+}
+
+/// Builds the rows of an inspector when it is added.
+fn on_inspector_added(
+    trigger: On<Add, Inspector>,
+    mut query: Query<(&mut Node, &Inspector)>,
+    mut commands: Commands,
+) {
+    let Ok((mut node, inspector)) = query.get_mut(trigger.entity) else {
+        error!("Failed to query inspector node");
+        return;
+    };
+
+    node.flex_direction = FlexDirection::Column;
+    node.row_gap = px(2.0);
+
+    commands
+        .entity(trigger.entity)
+        .insert(inspector.theme.inner_window.clone());
+
+    build_rows(
+        &mut commands,
+        trigger.entity,
+        trigger.entity,
+        inspector.value.as_ref(),
+        "",
+        0,
+        &inspector.theme,
+    );
+}
+
+/// Recursively builds the rows for each field of the given struct value.
+fn build_rows(
+    commands: &mut Commands,
+    inspector: Entity,
+    parent: Entity,
+    value: &dyn PartialReflect,
+    path: &str,
+    depth: u16,
+    theme: &UiTheme,
+) {
+    let ReflectRef::Struct(value) = value.reflect_ref() else {
+        commands.spawn((
+            ChildOf(parent),
+            Text::new("This value cannot be inspected."),
+            theme.inner_window.text.clone(),
+        ));
+        return;
+    };
+
+    for (index, field) in value.iter_fields().enumerate() {
+        let Some(name) = value.name_at(index) else {
+            continue;
+        };
+        let field_path = format!("{path}.{name}");
+
+        let row = commands
+            .spawn((
+                ChildOf(parent),
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: px(4.0),
+                    margin: UiRect::left(px(INDENT * depth as f32)),
+                    ..default()
+                },
+                children![(Text::new(name), theme.inner_window.text.clone())],
+            ))
+            .id();
+
+        match field_kind(field) {
+            FieldKind::Struct => {
+                build_rows(
+                    commands,
+                    inspector,
+                    parent,
+                    field,
+                    &field_path,
+                    depth + 1,
+                    theme,
+                );
+            }
+            FieldKind::Number(step) => {
+                let decrement = EditAction::Step(-step);
+                let increment = EditAction::Step(step);
+                spawn_edit_button(commands, row, inspector, &field_path, "-", decrement, theme);
+                spawn_text_field(commands, row, inspector, &field_path, field, theme);
+                spawn_edit_button(commands, row, inspector, &field_path, "+", increment, theme);
+            }
+            FieldKind::Bool => {
+                spawn_checkbox(commands, row, inspector, &field_path, field, theme);
+            }
+            FieldKind::Enum => {
+                spawn_dropdown(commands, row, inspector, &field_path, field, theme);
+            }
+            FieldKind::Color => {
+                spawn_swatch(commands, row, inspector, &field_path, field, theme);
+                spawn_text_field(commands, row, inspector, &field_path, field, theme);
+            }
+            FieldKind::ReadOnly => {
+                commands.spawn((
+                    ChildOf(row),
+                    Text::new(display_value(field)),
+                    InspectorValueText {
+                        inspector,
+                        path: field_path,
+                    },
+                    theme.inner_window.text.clone(),
+                ));
+            }
+        }
+    }
+}
+
+/// Spawns a button that applies the given edit action when activated.
+///
+/// Step buttons repeat while held, so values can be changed quickly.
+fn spawn_edit_button(
+    commands: &mut Commands,
+    row: Entity,
+    inspector: Entity,
+    path: &str,
+    label: &str,
+    action: EditAction,
+    theme: &UiTheme,
+) {
+    let mut button_commands = commands.spawn((
+        ChildOf(row),
+        button(ButtonBuilder {
+            node: Node::default(),
+            content: ButtonContent::text(label),
+            theme: theme.clone(),
+        }),
+        InspectorEdit {
+            inspector,
+            path: path.to_string(),
+            action,
+            dropdown: None,
+        },
+    ));
+
+    if matches!(action, EditAction::Step(_)) {
+        button_commands.insert(ButtonRepeat::default());
+    }
+}
+
+/// Spawns a text input that edits the value of a number or color field as
+/// text. The value is applied when Enter is pressed.
+fn spawn_text_field(
+    commands: &mut Commands,
+    row: Entity,
+    inspector: Entity,
+    path: &str,
+    field: &dyn PartialReflect,
+    theme: &UiTheme,
+) {
+    commands.spawn((
+        ChildOf(row),
+        Node {
+            width: px(FIELD_WIDTH),
+            ..default()
+        },
+        TextInput::new(theme.clone()).with_value(display_value(field)),
+        InspectorField {
+            inspector,
+            path: path.to_string(),
+        },
+    ));
+}
+
+/// Spawns a checkbox that toggles a boolean field when activated.
+fn spawn_checkbox(
+    commands: &mut Commands,
+    row: Entity,
+    inspector: Entity,
+    path: &str,
+    field: &dyn PartialReflect,
+    theme: &UiTheme,
+) {
+    let size = theme.button.container.icon_size;
+    let checked = field.try_downcast_ref::<bool>().copied().unwrap_or(false);
+
+    commands.spawn((
+        ChildOf(row),
+        Button,
+        Node {
+            width: px(size),
+            height: px(size),
+            border: UiRect::all(px(CHECKBOX_BORDER)),
+            padding: UiRect::all(px(CHECKBOX_BORDER)),
+            ..default()
+        },
+        InteractiveColor::<BorderColor>::from(&theme.button.container.icon_color),
+        InspectorEdit {
+            inspector,
+            path: path.to_string(),
+            action: EditAction::Toggle,
+            dropdown: None,
+        },
+        children![(
+            Node {
+                display: check_mark_display(checked),
+                width: percent(100.0),
+                height: percent(100.0),
+                ..default()
+            },
+            InteractiveColor::<BackgroundColor>::from(&theme.button.container.icon_color),
+            InspectorCheckMark {
+                inspector,
+                path: path.to_string(),
+            },
+        )],
+    ));
+}
+
+/// Spawns a swatch filled with the color of a color field, which opens a
+/// palette of colors when activated.
+fn spawn_swatch(
+    commands: &mut Commands,
+    row: Entity,
+    inspector: Entity,
+    path: &str,
+    field: &dyn PartialReflect,
+    theme: &UiTheme,
+) {
+    let size = theme.button.container.icon_size;
+    let color = field
+        .try_downcast_ref::<Color>()
+        .copied()
+        .unwrap_or_default();
+
+    commands.spawn((
+        ChildOf(row),
+        Button,
+        Node {
+            width: px(size * 2.0),
+            height: px(size),
+            border: UiRect::all(px(CHECKBOX_BORDER)),
+            ..default()
+        },
+        BackgroundColor(color),
+        InteractiveColor::<BorderColor>::from(&theme.button.container.border_color),
+        InspectorSwatch {
+            inspector,
+            path: path.to_string(),
+        },
+        InspectorDropdown {
+            inspector,
+            path: path.to_string(),
+            choices: DropdownChoices::Colors,
+            menu: None,
+        },
+    ));
+}
+
+/// Spawns a dropdown showing the variant of an enum field, which opens a menu
+/// of its variants when activated.
+fn spawn_dropdown(
+    commands: &mut Commands,
+    row: Entity,
+    inspector: Entity,
+    path: &str,
+    field: &dyn PartialReflect,
+    theme: &UiTheme,
+) {
+    commands.spawn((
+        ChildOf(row),
+        button(ButtonBuilder {
+            node: Node::default(),
+            content: ButtonContent::text(dropdown_label(field)),
+            theme: theme.clone(),
+        }),
+        InspectorDropdown {
+            inspector,
+            path: path.to_string(),
+            choices: DropdownChoices::Variants,
+            menu: None,
+        },
+    ));
+}
+
+/// Opens or closes the menu of a dropdown when it is activated.
+fn on_dropdown_activated(
+    trigger: On<Activate>,
+    mut dropdowns: Query<(&mut InspectorDropdown, &ChildOf)>,
+    inspectors: Query<&Inspector>,
+    mut sounds: MessageWriter<PlayUiSound>,
+    mut commands: Commands,
+) {
+    let entity = trigger.event().entity;
+    let Ok((mut dropdown, row)) = dropdowns.get_mut(entity) else {
+        return;
+    };
+
+    if let Some(menu) = dropdown.menu.take() {
+        commands.entity(menu).try_despawn();
+        return;
+    }
+
+    let Ok(inspector) = inspectors.get(dropdown.inspector) else {
+        return;
+    };
+
+    let Ok(field) = inspector
+        .value
+        .as_ref()
+        .reflect_path(dropdown.path.as_str())
+    else {
+        return;
+    };
+
+    let theme = &inspector.theme;
+    let menu = commands
+        .spawn((
+            ChildOf(row.parent()),
+            Node {
+                position_type: PositionType::Absolute,
+                top: percent(100.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: px(2.0),
+                ..default()
+            },
+            theme.inner_window.clone(),
+            UiLayer::Menus,
+        ))
+        .id();
+
+    let edit = |action| InspectorEdit {
+        inspector: dropdown.inspector,
+        path: dropdown.path.clone(),
+        action,
+        dropdown: Some(entity),
+    };
+
+    match dropdown.choices {
+        DropdownChoices::Variants => {
+            for variant in unit_variants(field).unwrap_or_default() {
+                commands.spawn((
+                    ChildOf(menu),
+                    button(ButtonBuilder {
+                        node: Node::default(),
+                        content: ButtonContent::text(variant),
+                        theme: theme.clone(),
+                    }),
+                    edit(EditAction::SetVariant(variant)),
+                ));
+            }
+        }
+        DropdownChoices::Colors => {
+            let size = theme.button.container.icon_size;
+            for colors in palette_rows() {
+                let palette_row = commands
+                    .spawn((
+                        ChildOf(menu),
+                        Node {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: px(2.0),
+                            ..default()
+                        },
+                    ))
+                    .id();
+
+                for color in colors {
+                    commands.spawn((
+                        ChildOf(palette_row),
+                        Button,
+                        Node {
+                            width: px(size),
+                            height: px(size),
+                            border: UiRect::all(px(CHECKBOX_BORDER)),
+                            ..default()
+                        },
+                        BackgroundColor(color),
+                        InteractiveColor::<BorderColor>::from(&theme.button.container.border_color),
+                        edit(EditAction::SetColor(color)),
+                    ));
+                }
+            }
+        }
+    }
+
+    dropdown.menu = Some(menu);
+    sounds.write(PlayUiSound(UiSound::OpenMenu));
+}
+
+/// Gets the rows of colors shown in the color palette: a row of grays,
+/// followed by a row of hues for each lightness.
+fn palette_rows() -> Vec<Vec<Color>> {
+    let grays = (0 .. PALETTE_HUES)
+        .map(|i| Color::hsl(0.0, 0.0, i as f32 / (PALETTE_HUES - 1) as f32))
+        .collect();
+
+    let hues = PALETTE_LIGHTNESS.iter().map(|&lightness| {
+        (0 .. PALETTE_HUES)
+            .map(|i| Color::hsl(i as f32 * 360.0 / PALETTE_HUES as f32, 0.8, lightness))
+            .collect()
+    });
+
+    std::iter::once(grays).chain(hues).collect()
+}
+
+/// Applies an edit when an inspector editor button is activated.
+fn on_edit_activated(
+    trigger: On<Activate>,
+    edits: Query<&InspectorEdit>,
+    mut inspectors: Query<&mut Inspector>,
+    mut dropdowns: Query<&mut InspectorDropdown>,
+    mut commands: Commands,
+) {
+    let Ok(edit) = edits.get(trigger.event().entity) else {
+        return;
+    };
+
+    if let Some(mut dropdown) = edit.dropdown.and_then(|d| dropdowns.get_mut(d).ok()) {
+        if let Some(menu) = dropdown.menu.take() {
+            commands.entity(menu).try_despawn();
+        }
+    }
+
+    let Ok(mut inspector) = inspectors.get_mut(edit.inspector) else {
+        return;
+    };
+
+    let Ok(field) = inspector
+        .value
+        .as_mut()
+        .reflect_path_mut(edit.path.as_str())
+    else {
+        warn!("Inspector field {} not found", edit.path);
+        return;
+    };
+
+    let changed = match edit.action {
+        EditAction::Step(delta) => match step_number_ref(field) {
+            Some(value) => set_number(field, value + delta),
+            None => false,
+        },
+        EditAction::Toggle => match field.try_downcast_mut::<bool>() {
+            Some(value) => {
+                *value = !*value;
+                true
+            }
+            None => false,
+        },
+        EditAction::SetVariant(variant) => set_variant(field, variant),
+        EditAction::SetColor(picked) => match field.try_downcast_mut::<Color>() {
+            Some(color) => {
+                *color = picked.with_alpha(color.alpha());
+                true
+            }
+            None => false,
+        },
+    };
+
+    if changed {
+        commands.trigger(InspectorChanged {
+            entity: edit.inspector,
+            path: edit.path.clone(),
+        });
+    }
+}
+
+/// Applies the text typed into a number or hex code field when Enter is
+/// pressed. Text that cannot be parsed is rejected, and the field is reset to
+/// the current value.
+fn on_field_submitted(
+    trigger: On<TextInputSubmit>,
+    fields: Query<&InspectorField>,
+    mut inspectors: Query<&mut Inspector>,
+    mut sounds: MessageWriter<PlayUiSound>,
+    mut commands: Commands,
+) {
+    let entity = trigger.event().entity;
+    let Ok(input) = fields.get(entity) else {
+        return;
+    };
+
+    let Ok(mut inspector) = inspectors.get_mut(input.inspector) else {
+        return;
+    };
+
+    // Unfocus the field, so that it shows the value as formatted by the
+    // inspector. Marking the inspector as changed refreshes the field even if
+    // the value is rejected.
+    commands.entity(entity).remove::<ImeFocus>();
+
+    let Ok(field) = inspector
+        .value
+        .as_mut()
+        .reflect_path_mut(input.path.as_str())
+    else {
+        warn!("Inspector field {} not found", input.path);
+        return;
+    };
+
+    let text = trigger.event().value.trim();
+    let changed = if let Some(color) = field.try_downcast_mut::<Color>() {
+        match Srgba::hex(text) {
+            Ok(parsed) => {
+                *color = parsed.into();
+                true
+            }
+            Err(_) => false,
+        }
+    } else {
+        match text.parse::<f64>() {
+            Ok(value) if value.is_finite() => set_number(field, value),
+            _ => false,
+        }
+    };
+
+    if changed {
+        commands.trigger(InspectorChanged {
+            entity: input.inspector,
+            path: input.path.clone(),
+        });
+    } else {
+        sounds.write(PlayUiSound(UiSound::Error));
+    }
+}
+
+/// Updates the editors of all fields of changed inspectors to show the values
+/// of their fields.
+///
+/// Text fields that are being typed into are left as they are.
+#[allow(clippy::too_many_arguments)]
+fn update_inspector_editors(
+    inspectors: Query<&Inspector, Changed<Inspector>>,
+    mut value_texts: Query<(&InspectorValueText, &mut Text)>,
+    mut fields: Query<(&InspectorField, &mut TextInput), Without<ImeFocus>>,
+    mut marks: Query<(&InspectorCheckMark, &mut Node)>,
+    mut swatches: Query<(&InspectorSwatch, &mut BackgroundColor)>,
+    dropdowns: Query<(Entity, &InspectorDropdown)>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text, Without<InspectorValueText>>,
+) {
+    if inspectors.is_empty() {
+        return;
+    }
+
+    let field = |inspector: Entity, path: &str| inspected_field(&inspectors, inspector, path);
+
+    for (value_text, mut text) in value_texts.iter_mut() {
+        if let Some(field) = field(value_text.inspector, &value_text.path) {
+            text.set_if_neq(Text(display_value(field)));
+        }
+    }
+
+    for (input, mut text_input) in fields.iter_mut() {
+        if let Some(field) = field(input.inspector, &input.path) {
+            let display = display_value(field);
+            if text_input.value() != display {
+                text_input.set_value(display);
+            }
+        }
+    }
+
+    for (mark, mut node) in marks.iter_mut() {
+        if let Some(checked) = field(mark.inspector, &mark.path)
+            .and_then(|field| field.try_downcast_ref::<bool>().copied())
+        {
+            let display = check_mark_display(checked);
+            if node.display != display {
+                node.display = display;
+            }
+        }
+    }
+
+    for (swatch, mut background) in swatches.iter_mut() {
+        if let Some(color) = field(swatch.inspector, &swatch.path)
+            .and_then(|field| field.try_downcast_ref::<Color>().copied())
+        {
+            background.set_if_neq(BackgroundColor(color));
+        }
+    }
+
+    for (entity, dropdown) in dropdowns.iter() {
+        if !matches!(dropdown.choices, DropdownChoices::Variants) {
+            continue;
+        }
+
+        let Some(field) = field(dropdown.inspector, &dropdown.path) else {
+            continue;
+        };
+
+        let label = dropdown_label(field);
+        let mut labels = texts.iter_many_mut(children.iter_descendants(entity));
+        while let Some(mut text) = labels.fetch_next() {
+            if text.0 != label {
+                text.0.clone_from(&label);
+            }
+        }
+    }
+}
+
+/// Gets the field at the given path of a changed inspector.
+fn inspected_field<'a>(
+    inspectors: &'a Query<&Inspector, Changed<Inspector>>,
+    inspector: Entity,
+    path: &str,
+) -> Option<&'a dyn PartialReflect> {
+    inspectors
+        .get(inspector)
+        .ok()?
+        .value
+        .as_ref()
+        .reflect_path(path)
+        .ok()
+}
+
+/// Gets the display of the mark of a checkbox with the given state.
+fn check_mark_display(checked: bool) -> Display {
+    if checked {
+        Display::Flex
+    } else {
+        Display::None
+    }
+}
+
+/// Gets the label of a dropdown showing the variant of the given enum field.
+fn dropdown_label(field: &dyn PartialReflect) -> String {
+    format!("{} \u{25BE}", display_value(field))
+}
+
+/// Determines the editor kind for the given field.
+fn field_kind(field: &dyn PartialReflect) -> FieldKind {
+    if field.try_downcast_ref::<bool>().is_some() {
+        return FieldKind::Bool;
+    }
+
+    if field.try_downcast_ref::<Color>().is_some() {
+        return FieldKind::Color;
+    }
+
+    if field.try_downcast_ref::<f32>().is_some() || field.try_downcast_ref::<f64>().is_some() {
+        return FieldKind::Number(FLOAT_STEP);
+    }
+
+    if step_number_ref(field).is_some() {
+        return FieldKind::Number(INTEGER_STEP);
+    }
+
+    match field.reflect_ref() {
+        ReflectRef::Struct(_) => FieldKind::Struct,
+        ReflectRef::Enum(_) if unit_variants(field).is_some() => FieldKind::Enum,
+        _ => FieldKind::ReadOnly,
+    }
+}
+
+/// Sets a numeric field to the given value, saturating at the bounds of the
+/// field type and rounding for integer types. Returns false if the field is
+/// not numeric.
+fn set_number(field: &mut dyn PartialReflect, value: f64) -> bool {
+    macro_rules! try_set_float {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(field) = field.try_downcast_mut::<$ty>() {
+                    *field = value.clamp(<$ty>::MIN as f64, <$ty>::MAX as f64) as $ty;
+                    return true;
+                }
+            )*
+        };
+    }
+
+    macro_rules! try_set_int {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(field) = field.try_downcast_mut::<$ty>() {
+                    *field = value.round().clamp(<$ty>::MIN as f64, <$ty>::MAX as f64) as $ty;
+                    return true;
+                }
+            )*
+        };
+    }
+
+    try_set_float!(f32, f64);
+    try_set_int!(i8, i16, i32, i64, u8, u16, u32, u64, usize, isize);
+    false
+}
+
+/// Gets the value of a numeric field as an `f64`, if it is numeric.
+fn step_number_ref(field: &dyn PartialReflect) -> Option<f64> {
+    macro_rules! try_types {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(value) = field.try_downcast_ref::<$ty>() {
+                    return Some(*value as f64);
+                }
+            )*
+        };
+    }
+
+    try_types!(f32, f64, i8, i16, i32, i64, u8, u16, u32, u64, usize, isize);
+    None
+}
+
+/// Gets the names of all variants of an enum field, if every variant is a unit
+/// variant.
+fn unit_variants(field: &dyn PartialReflect) -> Option<Vec<&'static str>> {
+    let Some(TypeInfo::Enum(info)) = field.get_represented_type_info() else {
+        return None;
+    };
+
+    info.iter()
+        .map(|variant| match variant {
+            VariantInfo::Unit(unit) => Some(unit.name()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Switches an enum field to the unit variant with the given name. Returns
+/// false if the field is not a unit-only enum, or is already that variant.
+fn set_variant(field: &mut dyn PartialReflect, variant: &'static str) -> bool {
+    let Some(variants) = unit_variants(field) else {
+        return false;
+    };
+
+    let ReflectRef::Enum(value) = field.reflect_ref() else {
+        return false;
+    };
+
+    if !variants.contains(&variant) || value.variant_name() == variant {
+        return false;
+    }
+
+    field
+        .try_apply(&DynamicEnum::new(variant, DynamicVariant::Unit))
+        .is_ok()
+}
+
+/// Formats the value of a field for display.
+fn display_value(field: &dyn PartialReflect) -> String {
+    if let Some(value) = field.try_downcast_ref::<bool>() {
+        return value.to_string();
+    }
+
+    if let Some(color) = field.try_downcast_ref::<Color>() {
+        return color.to_srgba().to_hex();
+    }
+
+    if let Some(value) = field.try_downcast_ref::<f32>() {
+        return format!("{value:.2}");
+    }
+
+    if let Some(value) = field.try_downcast_ref::<f64>() {
+        return format!("{value:.2}");
+    }
+
+    if let Some(value) = step_number_ref(field) {
+        return value.to_string();
+    }
+
+    if let Some(value) = field.try_downcast_ref::<String>() {
+        return value.clone();
+    }
+
+    match field.reflect_ref() {
+        ReflectRef::Enum(value) => value.variant_name().to_string(),
+        _ => format!("{field:?}"),
+    }
+}
@@ -1,5 +1,18 @@
 //! The base widgets implemented by the UI library.
 
+pub mod busy;
 pub mod button;
+#[cfg(feature = "command_palette")]
+pub mod command_palette;
+#[cfg(feature = "console")]
+pub mod console;
+#[cfg(feature = "dialogs")]
+pub mod form;
+#[cfg(feature = "grid_preview")]
 pub mod grid_preview;
+#[cfg(feature = "inspector")]
+pub mod inspector;
+pub mod placeholder;
+pub mod text_input;
+#[cfg(feature = "tree_view")]
 pub mod tree_view;
@@ -0,0 +1,281 @@
+//! This module implements the empty and error placeholders of container
+//! widgets.
+//!
+//! Adding a [`Placeholders`] component to a container widget, such as a tree
+//! view or a grid preview, makes it show a themed placeholder in place of its
+//! content while it has no items, or while loading its items has failed. A
+//! placeholder shows an icon and a message, and optionally an action button
+//! that triggers a [`PlaceholderActionPressed`] event on the widget.
+
+use bevy::ecs::relationship::RelatedSpawner;
+use bevy::prelude::*;
+use bevy::ui_widgets::Activate;
+
+use crate::color::InteractiveColor;
+use crate::theme::UiTheme;
+use crate::widgets::button::{ButtonBuilder, ButtonContent, button};
+
+/// A plugin that adds support for placeholders.
+pub(crate) struct PlaceholderPlugin;
+impl Plugin for PlaceholderPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_observer(on_action_activated);
+    }
+}
+
+/// The kind of a placeholder, which decides how it is themed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderKind {
+    /// The widget has no content.
+    Empty,
+
+    /// Loading the content of the widget failed.
+    Error,
+}
+
+/// A placeholder shown by a container widget in place of its content.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placeholder {
+    /// The message of the placeholder.
+    pub message: String,
+
+    /// The icon of the placeholder. If `None`, the icon of the placeholder
+    /// theme for its kind is used.
+    pub icon: Option<Handle<Image>>,
+
+    /// The label of the action button of the placeholder, such as "Retry". If
+    /// `None`, no button is shown.
+    pub action: Option<String>,
+}
+
+impl Placeholder {
+    /// Creates a placeholder with the given message, the icon of the theme, and
+    /// no action button.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            icon: None,
+            action: None,
+        }
+    }
+
+    /// Replaces the icon of the theme with the given icon.
+    pub fn with_icon(mut self, icon: Handle<Image>) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Adds an action button with the given label.
+    pub fn with_action(mut self, label: impl Into<String>) -> Self {
+        self.action = Some(label.into());
+        self
+    }
+}
+
+/// A component on a container widget that holds the placeholders it shows in
+/// place of its content.
+///
+/// The error placeholder takes priority over the empty placeholder, so a
+/// widget that failed to load shows the error until it is cleared.
+#[derive(Debug, Default, Component)]
+pub struct Placeholders {
+    /// The placeholder shown while the widget has no content. If `None`, the
+    /// empty widget is shown as it is.
+    pub empty: Option<Placeholder>,
+
+    /// The placeholder shown when loading the content of the widget has
+    /// failed. This should be cleared once the content has loaded.
+    pub error: Option<Placeholder>,
+
+    /// The placeholder node that is currently shown, and what it shows.
+    shown: Option<ShownPlaceholder>,
+}
+
+impl Placeholders {
+    /// Creates placeholders that show the given placeholder while the widget
+    /// has no content.
+    pub fn empty(placeholder: Placeholder) -> Self {
+        Self {
+            empty: Some(placeholder),
+            ..default()
+        }
+    }
+
+    /// Gets the placeholder that should be shown, given whether the widget has
+    /// any content.
+    fn active(&self, is_empty: bool) -> Option<(PlaceholderKind, &Placeholder)> {
+        if let Some(error) = &self.error {
+            return Some((PlaceholderKind::Error, error));
+        }
+
+        self.empty
+            .as_ref()
+            .filter(|_| is_empty)
+            .map(|empty| (PlaceholderKind::Empty, empty))
+    }
+}
+
+/// The placeholder node shown by a widget.
+#[derive(Debug)]
+struct ShownPlaceholder {
+    /// The placeholder node.
+    node: Entity,
+
+    /// The kind of the shown placeholder.
+    kind: PlaceholderKind,
+
+    /// The shown placeholder.
+    placeholder: Placeholder,
+}
+
+/// An event that is triggered on a container widget when the action button of
+/// its placeholder is pressed.
+#[derive(Debug, EntityEvent)]
+pub struct PlaceholderActionPressed {
+    /// The container widget entity.
+    pub entity: Entity,
+
+    /// The kind of the placeholder whose button was pressed.
+    pub kind: PlaceholderKind,
+}
+
+/// A component on the action button of a placeholder.
+#[derive(Debug, Component)]
+struct PlaceholderButton {
+    /// The container widget that shows the placeholder.
+    widget: Entity,
+
+    /// The kind of the placeholder.
+    kind: PlaceholderKind,
+}
+
+/// Shows or hides the placeholder of a container widget.
+///
+/// While a placeholder is shown, the content node of the widget is hidden. The
+/// placeholder node is only rebuilt when the placeholder to show changes.
+pub(crate) fn sync_placeholder(
+    widget: Entity,
+    placeholders: &mut Mut<Placeholders>,
+    content: Entity,
+    is_empty: bool,
+    theme: &UiTheme,
+    nodes: &mut Query<&mut Node>,
+    commands: &mut Commands,
+) {
+    let active = placeholders
+        .active(is_empty)
+        .map(|(kind, placeholder)| (kind, placeholder.clone()));
+
+    let unchanged = match (&placeholders.shown, &active) {
+        (Some(shown), Some((kind, placeholder))) => {
+            shown.kind == *kind && shown.placeholder == *placeholder
+        }
+        (None, None) => true,
+        _ => false,
+    };
+
+    if unchanged {
+        return;
+    }
+
+    let placeholders = placeholders.bypass_change_detection();
+    if let Some(shown) = placeholders.shown.take() {
+        commands.entity(shown.node).despawn();
+    }
+
+    if let Ok(mut node) = nodes.get_mut(content) {
+        node.display = if active.is_some() {
+            Display::None
+        } else {
+            Display::Flex
+        };
+    }
+
+    let Some((kind, placeholder)) = active else {
+        return;
+    };
+
+    let node = commands
+        .spawn(placeholder_node(widget, kind, placeholder.clone(), theme))
+        .id();
+    placeholders.shown = Some(ShownPlaceholder {
+        node,
+        kind,
+        placeholder,
+    });
+}
+
+/// Creates the node of a placeholder shown by the given widget.
+fn placeholder_node(
+    widget: Entity,
+    kind: PlaceholderKind,
+    placeholder: Placeholder,
+    theme: &UiTheme,
+) -> impl Bundle {
+    let container = theme.placeholder.container.clone();
+    let (icon, text) = match kind {
+        PlaceholderKind::Empty => (theme.placeholder.empty_icon.clone(), container.text.clone()),
+        PlaceholderKind::Error => (
+            theme.placeholder.error_icon.clone(),
+            theme.placeholder.error_text.clone(),
+        ),
+    };
+    let icon = placeholder.icon.unwrap_or(icon);
+    let icon_size = container.icon_size;
+    let icon_color = container.icon_color.clone();
+    let theme = theme.clone();
+
+    (
+        ChildOf(widget),
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: px(8.0),
+            flex_grow: 1.0,
+            ..default()
+        },
+        container,
+        Children::spawn(SpawnWith(move |parent: &mut RelatedSpawner<ChildOf>| {
+            parent.spawn((
+                Node {
+                    width: px(icon_size),
+                    height: px(icon_size),
+                    ..default()
+                },
+                ImageNode::new(icon),
+                InteractiveColor::<ImageNode>::from(&icon_color),
+            ));
+
+            parent.spawn((Text::new(placeholder.message), text));
+
+            if let Some(label) = placeholder.action {
+                parent.spawn((
+                    PlaceholderButton { widget, kind },
+                    button(ButtonBuilder {
+                        node: Node::default(),
+                        content: ButtonContent::Label(label),
+                        theme,
+                    }),
+                ));
+            }
+        })),
+    )
+}
+
+/// Triggers a [`PlaceholderActionPressed`] event on the widget of a placeholder
+/// when its action button is activated.
+fn on_action_activated(
+    trigger: On<Activate>,
+    buttons: Query<&PlaceholderButton>,
+    mut commands: Commands,
+) {
+    let Ok(action) = buttons.get(trigger.event().entity) else {
+        return;
+    };
+
+    commands.trigger(PlaceholderActionPressed {
+        entity: action.widget,
+        kind: action.kind,
+    });
+}
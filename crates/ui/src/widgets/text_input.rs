@@ -0,0 +1,588 @@
+//! This module implements a single-line text input widget, such as for rename
+//! and search boxes.
+//!
+//! A text input is focused by clicking it, and loses focus when Escape is
+//! pressed or when the user clicks anywhere else. While focused, it receives
+//! text input and IME composition, and shows a blinking cursor. The selection
+//! is extended with Shift and the arrow, Home, and End keys, or replaced with
+//! the whole text with Ctrl+A, and can be copied, cut, and pasted through the
+//! [`Clipboard`]. Pressing Enter triggers a [`TextInputSubmit`] event on the
+//! text input.
+//!
+//! Text inputs within a form also update their
+//! [`FieldValue`](crate::widgets::form::FieldValue) when their text changes.
+
+use std::ops::Range;
+
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::picking::hover::HoverMap;
+use bevy::prelude::*;
+use bevy::text::TextLayoutInfo;
+
+use crate::accessibility::AccessibilityOptions;
+use crate::clipboard::Clipboard;
+use crate::ime::{ImeCommit, ImeFocus, ImePreview};
+use crate::theme::UiTheme;
+
+/// The time, in seconds, that the cursor of a focused text input stays shown
+/// or hidden while blinking.
+const CURSOR_BLINK_INTERVAL: f32 = 0.5;
+
+/// The width of the cursor of a text input.
+const CURSOR_WIDTH: f32 = 2.0;
+
+/// A plugin that adds support for text inputs.
+pub(crate) struct TextInputPlugin;
+impl Plugin for TextInputPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_observer(on_text_input_add)
+            .add_observer(on_text_input_press)
+            .add_observer(on_ime_commit)
+            .add_systems(
+                Update,
+                (
+                    blur_on_outside_press,
+                    type_input,
+                    update_input_text,
+                    update_cursor,
+                )
+                    .chain(),
+            );
+
+        #[cfg(feature = "dialogs")]
+        app_.add_systems(
+            Update,
+            sync_field_values
+                .after(update_input_text)
+                .before(crate::widgets::form::FormSystems::Validate),
+        );
+    }
+}
+
+/// A single-line text input widget.
+#[derive(Debug, Component)]
+#[require(Node)]
+pub struct TextInput {
+    /// The theme for the text input.
+    theme: UiTheme,
+
+    /// The text shown while the text input is empty, such as "Search...".
+    pub placeholder: String,
+
+    /// The text currently typed in the text input.
+    value: String,
+
+    /// The byte index of the cursor within the value.
+    cursor: usize,
+
+    /// The byte index of the end of the selection that stays in place while
+    /// the selection is extended. The selection is empty when it is equal to
+    /// the cursor.
+    anchor: usize,
+
+    /// The time at which the text input was last edited, which restarts the
+    /// blinking of the cursor.
+    last_edit: f32,
+
+    /// The ID of the text before the selection.
+    ///
+    /// This value is assigned when the text input is initialized.
+    text_id: Option<Entity>,
+
+    /// The ID of the text span of the selection.
+    ///
+    /// This value is assigned when the text input is initialized.
+    selection_id: Option<Entity>,
+
+    /// The ID of the text span after the selection.
+    ///
+    /// This value is assigned when the text input is initialized.
+    after_id: Option<Entity>,
+
+    /// The ID of the placeholder text.
+    ///
+    /// This value is assigned when the text input is initialized.
+    placeholder_id: Option<Entity>,
+
+    /// The ID of the cursor.
+    ///
+    /// This value is assigned when the text input is initialized.
+    cursor_id: Option<Entity>,
+}
+
+impl TextInput {
+    /// Creates a new empty text input with the given theme.
+    pub fn new(theme: UiTheme) -> Self {
+        Self {
+            theme,
+            placeholder: String::new(),
+            value: String::new(),
+            cursor: 0,
+            anchor: 0,
+            last_edit: 0.0,
+            text_id: None,
+            selection_id: None,
+            after_id: None,
+            placeholder_id: None,
+            cursor_id: None,
+        }
+    }
+
+    /// Sets the initial text of the text input.
+    pub fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.set_value(value);
+        self
+    }
+
+    /// Sets the text shown while the text input is empty.
+    pub fn with_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    /// Gets the text currently typed in the text input.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Replaces the text of the text input, moving the cursor to its end.
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.value.len();
+        self.anchor = self.cursor;
+    }
+
+    /// Gets the byte range of the selection within the value.
+    pub fn selection(&self) -> Range<usize> {
+        self.cursor.min(self.anchor) .. self.cursor.max(self.anchor)
+    }
+
+    /// Gets the selected text.
+    pub fn selected_text(&self) -> &str {
+        &self.value[self.selection()]
+    }
+
+    /// Selects the whole text.
+    pub fn select_all(&mut self) {
+        self.anchor = 0;
+        self.cursor = self.value.len();
+    }
+
+    /// Replaces the selection with the given text, and moves the cursor to the
+    /// end of the inserted text.
+    fn insert(&mut self, text: &str) {
+        let selection = self.selection();
+        self.value.replace_range(selection.clone(), text);
+        self.cursor = selection.start + text.len();
+        self.anchor = self.cursor;
+    }
+
+    /// Deletes the selection, or the character before the cursor if the
+    /// selection is empty.
+    fn delete_backward(&mut self) {
+        if self.anchor == self.cursor {
+            self.anchor = self.previous_boundary();
+        }
+        self.insert("");
+    }
+
+    /// Deletes the selection, or the character after the cursor if the
+    /// selection is empty.
+    fn delete_forward(&mut self) {
+        if self.anchor == self.cursor {
+            self.anchor = self.next_boundary();
+        }
+        self.insert("");
+    }
+
+    /// Moves the cursor to the given byte index. If `extend` is true, the
+    /// selection is extended to it, otherwise the selection is cleared.
+    fn move_cursor(&mut self, index: usize, extend: bool) {
+        self.cursor = index;
+        if !extend {
+            self.anchor = index;
+        }
+    }
+
+    /// Gets the byte index of the character boundary before the cursor.
+    fn previous_boundary(&self) -> usize {
+        self.value[.. self.cursor]
+            .chars()
+            .next_back()
+            .map_or(0, |c| self.cursor - c.len_utf8())
+    }
+
+    /// Gets the byte index of the character boundary after the cursor.
+    fn next_boundary(&self) -> usize {
+        self.value[self.cursor ..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+}
+
+/// An event that is triggered on a [`TextInput`] when the user presses Enter
+/// while it is focused.
+#[derive(Debug, EntityEvent)]
+pub struct TextInputSubmit {
+    /// The text input entity.
+    pub entity: Entity,
+
+    /// The submitted text.
+    pub value: String,
+}
+
+/// Observer system that runs when a [`TextInput`] component is added.
+fn on_text_input_add(
+    trigger: On<Add, TextInput>,
+    options: Res<AccessibilityOptions>,
+    mut query: Query<(&mut Node, &mut TextInput)>,
+    mut commands: Commands,
+) {
+    let Ok((mut node, mut input)) = query.get_mut(trigger.entity) else {
+        error!("TextInput added to entity without Node component");
+        return;
+    };
+
+    node.flex_direction = FlexDirection::Row;
+    node.align_items = AlignItems::Center;
+    node.overflow = Overflow::clip();
+
+    let theme = input.theme.text_input.clone();
+    let font = TextFont {
+        font: theme.container.text.font.clone(),
+        font_size: theme.container.text.font_size,
+        ..default()
+    };
+    let text_color = TextColor(
+        options
+            .adapt_color(&theme.container.text.color)
+            .default_color(),
+    );
+
+    let selection_id = commands
+        .spawn((
+            TextSpan::default(),
+            font.clone(),
+            text_color,
+            TextBackgroundColor(options.palette.adapt(theme.selection_color)),
+        ))
+        .id();
+
+    let after_id = commands
+        .spawn((TextSpan::default(), font.clone(), text_color))
+        .id();
+
+    let text_id = commands
+        .spawn((Text::default(), theme.container.text.clone()))
+        .add_children(&[selection_id, after_id])
+        .id();
+
+    let placeholder_id = commands
+        .spawn((
+            Text::new(input.placeholder.clone()),
+            font.clone(),
+            TextColor(options.palette.adapt(theme.placeholder_color)),
+        ))
+        .id();
+
+    let cursor_id = commands
+        .spawn((
+            Node {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                width: px(CURSOR_WIDTH),
+                height: px(theme.container.text.font_size),
+                ..default()
+            },
+            BackgroundColor(options.palette.adapt(theme.cursor_color)),
+        ))
+        .id();
+
+    let line_id = commands
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .add_children(&[text_id, placeholder_id, cursor_id])
+        .id();
+
+    commands
+        .entity(trigger.entity)
+        .insert(theme.container.clone())
+        .add_children(&[line_id])
+        .with_child((ImePreview, theme.container.text.clone()));
+
+    input.text_id = Some(text_id);
+    input.selection_id = Some(selection_id);
+    input.after_id = Some(after_id);
+    input.placeholder_id = Some(placeholder_id);
+    input.cursor_id = Some(cursor_id);
+}
+
+/// Focuses a text input when it is pressed, moving the focus away from any
+/// other text input.
+fn on_text_input_press(
+    trigger: On<Pointer<Press>>,
+    inputs: Query<(), With<TextInput>>,
+    focused: Query<Entity, (With<TextInput>, With<ImeFocus>)>,
+    mut commands: Commands,
+) {
+    let entity = trigger.entity;
+    if !inputs.contains(entity) || focused.contains(entity) {
+        return;
+    }
+
+    for other in focused.iter() {
+        commands.entity(other).remove::<ImeFocus>();
+    }
+    commands.entity(entity).insert(ImeFocus);
+}
+
+/// Removes the focus from text inputs when the user clicks outside of them.
+fn blur_on_outside_press(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    hover_map: Res<HoverMap>,
+    focused: Query<Entity, (With<TextInput>, With<ImeFocus>)>,
+    parents: Query<&ChildOf>,
+    mut commands: Commands,
+) {
+    if mouse_input.get_just_pressed().next().is_none() {
+        return;
+    }
+
+    for input in focused.iter() {
+        let inside = hover_map
+            .values()
+            .flat_map(|pointer_map| pointer_map.keys().copied())
+            .any(|entity| {
+                entity == input || parents.iter_ancestors(entity).any(|parent| parent == input)
+            });
+
+        if !inside {
+            commands.entity(input).remove::<ImeFocus>();
+        }
+    }
+}
+
+/// Handles typing into focused text inputs.
+fn type_input(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut key_presses: MessageReader<KeyboardInput>,
+    mut clipboard: ResMut<Clipboard>,
+    mut inputs: Query<(Entity, &mut TextInput), With<ImeFocus>>,
+    mut commands: Commands,
+) {
+    let key_presses = key_presses
+        .read()
+        .filter(|ev| ev.state == ButtonState::Pressed)
+        .collect::<Vec<_>>();
+
+    if key_presses.is_empty() {
+        return;
+    }
+
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    for (entity, mut input) in inputs.iter_mut() {
+        input.last_edit = time.elapsed_secs();
+
+        for ev in &key_presses {
+            if ctrl {
+                match ev.key_code {
+                    KeyCode::KeyA => input.select_all(),
+                    KeyCode::KeyC | KeyCode::KeyX if !input.selected_text().is_empty() => {
+                        if let Err(e) = clipboard.copy(input.selected_text()) {
+                            error!("Failed to copy to clipboard: {e}");
+                        } else if ev.key_code == KeyCode::KeyX {
+                            input.insert("");
+                        }
+                    }
+                    KeyCode::KeyV => match clipboard.paste() {
+                        Ok(Some(text)) => {
+                            let line = text.lines().next().unwrap_or_default();
+                            input.insert(line);
+                        }
+                        Ok(None) => {}
+                        Err(e) => error!("Failed to paste from clipboard: {e}"),
+                    },
+                    _ => {}
+                }
+                continue;
+            }
+
+            match &ev.logical_key {
+                Key::Enter => {
+                    commands.trigger(TextInputSubmit {
+                        entity,
+                        value: input.value.clone(),
+                    });
+                }
+                Key::Escape => {
+                    commands.entity(entity).remove::<ImeFocus>();
+                    break;
+                }
+                Key::Backspace => input.delete_backward(),
+                Key::Delete => input.delete_forward(),
+                Key::ArrowLeft => {
+                    let index = if shift || input.anchor == input.cursor {
+                        input.previous_boundary()
+                    } else {
+                        input.selection().start
+                    };
+                    input.move_cursor(index, shift);
+                }
+                Key::ArrowRight => {
+                    let index = if shift || input.anchor == input.cursor {
+                        input.next_boundary()
+                    } else {
+                        input.selection().end
+                    };
+                    input.move_cursor(index, shift);
+                }
+                Key::Home => input.move_cursor(0, shift),
+                Key::End => {
+                    let end = input.value.len();
+                    input.move_cursor(end, shift);
+                }
+                Key::Space => input.insert(" "),
+                Key::Character(chars) => {
+                    let chars = chars
+                        .chars()
+                        .filter(|c| !c.is_control())
+                        .collect::<String>();
+                    input.insert(&chars);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Inserts text committed by the IME into the text input it was typed into.
+fn on_ime_commit(trigger: On<ImeCommit>, mut inputs: Query<&mut TextInput>) {
+    if let Ok(mut input) = inputs.get_mut(trigger.entity) {
+        input.insert(&trigger.value);
+    }
+}
+
+/// Updates the displayed text, selection, and placeholder of text inputs whose
+/// text or selection changed.
+fn update_input_text(
+    inputs: Query<&TextInput, Changed<TextInput>>,
+    mut texts: Query<&mut Text>,
+    mut spans: Query<&mut TextSpan>,
+    mut nodes: Query<&mut Node>,
+) {
+    for input in inputs.iter() {
+        let selection = input.selection();
+        let parts = [
+            (input.text_id, &input.value[.. selection.start]),
+            (input.selection_id, &input.value[selection.clone()]),
+            (input.after_id, &input.value[selection.end ..]),
+        ];
+
+        for (id, part) in parts {
+            let Some(id) = id else {
+                continue;
+            };
+
+            if let Ok(mut text) = texts.get_mut(id) {
+                if text.0 != part {
+                    text.0 = part.to_string();
+                }
+            } else if let Ok(mut span) = spans.get_mut(id) {
+                if span.0 != part {
+                    span.0 = part.to_string();
+                }
+            }
+        }
+
+        if let Some(placeholder_id) = input.placeholder_id {
+            if let Ok(mut text) = texts.get_mut(placeholder_id) {
+                if text.0 != input.placeholder {
+                    text.0.clone_from(&input.placeholder);
+                }
+            }
+
+            if let Ok(mut node) = nodes.get_mut(placeholder_id) {
+                node.display = if input.value.is_empty() {
+                    Display::Flex
+                } else {
+                    Display::None
+                };
+            }
+        }
+    }
+}
+
+/// Positions the cursor of focused text inputs at the end of the selection it
+/// is on, and makes it blink. The cursor does not blink while
+/// [`AccessibilityOptions::reduce_motion`] is set, and is hidden while the text
+/// input is not focused.
+fn update_cursor(
+    time: Res<Time>,
+    options: Res<AccessibilityOptions>,
+    inputs: Query<(&TextInput, Has<ImeFocus>)>,
+    layouts: Query<(&TextLayoutInfo, &ComputedNode)>,
+    mut nodes: Query<&mut Node>,
+) {
+    for (input, focused) in inputs.iter() {
+        let (Some(cursor_id), Some(text_id)) = (input.cursor_id, input.text_id) else {
+            continue;
+        };
+
+        let blink = ((time.elapsed_secs() - input.last_edit) / CURSOR_BLINK_INTERVAL) as u32;
+        let shown = focused && (options.reduce_motion || blink % 2 == 0);
+
+        // The cursor is placed after the text before it, which is the
+        // selection if the cursor is at its end.
+        let selection = input.selection();
+        let before_id = if input.cursor == selection.end && !selection.is_empty() {
+            input.selection_id
+        } else {
+            Some(text_id)
+        };
+
+        let left = layouts
+            .get(text_id)
+            .ok()
+            .and_then(|(layout, computed)| {
+                layout
+                    .section_rects
+                    .iter()
+                    .find(|(entity, _)| Some(*entity) == before_id)
+                    .map(|(_, rect)| rect.max.x * computed.inverse_scale_factor())
+            })
+            .unwrap_or(0.0);
+
+        let Ok(mut node) = nodes.get_mut(cursor_id) else {
+            continue;
+        };
+
+        let display = if shown { Display::Flex } else { Display::None };
+        if node.display != display {
+            node.display = display;
+        }
+
+        if node.left != px(left) {
+            node.left = px(left);
+        }
+    }
+}
+
+/// Copies the text of text inputs within a form into their field value.
+#[cfg(feature = "dialogs")]
+fn sync_field_values(
+    mut inputs: Query<(&TextInput, &mut crate::widgets::form::FieldValue), Changed<TextInput>>,
+) {
+    for (input, mut field) in inputs.iter_mut() {
+        if field.0 != input.value {
+            field.0.clone_from(&input.value);
+        }
+    }
+}
@@ -1,12 +1,64 @@
 //! This module implements the tree view widget.
+//!
+//! Tree views may optionally show a checkbox before each node. Checking or
+//! unchecking a node applies to its whole subtree, and each parent node shows
+//! whether all, none, or only some of its children are checked. Every click
+//! triggers a single [`TreeChecksChanged`] event on the tree view, listing all
+//! nodes whose state changed.
+//!
+//! Tree views with a [`Placeholders`] component show the empty placeholder
+//! while they have no nodes, and the error placeholder while it is set.
+//!
+//! Each node may show a small badge at the end of its label, such as the number
+//! of items in a folder. Badges can be changed after the node is built with
+//! [`TreeNodeEditor::set_badge`].
+//!
+//! Each node may also hold text that is copied to the [`Clipboard`] when Ctrl+C
+//! is pressed while it is hovered, such as the ID or path of the item it
+//! represents.
+//!
+//! Clicking the arrow of a node with children collapses or expands it, which
+//! triggers a [`TreeNodeToggled`] event on the tree view. A node that starts
+//! collapsed shows an expand arrow even without children, so that they can be
+//! added lazily when it is first expanded.
+//!
+//! Clicking the rest of a label selects its node. Ctrl-clicking adds or
+//! removes a node from the selection, and shift-clicking selects the range of
+//! visible nodes from the last clicked node. The selection of each tree view
+//! is stored in its [`TreeSelection`] component, and every change triggers a
+//! [`TreeNodeSelected`] event on the tree view.
+//!
+//! Tree views created with [`TreeView::from_provider`] request their rows from
+//! a callback as they scroll into view, rather than spawning a node for every
+//! row up front. The provider supplies the visible rows of the tree in order,
+//! so it decides which rows are collapsed. Clicking a row triggers a
+//! [`TreeRowClicked`] event on the tree view, which tells whether the arrow of
+//! the row was clicked. Clicking the rest of a row selects it in the same way
+//! as a node, but the selection is stored by row index in the
+//! [`TreeRowSelection`] component, and every change triggers a
+//! [`TreeRowsSelected`] event instead. Such tree views have no tree nodes, so
+//! they do not show checkboxes or support editing with a [`TreeEditor`].
+//!
+//! [`Clipboard`]: crate::clipboard::Clipboard
 
+use bevy::app::Propagate;
 use bevy::ecs::relationship::RelatedSpawner;
 use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+use bevy::ui_widgets::{Activate, Button};
 
+use crate::clipboard::ClipboardText;
 use crate::color::InteractiveColor;
+use crate::interaction::InteractionReceiver;
+use crate::label::LabelOverflow;
 use crate::prelude::InteractionSender;
-use crate::theme::UiTheme;
+use crate::scroll::{BindVirtualItem, ItemProvider, VirtualItem, VirtualScroll};
+use crate::theme::{ContainerTheme, FontTheme, TreeViewTheme, UiTheme};
+use crate::widgets::placeholder::{Placeholders, sync_placeholder};
+
+/// The thickness of the border of a tree node checkbox, and of the gap between
+/// the border and the check mark.
+const CHECKBOX_BORDER: f32 = 2.0;
 
 /// A builder for a single tree node within a [`TreeView`].
 ///
@@ -21,9 +73,23 @@ pub struct TreeNodeBuilder {
     pub children: Vec<TreeNodeBuilder>,
 }
 
+impl TreeNodeBuilder {
+    /// Gets the initial check state of this node. A checked node checks its
+    /// whole subtree, while the state of an unchecked node with children is
+    /// aggregated from its children.
+    fn initial_check(&self, inherited: bool) -> CheckState {
+        let checked = inherited || self.content.checked;
+        self.children
+            .iter()
+            .map(|child| child.initial_check(checked))
+            .reduce(CheckState::merge)
+            .unwrap_or(CheckState::from(checked))
+    }
+}
+
 /// A [`TreeView`] component.
 #[derive(Debug, Component)]
-#[require(Node)]
+#[require(Node, TreeSelection)]
 pub struct TreeView {
     /// The root node of the tree view.
     ///
@@ -37,6 +103,16 @@ pub struct TreeView {
     /// An optional builder used to initialize the tree view. This is only used
     /// when the tree view is first created and then discarded.
     builder: Option<TreeNodeBuilder>,
+
+    /// Whether a checkbox is shown before each node.
+    checkboxes: bool,
+
+    /// The callback that supplies the rows of a virtualized tree view.
+    provider: Option<ItemProvider<TreeRow>>,
+
+    /// The initial number of rows supplied by the provider. This value is only
+    /// used when the tree view is first created.
+    init_count: usize,
 }
 
 impl TreeView {
@@ -46,6 +122,9 @@ impl TreeView {
             root_node: None,
             theme,
             builder: None,
+            checkboxes: false,
+            provider: None,
+            init_count: 0,
         }
     }
 
@@ -57,12 +136,42 @@ impl TreeView {
     /// the tree view.
     pub fn from_builder(theme: UiTheme, builder: TreeNodeBuilder) -> Self {
         Self {
-            root_node: None,
-            theme,
             builder: Some(builder),
+            ..Self::new(theme)
+        }
+    }
+
+    /// Creates a new tree view whose rows are requested from the given
+    /// provider as they scroll into view.
+    ///
+    /// The number of rows can be changed later through the [`VirtualScroll`]
+    /// of the tree view, which should also be refreshed when the rows change,
+    /// such as when a row is collapsed or expanded.
+    pub fn from_provider(
+        theme: UiTheme,
+        row_count: usize,
+        provider: ItemProvider<TreeRow>,
+    ) -> Self {
+        Self {
+            provider: Some(provider),
+            init_count: row_count,
+            ..Self::new(theme)
         }
     }
 
+    /// Shows a checkbox before each node of the tree view.
+    ///
+    /// This must be set before the tree view is spawned.
+    pub fn with_checkboxes(mut self) -> Self {
+        self.checkboxes = true;
+        self
+    }
+
+    /// Returns true if a checkbox is shown before each node.
+    pub fn has_checkboxes(&self) -> bool {
+        self.checkboxes
+    }
+
     /// Gets a reference to the theme of the tree view.
     pub fn theme(&self) -> &UiTheme {
         &self.theme
@@ -77,6 +186,86 @@ impl TreeView {
     }
 }
 
+/// A single row of a tree view with a provider.
+#[derive(Debug, Default, Clone)]
+pub struct TreeRow {
+    /// The content of the row. Whether the row is checked is ignored.
+    pub content: TreeNodeContent,
+
+    /// The depth of the row, where top-level rows have a depth of 1.
+    pub depth: u16,
+
+    /// Whether the row has children, which decides the arrow shown before an
+    /// expanded row.
+    pub has_children: bool,
+}
+
+/// The selected nodes of a [`TreeView`], stored on the tree view entity.
+#[derive(Debug, Default, Component)]
+pub struct TreeSelection {
+    /// The selected nodes, in the order they were selected.
+    selected: Vec<Entity>,
+
+    /// The node that range selections start from.
+    anchor: Option<Entity>,
+}
+
+impl TreeSelection {
+    /// Gets the selected nodes, in the order they were selected.
+    pub fn selected(&self) -> &[Entity] {
+        &self.selected
+    }
+
+    /// Returns true if the given node is selected.
+    pub fn is_selected(&self, node: Entity) -> bool {
+        self.selected.contains(&node)
+    }
+
+    /// Gets the node that range selections start from, which is the node that
+    /// was last clicked without holding shift.
+    pub fn anchor(&self) -> Option<Entity> {
+        self.anchor
+    }
+}
+
+/// The selected rows of a [`TreeView`] with a provider, stored on the tree view
+/// entity.
+#[derive(Debug, Default, Component)]
+pub struct TreeRowSelection {
+    /// The indices of the selected rows, in the order they were selected.
+    selected: Vec<usize>,
+
+    /// The index of the row that range selections start from.
+    anchor: Option<usize>,
+}
+
+impl TreeRowSelection {
+    /// Gets the indices of the selected rows, in the order they were selected.
+    pub fn selected(&self) -> &[usize] {
+        &self.selected
+    }
+
+    /// Returns true if the row with the given index is selected.
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected.contains(&index)
+    }
+
+    /// Gets the index of the row that range selections start from, which is
+    /// the row that was last clicked without holding shift.
+    pub fn anchor(&self) -> Option<usize> {
+        self.anchor
+    }
+
+    /// Clears the selection, such as when the rows of the tree view are
+    /// replaced and their indices no longer refer to the same rows.
+    ///
+    /// This does not trigger a [`TreeRowsSelected`] event.
+    pub fn clear(&mut self) {
+        self.selected.clear();
+        self.anchor = None;
+    }
+}
+
 /// A single node within a tree view.
 #[derive(Debug, Component)]
 #[require(Node)]
@@ -86,6 +275,12 @@ pub struct TreeNode {
 
     /// The tree view this node belongs to.
     tree: Entity,
+
+    /// The check state of the tree node.
+    check: CheckState,
+
+    /// Whether the children of the tree node are hidden.
+    collapsed: bool,
 }
 
 impl TreeNode {
@@ -93,6 +288,200 @@ impl TreeNode {
     pub fn depth(&self) -> u16 {
         self.depth
     }
+
+    /// Gets the tree view this node belongs to.
+    pub fn tree(&self) -> Entity {
+        self.tree
+    }
+
+    /// Gets the check state of the tree node.
+    ///
+    /// This is tracked even if the tree view does not show checkboxes.
+    pub fn check_state(&self) -> CheckState {
+        self.check
+    }
+
+    /// Returns true if the children of the tree node are hidden.
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+}
+
+/// The check state of a tree node.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CheckState {
+    /// The node and all of its children are unchecked.
+    #[default]
+    Unchecked,
+
+    /// The node and all of its children are checked.
+    Checked,
+
+    /// Some, but not all, of the children of the node are checked.
+    Partial,
+}
+
+impl CheckState {
+    /// Returns true if the node is fully checked.
+    pub fn is_checked(self) -> bool {
+        self == CheckState::Checked
+    }
+
+    /// Merges the states of two sibling nodes into the state of their parent.
+    fn merge(self, other: CheckState) -> CheckState {
+        if self == other {
+            self
+        } else {
+            CheckState::Partial
+        }
+    }
+}
+
+impl From<bool> for CheckState {
+    fn from(checked: bool) -> Self {
+        if checked {
+            CheckState::Checked
+        } else {
+            CheckState::Unchecked
+        }
+    }
+}
+
+/// A single change of the check state of a tree node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeCheckChange {
+    /// The tree node whose state changed.
+    pub node: Entity,
+
+    /// The new check state of the node.
+    pub state: CheckState,
+}
+
+/// An event that is triggered on a [`TreeView`] entity when the check state of
+/// one or more of its nodes changes.
+///
+/// Checking a node also changes its descendants and ancestors, so a single
+/// event lists every affected node.
+#[derive(Debug, EntityEvent)]
+pub struct TreeChecksChanged {
+    /// The tree view entity.
+    pub entity: Entity,
+
+    /// The nodes whose check state changed.
+    pub changes: Vec<TreeCheckChange>,
+}
+
+/// An event that is triggered on a [`TreeView`] entity when one of its nodes
+/// is collapsed or expanded.
+#[derive(Debug, EntityEvent)]
+pub struct TreeNodeToggled {
+    /// The tree view entity.
+    pub entity: Entity,
+
+    /// The tree node that was toggled.
+    pub node: Entity,
+
+    /// Whether the node is now collapsed.
+    pub collapsed: bool,
+}
+
+/// An event that is triggered on a [`TreeView`] entity when its selection
+/// changes.
+#[derive(Debug, EntityEvent)]
+pub struct TreeNodeSelected {
+    /// The tree view entity.
+    pub entity: Entity,
+
+    /// The selected nodes, in the order they were selected.
+    pub selected: Vec<Entity>,
+}
+
+/// An event that is triggered on a [`TreeView`] entity with a provider when one
+/// of its rows is clicked.
+#[derive(Debug, EntityEvent)]
+pub struct TreeRowClicked {
+    /// The tree view entity.
+    pub entity: Entity,
+
+    /// The index of the row that was clicked.
+    pub index: usize,
+
+    /// Whether the arrow of the row was clicked, in which case the row should
+    /// be collapsed or expanded rather than selected.
+    pub arrow: bool,
+}
+
+/// An event that is triggered on a [`TreeView`] entity with a provider when
+/// its selection changes.
+#[derive(Debug, EntityEvent)]
+pub struct TreeRowsSelected {
+    /// The tree view entity.
+    pub entity: Entity,
+
+    /// The indices of the selected rows, in the order they were selected.
+    pub selected: Vec<usize>,
+}
+
+/// How a click on a tree node label or row changes the selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SelectMode {
+    /// Only the clicked node is selected.
+    Replace,
+
+    /// The clicked node is added to or removed from the selection.
+    Toggle,
+
+    /// The range of nodes from the anchor to the clicked node is selected.
+    Range,
+
+    /// The range of nodes from the anchor to the clicked node is added to the
+    /// selection.
+    ExtendRange,
+}
+
+impl SelectMode {
+    /// Gets how a click changes the selection while the given keys are held.
+    fn from_keys(keys: &ButtonInput<KeyCode>) -> Self {
+        let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        match (ctrl, shift) {
+            (false, false) => SelectMode::Replace,
+            (true, false) => SelectMode::Toggle,
+            (false, true) => SelectMode::Range,
+            (true, true) => SelectMode::ExtendRange,
+        }
+    }
+}
+
+/// A component on the label row of a tree node.
+#[derive(Debug, Component)]
+struct TreeNodeLabel;
+
+/// A component on the arrow icon of a tree node label.
+#[derive(Debug, Component)]
+struct TreeNodeArrow;
+
+/// A component on the checkbox of a tree node.
+#[derive(Debug, Component)]
+struct TreeCheckbox;
+
+/// A component on the mark inside the checkbox of a tree node, which is sized
+/// to match the check state of the node.
+#[derive(Debug, Component)]
+struct TreeCheckMark;
+
+/// A component on the badge at the end of a tree node label.
+#[derive(Debug, Component)]
+struct TreeNodeBadge;
+
+/// A component on the label of a row of a tree view with a provider.
+#[derive(Debug, Component)]
+pub(crate) struct TreeRowLabel {
+    /// The tree view that the row belongs to.
+    tree: Entity,
+
+    /// The index of the row shown by the label.
+    index: usize,
 }
 
 /// The content of a tree node.
@@ -103,6 +492,26 @@ pub struct TreeNodeContent {
 
     /// An optional icon for the tree node.
     pub icon: Option<Handle<Image>>,
+
+    /// Whether the tree node starts checked. This checks all children of the
+    /// node as well.
+    pub checked: bool,
+
+    /// How the text behaves when it does not fit within the tree view. If
+    /// `None`, the overflow of the label theme is used.
+    pub overflow: Option<LabelOverflow>,
+
+    /// The text of the badge shown at the end of the label, such as the number
+    /// of items in a folder. If `None`, no badge is shown.
+    pub badge: Option<String>,
+
+    /// Optional text that is copied to the clipboard when Ctrl+C is pressed
+    /// while the node is hovered, such as the ID or path of the item that it
+    /// represents.
+    pub copy_text: Option<String>,
+
+    /// Whether the tree node starts collapsed, hiding its children.
+    pub collapsed: bool,
 }
 
 impl<S> From<S> for TreeNodeContent
@@ -113,6 +522,11 @@ where
         Self {
             text: value.into(),
             icon: None,
+            checked: false,
+            overflow: None,
+            badge: None,
+            copy_text: None,
+            collapsed: false,
         }
     }
 }
@@ -124,7 +538,10 @@ pub struct TreeEditor<'w, 's> {
     trees: Query<'w, 's, &'static TreeView>,
 
     /// The tree nodes in the world.
-    tree_nodes: Query<'w, 's, &'static TreeNode>,
+    tree_nodes: Query<'w, 's, (Entity, &'static TreeNode)>,
+
+    /// The selections of the tree views in the world.
+    selections: Query<'w, 's, &'static TreeSelection>,
 
     /// The commands to modify the world.
     commands: Commands<'w, 's>,
@@ -152,13 +569,14 @@ impl<'w, 's> TreeEditor<'w, 's> {
             node: root_node,
             theme: tree_node.theme.clone(),
             depth: 0,
+            checkboxes: tree_node.checkboxes,
         })
     }
 
     /// Begins editing the specified tree node, returning a [`TreeNodeEditor`]
     /// for that node.
     pub fn node(&mut self, node: Entity) -> Result<TreeNodeEditor<'_>, TreeEditorError> {
-        let tree_node = self
+        let (_, tree_node) = self
             .tree_nodes
             .get(node)
             .map_err(|_| TreeEditorError::TreeNodeNotFound(node))?;
@@ -174,8 +592,28 @@ impl<'w, 's> TreeEditor<'w, 's> {
             node,
             theme: tree_view.theme.clone(),
             depth: tree_node.depth,
+            checkboxes: tree_view.checkboxes,
         })
     }
+
+    /// Gets all fully checked nodes of the specified tree view, in no
+    /// particular order.
+    pub fn checked_nodes(&self, tree: Entity) -> Vec<Entity> {
+        self.tree_nodes
+            .iter()
+            .filter(|(_, node)| node.tree == tree && node.depth > 0 && node.check.is_checked())
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    /// Gets the selected nodes of the specified tree view, in the order they
+    /// were selected.
+    pub fn selected_nodes(&self, tree: Entity) -> Vec<Entity> {
+        self.selections
+            .get(tree)
+            .map(|selection| selection.selected.clone())
+            .unwrap_or_default()
+    }
 }
 
 /// An editor for a specific tree node within a tree view.
@@ -194,42 +632,109 @@ pub struct TreeNodeEditor<'a> {
 
     /// The depth of the current node.
     depth: u16,
+
+    /// Whether the tree view shows a checkbox before each node.
+    checkboxes: bool,
 }
 
 impl<'a> TreeNodeEditor<'a> {
+    /// Gets the ID of the node currently being edited.
+    pub fn id(&self) -> Entity {
+        self.node
+    }
+
     /// Adds a new node as a child of the specified parent node, and returns the
     /// ID of the newly created node.
     ///
     /// The specified `node` must be a valid [`TreeNode`] entity.
     pub fn add_child(mut self, content: TreeNodeContent) -> TreeNodeEditor<'a> {
-        let id = self
-            .commands
-            .spawn(build_node(
-                self.node,
-                self.tree,
-                content.clone(),
-                self.depth + 1,
-                &self.theme,
-                false,
-                false,
-            ))
-            .id();
+        let parent = self.node;
+        let check = CheckState::from(content.checked);
+        let copy_text = content.copy_text.clone();
+        let mut node_commands = self.commands.spawn(build_node(
+            parent,
+            self.tree,
+            content,
+            self.depth + 1,
+            &self.theme,
+            false,
+            false,
+            check,
+            self.checkboxes,
+        ));
+        if let Some(copy_text) = copy_text {
+            node_commands.insert(ClipboardText(copy_text));
+        }
+        let id = node_commands.id();
+
+        self.commands.queue(move |world: &mut World| {
+            refresh_checks(world, parent, Vec::new());
+            sync_expansion(world, parent);
+        });
 
         self.depth += 1;
         self.node = id;
         self
     }
 
+    /// Checks or unchecks the current node and all of its children.
+    pub fn set_checked(mut self, checked: bool) -> TreeNodeEditor<'a> {
+        let node = self.node;
+        self.commands
+            .queue(move |world: &mut World| set_checked(world, node, checked));
+        self
+    }
+
+    /// Adds the current node to the selection of its tree view, or removes it.
+    pub fn set_selected(mut self, selected: bool) -> TreeNodeEditor<'a> {
+        let node = self.node;
+        self.commands
+            .queue(move |world: &mut World| set_selected(world, node, selected));
+        self
+    }
+
+    /// Collapses or expands the current node.
+    pub fn set_collapsed(mut self, collapsed: bool) -> TreeNodeEditor<'a> {
+        let node = self.node;
+        self.commands
+            .queue(move |world: &mut World| set_collapsed(world, node, collapsed));
+        self
+    }
+
+    /// Sets the text of the badge shown at the end of the current node label,
+    /// or hides the badge if `None`.
+    pub fn set_badge(mut self, badge: Option<String>) -> TreeNodeEditor<'a> {
+        let node = self.node;
+        self.commands
+            .queue(move |world: &mut World| set_badge(world, node, badge));
+        self
+    }
+
     /// Removes the current node from the tree.
     ///
     /// If the node currently being edited is the root node, all its children
     /// will be removed instead.
     pub fn remove(mut self) {
+        let tree = self.tree;
         if self.depth == 0 {
             self.commands.entity(self.node).despawn_children();
-        } else {
-            self.commands.entity(self.node).despawn();
+            self.commands
+                .queue(move |world: &mut World| prune_selection(world, tree));
+            return;
         }
+
+        let node = self.node;
+        self.commands.queue(move |world: &mut World| {
+            let parent = world.get::<ChildOf>(node).map(ChildOf::parent);
+            world.despawn(node);
+
+            if let Some(parent) = parent {
+                refresh_checks(world, parent, Vec::new());
+                sync_expansion(world, parent);
+            }
+
+            prune_selection(world, tree);
+        });
     }
 }
 
@@ -249,142 +754,744 @@ pub enum TreeEditorError {
     TreeNodeNotFound(Entity),
 }
 
-/// When a [`TreeView`] is added, set up its node properties.
-pub(crate) fn on_tree_added(
-    trigger: On<Add, TreeView>,
-    mut query: Query<(&mut Node, &mut TreeView)>,
-    mut commands: Commands,
-) {
-    let Ok((mut node, mut tree)) = query.get_mut(trigger.entity) else {
-        error!("Failed to query tree view node");
+/// Sets the check state of a tree node and all of its descendants, and updates
+/// the state of its ancestors.
+///
+/// A [`TreeChecksChanged`] event is triggered on the tree view if any state
+/// changed.
+fn set_checked(world: &mut World, node: Entity, checked: bool) {
+    let state = CheckState::from(checked);
+    let mut changes = Vec::new();
+
+    let mut stack = vec![node];
+    while let Some(entity) = stack.pop() {
+        if let Some(children) = world.get::<Children>(entity) {
+            stack.extend(children.iter());
+        }
+
+        let Some(mut tree_node) = world.get_mut::<TreeNode>(entity) else {
+            continue;
+        };
+
+        if tree_node.depth > 0 && tree_node.check != state {
+            tree_node.check = state;
+            changes.push(TreeCheckChange {
+                node: entity,
+                state,
+            });
+        }
+    }
+
+    match world.get::<ChildOf>(node).map(ChildOf::parent) {
+        Some(parent) => refresh_checks(world, parent, changes),
+        None => trigger_check_changes(world, node, changes),
+    }
+}
+
+/// Shows the given badge text at the end of the label of a tree node, or hides
+/// the badge if `None`.
+fn set_badge(world: &mut World, node: Entity, badge: Option<String>) {
+    let Some(row) = world
+        .get::<Children>(node)
+        .and_then(|children| children.first().copied())
+    else {
         return;
     };
 
-    node.display = Display::Flex;
-    node.flex_direction = FlexDirection::Column;
-    node.overflow = Overflow::scroll();
-    // node.scrollbar_width = 4.0;
-    node.scrollbar_width = 0.0;
+    let Some(badge_node) = world.get::<Children>(row).and_then(|children| {
+        children
+            .iter()
+            .find(|child| world.get::<TreeNodeBadge>(*child).is_some())
+    }) else {
+        return;
+    };
 
-    commands
-        .entity(trigger.entity)
-        .insert(tree.theme.tree_view.container.clone());
+    let (display, badge) = badge_layout(badge);
+    if let Some(mut node) = world.get_mut::<Node>(badge_node) {
+        node.display = display;
+    }
 
-    let builder = tree.builder.take().unwrap_or_default();
-    tree.root_node = Some(build_tree_recursive(
-        &mut commands,
-        trigger.entity,
-        trigger.entity,
-        builder,
-        0,
-        &tree.theme,
-    ));
+    let Some(text) = world
+        .get::<Children>(badge_node)
+        .and_then(|children| children.first().copied())
+    else {
+        return;
+    };
+
+    if let Some(mut text) = world.get_mut::<Text>(text) {
+        text.0 = badge;
+    }
 }
 
-/// Recursively builds the tree nodes from the given builder.
-fn build_tree_recursive(
-    commands: &mut Commands,
-    tree: Entity,
-    parent: Entity,
-    builder: TreeNodeBuilder,
-    depth: u16,
-    theme: &UiTheme,
-) -> Entity {
-    let id = if depth == 0 {
-        commands
-            .spawn((
-                ChildOf(parent),
-                Node {
-                    flex_direction: FlexDirection::Column,
-                    ..default()
-                },
-                TreeNode { depth, tree },
-            ))
-            .id()
-    } else {
-        commands
-            .spawn(build_node(
-                parent,
-                tree,
-                builder.content,
-                depth,
-                theme,
-                !builder.children.is_empty(),
-                false,
-            ))
-            .id()
+/// Gets the display mode and text of a tree node badge.
+fn badge_layout(badge: Option<String>) -> (Display, String) {
+    match badge {
+        Some(badge) => (Display::Flex, badge),
+        None => (Display::None, String::new()),
+    }
+}
+
+/// Collapses or expands a tree node, and triggers a [`TreeNodeToggled`] event
+/// on its tree view if its state changed.
+fn set_collapsed(world: &mut World, node: Entity, collapsed: bool) {
+    let Some(mut tree_node) = world.get_mut::<TreeNode>(node) else {
+        return;
     };
 
-    for child_builder in builder.children {
-        build_tree_recursive(commands, tree, id, child_builder, depth + 1, theme);
+    if tree_node.depth == 0 || tree_node.collapsed == collapsed {
+        return;
     }
 
-    id
+    tree_node.collapsed = collapsed;
+    let tree = tree_node.tree;
+    sync_expansion(world, node);
+
+    world.trigger(TreeNodeToggled {
+        entity: tree,
+        node,
+        collapsed,
+    });
 }
 
-/// Builds a single tree node bundle.
-fn build_node(
-    parent: Entity,
-    tree: Entity,
-    content: TreeNodeContent,
-    depth: u16,
-    theme: &UiTheme,
-    has_children: bool,
-    is_collapsed: bool,
-) -> impl Bundle {
-    let right_arrow_icon = theme.tree_view.right_arrow_icon.clone();
-    let down_arrow_icon = theme.tree_view.down_arrow_icon.clone();
-    let spacer_icon = theme.tree_view.spacer_icon.clone();
-    let icon_size = theme.tree_view.container.icon_size;
-    let label_theme = theme.tree_view.label.clone();
+/// Shows or hides the child nodes of a tree node to match whether it is
+/// collapsed, and updates the arrow icon of its label.
+fn sync_expansion(world: &mut World, node: Entity) {
+    let Some(tree_node) = world.get::<TreeNode>(node) else {
+        return;
+    };
 
-    (
-        ChildOf(parent),
-        Node {
-            flex_direction: FlexDirection::Column,
-            ..default()
-        },
-        TreeNode { depth, tree },
-        children![(
-            Node {
-                flex_direction: FlexDirection::Row,
-                ..default()
-            },
-            theme.tree_view.label.clone(),
-            InteractionSender,
-            Children::spawn(SpawnWith(move |parent: &mut RelatedSpawner<ChildOf>| {
+    if tree_node.depth == 0 {
+        return;
+    }
+
+    let (tree, collapsed) = (tree_node.tree, tree_node.collapsed);
+    let children = world
+        .get::<Children>(node)
+        .map(|children| children.to_vec())
+        .unwrap_or_default();
+
+    let display = if collapsed {
+        Display::None
+    } else {
+        Display::Flex
+    };
+
+    let mut has_children = false;
+    for &child in &children {
+        if world.get::<TreeNode>(child).is_none() {
+            continue;
+        }
+
+        has_children = true;
+        if let Some(mut child) = world.get_mut::<Node>(child) {
+            child.display = display;
+        }
+    }
+
+    let Some(icon) = world
+        .get::<TreeView>(tree)
+        .map(|tree| arrow_icon(&tree.theme.tree_view, has_children, collapsed))
+    else {
+        return;
+    };
+
+    let Some(arrow) = children
+        .first()
+        .and_then(|row| world.get::<Children>(*row))
+        .and_then(|row| {
+            row.iter()
+                .find(|child| world.get::<TreeNodeArrow>(*child).is_some())
+        })
+    else {
+        return;
+    };
+
+    if let Some(mut image) = world.get_mut::<ImageNode>(arrow) {
+        image.image = icon;
+    }
+}
+
+/// Gets the arrow icon of a tree node label. Collapsed nodes show an arrow
+/// even without children, as their children may not have been added yet.
+fn arrow_icon(theme: &TreeViewTheme, has_children: bool, collapsed: bool) -> Handle<Image> {
+    if collapsed {
+        theme.right_arrow_icon.clone()
+    } else if has_children {
+        theme.down_arrow_icon.clone()
+    } else {
+        theme.spacer_icon.clone()
+    }
+}
+
+/// Changes the selection of the tree view of a tree node after a click on its
+/// label.
+fn select_node(world: &mut World, node: Entity, mode: SelectMode) {
+    let Some(tree) = world
+        .get::<TreeNode>(node)
+        .filter(|tree_node| tree_node.depth > 0)
+        .map(TreeNode::tree)
+    else {
+        return;
+    };
+
+    let Some(selection) = world.get::<TreeSelection>(tree) else {
+        return;
+    };
+
+    let mut selected = selection.selected.clone();
+    let mut anchor = node;
+    match mode {
+        SelectMode::Replace => selected = vec![node],
+        SelectMode::Toggle => match selected.iter().position(|&entity| entity == node) {
+            Some(index) => {
+                selected.remove(index);
+            }
+            None => selected.push(node),
+        },
+        SelectMode::Range | SelectMode::ExtendRange => {
+            anchor = selection.anchor.unwrap_or(node);
+            let order = visible_nodes(world, tree);
+            let index_of = |target: Entity| order.iter().position(|&entity| entity == target);
+            let range = match (index_of(anchor), index_of(node)) {
+                (Some(from), Some(to)) => order[from.min(to) ..= from.max(to)].to_vec(),
+                _ => vec![node],
+            };
+
+            if mode == SelectMode::Range {
+                selected = range;
+            } else {
+                for entity in range {
+                    if !selected.contains(&entity) {
+                        selected.push(entity);
+                    }
+                }
+            }
+        }
+    }
+
+    set_selection(world, tree, selected, Some(anchor));
+}
+
+/// Adds a tree node to the selection of its tree view, or removes it.
+fn set_selected(world: &mut World, node: Entity, selected: bool) {
+    let Some(tree) = world
+        .get::<TreeNode>(node)
+        .filter(|tree_node| tree_node.depth > 0)
+        .map(TreeNode::tree)
+    else {
+        return;
+    };
+
+    let Some(selection) = world.get::<TreeSelection>(tree) else {
+        return;
+    };
+
+    let mut nodes = selection.selected.clone();
+    let anchor = selection.anchor;
+    if selected && !nodes.contains(&node) {
+        nodes.push(node);
+    } else if !selected {
+        nodes.retain(|&entity| entity != node);
+    }
+
+    set_selection(world, tree, nodes, anchor);
+}
+
+/// Removes despawned nodes from the selection of a tree view.
+fn prune_selection(world: &mut World, tree: Entity) {
+    let Some(selection) = world.get::<TreeSelection>(tree) else {
+        return;
+    };
+
+    let mut selected = selection.selected.clone();
+    selected.retain(|&node| world.get_entity(node).is_ok());
+
+    let anchor = selection
+        .anchor
+        .filter(|&anchor| world.get_entity(anchor).is_ok());
+
+    set_selection(world, tree, selected, anchor);
+}
+
+/// Replaces the selection of a tree view, updating the checked state of the
+/// labels whose selection changed.
+///
+/// A [`TreeNodeSelected`] event is triggered on the tree view if the selected
+/// nodes changed.
+fn set_selection(world: &mut World, tree: Entity, selected: Vec<Entity>, anchor: Option<Entity>) {
+    let Some(mut selection) = world.get_mut::<TreeSelection>(tree) else {
+        return;
+    };
+
+    selection.anchor = anchor;
+    if selection.selected == selected {
+        return;
+    }
+
+    let previous = std::mem::replace(&mut selection.selected, selected.clone());
+    for &node in previous.iter().filter(|node| !selected.contains(node)) {
+        set_label_checked(world, node, false);
+    }
+    for &node in selected.iter().filter(|node| !previous.contains(node)) {
+        set_label_checked(world, node, true);
+    }
+
+    world.trigger(TreeNodeSelected {
+        entity: tree,
+        selected,
+    });
+}
+
+/// Shows the label of a tree node in its checked color while it is selected.
+fn set_label_checked(world: &mut World, node: Entity, checked: bool) {
+    let Some(row) = world
+        .get::<Children>(node)
+        .and_then(|children| children.first().copied())
+    else {
+        return;
+    };
+
+    if let Some(mut interaction) = world.get_mut::<Propagate<InteractionReceiver>>(row) {
+        interaction.0 = interaction.0.with_checked(checked);
+    }
+}
+
+/// Gets the visible nodes of a tree view, in the order they are shown. The
+/// children of collapsed nodes are skipped.
+fn visible_nodes(world: &World, tree: Entity) -> Vec<Entity> {
+    let Some(root) = world.get::<TreeView>(tree).and_then(TreeView::root_node) else {
+        return Vec::new();
+    };
+
+    let mut nodes = Vec::new();
+    let mut stack = vec![root];
+    while let Some(entity) = stack.pop() {
+        let Some(tree_node) = world.get::<TreeNode>(entity) else {
+            continue;
+        };
+
+        if tree_node.depth > 0 {
+            nodes.push(entity);
+        }
+
+        if tree_node.collapsed {
+            continue;
+        }
+
+        if let Some(children) = world.get::<Children>(entity) {
+            stack.extend(children.iter().rev());
+        }
+    }
+
+    nodes
+}
+
+/// Recomputes the check state of a tree node and its ancestors from their
+/// children, then triggers a [`TreeChecksChanged`] event with the given
+/// changes and any new ones.
+fn refresh_checks(world: &mut World, node: Entity, mut changes: Vec<TreeCheckChange>) {
+    let mut current = Some(node);
+    while let Some(entity) = current {
+        current = world.get::<ChildOf>(entity).map(ChildOf::parent);
+
+        let Some(tree_node) = world.get::<TreeNode>(entity) else {
+            break;
+        };
+
+        if tree_node.depth == 0 {
+            break;
+        }
+
+        let Some(state) = world.get::<Children>(entity).and_then(|children| {
+            children
+                .iter()
+                .filter_map(|child| world.get::<TreeNode>(child))
+                .map(|child| child.check)
+                .reduce(CheckState::merge)
+        }) else {
+            continue;
+        };
+
+        if let Some(mut tree_node) = world.get_mut::<TreeNode>(entity) {
+            if tree_node.check != state {
+                tree_node.check = state;
+                changes.push(TreeCheckChange {
+                    node: entity,
+                    state,
+                });
+            }
+        }
+    }
+
+    trigger_check_changes(world, node, changes);
+}
+
+/// Triggers a [`TreeChecksChanged`] event on the tree view of the given node,
+/// unless there are no changes.
+fn trigger_check_changes(world: &mut World, node: Entity, changes: Vec<TreeCheckChange>) {
+    if changes.is_empty() {
+        return;
+    }
+
+    let Some(tree) = world.get::<TreeNode>(node).map(|node| node.tree) else {
+        return;
+    };
+
+    world.trigger(TreeChecksChanged {
+        entity: tree,
+        changes,
+    });
+}
+
+/// Toggles the check state of a tree node when its checkbox is activated.
+///
+/// A partially checked node becomes fully checked.
+pub(crate) fn on_checkbox_activated(
+    trigger: On<Activate>,
+    checkboxes: Query<&ChildOf, With<TreeCheckbox>>,
+    parents: Query<&ChildOf>,
+    nodes: Query<&TreeNode>,
+    mut commands: Commands,
+) {
+    let Ok(row) = checkboxes.get(trigger.event().entity) else {
+        return;
+    };
+
+    let Ok(node) = parents.get(row.parent()).map(ChildOf::parent) else {
+        return;
+    };
+
+    let Ok(tree_node) = nodes.get(node) else {
+        return;
+    };
+
+    let checked = !tree_node.check.is_checked();
+    commands.queue(move |world: &mut World| set_checked(world, node, checked));
+}
+
+/// Selects a tree node when its label is clicked, or collapses or expands it
+/// when its arrow is clicked. Clicking the arrow of an expanded node without
+/// children does nothing.
+///
+/// Clicks on the checkbox of the node are ignored.
+pub(crate) fn on_label_clicked(
+    trigger: On<Pointer<Click>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    labels: Query<&ChildOf, With<TreeNodeLabel>>,
+    checkboxes: Query<(), Or<(With<TreeCheckbox>, With<TreeCheckMark>)>>,
+    arrows: Query<(), With<TreeNodeArrow>>,
+    mut commands: Commands,
+) {
+    if trigger.event().button != PointerButton::Primary
+        || checkboxes.contains(trigger.original_event_target())
+    {
+        return;
+    }
+
+    let Ok(node) = labels.get(trigger.event().entity).map(ChildOf::parent) else {
+        return;
+    };
+
+    if arrows.contains(trigger.original_event_target()) {
+        commands.queue(move |world: &mut World| {
+            let Some(collapsed) = world.get::<TreeNode>(node).map(TreeNode::is_collapsed) else {
+                return;
+            };
+
+            let has_children = world.get::<Children>(node).is_some_and(|children| {
+                children
+                    .iter()
+                    .any(|child| world.get::<TreeNode>(child).is_some())
+            });
+
+            if collapsed || has_children {
+                set_collapsed(world, node, !collapsed);
+            }
+        });
+        return;
+    }
+
+    let mode = SelectMode::from_keys(&keys);
+    commands.queue(move |world: &mut World| select_node(world, node, mode));
+}
+
+/// Resizes the checkbox marks of tree nodes whose check state has changed.
+pub(crate) fn update_check_marks(
+    mut marks: Query<(&mut Node, &ChildOf), With<TreeCheckMark>>,
+    parents: Query<&ChildOf>,
+    nodes: Query<Ref<TreeNode>>,
+) {
+    for (mut mark, checkbox) in marks.iter_mut() {
+        let Some(node) = parents
+            .iter_ancestors(checkbox.parent())
+            .nth(1)
+            .and_then(|node| nodes.get(node).ok())
+        else {
+            continue;
+        };
+
+        if !node.is_changed() {
+            continue;
+        }
+
+        let (display, height) = check_mark_layout(node.check);
+        mark.display = display;
+        mark.height = height;
+    }
+}
+
+/// Gets the display and height of a checkbox mark for the given check state.
+/// A fully checked box is filled, while a partially checked box shows a bar.
+fn check_mark_layout(state: CheckState) -> (Display, Val) {
+    match state {
+        CheckState::Unchecked => (Display::None, percent(100.0)),
+        CheckState::Checked => (Display::Flex, percent(100.0)),
+        CheckState::Partial => (Display::Flex, percent(35.0)),
+    }
+}
+
+/// Shows the placeholders of tree views that have a [`Placeholders`]
+/// component, while their root has no nodes.
+pub(crate) fn update_tree_placeholders(
+    mut trees: Query<(Entity, &TreeView, &mut Placeholders)>,
+    children: Query<&Children>,
+    virtual_scrolls: Query<&VirtualScroll>,
+    mut nodes: Query<&mut Node>,
+    mut commands: Commands,
+) {
+    for (entity, tree, mut placeholders) in trees.iter_mut() {
+        let virtual_scroll = virtual_scrolls.get(entity).ok();
+        let (content, is_empty) = match (tree.root_node, virtual_scroll) {
+            (Some(root), _) => (
+                root,
+                children
+                    .get(root)
+                    .map_or(true, |children| children.is_empty()),
+            ),
+            (None, Some(virtual_scroll)) => match virtual_scroll.content() {
+                Some(content) => (content, virtual_scroll.item_count() == 0),
+                None => continue,
+            },
+            (None, None) => continue,
+        };
+
+        sync_placeholder(
+            entity,
+            &mut placeholders,
+            content,
+            is_empty,
+            &tree.theme,
+            &mut nodes,
+            &mut commands,
+        );
+    }
+}
+
+/// When a [`TreeView`] is added, set up its node properties.
+pub(crate) fn on_tree_added(
+    trigger: On<Add, TreeView>,
+    mut query: Query<(&mut Node, &mut TreeView)>,
+    mut commands: Commands,
+) {
+    let Ok((mut node, mut tree)) = query.get_mut(trigger.entity) else {
+        error!("Failed to query tree view node");
+        return;
+    };
+
+    node.display = Display::Flex;
+    node.flex_direction = FlexDirection::Column;
+    node.overflow = Overflow::scroll();
+    // node.scrollbar_width = 4.0;
+    node.scrollbar_width = 0.0;
+
+    commands
+        .entity(trigger.entity)
+        .insert(tree.theme.tree_view.container.clone());
+
+    if tree.provider.is_some() {
+        commands.entity(trigger.entity).insert((
+            VirtualScroll::rows(tree.theme.tree_view.container.icon_size)
+                .with_item_count(tree.init_count),
+            TreeRowSelection::default(),
+        ));
+        return;
+    }
+
+    let builder = tree.builder.take().unwrap_or_default();
+    tree.root_node = Some(build_tree_recursive(
+        &mut commands,
+        trigger.entity,
+        trigger.entity,
+        builder,
+        0,
+        &tree.theme,
+        tree.checkboxes,
+        false,
+        false,
+    ));
+}
+
+/// Recursively builds the tree nodes from the given builder.
+///
+/// The `inherited` flag is set if an ancestor of the node starts checked, and
+/// the `hidden` flag is set if the parent of the node starts collapsed.
+#[allow(clippy::too_many_arguments)]
+fn build_tree_recursive(
+    commands: &mut Commands,
+    tree: Entity,
+    parent: Entity,
+    builder: TreeNodeBuilder,
+    depth: u16,
+    theme: &UiTheme,
+    checkboxes: bool,
+    inherited: bool,
+    hidden: bool,
+) -> Entity {
+    let check = builder.initial_check(inherited);
+    let inherited = inherited || builder.content.checked;
+    let collapsed = depth > 0 && builder.content.collapsed;
+
+    let id = if depth == 0 {
+        commands
+            .spawn((
+                ChildOf(parent),
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                TreeNode {
+                    depth,
+                    tree,
+                    check,
+                    collapsed: false,
+                },
+            ))
+            .id()
+    } else {
+        let copy_text = builder.content.copy_text.clone();
+        let mut node_commands = commands.spawn(build_node(
+            parent,
+            tree,
+            builder.content,
+            depth,
+            theme,
+            !builder.children.is_empty(),
+            hidden,
+            check,
+            checkboxes,
+        ));
+        if let Some(copy_text) = copy_text {
+            node_commands.insert(ClipboardText(copy_text));
+        }
+        node_commands.id()
+    };
+
+    for child_builder in builder.children {
+        build_tree_recursive(
+            commands,
+            tree,
+            id,
+            child_builder,
+            depth + 1,
+            theme,
+            checkboxes,
+            inherited,
+            collapsed,
+        );
+    }
+
+    id
+}
+
+/// Builds a single tree node bundle.
+///
+/// The `hidden` flag is set if the parent of the node is collapsed.
+#[allow(clippy::too_many_arguments)]
+fn build_node(
+    parent: Entity,
+    tree: Entity,
+    content: TreeNodeContent,
+    depth: u16,
+    theme: &UiTheme,
+    has_children: bool,
+    hidden: bool,
+    check: CheckState,
+    checkbox: bool,
+) -> impl Bundle {
+    let collapsed = content.collapsed;
+    let arrow_icon = arrow_icon(&theme.tree_view, has_children, collapsed);
+    let spacer_icon = theme.tree_view.spacer_icon.clone();
+    let icon_size = theme.tree_view.container.icon_size;
+    let label_theme = theme.tree_view.label.clone();
+    let badge_theme = theme.tree_view.badge.clone();
+
+    (
+        ChildOf(parent),
+        Node {
+            display: if hidden { Display::None } else { Display::Flex },
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        TreeNode {
+            depth,
+            tree,
+            check,
+            collapsed,
+        },
+        children![(
+            TreeNodeLabel,
+            Node {
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+            theme.tree_view.label.clone(),
+            InteractionSender,
+            Children::spawn(SpawnWith(move |parent: &mut RelatedSpawner<ChildOf>| {
                 for _ in 1 .. depth {
-                    parent.spawn((
-                        Node {
-                            width: px(icon_size),
-                            height: px(icon_size),
-                            ..default()
-                        },
-                        ImageNode {
-                            image: spacer_icon.clone(),
-                            ..default()
-                        },
-                        InteractiveColor::<ImageNode>::from(&label_theme.icon_color),
-                    ));
+                    parent.spawn(spacer_node(&spacer_icon, icon_size, &label_theme));
                 }
 
                 parent.spawn((
+                    TreeNodeArrow,
                     Node {
                         width: px(icon_size),
                         height: px(icon_size),
                         ..default()
                     },
                     ImageNode {
-                        image: match (has_children, is_collapsed) {
-                            (true, false) => right_arrow_icon,
-                            (true, true) => down_arrow_icon,
-                            (false, _) => spacer_icon.clone(),
-                        },
+                        image: arrow_icon,
                         ..default()
                     },
                     InteractiveColor::<ImageNode>::from(&label_theme.icon_color),
                 ));
 
+                if checkbox {
+                    let (display, height) = check_mark_layout(check);
+                    parent.spawn((
+                        TreeCheckbox,
+                        Button,
+                        Node {
+                            width: px(icon_size),
+                            height: px(icon_size),
+                            border: UiRect::all(px(CHECKBOX_BORDER)),
+                            padding: UiRect::all(px(CHECKBOX_BORDER)),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        InteractiveColor::<BorderColor>::from(&label_theme.icon_color),
+                        children![(
+                            TreeCheckMark,
+                            Node {
+                                display,
+                                width: percent(100.0),
+                                height,
+                                ..default()
+                            },
+                            InteractiveColor::<BackgroundColor>::from(&label_theme.icon_color),
+                        )],
+                    ));
+                }
+
                 if let Some(icon) = content.icon {
                     parent.spawn((
                         Node {
@@ -400,8 +1507,397 @@ fn build_node(
                     ));
                 }
 
-                parent.spawn((Text::from(content.text), label_theme.text.clone()));
+                let text_theme = FontTheme {
+                    overflow: content.overflow.unwrap_or(label_theme.text.overflow),
+                    ..label_theme.text.clone()
+                };
+                parent.spawn((
+                    Text::from(content.text),
+                    Node {
+                        flex_grow: 1.0,
+                        ..default()
+                    },
+                    text_theme,
+                ));
+
+                let (display, badge) = badge_layout(content.badge);
+                let badge_text = badge_theme.text.clone();
+                parent.spawn((
+                    TreeNodeBadge,
+                    Node {
+                        display,
+                        align_self: AlignSelf::Center,
+                        flex_shrink: 0.0,
+                        margin: UiRect::left(px(4.0)),
+                        ..default()
+                    },
+                    badge_theme,
+                    children![(Text::new(badge), badge_text)],
+                ));
             })),
         ),],
     )
 }
+
+/// Fills in an item node of a tree view with a provider, once it is bound to a
+/// row.
+pub(crate) fn on_bind_tree_row(
+    mut trigger: On<BindVirtualItem>,
+    trees: Query<&TreeView>,
+    children: Query<&Children>,
+    mut commands: Commands,
+) {
+    let tree_id = trigger.event().entity;
+    let Ok(tree) = trees.get(tree_id) else {
+        return;
+    };
+
+    trigger.propagate(false);
+    let Some(provider) = &tree.provider else {
+        return;
+    };
+
+    let item = trigger.original_event_target();
+    let index = trigger.event().index;
+    let row = provider.get(index);
+
+    let mut item_commands = commands.entity(item);
+    match row.content.copy_text.clone() {
+        Some(copy_text) => {
+            item_commands.insert(ClipboardText(copy_text));
+        }
+        None => {
+            item_commands.remove::<ClipboardText>();
+        }
+    }
+
+    match children
+        .get(item)
+        .ok()
+        .and_then(|children| children.first().copied())
+    {
+        Some(label) => {
+            commands.queue(move |world: &mut World| bind_row(world, item, label, index, row));
+        }
+        None => {
+            commands.spawn(build_row(item, tree_id, index, row, &tree.theme));
+        }
+    }
+}
+
+/// Shows a row in an existing row label of a tree view with a provider.
+///
+/// If the text of the row overflows differently, the label is replaced
+/// instead, as the overflow of a label cannot be changed after it is spawned.
+fn bind_row(world: &mut World, item: Entity, label: Entity, index: usize, row: TreeRow) {
+    let Some(mut row_label) = world.get_mut::<TreeRowLabel>(label) else {
+        return;
+    };
+    row_label.index = index;
+    let tree = row_label.tree;
+
+    let Some(theme) = world.get::<TreeView>(tree).map(|tree| tree.theme.clone()) else {
+        return;
+    };
+
+    let Some(&[indent, arrow, icon, text, badge]) =
+        world.get::<Children>(label).map(|children| &children[..])
+    else {
+        return;
+    };
+
+    let overflow = row
+        .content
+        .overflow
+        .unwrap_or(theme.tree_view.label.text.overflow);
+    if world
+        .get::<FontTheme>(text)
+        .is_none_or(|text_theme| text_theme.overflow != overflow)
+    {
+        world.despawn(label);
+        world.spawn(build_row(item, tree, index, row, &theme));
+        return;
+    }
+
+    let label_theme = theme.tree_view.label.clone();
+    let spacer_icon = theme.tree_view.spacer_icon.clone();
+    let icon_size = theme.tree_view.container.icon_size;
+    world
+        .entity_mut(indent)
+        .despawn_children()
+        .with_children(|parent| {
+            for _ in 1 .. row.depth {
+                parent.spawn(spacer_node(&spacer_icon, icon_size, &label_theme));
+            }
+        });
+
+    let collapsed = row.content.collapsed;
+    if let Some(mut image) = world.get_mut::<ImageNode>(arrow) {
+        image.image = arrow_icon(&theme.tree_view, row.has_children, collapsed);
+    }
+
+    let (display, image) = icon_layout(row.content.icon);
+    if let Some(mut node) = world.get_mut::<Node>(icon) {
+        node.display = display;
+    }
+    if let Some(mut icon) = world.get_mut::<ImageNode>(icon) {
+        icon.image = image;
+    }
+
+    if let Some(mut text) = world.get_mut::<Text>(text) {
+        text.0 = row.content.text;
+    }
+
+    let (display, badge_text) = badge_layout(row.content.badge);
+    if let Some(mut node) = world.get_mut::<Node>(badge) {
+        node.display = display;
+    }
+
+    let Some(badge_text_id) = world
+        .get::<Children>(badge)
+        .and_then(|children| children.first().copied())
+    else {
+        return;
+    };
+
+    if let Some(mut text) = world.get_mut::<Text>(badge_text_id) {
+        text.0 = badge_text;
+    }
+}
+
+/// Gets the display mode and image of the icon of a row label.
+fn icon_layout(icon: Option<Handle<Image>>) -> (Display, Handle<Image>) {
+    match icon {
+        Some(icon) => (Display::Flex, icon),
+        None => (Display::None, Handle::default()),
+    }
+}
+
+/// Triggers a [`TreeRowClicked`] event when the label of a row of a tree view
+/// with a provider is clicked, and selects the row unless its arrow was
+/// clicked.
+pub(crate) fn on_row_clicked(
+    trigger: On<Pointer<Click>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    labels: Query<(&TreeRowLabel, &ChildOf)>,
+    items: Query<&VirtualItem>,
+    arrows: Query<(), With<TreeNodeArrow>>,
+    mut commands: Commands,
+) {
+    if trigger.event().button != PointerButton::Primary {
+        return;
+    }
+
+    let Ok((label, item)) = labels.get(trigger.event().entity) else {
+        return;
+    };
+
+    let Some(index) = items.get(item.parent()).ok().and_then(VirtualItem::index) else {
+        return;
+    };
+
+    let tree = label.tree;
+    let arrow = arrows.contains(trigger.original_event_target());
+    commands.trigger(TreeRowClicked {
+        entity: tree,
+        index,
+        arrow,
+    });
+
+    if !arrow {
+        let mode = SelectMode::from_keys(&keys);
+        commands.queue(move |world: &mut World| select_row(world, tree, index, mode));
+    }
+}
+
+/// Changes the selection of a tree view with a provider after a click on one
+/// of its rows.
+///
+/// Rows are listed in order by the provider, so a range of rows is the range
+/// of their indices.
+fn select_row(world: &mut World, tree: Entity, index: usize, mode: SelectMode) {
+    let Some(mut selection) = world.get_mut::<TreeRowSelection>(tree) else {
+        return;
+    };
+
+    let mut selected = selection.selected.clone();
+    let mut anchor = index;
+    match mode {
+        SelectMode::Replace => selected = vec![index],
+        SelectMode::Toggle => match selected.iter().position(|&row| row == index) {
+            Some(position) => {
+                selected.remove(position);
+            }
+            None => selected.push(index),
+        },
+        SelectMode::Range | SelectMode::ExtendRange => {
+            anchor = selection.anchor.unwrap_or(index);
+            let range = anchor.min(index) ..= anchor.max(index);
+
+            if mode == SelectMode::Range {
+                selected = range.collect();
+            } else {
+                for row in range {
+                    if !selected.contains(&row) {
+                        selected.push(row);
+                    }
+                }
+            }
+        }
+    }
+
+    selection.anchor = Some(anchor);
+    if selection.selected == selected {
+        return;
+    }
+
+    selection.selected = selected.clone();
+    world.trigger(TreeRowsSelected {
+        entity: tree,
+        selected,
+    });
+}
+
+/// Shows the rows of each tree view with a provider in their checked color
+/// while they are selected.
+///
+/// Rows are updated when the selection of their tree view changes, and when
+/// they are spawned or bound to another row.
+pub(crate) fn show_selected_rows(
+    selections: Query<Ref<TreeRowSelection>>,
+    mut labels: Query<(Ref<TreeRowLabel>, &mut Propagate<InteractionReceiver>)>,
+) {
+    for (label, mut interaction) in labels.iter_mut() {
+        let Ok(selection) = selections.get(label.tree) else {
+            continue;
+        };
+
+        if !selection.is_changed() && !label.is_changed() {
+            continue;
+        }
+
+        let checked = selection.is_selected(label.index);
+        if interaction.0.is_checked() != checked {
+            interaction.0 = interaction.0.with_checked(checked);
+        }
+    }
+}
+
+/// Builds the label of a row of a tree view with a provider, as a child of the
+/// given item node.
+///
+/// The children of the label are always the indent, arrow, icon, text and
+/// badge, in that order, so that they can be updated in place.
+fn build_row(
+    item: Entity,
+    tree: Entity,
+    index: usize,
+    row: TreeRow,
+    theme: &UiTheme,
+) -> impl Bundle {
+    let arrow_icon = arrow_icon(&theme.tree_view, row.has_children, row.content.collapsed);
+    let spacer_icon = theme.tree_view.spacer_icon.clone();
+    let icon_size = theme.tree_view.container.icon_size;
+    let label_theme = theme.tree_view.label.clone();
+    let badge_theme = theme.tree_view.badge.clone();
+    let (icon_display, icon) = icon_layout(row.content.icon);
+    let (badge_display, badge) = badge_layout(row.content.badge);
+    let badge_text = badge_theme.text.clone();
+    let text_theme = FontTheme {
+        overflow: row.content.overflow.unwrap_or(label_theme.text.overflow),
+        ..label_theme.text.clone()
+    };
+
+    (
+        ChildOf(item),
+        TreeRowLabel { tree, index },
+        Node {
+            flex_direction: FlexDirection::Row,
+            ..default()
+        },
+        theme.tree_view.label.clone(),
+        InteractionSender,
+        children![
+            (
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                },
+                Children::spawn(SpawnWith({
+                    let label_theme = label_theme.clone();
+                    move |parent: &mut RelatedSpawner<ChildOf>| {
+                        for _ in 1 .. row.depth {
+                            parent.spawn(spacer_node(&spacer_icon, icon_size, &label_theme));
+                        }
+                    }
+                })),
+            ),
+            (
+                TreeNodeArrow,
+                Node {
+                    width: px(icon_size),
+                    height: px(icon_size),
+                    ..default()
+                },
+                ImageNode {
+                    image: arrow_icon,
+                    ..default()
+                },
+                InteractiveColor::<ImageNode>::from(&label_theme.icon_color),
+            ),
+            (
+                Node {
+                    display: icon_display,
+                    width: px(icon_size),
+                    height: px(icon_size),
+                    ..default()
+                },
+                ImageNode {
+                    image: icon,
+                    ..default()
+                },
+                InteractiveColor::<ImageNode>::from(&label_theme.icon_color),
+            ),
+            (
+                Text::from(row.content.text),
+                Node {
+                    flex_grow: 1.0,
+                    ..default()
+                },
+                text_theme,
+            ),
+            (
+                TreeNodeBadge,
+                Node {
+                    display: badge_display,
+                    align_self: AlignSelf::Center,
+                    flex_shrink: 0.0,
+                    margin: UiRect::left(px(4.0)),
+                    ..default()
+                },
+                badge_theme,
+                children![(Text::new(badge), badge_text)],
+            ),
+        ],
+    )
+}
+
+/// Builds a spacer icon, used to indent tree node labels by one level.
+fn spacer_node(
+    spacer_icon: &Handle<Image>,
+    icon_size: f32,
+    label_theme: &ContainerTheme,
+) -> impl Bundle {
+    (
+        Node {
+            width: px(icon_size),
+            height: px(icon_size),
+            ..default()
+        },
+        ImageNode {
+            image: spacer_icon.clone(),
+            ..default()
+        },
+        InteractiveColor::<ImageNode>::from(&label_theme.icon_color),
+    )
+}
@@ -0,0 +1,209 @@
+//! The context menu of the asset grid.
+//!
+//! Right-clicking a grid cell opens a menu at the cursor with actions for the
+//! asset of the cell, such as copying its ID or pinning it as a favorite. The
+//! menu closes when one of its entries is activated, when Escape is pressed,
+//! or when the pointer is pressed anywhere outside of it.
+
+use awgen_asset_db::prelude::*;
+use awgen_ui::prelude::*;
+use awgen_ui::themes::hearth_theme;
+use bevy::picking::hover::HoverMap;
+use bevy::prelude::*;
+
+use crate::recent::AssetUsageChanged;
+use crate::{AssetCells, ProjectDatabase};
+
+/// Plugin that adds the context menu of the asset grid.
+pub struct AssetContextMenuPlugin;
+impl Plugin for AssetContextMenuPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<AssetContextMenu>()
+            .add_observer(open_context_menu)
+            .add_observer(on_entry_activate)
+            .add_systems(Update, close_context_menu);
+    }
+}
+
+/// A resource holding the open context menu, if any.
+#[derive(Debug, Default, Resource)]
+struct AssetContextMenu(Option<Entity>);
+
+/// An action in the context menu of an asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContextAction {
+    /// Copies the full ID of the asset to the clipboard.
+    CopyId,
+
+    /// Pins the asset as a favorite.
+    PinFavorite,
+
+    /// Unpins the asset from the favorites.
+    UnpinFavorite,
+}
+
+impl ContextAction {
+    /// Gets the label of the menu entry of this action.
+    fn label(self) -> &'static str {
+        match self {
+            ContextAction::CopyId => "Copy ID",
+            ContextAction::PinFavorite => "Pin to Favorites",
+            ContextAction::UnpinFavorite => "Unpin from Favorites",
+        }
+    }
+}
+
+/// A component on each entry of the context menu.
+#[derive(Debug, Component)]
+struct ContextEntry {
+    /// The asset that the entry acts on.
+    asset: AssetRecordID,
+
+    /// The action of the entry.
+    action: ContextAction,
+}
+
+/// Opens the context menu of a grid cell when it is right-clicked, replacing
+/// any menu that is already open.
+#[allow(clippy::too_many_arguments)]
+fn open_context_menu(
+    mut click: On<Pointer<Click>>,
+    cells: Query<&GridCell>,
+    asset_cells: Res<AssetCells>,
+    overlay: Query<Entity, With<OverlayRoot>>,
+    asset_server: Res<AssetServer>,
+    asset_db: AwgenAssets<ProjectDatabase>,
+    mut menu: ResMut<AssetContextMenu>,
+    mut sounds: MessageWriter<PlayUiSound>,
+    mut commands: Commands,
+) {
+    if click.button != PointerButton::Secondary {
+        return;
+    }
+
+    let Ok(cell) = cells.get(click.entity) else {
+        return;
+    };
+    click.propagate(false);
+
+    let Some(id) = asset_cells.get(cell) else {
+        return;
+    };
+
+    let Ok(overlay) = overlay.single() else {
+        error!("Failed to open the context menu: no OverlayRoot found");
+        return;
+    };
+
+    let favorite = match asset_db.is_favorite(id) {
+        Ok(true) => ContextAction::UnpinFavorite,
+        Ok(false) => ContextAction::PinFavorite,
+        Err(e) => {
+            error!("Failed to check whether asset {} is a favorite: {}", id, e);
+            ContextAction::PinFavorite
+        }
+    };
+
+    if let Some(open) = menu.0.take() {
+        commands.entity(open).despawn();
+    }
+
+    let theme = hearth_theme(&asset_server);
+    let position = click.pointer_location.position;
+    let menu_id = commands
+        .spawn((
+            ChildOf(overlay),
+            Node {
+                position_type: PositionType::Absolute,
+                left: px(position.x),
+                top: px(position.y),
+                flex_direction: FlexDirection::Column,
+                row_gap: px(2.0),
+                ..default()
+            },
+            theme.inner_window.clone(),
+            UiLayer::Menus,
+        ))
+        .id();
+
+    for action in [ContextAction::CopyId, favorite] {
+        commands.spawn((
+            ChildOf(menu_id),
+            ContextEntry { asset: id, action },
+            button(ButtonBuilder {
+                node: Node::default(),
+                content: ButtonContent::text(action.label()),
+                theme: theme.clone(),
+            }),
+        ));
+    }
+
+    menu.0 = Some(menu_id);
+    sounds.write(PlayUiSound(UiSound::OpenMenu));
+}
+
+/// Runs the action of a context menu entry when it is activated, and closes
+/// the menu.
+fn on_entry_activate(
+    trigger: On<Activate>,
+    entries: Query<&ContextEntry>,
+    asset_db: AwgenAssets<ProjectDatabase>,
+    mut clipboard: ResMut<Clipboard>,
+    mut menu: ResMut<AssetContextMenu>,
+    mut usage_changes: MessageWriter<AssetUsageChanged>,
+    mut commands: Commands,
+) {
+    let Ok(entry) = entries.get(trigger.event().entity) else {
+        return;
+    };
+
+    if let Some(open) = menu.0.take() {
+        commands.entity(open).despawn();
+    }
+
+    let id = entry.asset;
+    match entry.action {
+        ContextAction::CopyId => match clipboard.copy(id.to_string()) {
+            Ok(()) => debug!("Copied asset ID to clipboard: {}", id),
+            Err(e) => error!("Failed to copy asset ID to clipboard: {}", e),
+        },
+        ContextAction::PinFavorite => match asset_db.pin_favorite(id) {
+            Ok(()) => {
+                usage_changes.write(AssetUsageChanged);
+            }
+            Err(e) => error!("Failed to pin asset {} as a favorite: {}", id, e),
+        },
+        ContextAction::UnpinFavorite => match asset_db.unpin_favorite(id) {
+            Ok(()) => {
+                usage_changes.write(AssetUsageChanged);
+            }
+            Err(e) => error!("Failed to unpin asset {} from the favorites: {}", id, e),
+        },
+    }
+}
+
+/// Closes the context menu when Escape is pressed, or when the pointer is
+/// pressed outside of it.
+fn close_context_menu(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    hover_map: Res<HoverMap>,
+    parents: Query<&ChildOf>,
+    mut menu: ResMut<AssetContextMenu>,
+    mut commands: Commands,
+) {
+    let Some(open) = menu.0 else {
+        return;
+    };
+
+    let pressed_outside = mouse_buttons.get_just_pressed().next().is_some()
+        && !hover_map
+            .values()
+            .flat_map(|pointer_map| pointer_map.keys().copied())
+            .any(|entity| entity == open || parents.iter_ancestors(entity).any(|e| e == open));
+
+    if keys.just_pressed(KeyCode::Escape) || pressed_outside {
+        commands.entity(open).despawn();
+        menu.0 = None;
+    }
+}
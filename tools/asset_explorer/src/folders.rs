@@ -0,0 +1,100 @@
+//! The asset count badges of the sidebar tree.
+//!
+//! The sidebar lists a folder for each asset module and each asset type, with
+//! the number of assets in it shown as a badge. The assets are counted by the
+//! project database on startup, and counted again whenever an asset record
+//! changes, so no asset records need to be read.
+//!
+//! Type folders are listed while they have assets. Module folders are kept
+//! while empty.
+
+use awgen_asset_db::prelude::*;
+use bevy::prelude::*;
+
+use crate::ProjectDatabase;
+
+/// Plugin that keeps the asset counts of the sidebar tree up to date.
+pub struct AssetFoldersPlugin;
+impl Plugin for AssetFoldersPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<AssetFolders>()
+            .add_systems(Update, update_folder_counts);
+    }
+}
+
+/// A resource holding the asset counts of the sidebar tree.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Resource)]
+pub struct AssetFolders {
+    /// The number of assets in all asset modules.
+    all: usize,
+
+    /// The name and number of assets of each asset module, in the order they
+    /// are listed.
+    modules: Vec<(String, usize)>,
+
+    /// The number of assets of each asset type that has any assets, in the
+    /// order they are listed.
+    types: Vec<(String, usize)>,
+}
+
+impl AssetFolders {
+    /// Counts the assets of the project database by module and by type.
+    pub fn load(asset_db: &AwgenAssets<ProjectDatabase>) -> Result<Self, AwgenAssetsError> {
+        let mut modules = Vec::new();
+        for module in asset_db.list_modules()? {
+            let filter = AssetFilter {
+                module: Some(module.id),
+                ..default()
+            };
+            modules.push((module.name, asset_db.count_assets_filtered(&filter)?));
+        }
+        modules.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(Self {
+            all: asset_db.count_assets_filtered(&AssetFilter::default())?,
+            modules,
+            types: asset_db.count_assets_by_type()?,
+        })
+    }
+
+    /// Gets the number of assets in all asset modules.
+    pub fn total(&self) -> usize {
+        self.all
+    }
+
+    /// Gets the name and number of assets of each asset module.
+    pub fn modules(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.modules
+            .iter()
+            .map(|(name, count)| (name.as_str(), *count))
+    }
+
+    /// Gets the name and number of assets of each asset type that has any
+    /// assets.
+    pub fn types(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.types
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(name, count)| (name.as_str(), *count))
+    }
+}
+
+/// Counts the assets again when an asset record changes.
+///
+/// The sidebar is only listed again if the counts actually changed.
+fn update_folder_counts(
+    mut changes: MessageReader<AssetRecordChanged<ProjectDatabase>>,
+    asset_db: AwgenAssets<ProjectDatabase>,
+    mut folders: ResMut<AssetFolders>,
+) {
+    if changes.read().count() == 0 {
+        return;
+    }
+
+    match AssetFolders::load(&asset_db) {
+        Ok(counts) => {
+            folders.set_if_neq(counts);
+        }
+        Err(e) => error!("Failed to count assets: {}", e),
+    }
+}
@@ -1,19 +1,64 @@
 //! The implementation for the Awgen AssetExplorer tool.
+//!
+//! The zoom level of the asset grid is stored in the settings of the project
+//! asset database.
+//!
+//! Tilesets are composed by dragging image assets from the grid onto the
+//! tileset composer panel, and are saved as `.tiles` files in the assets folder
+//! of the project.
+//!
+//! The sidebar tree shows the number of assets in each folder as a badge, and
+//! keeps its recently used and favorite asset folders up to date.
+//!
+//! Both the sidebar tree and the asset grid request their rows and cells from
+//! a provider as they scroll into view, so only the visible ones have entities
+//! and previews are only loaded for the assets that are shown.
+//!
+//! The size, position, and maximized state of the window are stored in the
+//! project asset database and restored on the next launch. The asset grid can
+//! be maximized over the window with Shift+Space, or moved into its own window
+//! with the "Detach Asset Grid" command, for browsing assets on a second
+//! monitor.
+//!
+//! With `--read-only`, the asset database is opened without write access, so
+//! projects can be browsed without being modified.
 
 #![warn(missing_docs)]
 #![warn(clippy::missing_docs_in_private_items)]
 
+use std::cmp::Ordering;
 use std::path::PathBuf;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use awgen_asset_db::prelude::*;
 use awgen_ui::FOLDER_ICON;
 use awgen_ui::prelude::*;
 use awgen_ui::themes::hearth_theme;
-use awgen_ui::widgets::grid_preview::GridPreview;
+use awgen_ui::widgets::command_palette::{PaletteCommand, RegisterCommandExt};
+use awgen_ui::widgets::grid_preview::{
+    GridCell,
+    GridPreview,
+    GridSort,
+    GridZoom,
+    GridZoomSettings,
+};
 use bevy::log::{Level, LogPlugin};
 use bevy::prelude::*;
+use bevy::ui::CalculatedClip;
 use clap::{Parser, command};
 
+use crate::folders::AssetFolders;
+use crate::recent::{AssetUsageChanged, RecentFolders};
+use crate::sidebar::{AssetFolderTree, SidebarRows};
+
+mod context_menu;
+mod folders;
+mod previews;
+mod recent;
+mod sidebar;
+mod tileset;
+mod window_state;
+
 /// The arguments for the command line interface.
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
@@ -21,8 +66,36 @@ struct Args {
     /// The project folder.
     #[arg(long)]
     project: PathBuf,
+
+    /// Generates the previews of all assets that have none without opening a
+    /// window, then exits.
+    #[arg(long, default_value_t = false)]
+    generate_previews: bool,
+
+    /// The maximum number of previews generated at once by
+    /// `--generate-previews`. Defaults to half of the available threads.
+    #[arg(long)]
+    jobs: Option<usize>,
+
+    /// Opens the asset database in read-only mode, for browsing the assets of
+    /// a project without modifying it.
+    #[arg(long, default_value_t = false)]
+    read_only: bool,
 }
 
+/// The key that the zoom level of the asset grid is stored under.
+const GRID_ZOOM_KEY: &str = "asset_explorer";
+
+/// The asset database setting that stores the zoom level of the asset grid.
+const GRID_ZOOM_SETTING: &str = "asset_explorer.grid_zoom";
+
+/// The maximum number of assets listed in the "Recent" folder.
+const RECENT_LIMIT: usize = 20;
+
+/// The number of asset records read from the project database at a time when
+/// listing the assets of the asset grid.
+const ASSET_PAGE_SIZE: usize = 256;
+
 /// The project asset database identifier.
 pub struct ProjectDatabase;
 impl AssetDatabaseName for ProjectDatabase {
@@ -31,11 +104,27 @@ impl AssetDatabaseName for ProjectDatabase {
     }
 }
 
-fn main() {
+fn main() -> AppExit {
     let args = Args::parse();
+    if args.generate_previews {
+        if args.read_only {
+            eprintln!("Cannot generate previews in read-only mode.");
+            return AppExit::from_code(1);
+        }
+        return previews::generate_previews(args.project, args.jobs);
+    }
+
+    let ui_assets = args.project.join("editor/assets/ui");
+    let assets_folder = args.project.join("assets");
 
-    App::new()
-        .register_asset_db::<ProjectDatabase, _>(args.project)
+    let mut app = App::new();
+    if args.read_only {
+        app.register_read_only_asset_db::<ProjectDatabase, _>(args.project);
+    } else {
+        app.register_asset_db::<ProjectDatabase, _>(args.project);
+    }
+
+    app.register_ui_assets(Some(ui_assets))
         .add_plugins((
             DefaultPlugins.set(LogPlugin {
                 level: Level::DEBUG,
@@ -45,24 +134,61 @@ fn main() {
             }),
             AwgenAssetPlugin,
             AwgenUiPlugin,
+            tileset::TilesetComposerPlugin { assets_folder },
+            folders::AssetFoldersPlugin,
+            recent::RecentFoldersPlugin,
+            sidebar::SidebarPlugin,
+            context_menu::AssetContextMenuPlugin,
+            window_state::ExplorerWindowStatePlugin,
         ))
-        .add_systems(Startup, setup)
-        .run();
+        .add_systems(Startup, (load_grid_zoom, setup).chain())
+        .add_observer(on_cell_click)
+        .register_command(
+            PaletteCommand::new("explorer.grid.detach", "Detach Asset Grid"),
+            toggle_grid_detached,
+        )
+        .add_systems(
+            Update,
+            (
+                save_grid_zoom.run_if(resource_changed::<GridZoomSettings>),
+                sort_asset_cells,
+                prioritize_visible_previews,
+            ),
+        )
+        .run()
 }
 
 /// Initializes the asset explorer ui.
 fn setup(
     asset_server: Res<AssetServer>,
-    // asset_db: AwgenAssets<ProjectDatabase>,
+    asset_db: AwgenAssets<ProjectDatabase>,
     mut commands: Commands,
 ) {
-    // let assets = asset_db.list_assets().expect("Failed to list assets");
     let theme = hearth_theme(&asset_server);
-    let mut folders = tree_builder();
-    let previews = grid_preview_builder();
+    let counts = AssetFolders::load(&asset_db).unwrap_or_else(|e| {
+        error!("Failed to count assets: {}", e);
+        AssetFolders::default()
+    });
+    let recent = RecentFolders::load(&asset_db);
+    let sidebar = SidebarRows::new(asset_server.load(FOLDER_ICON), &counts, &recent);
+    let mut placeholders = Placeholders::empty(Placeholder::new("This project has no assets yet"));
+    let cells = AssetCells::load(&asset_db, GridSort::Name).unwrap_or_else(|e| {
+        error!("Failed to list assets: {}", e);
+        placeholders.error = Some(Placeholder::new(format!("Failed to list assets: {}", e)));
+        AssetCells::default()
+    });
 
-    set_icon_recursive(&mut folders, asset_server.load(FOLDER_ICON));
+    let tree = TreeView::from_provider(theme.clone(), sidebar.row_count(), sidebar.provider());
+    let grid = GridPreview::with_provider(
+        theme.clone(),
+        cells.len(),
+        cells.provider(asset_server.clone()),
+    );
 
+    commands.insert_resource(counts);
+    commands.insert_resource(recent);
+    commands.insert_resource(sidebar);
+    commands.insert_resource(cells);
     commands.spawn(Camera2d);
     commands.spawn((
         ScreenAnchor::Fullscreen,
@@ -78,93 +204,341 @@ fn setup(
                     width: percent(20.0),
                     ..default()
                 },
-                TreeView::from_builder(theme.clone(), folders),
+                tree,
+                AssetFolderTree,
             ),
             (
                 Node {
-                    width: percent(80.0),
+                    width: percent(55.0),
+                    ..default()
+                },
+                grid.with_zoom_key(GRID_ZOOM_KEY).with_sort(GridSort::Name),
+                placeholders,
+                Maximizable,
+            ),
+            tileset::composer_panel(
+                Node {
+                    width: percent(25.0),
                     ..default()
                 },
-                GridPreview::with_cells(theme, previews)
-            )
+                &theme,
+            ),
         ],
     ));
 }
 
-/// Builds a sample tree structure for the TreeView.
-fn tree_builder() -> TreeNodeBuilder {
-    TreeNodeBuilder {
-        content: TreeNodeContent::from("root"),
-        children: vec![
-            TreeNodeBuilder {
-                content: TreeNodeContent::from("child 1"),
-                children: vec![TreeNodeBuilder {
-                    content: TreeNodeContent::from("grandchild 1.1"),
-                    children: vec![],
-                }],
-            },
-            TreeNodeBuilder {
-                content: TreeNodeContent::from("child 2"),
-                children: vec![
-                    TreeNodeBuilder {
-                        content: TreeNodeContent::from("grandchild 2.1"),
-                        children: vec![],
-                    },
-                    TreeNodeBuilder {
-                        content: TreeNodeContent::from("grandchild 2.2"),
-                        children: vec![],
-                    },
-                    TreeNodeBuilder {
-                        content: TreeNodeContent::from("grandchild 2.3"),
-                        children: vec![],
-                    },
-                ],
-            },
-            TreeNodeBuilder {
-                content: TreeNodeContent::from("child 3"),
-                children: vec![
-                    TreeNodeBuilder {
-                        content: TreeNodeContent::from("grandchild 3.1"),
-                        children: vec![],
-                    },
-                    TreeNodeBuilder {
-                        content: TreeNodeContent::from("grandchild 3.2"),
-                        children: vec![
-                            TreeNodeBuilder {
-                                content: TreeNodeContent::from("great-grandchild 3.2.1"),
-                                children: vec![],
-                            },
-                            TreeNodeBuilder {
-                                content: TreeNodeContent::from("great-grandchild 3.2.2"),
-                                children: vec![],
-                            },
-                        ],
-                    },
-                    TreeNodeBuilder {
-                        content: TreeNodeContent::from("grandchild 3.3"),
-                        children: vec![],
-                    },
-                ],
-            },
-        ],
+/// Moves the asset grid into its own window, or docks it back into the main
+/// window if it is already detached.
+fn toggle_grid_detached(
+    grids: Query<(Entity, Has<Detached>), With<GridPreview>>,
+    mut commands: Commands,
+) {
+    let Ok((entity, detached)) = grids.single() else {
+        return;
+    };
+
+    if detached {
+        commands.trigger(DockPanel { entity });
+    } else {
+        commands.trigger(DetachPanel {
+            entity,
+            title: "Asset Grid".to_string(),
+        });
     }
 }
 
-/// Recursively sets the icon for a tree node and its children.
-fn set_icon_recursive(node: &mut TreeNodeBuilder, icon: Handle<Image>) {
-    node.content.icon = Some(icon.clone());
-    for child in &mut node.children {
-        set_icon_recursive(child, icon.clone());
-    }
+/// Gets the display name of an asset, which is its file name, or its short ID
+/// if it has none.
+fn asset_name(asset: &ErasedAssetRecord) -> String {
+    asset
+        .pathname
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| asset.id.short().to_string())
+}
+
+/// An asset shown in a cell of the asset grid.
+#[derive(Debug, Clone)]
+struct GridAsset {
+    /// The ID of the asset.
+    id: AssetRecordID,
+
+    /// The display name of the asset.
+    name: String,
+
+    /// The type of the asset.
+    asset_type: String,
+
+    /// The time that the asset was last modified, as a Unix timestamp.
+    modified: i64,
+
+    /// The asset path of the preview of the asset.
+    preview: String,
+
+    /// The asset path of the thumbnail of the asset, for image assets.
+    thumbnail: Option<String>,
 }
 
-/// Builds sample grid preview cells.
-fn grid_preview_builder() -> Vec<GridNodeBuilder> {
-    vec![
+impl GridAsset {
+    /// Builds the grid cell of this asset, loading its preview.
+    fn cell(&self, asset_server: &AssetServer) -> GridNodeBuilder {
         GridNodeBuilder {
-            icon: Handle::default(),
-            label: "Asset".into(),
+            icon: GridIcon::Deferred(asset_server.load(&self.preview)),
+            large_icon: self.thumbnail.clone(),
+            label: self.name.clone(),
+            copy_text: Some(self.id.to_string()),
+            overflow: None,
+            kind: Some(self.asset_type.clone()),
+            modified: Some(self.modified),
+        }
+    }
+
+    /// Compares two assets in the given sort order, in the same way that the
+    /// grid compares its cells.
+    fn compare(&self, other: &Self, sort: GridSort) -> Ordering {
+        let by_name = || {
+            self.name
+                .to_lowercase()
+                .cmp(&other.name.to_lowercase())
+                .then_with(|| self.id.cmp(&other.id))
+        };
+
+        match sort {
+            GridSort::Name => by_name(),
+            GridSort::Type => self.asset_type.cmp(&other.asset_type).then_with(by_name),
+            GridSort::Modified => other.modified.cmp(&self.modified).then_with(by_name),
+        }
+    }
+}
+
+/// A resource holding the assets shown in the asset grid, by cell index.
+///
+/// The assets are shared with the provider of the grid, and are kept in the
+/// sort order of the grid, as grids with a provider are not sorted in place.
+/// They can be sorted by file name, asset type and modified date.
+///
+/// Clicking a cell records a use of the asset, and right-clicking it opens a
+/// context menu to copy the asset ID or pin the asset as a favorite.
+#[derive(Debug, Default, Resource)]
+pub struct AssetCells {
+    /// The assets of the cells, in the order they are shown.
+    assets: Arc<RwLock<Vec<GridAsset>>>,
+
+    /// The order that the assets are sorted in.
+    sort: GridSort,
+}
+
+impl AssetCells {
+    /// Lists all assets in the project database, sorted in the given order.
+    ///
+    /// The asset records are read one page of [`ASSET_PAGE_SIZE`] records at a
+    /// time, so that large projects are never read in a single query.
+    fn load(
+        asset_db: &AwgenAssets<ProjectDatabase>,
+        sort: GridSort,
+    ) -> Result<Self, AwgenAssetsError> {
+        let filter = AssetFilter::default();
+        let mut assets = Vec::with_capacity(asset_db.count_assets_filtered(&filter)?);
+
+        loop {
+            let page = asset_db.list_assets_filtered(&filter, ASSET_PAGE_SIZE, assets.len())?;
+            let last_page = page.len() < ASSET_PAGE_SIZE;
+
+            assets.extend(page.into_iter().map(|asset| {
+                GridAsset {
+                    id: asset.id,
+                    name: asset_name(&asset),
+                    preview: asset_db.preview_path(asset.id),
+                    thumbnail: (asset.asset_type == Image::type_name())
+                        .then(|| asset_db.thumbnail_path(asset.id)),
+                    asset_type: asset.asset_type,
+                    modified: asset.last_modified,
+                }
+            }));
+
+            if last_page {
+                break;
+            }
+        }
+
+        let cells = Self {
+            assets: Arc::new(RwLock::new(assets)),
+            sort,
         };
-        10
-    ]
+        cells.write().sort_by(|a, b| a.compare(b, sort));
+        Ok(cells)
+    }
+
+    /// Gets the number of cells.
+    fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Gets the ID of the asset shown in the given grid cell.
+    pub fn get(&self, cell: &GridCell) -> Option<AssetRecordID> {
+        self.read().get(cell.index()).map(|asset| asset.id)
+    }
+
+    /// Creates a provider that supplies the cells of the asset grid, loading
+    /// the previews of the assets as their cells scroll into view.
+    fn provider(&self, asset_server: AssetServer) -> ItemProvider<GridNodeBuilder> {
+        let assets = self.assets.clone();
+        ItemProvider::new(move |index| {
+            let assets = assets.read().unwrap_or_else(PoisonError::into_inner);
+            match assets.get(index) {
+                Some(asset) => asset.cell(&asset_server),
+                None => GridNodeBuilder {
+                    icon: GridIcon::Ready(Handle::default()),
+                    large_icon: None,
+                    label: String::new(),
+                    copy_text: None,
+                    overflow: None,
+                    kind: None,
+                    modified: None,
+                },
+            }
+        })
+    }
+
+    /// Locks the assets for reading.
+    fn read(&self) -> RwLockReadGuard<'_, Vec<GridAsset>> {
+        self.assets.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the assets for writing.
+    fn write(&self) -> RwLockWriteGuard<'_, Vec<GridAsset>> {
+        self.assets.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Sorts the assets of the asset grid again when the sort order of the grid
+/// changes, and shows the cells in their new order.
+fn sort_asset_cells(
+    grids: Query<&GridPreview, Changed<GridPreview>>,
+    mut asset_cells: ResMut<AssetCells>,
+    mut virtual_scrolls: Query<&mut VirtualScroll>,
+) {
+    for grid in grids.iter() {
+        let Some(sort) = grid.sort() else {
+            continue;
+        };
+
+        if asset_cells.sort == sort {
+            continue;
+        }
+
+        asset_cells.sort = sort;
+        asset_cells.write().sort_by(|a, b| a.compare(b, sort));
+
+        if let Some(mut virtual_scroll) = grid
+            .panel()
+            .and_then(|panel| virtual_scrolls.get_mut(panel).ok())
+        {
+            virtual_scroll.refresh();
+        }
+    }
+}
+
+/// Moves the queued previews of the grid cells that came into view ahead of
+/// any bulk preview generation, so that the visible thumbnails load first.
+///
+/// Cells are checked again whenever they move, such as when the grid is
+/// scrolled.
+fn prioritize_visible_previews(
+    cells: Query<
+        (
+            &GridCell,
+            &ComputedNode,
+            &UiGlobalTransform,
+            Option<&CalculatedClip>,
+        ),
+        Changed<UiGlobalTransform>,
+    >,
+    asset_cells: Res<AssetCells>,
+    mut asset_db: AwgenAssets<ProjectDatabase>,
+) {
+    if asset_db.pending_previews() == 0 {
+        return;
+    }
+
+    for (cell, computed, transform, clip) in cells.iter() {
+        let size = computed.size();
+        if size.x <= 0.0 || size.y <= 0.0 {
+            continue;
+        }
+
+        let bounds = Rect::from_center_size(transform.translation, size);
+        if clip.is_some_and(|clip| clip.clip.intersect(bounds).is_empty()) {
+            continue;
+        }
+
+        if let Some(id) = asset_cells.get(cell) {
+            asset_db.prioritize_preview(id);
+        }
+    }
+}
+
+/// Records a use of the asset of a grid cell when it is clicked.
+fn on_cell_click(
+    mut click: On<Pointer<Click>>,
+    cells: Query<&GridCell>,
+    asset_cells: Res<AssetCells>,
+    asset_db: AwgenAssets<ProjectDatabase>,
+    mut usage_changes: MessageWriter<AssetUsageChanged>,
+) {
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    let Ok(cell) = cells.get(click.entity) else {
+        return;
+    };
+    click.propagate(false);
+
+    let Some(id) = asset_cells.get(cell) else {
+        return;
+    };
+
+    match asset_db.mark_asset_used(id) {
+        Ok(()) => {
+            usage_changes.write(AssetUsageChanged);
+        }
+        Err(e) => error!("Failed to update the usage of asset {}: {}", id, e),
+    }
+}
+
+/// Loads the zoom level of the asset grid from the asset database settings,
+/// before the grid is created.
+fn load_grid_zoom(asset_db: AwgenAssets<ProjectDatabase>, mut settings: ResMut<GridZoomSettings>) {
+    let name = match asset_db.setting(GRID_ZOOM_SETTING) {
+        Ok(Some(name)) => name,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Failed to load the grid zoom level: {}", e);
+            return;
+        }
+    };
+
+    match GridZoom::from_name(&name) {
+        Some(zoom) => {
+            settings.insert(GRID_ZOOM_KEY.to_string(), zoom);
+        }
+        None => warn!("Unknown grid zoom level: {}", name),
+    }
+}
+
+/// Stores the zoom level of the asset grid in the asset database settings
+/// when it changes.
+fn save_grid_zoom(settings: Res<GridZoomSettings>, asset_db: AwgenAssets<ProjectDatabase>) {
+    if asset_db.is_read_only() {
+        return;
+    }
+
+    let Some(zoom) = settings.get(GRID_ZOOM_KEY) else {
+        return;
+    };
+
+    if let Err(e) = asset_db.set_setting(GRID_ZOOM_SETTING, zoom.name()) {
+        error!("Failed to store the grid zoom level: {}", e);
+    }
 }
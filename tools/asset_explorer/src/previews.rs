@@ -0,0 +1,205 @@
+//! Headless batch generation of missing asset previews.
+//!
+//! When the asset explorer is started with `--generate-previews`, no window is
+//! opened. Instead, every asset in the project database without a preview has
+//! its preview generated, with a limited number of previews generated at once,
+//! and the tool exits once all previews are saved. This is useful for preparing
+//! content packs before they are shipped.
+//!
+//! Only image previews can be generated. Assets of other types without a
+//! preview are reported and counted as failures, and the tool exits with a
+//! failure code if any preview could not be generated.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use awgen_asset_db::AwgenAssetSystems;
+use awgen_asset_db::prelude::*;
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::log::{Level, LogPlugin};
+use bevy::prelude::*;
+
+use crate::ProjectDatabase;
+
+/// The width of the progress bar, in characters.
+const PROGRESS_BAR_WIDTH: usize = 30;
+
+/// The time between updates while waiting for previews to be generated.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The assets whose previews are still to be generated.
+#[derive(Debug, Resource)]
+struct PreviewBatch {
+    /// The assets whose preview tasks have not been queued yet.
+    remaining: Vec<AssetRecordID>,
+
+    /// The number of assets in the batch.
+    total: usize,
+
+    /// The maximum number of previews generated at once.
+    jobs: usize,
+
+    /// The number of finished previews when progress was last printed.
+    last_printed: Option<usize>,
+
+    /// The number of assets whose previews could not be generated, not
+    /// counting failed image previews, which are found once the batch is done.
+    failures: usize,
+}
+
+/// Runs the asset explorer headlessly, generating all missing previews of the
+/// project database at the given path.
+///
+/// At most `jobs` previews are generated at once. By default, half of the
+/// available threads are used.
+pub fn generate_previews(project: PathBuf, jobs: Option<usize>) -> AppExit {
+    let mut app = App::new();
+    app.register_asset_db::<ProjectDatabase, _>(project)
+        .add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(UPDATE_INTERVAL)),
+            LogPlugin {
+                level: Level::WARN,
+                ..default()
+            },
+            AssetPlugin::default(),
+            AwgenAssetPlugin,
+        ))
+        .init_asset::<Image>();
+
+    let mut tasks = app.world_mut().resource_mut::<AssetDatabaseTasks>();
+    if let Some(jobs) = jobs {
+        tasks.set_max_running_previews(jobs);
+    }
+
+    let jobs = tasks.max_running_previews();
+    app.insert_resource(PreviewBatch {
+        remaining: Vec::new(),
+        total: 0,
+        jobs,
+        last_printed: None,
+        failures: 0,
+    });
+
+    app.add_systems(Startup, find_missing_previews)
+        .add_systems(
+            Update,
+            (
+                queue_previews.before(AwgenAssetSystems::TaskPolling),
+                report_progress.after(AwgenAssetSystems::TaskPolling),
+            ),
+        )
+        .run()
+}
+
+/// Lists the assets that have no preview yet, and rejects the assets of types
+/// that previews cannot be generated for.
+fn find_missing_previews(asset_db: AwgenAssets<ProjectDatabase>, mut batch: ResMut<PreviewBatch>) {
+    match asset_db.count_assets_missing_preview_by_type() {
+        Ok(counts) => {
+            for (asset_type, count) in counts {
+                if asset_type == Image::type_name() {
+                    continue;
+                }
+
+                error!(
+                    "Cannot generate previews for {} assets of type {}",
+                    count, asset_type
+                );
+                batch.failures += count;
+            }
+        }
+        Err(e) => {
+            error!("Failed to count assets without previews: {}", e);
+            batch.failures += 1;
+        }
+    }
+
+    let assets = asset_db
+        .list_assets_missing_preview::<Image>()
+        .unwrap_or_else(|e| {
+            error!("Failed to list assets without previews: {}", e);
+            batch.failures += 1;
+            Vec::new()
+        });
+
+    // Previews are queued from the end of the list, so reverse it to keep the
+    // database order.
+    batch.remaining = assets.into_iter().rev().map(|asset| asset.id).collect();
+    batch.total = batch.remaining.len();
+
+    println!(
+        "Generating {} missing previews, {} at a time",
+        batch.total, batch.jobs
+    );
+}
+
+/// Queues preview tasks until the running limit is reached, so that only the
+/// assets being processed are held in memory.
+fn queue_previews(mut batch: ResMut<PreviewBatch>, mut asset_db: AwgenAssets<ProjectDatabase>) {
+    while asset_db.pending_previews() < batch.jobs {
+        let Some(id) = batch.remaining.pop() else {
+            return;
+        };
+
+        if let Err(e) = asset_db.regenerate_preview::<Image>(id) {
+            error!("Failed to read asset {}: {}", id, e);
+        }
+    }
+}
+
+/// Prints a progress bar whenever a preview finishes, and exits once all
+/// previews have been saved.
+///
+/// Image previews that failed to generate are left empty, so they are found by
+/// listing the images without previews again. The app exits with a failure
+/// code if any preview is still missing.
+fn report_progress(
+    mut batch: ResMut<PreviewBatch>,
+    asset_db: AwgenAssets<ProjectDatabase>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    let pending = asset_db.pending_previews();
+    let finished = batch.total - batch.remaining.len() - pending;
+
+    if batch.last_printed != Some(finished) {
+        batch.last_printed = Some(finished);
+        print!("\r{}", progress_bar(finished, batch.total));
+        let _ = std::io::stdout().flush();
+    }
+
+    if !batch.remaining.is_empty() || pending > 0 {
+        return;
+    }
+    println!();
+
+    let failed_images = match asset_db.list_assets_missing_preview::<Image>() {
+        Ok(assets) => assets.len(),
+        Err(e) => {
+            error!("Failed to list assets without previews: {}", e);
+            1
+        }
+    };
+
+    let failures = batch.failures + failed_images;
+    if failures == 0 {
+        app_exit.write(AppExit::Success);
+    } else {
+        eprintln!("Failed to generate {} previews", failures);
+        app_exit.write(AppExit::from_code(1));
+    }
+}
+
+/// Formats a progress bar, such as `[#######        ] 12/40`.
+fn progress_bar(finished: usize, total: usize) -> String {
+    let filled = (finished * PROGRESS_BAR_WIDTH)
+        .checked_div(total)
+        .unwrap_or(PROGRESS_BAR_WIDTH);
+    format!(
+        "[{}{}] {}/{}",
+        "#".repeat(filled),
+        " ".repeat(PROGRESS_BAR_WIDTH - filled),
+        finished,
+        total
+    )
+}
@@ -0,0 +1,93 @@
+//! The recently used and favorite asset folders of the sidebar tree.
+//!
+//! Both folders are the first rows of the sidebar tree, and list their assets
+//! by name, and the ID of an asset is copied by pressing Ctrl+C while its row
+//! is hovered. They are listed again whenever an asset record changes, as a
+//! renamed or removed asset may be listed in either of them, and whenever the
+//! explorer records a use of an asset or pins or unpins a favorite.
+
+use awgen_asset_db::prelude::*;
+use bevy::prelude::*;
+
+use crate::{ProjectDatabase, RECENT_LIMIT, asset_name};
+
+/// Plugin that keeps the recently used and favorite asset folders up to date.
+pub struct RecentFoldersPlugin;
+impl Plugin for RecentFoldersPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<RecentFolders>()
+            .add_message::<AssetUsageChanged>()
+            .add_systems(Update, update_recent_folders);
+    }
+}
+
+/// A message sent when the explorer records a use of an asset, or pins or
+/// unpins it as a favorite.
+#[derive(Debug, Message)]
+pub struct AssetUsageChanged;
+
+/// A resource holding the IDs and names of the assets in the recently used
+/// and favorite asset folders.
+#[derive(Debug, Default, PartialEq, Resource)]
+pub struct RecentFolders {
+    /// The recently used assets, most recently used first.
+    recent: Vec<(AssetRecordID, String)>,
+
+    /// The favorite assets.
+    favorites: Vec<(AssetRecordID, String)>,
+}
+
+impl RecentFolders {
+    /// Lists the recently used assets and the favorite assets, logging any
+    /// error.
+    pub fn load(asset_db: &AwgenAssets<ProjectDatabase>) -> Self {
+        let recent = asset_db.list_recent(RECENT_LIMIT).unwrap_or_else(|e| {
+            error!("Failed to list recent assets: {}", e);
+            Vec::new()
+        });
+
+        let favorites = asset_db.list_favorites().unwrap_or_else(|e| {
+            error!("Failed to list favorite assets: {}", e);
+            Vec::new()
+        });
+
+        Self {
+            recent: recent
+                .iter()
+                .map(|asset| (asset.id, asset_name(asset)))
+                .collect(),
+            favorites: favorites
+                .iter()
+                .map(|asset| (asset.id, asset_name(asset)))
+                .collect(),
+        }
+    }
+
+    /// Gets the IDs and names of the recently used assets, most recently used
+    /// first.
+    pub fn recent(&self) -> &[(AssetRecordID, String)] {
+        &self.recent
+    }
+
+    /// Gets the IDs and names of the favorite assets.
+    pub fn favorites(&self) -> &[(AssetRecordID, String)] {
+        &self.favorites
+    }
+}
+
+/// Lists the recently used and favorite assets again when an asset record
+/// changes or the explorer changes the usage of an asset.
+fn update_recent_folders(
+    mut record_changes: MessageReader<AssetRecordChanged<ProjectDatabase>>,
+    mut usage_changes: MessageReader<AssetUsageChanged>,
+    asset_db: AwgenAssets<ProjectDatabase>,
+    mut folders: ResMut<RecentFolders>,
+) {
+    let changed = record_changes.read().count() + usage_changes.read().count() > 0;
+    if !changed {
+        return;
+    }
+
+    // The sidebar is only listed again if the folders actually changed.
+    folders.set_if_neq(RecentFolders::load(&asset_db));
+}
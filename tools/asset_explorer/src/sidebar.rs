@@ -0,0 +1,284 @@
+//! The sidebar tree of the asset explorer.
+//!
+//! The sidebar lists the recently used and favorite asset folders, followed by
+//! the asset modules and the asset types. Its rows are supplied to the tree
+//! view by a provider, so only the rows in view have entities, however many
+//! assets the folders list.
+//!
+//! The rows are listed again whenever the asset counts or the recently used and
+//! favorite assets change, and whenever a folder is collapsed or expanded by
+//! clicking its arrow. The selection of the tree is cleared when the number of
+//! rows changes, as the selected indices would no longer refer to the same
+//! rows.
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use awgen_ui::prelude::*;
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+
+use crate::folders::AssetFolders;
+use crate::recent::RecentFolders;
+
+/// Plugin that keeps the rows of the sidebar tree up to date.
+pub struct SidebarPlugin;
+impl Plugin for SidebarPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.add_observer(on_folder_arrow_clicked).add_systems(
+            Update,
+            update_sidebar_rows
+                .run_if(resource_changed::<AssetFolders>.or(resource_changed::<RecentFolders>)),
+        );
+    }
+}
+
+/// A component on the tree view of the sidebar.
+#[derive(Debug, Component)]
+pub struct AssetFolderTree;
+
+/// A folder of the sidebar tree that can be collapsed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum SidebarFolder {
+    /// The folder of the recently used assets.
+    Recent,
+
+    /// The folder of the favorite assets.
+    Favorites,
+
+    /// The folder listing all asset modules.
+    Modules,
+
+    /// The folder of an asset type.
+    Type(String),
+}
+
+/// A resource holding the rows of the sidebar tree, in the order they are
+/// shown.
+#[derive(Debug, Resource)]
+pub struct SidebarRows {
+    /// The rows of the tree, shared with its provider.
+    rows: Arc<RwLock<Vec<TreeRow>>>,
+
+    /// The folder shown by each row, or `None` for rows that are not folders.
+    folders: Vec<Option<SidebarFolder>>,
+
+    /// The folders that are collapsed.
+    collapsed: HashSet<SidebarFolder>,
+
+    /// The icon shown before each row.
+    icon: Handle<Image>,
+}
+
+impl SidebarRows {
+    /// Lists the rows of the sidebar tree, showing the given icon before each
+    /// row.
+    pub fn new(icon: Handle<Image>, counts: &AssetFolders, recent: &RecentFolders) -> Self {
+        let mut rows = Self {
+            rows: Arc::default(),
+            folders: Vec::new(),
+            collapsed: HashSet::new(),
+            icon,
+        };
+        rows.list(counts, recent);
+        rows
+    }
+
+    /// Gets the number of rows.
+    pub fn row_count(&self) -> usize {
+        self.folders.len()
+    }
+
+    /// Creates a provider that supplies the rows to the tree view.
+    pub fn provider(&self) -> ItemProvider<TreeRow> {
+        let rows = self.rows.clone();
+        ItemProvider::new(move |index| {
+            rows.read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(index)
+                .cloned()
+                .unwrap_or_default()
+        })
+    }
+
+    /// Lists the rows again from the given asset counts and recently used and
+    /// favorite assets.
+    fn list(&mut self, counts: &AssetFolders, recent: &RecentFolders) {
+        let mut rows = Vec::new();
+        self.folders.clear();
+
+        let lists = [
+            (SidebarFolder::Recent, "Recent", recent.recent()),
+            (SidebarFolder::Favorites, "Favorites", recent.favorites()),
+        ];
+        for (folder, name, assets) in lists {
+            let collapsed =
+                self.push_folder(&mut rows, folder, name, assets.len(), !assets.is_empty());
+            if !collapsed {
+                for (id, name) in assets {
+                    self.push_row(&mut rows, name, None, id.to_string());
+                }
+            }
+        }
+
+        let modules = counts.modules().collect::<Vec<_>>();
+        let collapsed = self.push_folder(
+            &mut rows,
+            SidebarFolder::Modules,
+            "Modules",
+            counts.total(),
+            !modules.is_empty(),
+        );
+        if !collapsed {
+            for (name, count) in modules {
+                self.push_row(&mut rows, name, Some(count), name.to_string());
+            }
+        }
+
+        for (asset_type, count) in counts.types() {
+            let folder = SidebarFolder::Type(asset_type.to_string());
+            self.push_folder(&mut rows, folder, asset_type, count, false);
+        }
+
+        *self.rows.write().unwrap_or_else(PoisonError::into_inner) = rows;
+    }
+
+    /// Adds the row of a top level folder with the given name and count
+    /// badge, and returns whether the folder is collapsed.
+    fn push_folder(
+        &mut self,
+        rows: &mut Vec<TreeRow>,
+        folder: SidebarFolder,
+        name: &str,
+        count: usize,
+        has_children: bool,
+    ) -> bool {
+        let collapsed = self.collapsed.contains(&folder);
+        rows.push(TreeRow {
+            content: TreeNodeContent {
+                icon: Some(self.icon.clone()),
+                badge: Some(count.to_string()),
+                collapsed,
+                ..TreeNodeContent::from(name)
+            },
+            depth: 1,
+            has_children,
+        });
+        self.folders.push(Some(folder));
+        collapsed
+    }
+
+    /// Adds a row inside of a top level folder, with an optional count badge.
+    /// The given text is copied when Ctrl+C is pressed while the row is
+    /// hovered.
+    fn push_row(
+        &mut self,
+        rows: &mut Vec<TreeRow>,
+        name: &str,
+        count: Option<usize>,
+        copy_text: String,
+    ) {
+        rows.push(TreeRow {
+            content: TreeNodeContent {
+                icon: Some(self.icon.clone()),
+                badge: count.map(|count| count.to_string()),
+                copy_text: Some(copy_text),
+                ..TreeNodeContent::from(name)
+            },
+            depth: 2,
+            has_children: false,
+        });
+        self.folders.push(None);
+    }
+
+    /// Collapses or expands the folder shown by the given row, and returns
+    /// whether it changed. Expanded folders without children are kept as they
+    /// are.
+    fn toggle(&mut self, index: usize) -> bool {
+        let Some(Some(folder)) = self.folders.get(index) else {
+            return false;
+        };
+
+        if self.collapsed.remove(folder) {
+            return true;
+        }
+
+        let has_children = self
+            .rows
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(index)
+            .is_some_and(|row| row.has_children);
+        if has_children {
+            self.collapsed.insert(folder.clone());
+        }
+        has_children
+    }
+}
+
+/// Lists the rows of the sidebar again, and shows them in the sidebar tree.
+fn refresh_sidebar(
+    sidebar: &mut SidebarRows,
+    counts: &AssetFolders,
+    recent: &RecentFolders,
+    virtual_scroll: &mut VirtualScroll,
+    selection: &mut TreeRowSelection,
+) {
+    let previous = sidebar.row_count();
+    sidebar.list(counts, recent);
+
+    if sidebar.row_count() != previous {
+        selection.clear();
+    }
+
+    virtual_scroll.set_item_count(sidebar.row_count());
+    virtual_scroll.refresh();
+}
+
+/// Lists the rows of the sidebar again when the asset counts or the recently
+/// used and favorite assets change.
+fn update_sidebar_rows(
+    counts: Res<AssetFolders>,
+    recent: Res<RecentFolders>,
+    mut sidebar: ResMut<SidebarRows>,
+    mut trees: Query<(&mut VirtualScroll, &mut TreeRowSelection), With<AssetFolderTree>>,
+) {
+    let Ok((mut virtual_scroll, mut selection)) = trees.single_mut() else {
+        return;
+    };
+
+    refresh_sidebar(
+        &mut sidebar,
+        &counts,
+        &recent,
+        &mut virtual_scroll,
+        &mut selection,
+    );
+}
+
+/// Collapses or expands a folder of the sidebar when its arrow is clicked.
+fn on_folder_arrow_clicked(
+    trigger: On<TreeRowClicked>,
+    counts: Res<AssetFolders>,
+    recent: Res<RecentFolders>,
+    mut sidebar: ResMut<SidebarRows>,
+    mut trees: Query<(&mut VirtualScroll, &mut TreeRowSelection), With<AssetFolderTree>>,
+) {
+    let event = trigger.event();
+    if !event.arrow {
+        return;
+    }
+
+    let Ok((mut virtual_scroll, mut selection)) = trees.get_mut(event.entity) else {
+        return;
+    };
+
+    if sidebar.toggle(event.index) {
+        refresh_sidebar(
+            &mut sidebar,
+            &counts,
+            &recent,
+            &mut virtual_scroll,
+            &mut selection,
+        );
+    }
+}
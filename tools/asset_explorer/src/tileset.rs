@@ -0,0 +1,707 @@
+//! The tileset composer panel of the asset explorer.
+//!
+//! Image assets are dragged from the asset grid onto the tile list of the
+//! panel, where they can be reordered or removed. The tiles are combined into
+//! a [`Tileset`], a texture array with a mipmap chain for each tile, and any
+//! tile of the array can be previewed at any mip level.
+//!
+//! Tilesets are stored as `.tiles` files in the assets folder of the project,
+//! the same format that the engine builds and loads. The asset path typed in
+//! the panel is used to load an existing tileset or to save the tileset under
+//! a new name, and later saves write to the same file.
+
+use std::path::{Component, Path, PathBuf};
+
+use awgen_asset_db::prelude::*;
+use awgen_asset_db::tileset::{Tileset, extract_tile, is_rgba8};
+use awgen_ui::prelude::*;
+use awgen_ui::themes::hearth_theme;
+use bevy::asset::RenderAssetUsages;
+use bevy::image::ImageSampler;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::{AssetCells, ProjectDatabase};
+
+/// The asset path suggested for new tilesets.
+const DEFAULT_TILESET_PATH: &str = "tileset.tiles";
+
+/// The size of the tile thumbnails in the tile list.
+const THUMBNAIL_SIZE: f32 = 32.0;
+
+/// The size of the tileset preview image.
+const PREVIEW_SIZE: f32 = 128.0;
+
+/// Plugin that sets up the tileset composer panel.
+pub struct TilesetComposerPlugin {
+    /// The assets folder of the project, which tilesets are stored in.
+    pub assets_folder: PathBuf,
+}
+
+impl Plugin for TilesetComposerPlugin {
+    fn build(&self, app_: &mut App) {
+        app_.insert_resource(TilesetComposer::new(self.assets_folder.clone()))
+            .add_systems(
+                Update,
+                (compose_tileset, refresh_tile_list, refresh_preview).chain(),
+            )
+            .add_observer(on_tile_drop)
+            .add_observer(on_composer_activate);
+    }
+}
+
+/// A tile in the tileset composer.
+#[derive(Debug, Clone)]
+struct ComposerTile {
+    /// The name of the tile, such as the name of its image asset.
+    name: String,
+
+    /// The image of the tile.
+    image: Image,
+
+    /// The preview image of the tile, shown in the tile list.
+    thumbnail: Handle<Image>,
+}
+
+/// The state of the tileset composer.
+#[derive(Debug, Resource)]
+pub struct TilesetComposer {
+    /// The assets folder of the project, which tilesets are stored in.
+    assets_folder: PathBuf,
+
+    /// The tiles of the tileset, in layer order.
+    tiles: Vec<ComposerTile>,
+
+    /// The asset path of the `.tiles` file that the tileset was loaded from or
+    /// last saved to, if any.
+    path: Option<String>,
+
+    /// The composed tileset image, if the tiles could be combined.
+    composed: Option<Image>,
+
+    /// The composed tileset in the `.tiles` file format, if the tiles could be
+    /// combined.
+    binary: Option<Vec<u8>>,
+
+    /// The image showing the previewed tile at the previewed mip level.
+    preview: Option<Handle<Image>>,
+
+    /// The tile shown in the preview.
+    layer: u32,
+
+    /// The mip level shown in the preview.
+    mip: u32,
+
+    /// A message describing the last error or save, if any.
+    status: Option<String>,
+
+    /// Whether the tile list changed since the tileset was last composed.
+    tiles_changed: bool,
+
+    /// Whether the tile list needs to be rebuilt.
+    list_changed: bool,
+
+    /// Whether the preview needs to be redrawn.
+    preview_changed: bool,
+}
+
+impl TilesetComposer {
+    /// Creates an empty tileset composer that stores tilesets in the given
+    /// assets folder.
+    fn new(assets_folder: PathBuf) -> Self {
+        Self {
+            assets_folder,
+            tiles: Vec::new(),
+            path: None,
+            composed: None,
+            binary: None,
+            preview: None,
+            layer: 0,
+            mip: 0,
+            status: None,
+            tiles_changed: false,
+            list_changed: false,
+            preview_changed: false,
+        }
+    }
+
+    /// Resolves the asset path of a tileset, such as `tiles/terrain.tiles`,
+    /// to its file in the assets folder.
+    fn tileset_file(&self, path: &str) -> Result<PathBuf, String> {
+        if !path.ends_with(".tiles") {
+            return Err(format!(
+                "Tilesets must have a '.tiles' extension. Found: {}",
+                path
+            ));
+        }
+
+        let relative = Path::new(path);
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(format!("Invalid tileset path: {}", path));
+        }
+
+        Ok(self.assets_folder.join(relative))
+    }
+}
+
+/// A marker component for the list of tiles, which image assets are dropped
+/// onto.
+#[derive(Debug, Component)]
+struct ComposerTileList;
+
+/// A marker component for the image showing the tileset preview.
+#[derive(Debug, Component)]
+struct ComposerPreview;
+
+/// A marker component for the text describing the tileset preview.
+#[derive(Debug, Component)]
+struct ComposerStatus;
+
+/// A marker component for the text input holding the asset path that
+/// tilesets are loaded from and saved as.
+#[derive(Debug, Component)]
+struct ComposerPathInput;
+
+/// An action performed when a button in the tileset composer is clicked.
+#[derive(Debug, Clone, Copy, Component)]
+enum ComposerAction {
+    /// Moves the tile at the given index one layer up.
+    MoveUp(usize),
+
+    /// Moves the tile at the given index one layer down.
+    MoveDown(usize),
+
+    /// Removes the tile at the given index.
+    Remove(usize),
+
+    /// Previews the previous tile.
+    PreviousTile,
+
+    /// Previews the next tile.
+    NextTile,
+
+    /// Previews the next larger mip level.
+    PreviousMip,
+
+    /// Previews the next smaller mip level.
+    NextMip,
+
+    /// Removes all tiles.
+    Clear,
+
+    /// Replaces the tiles with the tiles of the tileset at the typed asset
+    /// path.
+    Load,
+
+    /// Saves the tileset to the file it was loaded from or last saved to, or
+    /// to the typed asset path if there is none.
+    Save,
+
+    /// Saves the tileset to the typed asset path.
+    SaveAs,
+}
+
+/// Creates the tileset composer panel, laid out with the given node. The flex
+/// direction and row gap of the node are overridden.
+pub fn composer_panel(node: Node, theme: &UiTheme) -> impl Bundle {
+    let small_button = |label: &str, action: ComposerAction| {
+        (
+            action,
+            button(ButtonBuilder {
+                node: Node::default(),
+                content: ButtonContent::text(label),
+                theme: theme.clone(),
+            }),
+        )
+    };
+
+    (
+        Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: px(8.0),
+            ..node
+        },
+        theme.inner_window.clone(),
+        children![
+            (
+                Text::new("Tileset Composer"),
+                theme.inner_window.text.clone()
+            ),
+            (
+                Text::new("Drag image assets here to add tiles."),
+                theme.inner_window.text.clone(),
+            ),
+            (
+                ComposerTileList,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(4.0),
+                    min_height: px(THUMBNAIL_SIZE * 2.0),
+                    max_height: percent(50.0),
+                    overflow: Overflow::scroll_y(),
+                    scrollbar_width: 4.0,
+                    ..default()
+                },
+            ),
+            (
+                ComposerPreview,
+                Node {
+                    width: px(PREVIEW_SIZE),
+                    height: px(PREVIEW_SIZE),
+                    align_self: AlignSelf::Center,
+                    ..default()
+                },
+                ImageNode::default(),
+            ),
+            (
+                ComposerStatus,
+                Text::default(),
+                theme.inner_window.text.clone()
+            ),
+            (
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    flex_wrap: FlexWrap::Wrap,
+                    column_gap: px(4.0),
+                    row_gap: px(4.0),
+                    ..default()
+                },
+                children![
+                    small_button("< Tile", ComposerAction::PreviousTile),
+                    small_button("Tile >", ComposerAction::NextTile),
+                    small_button("Mip -", ComposerAction::PreviousMip),
+                    small_button("Mip +", ComposerAction::NextMip),
+                ],
+            ),
+            (
+                ComposerPathInput,
+                TextInput::new(theme.clone())
+                    .with_value(DEFAULT_TILESET_PATH)
+                    .with_placeholder("Tileset asset path"),
+            ),
+            (
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    flex_wrap: FlexWrap::Wrap,
+                    column_gap: px(4.0),
+                    row_gap: px(4.0),
+                    ..default()
+                },
+                children![
+                    small_button("Clear", ComposerAction::Clear),
+                    small_button("Load", ComposerAction::Load),
+                    small_button("Save", ComposerAction::Save),
+                    small_button("Save As", ComposerAction::SaveAs),
+                ],
+            ),
+        ],
+    )
+}
+
+/// Adds the image asset of a grid cell to the tileset when it is dropped onto
+/// the tile list.
+///
+/// The tile is named by the label of the cell. Images that cannot be read, or
+/// that do not use an RGBA8 texture format, are rejected.
+#[allow(clippy::too_many_arguments)]
+fn on_tile_drop(
+    mut drop: On<Pointer<DragDrop>>,
+    lists: Query<(), With<ComposerTileList>>,
+    parents: Query<&ChildOf>,
+    cells: Query<(&GridCell, &Children)>,
+    asset_cells: Res<AssetCells>,
+    labels: Query<&Text>,
+    asset_db: AwgenAssets<ProjectDatabase>,
+    mut composer: ResMut<TilesetComposer>,
+) {
+    if !lists.contains(drop.entity) {
+        return;
+    }
+    drop.propagate(false);
+
+    let dropped = drop.dropped;
+    let Some((cell, children)) = std::iter::once(dropped)
+        .chain(parents.iter_ancestors(dropped))
+        .find_map(|entity| cells.get(entity).ok())
+    else {
+        return;
+    };
+
+    let Some(id) = asset_cells.get(cell) else {
+        return;
+    };
+
+    let name = labels
+        .iter_many(children)
+        .next()
+        .map_or_else(|| id.short().to_string(), |label| label.0.clone());
+
+    let image = match asset_db.read_asset::<Image>(id) {
+        Ok(image) => image,
+        Err(err) => {
+            composer.status = Some(format!("Failed to read tile {}: {}", name, err));
+            composer.preview_changed = true;
+            return;
+        }
+    };
+
+    if !is_rgba8(&image) {
+        composer.status = Some(format!(
+            "Tile {} has the texture format {:?}, but tiles must be RGBA8",
+            name, image.texture_descriptor.format
+        ));
+        composer.preview_changed = true;
+        return;
+    }
+
+    composer.tiles.push(ComposerTile {
+        name,
+        image,
+        thumbnail: asset_db.load_asset_preview(id),
+    });
+    composer.tiles_changed = true;
+}
+
+/// Performs the action of a tileset composer button when it is clicked.
+fn on_composer_activate(
+    trigger: On<Activate>,
+    actions: Query<&ComposerAction>,
+    path_inputs: Query<&TextInput, With<ComposerPathInput>>,
+    mut images: ResMut<Assets<Image>>,
+    mut composer: ResMut<TilesetComposer>,
+) {
+    let Ok(action) = actions.get(trigger.event().entity) else {
+        return;
+    };
+
+    let typed_path = path_inputs
+        .iter()
+        .next()
+        .map_or_else(String::new, |input| input.value().trim().to_string());
+
+    let tile_count = composer.tiles.len();
+    let mip_count = composer
+        .composed
+        .as_ref()
+        .map_or(1, |image| image.texture_descriptor.mip_level_count);
+
+    match *action {
+        ComposerAction::MoveUp(index) if index > 0 && index < tile_count => {
+            composer.tiles.swap(index - 1, index);
+            composer.tiles_changed = true;
+        }
+        ComposerAction::MoveDown(index) if index + 1 < tile_count => {
+            composer.tiles.swap(index, index + 1);
+            composer.tiles_changed = true;
+        }
+        ComposerAction::Remove(index) if index < tile_count => {
+            composer.tiles.remove(index);
+            composer.tiles_changed = true;
+        }
+        ComposerAction::PreviousTile => {
+            composer.layer = composer.layer.saturating_sub(1);
+            composer.preview_changed = true;
+        }
+        ComposerAction::NextTile => {
+            composer.layer = (composer.layer + 1).min(tile_count.max(1) as u32 - 1);
+            composer.preview_changed = true;
+        }
+        ComposerAction::PreviousMip => {
+            composer.mip = composer.mip.saturating_sub(1);
+            composer.preview_changed = true;
+        }
+        ComposerAction::NextMip => {
+            composer.mip = (composer.mip + 1).min(mip_count - 1);
+            composer.preview_changed = true;
+        }
+        ComposerAction::Clear => {
+            composer.tiles.clear();
+            composer.tiles_changed = true;
+        }
+        ComposerAction::Load => {
+            match load_tileset(&composer, &typed_path, &mut images) {
+                Ok(tiles) => {
+                    composer.tiles = tiles;
+                    composer.path = Some(typed_path.clone());
+                    composer.tiles_changed = true;
+                }
+                Err(err) => {
+                    composer.status = Some(format!("Failed to load tileset: {}", err));
+                }
+            }
+            composer.preview_changed = true;
+        }
+        ComposerAction::Save | ComposerAction::SaveAs => {
+            let path = match (*action, composer.path.clone()) {
+                (ComposerAction::Save, Some(path)) => path,
+                _ => typed_path,
+            };
+
+            composer.status = Some(match save_tileset(&composer, &path) {
+                Ok(()) => {
+                    composer.path = Some(path.clone());
+                    format!("Saved tileset {}", path)
+                }
+                Err(err) => format!("Failed to save tileset: {}", err),
+            });
+            composer.preview_changed = true;
+        }
+        _ => {}
+    }
+}
+
+/// Writes the composed tileset to the `.tiles` file at the given asset path.
+fn save_tileset(composer: &TilesetComposer, path: &str) -> Result<(), String> {
+    let Some(binary) = &composer.binary else {
+        if composer.tiles.is_empty() {
+            return Err(String::from("The tileset has no tiles"));
+        }
+        return Err(String::from("The tiles could not be combined"));
+    };
+
+    let file = composer.tileset_file(path)?;
+    if let Some(folder) = file.parent() {
+        std::fs::create_dir_all(folder).map_err(|err| err.to_string())?;
+    }
+
+    std::fs::write(&file, binary).map_err(|err| err.to_string())
+}
+
+/// Reads the `.tiles` file at the given asset path, and splits it into its
+/// tiles, named by their layer.
+fn load_tileset(
+    composer: &TilesetComposer,
+    path: &str,
+    images: &mut Assets<Image>,
+) -> Result<Vec<ComposerTile>, String> {
+    let file = composer.tileset_file(path)?;
+    let binary = std::fs::read(&file).map_err(|err| err.to_string())?;
+    let tileset = Tileset::from_binary(binary).map_err(|err| err.to_string())?;
+
+    let tile_count = tileset.tile_count();
+    let image = tileset.into_image();
+
+    (0 .. tile_count)
+        .map(|layer| {
+            let tile = extract_tile(&image, layer)
+                .ok_or_else(|| format!("Tile {} is missing from the tileset", layer + 1))?;
+            Ok(ComposerTile {
+                name: format!("Layer {}", layer + 1),
+                thumbnail: images.add(tile.clone()),
+                image: tile,
+            })
+        })
+        .collect()
+}
+
+/// Combines the tiles into a tileset when the tile list changes.
+fn compose_tileset(mut composer: ResMut<TilesetComposer>) {
+    if !composer.tiles_changed {
+        return;
+    }
+    composer.tiles_changed = false;
+    composer.list_changed = true;
+    composer.preview_changed = true;
+
+    if composer.tiles.is_empty() {
+        composer.composed = None;
+        composer.binary = None;
+        composer.status = None;
+        return;
+    }
+
+    let mut tileset = Tileset::new();
+    let result = composer.tiles.iter().try_for_each(|tile| {
+        tileset
+            .append_tile(tile.image.clone())
+            .map_err(|err| format!("Tile {}: {}", tile.name, err))
+    });
+
+    match result {
+        Ok(()) => {
+            let binary = tileset.as_binary();
+            let image = tileset.into_image();
+            let mips = image.texture_descriptor.mip_level_count;
+            composer.layer = composer.layer.min(composer.tiles.len() as u32 - 1);
+            composer.mip = composer.mip.min(mips - 1);
+            composer.composed = Some(image);
+            composer.binary = Some(binary);
+            composer.status = None;
+        }
+        Err(err) => {
+            composer.composed = None;
+            composer.binary = None;
+            composer.status = Some(err);
+        }
+    }
+}
+
+/// Rebuilds the tile list when the tiles change.
+fn refresh_tile_list(
+    asset_server: Res<AssetServer>,
+    mut composer: ResMut<TilesetComposer>,
+    lists: Query<Entity, With<ComposerTileList>>,
+    mut commands: Commands,
+) {
+    if !composer.list_changed {
+        return;
+    }
+    composer.list_changed = false;
+
+    let theme = hearth_theme(&asset_server);
+    for list in lists.iter() {
+        commands.entity(list).despawn_related::<Children>();
+
+        for (index, tile) in composer.tiles.iter().enumerate() {
+            let tile_button = |label: &str, action: ComposerAction| {
+                (
+                    action,
+                    button(ButtonBuilder {
+                        node: Node::default(),
+                        content: ButtonContent::text(label),
+                        theme: theme.clone(),
+                    }),
+                )
+            };
+
+            commands.spawn((
+                ChildOf(list),
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: px(4.0),
+                    ..default()
+                },
+                children![
+                    (
+                        Node {
+                            width: px(THUMBNAIL_SIZE),
+                            height: px(THUMBNAIL_SIZE),
+                            ..default()
+                        },
+                        ImageNode::new(tile.thumbnail.clone()),
+                    ),
+                    (
+                        Node {
+                            flex_grow: 1.0,
+                            ..default()
+                        },
+                        Text::new(format!("{}. {}", index + 1, tile.name)),
+                        theme.inner_window.text.clone(),
+                    ),
+                    tile_button("^", ComposerAction::MoveUp(index)),
+                    tile_button("v", ComposerAction::MoveDown(index)),
+                    tile_button("x", ComposerAction::Remove(index)),
+                ],
+            ));
+        }
+    }
+}
+
+/// Redraws the preview of the previewed tile and mip level.
+fn refresh_preview(
+    mut composer: ResMut<TilesetComposer>,
+    mut images: ResMut<Assets<Image>>,
+    mut previews: Query<&mut ImageNode, With<ComposerPreview>>,
+    mut texts: Query<&mut Text, With<ComposerStatus>>,
+) {
+    if !composer.preview_changed {
+        return;
+    }
+    composer.preview_changed = false;
+
+    let preview = composer
+        .composed
+        .as_ref()
+        .and_then(|image| extract_mip(image, composer.layer, composer.mip));
+
+    let handle = match (preview, composer.preview.clone()) {
+        (Some(image), Some(handle)) => {
+            let _ = images.insert(&handle, image);
+            handle
+        }
+        (Some(image), None) => {
+            let handle = images.add(image);
+            composer.preview = Some(handle.clone());
+            handle
+        }
+        (None, _) => Handle::default(),
+    };
+
+    for mut preview in previews.iter_mut() {
+        preview.image = handle.clone();
+    }
+
+    let description = match &composer.composed {
+        Some(image) => {
+            let size = (image.width() >> composer.mip).max(1);
+            format!(
+                "Tile {} of {}, mip {} of {} ({}x{})",
+                composer.layer + 1,
+                composer.tiles.len(),
+                composer.mip,
+                image.texture_descriptor.mip_level_count - 1,
+                size,
+                size
+            )
+        }
+        None => String::from("No tiles"),
+    };
+
+    let text = match &composer.status {
+        Some(status) => format!("{}\n{}", description, status),
+        None => description,
+    };
+
+    for mut status in texts.iter_mut() {
+        status.0 = text.clone();
+    }
+}
+
+/// Copies a single mip level of a single layer of a tileset image into a new
+/// image, for previewing it.
+fn extract_mip(tileset: &Image, layer: u32, mip: u32) -> Option<Image> {
+    let size = tileset.width();
+    let mips = tileset.texture_descriptor.mip_level_count;
+    let mip_bytes = |level: u32| ((size >> level).max(1) * (size >> level).max(1) * 4) as usize;
+
+    let layer_bytes = (0 .. mips).map(mip_bytes).sum::<usize>();
+    let start = layer_bytes * layer as usize + (0 .. mip).map(mip_bytes).sum::<usize>();
+    let end = start + mip_bytes(mip);
+    let data = tileset.data.as_ref()?.get(start .. end)?.to_vec();
+
+    let mip_size = (size >> mip).max(1);
+    let mut image = Image::new(
+        Extent3d {
+            width: mip_size,
+            height: mip_size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::nearest();
+    Some(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tileset_paths_stay_in_the_assets_folder() {
+        let composer = TilesetComposer::new(PathBuf::from("project/assets"));
+        assert_eq!(
+            composer.tileset_file("tiles/terrain.tiles"),
+            Ok(PathBuf::from("project/assets/tiles/terrain.tiles"))
+        );
+        assert!(composer.tileset_file("terrain.png").is_err());
+        assert!(composer.tileset_file("../terrain.tiles").is_err());
+        assert!(composer.tileset_file("/terrain.tiles").is_err());
+    }
+}
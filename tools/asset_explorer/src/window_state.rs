@@ -0,0 +1,250 @@
+//! This module persists the geometry of the asset explorer window, so that it
+//! reopens with the same size, position, and mode that it had when it was
+//! last closed.
+//!
+//! The window state is stored in the settings table of the project asset
+//! database, as a single line of whitespace separated values. It is stored
+//! once it has stayed the same for [`SAVE_DELAY`] seconds after changing, so
+//! that it is not lost if the explorer does not exit cleanly, and again when
+//! the explorer exits.
+
+use awgen_asset_db::prelude::*;
+use bevy::app::AppExit;
+use bevy::ecs::system::NonSendMarker;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy::winit::WINIT_WINDOWS;
+
+use crate::ProjectDatabase;
+
+/// The settings key for the window state.
+const WINDOW_STATE_KEY: &str = "asset_explorer.window_state";
+
+/// The time, in seconds, that the window state must stay unchanged before it
+/// is stored, so that dragging or resizing the window does not write to the
+/// database every frame.
+const SAVE_DELAY: f32 = 1.0;
+
+/// Plugin that restores the explorer window state on startup and stores it
+/// when it changes.
+pub struct ExplorerWindowStatePlugin;
+impl Plugin for ExplorerWindowStatePlugin {
+    fn build(&self, app_: &mut App) {
+        app_.init_resource::<WindowState>()
+            .add_systems(Startup, restore_window_state)
+            .add_systems(Last, (track_window_state, save_window_state).chain());
+    }
+}
+
+/// A resource that holds the last known state of the explorer window.
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+struct WindowState {
+    /// The logical width of the window, while it is not maximized.
+    width: f32,
+
+    /// The logical height of the window, while it is not maximized.
+    height: f32,
+
+    /// The physical position of the top-left corner of the window, while it
+    /// is not maximized, if it is known.
+    position: Option<IVec2>,
+
+    /// Whether the window is maximized.
+    maximized: bool,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        let resolution = Window::default().resolution;
+        Self {
+            width: resolution.width(),
+            height: resolution.height(),
+            position: None,
+            maximized: false,
+        }
+    }
+}
+
+impl WindowState {
+    /// Encodes the window state as `width height maximized [x y]`.
+    fn encode(&self) -> String {
+        let mut value = format!("{} {} {}", self.width, self.height, self.maximized);
+        if let Some(position) = self.position {
+            value.push_str(&format!(" {} {}", position.x, position.y));
+        }
+        value
+    }
+
+    /// Decodes a window state written by [`WindowState::encode`], returning
+    /// `None` if the value is malformed.
+    fn decode(value: &str) -> Option<Self> {
+        let mut parts = value.split_whitespace();
+        let width = parts.next()?.parse().ok()?;
+        let height = parts.next()?.parse().ok()?;
+        let maximized = parts.next()?.parse().ok()?;
+
+        let position = match (parts.next(), parts.next()) {
+            (Some(x), Some(y)) => Some(IVec2::new(x.parse().ok()?, y.parse().ok()?)),
+            (None, None) => None,
+            _ => return None,
+        };
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            width,
+            height,
+            position,
+            maximized,
+        })
+    }
+}
+
+/// Applies the stored window state to the primary window.
+fn restore_window_state(
+    asset_db: AwgenAssets<ProjectDatabase>,
+    mut state: ResMut<WindowState>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = windows.single_mut() else {
+        return;
+    };
+
+    let stored = match asset_db.setting(WINDOW_STATE_KEY) {
+        Ok(Some(value)) => match WindowState::decode(&value) {
+            Some(stored) => stored,
+            None => {
+                warn!("Ignoring malformed window state: {}", value);
+                return;
+            }
+        },
+        Ok(None) => return,
+        Err(err) => {
+            warn!("Failed to load the window state: {}", err);
+            return;
+        }
+    };
+
+    window.resolution.set(stored.width, stored.height);
+    if let Some(position) = stored.position {
+        window.position = WindowPosition::At(position);
+    }
+
+    if stored.maximized {
+        window.set_maximized(true);
+    }
+
+    *state = stored;
+}
+
+/// Records the state of the primary window whenever it changes.
+fn track_window_state(
+    mut state: ResMut<WindowState>,
+    windows: Query<(Entity, &Window), (With<PrimaryWindow>, Changed<Window>)>,
+    _main_thread: NonSendMarker,
+) {
+    let Ok((entity, window)) = windows.single() else {
+        return;
+    };
+
+    let (maximized, minimized) = WINIT_WINDOWS.with_borrow(|winit_windows| {
+        winit_windows
+            .get_window(entity)
+            .map(|winit_window| {
+                let minimized = winit_window.is_minimized().unwrap_or(false);
+                (winit_window.is_maximized(), minimized)
+            })
+            .unwrap_or_default()
+    });
+
+    if minimized {
+        return;
+    }
+
+    let mut current = WindowState {
+        maximized,
+        ..*state
+    };
+
+    // The windowed geometry is kept while maximized, so the window returns to
+    // it once restored.
+    if !maximized {
+        current.width = window.resolution.width();
+        current.height = window.resolution.height();
+        if let WindowPosition::At(position) = window.position {
+            current.position = Some(position);
+        }
+    }
+
+    // The window also changes when the cursor moves, which must not count as
+    // a change of its state.
+    state.set_if_neq(current);
+}
+
+/// Stores the window state in the project asset database once it has stayed
+/// the same for [`SAVE_DELAY`] seconds after changing, or when the app exits.
+fn save_window_state(
+    mut exit: MessageReader<AppExit>,
+    state: Res<WindowState>,
+    asset_db: AwgenAssets<ProjectDatabase>,
+    time: Res<Time<Real>>,
+    mut save_at: Local<Option<f32>>,
+) {
+    // The state was just initialized, so there is nothing new to store.
+    if state.is_changed() && !state.is_added() {
+        *save_at = Some(time.elapsed_secs() + SAVE_DELAY);
+    }
+
+    let exiting = !exit.is_empty();
+    exit.clear();
+
+    let Some(at) = *save_at else {
+        return;
+    };
+
+    if !exiting && time.elapsed_secs() < at {
+        return;
+    }
+    *save_at = None;
+
+    if asset_db.is_read_only() {
+        return;
+    }
+
+    if let Err(err) = asset_db.set_setting(WINDOW_STATE_KEY, &state.encode()) {
+        error!("Failed to store the window state: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_state_round_trip() {
+        let state = WindowState {
+            width: 1024.5,
+            height: 768.0,
+            position: Some(IVec2::new(-20, 40)),
+            maximized: true,
+        };
+        assert_eq!(WindowState::decode(&state.encode()), Some(state));
+
+        let state = WindowState {
+            position: None,
+            ..state
+        };
+        assert_eq!(WindowState::decode(&state.encode()), Some(state));
+    }
+
+    #[test]
+    fn malformed_window_state_is_rejected() {
+        assert_eq!(WindowState::decode(""), None);
+        assert_eq!(WindowState::decode("800 600"), None);
+        assert_eq!(WindowState::decode("800 600 false 10"), None);
+        assert_eq!(WindowState::decode("800 600 false 10 20 30"), None);
+        assert_eq!(WindowState::decode("wide 600 false"), None);
+    }
+}